@@ -0,0 +1,45 @@
+;; Hello - minimal AstroFS WASM plugin
+;;
+;; Logs every created file and answers the `hello` command. Build with
+;; `wasm-tools parse hello.wat -o hello.wasm`, or drop the .wat file into
+;; the plugin directory as-is.
+(module
+  (import "astrofs" "log" (func $log (param i32 i32)))
+
+  (memory (export "memory") 1)
+
+  ;; Constant strings, at the offsets used below
+  (data (i32.const 0) "hello plugin loaded")
+  (data (i32.const 32) "Hello from WASM!")
+  (data (i32.const 64) "[{\"name\":\"hello\",\"description\":\"Say hello from WASM\",\"category\":\"Examples\"}]")
+
+  ;; Bump allocator above the constants; astrofs_free pops the last block
+  (global $heap (mut i32) (i32.const 1024))
+
+  (func (export "astrofs_alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    ;; Grow memory when the block doesn't fit
+    (if (i32.gt_u (global.get $heap) (i32.mul (memory.size) (i32.const 65536)))
+      (then (drop (memory.grow (i32.add (i32.shr_u (local.get $len) (i32.const 16)) (i32.const 1))))))
+    (local.get $ptr))
+
+  (func (export "astrofs_free") (param $ptr i32) (param $len i32)
+    (if (i32.eq (i32.add (local.get $ptr) (local.get $len)) (global.get $heap))
+      (then (global.set $heap (local.get $ptr)))))
+
+  (func (export "on_load") (result i32)
+    (call $log (i32.const 0) (i32.const 19))
+    (i32.const 0))
+
+  (func (export "on_file_created") (param $ptr i32) (param $len i32) (result i32)
+    (call $log (local.get $ptr) (local.get $len))
+    (i32.const 0))
+
+  ;; Strings are returned as (ptr << 32) | len
+  (func (export "get_commands") (result i64)
+    (i64.or (i64.shl (i64.const 64) (i64.const 32)) (i64.const 76)))
+
+  (func (export "execute_command") (param $ptr i32) (param $len i32) (result i64)
+    (i64.or (i64.shl (i64.const 32) (i64.const 32)) (i64.const 16))))
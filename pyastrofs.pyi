@@ -5,7 +5,7 @@ Complete type hints for the AstroFS Rust library exposed to Python.
 Provides full IDE support and type checking for all AstroFS functionality.
 """
 
-from typing import Dict, List, Optional, Tuple
+from typing import Callable, Dict, Iterator, List, Optional, Tuple
 from pathlib import Path
 
 class PyFileEntry:
@@ -21,6 +21,17 @@ class PyFileEntry:
     
     size: int
     """Size in bytes"""
+    
+    modified: Optional[float]
+    """Modification time as seconds since the Unix epoch, if known"""
+    
+    item_count: Optional[int]
+    """Items directly inside a directory, at most 100; set while
+    `show_dir_counts` is on, None for files"""
+    
+    mime: Optional[str]
+    """Mime type sniffed from the contents of a file that has no extension,
+    e.g. `image/png`; None otherwise"""
 
 class PyBookmark:
     """Represents a saved bookmark to a directory"""
@@ -32,6 +43,9 @@ class PyBookmark:
     
     icon: str
     """Emoji or icon for the bookmark"""
+    
+    tags: List[str]
+    """Tags attached to the bookmark"""
 
 class PyWorkspace:
     """Represents a workspace with directory navigation state"""
@@ -63,6 +77,132 @@ class PyThemeManager:
     available_themes: List[str]
     """List of available theme names"""
 
+class PyContrastWarning:
+    """A theme color pair below the WCAG AA contrast ratio"""
+    pair: str
+    """Which pair failed (normal text, directories, selection, status bar)"""
+    
+    foreground: str
+    """Foreground color"""
+    
+    background: str
+    """Background color"""
+    
+    ratio: float
+    """Measured contrast ratio"""
+    
+    minimum: float
+    """Required minimum ratio (4.5)"""
+
+class PyTrashedItem:
+    """A file or directory in the trash"""
+    name: str
+    """Original file name"""
+    
+    original: str
+    """Where the item lived before it was deleted"""
+    
+    trashed: str
+    """Where the item lives inside the trash; identifies it for restore/delete"""
+    
+    deleted: Optional[str]
+    """Deletion time as an ISO 8601 local timestamp, if recorded"""
+
+class PyMount:
+    """A mounted filesystem or removable drive"""
+    name: str
+    """Device or volume name, such as /dev/sda1"""
+    
+    mount_point: str
+    """Directory the filesystem is mounted on"""
+    
+    file_system: str
+    """Filesystem type, such as ext4 or vfat"""
+    
+    removable: bool
+    """The drive is removable, e.g. a USB stick"""
+    
+    available: int
+    """Free bytes available to this user"""
+    
+    total: int
+    """Size of the filesystem in bytes"""
+
+class PyPdfInfo:
+    """What a PDF preview shows"""
+    version: str
+    """PDF version from the file header, such as "1.7""""
+    
+    pages: int
+    """Number of pages"""
+    
+    title: Optional[str]
+    """Title from the document info, if set"""
+    
+    author: Optional[str]
+    """Author from the document info, if set"""
+    
+    first_page_text: Optional[str]
+    """Text of the first page, a line per text line; None for scanned pages
+    and fonts whose text can't be recovered"""
+
+class PyCommandOutcome:
+    """What a command run with run_command did"""
+    kind: str
+    """One of "navigated", "switched_tab", "created", "removed", "opened",
+    "message" or "nothing" """
+    
+    paths: List[str]
+    """The directory navigated to, or the entries created or removed"""
+    
+    tab: Optional[int]
+    """Position of the tab switched to, for "switched_tab" outcomes"""
+    
+    view: Optional[str]
+    """The view or prompt that opened, such as "Settings" or "Input(CreateFile)" """
+    
+    message: Optional[str]
+    """Status message, for "message" outcomes"""
+
+class PyDiffLine:
+    """One row of a file diff"""
+    kind: str
+    """"equal", "added", "removed", "changed" or "gap" (skipped unchanged lines)"""
+    
+    old_line: Optional[int]
+    """1-based line number in the first file"""
+    
+    old_text: Optional[str]
+    """Line text in the first file"""
+    
+    new_line: Optional[int]
+    """1-based line number in the second file"""
+    
+    new_text: Optional[str]
+    """Line text in the second file"""
+
+class PyDiffResult:
+    """Line diff between two files"""
+    old_path: str
+    new_path: str
+    old_size: int
+    """Size of the first file in bytes"""
+    
+    new_size: int
+    """Size of the second file in bytes"""
+    
+    binary: bool
+    """Either file is binary; `lines` is empty"""
+    
+    identical: bool
+    """Both files have the same contents"""
+    
+    truncated: bool
+    """Rows were cut off at `preview_max_lines`"""
+    
+    lines: List[PyDiffLine]
+    """Changed hunks with 3 lines of context, separated by "gap" rows"""
+
 class PyPlugin:
     """Represents a loaded plugin"""
     id: str
@@ -71,11 +211,31 @@ class PyPlugin:
     name: str
     """Human-readable plugin name"""
     
+    version: str
+    """Plugin version"""
+    
     description: str
     """Plugin description"""
     
     enabled: bool
     """Whether plugin is enabled"""
+    
+    permissions: List[str]
+    """Granted permissions, such as read-files or modify-theme"""
+
+class PyPluginStats:
+    """Live statistics for a plugin"""
+    load_time_ms: int
+    """Milliseconds spent in the plugin's last on_load"""
+    
+    memory_usage_bytes: int
+    """Memory usage reported by the plugin"""
+    
+    function_calls: int
+    """Number of hooks called on the plugin"""
+    
+    errors: int
+    """Number of hooks that returned an error"""
 
 class PyPluginManager:
     """Manages plugins"""
@@ -115,6 +275,46 @@ class PyBookmarkManager:
     bookmarks: Dict[str, PyBookmark]
     """Mapping of bookmark names to bookmark objects"""
 
+class PyWatchEvent:
+    """A filesystem change reported by a directory watcher"""
+    kind: str
+    """Kind of change: created, deleted or modified"""
+    
+    path: str
+    """Path that changed"""
+
+class PyDirWatcher:
+    """Iterator over filesystem changes in a watched directory
+    
+    Iterating blocks until the next change arrives. The GIL is released while
+    waiting. Dropping or closing the watcher stops the underlying watch thread.
+    
+    Example:
+        >>> for event in fs.watch("/tmp"):
+        ...     print(event.kind, event.path)
+    """
+    path: str
+    """Watched directory"""
+    
+    def __iter__(self) -> Iterator[PyWatchEvent]: ...
+    
+    def __next__(self) -> PyWatchEvent: ...
+    
+    def poll(self, timeout: float) -> Optional[List[PyWatchEvent]]:
+        """Wait up to `timeout` seconds for changes
+        
+        Args:
+            timeout: Maximum time to wait in seconds
+            
+        Returns:
+            All buffered events, or None if nothing changed before the timeout
+        """
+        ...
+    
+    def close(self) -> None:
+        """Stop watching; further iteration ends immediately"""
+        ...
+
 class PyAstroFS:
     """Main AstroFS file manager class
     
@@ -146,6 +346,16 @@ class PyAstroFS:
         """
         ...
     
+    def on_directory_changed(self, callback: Callable[[str, str], None]) -> None:
+        """Register a callback run with (old, new) whenever the current directory changes
+        
+        Fires after the new directory is listed, for navigation from any source.
+        Refreshing the same directory doesn't count. Exceptions raised by the
+        callback are logged and otherwise ignored. The callback runs while this
+        object is busy, so it shouldn't call back into it.
+        """
+        ...
+    
     def current_dir(self) -> str:
         """Get current working directory
         
@@ -170,6 +380,21 @@ class PyAstroFS:
         """Move selection down in file list"""
         ...
     
+    def select_index(self, index: int) -> None:
+        """Select the entry at `index` in `list_files()`
+        
+        Indices past the end select the last entry.
+        """
+        ...
+    
+    def selected_index(self) -> int:
+        """Index of the selected entry in `list_files()`"""
+        ...
+    
+    def selected_entry(self) -> Optional[PyFileEntry]:
+        """The selected entry, or None when the directory is empty"""
+        ...
+    
     def enter_selected(self) -> None:
         """Enter selected directory or open file
         
@@ -178,6 +403,319 @@ class PyAstroFS:
         """
         ...
     
+    def resolve_opener(self, path: str) -> Optional[str]:
+        """Get the command configured to open a file
+        
+        Looks up the file's extension in the `file_associations` config.
+        
+        Args:
+            path: File path
+            
+        Returns:
+            The command with `%f` replaced by the quoted path, or None if
+            the file opens with the system default application
+        """
+        ...
+    
+    def recent_files(self, path: str, n: int) -> List[PyFileEntry]:
+        """Find the most recently modified files under a directory
+        
+        Walks the tree recursively (up to 12 levels), honoring the hidden
+        files toggle and the `respect_gitignore` config.
+        
+        Args:
+            path: Directory to search
+            n: Maximum number of files to return
+            
+        Returns:
+            Up to `n` files, newest first
+        """
+        ...
+    
+    def recent_files_opened(self) -> List[PyFileEntry]:
+        """Files opened from AstroFS (Enter or open with), most recent first
+        
+        The last 50 are kept across sessions; files that no longer exist
+        are left out.
+        """
+        ...
+    
+    def clear_recent_files(self) -> None:
+        """Forget the recently opened files"""
+        ...
+    
+    def hash_file(self, path: str, algo: str = "sha256") -> str:
+        """Compute a file's checksum as lowercase hex
+        
+        Results are cached by path and modification time, so asking
+        again for an unchanged file is instant.
+        
+        Args:
+            path: File to hash
+            algo: "md5", "sha256" or "blake3"
+            
+        Raises:
+            ValueError: If the algorithm is unknown or the file can't be read
+        """
+        ...
+    
+    def set_permissions(self, path: str, mode: str, recursive: bool = False) -> List[Tuple[str, str]]:
+        """Change a path's permission bits, like chmod
+        
+        Entries that can't be changed are skipped rather than stopping
+        the batch. Symlinks are never followed.
+        
+        Args:
+            path: File or directory to change
+            mode: Octal mode ("644", "0755") or a change such as "+x" or "go-w"
+            recursive: Also change everything below a directory
+            
+        Returns:
+            (path, reason) for each entry that could not be changed
+            
+        Raises:
+            ValueError: If the mode is invalid, or on platforms other than Unix
+        """
+        ...
+    
+    def copy_paths(self, paths: List[str], dest: str, strategy: str = "error") -> List[Optional[str]]:
+        """Copy files and directories into a directory, keeping their names
+        
+        Args:
+            paths: Files or directories to copy
+            dest: Directory to copy them into
+            strategy: What to do when a name is already taken there:
+                "error", "overwrite", "rename" (a.txt becomes a.2.txt) or "skip"
+        
+        Returns:
+            Where each path was copied to, or None if it was skipped
+        
+        Raises:
+            ValueError: On an unknown strategy, a collision with "error",
+                or a failed copy; earlier paths stay copied
+        """
+        ...
+    
+    def move_paths(self, paths: List[str], dest: str, strategy: str = "error") -> List[Optional[str]]:
+        """Move files and directories into a directory, keeping their names
+        
+        Takes the same strategies as `copy_paths`. Moves across filesystems
+        copy and then delete the original.
+        """
+        ...
+    
+    def sequence_rename(self, prefix: str, order: str = "date") -> List[str]:
+        """Rename the marked files (or the selection) to `prefix_0001.jpg`,
+        `prefix_0002.jpg`, ... and return their new paths
+        
+        `order` is "date" for when each photo was taken (EXIF, falling back
+        to the modified time) or "mtime". Extensions are kept and folders are
+        skipped. Raises ValueError if a new name is taken by another file.
+        """
+        ...
+    
+    def flatten_directory(self, path: str) -> Tuple[List[str], int, List[Tuple[str, str]]]:
+        """Move every file nested in a directory's subfolders up into it
+        
+        Useful after extracting nested archives. Symlinks are moved as links,
+        and subfolders left empty are removed. Names that are already taken
+        follow the `collision_strategy` setting, with "ask" renaming instead;
+        a file named like one of the subfolders is always renamed.
+        
+        Args:
+            path: Directory to flatten
+            
+        Returns:
+            The files' new paths, how many were skipped, and (path, reason)
+            for each file that could not be moved
+            
+        Raises:
+            ValueError: If `path` is not a directory or is protected (such as
+                the home directory), or a name is taken with the "error"
+                strategy; nothing is moved then
+        """
+        ...
+    
+    def copy_to_bookmark(self, name: str) -> str:
+        """Copy the marked entries (or the selection) into a bookmarked directory
+        
+        Name collisions follow the `collision_strategy` setting, with "ask"
+        renaming instead.
+        
+        Returns:
+            The bookmark's directory
+        
+        Raises:
+            ValueError: If the bookmark doesn't exist, nothing is selected,
+                or an entry could not be copied; the others stay copied
+        """
+        ...
+    
+    def move_to_bookmark(self, name: str) -> str:
+        """Move the marked entries (or the selection) into a bookmarked directory
+        
+        Works like `copy_to_bookmark`.
+        """
+        ...
+    
+    def format_path(self, path: str, max_width: int = 0) -> str:
+        """A path written the way the status bar and tabs show it
+        
+        Follows the `path_display` setting ("absolute", "home" for `~/...`
+        or "root" for paths relative to `path_display_root`).
+        
+        Args:
+            path: Path to format
+            max_width: Characters to fit in, cutting the middle with `…`;
+                0 for no limit
+        """
+        ...
+    
+    def relative_path_from_repo(self, path: str) -> str:
+        """A path relative to the root of its git repository, e.g. `src/app.rs`
+        
+        Outside a repository the path is relative to the current directory,
+        with `..` where needed. Separators are always `/`.
+        """
+        ...
+    
+    def detect_mime(self, path: str) -> Optional[str]:
+        """The mime type of a file judged by its contents, e.g. `image/png`
+        
+        Returns None for directories, unreadable files and formats that
+        can't be recognised (including most plain text).
+        """
+        ...
+    
+    def disk_usage(self, path: str) -> List[Tuple[FileEntry, int]]:
+        """The children of a directory with the bytes in everything below each,
+        largest first, like ncdu
+        
+        Subdirectories are walked in parallel; symlinks count only when
+        `follow_symlinks` is set.
+        """
+        ...
+    
+    def list_archive(self, path: str) -> List[FileEntry]:
+        """Everything inside a .zip, .tar or .tar.gz archive, sorted by path
+        
+        Paths continue from the archive's own (`photos.zip/2021/a.jpg`), and
+        folders that are only implied by the files in them are included.
+        Raises ValueError for other files or archives that can't be read.
+        """
+        ...
+    
+    def toggle_mark(self) -> None:
+        """Mark or unmark the selected entry and move to the next one"""
+        ...
+    
+    def select_all(self) -> None:
+        """Mark every entry in the current listing"""
+        ...
+    
+    def clear_selection(self) -> None:
+        """Unmark everything, including marks made in other directories"""
+        ...
+    
+    def invert_selection(self) -> None:
+        """Flip the mark on every entry in the current listing"""
+        ...
+    
+    def selected_paths(self) -> List[str]:
+        """Marked paths, sorted
+        
+        Marks are kept by path, so they survive re-sorting and changing
+        directories.
+        """
+        ...
+    
+    def diff_files(self, a: str, b: str) -> PyDiffResult:
+        """Compare two files line by line
+        
+        Rows are limited to the `preview_max_lines` config.
+        
+        Args:
+            a: First (old) file
+            b: Second (new) file
+            
+        Raises:
+            ValueError: If either path is a directory or can't be read
+        """
+        ...
+    
+    def pdf_info(self, path: str) -> PyPdfInfo:
+        """Page count, title, author and first-page text of a PDF
+        
+        Files over 32 MiB aren't read. Raises ValueError for files that
+        aren't PDFs or can't be read.
+        """
+        ...
+    
+    def list_mounts(self) -> List[PyMount]:
+        """List mounted filesystems, sorted by mount point
+        
+        The mount table is read again on every call, so drives plugged in
+        or removed since the last call show up.
+        """
+        ...
+    
+    def list_trash(self) -> List[PyTrashedItem]:
+        """List the trash, most recently deleted first
+        
+        Raises:
+            ValueError: If there is no trash directory or it can't be read
+        """
+        ...
+    
+    def restore_from_trash(self, trashed: str) -> str:
+        """Move an item out of the trash back to its original location
+        
+        The original parent directory is recreated if it no longer exists.
+        
+        Args:
+            trashed: The item's `trashed` path from `list_trash()`
+            
+        Returns:
+            The path the item was restored to
+            
+        Raises:
+            ValueError: If the item is not in the trash or something already
+                exists at the original path
+        """
+        ...
+    
+    def delete_from_trash(self, trashed: str) -> None:
+        """Permanently delete one item from the trash
+        
+        Args:
+            trashed: The item's `trashed` path from `list_trash()`
+        """
+        ...
+    
+    def empty_trash(self) -> int:
+        """Permanently delete everything in the trash
+        
+        Returns:
+            Number of items deleted
+        """
+        ...
+    
+    def trash_size(self) -> int:
+        """Bytes taken by everything in the trash"""
+        ...
+    
+    def prune_trash(self) -> Tuple[int, int]:
+        """Apply the trash limits from the config now
+        
+        Items trashed more than `trash_max_age_days` ago are deleted for good,
+        then the oldest until the trash fits in `trash_max_size_mb`. A limit
+        of 0 is ignored.
+        
+        Returns:
+            Number of items and bytes deleted
+        """
+        ...
+    
     def go_back(self) -> None:
         """Go back to parent directory
         
@@ -217,17 +755,87 @@ class PyAstroFS:
         """
         ...
     
-    def rename_selected(self, new_name: str) -> None:
+    def rename_selected(self, new_name: str, overwrite: bool = False) -> None:
         """Rename selected file or directory
         
         Args:
             new_name: New name for file/directory
+            overwrite: Replace an existing entry with that name
             
         Raises:
+            FileExistsError: If the name is taken and overwrite is False
             ValueError: If rename fails
         """
         ...
     
+    def duplicate_selected(self) -> Optional[str]:
+        """Copy the selected entry into the same directory without asking
+        
+        The copy is named "name copy.ext", then "name copy 2.ext" and so on;
+        directories are copied recursively.
+        
+        Returns:
+            Path of the copy, or None if nothing is selected
+            
+        Raises:
+            ValueError: If the copy fails or the listing is inside an archive
+        """
+        ...
+    
+    def move_selected(self, dest: str) -> Optional[str]:
+        """Move the selected entry to another place
+        
+        An existing directory as `dest` receives the entry under its own name;
+        any other path is the entry's new path, with missing parent
+        directories created. Relative paths start from the current directory
+        and `~` is the home directory. A name that is already taken is
+        handled by the `collision_strategy` setting.
+        
+        Args:
+            dest: Destination directory or path
+            
+        Returns:
+            Where the entry went, or None if nothing is selected or the
+            collision strategy skipped it
+            
+        Raises:
+            ValueError: If the move fails, the name is taken (with the "error"
+                or "ask" strategy), or the listing is inside an archive
+        """
+        ...
+    
+    def refresh(self) -> None:
+        """List the current directory again, picking up changes made outside AstroFS
+        
+        The selected entry stays selected if it still exists.
+        """
+        ...
+    
+    def format_time(self, timestamp: float) -> str:
+        """Format a time the way the file list shows it, using `date_format`
+        
+        Args:
+            timestamp: Seconds since the Unix epoch, like `FileEntry.modified`
+            
+        Returns:
+            The local time in the configured format, or e.g. "3h ago" for `relative`
+            
+        Raises:
+            ValueError: If the timestamp is negative or not finite
+        """
+        ...
+    
+    def copy_file_contents(self, path: str) -> None:
+        """Copy a text file's contents to the system clipboard
+        
+        Args:
+            path: Text file of at most 1 MiB
+            
+        Raises:
+            ValueError: If the file is binary, too large, or there is no clipboard
+        """
+        ...
+    
     def copy_selected(self) -> None:
         """Copy selected file or directory
         
@@ -245,11 +853,67 @@ class PyAstroFS:
         """Start search mode"""
         ...
     
-    def search(self, query: str) -> None:
+    def search(self, query: str, scope: Optional[str] = None) -> None:
         """Perform search with given query
         
         Args:
             query: Search query string
+            scope: "current", "recursive" or "drive"; keeps the current scope if omitted
+        
+        A "drive" search blocks until it finishes or hits
+        `drive_search_time_limit_secs`, so its results may be partial.
+            
+        Raises:
+            ValueError: If scope is not a known scope
+        """
+        ...
+    
+    def search_in(self, path: str, query: str) -> List[PyFileEntry]:
+        """Search below a directory other than the current one
+        
+        Looks through `path` and everything below it (`search_max_depth`
+        levels), or only its direct children with the "current" scope. A
+        "drive" scope is treated as "recursive". Later calls to `search()`
+        go back to the current directory.
+        
+        Args:
+            path: Directory to search in, such as a bookmark's path
+            query: Search query string
+            
+        Returns:
+            The results, best match first
+            
+        Raises:
+            ValueError: If `path` is not a directory
+        """
+        ...
+    
+    def search_scope(self) -> str:
+        """Get the active search scope ("current", "recursive" or "drive")"""
+        ...
+    
+    def set_search_scope(self, scope: str) -> None:
+        """Set the search scope used by later searches
+        
+        Args:
+            scope: "current" (this folder only), "recursive" (everything below
+                the current folder) or "drive" (everything below search_root)
+            
+        Raises:
+            ValueError: If scope is not a known scope
+        """
+        ...
+    
+    def search_threads(self) -> int:
+        """Threads searches walk the disk with; 0 means one per CPU"""
+        ...
+    
+    def set_search_threads(self, threads: int) -> None:
+        """Limit how many threads searches use, e.g. on a shared machine
+        
+        Args:
+            threads: Thread count, or 0 for one per CPU. Saved as
+                parallel_search_threads by save_settings().
         """
         ...
     
@@ -261,6 +925,40 @@ class PyAstroFS:
         """
         ...
     
+    def filter_results_by_category(self, category: str) -> None:
+        """Keep only the current search results in a category
+        
+        Each call narrows the results further; the next search, or
+        clear_result_filters, brings the rest back.
+        
+        Args:
+            category: "images", "audio", "video", "code", "documents" or "archives"
+            
+        Raises:
+            ValueError: If category is not a known category
+        """
+        ...
+    
+    def clear_result_filters(self) -> None:
+        """Show all search results again"""
+        ...
+    
+    def collect_search_results(self, dest: str, preserve_structure: bool = False) -> Tuple[int, List[Tuple[str, str]]]:
+        """Copy the files among the current search results into a directory
+        
+        Folders in the results are skipped. Name collisions follow the
+        `collision_strategy` setting, with "ask" renaming instead.
+        
+        Args:
+            dest: Directory to copy into; created if missing
+            preserve_structure: Keep each file's folders relative to the
+                search root instead of putting every file directly in dest
+        
+        Returns:
+            How many files were copied, and (path, reason) for each failure
+        """
+        ...
+    
     def navigate_to_search_result(self, index: int) -> None:
         """Navigate to a search result
         
@@ -272,6 +970,20 @@ class PyAstroFS:
         """
         ...
     
+    def search_result_action(self, index: int, action: str) -> None:
+        """Act on a search result without necessarily leaving the search
+        
+        Args:
+            index: Index of search result
+            action: "open" (default app, or enter a folder), "reveal" (show it
+                selected in its folder), "copy-path", "delete" (to the trash when
+                `safe_delete` is on) or "bookmark" (its folder)
+            
+        Raises:
+            ValueError: If index or action is invalid
+        """
+        ...
+    
     # Bookmarks
     def add_bookmark(self, name: str) -> None:
         """Add bookmark to current directory
@@ -295,6 +1007,53 @@ class PyAstroFS:
         """
         ...
     
+    def import_jump_data(self, source: str, path: Optional[str] = None) -> int:
+        """Seed the sidebar's recent directories from another jump tool
+        
+        Imported directories go after the ones visited in astrofs, highest
+        score first; directories that no longer exist are skipped.
+        
+        Args:
+            source: "zoxide" (its `db.zo`, or saved `zoxide query --list --score`
+                output) or "autojump" (its `autojump.txt`)
+            path: Data file to read; the tool's usual location when omitted
+        
+        Returns:
+            How many directories were added
+        
+        Raises:
+            ValueError: On an unknown source or an unreadable file
+        """
+        ...
+    
+    def export_bookmarks(self, path: str) -> None:
+        """Export all bookmarks, in order, to a portable file
+        
+        Args:
+            path: Destination file path
+            
+        Raises:
+            ValueError: If export fails
+        """
+        ...
+    
+    def import_bookmarks(self, path: str, merge: bool) -> int:
+        """Import bookmarks from a file written by export_bookmarks
+        
+        Args:
+            path: Source file path
+            merge: Union with existing bookmarks instead of replacing them.
+                Exact duplicates are skipped; name collisions with a different
+                path are renamed ("name (2)").
+            
+        Returns:
+            Number of bookmarks added
+            
+        Raises:
+            ValueError: If the file cannot be read or parsed
+        """
+        ...
+    
     # Themes
     def switch_theme(self, theme_name: str) -> None:
         """Switch to a different theme
@@ -315,6 +1074,50 @@ class PyAstroFS:
         """
         ...
     
+    def import_theme(self, path: str) -> List[PyContrastWarning]:
+        """Import a theme file into the user theme directory
+        
+        Args:
+            path: Path to theme JSON file
+            
+        Returns:
+            Contrast warnings for the imported theme (empty if readable)
+            
+        Raises:
+            ValueError: If the file cannot be read or parsed
+        """
+        ...
+    
+    def check_theme_contrast(self, name: str) -> List[PyContrastWarning]:
+        """Check a theme's key color pairs for readability
+        
+        Args:
+            name: Theme name
+            
+        Returns:
+            Pairs whose contrast ratio is below 4.5
+            
+        Raises:
+            ValueError: If theme not found
+        """
+        ...
+    
+    def auto_adjust_theme(self, name: str) -> int:
+        """Nudge low-contrast foreground colors until the theme is readable
+        
+        The adjusted theme is saved to the user theme directory.
+        
+        Args:
+            name: Theme name
+            
+        Returns:
+            Number of colors changed
+            
+        Raises:
+            ValueError: If theme not found or saving fails
+        """
+        ...
+    
     # Plugins
     def load_plugins(self) -> None:
         """Load all plugins from plugin directory
@@ -346,6 +1149,67 @@ class PyAstroFS:
         """
         ...
     
+    def reload_plugin(self, id: str) -> None:
+        """Reload a plugin by running its unload and load hooks again
+        
+        Args:
+            id: Plugin ID to reload
+            
+        Raises:
+            ValueError: If plugin doesn't exist or its hooks fail
+        """
+        ...
+    
+    def reload_plugins(self) -> None:
+        """Unload every plugin and load them again from the plugin directory
+        
+        Built-in plugins are registered again, and plugins that were enabled
+        are enabled again. Use this after adding a plugin file.
+        
+        Raises:
+            ValueError: If the plugin directory can't be read or a plugin fails to unload
+        """
+        ...
+    
+    def set_plugin_dir(self, path: str) -> None:
+        """Load plugins from another directory, remembered between sessions
+        
+        Plugins are reloaded from the new directory right away. A relative
+        path is taken from the current directory; the directory is created
+        if missing.
+        
+        Args:
+            path: Plugin directory, or "" to go back to the config's plugin_directory
+            
+        Raises:
+            ValueError: If the path is a file or plugins can't be reloaded
+        """
+        ...
+    
+    def plugin_dir(self) -> str:
+        """Get the directory plugins are loaded from
+        
+        Returns:
+            The directory picked with set_plugin_dir, else the config's plugin_directory
+        """
+        ...
+    
+    def plugin_stats(self, id: str) -> PyPluginStats:
+        """Get live statistics for a plugin
+        
+        Hook calls and errors are counted as file and theme hooks fire.
+        
+        Args:
+            id: Plugin ID
+            
+        Returns:
+            Load time, memory usage, hook calls and errors
+            
+        Raises:
+            ValueError: If plugin doesn't exist
+        """
+        ...
+    
     # Media
     def preview_media(self, path: str) -> Optional[str]:
         """Preview media file (image, video, audio)
@@ -361,6 +1225,22 @@ class PyAstroFS:
         """
         ...
     
+    def export_media_info(self, path: str, out: Optional[str] = None, overwrite: bool = False) -> str:
+        """Save a media file's metadata preview as text
+        
+        Args:
+            path: Path to an image, audio or video file
+            out: Output file; defaults to a sidecar such as photo.jpg.txt
+            overwrite: Replace the output file if it already exists
+            
+        Returns:
+            Path of the written file
+            
+        Raises:
+            ValueError: If the file isn't media, or out exists and overwrite is False
+        """
+        ...
+    
     def play_media(self, path: str) -> None:
         """Start playing media file
         
@@ -430,7 +1310,7 @@ class PyAstroFS:
         ...
     
     def export_settings(self, path: str) -> None:
-        """Export settings to file
+        """Export settings to file, including changes not saved yet
         
         Args:
             path: Path to export file
@@ -443,6 +1323,9 @@ class PyAstroFS:
     def import_settings(self, path: str) -> None:
         """Import settings from file
         
+        The settings are saved and applied right away: theme, plugins,
+        pane sizes and whether hidden files are shown.
+        
         Args:
             path: Path to settings file
             
@@ -451,6 +1334,57 @@ class PyAstroFS:
         """
         ...
     
+    # Watching
+    def watch(self, path: str) -> PyDirWatcher:
+        """Watch a directory for changes
+        
+        Args:
+            path: Directory to watch (non-recursively)
+            
+        Returns:
+            Watcher yielding change events
+            
+        Raises:
+            ValueError: If the directory cannot be watched
+        """
+        ...
+    
+    def run_command(self, name: str) -> PyCommandOutcome:
+        """Run a command palette command by its id and report what it did
+        
+        Commands act on the current directory and selection, as if picked
+        from the palette. Ones that open a prompt or view (such as "rename")
+        report "opened" and leave it waiting for input.
+        
+        Args:
+            name: Command id, e.g. "duplicate", "parent" or "new-workspace"
+            
+        Returns:
+            The outcome: the directory navigated to, entries created or
+            removed, the tab switched to, the view opened, or a message
+            
+        Raises:
+            ValueError: If there is no such command, or it fails
+        """
+        ...
+    
+    def open_in_new_tab(self, path: str) -> int:
+        """Open a directory in a new tab after the existing ones
+        
+        The new tab becomes the current one when the `focus_new_tab` setting
+        is on (the default); otherwise the current tab stays active.
+        
+        Args:
+            path: Directory to open
+            
+        Returns:
+            Position of the new tab, counting from 0
+            
+        Raises:
+            ValueError: If `path` is not a directory
+        """
+        ...
+    
     # State Getters
     def get_current_workspace(self) -> PyWorkspace:
         """Get current workspace state
@@ -484,6 +1418,18 @@ class PyAstroFS:
         """
         ...
     
+    def config_dir(self) -> str:
+        """Directory holding config.json, settings, themes and the log file"""
+        ...
+    
+    def data_dir(self) -> str:
+        """Directory holding bookmarks, search history and other saved data"""
+        ...
+    
+    def theme_dirs(self) -> List[str]:
+        """Directories themes are loaded from: the bundled themes, then user themes"""
+        ...
+    
     def get_plugin_manager(self) -> PyPluginManager:
         """Get plugin manager state
         
@@ -1,23 +1,50 @@
+use crate::archive::{is_archive, ArchiveListing};
 use crate::bookmarks::BookmarkManager;
-use crate::config::AppConfig;
-use crate::fileops::FileOperation;
-use crate::files::list_directory;
+use crate::config::{AppConfig, EDITABLE_SETTINGS};
+use crate::fileops::{
+    is_already_exists, CollectReport, CollisionStrategy, FileOperation, FlattenPlan, FlattenReport, PermissionChange, SequenceOrder, TransferKind, TransferOutcome,
+};
+use crate::editor::TextEditor;
+use crate::disk_usage::{UsageCache, UsageEntry, UsageScan};
+use crate::files::{
+    apply_custom_order, diff_files, disk_space, hash_file_with, list_directory_with, list_mounts, recent_files, DiffResult,
+    DirCountCache, DiskSpace, FileEntry, HashAlgo, HiddenPatterns, IgnorePatterns, MimeCache, Mount, SortMode, SortRules, WalkOptions,
+    RECENT_FILES_MAX_DEPTH,
+};
 use crate::palette::{Command, CommandPalette};
 use crate::plugin::PluginManager;
-use crate::preview::{generate_preview, PreviewContent};
-use crate::search::SearchEngine;
+use crate::preview::{generate_preview_with, HighlightOptions, PreviewContent, QuickLook};
+use crate::search::{group_results, DriveSearchEnd, FileCategory, ResultAction, ResultGroup, SearchEngine, SearchGrouping, SearchScope};
 use crate::search_history::SearchHistory;
+use crate::structured::StructuredDoc;
+use crate::sidebar::{push_recent_dir, sidebar_items, SidebarItem};
 use crate::theme::Theme;
-use crate::workspace::{Workspace, WorkspaceManager};
-use crate::persistence::{PersistenceManager, UserSettings};
-use crate::theme_manager::ThemeManager;
+use crate::workspace::{EmptyListing, SelectionMemory, Workspace, WorkspaceManager};
+use crate::persistence::{
+    Autosave, PersistenceManager, RecoveryFile, SelectionState, SessionSnapshot, SessionTab, UserSettings, DIR_VISITS_MAX,
+    RECENT_OPENED_MAX, SAVED_SELECTIONS_MAX,
+};
+use crate::theme_manager::{ContrastWarning, ThemeManager, MIN_CONTRAST_RATIO};
 use crate::plugin_api::PluginManager as ApiPluginManager;
 use crate::media_preview::MediaPreview;
 use crate::media_player::{MediaPlayer, PlaybackController};
+use crate::watcher::DirWatcher;
+use crate::jobs::{JobRegistry, JobState};
+use crate::input::{ChordStep, Chords, IdleTracker};
+use crate::thumbnails::{GraphicsProtocol, ThumbnailLoader};
+use crate::trash::{Pruned, Trash, TrashLimits, TrashedItem};
+use crate::logging::LogErr;
+use crate::git::FileStatus;
 use anyhow::Result;
 use open::that;
+use ratatui::layout::Rect;
 use ratatui::text::Line;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum AppMode {
@@ -25,9 +52,196 @@ pub enum AppMode {
     Search,
     CommandPalette,
     Help,
+    ThemeGallery,
+    ConfirmQuit,
+    ConfirmOverwrite,
+    ConfirmRestore,
+    DiskUsage,
+    Settings,
+    RecentFiles,
+    PluginManager,
+    Trash,
+    Diff,
+    ConfirmPermissions,
+    ConfirmSequenceRename,
+    ConfirmFlatten,
+    ConfirmCollision,
+    Jump,
+    Edit,
+    Places,
+    Jobs,
+    Paths,
+    StructuredPreview,
+    QuickLook,
+    SearchActions,
     Input(InputMode),
 }
 
+/// The disk usage view: a directory's children by recursive size
+pub struct DiskUsageView {
+    pub dir: PathBuf,
+    /// Largest first; empty while the first scan of `dir` runs
+    pub entries: Vec<UsageEntry>,
+    pub total: u64,
+    pub selected: usize,
+    /// Child to select once the scan finishes, after going up a level
+    reselect: Option<PathBuf>,
+    scan: Option<UsageScanTask>,
+}
+
+impl DiskUsageView {
+    /// The job sizing `dir`, while one runs
+    pub fn scan_job(&self) -> Option<u64> {
+        self.scan.as_ref().map(|scan| scan.job)
+    }
+}
+
+/// A disk usage scan running on a worker thread
+struct UsageScanTask {
+    job: u64,
+    results: Receiver<Result<Option<UsageScan>>>,
+}
+
+/// Search results being copied into a directory on a worker thread
+pub struct CollectTask {
+    pub dest: PathBuf,
+    results: Receiver<Result<CollectReport>>,
+}
+
+/// A permission change shown for review before it is applied
+#[derive(Clone, Debug)]
+pub struct PendingPermissions {
+    pub change: PermissionChange,
+    /// Marked entries (or the selection) the change was asked for
+    pub roots: Vec<PathBuf>,
+    /// Everything the change will touch, directories recursed
+    pub targets: Vec<PathBuf>,
+}
+
+/// A numbered rename of photos waiting for confirmation
+#[derive(Clone, Debug)]
+pub struct PendingSequenceRename {
+    pub prefix: String,
+    pub order: SequenceOrder,
+    /// Marked entries (or the selection) the rename was asked for
+    pub sources: Vec<PathBuf>,
+    /// Old and new path of every file, in sequence
+    pub plan: Vec<(PathBuf, PathBuf)>,
+}
+
+/// A row in the places view: a mounted drive or a bookmark
+#[derive(Clone, Debug)]
+pub enum Place {
+    Mount(Mount),
+    Bookmark { name: String, path: PathBuf },
+}
+
+impl Place {
+    pub fn path(&self) -> &Path {
+        match self {
+            Place::Mount(mount) => &mount.mount_point,
+            Place::Bookmark { path, .. } => path,
+        }
+    }
+}
+
+/// Entries yanked with `c` (copy) or `m` (move), waiting to be pasted
+#[derive(Clone, Debug)]
+pub struct Yank {
+    pub paths: Vec<PathBuf>,
+    pub kind: TransferKind,
+}
+
+/// A paste in progress. With the `Ask` strategy it stops at each name
+/// collision until the user picks what to do.
+#[derive(Clone, Debug)]
+pub struct PasteBatch {
+    pub kind: TransferKind,
+    pub dest_dir: PathBuf,
+    pub queue: VecDeque<PathBuf>,
+    pub strategy: CollisionStrategy,
+    /// Source and the existing path it collides with, waiting for an answer
+    pub conflict: Option<(PathBuf, PathBuf)>,
+    /// Where entries landed so far
+    pub pasted: Vec<PathBuf>,
+    pub skipped: usize,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// A JSON, YAML or TOML preview being folded from the keyboard
+#[derive(Clone, Debug)]
+pub struct StructuredView {
+    pub path: PathBuf,
+    pub doc: StructuredDoc,
+    /// Index into `doc.rows()`
+    pub cursor: usize,
+}
+
+/// The last safe delete, undoable until `expires`
+#[derive(Clone, Debug)]
+pub struct PendingUndo {
+    pub item: TrashedItem,
+    pub expires: Instant,
+}
+
+/// How long a free disk space reading is reused before asking the OS again
+const DISK_SPACE_REFRESH: Duration = Duration::from_secs(30);
+
+/// Bookmarks there must be before adding one suggests a frequently visited directory too
+const BOOKMARK_HINT_AFTER: usize = 5;
+
+/// Largest count a digit prefix builds (`5j`), so a held key can't queue a huge repeat
+const MAX_COUNT: usize = 999;
+
+/// Largest file whose text can be copied to the clipboard, or diffed
+const COPY_CONTENTS_LIMIT: u64 = 1024 * 1024;
+
+/// Where copied paths, hashes and file contents go; the system clipboard
+/// unless a test puts a stand-in in its place
+pub trait TextClipboard: Send {
+    fn set_text(&mut self, text: &str) -> Result<()>;
+}
+
+impl TextClipboard for arboard::Clipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        arboard::Clipboard::set_text(self, text.to_string())?;
+        Ok(())
+    }
+}
+
+/// Directories whose selected entry is remembered while running
+const SELECTION_MEMORY_MAX: usize = 500;
+
+/// Digests of one file, in `HashAlgo::ALL` order
+pub type FileHashes = Vec<(HashAlgo, String)>;
+
+/// Checksums being computed on a worker thread
+pub struct HashTask {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+    pub size: u64,
+    /// The registry job doing the hashing, which tracks how far it got
+    pub job: u64,
+    cancel: Arc<AtomicBool>,
+    results: Receiver<Result<FileHashes>>,
+}
+
+/// A destructive trash action waiting for `y`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrashAction {
+    DeleteSelected,
+    Empty,
+}
+
+/// A line in the search results view
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchRow {
+    /// Header of the group at this index
+    Header(usize),
+    /// Index into the search results
+    Result(usize),
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum InputMode {
     CreateFile,
@@ -35,8 +249,90 @@ pub enum InputMode {
     Rename,
     GoToPath,
     AddBookmark,
+    EditSetting,
+    ChangeMode,
+    ChangeOwner,
+    CollectResults,
+    SequenceRename,
+    MoveTo,
+    SearchIn,
+}
+
+/// What a palette command did, for callers that script the app rather than
+/// read the status bar
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommandOutcome {
+    /// The active tab moved to this directory
+    Navigated(PathBuf),
+    /// Another tab became active, at this position
+    SwitchedTab(usize),
+    /// These entries appeared in the current directory
+    Created(Vec<PathBuf>),
+    /// These entries of the current directory are gone
+    Removed(Vec<PathBuf>),
+    /// A view or prompt opened and is waiting for input
+    Opened(AppMode),
+    /// Nothing else changed, but the command said this
+    Message(String),
+    Nothing,
+}
+
+/// Where an `App` keeps what it saves between sessions
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppDirs {
+    /// Config file, user settings and themes
+    pub config_dir: PathBuf,
+    /// Bookmarks and search history
+    pub data_dir: PathBuf,
+    /// Deleted files, see `Trash`
+    pub trash_dir: PathBuf,
+}
+
+impl AppDirs {
+    /// The user's AstroFS folders
+    pub fn user() -> Result<Self> {
+        let data_dir = dirs::data_dir().ok_or_else(|| anyhow::anyhow!("Cannot find data directory"))?;
+        Ok(Self {
+            config_dir: PersistenceManager::default_config_dir()?,
+            data_dir: data_dir.join("astrofs"),
+            trash_dir: Trash::default_root()?,
+        })
+    }
+
+    /// Everything in folders under `root`, e.g. a temporary folder
+    #[allow(dead_code)]
+    pub fn under(root: &Path) -> Self {
+        Self {
+            config_dir: root.join("config"),
+            data_dir: root.join("data"),
+            trash_dir: root.join("data").join("Trash"),
+        }
+    }
+
+    /// Searches kept between sessions, see `SearchHistory`
+    pub fn search_history_file(&self) -> PathBuf {
+        self.data_dir.join("search_history.json")
+    }
+
+    /// The running session's snapshot, see `RecoveryFile`
+    pub fn recovery_file(&self) -> PathBuf {
+        self.config_dir.join("session_recovery.json")
+    }
+
+    /// Where log lines are appended
+    pub fn log_file(&self) -> PathBuf {
+        self.config_dir.join("astrofs.log")
+    }
+
+    /// Extra `.tmTheme` files for code previews
+    pub fn highlight_theme_dir(&self) -> PathBuf {
+        self.config_dir.join("highlight_themes")
+    }
 }
 
+/// Called with the old and new directory when the active tab moves
+pub type DirListener = Box<dyn Fn(&Path, &Path) + Send>;
+
 pub struct App {
     // Core
     pub workspace_manager: WorkspaceManager,
@@ -54,11 +350,26 @@ pub struct App {
     pub error: Option<String>,
     pub input_buffer: String,
     pub input_mode: Option<InputMode>,
+    // Often visited directory without a bookmark, offered while adding one
+    pub bookmark_hint: Option<PathBuf>,
 
     // Search
     pub search_engine: SearchEngine,
     pub search_history: SearchHistory,
     pub search_query: String,
+    pub search_scope: SearchScope,
+    // Folder picked to search in place of the current directory
+    pub scoped_root: Option<PathBuf>,
+    // Query the current results were found for
+    pub searched_query: Option<String>,
+    pub search_grouping: SearchGrouping,
+    // Selected row in the results view
+    pub search_index: usize,
+    // Keys of result groups folded away
+    pub collapsed_groups: HashSet<String>,
+    // Result the actions menu is open for, and the highlighted action
+    pub search_action_target: Option<usize>,
+    pub search_action_index: usize,
 
     // Command palette
     pub command_palette: CommandPalette,
@@ -76,33 +387,227 @@ pub struct App {
     pub media_preview: MediaPreview,
     pub media_player: MediaPlayer,
     pub playback_controller: PlaybackController,
+
+    // Filesystem watching for the active directory
+    pub dir_watcher: Option<DirWatcher>,
+
+    // Theme gallery: render themes built on open, sorted by name
+    pub theme_gallery: Vec<(String, Theme)>,
+    pub theme_gallery_index: usize,
+
+    // Background operations still running
+    pub jobs: JobRegistry,
+    pub quit_when_idle: bool,
+    // When input last arrived, to slow polling down while idle
+    pub idle: IdleTracker,
+    // Periodic saving of settings, bookmarks and search history
+    autosave: Autosave,
+    // Session snapshot kept for crash recovery; None until main sets it up
+    recovery: Option<RecoveryFile>,
+    // Session a crashed run left behind, while the user decides whether to restore it
+    pub recovered_session: Option<SessionSnapshot>,
+    // Multi-key bindings from the config, and the keys typed so far
+    chords: Chords,
+    // Digits typed before a movement key to repeat it (`5j`)
+    pub pending_count: Option<usize>,
+    // Name prefix typed to jump to an entry (`f`), and when its last key came
+    pub jump_prefix: String,
+    pub jump_last_key: Option<Instant>,
+
+    // Settings screen
+    pub settings_index: usize,
+
+    // External command waiting to run with the terminal released
+    pub pending_command: Option<String>,
+
+    // Told about directory changes, alongside the plugins' on_directory_changed
+    dir_listeners: Vec<DirListener>,
+
+    // Image gallery view
+    pub gallery_view: bool,
+    pub gallery_columns: usize,
+    pub thumbnails: ThumbnailLoader,
+    pub graphics_protocol: GraphicsProtocol,
+    // Thumbnail cells to fill with Kitty/Sixel images after a frame is drawn
+    pub gallery_placements: Vec<(PathBuf, Rect)>,
+
+    // Recently-modified files under the current directory, or recently opened files
+    pub recent_files: Vec<FileEntry>,
+    pub recent_index: usize,
+    pub recent_preview: PreviewContent,
+    // Whether `recent_files` holds opened files rather than modified ones
+    pub recent_opened: bool,
+
+    // Safe delete
+    pub trash: Option<Trash>,
+    pub pending_undo: Option<PendingUndo>,
+
+    // Trash browser
+    pub trash_items: Vec<TrashedItem>,
+    pub trash_index: usize,
+    // Bytes in the trash, measured when the trash view opens or changes
+    pub trash_size: u64,
+    pub pending_trash_action: Option<TrashAction>,
+
+    // Plugin manager overlay
+    pub plugin_index: usize,
+
+    // Media info export waiting for overwrite confirmation: (media file, sidecar)
+    pub pending_export: Option<(PathBuf, PathBuf)>,
+    // Rename onto an existing name waiting for confirmation: (entry, new name)
+    pub pending_rename: Option<(PathBuf, String)>,
+
+    // chmod/chown waiting for confirmation
+    pub pending_permissions: Option<PendingPermissions>,
+
+    // Numbered rename waiting for confirmation
+    pub pending_sequence: Option<PendingSequenceRename>,
+
+    // Flattened folder waiting for confirmation
+    pub pending_flatten: Option<FlattenPlan>,
+
+    // Copy/move clipboard and the paste working through it
+    pub yank: Option<Yank>,
+    pub paste_batch: Option<PasteBatch>,
+
+    // Compiled `ignore_patterns`, and whether they are bypassed for this session
+    pub ignore_patterns: IgnorePatterns,
+    pub ignore_patterns_bypassed: bool,
+    // Compiled `always_show`/`always_hide`
+    hidden_patterns: HiddenPatterns,
+    // Compiled `sort_rules`, and sort modes picked with `s` this session, per directory
+    sort_rules: SortRules,
+    sort_overrides: HashMap<PathBuf, SortMode>,
+
+    // File checksums, cached by path + mtime
+    pub file_hashes: HashMap<PathBuf, (SystemTime, FileHashes)>,
+    pub hash_task: Option<HashTask>,
+    pub collect_task: Option<CollectTask>,
+    // Disk usage view, while in AppMode::DiskUsage, and directory sizes found so far
+    pub disk_usage: Option<DiskUsageView>,
+    usage_cache: UsageCache,
+    // Where files inside archives are extracted for previews and opening
+    archive_temp: Option<tempfile::TempDir>,
+    // Kept open because X11 clipboards lose their contents when the owner goes away
+    clipboard: Option<Box<dyn TextClipboard>>,
+
+    // Diff overlay
+    pub diff: Option<DiffResult>,
+    pub diff_scroll: usize,
+    pub diff_side_by_side: bool,
+
+    // Built-in editor
+    pub editor: Option<TextEditor>,
+
+    // Places view: drives first, then bookmarks
+    pub places: Vec<Place>,
+    pub places_index: usize,
+    /// Set while the places view is picking a bookmark to copy or move the marked entries into
+    pub places_transfer: Option<TransferKind>,
+
+    // Jobs overlay selection, into `jobs.overview()`
+    pub jobs_index: usize,
+
+    /// The entry last selected in each visited directory, selected again on return
+    pub selection_memory: SelectionMemory,
+
+    // Paths overlay selection, into `app_paths()`
+    pub paths_index: usize,
+
+    // Structured preview with folds, while in AppMode::StructuredPreview
+    pub structured_view: Option<StructuredView>,
+
+    // Full-screen preview, while in AppMode::QuickLook
+    pub quick_look: Option<QuickLook>,
+
+    // Status bar disk space, re-read every DISK_SPACE_REFRESH or when the directory changes
+    pub disk_space: Option<DiskSpace>,
+    disk_space_checked: Option<(PathBuf, Instant)>,
+
+    // Folder item counts for the listing, while `show_dir_counts` is on
+    dir_counts: DirCountCache,
+    // Sniffed mime types of extensionless files in the listing
+    mimes: MimeCache,
+
+    // Git state of the listed entries, while `git_status_column` is on
+    git_statuses: HashMap<PathBuf, FileStatus>,
+
+    // Where state is loaded from and saved to
+    dirs: AppDirs,
 }
 
 impl App {
+    #[allow(dead_code)]
     pub fn new() -> Result<Self> {
-        let mut config = AppConfig::load().unwrap_or_default();
-        config.validate();
+        Self::with_config(AppConfig::load().log_err("load config").unwrap_or_default())
+    }
+
+    /// Start with an already loaded config, e.g. one given with `--config`
+    #[allow(dead_code)]
+    pub fn with_config(config: AppConfig) -> Result<Self> {
+        Self::with_dirs(config, AppDirs::user()?)
+    }
+
+    /// Start with state loaded from and saved to `dirs`; the config is saved
+    /// to the config folder too unless it came from a file of its own
+    pub fn with_dirs(mut config: AppConfig, dirs: AppDirs) -> Result<Self> {
+        if config.source_path.is_none() {
+            config.source_path = Some(dirs.config_dir.join("config.json"));
+        }
+        for warning in config.validate() {
+            tracing::warn!("Config corrected: {}", warning);
+        }
 
         let start_dir = PathBuf::from(&config.default_directory);
         let workspace_manager = WorkspaceManager::new(start_dir);
 
-        let bookmark_manager = BookmarkManager::new().unwrap_or_default();
-        let mut plugin_manager = PluginManager::default();
+        std::fs::create_dir_all(&dirs.data_dir).log_err("create the data folder");
+        let bookmark_manager = BookmarkManager::with_file(dirs.data_dir.join("bookmarks.json"))
+            .log_err("load bookmarks")
+            .unwrap_or_default();
+        let search_history = SearchHistory::load_from(&dirs.search_history_file())
+            .log_err("load search history")
+            .unwrap_or_default();
 
-        // Load plugins silently, don't fail if plugins directory doesn't exist
-        let _ = plugin_manager.load_plugins();
-
-        let search_history = SearchHistory::load().unwrap_or_default();
+        let chords = Chords::new(&config.chords);
 
         // Initialize Beast Mode managers
-        let persistence_manager = PersistenceManager::new()?;
-        let user_settings = PersistenceManager::load_default().unwrap_or_default();
-        let theme_manager = ThemeManager::new().unwrap_or_default();
+        let persistence_manager = PersistenceManager::with_dir(dirs.config_dir.clone())?;
+        let user_settings = persistence_manager.load_settings().log_err("load user settings").unwrap_or_default();
+        let mut selection_memory = SelectionMemory::new(SELECTION_MEMORY_MAX);
+        if config.persist_selections {
+            for saved in user_settings.selections.iter().rev() {
+                selection_memory.remember(&saved.dir, &saved.name);
+            }
+        }
+        let theme_manager = ThemeManager::with_paths(dirs.config_dir.join("themes"), dirs.config_dir.join("user_themes"))
+            .log_err("load themes")
+            .unwrap_or_default();
+        if let Some(warning) = config.check_theme(&theme_manager.list_themes()) {
+            tracing::warn!("Config corrected: {}", warning);
+        }
         let _current_theme = user_settings.current_theme.clone();
-        let api_plugin_manager = ApiPluginManager::default();
+        let plugin_dir = plugin_directory(&config, &user_settings);
+        let mut plugin_manager = PluginManager::new(plugin_dir.clone());
+        // A missing plugins directory is not an error worth stopping for
+        plugin_manager.load_plugins().log_err("load legacy plugins");
+        let api_plugin_manager = ApiPluginManager::new(plugin_dir);
         let media_preview = MediaPreview::new();
         let media_player = MediaPlayer::new();
         let playback_controller = PlaybackController::new();
+        let graphics_protocol = GraphicsProtocol::from_setting(&config.thumbnail_protocol);
+        let search_scope = SearchScope::parse(&config.search_scope).unwrap_or_default();
+        let search_grouping = SearchGrouping::parse(&config.search_grouping).unwrap_or_default();
+        let ignore_patterns = IgnorePatterns::new(&config.ignore_patterns)
+            .log_err("compile ignore_patterns")
+            .unwrap_or_default();
+        let hidden_patterns = HiddenPatterns::new(&config.always_show, &config.always_hide)
+            .log_err("compile always_show/always_hide")
+            .unwrap_or_default();
+        let sort_rules = SortRules::new(&config.sort_rules).log_err("compile sort_rules").unwrap_or_default();
+        let mut jobs = JobRegistry::with_limit(config.max_concurrent_jobs);
+        jobs.set_low_priority(config.low_priority_jobs);
+        let autosave = Autosave::new(Duration::from_secs(config.autosave_interval_secs), Instant::now());
 
         let mut app = Self {
             workspace_manager,
@@ -115,10 +620,19 @@ impl App {
             message: None,
             error: None,
             input_buffer: String::new(),
+            bookmark_hint: None,
             input_mode: None,
             search_engine: SearchEngine::new(),
             search_history,
             search_query: String::new(),
+            search_scope,
+            scoped_root: None,
+            searched_query: None,
+            search_grouping,
+            search_index: 0,
+            collapsed_groups: HashSet::new(),
+            search_action_target: None,
+            search_action_index: 0,
             command_palette: CommandPalette::new(),
             command_search_index: 0,
             bookmark_manager,
@@ -130,37 +644,477 @@ impl App {
             media_preview,
             media_player,
             playback_controller,
+            dir_watcher: None,
+            theme_gallery: Vec::new(),
+            theme_gallery_index: 0,
+            jobs,
+            quit_when_idle: false,
+            idle: IdleTracker::new(Instant::now()),
+            autosave,
+            recovery: None,
+            recovered_session: None,
+            chords,
+            pending_count: None,
+            jump_prefix: String::new(),
+            jump_last_key: None,
+            settings_index: 0,
+            pending_command: None,
+            dir_listeners: Vec::new(),
+            gallery_view: false,
+            gallery_columns: 1,
+            thumbnails: ThumbnailLoader::new(),
+            graphics_protocol,
+            gallery_placements: Vec::new(),
+            recent_files: Vec::new(),
+            recent_index: 0,
+            recent_preview: PreviewContent {
+                lines: Vec::new(),
+                is_binary: false,
+                preview_type: crate::preview::PreviewType::Text,
+            },
+            recent_opened: false,
+            trash: Some(Trash::with_root(dirs.trash_dir.clone())),
+            pending_undo: None,
+            trash_items: Vec::new(),
+            trash_index: 0,
+            trash_size: 0,
+            pending_trash_action: None,
+            plugin_index: 0,
+            pending_export: None,
+            pending_rename: None,
+            pending_permissions: None,
+            pending_sequence: None,
+            pending_flatten: None,
+            yank: None,
+            paste_batch: None,
+            ignore_patterns,
+            hidden_patterns,
+            sort_rules,
+            sort_overrides: HashMap::new(),
+            ignore_patterns_bypassed: false,
+            file_hashes: HashMap::new(),
+            hash_task: None,
+            collect_task: None,
+            disk_usage: None,
+            usage_cache: UsageCache::default(),
+            archive_temp: None,
+            clipboard: None,
+            diff: None,
+            diff_scroll: 0,
+            diff_side_by_side: true,
+            editor: None,
+            places: Vec::new(),
+            places_index: 0,
+            places_transfer: None,
+            jobs_index: 0,
+            selection_memory,
+            paths_index: 0,
+            structured_view: None,
+            quick_look: None,
+            disk_space: None,
+            disk_space_checked: None,
+            dir_counts: DirCountCache::default(),
+            mimes: MimeCache::default(),
+            git_statuses: HashMap::new(),
+            dirs,
         };
 
         // Validate app state to ensure all functionality is exercised
-        let _ = crate::integration_helpers::validate_app_state(&mut app);
+        crate::integration_helpers::validate_app_state(&mut app).log_err("validate app state");
         
         // Run all demo functions to exercise Beast Mode code
-        let _ = crate::integration_helpers::demo_theme_operations(&mut app.theme_manager);
-        let _ = crate::integration_helpers::demo_persistence_operations(&mut app.user_settings);
-        let _ = crate::integration_helpers::demo_plugin_manager_comprehensive(&mut app.api_plugin_manager);
-        let _ = crate::integration_helpers::demo_media_player(&mut app.media_player, &app.playback_controller);
+        crate::integration_helpers::demo_theme_operations(&mut app.theme_manager).log_err("run theme operations");
+        crate::integration_helpers::demo_persistence_operations(&mut app.user_settings);
+        crate::integration_helpers::demo_plugin_manager_comprehensive(&mut app.api_plugin_manager);
+        crate::integration_helpers::demo_media_player(&mut app.media_player, &app.playback_controller);
         
         // Try demo media detection on current directory
         let current_dir = app.workspace_manager.active_workspace().current_dir.clone();
-        let _ = crate::integration_helpers::demo_media_detection(&current_dir);
+        crate::integration_helpers::demo_media_detection(&current_dir).log_err("detect media in the start directory");
+
+        // Built-in plugins start disabled unless the user enabled them before
+        let plugin_data = app.user_settings.plugin_settings.clone();
+        app.api_plugin_manager.set_plugin_data(plugin_data);
+        app.api_plugin_manager.set_persistence(app.persistence_manager.clone());
+        app.api_plugin_manager.register_builtin();
+        if app.config.enable_plugins {
+            app.api_plugin_manager.load_all().log_err("load plugins");
+            for (path, reason) in app.api_plugin_manager.load_errors() {
+                tracing::warn!("Skipped plugin {:?}: {}", path, reason);
+            }
+        }
+        app.apply_user_settings();
+        // What was just loaded is what's on disk
+        for (store, snapshot) in app.autosave_snapshots() {
+            app.autosave.mark_saved(store, &snapshot);
+        }
 
+        app.refresh_workspace().log_err("list the start directory");
         Ok(app)
     }
 
+    /// Serialised state of each store autosave writes, to tell which changed
+    fn autosave_snapshots(&mut self) -> Vec<(&'static str, String)> {
+        self.user_settings.plugin_settings = self.api_plugin_manager.plugin_data();
+        self.store_selections();
+        let snapshots = [
+            ("user settings", serde_json::to_string(&self.user_settings)),
+            ("bookmarks", serde_json::to_string(&self.bookmark_manager.list())),
+            ("search history", serde_json::to_string(&self.search_history)),
+        ];
+        snapshots
+            .into_iter()
+            .filter_map(|(store, snapshot)| Some((store, snapshot.log_err("snapshot state for autosave")?)))
+            .collect()
+    }
+
+    /// Save settings, bookmarks and search history that changed, once every
+    /// `autosave_interval_secs`; called from the main loop
+    pub fn autosave(&mut self, now: Instant) {
+        if !self.autosave.due(now) {
+            return;
+        }
+        let snapshots = self.autosave_snapshots();
+        let (user_settings, bookmarks, search_history) = (&self.user_settings, &self.bookmark_manager, &self.search_history);
+        let (persistence, search_history_file) = (&self.persistence_manager, self.dirs.search_history_file());
+        let saved = self.autosave.run(now, snapshots, |store| match store {
+            "user settings" => persistence.save_settings(user_settings),
+            "bookmarks" => bookmarks.save(),
+            _ => search_history.save_to(&search_history_file),
+        });
+        if !saved.is_empty() {
+            tracing::debug!("Autosaved {}", saved.join(", "));
+        }
+    }
+
+    /// Copy the most recent selections into the settings to be saved, or clear
+    /// them when `persist_selections` is off
+    fn store_selections(&mut self) {
+        self.user_settings.selections = if self.config.persist_selections {
+            self.selection_memory
+                .recent()
+                .take(SAVED_SELECTIONS_MAX)
+                .map(|(dir, name)| SelectionState { dir: dir.to_path_buf(), name: name.to_string() })
+                .collect()
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Feed a file-list keypress to the chords; true if it was taken, either
+    /// completing a chord (whose command runs) or waiting for the next key
+    pub fn press_chord_key(&mut self, key: char) -> Result<bool> {
+        match self.chords.press(key, Instant::now()) {
+            ChordStep::Pending => Ok(true),
+            ChordStep::Complete(id) => {
+                if let Some(command) = self.command_palette.get(&id).cloned() {
+                    self.execute_command(&command)?;
+                }
+                Ok(true)
+            }
+            ChordStep::Unbound => Ok(false),
+        }
+    }
+
+    /// Bring the running app in line with `user_settings`: plugins, themes and pane sizes
+    fn apply_user_settings(&mut self) {
+        for id in self.user_settings.enabled_plugins.clone() {
+            self.api_plugin_manager.enable(&id).log_err(&format!("enable plugin {}", id));
+        }
+
+        // Restore the saved theme, keeping the current one if it no longer exists
+        let saved_theme = self.user_settings.current_theme.clone();
+        if self.theme_manager.set_current(&saved_theme).is_ok() {
+            self.apply_current_theme();
+        } else {
+            self.apply_icon_set();
+        }
+        if !self.user_settings.highlight_theme.is_empty() {
+            self.config.highlight_theme = self.user_settings.highlight_theme.clone();
+        }
+        // Pane sizes picked with the resize keys win over the config's
+        if crate::layout::PREVIEW_RATIO_RANGE.contains(&self.user_settings.preview_width_ratio) {
+            self.config.preview_width_ratio = self.user_settings.preview_width_ratio;
+        }
+        if crate::layout::SIDEBAR_WIDTH_RANGE.contains(&self.user_settings.sidebar_width) {
+            self.config.sidebar_width = self.user_settings.sidebar_width;
+        }
+        self.search_engine.set_threads(self.user_settings.parallel_search_threads);
+        self.search_engine.set_low_priority(self.config.low_priority_jobs);
+    }
+
+    /// Limit searches to `threads` threads (0 for one per CPU), remembered in the settings
+    #[allow(dead_code)]
+    pub fn set_search_threads(&mut self, threads: usize) {
+        self.user_settings.parallel_search_threads = threads;
+        self.search_engine.set_threads(threads);
+    }
+
+    /// Keep a session snapshot in `recovery` from now on, first offering to
+    /// restore the one an unclean exit left there. While another instance is
+    /// using the file this one keeps no snapshot.
+    pub fn start_session_recovery(&mut self, mut recovery: RecoveryFile) {
+        match recovery.lock() {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::info!("Another instance is running; session recovery is off");
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to lock the session recovery file: {:#}", e);
+                return;
+            }
+        }
+        let session = recovery.read().log_err("read the session recovery file").flatten();
+        if let Some(session) = session.filter(|session| !session.tabs.is_empty()) {
+            self.recovered_session = Some(session);
+            self.mode = AppMode::ConfirmRestore;
+        }
+        self.recovery = Some(recovery);
+    }
+
+    fn session_snapshot(&self) -> SessionSnapshot {
+        let tabs = self
+            .workspace_manager
+            .workspaces()
+            .iter()
+            .map(|workspace| SessionTab {
+                // A folder inside an archive can't be listed until the archive is opened again
+                path: match &workspace.archive {
+                    Some(archive) => archive.root.parent().unwrap_or(&archive.root).to_path_buf(),
+                    None => workspace.current_dir.clone(),
+                },
+                selected_index: workspace.selected_index,
+                scroll_offset: workspace.scroll_offset,
+                title: workspace.renamed.then(|| workspace.title.clone()),
+            })
+            .collect();
+        let search_query = if self.mode == AppMode::Search { self.search_query.clone() } else { String::new() };
+        SessionSnapshot {
+            tabs,
+            active_tab_index: self.workspace_manager.active_index(),
+            search_query,
+        }
+    }
+
+    /// Update the recovery file when tabs, selection or the search changed;
+    /// called from the main loop. The old session is left alone until the
+    /// user has answered the restore prompt.
+    pub fn write_recovery(&mut self) {
+        if self.recovered_session.is_some() {
+            return;
+        }
+        let snapshot = self.session_snapshot();
+        if let Some(recovery) = self.recovery.as_mut() {
+            recovery.write(&snapshot).log_err("write the session recovery file");
+        }
+    }
+
+    /// Reopen the tabs (and search) of the session a crashed run left behind
+    pub fn restore_session(&mut self) -> Result<()> {
+        self.mode = AppMode::Normal;
+        let Some(session) = self.recovered_session.take() else {
+            return Ok(());
+        };
+        // Folders gone since are left out; the active tab, if gone, passes to the one before it
+        let mut tabs: Vec<SessionTab> = Vec::new();
+        let mut active_tab = 0;
+        for (i, tab) in session.tabs.into_iter().enumerate() {
+            if !tab.path.is_dir() {
+                continue;
+            }
+            if i <= session.active_tab_index {
+                active_tab = tabs.len();
+            }
+            tabs.push(tab);
+        }
+        if tabs.is_empty() {
+            self.error = Some("Nothing to restore: the session's folders are gone".to_string());
+            return Ok(());
+        }
+
+        let show_hidden = self.workspace_manager.active_workspace().show_hidden;
+        let mut ids = Vec::new();
+        for (i, tab) in tabs.iter().enumerate() {
+            if i == 0 {
+                self.workspace_manager = WorkspaceManager::new(tab.path.clone());
+            } else {
+                self.workspace_manager.create_workspace(tab.path.clone());
+            }
+            self.workspace_manager.active_workspace_mut().show_hidden = show_hidden;
+            self.refresh_workspace()?;
+            let workspace = self.workspace_manager.active_workspace_mut();
+            workspace.select(tab.selected_index);
+            workspace.scroll_offset = tab.scroll_offset.min(workspace.selected_index);
+            if let Some(title) = &tab.title {
+                workspace.rename(title.clone());
+            }
+            ids.push(workspace.id);
+        }
+        let active = ids[active_tab];
+        self.workspace_manager.switch_workspace(active);
+        self.refresh_workspace()?;
+
+        if !session.search_query.is_empty() {
+            self.start_search();
+            self.search_query = session.search_query;
+            self.perform_search();
+        }
+        self.message = Some(format!("Restored {} tab(s) from the last session", ids.len()));
+        Ok(())
+    }
+
+    /// Start fresh; the crashed session's file is replaced by this one's
+    pub fn discard_recovered_session(&mut self) {
+        self.recovered_session = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Open at a directory, or at a file's parent with the file selected
+    pub fn open_start_path(&mut self, path: &Path) -> Result<()> {
+        let path = std::fs::canonicalize(path)
+            .map_err(|e| anyhow::anyhow!("Cannot open {}: {}", path.display(), e))?;
+        let (dir, file) = if path.is_dir() {
+            (path, None)
+        } else {
+            let parent = path
+                .parent()
+                .ok_or_else(|| anyhow::anyhow!("{} has no parent directory", path.display()))?
+                .to_path_buf();
+            (parent, Some(path))
+        };
+
+        self.workspace_manager = WorkspaceManager::new(dir);
+        self.refresh_workspace()?;
+
+        if let Some(file) = file {
+            let workspace = self.workspace_manager.active_workspace_mut();
+            if let Some(index) = workspace.entries.iter().position(|e| e.path == file) {
+                workspace.select(index);
+            }
+            self.update_preview();
+        }
+        Ok(())
+    }
+
     // ========== Navigation ==========
+    #[allow(dead_code)]
     pub fn move_up(&mut self) {
+        self.move_up_by(1);
+    }
+
+    #[allow(dead_code)]
+    pub fn move_down(&mut self) {
+        self.move_down_by(1);
+    }
+
+    /// Move the selection up `rows`, stopping at the top
+    pub fn move_up_by(&mut self, rows: usize) {
         let workspace = self.workspace_manager.active_workspace_mut();
-        workspace.move_up();
+        for _ in 0..rows {
+            workspace.move_up();
+        }
         self.update_preview();
     }
 
-    pub fn move_down(&mut self) {
+    /// Move the selection down `rows`, stopping at the bottom
+    pub fn move_down_by(&mut self, rows: usize) {
         let workspace = self.workspace_manager.active_workspace_mut();
-        workspace.move_down();
+        for _ in 0..rows {
+            workspace.move_down();
+        }
+        self.update_preview();
+    }
+
+    /// Add a typed digit to the count for the next movement; false if it
+    /// doesn't start or extend one (`0` alone, or the sidebar's jump keys)
+    pub fn push_count_digit(&mut self, digit: char) -> bool {
+        let Some(value) = digit.to_digit(10) else {
+            return false;
+        };
+        let count = match self.pending_count {
+            Some(count) => count * 10 + value as usize,
+            None if value == 0 || self.user_settings.show_sidebar => return false,
+            None => value as usize,
+        };
+        self.pending_count = Some(count.min(MAX_COUNT));
+        true
+    }
+
+    /// The pending count (1 without one), cleared for the next key
+    pub fn take_count(&mut self) -> usize {
+        self.pending_count.take().unwrap_or(1)
+    }
+
+    /// Select the entry at `index`, clamped to the listing
+    pub fn select_index(&mut self, index: usize) {
+        self.workspace_manager.active_workspace_mut().select(index);
         self.update_preview();
     }
 
+    #[allow(dead_code)]
+    pub fn selected_index(&self) -> usize {
+        self.get_current_workspace().selected_index
+    }
+
+    /// Select entries by typing the start of their name
+    pub fn start_jump(&mut self) {
+        self.mode = AppMode::Jump;
+        self.jump_prefix.clear();
+        self.jump_last_key = None;
+        self.message = None;
+    }
+
+    pub fn cancel_jump(&mut self) {
+        self.mode = AppMode::Normal;
+        self.jump_prefix.clear();
+        self.jump_last_key = None;
+    }
+
+    /// Add `c` to the jump prefix, starting over when the last key came more than
+    /// `jump_timeout_ms` before `now`, and select the first entry the prefix starts
+    pub fn jump_key(&mut self, c: char, now: Instant) {
+        if self.jump_prefix_shown(now).is_none() {
+            self.jump_prefix.clear();
+        }
+        self.jump_prefix.extend(c.to_lowercase());
+        self.jump_last_key = Some(now);
+        self.jump_to_prefix();
+    }
+
+    pub fn jump_backspace(&mut self, now: Instant) {
+        self.jump_prefix.pop();
+        self.jump_last_key = Some(now);
+        self.jump_to_prefix();
+    }
+
+    /// The prefix typed so far, while more keys would still add to it
+    pub fn jump_prefix_shown(&self, now: Instant) -> Option<&str> {
+        let last = self.jump_last_key?;
+        let timeout = Duration::from_millis(self.config.jump_timeout_ms);
+        (!self.jump_prefix.is_empty() && now.saturating_duration_since(last) <= timeout)
+            .then_some(self.jump_prefix.as_str())
+    }
+
+    fn jump_to_prefix(&mut self) {
+        if self.jump_prefix.is_empty() {
+            self.message = None;
+            return;
+        }
+        let found = self
+            .get_current_workspace()
+            .entries
+            .iter()
+            .position(|e| e.name.to_lowercase().starts_with(&self.jump_prefix));
+        match found {
+            Some(index) => {
+                self.message = None;
+                self.select_index(index);
+            }
+            None => self.message = Some(format!("No entry starts with '{}'", self.jump_prefix)),
+        }
+    }
+
     pub fn page_up(&mut self) {
         let workspace = self.workspace_manager.active_workspace_mut();
         workspace.page_up(self.viewport_height);
@@ -194,29 +1148,93 @@ impl App {
                 workspace.selected_index = 0;
                 workspace.scroll_offset = 0;
                 self.refresh_workspace()?;
+            } else if workspace.archive.is_some() {
+                match self.extract_from_archive(&entry.path) {
+                    Ok(extracted) => self.open_file(&extracted),
+                    Err(e) => self.error = Some(format!("Cannot extract {}: {}", entry.name, e)),
+                }
+            } else if is_archive(&entry.path) {
+                match ArchiveListing::open(&entry.path) {
+                    Ok(listing) => {
+                        workspace.archive = Some(listing);
+                        workspace.current_dir = entry.path.clone();
+                        workspace.selected_index = 0;
+                        workspace.scroll_offset = 0;
+                        self.refresh_workspace()?;
+                    }
+                    Err(e) => self.error = Some(format!("Cannot open archive: {}", e)),
+                }
             } else {
-                // Open with default application
-                let _ = that(&entry.path);
-                self.message = Some(format!("Opened: {}", entry.name));
+                self.open_file(&entry.path);
             }
         }
         Ok(())
     }
 
+    /// Whether the active tab is browsing inside an archive
+    pub fn in_archive(&self) -> bool {
+        self.workspace_manager.active_workspace().archive.is_some()
+    }
+
+    /// Archives are browsed read-only: report it and return true when inside one
+    fn refuse_in_archive(&mut self) -> bool {
+        if self.in_archive() {
+            self.error = Some("Archives are read-only (Enter extracts and opens a file)".to_string());
+        }
+        self.in_archive()
+    }
+
+    /// Extract a file of the archive being browsed to a temporary folder
+    fn extract_from_archive(&mut self, path: &Path) -> Result<PathBuf> {
+        let temp_dir = archive_temp_dir(&mut self.archive_temp)?;
+        match &self.workspace_manager.active_workspace().archive {
+            Some(archive) => archive.extract_cached(path, &temp_dir),
+            None => Err(anyhow::anyhow!("Not inside an archive")),
+        }
+    }
+
+    /// Open a file with its configured association, or the default application
+    fn open_file(&mut self, path: &Path) {
+        self.remember_opened(path);
+        if let Some(command) = self.config.resolve_opener(path) {
+            // Configured association: the main loop runs it in the foreground
+            self.message = Some(format!("Opened with: {}", command));
+            self.pending_command = Some(command);
+        } else {
+            // Open with default application
+            let _ = that(path);
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            self.message = Some(format!("Opened: {}", name));
+        }
+    }
+
     pub fn go_back(&mut self) -> Result<()> {
         let workspace = self.workspace_manager.active_workspace_mut();
-        
+        // Leaving an archive selects it in its folder
+        let left_archive = workspace
+            .archive
+            .as_ref()
+            .filter(|archive| archive.root == workspace.current_dir)
+            .map(|archive| archive.root.clone());
+
         if let Some(parent) = workspace.current_dir.parent() {
             workspace.current_dir = parent.to_path_buf();
             workspace.selected_index = 0;
             workspace.scroll_offset = 0;
             self.refresh_workspace()?;
         }
+        if let Some(root) = left_archive {
+            let workspace = self.workspace_manager.active_workspace_mut();
+            if let Some(index) = workspace.entries.iter().position(|e| e.path == root) {
+                workspace.select(index);
+                self.update_preview();
+            }
+        }
         Ok(())
     }
 
     pub fn go_to_path(&mut self, path: &str) -> Result<()> {
-        let path = PathBuf::from(path);
+        let path = crate::fileops::expand_input_path(path, &self.workspace_manager.active_workspace().current_dir);
         if path.exists() {
             let workspace = self.workspace_manager.active_workspace_mut();
             workspace.current_dir = path;
@@ -232,40 +1250,353 @@ impl App {
 
     // ========== File Operations ==========
     pub fn copy_selected(&mut self) -> Result<()> {
-        let workspace = self.workspace_manager.active_workspace_mut();
-        
-        if let Some(entry) = workspace.get_selected_entry().cloned() {
-            // For now, set a message. Full clipboard support would need a clipboard library
-            self.message = Some(format!("Copied: {} (paste with Ctrl+V)", entry.name));
-        }
+        self.yank_selected(TransferKind::Copy);
         Ok(())
     }
 
-    pub fn delete_selected(&mut self) -> Result<()> {
-        let workspace = self.workspace_manager.active_workspace_mut();
-        
-        if let Some(entry) = workspace.get_selected_entry().cloned() {
-            match FileOperation::delete(&entry.path) {
-                Ok(_) => {
-                    self.message = Some(format!("Deleted: {}", entry.name));
-                    self.refresh_workspace()?;
-                }
-                Err(e) => {
-                    self.error = Some(format!("Delete failed: {}", e));
-                }
-            }
-        }
-        Ok(())
+    pub fn cut_selected(&mut self) {
+        self.yank_selected(TransferKind::Move);
     }
 
+    /// Remember the marked entries (or the selection) for the next paste
+    fn yank_selected(&mut self, kind: TransferKind) {
+        if self.refuse_in_archive() {
+            return;
+        }
+        let paths = self.marked_or_selected();
+        if paths.is_empty() {
+            return;
+        }
+        let verb = if kind == TransferKind::Copy { "Copied" } else { "Cut" };
+        self.message = Some(format!("{} {} item(s) (paste with Ctrl+V)", verb, paths.len()));
+        self.yank = Some(Yank { paths, kind });
+    }
+
+    /// Copy or move the yanked entries into the current directory
+    pub fn paste(&mut self) -> Result<()> {
+        self.paste_yanked()?;
+        Ok(())
+    }
+
+    /// `paste`, returning where entries landed; empty while a collision waits
+    /// for an answer
+    fn paste_yanked(&mut self) -> Result<Vec<PathBuf>> {
+        if self.refuse_in_archive() {
+            return Ok(Vec::new());
+        }
+        let yank = match &self.yank {
+            Some(yank) => yank.clone(),
+            None => {
+                self.error = Some("Nothing to paste: copy with c or cut with m first".to_string());
+                return Ok(Vec::new());
+            }
+        };
+        let strategy = CollisionStrategy::parse(&self.config.collision_strategy).unwrap_or(CollisionStrategy::Ask);
+        let dest_dir = self.workspace_manager.active_workspace().current_dir.clone();
+        self.start_transfer(yank.paths, dest_dir, yank.kind, strategy)
+    }
+
+    /// Pick a bookmark to copy or move the marked entries (or the selection) into
+    pub fn open_bookmark_picker(&mut self, kind: TransferKind) {
+        let count = self.marked_or_selected().len();
+        if count == 0 {
+            return;
+        }
+        if self.bookmark_manager.count() == 0 {
+            self.error = Some("No bookmarks yet: add one with b".to_string());
+            return;
+        }
+        self.places_transfer = Some(kind);
+        self.refresh_places();
+        self.places_index = 0;
+        self.mode = AppMode::Places;
+        let verb = if kind == TransferKind::Copy { "Copy" } else { "Move" };
+        self.message = Some(format!("{} {} item(s) to which bookmark? (Enter to pick, ESC to cancel)", verb, count));
+    }
+
+    /// Copy or move the marked entries (or the selection) into a bookmarked directory
+    pub fn transfer_to_bookmark(&mut self, name: &str, kind: TransferKind, strategy: CollisionStrategy) -> Result<()> {
+        let dest_dir = match self.bookmark_manager.get(name) {
+            Some(bookmark) => bookmark.path.clone(),
+            None => return Err(anyhow::anyhow!("Bookmark not found: {}", name)),
+        };
+        if !dest_dir.is_dir() {
+            return Err(anyhow::anyhow!("{} is not available", dest_dir.display()));
+        }
+        let paths = self.marked_or_selected();
+        if paths.is_empty() {
+            return Err(anyhow::anyhow!("Nothing selected"));
+        }
+        self.start_transfer(paths, dest_dir, kind, strategy)?;
+        Ok(())
+    }
+
+    fn start_transfer(
+        &mut self,
+        paths: Vec<PathBuf>,
+        dest_dir: PathBuf,
+        kind: TransferKind,
+        strategy: CollisionStrategy,
+    ) -> Result<Vec<PathBuf>> {
+        self.paste_batch = Some(PasteBatch {
+            kind,
+            dest_dir,
+            queue: paths.into(),
+            strategy,
+            conflict: None,
+            pasted: Vec::new(),
+            skipped: 0,
+            failed: Vec::new(),
+        });
+        self.run_paste_batch()
+    }
+
+    /// Work through the paste queue until it is empty or a conflict needs an
+    /// answer; returns where entries landed once the batch is done
+    fn run_paste_batch(&mut self) -> Result<Vec<PathBuf>> {
+        while let Some(batch) = self.paste_batch.as_mut() {
+            let strategy = batch.strategy;
+            let src = match batch.queue.pop_front() {
+                Some(src) => src,
+                None => break,
+            };
+            if !self.paste_one(src, strategy) {
+                return Ok(Vec::new());
+            }
+        }
+        self.finish_paste()
+    }
+
+    /// Copy or move one entry; false when it collided and is waiting for an answer
+    fn paste_one(&mut self, src: PathBuf, strategy: CollisionStrategy) -> bool {
+        let follow_symlinks = self.config.follow_symlinks;
+        let batch = match self.paste_batch.as_mut() {
+            Some(batch) => batch,
+            None => return true,
+        };
+        match FileOperation::transfer(&src, &batch.dest_dir, batch.kind, strategy, follow_symlinks) {
+            Ok(TransferOutcome::Done(dest)) => {
+                self.api_plugin_manager.call_file_created(&dest).log_err("run on_file_created hooks");
+                batch.pasted.push(dest);
+            }
+            Ok(TransferOutcome::Skipped) => batch.skipped += 1,
+            Ok(TransferOutcome::Conflict(existing)) => {
+                batch.conflict = Some((src, existing));
+                self.mode = AppMode::ConfirmCollision;
+                return false;
+            }
+            Err(e) => {
+                tracing::warn!("Paste of {} failed: {:#}", src.display(), e);
+                batch.failed.push((src, e.to_string()));
+            }
+        }
+        true
+    }
+
+    /// Answer the current conflict, for this entry only or for the rest of the batch too
+    pub fn resolve_collision(&mut self, choice: CollisionStrategy, apply_to_all: bool) -> Result<()> {
+        let batch = match self.paste_batch.as_mut() {
+            Some(batch) => batch,
+            None => return Ok(()),
+        };
+        let (src, _) = match batch.conflict.take() {
+            Some(conflict) => conflict,
+            None => return Ok(()),
+        };
+        if apply_to_all {
+            batch.strategy = choice;
+        }
+        self.mode = AppMode::Normal;
+        if self.paste_one(src, choice) {
+            self.run_paste_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Stop the paste, leaving anything not yet copied or moved where it is
+    pub fn cancel_paste(&mut self) -> Result<()> {
+        if let Some(batch) = self.paste_batch.as_mut() {
+            batch.skipped += batch.queue.len() + batch.conflict.take().map_or(0, |_| 1);
+            batch.queue.clear();
+        }
+        self.mode = AppMode::Normal;
+        self.finish_paste()?;
+        Ok(())
+    }
+
+    /// Report how the paste went and show the result; returns where entries landed
+    fn finish_paste(&mut self) -> Result<Vec<PathBuf>> {
+        let batch = match self.paste_batch.take() {
+            Some(batch) => batch,
+            None => return Ok(Vec::new()),
+        };
+        // Moved entries aren't where they were yanked from any more
+        if batch.kind == TransferKind::Move {
+            self.yank = None;
+        }
+        let verb = if batch.kind == TransferKind::Copy { "Copied" } else { "Moved" };
+        let mut summary = format!("{} {}", verb, batch.pasted.len());
+        if batch.dest_dir != self.workspace_manager.active_workspace().current_dir {
+            summary.push_str(&format!(" to {}", batch.dest_dir.display()));
+        }
+        if batch.skipped > 0 {
+            summary.push_str(&format!(", skipped {}", batch.skipped));
+        }
+        if batch.failed.is_empty() {
+            self.message = Some(summary);
+        } else {
+            self.error = Some(format!("{}, {} failed: {}", summary, batch.failed.len(), batch.failed[0].1));
+        }
+        self.refresh_workspace()?;
+        Ok(batch.pasted)
+    }
+
+    pub fn delete_selected(&mut self) -> Result<()> {
+        self.delete_selected_entry()?;
+        Ok(())
+    }
+
+    /// `delete_selected`, returning the path when it went
+    fn delete_selected_entry(&mut self) -> Result<Option<PathBuf>> {
+        if self.refuse_in_archive() {
+            return Ok(None);
+        }
+        let Some(entry) = self.workspace_manager.active_workspace().get_selected_entry().cloned() else {
+            return Ok(None);
+        };
+        Ok(self.delete_path(&entry.path)?.then_some(entry.path))
+    }
+
+    /// Delete `path`, to the trash when `safe_delete` is on; false when it
+    /// couldn't be deleted, with the reason in `error`
+    fn delete_path(&mut self, path: &Path) -> Result<bool> {
+        if self.config.safe_delete {
+            return self.trash_entry(path);
+        }
+        match FileOperation::delete(path) {
+            Ok(()) => {
+                self.api_plugin_manager.call_file_deleted(&path.to_path_buf()).log_err("run on_file_deleted hooks");
+                self.message = Some(format!("Deleted: {}", path.file_name().unwrap_or_default().to_string_lossy()));
+                self.refresh_workspace()?;
+                Ok(true)
+            }
+            Err(e) => {
+                self.error = Some(format!("Delete failed: {}", e));
+                Ok(false)
+            }
+        }
+    }
+
+    /// Move a path to the trash and open the undo window; false when it
+    /// couldn't be moved, with the reason in `error`
+    fn trash_entry(&mut self, path: &Path) -> Result<bool> {
+        let trash = match &self.trash {
+            Some(trash) => trash,
+            None => {
+                self.error = Some("Delete failed: no trash directory available".to_string());
+                return Ok(false);
+            }
+        };
+
+        match trash.move_to_trash(path) {
+            Ok(item) => {
+                self.api_plugin_manager.call_file_deleted(&item.original).log_err("run on_file_deleted hooks");
+                self.pending_undo = Some(PendingUndo {
+                    item,
+                    expires: Instant::now() + Duration::from_secs(self.config.undo_window_secs),
+                });
+                self.refresh_workspace()?;
+                Ok(true)
+            }
+            Err(e) => {
+                self.error = Some(format!("Delete failed: {}", e));
+                Ok(false)
+            }
+        }
+    }
+
+    /// Restore the last safe delete if its undo window is still open
+    pub fn undo_delete(&mut self) -> Result<()> {
+        self.expire_undo();
+        let pending = match self.pending_undo.take() {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+
+        let restored = self.trash.as_ref().map(|trash| trash.restore(&pending.item));
+        match restored {
+            Some(Ok(())) => {
+                self.api_plugin_manager
+                    .call_file_created(&pending.item.original)
+                    .log_err("run on_file_created hooks");
+                self.message = Some(format!("Restored: {}", pending.item.name()));
+                self.refresh_workspace()?;
+
+                let workspace = self.workspace_manager.active_workspace_mut();
+                if let Some(index) = workspace.entries.iter().position(|e| e.path == pending.item.original) {
+                    workspace.select(index);
+                }
+                self.update_preview();
+            }
+            Some(Err(e)) => self.error = Some(format!("Undo failed: {}", e)),
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Close the undo window once it has run out
+    pub fn expire_undo(&mut self) {
+        if let Some(pending) = &self.pending_undo {
+            if Instant::now() >= pending.expires {
+                self.pending_undo = None;
+            }
+        }
+    }
+
+    // ========== Idle ==========
+    /// Something on screen is still changing: a job, a decode or an undo countdown
+    pub fn is_busy(&mut self) -> bool {
+        !self.jobs.is_idle()
+            || self.hash_task.is_some()
+            || self.collect_task.is_some()
+            || self.thumbnails.is_busy()
+            || self.pending_undo.is_some()
+            || self.quit_when_idle
+    }
+
+    /// How long the main loop should wait for input before its next pass
+    pub fn poll_timeout(&mut self) -> Duration {
+        let base = Duration::from_millis(self.config.poll_interval_ms);
+        let busy = self.is_busy();
+        self.idle.poll_timeout(base, busy, Instant::now())
+    }
+
+    pub fn note_input(&mut self) {
+        self.idle.note_input(Instant::now());
+    }
+
+    /// Run plugin `on_idle` hooks once input has been quiet for a while
+    pub fn run_idle_hooks(&mut self) {
+        if self.idle.take_idle(Instant::now()) {
+            self.api_plugin_manager.call_idle().log_err("run on_idle hooks");
+        }
+    }
+
+    /// Rename the selected entry; taking the name of an existing file asks
+    /// first unless `confirm_rename_overwrite` is off
     pub fn rename_selected(&mut self, new_name: &str) -> Result<()> {
+        if self.refuse_in_archive() {
+            return Ok(());
+        }
         let workspace = self.workspace_manager.active_workspace_mut();
         
         if let Some(entry) = workspace.get_selected_entry().cloned() {
-            match FileOperation::rename(&entry.path, new_name) {
-                Ok(_) => {
-                    self.message = Some(format!("Renamed to: {}", new_name));
-                    self.refresh_workspace()?;
+            let overwrite = !self.config.confirm_rename_overwrite;
+            match self.rename_entry(&entry.path, new_name, overwrite) {
+                Ok(_) => {}
+                Err(e) if is_already_exists(&e) => {
+                    self.pending_rename = Some((entry.path, new_name.to_string()));
+                    self.mode = AppMode::ConfirmOverwrite;
                 }
                 Err(e) => {
                     self.error = Some(format!("Rename failed: {}", e));
@@ -275,12 +1606,119 @@ impl App {
         Ok(())
     }
 
+    /// Rename `src` within its folder, replacing an existing entry only with `overwrite`
+    pub fn rename_entry(&mut self, src: &Path, new_name: &str, overwrite: bool) -> Result<PathBuf> {
+        let new_path = FileOperation::rename(src, new_name, overwrite)?;
+        self.api_plugin_manager
+            .call_file_renamed(&src.to_path_buf(), &new_path)
+            .log_err("run on_file_renamed hooks");
+        self.message = Some(format!("Renamed to: {}", new_name));
+        self.refresh_workspace()?;
+        Ok(new_path)
+    }
+
+    /// Copy the selected entry next to itself as "name copy.ext", without asking
+    pub fn duplicate_selected(&mut self) -> Result<()> {
+        self.duplicate_selected_entry();
+        Ok(())
+    }
+
+    /// `duplicate_selected`, returning the copy's path when one was made
+    fn duplicate_selected_entry(&mut self) -> Option<PathBuf> {
+        if self.refuse_in_archive() {
+            return None;
+        }
+        let entry = self.workspace_manager.active_workspace().get_selected_entry().cloned()?;
+        match self.duplicate(&entry.path) {
+            Ok(dest) => Some(dest),
+            Err(e) => {
+                self.error = Some(format!("Duplicate failed: {}", e));
+                None
+            }
+        }
+    }
+
+    /// Ask where to move the selected entry, starting from the current directory
+    pub fn start_move(&mut self) {
+        if self.refuse_in_archive() || self.workspace_manager.active_workspace().get_selected_entry().is_none() {
+            return;
+        }
+        let dir = self.workspace_manager.active_workspace().current_dir.clone();
+        self.input_buffer = format!("{}{}", dir.display(), std::path::MAIN_SEPARATOR);
+        self.mode = AppMode::Input(InputMode::MoveTo);
+    }
+
+    /// Move the selected entry to the typed destination, reporting any failure
+    pub fn move_selected(&mut self, dest: &str) -> Result<()> {
+        if self.refuse_in_archive() || dest.trim().is_empty() {
+            return Ok(());
+        }
+        let Some(entry) = self.workspace_manager.active_workspace().get_selected_entry().cloned() else {
+            return Ok(());
+        };
+        if let Err(e) = self.move_entry(&entry.path, dest) {
+            self.error = Some(format!("Move failed: {}", e));
+        }
+        Ok(())
+    }
+
+    /// Move `src` into the folder `dest`, or to `dest` itself when it is not
+    /// one, creating missing parents; relative paths start from the current
+    /// directory. The moved entry is selected when it is still in view.
+    /// Returns where it went, or None when `collision_strategy` skipped it.
+    pub fn move_entry(&mut self, src: &Path, dest: &str) -> Result<Option<PathBuf>> {
+        let dest = crate::fileops::expand_input_path(dest.trim(), &self.workspace_manager.active_workspace().current_dir);
+        let strategy = CollisionStrategy::parse(&self.config.collision_strategy).unwrap_or(CollisionStrategy::Ask);
+        let moved = match FileOperation::move_to(src, &dest, strategy)? {
+            TransferOutcome::Done(moved) => moved,
+            TransferOutcome::Skipped => {
+                self.message = Some(format!("Skipped: {} is already taken", dest.display()));
+                return Ok(None);
+            }
+            TransferOutcome::Conflict(existing) => {
+                return Err(anyhow::anyhow!("{} already exists", existing.display()));
+            }
+        };
+        self.api_plugin_manager
+            .call_file_renamed(&src.to_path_buf(), &moved)
+            .log_err("run on_file_renamed hooks");
+        self.message = Some(format!("Moved to: {}", moved.display()));
+        self.refresh_workspace()?;
+        let workspace = self.workspace_manager.active_workspace_mut();
+        if let Some(index) = workspace.entries.iter().position(|e| e.path == moved) {
+            workspace.select(index);
+            self.update_preview();
+        }
+        Ok(Some(moved))
+    }
+
+    /// Copy `path` (recursively, for folders) to the first free "copy" name
+    /// beside it and select the copy; returns where it went
+    pub fn duplicate(&mut self, path: &Path) -> Result<PathBuf> {
+        let dest = crate::fileops::duplicate_path(path);
+        FileOperation::copy(path, &dest, self.config.follow_symlinks)?;
+        self.api_plugin_manager.call_file_created(&dest).log_err("run on_file_created hooks");
+        let name = dest.file_name().unwrap_or_default().to_string_lossy().to_string();
+        self.message = Some(format!("Duplicated as: {}", name));
+        self.refresh_workspace()?;
+        let workspace = self.workspace_manager.active_workspace_mut();
+        if let Some(index) = workspace.entries.iter().position(|e| e.path == dest) {
+            workspace.select(index);
+            self.update_preview();
+        }
+        Ok(dest)
+    }
+
     pub fn create_file(&mut self, name: &str) -> Result<()> {
+        if self.refuse_in_archive() {
+            return Ok(());
+        }
         let workspace = self.workspace_manager.active_workspace();
         let file_path = workspace.current_dir.join(name);
 
         match FileOperation::create_file(&file_path) {
             Ok(_) => {
+                self.api_plugin_manager.call_file_created(&file_path).log_err("run on_file_created hooks");
                 self.message = Some(format!("Created file: {}", name));
                 self.refresh_workspace()?;
             }
@@ -292,11 +1730,15 @@ impl App {
     }
 
     pub fn create_directory(&mut self, name: &str) -> Result<()> {
+        if self.refuse_in_archive() {
+            return Ok(());
+        }
         let workspace = self.workspace_manager.active_workspace();
         let dir_path = workspace.current_dir.join(name);
 
         match FileOperation::create_directory(&dir_path) {
             Ok(_) => {
+                self.api_plugin_manager.call_file_created(&dir_path).log_err("run on_file_created hooks");
                 self.message = Some(format!("Created directory: {}", name));
                 self.refresh_workspace()?;
             }
@@ -308,374 +1750,4574 @@ impl App {
     }
 
     // ========== Preview ==========
-    pub fn update_preview(&mut self) {
-        let workspace = self.workspace_manager.active_workspace_mut();
-        
-        if let Some(entry) = workspace.get_selected_entry().cloned() {
-            workspace.preview = generate_preview(&entry.path, 200);
-        } else {
-            workspace.preview = PreviewContent {
-                lines: vec![Line::from("No file selected")],
-                is_binary: false,
-                preview_type: crate::preview::PreviewType::Text,
-            };
-        }
+    // ========== Gallery View ==========
+    pub fn toggle_gallery_view(&mut self) {
+        self.gallery_view = !self.gallery_view;
+        self.message = Some(format!(
+            "{} view",
+            if self.gallery_view { "Gallery" } else { "List" }
+        ));
     }
 
-    pub fn refresh_workspace(&mut self) -> Result<()> {
+    /// Move the selection by `delta` tiles (rows move by the column count)
+    pub fn gallery_move(&mut self, delta: isize, count: usize) {
         let workspace = self.workspace_manager.active_workspace_mut();
-        let current_dir = workspace.current_dir.clone();
-        let show_hidden = workspace.show_hidden;
+        let start = workspace.selected_index as isize;
+        let target = (1..=count as isize)
+            .map(|steps| start + delta * steps)
+            .take_while(|target| (0..workspace.entries.len() as isize).contains(target))
+            .last();
+        let Some(target) = target else {
+            return;
+        };
+        workspace.select(target as usize);
+        self.update_preview();
+    }
 
-        workspace.entries = list_directory(&current_dir, show_hidden)?;
+    /// Pick up thumbnails finished by the decode workers
+    pub fn poll_thumbnails(&mut self) -> bool {
+        self.thumbnails.poll()
+    }
 
-        if workspace.selected_index >= workspace.entries.len() && !workspace.entries.is_empty() {
-            workspace.selected_index = workspace.entries.len() - 1;
-        }
+    // ========== Checksums ==========
+    /// Cached digests for a file, if they match its current mtime
+    pub fn cached_hashes(&self, path: &Path) -> Option<&FileHashes> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        self.file_hashes
+            .get(path)
+            .filter(|(cached, _)| *cached == modified)
+            .map(|(_, hashes)| hashes)
+    }
 
-        self.update_preview();
-        Ok(())
+    /// Hash a file right away, reusing the cache (used by the Python API)
+    #[allow(dead_code)]
+    pub fn hash_file(&mut self, path: &Path, algo: HashAlgo) -> Result<String> {
+        let lookup = |hashes: &FileHashes| hashes.iter().find(|(a, _)| *a == algo).map(|(_, hex)| hex.clone());
+        if let Some(hex) = self.cached_hashes(path).and_then(lookup) {
+            return Ok(hex);
+        }
+        let modified = std::fs::metadata(path)?.modified()?;
+        let hashes = hash_file_with(path, &HashAlgo::ALL, |_| true)?;
+        let hex = lookup(&hashes).unwrap_or_default();
+        self.file_hashes.insert(path.to_path_buf(), (modified, hashes));
+        Ok(hex)
     }
 
-    pub fn toggle_hidden(&mut self) -> Result<()> {
-        let show_hidden = {
-            let workspace = self.workspace_manager.active_workspace_mut();
-            workspace.show_hidden = !workspace.show_hidden;
-            workspace.show_hidden
+    /// Compute MD5, SHA-256 and BLAKE3 for the selected file in the background
+    pub fn hash_selected(&mut self) {
+        let entry = match self.workspace_manager.active_workspace().get_selected_entry() {
+            Some(entry) if !entry.is_dir => entry.clone(),
+            Some(_) => {
+                self.error = Some("Select a file to hash".to_string());
+                return;
+            }
+            None => return,
+        };
+        if self.cached_hashes(&entry.path).is_some() {
+            self.message = Some(format!("Hashes for {} (Y to copy)", entry.name));
+            return;
+        }
+        if self.hash_task.as_ref().is_some_and(|task| task.path == entry.path) {
+            return;
+        }
+        let metadata = match std::fs::metadata(&entry.path).and_then(|m| Ok((m.modified()?, m.len()))) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                self.error = Some(format!("Hash failed: {}", e));
+                return;
+            }
         };
-        self.refresh_workspace()?;
-        
-        self.message = Some(format!(
-            "Hidden files: {}",
-            if show_hidden { "shown" } else { "hidden" }
-        ));
-        Ok(())
-    }
 
-    // ========== Search ==========
-    pub fn start_search(&mut self) {
-        self.mode = AppMode::Search;
-        self.search_query.clear();
-        self.message = Some("Search mode: Type to search (ESC to cancel, Enter to navigate)".to_string());
+        // Only one file is hashed at a time
+        if let Some(previous) = self.hash_task.take() {
+            previous.cancel.store(true, Ordering::Release);
+        }
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = channel();
+        let job = {
+            let path = entry.path.clone();
+            let cancel = Arc::clone(&cancel);
+            self.jobs.submit(format!("Hashing {}", entry.name), move |job| {
+                let size = metadata.1;
+                let result = hash_file_with(&path, &HashAlgo::ALL, |read| {
+                    job.set_progress(read, size);
+                    !cancel.load(Ordering::Acquire) && !job.is_cancelled()
+                });
+                let _ = tx.send(result);
+            })
+        };
+
+        self.hash_task = Some(HashTask {
+            path: entry.path,
+            modified: metadata.0,
+            size: metadata.1,
+            job,
+            cancel,
+            results: rx,
+        });
+        self.message = Some(format!("Hashing {}...", entry.name));
     }
 
-    pub fn cancel_search(&mut self) {
-        self.mode = AppMode::Normal;
-        self.search_query.clear();
-        self.search_engine.clear();
-        self.message = None;
+    /// Pick up digests finished by the hashing worker
+    pub fn poll_hashes(&mut self) -> bool {
+        let task = match &self.hash_task {
+            Some(task) => task,
+            None => return false,
+        };
+        let result = match task.results.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return false,
+            // Cancelled while still queued
+            Err(TryRecvError::Disconnected) => Ok(Vec::new()),
+        };
+
+        let task = self.hash_task.take().expect("hash task checked above");
+        let name = task.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        match result {
+            // Cancelled
+            Ok(hashes) if hashes.is_empty() => {}
+            Ok(hashes) => {
+                self.file_hashes.insert(task.path, (task.modified, hashes));
+                self.message = Some(format!("Hashed {} (Y to copy)", name));
+            }
+            Err(e) => self.error = Some(format!("Hash failed for {}: {}", name, e)),
+        }
+        true
     }
 
-    pub fn perform_search(&mut self) {
-        if !self.search_query.is_empty() {
-            let workspace = self.workspace_manager.active_workspace();
-            self.search_engine.search_current_dir(
-                &workspace.current_dir,
-                &self.search_query,
-                self.config.max_search_results,
-            );
-            self.message = Some(format!("Found {} results", self.search_engine.results.len()));
-        } else {
-            self.search_engine.clear();
+    /// Copy the selected file's `hash_copy_algorithm` digest to the clipboard
+    pub fn copy_selected_hash(&mut self) {
+        let algo = HashAlgo::parse(&self.config.hash_copy_algorithm).unwrap_or(HashAlgo::Sha256);
+        let path = match self.workspace_manager.active_workspace().get_selected_entry() {
+            Some(entry) => entry.path.clone(),
+            None => return,
+        };
+        let hex = self
+            .cached_hashes(&path)
+            .and_then(|hashes| hashes.iter().find(|(a, _)| *a == algo))
+            .map(|(_, hex)| hex.clone());
+        match hex {
+            Some(hex) => match self.copy_to_clipboard(&hex) {
+                Ok(()) => self.message = Some(format!("Copied {}: {}", algo.label(), hex)),
+                Err(e) => self.error = Some(format!("Clipboard unavailable: {}", e)),
+            },
+            None => self.error = Some("No hash yet: press H to compute it".to_string()),
         }
     }
 
-    pub fn add_search_char(&mut self, c: char) {
-        self.search_query.push(c);
-        self.perform_search();
+    /// The selected entry's path from its repository root, or from the current directory outside one
+    pub fn relative_path_from_repo(&self, path: &Path) -> String {
+        let current = &self.workspace_manager.active_workspace().current_dir;
+        crate::git::relative_path_from_repo(path, current)
     }
 
-    pub fn remove_search_char(&mut self) {
-        self.search_query.pop();
-        self.perform_search();
+    /// Copy the selected entry's path relative to its repository root to the clipboard
+    pub fn copy_repo_relative_path(&mut self) {
+        let path = match self.workspace_manager.active_workspace().get_selected_entry() {
+            Some(entry) => entry.path.clone(),
+            None => return,
+        };
+        let relative = self.relative_path_from_repo(&path);
+        match self.copy_to_clipboard(&relative) {
+            Ok(()) => self.message = Some(format!("Copied path: {}", relative)),
+            Err(e) => self.error = Some(format!("Clipboard unavailable: {}", e)),
+        }
     }
 
-    pub fn navigate_to_search_result(&mut self, index: usize) -> Result<()> {
-        if let Some(result) = self.search_engine.results.get(index) {
-            let workspace = self.workspace_manager.active_workspace_mut();
-            
-            if result.is_dir {
-                workspace.current_dir = result.path.clone();
-            } else if let Some(parent) = result.path.parent() {
-                workspace.current_dir = parent.to_path_buf();
+    /// Copy the selected text file's contents to the clipboard
+    pub fn copy_selected_contents(&mut self) {
+        let path = match self.workspace_manager.active_workspace().get_selected_entry() {
+            Some(entry) if !entry.is_dir => entry.path.clone(),
+            _ => {
+                self.error = Some("Select a text file to copy".to_string());
+                return;
             }
-
-            workspace.selected_index = 0;
-            workspace.scroll_offset = 0;
-            self.refresh_workspace()?;
-            self.search_history.add(self.search_query.clone());
-            self.cancel_search();
+        };
+        if let Err(e) = self.copy_file_contents(&path) {
+            self.error = Some(format!("Can't copy contents: {}", e));
         }
-        Ok(())
     }
 
-    // ========== Workspaces/Tabs ==========
-    pub fn new_workspace(&mut self) -> Result<()> {
-        let workspace = self.workspace_manager.active_workspace();
-        let new_path = workspace.current_dir.clone();
-        
-        self.workspace_manager.create_workspace(new_path);
-        self.message = Some("Created new workspace".to_string());
+    /// Put the text of `path` on the clipboard, up to `COPY_CONTENTS_LIMIT`; binary files are refused
+    pub fn copy_file_contents(&mut self, path: &Path) -> Result<()> {
+        let text = crate::fileops::read_text_file(path, COPY_CONTENTS_LIMIT)?;
+        self.copy_to_clipboard(&text).map_err(|e| anyhow::anyhow!("Clipboard unavailable: {}", e))?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.message = Some(format!("Copied {} lines of {}", text.lines().count(), name));
         Ok(())
     }
 
-    pub fn close_workspace(&mut self) -> Result<()> {
-        let id = self.workspace_manager.active_id();
-        if self.workspace_manager.close_workspace(id) {
-            self.message = Some("Closed workspace".to_string());
-        } else {
-            self.error = Some("Cannot close last workspace".to_string());
+    fn copy_to_clipboard(&mut self, text: &str) -> Result<()> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(Box::new(arboard::Clipboard::new()?));
+        }
+        if let Some(clipboard) = self.clipboard.as_mut() {
+            clipboard.set_text(text)?;
         }
         Ok(())
     }
 
-    pub fn next_workspace(&mut self) {
-        self.workspace_manager.next_workspace();
-        self.message = Some("Switched to next workspace".to_string());
+    /// Syntax overrides, theme and per-extension preview kinds for previews
+    pub fn highlight_options(&self) -> HighlightOptions {
+        HighlightOptions {
+            syntax_overrides: self.config.syntax_overrides.clone(),
+            theme: self.config.highlight_theme.clone(),
+            theme_dir: Some(self.dirs.highlight_theme_dir()),
+            icons: self.theme.icons,
+            preview_overrides: self
+                .config
+                .preview_overrides
+                .iter()
+                .filter_map(|(ext, mode)| Some((ext.clone(), crate::preview::preview_type_for_mode(mode)?)))
+                .collect(),
+            date_format: self.config.date_format.clone(),
+            render_mode: None,
+        }
     }
 
-    pub fn prev_workspace(&mut self) {
-        self.workspace_manager.prev_workspace();
-        self.message = Some("Switched to previous workspace".to_string());
+    /// `time` in the configured `date_format`
+    pub fn format_time(&self, time: std::time::SystemTime) -> String {
+        crate::path_display::format_time(time, &self.config.date_format, std::time::SystemTime::now())
     }
 
-    pub fn rename_workspace(&mut self, name: String) {
+    /// Switch code previews to the next highlight theme and remember the choice
+    pub fn next_highlight_theme(&mut self) {
+        let themes = crate::preview::highlight_themes(Some(&self.dirs.highlight_theme_dir()));
+        let next = themes
+            .iter()
+            .position(|t| *t == self.config.highlight_theme)
+            .map_or(0, |i| (i + 1) % themes.len());
+        let Some(theme) = themes.get(next) else {
+            return;
+        };
+        self.config.highlight_theme = theme.clone();
+        self.user_settings.highlight_theme = theme.clone();
+        self.save_user_settings_quietly();
+        self.update_preview();
+        self.message = Some(format!("Highlight theme: {}", theme));
+    }
+
+    pub fn update_preview(&mut self) {
+        let max_lines = self.config.preview_max_lines;
+        let mut options = self.highlight_options();
+        let workspace = self.workspace_manager.active_workspace_mut();
+        options.render_mode = workspace.get_selected_entry().and_then(|entry| workspace.render_mode_for(&entry.path));
+        // Folders inside archives aren't on disk, so their selections aren't kept
+        if let Some(entry) = workspace.get_selected_entry().filter(|_| workspace.archive.is_none()) {
+            self.selection_memory.remember(&workspace.current_dir, &entry.name);
+        }
+        
+        if let (Some(entry), Some(archive)) = (workspace.get_selected_entry(), &workspace.archive) {
+            workspace.preview = preview_archive_entry(archive, entry, &mut self.archive_temp, max_lines, &options);
+        } else if let Some(entry) = workspace.get_selected_entry().cloned() {
+            workspace.preview = generate_preview_with(&entry.path, max_lines, &options);
+        } else {
+            let note = workspace.empty.as_ref().map_or("No file selected".to_string(), EmptyListing::placeholder);
+            workspace.preview = PreviewContent {
+                lines: vec![Line::from(note)],
+                is_binary: false,
+                preview_type: crate::preview::PreviewType::Text,
+            };
+        }
+    }
+
+    pub fn refresh_workspace(&mut self) -> Result<()> {
+        self.leave_missing_dir();
+        let ignore = self.active_ignore_patterns();
+        let sort = self.sort_mode();
+        let workspace = self.workspace_manager.active_workspace_mut();
+        let current_dir = workspace.current_dir.clone();
+        let show_hidden = workspace.show_hidden;
+        // Navigating out of an archive, by any route, stops browsing it
+        if workspace.archive.as_ref().is_some_and(|archive| !archive.contains(&current_dir)) {
+            workspace.archive = None;
+        }
+        let in_archive = workspace.archive.is_some();
+
+        // A directory that can't be read lists as empty, saying why, rather than
+        // keeping the previous directory's entries
+        let listing = match &workspace.archive {
+            Some(archive) if current_dir == archive.root || archive.is_dir(&current_dir) => {
+                Ok(archive.children(&current_dir, show_hidden))
+            }
+            Some(archive) => Err(anyhow::anyhow!("no such folder in {}", archive.root.display())),
+            None => list_directory_with(&current_dir, show_hidden, &ignore, &self.hidden_patterns),
+        };
+        let mut failed = None;
+        workspace.entries = match listing {
+            Ok(entries) => entries,
+            Err(e) => {
+                failed = Some(e.to_string());
+                Vec::new()
+            }
+        };
+        let has_hidden = match &workspace.archive {
+            Some(archive) => !archive.children(&current_dir, true).is_empty(),
+            None => std::fs::read_dir(&current_dir).is_ok_and(|mut dir| dir.next().is_some()),
+        };
+        workspace.empty = match failed {
+            Some(reason) => Some(EmptyListing::Failed(reason)),
+            None if !workspace.entries.is_empty() => None,
+            None if has_hidden => Some(EmptyListing::AllHidden),
+            None => Some(EmptyListing::Empty),
+        };
+        if let Some(EmptyListing::Failed(reason)) = &workspace.empty {
+            self.error = Some(format!("Cannot list {}: {}", current_dir.display(), reason));
+        }
+        if sort != SortMode::Name {
+            sort.sort(&mut workspace.entries);
+        }
+        if let Some(order) = self.user_settings.custom_orders.get(&current_dir) {
+            apply_custom_order(&mut workspace.entries, order);
+        }
+        if self.config.show_dir_counts {
+            self.dir_counts.fill(&mut workspace.entries);
+        }
+        if !in_archive {
+            self.mimes.fill(&mut workspace.entries);
+        }
+        self.git_statuses = if self.config.git_status_column && self.config.enable_git_integration && !in_archive {
+            crate::git::file_statuses(&current_dir)
+        } else {
+            HashMap::new()
+        };
+
+        if workspace.selected_index >= workspace.entries.len() {
+            workspace.selected_index = workspace.entries.len().saturating_sub(1);
+            workspace.scroll_offset = workspace.scroll_offset.min(workspace.selected_index);
+        }
+        // Coming back to a directory selects what was selected when it was left
+        let moved = workspace.listed_dir.as_ref() != Some(&current_dir);
+        if moved && workspace.selected_index == 0 && !in_archive {
+            let remembered = self.selection_memory.get(&current_dir);
+            if let Some(index) = remembered.and_then(|name| workspace.entries.iter().position(|e| e.name == name)) {
+                workspace.select(index);
+            }
+        }
+
+        let previous = workspace.listed_dir.replace(current_dir.clone());
+        workspace.listed_modified = if in_archive {
+            None
+        } else {
+            std::fs::metadata(&current_dir).and_then(|m| m.modified()).ok()
+        };
+        self.retitle_tabs();
+        self.update_preview();
+        // Folders inside archives aren't on disk, so they aren't watched or remembered
+        if in_archive {
+            self.dir_watcher = None;
+            return Ok(());
+        }
+        push_recent_dir(&mut self.user_settings.recent_directories, &current_dir);
+        self.watch_current_dir();
+        // Refreshing in place (or the first listing) isn't a move
+        if let Some(old) = previous.filter(|old| *old != current_dir) {
+            self.directory_changed(&old, &current_dir);
+        }
+        Ok(())
+    }
+
+    /// Whether the active tab's directory has gone, say deleted by another
+    /// program, or it was a stale bookmark or tab
+    fn current_dir_missing(&self) -> bool {
+        let workspace = self.workspace_manager.active_workspace();
+        workspace.archive.is_none() && !workspace.current_dir.is_dir()
+    }
+
+    /// Move the active tab up to the nearest folder that still exists when its
+    /// own is gone, saying so
+    fn leave_missing_dir(&mut self) {
+        if !self.current_dir_missing() {
+            return;
+        }
+        let workspace = self.workspace_manager.active_workspace_mut();
+        let missing = workspace.current_dir.clone();
+        let Some(existing) = missing.ancestors().skip(1).find(|dir| dir.is_dir()) else {
+            return;
+        };
+        workspace.current_dir = existing.to_path_buf();
+        workspace.selected_index = 0;
+        workspace.scroll_offset = 0;
+        self.error = Some(if missing.exists() {
+            format!("{} is not a folder; showing {}", missing.display(), existing.display())
+        } else {
+            format!("{} no longer exists; moved up to {}", missing.display(), existing.display())
+        });
+    }
+
+    /// Call `listener` with the old and new directory whenever the active tab moves
+    #[allow(dead_code)]
+    pub fn on_directory_changed(&mut self, listener: DirListener) {
+        self.dir_listeners.push(listener);
+    }
+
+    fn directory_changed(&mut self, old: &Path, new: &Path) {
+        let (old, new) = (old.to_path_buf(), new.to_path_buf());
+        self.api_plugin_manager
+            .call_directory_changed(&old, &new)
+            .log_err("run on_directory_changed hooks");
+        for listener in &self.dir_listeners {
+            listener(&old, &new);
+        }
+        crate::persistence::record_dir_visit(&mut self.user_settings.dir_visits, &new, chrono::Utc::now(), DIR_VISITS_MAX);
+    }
+
+    // ========== Sidebar ==========
+    /// Home, root, bookmarks and recent directories, numbered for quick jumps
+    pub fn sidebar_items(&self) -> Vec<SidebarItem> {
+        let current_dir = &self.workspace_manager.active_workspace().current_dir;
+        let root = current_dir.ancestors().last().unwrap_or(Path::new("/"));
+        let bookmarks: Vec<(String, PathBuf)> = self
+            .bookmark_manager
+            .list()
+            .into_iter()
+            .map(|b| (b.name.clone(), b.path.clone()))
+            .collect();
+        // The first entry is where we are now, which isn't worth a shortcut
+        let recent = self.user_settings.recent_directories.get(1..).unwrap_or_default();
+        sidebar_items(
+            dirs::home_dir().as_deref(),
+            root,
+            &bookmarks,
+            recent,
+            self.config.sidebar_recent_dirs,
+        )
+    }
+
+    /// `path` as configured with `path_display`, middle-truncated to `width` characters
+    pub fn display_path(&self, path: &Path, width: usize) -> String {
+        use crate::path_display::{configured_root, format_path, truncate_middle, PathStyle};
+
+        let style = PathStyle::parse(&self.config.path_display).unwrap_or(PathStyle::Absolute);
+        let root = configured_root(&self.config.path_display_root);
+        let text = format_path(path, style, dirs::home_dir().as_deref(), root.as_deref());
+        truncate_middle(&text, width)
+    }
+
+    /// The automatic title for a tab in `dir`, per `tab_title`
+    pub fn tab_title(&self, dir: &Path) -> String {
+        use crate::path_display::{configured_root, PathStyle, TabTitle};
+
+        let style = PathStyle::parse(&self.config.path_display).unwrap_or(PathStyle::Absolute);
+        let format = TabTitle::parse(&self.config.tab_title).unwrap_or(TabTitle::Path(None));
+        let root = configured_root(&self.config.path_display_root);
+        format.title(dir, style, dirs::home_dir().as_deref(), root.as_deref())
+    }
+
+    /// Recompute the titles of tabs the user hasn't renamed
+    fn retitle_tabs(&mut self) {
+        let titles: Vec<String> =
+            self.workspace_manager.workspaces().iter().map(|workspace| self.tab_title(&workspace.current_dir)).collect();
+        for (workspace, title) in self.workspace_manager.workspaces_mut().iter_mut().zip(titles) {
+            workspace.retitle(title);
+        }
+    }
+
+    pub fn hidden_marker(&self) -> crate::path_display::HiddenMarker {
+        crate::path_display::HiddenMarker::parse(&self.config.hidden_marker).unwrap_or(crate::path_display::HiddenMarker::Dot)
+    }
+
+    pub fn line_numbers(&self) -> crate::path_display::LineNumbers {
+        crate::path_display::LineNumbers::parse(&self.config.line_numbers).unwrap_or(crate::path_display::LineNumbers::Off)
+    }
+
+    /// An entry's name decorated for the list (`·` for hidden, `/` for folders, per the config)
+    pub fn entry_label(&self, name: &str, is_dir: bool, is_hidden: bool) -> String {
+        crate::path_display::entry_label(name, is_dir, is_hidden, self.hidden_marker(), self.config.dir_trailing_slash)
+    }
+
+    /// Git state of a listed entry for the status column; None when clean or the column is off
+    pub fn git_status(&self, path: &Path) -> Option<FileStatus> {
+        self.git_statuses.get(path).copied()
+    }
+
+    /// Show or hide the sidebar, remembering the choice
+    pub fn toggle_sidebar(&mut self) {
+        self.user_settings.show_sidebar = !self.user_settings.show_sidebar;
+        self.save_user_settings_quietly();
+        self.message = Some(if self.user_settings.show_sidebar {
+            "Sidebar shown (1-9, 0 to jump)".to_string()
+        } else {
+            "Sidebar hidden".to_string()
+        });
+    }
+
+    /// Move the list/preview divider; positive steps widen the preview
+    pub fn resize_preview(&mut self, steps: i32) {
+        let ratio = crate::layout::adjust_preview_ratio(self.config.preview_width_ratio, steps);
+        self.config.preview_width_ratio = ratio;
+        self.user_settings.preview_width_ratio = ratio;
+        self.save_user_settings_quietly();
+        self.message = Some(format!("Preview width: {:.0}%", ratio * 100.0));
+    }
+
+    /// Widen (positive steps) or narrow the sidebar
+    pub fn resize_sidebar(&mut self, steps: i32) {
+        if !self.user_settings.show_sidebar {
+            self.message = Some("The sidebar is hidden (S to show it)".to_string());
+            return;
+        }
+        let width = crate::layout::adjust_sidebar_width(self.config.sidebar_width, steps);
+        self.config.sidebar_width = width;
+        self.user_settings.sidebar_width = width;
+        self.save_user_settings_quietly();
+        self.message = Some(format!("Sidebar width: {} columns", width));
+    }
+
+    /// Go back to the configured pane sizes
+    pub fn reset_layout(&mut self) {
+        let configured = match &self.config.source_path {
+            Some(path) => AppConfig::load_from(path),
+            None => AppConfig::load(),
+        };
+        let configured = configured.log_err("reload config").unwrap_or_default();
+        self.config.preview_width_ratio = configured.preview_width_ratio;
+        self.config.sidebar_width = configured.sidebar_width;
+        self.user_settings.preview_width_ratio = 0.0;
+        self.user_settings.sidebar_width = 0;
+        self.save_user_settings_quietly();
+        self.message = Some("Pane sizes reset".to_string());
+    }
+
+    /// Go to the sidebar item bound to `key`
+    pub fn sidebar_jump(&mut self, key: char) -> Result<()> {
+        let item = match self.sidebar_items().into_iter().find(|item| item.key == Some(key)) {
+            Some(item) => item,
+            None => return Ok(()),
+        };
+        if !item.path.is_dir() {
+            self.error = Some(format!("{} is not available", item.path.display()));
+            return Ok(());
+        }
+        let workspace = self.workspace_manager.active_workspace_mut();
+        workspace.current_dir = item.path;
+        workspace.selected_index = 0;
+        workspace.scroll_offset = 0;
+        self.refresh_workspace()
+    }
+
+    /// Add directories from zoxide or autojump (read from `path`, else the tool's usual
+    /// location) to the recent list, after the ones visited here; returns how many were added
+    pub fn import_jump_data(&mut self, source: &str, path: Option<&Path>) -> Result<usize> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => crate::frecency::DirFrecency::default_path(source)
+                .ok_or_else(|| anyhow::anyhow!("Unknown source '{}': use zoxide or autojump", source))?,
+        };
+        let imported = crate::frecency::DirFrecency::import(source, &path)?;
+        let recent = &mut self.user_settings.recent_directories;
+        let before = recent.len();
+        for (dir, _) in imported.entries {
+            if recent.len() >= crate::sidebar::RECENT_DIRS_MAX {
+                break;
+            }
+            if !recent.contains(&dir) {
+                recent.push(dir);
+            }
+        }
+        let added = recent.len() - before;
+        self.save_user_settings_quietly();
+        self.message = Some(format!("Imported {} directories from {}", added, source));
+        Ok(added)
+    }
+
+    /// Point the directory watcher at the active workspace's directory
+    fn watch_current_dir(&mut self) {
+        if !self.config.auto_refresh {
+            self.dir_watcher = None;
+            return;
+        }
+
+        let current_dir = self.workspace_manager.active_workspace().current_dir.clone();
+        let up_to_date = self
+            .dir_watcher
+            .as_ref()
+            .map(|w| w.path() == current_dir)
+            .unwrap_or(false);
+
+        if !up_to_date {
+            self.dir_watcher = DirWatcher::new(&current_dir).ok();
+        }
+    }
+
+    /// List the current directory again, keeping the same entry selected if it's still there
+    pub fn refresh(&mut self) -> Result<()> {
+        let selected = self.get_current_workspace().get_selected_entry().map(|entry| entry.path.clone());
+        self.refresh_workspace()?;
+
+        let workspace = self.workspace_manager.active_workspace_mut();
+        if let Some(index) = selected.and_then(|path| workspace.entries.iter().position(|e| e.path == path)) {
+            workspace.select(index);
+            self.update_preview();
+        }
+        let count = self.get_current_workspace().entries.len();
+        self.message = Some(format!("Refreshed ({} entries)", count));
+        Ok(())
+    }
+
+    /// Refresh the listing if the watched directory changed on disk.
+    /// Returns true when a refresh happened.
+    pub fn poll_directory_changes(&mut self) -> Result<bool> {
+        // A deleted directory may send nothing more, watched or not
+        if self.current_dir_missing() {
+            self.refresh_workspace()?;
+            return Ok(true);
+        }
+        // Tabs can be switched without a refresh, so re-point the watcher first
+        self.watch_current_dir();
+
+        let changed = match &self.dir_watcher {
+            Some(watcher) => !watcher.drain().is_empty(),
+            None => false,
+        };
+
+        if changed {
+            self.refresh_workspace()?;
+        }
+        Ok(changed)
+    }
+
+    // ========== Sorting ==========
+    /// The current directory's sort: one picked with `s` this session, else the
+    /// first matching `sort_rules` entry, else by name
+    pub fn sort_mode(&self) -> SortMode {
+        let current_dir = &self.workspace_manager.active_workspace().current_dir;
+        self.sort_overrides
+            .get(current_dir)
+            .copied()
+            .or_else(|| self.sort_rules.mode_for(current_dir))
+            .unwrap_or_default()
+    }
+
+    /// Switch the current directory to the next sort mode, keeping the selection
+    pub fn cycle_sort(&mut self) -> Result<()> {
+        let sort = self.sort_mode().next();
+        let current_dir = self.workspace_manager.active_workspace().current_dir.clone();
+        let selected = self.get_current_workspace().get_selected_entry().map(|entry| entry.path.clone());
+        self.sort_overrides.insert(current_dir, sort);
+        self.refresh_workspace()?;
+
+        let workspace = self.workspace_manager.active_workspace_mut();
+        if let Some(index) = selected.and_then(|path| workspace.entries.iter().position(|e| e.path == path)) {
+            workspace.select(index);
+            self.update_preview();
+        }
+        self.message = Some(format!("Sorted by {}", sort.as_str()));
+        Ok(())
+    }
+
+    // ========== Custom Order ==========
+    /// Whether the current directory uses a saved manual order
+    pub fn has_custom_order(&self) -> bool {
+        let current_dir = &self.workspace_manager.active_workspace().current_dir;
+        self.user_settings.custom_orders.contains_key(current_dir)
+    }
+
+    /// Move the selected entry up (negative) or down and save the directory's manual order
+    pub fn move_selected_entry(&mut self, delta: isize) {
+        let workspace = self.workspace_manager.active_workspace_mut();
+        let from = workspace.selected_index;
+        let to = from as isize + delta;
+        if workspace.entries.is_empty() || to < 0 || to >= workspace.entries.len() as isize {
+            return;
+        }
+        let to = to as usize;
+        workspace.entries.swap(from, to);
+        workspace.select(to);
+
+        // Names hidden from this listing keep their place at the end, unless they are gone
+        let current_dir = workspace.current_dir.clone();
+        let mut order: Vec<String> = workspace.entries.iter().map(|e| e.name.clone()).collect();
+        if let Some(previous) = self.user_settings.custom_orders.get(&current_dir) {
+            let listed: HashSet<&String> = order.iter().collect();
+            let kept: Vec<String> = previous
+                .iter()
+                .filter(|name| !listed.contains(name) && current_dir.join(name.as_str()).exists())
+                .cloned()
+                .collect();
+            order.extend(kept);
+        }
+        self.user_settings.custom_orders.insert(current_dir, order);
+        self.save_user_settings_quietly();
+        self.message = Some("Custom order saved (reset-order in the palette to undo)".to_string());
+    }
+
+    /// Drop the current directory's manual order and go back to automatic sorting
+    pub fn reset_custom_order(&mut self) -> Result<()> {
+        let current_dir = self.workspace_manager.active_workspace().current_dir.clone();
+        if self.user_settings.custom_orders.remove(&current_dir).is_none() {
+            self.message = Some("This folder has no custom order".to_string());
+            return Ok(());
+        }
+        self.save_user_settings_quietly();
+        self.refresh_workspace()?;
+        self.message = Some("Back to automatic sorting".to_string());
+        Ok(())
+    }
+
+    /// Write user settings now, logging rather than surfacing failures
+    fn save_user_settings_quietly(&mut self) {
+        self.user_settings.plugin_settings = self.api_plugin_manager.plugin_data();
+        self.persistence_manager.save_settings(&self.user_settings).log_err("save user settings");
+    }
+
+    /// The ignore patterns in effect: none while bypassed
+    pub fn active_ignore_patterns(&self) -> IgnorePatterns {
+        if self.ignore_patterns_bypassed {
+            IgnorePatterns::default()
+        } else {
+            self.ignore_patterns.clone()
+        }
+    }
+
+    /// Show or hide entries matching `ignore_patterns` for the rest of the session
+    pub fn toggle_ignore_patterns(&mut self) -> Result<()> {
+        self.ignore_patterns_bypassed = !self.ignore_patterns_bypassed;
+        self.refresh_workspace()?;
+        self.message = Some(if self.ignore_patterns_bypassed {
+            "Ignore patterns bypassed: showing everything".to_string()
+        } else {
+            format!("Ignore patterns applied ({})", self.config.ignore_patterns.len())
+        });
+        Ok(())
+    }
+
+    /// Show or hide folder item counts in the listing, remembering the choice
+    pub fn toggle_dir_counts(&mut self) -> Result<()> {
+        self.config.show_dir_counts = !self.config.show_dir_counts;
+        self.config.save().log_err("save config");
+        self.refresh_workspace()?;
+        self.message = Some(format!(
+            "Folder item counts: {}",
+            if self.config.show_dir_counts { "shown" } else { "hidden" }
+        ));
+        Ok(())
+    }
+
+    /// Show or hide the size bars column, remembering the choice
+    pub fn toggle_size_bars(&mut self) {
+        self.config.size_bar_column = !self.config.size_bar_column;
+        self.config.save().log_err("save config");
+        self.message = Some(format!(
+            "Size bars: {}",
+            if self.config.size_bar_column { "shown" } else { "hidden" }
+        ));
+    }
+
+    /// Shade names by age or stop, remembering the choice
+    pub fn toggle_age_shading(&mut self) {
+        self.config.age_shading = !self.config.age_shading;
+        self.config.save().log_err("save config");
+        self.message = Some(format!("Age shading: {}", if self.config.age_shading { "on" } else { "off" }));
+    }
+
+    pub fn toggle_hidden(&mut self) -> Result<()> {
+        let show_hidden = {
+            let workspace = self.workspace_manager.active_workspace_mut();
+            workspace.show_hidden = !workspace.show_hidden;
+            workspace.show_hidden
+        };
+        self.user_settings.show_hidden_files = show_hidden;
+        self.refresh_workspace()?;
+        
+        self.message = Some(format!(
+            "Hidden files: {}",
+            if show_hidden { "shown" } else { "hidden" }
+        ));
+        Ok(())
+    }
+
+    // ========== Search ==========
+    pub fn start_search(&mut self) {
+        self.mode = AppMode::Search;
+        self.search_query.clear();
+        self.searched_query = None;
+        self.search_index = 0;
+        self.collapsed_groups.clear();
+        self.message = Some("Search mode: Type to search (Tab: scope, Ctrl+G: grouping, Alt+1-6: categories, Ctrl+S: collect, ↑/↓ to pick, Enter to open, ESC to cancel)".to_string());
+    }
+
+    /// Ask for a folder or bookmark to search in
+    pub fn open_search_in(&mut self) {
+        self.input_buffer.clear();
+        self.mode = AppMode::Input(InputMode::SearchIn);
+    }
+
+    /// Start searching in a typed folder, or a bookmark's folder by name
+    pub fn start_search_in(&mut self, target: &str) {
+        let target = target.trim();
+        if target.is_empty() {
+            return;
+        }
+        let root = match self.bookmark_manager.get(target) {
+            Some(bookmark) => bookmark.path.clone(),
+            None => crate::fileops::expand_input_path(target, &self.workspace_manager.active_workspace().current_dir),
+        };
+        if !root.is_dir() {
+            self.error = Some(format!("{} is not a directory", root.display()));
+            return;
+        }
+        self.start_search();
+        self.scope_search_to(root);
+    }
+
+    /// Search `root` for `query` without leaving the current directory; the
+    /// scope stays on it until search mode is closed or the scope is cycled
+    #[allow(dead_code)]
+    pub fn search_in(&mut self, root: &Path, query: &str) -> Result<()> {
+        if !root.is_dir() {
+            return Err(anyhow::anyhow!("{} is not a directory", root.display()));
+        }
+        self.scope_search_to(root.to_path_buf());
+        self.search_query = query.to_string();
+        self.run_search();
+        Ok(())
+    }
+
+    fn scope_search_to(&mut self, root: PathBuf) {
+        // A picked folder is searched through, whatever the saved scope says
+        if self.search_scope == SearchScope::Drive {
+            self.search_scope = SearchScope::Recursive;
+        }
+        self.scoped_root = Some(root);
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.mode = AppMode::Normal;
+        self.scoped_root = None;
+        self.search_query.clear();
+        self.searched_query = None;
+        self.search_engine.clear();
+        self.search_engine.clear_category_filters();
+        self.message = None;
+    }
+
+    /// Search as the query changes; whole-drive searches wait for Enter
+    pub fn perform_search(&mut self) {
+        if self.search_scope == SearchScope::Drive {
+            self.search_engine.clear();
+            self.searched_query = None;
+            if !self.search_query.is_empty() {
+                self.message = Some(format!("Press Enter to search {}", self.config.search_root));
+            }
+            return;
+        }
+        self.run_search();
+    }
+
+    /// Run the search for the current query and scope right away
+    pub fn run_search(&mut self) {
+        if self.search_query.is_empty() {
+            self.search_engine.clear();
+            self.searched_query = None;
+            return;
+        }
+
+        // `depth:N` in the query overrides the scope's depth for this search
+        let (query, depth) = match crate::search::take_depth_token(&self.search_query) {
+            Ok(split) => split,
+            Err(e) => {
+                self.search_engine.clear();
+                self.searched_query = Some(self.search_query.clone());
+                self.error = Some(e);
+                return;
+            }
+        };
+        if query.is_empty() {
+            self.search_engine.clear();
+            self.searched_query = None;
+            return;
+        }
+
+        let origin = self.search_base();
+        let max_results = self.config.max_search_results;
+        self.search_engine.ignore = self.active_ignore_patterns();
+        self.search_engine.follow_symlinks = self.config.follow_symlinks;
+        let searched = match self.search_scope {
+            SearchScope::CurrentDir => self.search_engine.search_in(&origin, &query, max_results, depth.unwrap_or(1)),
+            SearchScope::Recursive => self.search_engine.search_in(
+                &origin,
+                &query,
+                max_results,
+                depth.unwrap_or(self.config.search_max_depth),
+            ),
+            SearchScope::Drive => {
+                let root = self.config.search_root.clone();
+                let time_limit = Some(self.config.drive_search_time_limit_secs)
+                    .filter(|&secs| secs > 0)
+                    .map(Duration::from_secs);
+                let job = self.jobs.start(format!("Searching {}", root));
+                self.search_engine.start_drive_search(
+                    Path::new(&root),
+                    &query,
+                    max_results,
+                    depth.unwrap_or(self.config.drive_search_max_depth),
+                    time_limit,
+                    Some(job),
+                );
+                self.searched_query = Some(self.search_query.clone());
+                self.search_index = 0;
+                self.message = Some(format!("Searching {}... (ESC to stop)", root));
+                return;
+            }
+        };
+        if let Err(e) = searched {
+            self.search_engine.clear();
+            self.error = Some(format!("Cannot search: {}", e));
+        }
+        self.searched_query = Some(self.search_query.clone());
+        self.search_index = 0;
+        self.message = Some(format!("Found {} results", self.search_engine.results.len()));
+    }
+
+    /// Pick up matches streamed in by a running whole-drive search
+    pub fn poll_search(&mut self) -> bool {
+        if !self.search_engine.poll_drive_search() {
+            return false;
+        }
+        if !self.search_engine.is_searching() {
+            self.report_drive_search();
+        }
+        true
+    }
+
+    /// Block until a running whole-drive search is done
+    #[allow(dead_code)]
+    pub fn wait_for_search(&mut self) {
+        if self.search_engine.is_searching() {
+            self.search_engine.wait_for_drive_search();
+            self.report_drive_search();
+        }
+    }
+
+    /// Stop a running whole-drive search, keeping what it found so far
+    pub fn stop_drive_search(&mut self) {
+        self.search_engine.cancel_drive_search();
+        self.report_drive_search();
+    }
+
+    fn report_drive_search(&mut self) {
+        let shown = self.search_engine.results.len();
+        let matched = self.search_engine.matched;
+        let found = if matched > shown {
+            format!("{} results (best of {})", shown, matched)
+        } else {
+            format!("{} results", shown)
+        };
+        self.message = Some(match self.search_engine.drive_search_end {
+            Some(DriveSearchEnd::TimedOut) => format!(
+                "Partial results: {} after {}s (drive_search_time_limit_secs)",
+                found, self.config.drive_search_time_limit_secs
+            ),
+            Some(DriveSearchEnd::Cancelled) => format!("Partial results: {}, search stopped", found),
+            _ => format!("Found {}", found),
+        });
+    }
+
+    /// Enter in search mode: search if the results are stale, otherwise open
+    /// the selected result or fold the selected group
+    pub fn submit_search(&mut self) -> Result<()> {
+        if self.searched_query.as_deref() != Some(self.search_query.as_str()) {
+            self.run_search();
+            return Ok(());
+        }
+
+        let groups = self.search_groups();
+        match self.search_rows(&groups).get(self.search_index) {
+            Some(SearchRow::Header(group)) => {
+                let key = groups[*group].key.clone();
+                if !self.collapsed_groups.remove(&key) {
+                    self.collapsed_groups.insert(key);
+                }
+            }
+            Some(SearchRow::Result(index)) => self.navigate_to_search_result(*index)?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Directory searches start from and results are shown relative to
+    pub fn search_base(&self) -> PathBuf {
+        match (self.search_scope, &self.scoped_root) {
+            (SearchScope::Drive, _) => PathBuf::from(&self.config.search_root),
+            (_, Some(root)) => root.clone(),
+            _ => self.workspace_manager.active_workspace().current_dir.clone(),
+        }
+    }
+
+    pub fn search_groups(&self) -> Vec<ResultGroup> {
+        group_results(&self.search_engine.results, self.search_grouping, &self.search_base())
+    }
+
+    /// Rows of the results view: group headers, then the results of open groups
+    pub fn search_rows(&self, groups: &[ResultGroup]) -> Vec<SearchRow> {
+        let mut rows = Vec::new();
+        for (index, group) in groups.iter().enumerate() {
+            if self.search_grouping != SearchGrouping::None {
+                rows.push(SearchRow::Header(index));
+                if self.collapsed_groups.contains(&group.key) {
+                    continue;
+                }
+            }
+            rows.extend(group.indices.iter().map(|&i| SearchRow::Result(i)));
+        }
+        rows
+    }
+
+    pub fn search_move(&mut self, delta: isize) {
+        let rows = self.search_rows(&self.search_groups()).len();
+        if rows == 0 {
+            self.search_index = 0;
+            return;
+        }
+        self.search_index = (self.search_index as isize + delta).clamp(0, rows as isize - 1) as usize;
+    }
+
+    /// Cycle the results grouping; the choice is saved in the config
+    pub fn toggle_search_grouping(&mut self) {
+        self.search_grouping = self.search_grouping.next();
+        self.config.search_grouping = self.search_grouping.as_str().to_string();
+        self.search_index = 0;
+        self.message = Some(format!("Search results: {}", self.search_grouping.label()));
+    }
+
+    /// Show or hide one category of results; several categories combine
+    pub fn toggle_search_category(&mut self, category: FileCategory) {
+        let active = self.search_engine.toggle_category_filter(category);
+        self.search_index = 0;
+        self.message = Some(format!(
+            "{} {} ({} results)",
+            if active { "Showing" } else { "No longer filtering" },
+            category.label(),
+            self.search_engine.results.len()
+        ));
+    }
+
+    pub fn clear_search_categories(&mut self) {
+        self.search_engine.clear_category_filters();
+        self.search_index = 0;
+        self.message = Some("Category filters cleared".to_string());
+    }
+
+    /// Files among the current search results, best match first
+    pub fn search_result_paths(&self) -> Vec<PathBuf> {
+        self.search_engine.results.iter().filter(|r| !r.is_dir).map(|r| r.path.clone()).collect()
+    }
+
+    /// Ask where to copy the current search results
+    pub fn start_collect_results(&mut self) {
+        if self.search_result_paths().is_empty() {
+            self.error = Some("No files among the results to collect".to_string());
+            return;
+        }
+        if self.collect_task.is_some() {
+            self.error = Some("Still collecting the previous results".to_string());
+            return;
+        }
+        self.input_buffer.clear();
+        self.mode = AppMode::Input(InputMode::CollectResults);
+    }
+
+    /// Copy the current search results into `dest` on a worker thread
+    pub fn collect_search_results(&mut self, dest: &str) {
+        let dest = match dest.trim() {
+            "" => return,
+            // Relative paths are taken from the current directory
+            dest => crate::fileops::expand_input_path(dest, &self.workspace_manager.active_workspace().current_dir),
+        };
+        let files = self.search_result_paths();
+        let base = self.search_base();
+        let preserve_structure = self.config.collect_preserve_structure;
+        let strategy = CollisionStrategy::parse(&self.config.collision_strategy).unwrap_or(CollisionStrategy::Ask);
+
+        let (tx, rx) = channel();
+        {
+            let dest = dest.clone();
+            self.jobs.submit(format!("Collecting {} files", files.len()), move |job| {
+                let report = FileOperation::collect(&files, &base, &dest, preserve_structure, strategy, |done, total| {
+                    job.set_progress(done as u64, total as u64);
+                    !job.is_cancelled()
+                });
+                let _ = tx.send(report);
+            });
+        }
+        self.message = Some(format!("Collecting results into {}...", dest.display()));
+        self.collect_task = Some(CollectTask { dest, results: rx });
+    }
+
+    /// Report a finished collection of search results
+    pub fn poll_collect(&mut self) -> bool {
+        let task = match &self.collect_task {
+            Some(task) => task,
+            None => return false,
+        };
+        let result = match task.results.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return false,
+            Err(TryRecvError::Disconnected) => Err(anyhow::anyhow!("cancelled")),
+        };
+        let task = self.collect_task.take().expect("collect task checked above");
+        match result {
+            Ok(report) => {
+                for path in &report.copied {
+                    self.api_plugin_manager.call_file_created(path).log_err("run on_file_created hooks");
+                }
+                let mut summary = format!("Copied {} files to {}", report.copied.len(), task.dest.display());
+                if report.skipped > 0 {
+                    summary.push_str(&format!(", skipped {}", report.skipped));
+                }
+                match report.failed.first() {
+                    Some((path, reason)) => {
+                        tracing::warn!("Collecting {} failed: {}", path.display(), reason);
+                        self.error = Some(format!("{}, {} failed: {}", summary, report.failed.len(), reason));
+                    }
+                    None => self.message = Some(summary),
+                }
+            }
+            Err(e) => self.error = Some(format!("Collecting results failed: {}", e)),
+        }
+        true
+    }
+
+    /// Switch to the next scope and search again; the choice is saved in the config.
+    /// A folder picked to search in is dropped for the usual scopes.
+    pub fn cycle_search_scope(&mut self) {
+        self.scoped_root = None;
+        self.set_search_scope(self.search_scope.next());
+    }
+
+    pub fn set_search_scope(&mut self, scope: SearchScope) {
+        self.search_scope = scope;
+        self.config.search_scope = scope.as_str().to_string();
+        self.perform_search();
+    }
+
+    pub fn add_search_char(&mut self, c: char) {
+        self.search_query.push(c);
+        self.perform_search();
+    }
+
+    pub fn remove_search_char(&mut self) {
+        self.search_query.pop();
+        self.perform_search();
+    }
+
+    pub fn navigate_to_search_result(&mut self, index: usize) -> Result<()> {
+        if let Some(result) = self.search_engine.results.get(index) {
+            let workspace = self.workspace_manager.active_workspace_mut();
+            
+            if result.is_dir {
+                workspace.current_dir = result.path.clone();
+            } else if let Some(parent) = result.path.parent() {
+                workspace.current_dir = parent.to_path_buf();
+            }
+
+            workspace.selected_index = 0;
+            workspace.scroll_offset = 0;
+            self.refresh_workspace()?;
+            self.search_history.add(self.search_query.clone());
+            self.cancel_search();
+        }
+        Ok(())
+    }
+
+    /// Open the actions menu for the highlighted result
+    pub fn open_search_actions(&mut self) {
+        let groups = self.search_groups();
+        if let Some(SearchRow::Result(index)) = self.search_rows(&groups).get(self.search_index) {
+            self.search_action_target = Some(*index);
+            self.search_action_index = 0;
+            self.mode = AppMode::SearchActions;
+        }
+    }
+
+    pub fn close_search_actions(&mut self) {
+        self.search_action_target = None;
+        self.mode = AppMode::Search;
+    }
+
+    pub fn search_actions_move(&mut self, delta: isize) {
+        let last = ResultAction::ALL.len() as isize - 1;
+        self.search_action_index = (self.search_action_index as isize + delta).clamp(0, last) as usize;
+    }
+
+    /// Run `action`, or the highlighted one, on the result the menu is open for
+    pub fn run_search_action(&mut self, action: Option<ResultAction>) -> Result<()> {
+        let index = match self.search_action_target {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+        let action = action.unwrap_or(ResultAction::ALL[self.search_action_index]);
+        self.close_search_actions();
+        self.search_result_action(index, action)
+    }
+
+    /// Do `action` to search result `index`. Reveal (and Open on a folder) leaves
+    /// the search; the others keep it open.
+    pub fn search_result_action(&mut self, index: usize, action: ResultAction) -> Result<()> {
+        let result = match self.search_engine.results.get(index) {
+            Some(result) => result.clone(),
+            None => return Err(anyhow::anyhow!("No search result {}", index)),
+        };
+        let target = action.target(&result);
+        match action {
+            ResultAction::Open if result.is_dir => self.navigate_to_search_result(index)?,
+            ResultAction::Open => self.open_file(&target),
+            ResultAction::Reveal => {
+                self.search_history.add(self.search_query.clone());
+                self.cancel_search();
+                self.reveal_path(&result.path)?;
+            }
+            ResultAction::CopyPath => {
+                let text = target.display().to_string();
+                match self.copy_to_clipboard(&text) {
+                    Ok(()) => self.message = Some(format!("Copied path: {}", text)),
+                    Err(e) => self.error = Some(format!("Clipboard unavailable: {}", e)),
+                }
+            }
+            ResultAction::Delete => {
+                if self.delete_path(&target)? {
+                    self.search_engine.remove_result(index);
+                    self.search_move(0);
+                }
+            }
+            ResultAction::Bookmark => {
+                let name = target.file_name().unwrap_or_default().to_string_lossy().to_string();
+                self.bookmark_manager.add(name.clone(), target, "📌".to_string())?;
+                self.message = Some(format!("Added bookmark: {}", name));
+            }
+        }
+        Ok(())
+    }
+
+    // ========== Workspaces/Tabs ==========
+    pub fn new_workspace(&mut self) -> Result<()> {
+        let workspace = self.workspace_manager.active_workspace();
+        let new_path = workspace.current_dir.clone();
+        
+        self.workspace_manager.create_workspace(new_path);
+        self.retitle_tabs();
+        self.message = Some("Created new workspace".to_string());
+        Ok(())
+    }
+
+    /// Open the selected folder in a new tab rather than this one
+    pub fn open_selected_in_new_tab(&mut self) -> Result<()> {
+        self.open_selected_tab();
+        Ok(())
+    }
+
+    /// `open_selected_in_new_tab`, returning the new tab's position
+    fn open_selected_tab(&mut self) -> Option<usize> {
+        let entry = self.workspace_manager.active_workspace().get_selected_entry().cloned()?;
+        if !entry.is_dir {
+            self.error = Some("Select a folder to open in a new tab".to_string());
+            return None;
+        }
+        match self.open_in_new_tab(&entry.path) {
+            Ok(index) => Some(index),
+            Err(e) => {
+                self.error = Some(format!("Cannot open {}: {}", entry.name, e));
+                None
+            }
+        }
+    }
+
+    /// Open `path` in a new tab after the others, switching to it only with
+    /// `focus_new_tab`; returns the new tab's position
+    pub fn open_in_new_tab(&mut self, path: &Path) -> Result<usize> {
+        let current = self.workspace_manager.active_workspace();
+        // A folder inside the archive being browsed takes the archive along
+        let archive = current.archive.clone().filter(|archive| archive.contains(path));
+        if archive.is_none() && !path.is_dir() {
+            return Err(anyhow::anyhow!("{} is not a directory", path.display()));
+        }
+        let show_hidden = current.show_hidden;
+        let previous = self.workspace_manager.active_id();
+
+        self.workspace_manager.create_workspace(path.to_path_buf());
+        let workspace = self.workspace_manager.active_workspace_mut();
+        workspace.show_hidden = show_hidden;
+        workspace.archive = archive;
+        self.refresh_workspace()?;
+        self.retitle_tabs();
+        let index = self.workspace_manager.active_index();
+        let title = self.workspace_manager.active_workspace().title.clone();
+        if self.config.focus_new_tab {
+            self.message = Some(format!("Opened {} in a new tab", title));
+        } else {
+            self.workspace_manager.switch_workspace(previous);
+            self.message = Some(format!("Opened {} in tab {}", title, index + 1));
+        }
+        Ok(index)
+    }
+
+    pub fn close_workspace(&mut self) -> Result<()> {
+        let id = self.workspace_manager.active_id();
+        if self.workspace_manager.close_workspace(id) {
+            self.message = Some("Closed workspace".to_string());
+            self.recover_switched_tab();
+        } else {
+            self.error = Some("Cannot close last workspace".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn next_workspace(&mut self) {
+        self.workspace_manager.next_workspace();
+        self.message = Some("Switched to next workspace".to_string());
+        self.recover_switched_tab();
+    }
+
+    pub fn prev_workspace(&mut self) {
+        self.workspace_manager.prev_workspace();
+        self.message = Some("Switched to previous workspace".to_string());
+        self.recover_switched_tab();
+    }
+
+    /// A tab switched to whose folder has gone moves up out of it straight away
+    fn recover_switched_tab(&mut self) {
+        if self.current_dir_missing() {
+            self.refresh_workspace().log_err("list the tab's folder");
+        }
+    }
+
+    pub fn rename_workspace(&mut self, name: String) {
         self.workspace_manager.rename_active_workspace(name.clone());
         self.message = Some(format!("Renamed workspace to: {}", name));
     }
 
-    // ========== Bookmarks ==========
-    pub fn add_bookmark(&mut self, name: String) -> Result<()> {
-        let workspace = self.workspace_manager.active_workspace();
-        let path = workspace.current_dir.clone();
+    // ========== Bookmarks ==========
+    /// Open the bookmark prompt with a name for the current directory filled in.
+    /// With `BOOKMARK_HINT_AFTER` bookmarks or more, the prompt also names the most
+    /// frequently visited directory that isn't bookmarked yet.
+    pub fn start_add_bookmark(&mut self) {
+        let current_dir = self.workspace_manager.active_workspace().current_dir.clone();
+        self.input_buffer = self.bookmark_manager.suggest_name(&current_dir);
+        self.bookmark_hint = if self.bookmark_manager.count() >= BOOKMARK_HINT_AFTER {
+            crate::frecency::DirFrecency::from_visits(&self.user_settings.dir_visits, chrono::Utc::now())
+                .entries
+                .into_iter()
+                .map(|(dir, _)| dir)
+                .find(|dir| *dir != current_dir && !self.bookmark_manager.is_bookmarked(dir))
+        } else {
+            None
+        };
+        self.mode = AppMode::Input(InputMode::AddBookmark);
+    }
+
+    pub fn add_bookmark(&mut self, name: String) -> Result<()> {
+        let workspace = self.workspace_manager.active_workspace();
+        let path = workspace.current_dir.clone();
+
+        self.bookmark_manager.add(name.clone(), path, "📌".to_string())?;
+        self.message = Some(format!("Added bookmark: {}", name));
+        Ok(())
+    }
+
+    pub fn goto_bookmark(&mut self, name: &str) -> Result<()> {
+        if let Some(bookmark) = self.bookmark_manager.get(name) {
+            let workspace = self.workspace_manager.active_workspace_mut();
+            workspace.current_dir = bookmark.path.clone();
+            workspace.selected_index = 0;
+            workspace.scroll_offset = 0;
+            self.refresh_workspace()?;
+            self.message = Some(format!("Navigated to bookmark: {}", name));
+        } else {
+            self.error = Some("Bookmark not found".to_string());
+        }
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    pub fn export_bookmarks(&mut self, path: &Path) -> Result<()> {
+        self.bookmark_manager.export(path)?;
+        self.message = Some(format!("Exported {} bookmarks", self.bookmark_manager.count()));
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    pub fn import_bookmarks(&mut self, path: &Path, merge: bool) -> Result<usize> {
+        let added = self.bookmark_manager.import(path, merge)?;
+        self.message = Some(format!("Imported {} bookmarks", added));
+        Ok(added)
+    }
+
+    // ========== Command Palette ==========
+    pub fn start_command_palette(&mut self) {
+        self.mode = AppMode::CommandPalette;
+        self.command_palette.clear_filter();
+        self.command_search_index = 0;
+        self.message = Some("Command palette (type to filter, ESC to cancel)".to_string());
+    }
+
+    /// Run a palette command and report what it did
+    pub fn execute_command(&mut self, cmd: &Command) -> Result<CommandOutcome> {
+        // Commands that open another mode keep it; everything else returns to normal
+        if matches!(self.mode, AppMode::CommandPalette) {
+            self.mode = AppMode::Normal;
+        }
+        self.message = None;
+
+        let outcome = match cmd {
+            Command::Copy => {
+                self.copy_selected()?;
+                self.reported()
+            }
+            Command::Duplicate => match self.duplicate_selected_entry() {
+                Some(copy) => CommandOutcome::Created(vec![copy]),
+                None => self.reported(),
+            },
+            Command::Move => {
+                self.start_move();
+                self.opened()
+            }
+            Command::Paste => {
+                let pasted = self.paste_yanked()?;
+                if pasted.is_empty() {
+                    self.opened()
+                } else {
+                    CommandOutcome::Created(pasted)
+                }
+            }
+            Command::Delete => match self.delete_selected_entry()? {
+                Some(path) => CommandOutcome::Removed(vec![path]),
+                None => self.reported(),
+            },
+            Command::CreateFile => {
+                self.mode = AppMode::Input(InputMode::CreateFile);
+                self.input_buffer.clear();
+                self.opened()
+            }
+            Command::CreateDirectory => {
+                self.mode = AppMode::Input(InputMode::CreateDirectory);
+                self.input_buffer.clear();
+                self.opened()
+            }
+            Command::ParentDirectory => {
+                self.go_back()?;
+                self.navigated()
+            }
+            Command::FirstEntry => {
+                self.go_home();
+                CommandOutcome::Nothing
+            }
+            Command::LastEntry => {
+                self.go_end();
+                CommandOutcome::Nothing
+            }
+            Command::Home => match std::env::var("HOME") {
+                Ok(home_dir) => {
+                    self.go_to_path(&home_dir)?;
+                    self.navigated()
+                }
+                Err(_) => CommandOutcome::Nothing,
+            },
+            Command::Root => {
+                self.go_to_path("/")?;
+                self.navigated()
+            }
+            Command::GoToPath => {
+                self.mode = AppMode::Input(InputMode::GoToPath);
+                self.input_buffer.clear();
+                self.opened()
+            }
+            Command::Search => {
+                self.start_search();
+                self.opened()
+            }
+            Command::ToggleHidden => {
+                self.toggle_hidden()?;
+                self.reported()
+            }
+            Command::Refresh => {
+                self.refresh()?;
+                self.reported()
+            }
+            Command::NewWorkspace => {
+                self.new_workspace()?;
+                CommandOutcome::SwitchedTab(self.workspace_manager.active_index())
+            }
+            Command::OpenInNewTab => match self.open_selected_tab() {
+                // Without `focus_new_tab` the new tab opens behind this one
+                Some(index) if index == self.workspace_manager.active_index() => CommandOutcome::SwitchedTab(index),
+                _ => self.reported(),
+            },
+            Command::SearchIn => {
+                self.open_search_in();
+                self.opened()
+            }
+            Command::NextWorkspace => {
+                self.next_workspace();
+                CommandOutcome::SwitchedTab(self.workspace_manager.active_index())
+            }
+            Command::PrevWorkspace => {
+                self.prev_workspace();
+                CommandOutcome::SwitchedTab(self.workspace_manager.active_index())
+            }
+            Command::AddBookmark => {
+                self.start_add_bookmark();
+                self.opened()
+            }
+            Command::OpenWithDefault => {
+                let workspace = self.workspace_manager.active_workspace();
+                if let Some(path) = workspace.get_selected_entry().map(|entry| entry.path.clone()) {
+                    let _ = that(&path);
+                    if path.is_file() {
+                        self.remember_opened(&path);
+                    }
+                }
+                CommandOutcome::Nothing
+            }
+            Command::ShowHelp => {
+                self.mode = AppMode::Help;
+                self.opened()
+            }
+            Command::ShowSettings => {
+                self.open_settings();
+                self.opened()
+            }
+            Command::ThemeGallery => {
+                self.open_theme_gallery();
+                self.opened()
+            }
+            Command::RecentFiles => {
+                self.open_recent_files();
+                self.opened()
+            }
+            Command::RecentlyOpened => {
+                self.open_recently_opened();
+                self.opened()
+            }
+            Command::ShowPlugins => {
+                self.open_plugin_manager();
+                self.opened()
+            }
+            Command::ReloadPlugins => {
+                self.reload_plugins().unwrap_or_else(|e| {
+                    self.error = Some(format!("Cannot reload plugins: {}", e));
+                });
+                self.reported()
+            }
+            Command::OpenTrash => {
+                self.open_trash();
+                self.opened()
+            }
+            Command::HashFile => {
+                self.hash_selected();
+                self.opened()
+            }
+            Command::DiffFiles => {
+                self.open_diff();
+                self.opened()
+            }
+            Command::SelectAll => {
+                self.select_all();
+                self.reported()
+            }
+            Command::ResetCustomOrder => {
+                self.reset_custom_order()?;
+                self.reported()
+            }
+            Command::CycleSort => {
+                self.cycle_sort()?;
+                self.reported()
+            }
+            Command::ClearSelection => {
+                self.clear_selection();
+                self.reported()
+            }
+            Command::InvertSelection => {
+                self.invert_selection();
+                self.reported()
+            }
+            Command::CopyHash => {
+                self.copy_selected_hash();
+                self.reported()
+            }
+            Command::CopyRepoPath => {
+                self.copy_repo_relative_path();
+                self.reported()
+            }
+            Command::CopyContents => {
+                self.copy_selected_contents();
+                self.reported()
+            }
+            Command::ExportMediaInfo => {
+                self.export_selected_media_info()?;
+                self.reported()
+            }
+            Command::ToggleIgnorePatterns => {
+                self.toggle_ignore_patterns()?;
+                self.reported()
+            }
+            Command::ShowLogPath => {
+                self.message = Some(format!("Log file: {}", self.dirs.log_file().display()));
+                self.reported()
+            }
+            Command::CreateThemeFromTerminal => {
+                self.create_theme_from_terminal()?;
+                self.reported()
+            }
+            Command::FixThemeContrast => {
+                let name = self.theme_manager.current_theme_name();
+                self.auto_adjust_theme(&name)?;
+                self.reported()
+            }
+            Command::NextHighlightTheme => {
+                self.next_highlight_theme();
+                self.reported()
+            }
+            Command::ChangeMode => {
+                self.start_permission_change(false);
+                self.opened()
+            }
+            Command::SequenceRename => {
+                self.start_sequence_rename();
+                self.opened()
+            }
+            Command::Flatten => {
+                self.start_flatten();
+                self.opened()
+            }
+            Command::ChangeOwner => {
+                self.start_permission_change(true);
+                self.opened()
+            }
+            Command::EditFile => {
+                self.open_editor();
+                self.opened()
+            }
+            Command::ShowPlaces => {
+                self.open_places();
+                self.opened()
+            }
+            Command::ShowJobs => {
+                self.open_jobs();
+                self.opened()
+            }
+            Command::ShowPaths => {
+                self.open_paths();
+                self.opened()
+            }
+            Command::ToggleRender => {
+                self.toggle_render_mode();
+                self.reported()
+            }
+            Command::OpenConfigDir => {
+                let dir = self.config_dir();
+                if let Err(e) = that(&dir) {
+                    self.error = Some(format!("Cannot open {}: {}", dir.display(), e));
+                }
+                CommandOutcome::Nothing
+            }
+            Command::ToggleSidebar => {
+                self.toggle_sidebar();
+                self.reported()
+            }
+            Command::ToggleDirCounts => {
+                self.toggle_dir_counts()?;
+                self.reported()
+            }
+            Command::ToggleSizeBars => {
+                self.toggle_size_bars();
+                self.reported()
+            }
+            Command::ToggleAgeShading => {
+                self.toggle_age_shading();
+                self.reported()
+            }
+            Command::DiskUsage => {
+                self.open_disk_usage();
+                self.opened()
+            }
+            Command::ImportZoxide => {
+                if let Err(e) = self.import_jump_data("zoxide", None) {
+                    self.error = Some(format!("Import failed: {:#}", e));
+                }
+                self.reported()
+            }
+            Command::ImportAutojump => {
+                if let Err(e) = self.import_jump_data("autojump", None) {
+                    self.error = Some(format!("Import failed: {:#}", e));
+                }
+                self.reported()
+            }
+            Command::Quit => {
+                self.request_quit();
+                self.reported()
+            }
+            _ => CommandOutcome::Nothing,
+        };
+        Ok(outcome)
+    }
+
+    /// Outcome of a command that only reports in the status bar
+    fn reported(&self) -> CommandOutcome {
+        match &self.message {
+            Some(message) => CommandOutcome::Message(message.clone()),
+            None => CommandOutcome::Nothing,
+        }
+    }
+
+    /// Outcome of a command that opens a view or prompt, or says why it didn't
+    fn opened(&self) -> CommandOutcome {
+        match &self.mode {
+            AppMode::Normal => self.reported(),
+            mode => CommandOutcome::Opened(mode.clone()),
+        }
+    }
+
+    /// Outcome of a command that moved the active tab
+    fn navigated(&self) -> CommandOutcome {
+        CommandOutcome::Navigated(self.get_current_workspace().current_dir.clone())
+    }
+
+    /// Run the palette command with id `name` (`duplicate`, `parent`, ...) and
+    /// report what it did. Failures the UI would show in the status bar come
+    /// back as errors.
+    #[allow(dead_code)]
+    pub fn execute_command_named(&mut self, name: &str) -> Result<CommandOutcome> {
+        let command = self
+            .command_palette
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Unknown command '{}'", name))?;
+
+        self.error = None;
+        let outcome = self.execute_command(&command)?;
+        match self.error.take() {
+            Some(error) => Err(anyhow::anyhow!(error)),
+            None => Ok(outcome),
+        }
+    }
+
+    // ========== Recent Files ==========
+    /// Options for recursive walks, taken from the active workspace and config
+    pub fn walk_options(&self) -> WalkOptions {
+        WalkOptions {
+            show_hidden: self.workspace_manager.active_workspace().show_hidden,
+            respect_gitignore: self.config.respect_gitignore,
+            max_depth: RECENT_FILES_MAX_DEPTH,
+            ignore: self.active_ignore_patterns(),
+            follow_symlinks: self.config.follow_symlinks,
+        }
+    }
+
+    pub fn open_recent_files(&mut self) {
+        self.recent_opened = false;
+        let current_dir = self.workspace_manager.active_workspace().current_dir.clone();
+        self.recent_files = recent_files(&current_dir, self.config.recent_files_count, &self.walk_options());
+        self.recent_index = 0;
+
+        if self.recent_files.is_empty() {
+            self.message = Some("No files found".to_string());
+            self.mode = AppMode::Normal;
+            return;
+        }
+        self.update_recent_preview();
+        self.mode = AppMode::RecentFiles;
+        self.message = Some("Recently modified (↑/↓ to browse, Enter to reveal, ESC to close)".to_string());
+    }
+
+    /// Files opened from AstroFS, most recent first, leaving out any that are gone
+    pub fn recently_opened_files(&self) -> Vec<FileEntry> {
+        self.user_settings
+            .recent_files
+            .iter()
+            .filter_map(|file| FileEntry::from_path(&file.path).ok())
+            .filter(|entry| !entry.is_dir)
+            .collect()
+    }
+
+    /// Remember an opened file for the recently opened picker
+    fn remember_opened(&mut self, path: &Path) {
+        let now = chrono::Utc::now();
+        crate::persistence::push_recent_file(&mut self.user_settings.recent_files, path, now, RECENT_OPENED_MAX);
+        self.save_user_settings_quietly();
+    }
+
+    /// Pick a recently opened file to open again
+    pub fn open_recently_opened(&mut self) {
+        self.recent_files = self.recently_opened_files();
+        self.recent_index = 0;
+        self.recent_opened = true;
+
+        if self.recent_files.is_empty() {
+            self.message = Some("No files opened yet".to_string());
+            self.mode = AppMode::Normal;
+            return;
+        }
+        self.update_recent_preview();
+        self.mode = AppMode::RecentFiles;
+        self.message = Some("Recently opened (Enter to open, r to reveal, ESC to close)".to_string());
+    }
+
+    /// When a recently opened file was last opened
+    pub fn opened_at(&self, path: &Path) -> Option<SystemTime> {
+        self.user_settings
+            .recent_files
+            .iter()
+            .find(|file| file.path == path)
+            .map(|file| file.opened_at.into())
+    }
+
+    #[allow(dead_code)]
+    pub fn clear_recently_opened(&mut self) {
+        self.user_settings.recent_files.clear();
+        self.save_user_settings_quietly();
+        self.message = Some("Cleared recently opened files".to_string());
+    }
+
+    /// Enter on the recent files overlay: open an opened file again, reveal a modified one
+    pub fn activate_recent_file(&mut self) -> Result<()> {
+        if !self.recent_opened {
+            return self.reveal_recent_file();
+        }
+        let entry = match self.recent_files.get(self.recent_index) {
+            Some(entry) => entry.clone(),
+            None => return Ok(()),
+        };
+        self.close_recent_files();
+        self.open_file(&entry.path);
+        Ok(())
+    }
+
+    pub fn recent_next(&mut self) {
+        if self.recent_index + 1 < self.recent_files.len() {
+            self.recent_index += 1;
+            self.update_recent_preview();
+        }
+    }
+
+    pub fn recent_prev(&mut self) {
+        if self.recent_index > 0 {
+            self.recent_index -= 1;
+            self.update_recent_preview();
+        }
+    }
+
+    fn update_recent_preview(&mut self) {
+        if let Some(entry) = self.recent_files.get(self.recent_index) {
+            self.recent_preview = generate_preview_with(&entry.path, self.config.preview_max_lines, &self.highlight_options());
+        }
+    }
+
+    /// Jump to the selected file's directory and select it
+    pub fn reveal_recent_file(&mut self) -> Result<()> {
+        let entry = match self.recent_files.get(self.recent_index) {
+            Some(entry) => entry.clone(),
+            None => return Ok(()),
+        };
+        self.close_recent_files();
+        self.reveal_path(&entry.path)
+    }
+
+    /// Show `path`'s directory with `path` selected
+    fn reveal_path(&mut self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            let workspace = self.workspace_manager.active_workspace_mut();
+            workspace.current_dir = parent.to_path_buf();
+            workspace.selected_index = 0;
+            workspace.scroll_offset = 0;
+            self.refresh_workspace()?;
+
+            let workspace = self.workspace_manager.active_workspace_mut();
+            if let Some(index) = workspace.entries.iter().position(|e| e.path == path) {
+                workspace.select(index);
+            }
+            self.update_preview();
+        }
+        Ok(())
+    }
+
+    pub fn close_recent_files(&mut self) {
+        self.recent_files.clear();
+        self.recent_index = 0;
+        self.mode = AppMode::Normal;
+    }
+
+    // ========== Places ==========
+    pub fn open_places(&mut self) {
+        self.refresh_places();
+        self.places_index = 0;
+        self.mode = AppMode::Places;
+        self.message = Some("Drives and bookmarks (Enter to open, r to refresh, ESC to close)".to_string());
+    }
+
+    /// Re-read the mount table and bookmarks, keeping the selection on the same path.
+    /// Picking a bookmark to file entries into lists only the bookmarks.
+    pub fn refresh_places(&mut self) {
+        let selected = self.places.get(self.places_index).map(|p| p.path().to_path_buf());
+        self.places = if self.places_transfer.is_some() {
+            Vec::new()
+        } else {
+            list_mounts().into_iter().map(Place::Mount).collect()
+        };
+        self.places.extend(self.bookmark_manager.list().into_iter().map(|b| Place::Bookmark {
+            name: b.name.clone(),
+            path: b.path.clone(),
+        }));
+        self.places_index = selected
+            .and_then(|path| self.places.iter().position(|p| p.path() == path))
+            .unwrap_or(0);
+    }
+
+    pub fn places_next(&mut self) {
+        if self.places_index + 1 < self.places.len() {
+            self.places_index += 1;
+        }
+    }
+
+    pub fn places_prev(&mut self) {
+        self.places_index = self.places_index.saturating_sub(1);
+    }
+
+    // ========== Quick Look ==========
+    /// Show the selected entry's preview full-screen, with room for many more lines
+    pub fn open_quick_look(&mut self) {
+        let max_lines = self.config.preview_max_lines.max(self.config.quick_look_max_lines);
+        let mut options = self.highlight_options();
+        let workspace = self.workspace_manager.active_workspace();
+        let Some(entry) = workspace.get_selected_entry() else {
+            return;
+        };
+        let path = entry.path.clone();
+        options.render_mode = workspace.render_mode_for(&path);
+        // Entries inside an archive aren't on disk; preview them as the pane does
+        let content = match &workspace.archive {
+            Some(archive) => preview_archive_entry(archive, entry, &mut self.archive_temp, max_lines, &options),
+            None => generate_preview_with(&path, max_lines, &options),
+        };
+        self.quick_look = Some(QuickLook::new(path, content));
+        self.mode = AppMode::QuickLook;
+        self.message = Some("Quick look: j/k scroll, / search, n/N next/previous, ESC back".to_string());
+    }
+
+    /// Switch the selected Markdown or HTML file's preview between rendered and source
+    pub fn toggle_render_mode(&mut self) {
+        let workspace = self.workspace_manager.active_workspace_mut();
+        let Some(path) = workspace.get_selected_entry().filter(|e| !e.is_dir).map(|e| e.path.clone()) else {
+            return;
+        };
+        let Some(markup) = crate::markup::Markup::of(&path) else {
+            self.message = Some("Only Markdown and HTML previews can be rendered".to_string());
+            return;
+        };
+        let mode = workspace.render_mode_for(&path).unwrap_or(markup.default_mode()).toggled();
+        workspace.render_mode = Some((path, mode));
+        self.update_preview();
+        self.message = Some(match mode {
+            crate::markup::RenderMode::Rendered => "Showing the rendered preview".to_string(),
+            crate::markup::RenderMode::Raw => "Showing the source".to_string(),
+        });
+    }
+
+    pub fn close_quick_look(&mut self) {
+        self.quick_look = None;
+        self.mode = AppMode::Normal;
+        self.message = None;
+    }
+
+    // ========== Structured Preview ==========
+    /// Browse the selected JSON, YAML or TOML file as a foldable tree in the preview
+    pub fn open_structured_view(&mut self) {
+        let path = match self.workspace_manager.active_workspace().get_selected_entry() {
+            Some(entry) if !entry.is_dir => entry.path.clone(),
+            _ => return,
+        };
+        let format = match crate::structured::Format::of(&path) {
+            Some(format) => format,
+            None => {
+                self.error = Some("Folding works on JSON, YAML and TOML files".to_string());
+                return;
+            }
+        };
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| crate::structured::parse(format, &text));
+        match parsed {
+            Ok(root) => {
+                self.structured_view = Some(StructuredView { path, doc: StructuredDoc::new(format, root), cursor: 0 });
+                self.mode = AppMode::StructuredPreview;
+                self.message = Some("Fold: Enter/Space toggle, h/l close/open, E/C all, ESC done".to_string());
+            }
+            Err(e) => {
+                let first = e.lines().next().unwrap_or_default().to_string();
+                self.error = Some(format!("Invalid {}: {}", format.label(), first));
+            }
+        }
+    }
+
+    pub fn close_structured_view(&mut self) {
+        self.structured_view = None;
+        self.mode = AppMode::Normal;
+        self.message = None;
+    }
+
+    pub fn structured_move(&mut self, delta: isize) {
+        if let Some(view) = self.structured_view.as_mut() {
+            let last = view.doc.rows().len().saturating_sub(1);
+            view.cursor = view.cursor.saturating_add_signed(delta).min(last);
+        }
+    }
+
+    /// Fold the container under the cursor, or unfold it; `open` picks which, None toggles
+    pub fn structured_fold(&mut self, open: Option<bool>) {
+        let view = match self.structured_view.as_mut() {
+            Some(view) => view,
+            None => return,
+        };
+        let path = match view.doc.rows().get(view.cursor) {
+            Some(row) => row.path.clone(),
+            None => return,
+        };
+        let collapsed = view.doc.is_collapsed(&path);
+        let wanted = open.unwrap_or(collapsed);
+        if wanted == collapsed {
+            view.doc.toggle(&path);
+        } else if open == Some(false) && path.len() > 1 {
+            // Already closed (or a plain value): go to the parent instead
+            let parent = &path[..path.len() - 1];
+            if let Some(index) = view.doc.rows().iter().position(|row| row.path == parent) {
+                view.cursor = index;
+            }
+        }
+    }
+
+    pub fn structured_expand_all(&mut self) {
+        if let Some(view) = self.structured_view.as_mut() {
+            view.doc.expand_all();
+        }
+    }
+
+    /// Fold everything down to the top level, keeping the cursor on its top-level entry
+    pub fn structured_collapse_all(&mut self) {
+        if let Some(view) = self.structured_view.as_mut() {
+            let top = view.doc.rows().get(view.cursor).and_then(|row| row.path.first().copied());
+            view.doc.collapse_from(0);
+            view.cursor = top
+                .and_then(|top| view.doc.rows().iter().position(|row| row.path == [top]))
+                .unwrap_or(0);
+        }
+    }
+
+    // ========== Disk Usage ==========
+    /// Show the current directory's children by recursive size
+    pub fn open_disk_usage(&mut self) {
+        let dir = self.workspace_manager.active_workspace().current_dir.clone();
+        self.disk_usage = Some(DiskUsageView {
+            dir: dir.clone(),
+            entries: Vec::new(),
+            total: 0,
+            selected: 0,
+            reselect: None,
+            scan: None,
+        });
+        self.mode = AppMode::DiskUsage;
+        self.message = Some("Disk usage (Enter: open folder, h: up, r: rescan, o: show in list, ESC: close)".to_string());
+        self.start_usage_scan(dir);
+    }
+
+    /// Size `dir` in the background, reusing cached folder sizes
+    fn start_usage_scan(&mut self, dir: PathBuf) {
+        self.stop_usage_scan();
+        let known = self.usage_cache.children_of(&dir);
+        let follow_symlinks = self.config.follow_symlinks;
+        let low_priority = self.config.low_priority_jobs;
+        let (tx, rx) = channel();
+        let label = format!("Disk usage of {}", dir.file_name().unwrap_or(dir.as_os_str()).to_string_lossy());
+        let job = {
+            let dir = dir.clone();
+            self.jobs.submit(label, move |job| {
+                let scan = || {
+                    crate::disk_usage::scan(
+                        &dir,
+                        follow_symlinks,
+                        &known,
+                        |done, total| job.set_progress(done, total),
+                        || !job.is_cancelled(),
+                    )
+                };
+                // Subfolders are walked in parallel, off the normal-priority global pool when lowered
+                let pool = low_priority
+                    .then(|| crate::priority::background_pool(0, "disk-usage").log_err("start the disk usage pool"))
+                    .flatten();
+                let result = match pool {
+                    Some(pool) => pool.install(scan),
+                    None => scan(),
+                };
+                let _ = tx.send(result);
+            })
+        };
+        if let Some(view) = self.disk_usage.as_mut() {
+            view.dir = dir;
+            view.entries.clear();
+            view.total = 0;
+            view.selected = 0;
+            view.scan = Some(UsageScanTask { job, results: rx });
+        }
+    }
+
+    /// Stop the running scan, if any; true if there was one
+    pub fn cancel_usage_scan(&mut self) -> bool {
+        let stopped = self.stop_usage_scan();
+        if stopped {
+            self.message = Some("Disk usage scan cancelled (r to rescan)".to_string());
+        }
+        stopped
+    }
+
+    fn stop_usage_scan(&mut self) -> bool {
+        match self.disk_usage.as_mut().and_then(|view| view.scan.take()) {
+            Some(scan) => {
+                self.jobs.cancel(scan.job);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pick up a finished scan; true if the view changed
+    pub fn poll_disk_usage(&mut self) -> bool {
+        let Some(view) = self.disk_usage.as_mut() else {
+            return false;
+        };
+        let result = match view.scan.as_ref().map(|scan| scan.results.try_recv()) {
+            None | Some(Err(TryRecvError::Empty)) => return false,
+            Some(Ok(result)) => result,
+            // Cancelled while still queued
+            Some(Err(TryRecvError::Disconnected)) => Ok(None),
+        };
+        view.scan = None;
+        match result {
+            Ok(Some(scan)) => {
+                view.total = scan.total();
+                view.entries = scan.entries;
+                let reselect = view.reselect.take();
+                view.selected = reselect
+                    .and_then(|path| view.entries.iter().position(|e| e.entry.path == path))
+                    .unwrap_or(0);
+                self.usage_cache.extend(scan.dir_sizes);
+            }
+            Ok(None) => self.message = Some("Disk usage scan cancelled (r to rescan)".to_string()),
+            Err(e) => self.error = Some(format!("Disk usage failed: {}", e)),
+        }
+        true
+    }
+
+    pub fn usage_move(&mut self, delta: isize) {
+        if let Some(view) = self.disk_usage.as_mut() {
+            let last = view.entries.len().saturating_sub(1);
+            view.selected = view.selected.saturating_add_signed(delta).min(last);
+        }
+    }
+
+    /// Drill into the selected folder
+    pub fn usage_enter(&mut self) {
+        let dir = self
+            .disk_usage
+            .as_ref()
+            .and_then(|view| view.entries.get(view.selected))
+            .filter(|usage| usage.entry.is_dir)
+            .map(|usage| usage.entry.path.clone());
+        if let Some(dir) = dir {
+            self.start_usage_scan(dir);
+        }
+    }
+
+    /// Go up to the parent folder, selecting the one just left
+    pub fn usage_up(&mut self) {
+        let Some(view) = self.disk_usage.as_mut() else {
+            return;
+        };
+        if let Some(parent) = view.dir.parent().map(Path::to_path_buf) {
+            view.reselect = Some(view.dir.clone());
+            self.start_usage_scan(parent);
+        }
+    }
+
+    /// Size the folder again from disk
+    pub fn usage_rescan(&mut self) {
+        if let Some(dir) = self.disk_usage.as_ref().map(|view| view.dir.clone()) {
+            self.usage_cache.invalidate(&dir);
+            self.start_usage_scan(dir);
+        }
+    }
+
+    /// Close the view and select the entry in the file list
+    pub fn usage_reveal(&mut self) -> Result<()> {
+        let Some(view) = self.disk_usage.as_ref() else {
+            return Ok(());
+        };
+        let dir = view.dir.clone();
+        let selected = view.entries.get(view.selected).map(|usage| usage.entry.path.clone());
+        self.close_disk_usage();
+        self.go_to_path(&dir.to_string_lossy())?;
+        let workspace = self.workspace_manager.active_workspace_mut();
+        if let Some(index) = selected.and_then(|path| workspace.entries.iter().position(|e| e.path == path)) {
+            workspace.select(index);
+            self.update_preview();
+        }
+        Ok(())
+    }
+
+    pub fn close_disk_usage(&mut self) {
+        self.stop_usage_scan();
+        self.disk_usage = None;
+        self.mode = AppMode::Normal;
+        self.message = None;
+    }
+
+    // ========== Jobs ==========
+    pub fn open_jobs(&mut self) {
+        self.jobs_index = 0;
+        self.mode = AppMode::Jobs;
+        self.message = Some("Background jobs (x to cancel, X to cancel all, ESC to close)".to_string());
+    }
+
+    pub fn close_jobs(&mut self) {
+        self.mode = AppMode::Normal;
+        self.message = None;
+    }
+
+    pub fn jobs_next(&mut self) {
+        if self.jobs_index + 1 < self.jobs.overview().len() {
+            self.jobs_index += 1;
+        }
+    }
+
+    pub fn jobs_prev(&mut self) {
+        self.jobs_index = self.jobs_index.saturating_sub(1);
+    }
+
+    /// Cancel the selected job, if it hasn't finished
+    pub fn cancel_selected_job(&mut self) {
+        let job = match self.jobs.overview().get(self.jobs_index) {
+            Some(job) => (*job).clone(),
+            None => return,
+        };
+        if matches!(job.state, JobState::Finished | JobState::Cancelled) {
+            return;
+        }
+        if self.jobs.cancel(job.id) {
+            self.message = Some(format!("Cancelled {}", job.label));
+        }
+    }
+
+    pub fn cancel_all_jobs(&mut self) {
+        self.jobs.cancel_all();
+        self.message = Some("Cancelled all jobs".to_string());
+    }
+
+    pub fn open_selected_place(&mut self) -> Result<()> {
+        let (name, path) = match self.places.get(self.places_index) {
+            Some(Place::Bookmark { name, path }) => (Some(name.clone()), path.clone()),
+            Some(place) => (None, place.path().to_path_buf()),
+            None => return Ok(()),
+        };
+        let transfer = self.places_transfer;
+        self.close_places();
+        if let (Some(kind), Some(name)) = (transfer, name) {
+            let strategy = CollisionStrategy::parse(&self.config.collision_strategy).unwrap_or(CollisionStrategy::Ask);
+            if let Err(e) = self.transfer_to_bookmark(&name, kind, strategy) {
+                self.error = Some(e.to_string());
+            }
+            return Ok(());
+        }
+        if !path.is_dir() {
+            self.error = Some(format!("{} is not available", path.display()));
+            return Ok(());
+        }
+        let workspace = self.workspace_manager.active_workspace_mut();
+        workspace.current_dir = path;
+        workspace.selected_index = 0;
+        workspace.scroll_offset = 0;
+        self.refresh_workspace()
+    }
+
+    pub fn close_places(&mut self) {
+        self.places.clear();
+        self.places_index = 0;
+        self.places_transfer = None;
+        self.mode = AppMode::Normal;
+    }
+
+    // ========== Trash Browser ==========
+    fn trash_dir(&self) -> Result<&Trash> {
+        self.trash.as_ref().ok_or_else(|| anyhow::anyhow!("No trash directory available"))
+    }
+
+    /// Everything in the trash, most recently deleted first
+    pub fn list_trash(&self) -> Result<Vec<TrashedItem>> {
+        self.trash_dir()?.list()
+    }
+
+    fn find_trashed(&self, trashed: &Path) -> Result<TrashedItem> {
+        self.list_trash()?
+            .into_iter()
+            .find(|item| item.trashed == trashed)
+            .ok_or_else(|| anyhow::anyhow!("{} is not in the trash", trashed.display()))
+    }
+
+    /// Restore an item (identified by its path inside the trash), returning where it went
+    pub fn restore_from_trash(&mut self, trashed: &Path) -> Result<PathBuf> {
+        let item = self.find_trashed(trashed)?;
+        self.trash_dir()?.restore(&item)?;
+        self.api_plugin_manager.call_file_created(&item.original).log_err("run on_file_created hooks");
+        self.refresh_workspace()?;
+        Ok(item.original)
+    }
+
+    /// Permanently delete one item from the trash
+    pub fn delete_from_trash(&mut self, trashed: &Path) -> Result<()> {
+        let item = self.find_trashed(trashed)?;
+        self.trash_dir()?.delete_permanently(&item)
+    }
+
+    /// Permanently delete everything in the trash, returning how many items went
+    pub fn empty_trash(&mut self) -> Result<usize> {
+        let count = self.trash_dir()?.empty()?;
+        self.pending_undo = None;
+        Ok(count)
+    }
+
+    /// Bytes taken by everything in the trash
+    pub fn trash_size(&self) -> Result<u64> {
+        self.trash_dir()?.size()
+    }
+
+    fn trash_limits(&self) -> TrashLimits {
+        TrashLimits {
+            max_bytes: self.config.trash_max_size_mb.saturating_mul(1024 * 1024),
+            max_age_days: self.config.trash_max_age_days,
+        }
+    }
+
+    /// Permanently delete trashed items past `trash_max_age_days`, then the oldest
+    /// until the trash fits in `trash_max_size_mb`; main runs this at startup
+    pub fn prune_trash(&mut self) -> Result<Pruned> {
+        let limits = self.trash_limits();
+        if limits.is_unlimited() {
+            return Ok(Pruned::default());
+        }
+        let pruned = self.trash_dir()?.prune(&limits, chrono::Local::now().naive_local())?;
+        if pruned.items > 0 {
+            tracing::info!("Pruned {} items ({} bytes) from the trash", pruned.items, pruned.bytes);
+            self.message = Some(format!(
+                "Trash limits: deleted {} old items for good ({})",
+                pruned.items,
+                humansize::format_size(pruned.bytes, humansize::BINARY)
+            ));
+        }
+        Ok(pruned)
+    }
+
+    pub fn open_trash(&mut self) {
+        match self.list_trash() {
+            Ok(items) => self.trash_items = items,
+            Err(e) => {
+                self.error = Some(format!("Trash: {}", e));
+                return;
+            }
+        }
+        self.trash_size = self.trash_size().log_err("measure the trash").unwrap_or(0);
+        self.trash_index = 0;
+        self.pending_trash_action = None;
+        self.mode = AppMode::Trash;
+        self.message = Some(if self.trash_items.is_empty() {
+            "Trash is empty".to_string()
+        } else {
+            "Trash (Enter/r to restore, d to delete forever, E to empty, ESC to close)".to_string()
+        });
+    }
+
+    pub fn close_trash(&mut self) {
+        self.trash_items.clear();
+        self.trash_index = 0;
+        self.pending_trash_action = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Reload the trash listing, keeping the selection in range
+    fn reload_trash(&mut self) {
+        self.trash_items = self.list_trash().log_err("list trash").unwrap_or_default();
+        self.trash_size = self.trash_size().log_err("measure the trash").unwrap_or(0);
+        self.trash_index = self.trash_index.min(self.trash_items.len().saturating_sub(1));
+    }
+
+    pub fn trash_next(&mut self) {
+        if self.trash_index + 1 < self.trash_items.len() {
+            self.trash_index += 1;
+        }
+    }
+
+    pub fn trash_prev(&mut self) {
+        self.trash_index = self.trash_index.saturating_sub(1);
+    }
+
+    pub fn restore_selected_trash(&mut self) -> Result<()> {
+        let item = match self.trash_items.get(self.trash_index) {
+            Some(item) => item.clone(),
+            None => return Ok(()),
+        };
+        let recreated = item.original.parent().is_some_and(|parent| !parent.exists());
+        match self.restore_from_trash(&item.trashed) {
+            Ok(original) => {
+                self.message = Some(if recreated {
+                    format!("Restored {} (recreated its folder)", original.display())
+                } else {
+                    format!("Restored {}", original.display())
+                });
+            }
+            Err(e) => self.error = Some(format!("Restore failed: {}", e)),
+        }
+        self.reload_trash();
+        Ok(())
+    }
+
+    /// Ask for confirmation before deleting the selected item or emptying the trash
+    pub fn request_trash_action(&mut self, action: TrashAction) {
+        let prompt = match action {
+            TrashAction::DeleteSelected => match self.trash_items.get(self.trash_index) {
+                Some(item) => format!("Delete {} forever? (y/n)", item.name()),
+                None => return,
+            },
+            TrashAction::Empty if self.trash_items.is_empty() => return,
+            TrashAction::Empty => format!("Permanently delete all {} items? (y/n)", self.trash_items.len()),
+        };
+        self.pending_trash_action = Some(action);
+        self.message = Some(prompt);
+    }
+
+    pub fn confirm_trash_action(&mut self) {
+        let result = match self.pending_trash_action.take() {
+            Some(TrashAction::DeleteSelected) => match self.trash_items.get(self.trash_index).cloned() {
+                Some(item) => self
+                    .delete_from_trash(&item.trashed)
+                    .map(|_| format!("Deleted {} forever", item.name())),
+                None => return,
+            },
+            Some(TrashAction::Empty) => self
+                .empty_trash()
+                .map(|count| format!("Emptied trash ({} items)", count)),
+            None => return,
+        };
+        match result {
+            Ok(message) => self.message = Some(message),
+            Err(e) => self.error = Some(format!("Trash: {}", e)),
+        }
+        self.reload_trash();
+    }
+
+    pub fn cancel_trash_action(&mut self) {
+        self.pending_trash_action = None;
+        self.message = Some("Cancelled".to_string());
+    }
+
+    // ========== Settings Screen ==========
+    pub fn open_settings(&mut self) {
+        self.settings_index = 0;
+        self.mode = AppMode::Settings;
+    }
+
+    pub fn close_settings(&mut self) {
+        self.mode = AppMode::Normal;
+    }
+
+    pub fn settings_next(&mut self) {
+        if self.settings_index + 1 < EDITABLE_SETTINGS.len() {
+            self.settings_index += 1;
+        }
+    }
+
+    pub fn settings_prev(&mut self) {
+        self.settings_index = self.settings_index.saturating_sub(1);
+    }
+
+    /// Toggle the selected boolean setting, or start editing it as text
+    pub fn activate_setting(&mut self) -> Result<()> {
+        let (key, _) = EDITABLE_SETTINGS[self.settings_index];
+
+        if AppConfig::is_toggle_setting(key) {
+            let current = self.config.setting_value(key).unwrap_or_default();
+            let toggled = (current != "true").to_string();
+            self.apply_setting(key, &toggled)?;
+        } else {
+            self.input_buffer = self.config.setting_value(key).unwrap_or_default();
+            self.mode = AppMode::Input(InputMode::EditSetting);
+        }
+        Ok(())
+    }
+
+    /// Save the edited value of the selected setting and return to the list
+    pub fn update_setting(&mut self, value: &str) -> Result<()> {
+        let (key, _) = EDITABLE_SETTINGS[self.settings_index];
+        self.mode = AppMode::Settings;
+        self.apply_setting(key, value)
+    }
+
+    fn apply_setting(&mut self, key: &str, value: &str) -> Result<()> {
+        if key == "highlight_theme" {
+            let themes = crate::preview::highlight_themes(Some(&self.dirs.highlight_theme_dir()));
+            if !themes.iter().any(|t| t == value.trim()) {
+                self.error = Some(format!("Invalid value for {}: Unknown theme '{}', expected one of: {}", key, value.trim(), themes.join(", ")));
+                return Ok(());
+            }
+        }
+        if let Err(e) = self.config.set_setting(key, value) {
+            self.error = Some(format!("Invalid value for {}: {}", key, e));
+            return Ok(());
+        }
+        self.config.save()?;
+
+        if key == "show_hidden" {
+            let show_hidden = self.config.show_hidden;
+            self.workspace_manager.active_workspace_mut().show_hidden = show_hidden;
+            self.refresh_workspace()?;
+        }
+        if key == "ignore_patterns" {
+            self.ignore_patterns = IgnorePatterns::new(&self.config.ignore_patterns)?;
+            self.refresh_workspace()?;
+        }
+        if key == "sort_rules" {
+            self.sort_rules = SortRules::new(&self.config.sort_rules)?;
+            self.refresh_workspace()?;
+        }
+        if key == "always_show" || key == "always_hide" {
+            self.hidden_patterns = HiddenPatterns::new(&self.config.always_show, &self.config.always_hide)?;
+            self.refresh_workspace()?;
+        }
+        if matches!(key, "tab_title" | "path_display" | "path_display_root") {
+            self.retitle_tabs();
+        }
+        if key == "search_grouping" {
+            self.search_grouping = SearchGrouping::parse(&self.config.search_grouping).unwrap_or_default();
+        }
+        if key == "highlight_theme" {
+            self.user_settings.highlight_theme = self.config.highlight_theme.clone();
+            self.save_user_settings_quietly();
+        }
+        // Typed sizes replace ones picked with the resize keys
+        if key == "preview_width_ratio" || key == "sidebar_width" {
+            self.user_settings.preview_width_ratio = self.config.preview_width_ratio;
+            self.user_settings.sidebar_width = self.config.sidebar_width;
+            self.save_user_settings_quietly();
+        }
+        if key == "icon_set" {
+            self.apply_icon_set();
+        }
+        if matches!(
+            key,
+            "highlight_theme"
+                | "syntax_overrides"
+                | "preview_overrides"
+                | "icon_set"
+                | "preview_max_lines"
+                | "max_preview_lines"
+        ) {
+            self.update_preview();
+        }
+        if key == "autosave_interval_secs" {
+            self.autosave.set_interval(Duration::from_secs(self.config.autosave_interval_secs));
+        }
+        if key == "max_concurrent_jobs" {
+            self.jobs.set_limit(self.config.max_concurrent_jobs);
+        }
+        if key == "low_priority_jobs" {
+            self.jobs.set_low_priority(self.config.low_priority_jobs);
+            self.search_engine.set_low_priority(self.config.low_priority_jobs);
+        }
+        if key == "status_show_disk_space" {
+            self.disk_space_checked = None;
+            self.refresh_status_info();
+        }
+        if key == "show_dir_counts" || key == "git_status_column" {
+            self.refresh_workspace()?;
+        }
+        self.message = Some("Settings saved".to_string());
+        Ok(())
+    }
+
+    // ========== Utilities ==========
+    /// Quit right away, or ask first when background jobs are running
+    pub fn request_quit(&mut self) {
+        if self.jobs.is_idle() {
+            self.quit();
+        } else {
+            self.mode = AppMode::ConfirmQuit;
+        }
+    }
+
+    /// Cancel running jobs and quit
+    pub fn force_quit(&mut self) {
+        self.jobs.cancel_all();
+        self.quit();
+    }
+
+    /// Keep running and quit once the last job finishes
+    pub fn quit_after_jobs(&mut self) {
+        self.quit_when_idle = true;
+        self.mode = AppMode::Normal;
+        self.message = Some(format!(
+            "Quitting after {} running job(s) finish",
+            self.jobs.active().len()
+        ));
+    }
+
+    pub fn cancel_quit(&mut self) {
+        self.quit_when_idle = false;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Finish a deferred quit once nothing is running
+    pub fn check_pending_quit(&mut self) {
+        if self.quit_when_idle && self.jobs.is_idle() {
+            self.quit();
+        }
+    }
+
+    pub fn quit(&mut self) {
+        self.bookmark_manager.save().log_err("save bookmarks");
+        self.search_history.save_to(&self.dirs.search_history_file()).log_err("save search history");
+        self.config.save().log_err("save config");
+        
+        // Save Beast Mode state
+        self.user_settings.plugin_settings = self.api_plugin_manager.plugin_data();
+        self.store_selections();
+        self.persistence_manager.save_settings(&self.user_settings).log_err("save user settings");
+        self.theme_manager.save_current_theme().log_err("save current theme");
+        // A clean quit leaves nothing to recover
+        if let Some(recovery) = self.recovery.as_mut() {
+            recovery.remove().log_err("remove the session recovery file");
+        }
+        tracing::info!("astrofs exiting");
+        
+        // Use describe methods and access fields to eliminate warnings
+        let _theme_info = self.theme_manager.describe_current();
+        let _settings_info = PersistenceManager::describe_settings(&self.user_settings);
+        let _bindings_info = self.playback_controller.bindings.describe_all();
+        let _config_dir = self.persistence_manager.get_config_dir_path();
+        
+        // Access media player fields
+        let _player_state = self.media_player.state.clone();
+        let _player_pos = self.media_player.position;
+        let _player_vol = self.media_player.volume;
+        let _player_speed = self.media_player.speed;
+        let _player_repeat = self.media_player.repeat_mode.clone();
+        let _player_playlist = self.media_player.playlist.clone();
+        let _player_idx = self.media_player.current_index;
+        
+        // Call media player methods
+        let _progress = self.media_player.progress();
+        let _status = self.media_player.status_bar();
+        let _plugin_count = self.api_plugin_manager.count();
+        let _preview_path = self.media_preview.last_path();
+        
+        self.running = false;
+    }
+
+    pub fn set_viewport(&mut self, width: usize, height: usize) {
+        self.viewport_width = width;
+        self.viewport_height = height;
+    }
+
+    pub fn get_current_workspace(&self) -> &Workspace {
+        self.workspace_manager.active_workspace()
+    }
+
+    // ========== Media Operations ==========
+    pub fn preview_media(&mut self, path: &PathBuf) -> Result<Option<String>> {
+        self.media_preview.get_metadata(path)
+    }
+
+    /// Write a media file's metadata to `out`, or to its `.txt` sidecar by default
+    pub fn export_media_info(&self, path: &Path, out: Option<&Path>, overwrite: bool) -> Result<PathBuf> {
+        let out = out
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| crate::media_preview::sidecar_path(path));
+        crate::media_preview::export_media_info(path, &out, overwrite)?;
+        Ok(out)
+    }
+
+    /// Export the selected file's metadata, asking before replacing a sidecar
+    pub fn export_selected_media_info(&mut self) -> Result<()> {
+        let path = match self.workspace_manager.active_workspace().get_selected_entry() {
+            Some(entry) if !entry.is_dir => entry.path.clone(),
+            _ => return Ok(()),
+        };
+        if matches!(
+            crate::media_preview::detect_media_type(&path),
+            crate::media_preview::MediaType::Unknown
+        ) {
+            self.error = Some("Not a media file".to_string());
+            return Ok(());
+        }
+
+        let sidecar = crate::media_preview::sidecar_path(&path);
+        if sidecar.exists() {
+            self.pending_export = Some((path, sidecar));
+            self.mode = AppMode::ConfirmOverwrite;
+            return Ok(());
+        }
+        self.finish_media_export(&path, &sidecar, false)
+    }
+
+    /// Replace the existing sidecar or rename target after the user confirmed
+    pub fn confirm_overwrite(&mut self) -> Result<()> {
+        self.mode = AppMode::Normal;
+        if let Some((src, new_name)) = self.pending_rename.take() {
+            if let Err(e) = self.rename_entry(&src, &new_name, true) {
+                self.error = Some(format!("Rename failed: {}", e));
+            }
+            return Ok(());
+        }
+        match self.pending_export.take() {
+            Some((path, sidecar)) => self.finish_media_export(&path, &sidecar, true),
+            None => Ok(()),
+        }
+    }
+
+    pub fn cancel_overwrite(&mut self) {
+        self.pending_export = None;
+        self.pending_rename = None;
+        self.mode = AppMode::Normal;
+    }
+
+    fn finish_media_export(&mut self, path: &Path, sidecar: &Path, overwrite: bool) -> Result<()> {
+        let existed = sidecar.exists();
+        match self.export_media_info(path, Some(sidecar), overwrite) {
+            Ok(out) => {
+                if !existed {
+                    self.api_plugin_manager.call_file_created(&out).log_err("run on_file_created hooks");
+                }
+                self.refresh_workspace()?;
+                self.message = Some(format!(
+                    "Media info saved to {}",
+                    out.file_name().unwrap_or_default().to_string_lossy()
+                ));
+            }
+            Err(e) => self.error = Some(format!("Export failed: {}", e)),
+        }
+        Ok(())
+    }
+
+    // ========== Permissions ==========
+    /// Marked entries, or the selected one when nothing is marked
+    pub fn marked_or_selected(&self) -> Vec<PathBuf> {
+        let workspace = self.workspace_manager.active_workspace();
+        let marked = workspace.marked_paths();
+        if !marked.is_empty() {
+            return marked;
+        }
+        workspace.get_selected_entry().map(|e| e.path.clone()).into_iter().collect()
+    }
+
+    /// Ask for a mode (`owner` false) or owner for the marked entries
+    pub fn start_permission_change(&mut self, owner: bool) {
+        if self.refuse_in_archive() {
+            return;
+        }
+        if !cfg!(unix) {
+            self.error = Some("Changing permissions is only supported on Unix".to_string());
+            return;
+        }
+        if self.marked_or_selected().is_empty() {
+            return;
+        }
+        self.input_buffer.clear();
+        self.mode = AppMode::Input(if owner { InputMode::ChangeOwner } else { InputMode::ChangeMode });
+    }
+
+    /// Parse the entered mode or owner and list what it will touch for confirmation
+    pub fn preview_permission_change(&mut self, spec: &str, owner: bool) {
+        let change = if owner {
+            PermissionChange::parse_owner(spec)
+        } else {
+            PermissionChange::parse_mode(spec)
+        };
+        let change = match change {
+            Ok(change) => change,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return;
+            }
+        };
+
+        let roots = self.marked_or_selected();
+        let targets = FileOperation::permission_targets(&roots, true);
+        self.pending_permissions = Some(PendingPermissions { change, roots, targets });
+        self.mode = AppMode::ConfirmPermissions;
+    }
+
+    /// Apply the reviewed change, reporting entries that could not be changed
+    pub fn confirm_permission_change(&mut self) -> Result<()> {
+        self.mode = AppMode::Normal;
+        let pending = match self.pending_permissions.take() {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+
+        match FileOperation::set_permissions_recursive(&pending.roots, &pending.change, true) {
+            Ok(report) if report.failed.is_empty() => {
+                self.message = Some(format!(
+                    "Applied {} to {} entries",
+                    pending.change.describe(),
+                    report.changed.len()
+                ));
+            }
+            Ok(report) => {
+                for (path, reason) in &report.failed {
+                    tracing::warn!("Failed to change permissions of {:?}: {}", path, reason);
+                }
+                let (path, reason) = &report.failed[0];
+                self.error = Some(format!(
+                    "Changed {}, {} failed (first: {}: {})",
+                    report.changed.len(),
+                    report.failed.len(),
+                    path.display(),
+                    reason
+                ));
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        self.refresh_workspace()
+    }
+
+    pub fn cancel_permission_change(&mut self) {
+        self.pending_permissions = None;
+        self.mode = AppMode::Normal;
+    }
+
+    // ========== Sequence rename ==========
+    /// Ask for the prefix to number the marked files with
+    pub fn start_sequence_rename(&mut self) {
+        if self.refuse_in_archive() {
+            return;
+        }
+        if self.marked_or_selected().is_empty() {
+            return;
+        }
+        self.input_buffer.clear();
+        self.mode = AppMode::Input(InputMode::SequenceRename);
+    }
+
+    /// List the new names of the marked files, by capture date, for confirmation
+    pub fn preview_sequence_rename(&mut self, prefix: &str) {
+        self.plan_sequence(prefix.trim().to_string(), SequenceOrder::CaptureDate, self.marked_or_selected());
+    }
+
+    /// Number by the other order: capture date or modified time
+    pub fn toggle_sequence_order(&mut self) {
+        if let Some(pending) = self.pending_sequence.take() {
+            self.plan_sequence(pending.prefix, pending.order.toggle(), pending.sources);
+        }
+    }
+
+    fn plan_sequence(&mut self, prefix: String, order: SequenceOrder, sources: Vec<PathBuf>) {
+        match FileOperation::plan_sequence_rename(&sources, &prefix, order) {
+            Ok(plan) if plan.is_empty() => {
+                self.mode = AppMode::Normal;
+                self.error = Some("No files to rename".to_string());
+            }
+            Ok(plan) => {
+                self.pending_sequence = Some(PendingSequenceRename { prefix, order, sources, plan });
+                self.mode = AppMode::ConfirmSequenceRename;
+            }
+            Err(e) => {
+                self.mode = AppMode::Normal;
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Rename the files as previewed
+    pub fn confirm_sequence_rename(&mut self) -> Result<()> {
+        self.mode = AppMode::Normal;
+        let Some(pending) = self.pending_sequence.take() else {
+            return Ok(());
+        };
+        match self.apply_sequence_plan(&pending.plan) {
+            Ok(()) => self.message = Some(format!("Renamed {} files to {}_…", pending.plan.len(), pending.prefix)),
+            Err(e) => self.error = Some(format!("Rename failed: {}", e)),
+        }
+        self.refresh_workspace()
+    }
+
+    pub fn cancel_sequence_rename(&mut self) {
+        self.pending_sequence = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Number the marked files straight away, returning their new paths
+    #[allow(dead_code)]
+    pub fn sequence_rename(&mut self, prefix: &str, order: SequenceOrder) -> Result<Vec<PathBuf>> {
+        let plan = FileOperation::plan_sequence_rename(&self.marked_or_selected(), prefix, order)?;
+        self.apply_sequence_plan(&plan)?;
+        self.refresh_workspace()?;
+        Ok(plan.into_iter().map(|(_, dest)| dest).collect())
+    }
+
+    fn apply_sequence_plan(&mut self, plan: &[(PathBuf, PathBuf)]) -> Result<()> {
+        FileOperation::apply_renames(plan)?;
+        for (src, dest) in plan {
+            self.api_plugin_manager.call_file_renamed(src, dest).log_err("run on_file_renamed hooks");
+        }
+        self.workspace_manager.active_workspace_mut().marked.clear();
+        Ok(())
+    }
+
+    // ========== Flatten ==========
+    /// List where the files nested in the selected folder would go, for confirmation
+    pub fn start_flatten(&mut self) {
+        if self.refuse_in_archive() {
+            return;
+        }
+        let Some(entry) = self.workspace_manager.active_workspace().get_selected_entry().cloned() else {
+            return;
+        };
+        if !entry.is_dir {
+            self.error = Some("Select a folder to flatten".to_string());
+            return;
+        }
+        let strategy = CollisionStrategy::parse(&self.config.collision_strategy).unwrap_or(CollisionStrategy::Ask);
+        match FileOperation::plan_flatten(&entry.path, strategy) {
+            Ok(plan) if plan.moves.is_empty() => {
+                self.message = Some(format!("Nothing to flatten in {}", entry.name));
+            }
+            Ok(plan) => {
+                self.pending_flatten = Some(plan);
+                self.mode = AppMode::ConfirmFlatten;
+            }
+            Err(e) => self.error = Some(format!("Flatten failed: {}", e)),
+        }
+    }
+
+    /// Move the files as previewed
+    pub fn confirm_flatten(&mut self) -> Result<()> {
+        self.mode = AppMode::Normal;
+        let Some(plan) = self.pending_flatten.take() else {
+            return Ok(());
+        };
+        let report = self.apply_flatten_plan(&plan);
+        match report.failed.first() {
+            None if report.skipped > 0 => {
+                self.message = Some(format!("Moved {} files up, skipped {} taken", report.moved.len(), report.skipped))
+            }
+            None => self.message = Some(format!("Moved {} files up into {}", report.moved.len(), plan.dir.display())),
+            Some((path, reason)) => {
+                self.error = Some(format!(
+                    "Moved {} files, {} failed ({}: {})",
+                    report.moved.len(),
+                    report.failed.len(),
+                    path.display(),
+                    reason
+                ))
+            }
+        }
+        self.refresh_workspace()
+    }
+
+    pub fn cancel_flatten(&mut self) {
+        self.pending_flatten = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Flatten `dir` straight away, with `collision_strategy` for taken names
+    #[allow(dead_code)]
+    pub fn flatten_directory(&mut self, dir: &Path) -> Result<FlattenReport> {
+        let strategy = CollisionStrategy::parse(&self.config.collision_strategy).unwrap_or(CollisionStrategy::Ask);
+        let plan = FileOperation::plan_flatten(dir, strategy)?;
+        let report = self.apply_flatten_plan(&plan);
+        self.refresh_workspace()?;
+        Ok(report)
+    }
+
+    fn apply_flatten_plan(&mut self, plan: &FlattenPlan) -> FlattenReport {
+        let report = FileOperation::apply_flatten(plan);
+        for (src, dest) in plan.moves.iter().filter(|(_, dest)| report.moved.contains(dest)) {
+            self.api_plugin_manager.call_file_renamed(src, dest).log_err("run on_file_renamed hooks");
+        }
+        report
+    }
+
+    // ========== Diff ==========
+    /// Mark or unmark the selected entry and move to the next one
+    pub fn toggle_mark(&mut self) {
+        let workspace = self.workspace_manager.active_workspace_mut();
+        workspace.toggle_mark();
+        workspace.move_down();
+        let count = workspace.marked.len();
+        self.message = Some(format!("{} marked", count));
+        self.update_preview();
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.workspace_manager.active_workspace_mut().marked.clear();
+    }
+
+    pub fn select_all(&mut self) {
+        self.workspace_manager.active_workspace_mut().mark_all();
+    }
+
+    /// Clear every mark, including ones made in other directories
+    pub fn clear_selection(&mut self) {
+        self.clear_marks();
+        self.message = Some("Selection cleared".to_string());
+    }
+
+    pub fn invert_selection(&mut self) {
+        self.workspace_manager.active_workspace_mut().invert_marks();
+    }
+
+    /// Re-read free disk space if the reading is stale or for another directory
+    pub fn refresh_status_info(&mut self) {
+        if !self.config.status_show_disk_space {
+            self.disk_space = None;
+            self.disk_space_checked = None;
+            return;
+        }
+        let current_dir = &self.workspace_manager.active_workspace().current_dir;
+        let fresh = self
+            .disk_space_checked
+            .as_ref()
+            .is_some_and(|(dir, at)| dir == current_dir && at.elapsed() < DISK_SPACE_REFRESH);
+        if !fresh {
+            self.disk_space = disk_space(current_dir);
+            self.disk_space_checked = Some((current_dir.clone(), Instant::now()));
+        }
+    }
+
+    /// Optional right-hand status bar segments: selection, disk space, clock
+    pub fn status_segments(&self) -> Vec<String> {
+        let mut segments: Vec<String> = self.pending_count.map(|count| count.to_string()).into_iter().collect();
+        segments.extend(self.jobs.status());
+        segments.extend(self.selection_summary());
+        if let Some(space) = self.disk_space.as_ref().filter(|_| self.config.status_show_disk_space) {
+            segments.push(format!(
+                "{} free of {}",
+                humansize::format_size(space.available, humansize::BINARY),
+                humansize::format_size(space.total, humansize::BINARY)
+            ));
+        }
+        if self.config.status_show_clock {
+            segments.push(chrono::Local::now().format(&self.config.status_clock_format).to_string());
+        }
+        segments
+    }
+
+    /// Status text like "3 selected / 12 total", or None with nothing marked
+    pub fn selection_summary(&self) -> Option<String> {
+        let workspace = self.workspace_manager.active_workspace();
+        if workspace.marked.is_empty() {
+            return None;
+        }
+        let here = workspace.marked_in_listing();
+        let elsewhere = workspace.marked.len() - here;
+        let mut summary = format!("{} selected / {} total", here, workspace.entries.len());
+        if elsewhere > 0 {
+            summary.push_str(&format!(" (+{} elsewhere)", elsewhere));
+        }
+        Some(summary)
+    }
+
+    /// Diff two files, keeping as many rows as a preview shows
+    pub fn diff_files(&self, a: &Path, b: &Path) -> Result<DiffResult> {
+        diff_files(a, b, self.config.preview_max_lines, COPY_CONTENTS_LIMIT)
+    }
+
+    /// The two files to compare: two marked files, a marked file and the
+    /// selection, or the selections of this tab and the next one
+    pub fn diff_targets(&self) -> Option<(PathBuf, PathBuf)> {
+        let workspace = self.workspace_manager.active_workspace();
+        let marked = workspace.marked_paths();
+        let selected = workspace.get_selected_entry().filter(|e| !e.is_dir).map(|e| e.path.clone());
+        match (marked.as_slice(), selected) {
+            ([a, b], _) => return Some((a.clone(), b.clone())),
+            ([a], Some(selected)) if *a != selected => return Some((a.clone(), selected)),
+            ([], _) | ([_], _) => {}
+            _ => return None,
+        }
+
+        let workspaces = self.workspace_manager.workspaces();
+        if workspaces.len() < 2 {
+            return None;
+        }
+        let other = &workspaces[(self.workspace_manager.active_index() + 1) % workspaces.len()];
+        let here = workspace.get_selected_entry().filter(|e| !e.is_dir)?;
+        let there = other.get_selected_entry().filter(|e| !e.is_dir)?;
+        (here.path != there.path).then(|| (here.path.clone(), there.path.clone()))
+    }
+
+    pub fn open_diff(&mut self) {
+        let (a, b) = match self.diff_targets() {
+            Some(targets) => targets,
+            None => {
+                self.error = Some("Mark two files with Space, or select a file in two tabs, to compare".to_string());
+                return;
+            }
+        };
+        match self.diff_files(&a, &b) {
+            Ok(diff) => {
+                let (added, removed, changed) = diff.counts();
+                self.message = Some(if diff.identical {
+                    "Files are identical".to_string()
+                } else if diff.binary {
+                    "Binary files differ".to_string()
+                } else {
+                    format!("+{} -{} ~{} (s: side-by-side/unified, ESC to close)", added, removed, changed)
+                });
+                self.diff = Some(diff);
+                self.diff_scroll = 0;
+                self.mode = AppMode::Diff;
+            }
+            Err(e) => self.error = Some(format!("Diff failed: {}", e)),
+        }
+    }
+
+    pub fn close_diff(&mut self) {
+        self.diff = None;
+        self.mode = AppMode::Normal;
+    }
+
+    pub fn diff_scroll_by(&mut self, delta: isize) {
+        let rows = self.diff.as_ref().map(|d| d.lines.len()).unwrap_or(0);
+        self.diff_scroll = self.diff_scroll.saturating_add_signed(delta).min(rows.saturating_sub(1));
+    }
+
+    pub fn toggle_diff_layout(&mut self) {
+        self.diff_side_by_side = !self.diff_side_by_side;
+    }
+
+    /// Open the selected file in the built-in editor
+    pub fn open_editor(&mut self) {
+        let path = match self.workspace_manager.active_workspace().get_selected_entry() {
+            Some(entry) if !entry.is_dir => entry.path.clone(),
+            _ => {
+                self.error = Some("Select a file to edit".to_string());
+                return;
+            }
+        };
+        match TextEditor::open(&path, self.config.max_edit_size) {
+            Ok(editor) => {
+                self.editor = Some(editor);
+                self.mode = AppMode::Edit;
+                self.message = Some("Ctrl+S to save, ESC to close".to_string());
+            }
+            Err(e) => self.error = Some(format!("Can't edit: {}", e)),
+        }
+    }
+
+    pub fn save_editor(&mut self) -> Result<()> {
+        let editor = match self.editor.as_mut() {
+            Some(editor) => editor,
+            None => return Ok(()),
+        };
+        match editor.save() {
+            Ok(()) => {
+                self.message = Some(format!("Saved {}", editor.path.display()));
+                self.refresh_workspace()?;
+            }
+            Err(e) => self.error = Some(format!("Save failed: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Leave the editor; with unsaved changes this only warns the first time
+    pub fn close_editor(&mut self) {
+        if let Some(editor) = self.editor.as_mut() {
+            if !editor.request_close() {
+                self.error = Some("Unsaved changes: Ctrl+S to save, ESC again to discard".to_string());
+                return;
+            }
+        }
+        self.editor = None;
+        self.mode = AppMode::Normal;
+        self.update_preview();
+    }
+
+    pub fn play_media(&mut self, path: &PathBuf) -> Result<()> {
+        self.media_player.play();
+        self.message = Some(format!("Now playing: {}", path.display()));
+        Ok(())
+    }
+
+    pub fn pause_media(&mut self) {
+        self.media_player.pause();
+        self.message = Some("Media paused".to_string());
+    }
+
+    pub fn toggle_media_playback(&mut self) {
+        self.media_player.toggle();
+    }
+
+    pub fn media_seek(&mut self, seconds: f32) {
+        let duration = std::time::Duration::from_secs_f32(seconds);
+        self.media_player.seek_forward(duration);
+    }
+
+    pub fn media_adjust_volume(&mut self, delta: f32) {
+        let new_volume = (self.media_player.volume + delta).clamp(0.0, 1.0);
+        self.media_player.set_volume(new_volume);
+    }
+
+    pub fn media_adjust_speed(&mut self, delta: f32) {
+        let new_speed = (self.media_player.speed + delta).clamp(0.25, 2.0);
+        self.media_player.set_speed(new_speed);
+    }
+
+    pub fn get_media_status(&self) -> String {
+        self.media_player.status_bar()
+    }
+
+    // ========== Theme Management ==========
+    pub fn switch_theme(&mut self, theme_name: &str) -> Result<()> {
+        self.theme_manager.set_current(theme_name)?;
+        self.apply_current_theme();
+        self.api_plugin_manager.call_theme_changed(theme_name).log_err("run on_theme_changed hooks");
+        self.user_settings.current_theme = theme_name.to_string();
+        self.message = Some(format!("Theme changed to: {}", theme_name));
+        Ok(())
+    }
+
+    pub fn list_available_themes(&self) -> Vec<String> {
+        self.theme_manager.list_themes()
+    }
+
+    pub fn reload_theme(&mut self) -> Result<()> {
+        let theme_name = self.user_settings.current_theme.clone();
+        self.theme_manager.set_current(&theme_name)?;
+        self.apply_current_theme();
+        self.message = Some("Theme reloaded".to_string());
+        Ok(())
+    }
+
+    /// Generate a theme from the terminal's palette, save it and switch to it
+    pub fn create_theme_from_terminal(&mut self) -> Result<()> {
+        let theme = ThemeManager::from_terminal();
+        let name = theme.name.clone();
+        self.theme_manager.update(name.clone(), theme)?;
+        self.switch_theme(&name)?;
+        self.message = Some(format!("Created theme '{}' from terminal colors", name));
+        Ok(())
+    }
+
+    /// Import a theme file, warning when its colors are hard to read
+    #[allow(dead_code)]
+    pub fn import_theme(&mut self, path: &PathBuf) -> Result<Vec<ContrastWarning>> {
+        let name = self.theme_manager.import(path)?;
+        let warnings = self.check_theme_contrast(&name)?;
+
+        if warnings.is_empty() {
+            self.message = Some(format!("Imported theme: {}", name));
+        } else {
+            let pairs: Vec<&str> = warnings.iter().map(|w| w.pair.as_str()).collect();
+            self.error = Some(format!(
+                "Imported theme '{}' has low contrast: {}",
+                name,
+                pairs.join(", ")
+            ));
+        }
+        Ok(warnings)
+    }
+
+    #[allow(dead_code)]
+    pub fn check_theme_contrast(&self, name: &str) -> Result<Vec<ContrastWarning>> {
+        let theme = self
+            .theme_manager
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Theme '{}' not found", name))?;
+        Ok(self.theme_manager.check_contrast(theme))
+    }
+
+    /// Raise low-contrast colors of a theme to the WCAG AA minimum and save it
+    pub fn auto_adjust_theme(&mut self, name: &str) -> Result<usize> {
+        let mut theme = self
+            .theme_manager
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Theme '{}' not found", name))?;
+
+        let adjusted = self.theme_manager.auto_adjust(&mut theme, MIN_CONTRAST_RATIO);
+        if adjusted > 0 {
+            self.theme_manager.update(name.to_string(), theme)?;
+            if self.theme_manager.current_theme_name() == name {
+                self.apply_current_theme();
+            }
+        }
+        self.message = Some(format!("Adjusted {} colors in theme '{}'", adjusted, name));
+        Ok(adjusted)
+    }
+
+    /// Rebuild the render styles from the theme manager's current theme
+    fn apply_current_theme(&mut self) {
+        if let Some(theme) = self.theme_manager.current() {
+            self.theme = theme.to_render_theme();
+        }
+        self.apply_icon_set();
+    }
+
+    /// Pick file icons from `icon_set`, the theme's fonts and what the terminal can draw
+    fn apply_icon_set(&mut self) {
+        let use_nerd_fonts = self.theme_manager.current().is_some_and(|theme| theme.fonts.use_nerd_fonts);
+        self.theme.icons = crate::theme::IconSet::resolve(
+            &self.config.icon_set,
+            use_nerd_fonts,
+            crate::theme::current_terminal_is_limited(),
+        );
+    }
+
+    pub fn open_theme_gallery(&mut self) {
+        let mut names = self.theme_manager.list_themes();
+        names.sort();
+
+        self.theme_gallery = names
+            .into_iter()
+            .filter_map(|name| {
+                let theme = self.theme_manager.get(&name)?.to_render_theme();
+                Some((name, theme))
+            })
+            .collect();
+
+        let current = self.theme_manager.current_theme_name();
+        self.theme_gallery_index = self
+            .theme_gallery
+            .iter()
+            .position(|(name, _)| *name == current)
+            .unwrap_or(0);
+        self.mode = AppMode::ThemeGallery;
+        self.message = Some("Theme gallery (↑/↓ to browse, Enter to apply, ESC to cancel)".to_string());
+    }
+
+    pub fn theme_gallery_next(&mut self) {
+        if self.theme_gallery_index + 1 < self.theme_gallery.len() {
+            self.theme_gallery_index += 1;
+        }
+    }
+
+    pub fn theme_gallery_prev(&mut self) {
+        self.theme_gallery_index = self.theme_gallery_index.saturating_sub(1);
+    }
+
+    pub fn apply_gallery_theme(&mut self) -> Result<()> {
+        let name = self
+            .theme_gallery
+            .get(self.theme_gallery_index)
+            .map(|(name, _)| name.clone());
+        self.close_theme_gallery();
+
+        if let Some(name) = name {
+            self.switch_theme(&name)?;
+        }
+        Ok(())
+    }
+
+    pub fn close_theme_gallery(&mut self) {
+        self.theme_gallery.clear();
+        self.theme_gallery_index = 0;
+        self.mode = AppMode::Normal;
+    }
+
+    // ========== Plugin Management ==========
+    pub fn load_plugins(&mut self) -> Result<()> {
+        self.api_plugin_manager.load_all()?;
+        let failed = self.api_plugin_manager.load_errors().len();
+        if failed == 0 {
+            self.message = Some("Plugins loaded".to_string());
+        } else {
+            self.error = Some(format!("{} plugin(s) failed to load", failed));
+        }
+        Ok(())
+    }
+
+    pub fn enable_plugin(&mut self, id: &str) -> Result<()> {
+        self.api_plugin_manager.enable(id)?;
+        if !self.user_settings.enabled_plugins.iter().any(|p| p == id) {
+            self.user_settings.enabled_plugins.push(id.to_string());
+        }
+        self.message = Some(format!("Plugin enabled: {}", id));
+        Ok(())
+    }
+
+    pub fn disable_plugin(&mut self, id: &str) -> Result<()> {
+        self.api_plugin_manager.disable(id)?;
+        self.user_settings.enabled_plugins.retain(|p| p != id);
+        self.message = Some(format!("Plugin disabled: {}", id));
+        Ok(())
+    }
+
+    pub fn reload_plugin(&mut self, id: &str) -> Result<()> {
+        self.api_plugin_manager.reload(id)?;
+        self.message = Some(format!("Plugin reloaded: {}", id));
+        Ok(())
+    }
+
+    /// Where plugins are loaded from: the folder picked with `set_plugin_dir`,
+    /// else the config's `plugin_directory`
+    pub fn plugin_dir(&self) -> PathBuf {
+        plugin_directory(&self.config, &self.user_settings)
+    }
+
+    /// Load plugins from `path` from now on, remembered in the user settings,
+    /// and reload them from there; an empty path goes back to the config's folder
+    #[allow(dead_code)]
+    pub fn set_plugin_dir(&mut self, path: &str) -> Result<()> {
+        let dir = if path.trim().is_empty() {
+            PathBuf::new()
+        } else {
+            crate::fileops::expand_input_path(path, &self.get_current_workspace().current_dir)
+        };
+        if dir.is_file() {
+            return Err(anyhow::anyhow!("{} is a file, not a folder", dir.display()));
+        }
+        self.user_settings.plugin_directory = dir;
+        self.reload_plugins()
+    }
+
+    /// Unload every plugin and scan the plugin folder again, e.g. after
+    /// dropping in a new one. Built-in plugins come back, and plugins the user
+    /// enabled are enabled again.
+    pub fn reload_plugins(&mut self) -> Result<()> {
+        let dir = self.plugin_dir();
+        self.plugin_manager.set_plugin_dir(dir.clone());
+        self.plugin_manager.reload().log_err("load legacy plugins");
+
+        // Plugin data lives outside the plugins, so it survives the reload
+        self.api_plugin_manager.unload_all()?;
+        self.api_plugin_manager.set_plugin_dir(dir);
+        self.api_plugin_manager.register_builtin();
+        // As at startup, plugins in the directory are only loaded when allowed
+        if self.config.enable_plugins {
+            self.api_plugin_manager.load_all()?;
+        }
+        for id in self.user_settings.enabled_plugins.clone() {
+            self.api_plugin_manager.enable(&id).log_err(&format!("enable plugin {}", id));
+        }
+        self.plugin_index = self.plugin_index.min(self.api_plugin_manager.count().saturating_sub(1));
+
+        let failed = self.api_plugin_manager.load_errors().len();
+        if failed == 0 {
+            let count = self.api_plugin_manager.count();
+            self.message = Some(format!("Reloaded {} plugin(s) from {}", count, self.plugin_dir().display()));
+        } else {
+            self.error = Some(format!("{} plugin(s) failed to load", failed));
+        }
+        Ok(())
+    }
+
+    /// Registered plugin IDs in display order
+    pub fn plugin_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .api_plugin_manager
+            .list()
+            .into_iter()
+            .map(|meta| meta.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    pub fn open_plugin_manager(&mut self) {
+        self.plugin_index = 0;
+        self.mode = AppMode::PluginManager;
+        self.message = Some("Plugins (e to enable/disable, r to reload, ESC to close)".to_string());
+    }
+
+    pub fn close_plugin_manager(&mut self) {
+        self.mode = AppMode::Normal;
+    }
+
+    pub fn plugin_next(&mut self) {
+        if self.plugin_index + 1 < self.api_plugin_manager.list().len() {
+            self.plugin_index += 1;
+        }
+    }
+
+    pub fn plugin_prev(&mut self) {
+        self.plugin_index = self.plugin_index.saturating_sub(1);
+    }
+
+    /// Enable or disable the plugin highlighted in the overlay
+    pub fn toggle_selected_plugin(&mut self) {
+        let id = match self.plugin_ids().get(self.plugin_index) {
+            Some(id) => id.clone(),
+            None => return,
+        };
+        let result = if self.api_plugin_manager.is_enabled(&id) {
+            self.disable_plugin(&id)
+        } else {
+            self.enable_plugin(&id)
+        };
+        if let Err(e) = result {
+            self.error = Some(format!("Plugin {}: {}", id, e));
+        }
+    }
+
+    pub fn reload_selected_plugin(&mut self) {
+        if let Some(id) = self.plugin_ids().get(self.plugin_index).cloned() {
+            if let Err(e) = self.reload_plugin(&id) {
+                self.error = Some(format!("Plugin {}: {}", id, e));
+            }
+        }
+    }
+
+    // ========== Paths ==========
+    /// Where settings, themes, bookmarks and the log are kept
+    pub fn config_dir(&self) -> PathBuf {
+        self.persistence_manager.get_config_dir_path().to_path_buf()
+    }
+
+    /// Where bookmarks, search history and session data are kept
+    pub fn data_dir(&self) -> PathBuf {
+        self.dirs.data_dir.clone()
+    }
+
+    /// Folders themes are loaded from, bundled ones first
+    #[allow(dead_code)]
+    pub fn theme_dirs(&self) -> Vec<PathBuf> {
+        self.theme_manager.theme_dirs().iter().map(|dir| dir.to_path_buf()).collect()
+    }
+
+    /// Every folder and file AstroFS reads or writes, labelled, for the paths overlay
+    pub fn app_paths(&self) -> Vec<(&'static str, PathBuf)> {
+        let mut paths = vec![("Config", self.config_dir())];
+        if let Some(file) = &self.config.source_path {
+            paths.push(("Config file", file.clone()));
+        }
+        let [themes, user_themes] = self.theme_manager.theme_dirs();
+        paths.push(("Themes", themes.to_path_buf()));
+        paths.push(("User themes", user_themes.to_path_buf()));
+        paths.push(("Highlight themes", self.dirs.highlight_theme_dir()));
+        paths.push(("Data", self.data_dir()));
+        if let Some(trash) = &self.trash {
+            paths.push(("Trash", trash.root().to_path_buf()));
+        }
+        paths.push(("Plugins", self.plugin_dir()));
+        paths.push(("Log file", self.dirs.log_file()));
+        paths
+    }
+
+    pub fn open_paths(&mut self) {
+        self.paths_index = 0;
+        self.mode = AppMode::Paths;
+        self.message = Some("Paths (Enter to go there, o to open, y to yank, ESC to close)".to_string());
+    }
+
+    pub fn close_paths(&mut self) {
+        self.mode = AppMode::Normal;
+        self.message = None;
+    }
+
+    pub fn paths_next(&mut self) {
+        if self.paths_index + 1 < self.app_paths().len() {
+            self.paths_index += 1;
+        }
+    }
+
+    pub fn paths_prev(&mut self) {
+        self.paths_index = self.paths_index.saturating_sub(1);
+    }
+
+    /// Show the selected path in the file list: a folder's contents, or a
+    /// file selected in its folder
+    pub fn reveal_selected_app_path(&mut self) -> Result<()> {
+        let Some((_, path)) = self.app_paths().into_iter().nth(self.paths_index) else {
+            return Ok(());
+        };
+        if !path.exists() {
+            self.error = Some(format!("{} doesn't exist yet", path.display()));
+            return Ok(());
+        }
+        self.close_paths();
+        if path.is_dir() {
+            self.go_to_path(&path.to_string_lossy())
+        } else {
+            self.reveal_path(&path)
+        }
+    }
+
+    /// Open the selected path with the system's default application
+    pub fn open_selected_app_path(&mut self) {
+        let Some((_, path)) = self.app_paths().into_iter().nth(self.paths_index) else {
+            return;
+        };
+        if let Err(e) = that(&path) {
+            self.error = Some(format!("Cannot open {}: {}", path.display(), e));
+        }
+    }
+
+    /// Copy the selected path to the clipboard
+    pub fn yank_selected_app_path(&mut self) {
+        let Some((_, path)) = self.app_paths().into_iter().nth(self.paths_index) else {
+            return;
+        };
+        let text = path.to_string_lossy().to_string();
+        match self.copy_to_clipboard(&text) {
+            Ok(()) => self.message = Some(format!("Copied path: {}", text)),
+            Err(e) => self.error = Some(format!("Clipboard unavailable: {}", e)),
+        }
+    }
+
+    // ========== Settings Persistence ==========
+    pub fn save_settings(&mut self) -> Result<()> {
+        self.user_settings.current_theme = self.theme_manager.current_theme_name();
+        self.user_settings.plugin_settings = self.api_plugin_manager.plugin_data();
+        self.persistence_manager.save_settings(&self.user_settings)?;
+        self.message = Some("Settings saved".to_string());
+        Ok(())
+    }
+
+    pub fn load_user_preferences(&mut self) -> Result<()> {
+        self.user_settings = self.persistence_manager.load_settings().log_err("load user settings").unwrap_or_default();
+        let plugin_data = self.user_settings.plugin_settings.clone();
+        self.api_plugin_manager.set_plugin_data(plugin_data);
+        self.message = Some("Preferences loaded".to_string());
+        Ok(())
+    }
+
+    /// Write the live settings, unsaved changes included, to a backup file
+    pub fn export_settings(&mut self, path: &str) -> Result<()> {
+        self.user_settings.current_theme = self.theme_manager.current_theme_name();
+        self.user_settings.plugin_settings = self.api_plugin_manager.plugin_data();
+        PersistenceManager::write_backup(&self.user_settings, Path::new(path))?;
+        self.message = Some(format!("Settings exported to: {}", path));
+        Ok(())
+    }
+
+    /// Replace the settings with a backup file's, saving them and applying them
+    /// to the running app
+    pub fn import_settings(&mut self, path: &str) -> Result<()> {
+        let settings = PersistenceManager::read_backup(Path::new(path))?;
+        self.persistence_manager.save_settings(&settings)?;
+        for id in self.user_settings.enabled_plugins.clone() {
+            if !settings.enabled_plugins.contains(&id) {
+                self.api_plugin_manager.disable(&id).log_err(&format!("disable plugin {}", id));
+            }
+        }
+        self.user_settings = settings;
+        self.api_plugin_manager.set_plugin_data(self.user_settings.plugin_settings.clone());
+        self.apply_user_settings();
+
+        let show_hidden = self.user_settings.show_hidden_files;
+        self.workspace_manager.active_workspace_mut().show_hidden = show_hidden;
+        self.refresh_workspace()?;
+        self.message = Some(format!("Settings imported from: {}", path));
+        Ok(())
+    }
+}
+
+/// Files inside archives bigger than this aren't extracted just to preview them
+const ARCHIVE_PREVIEW_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// Saved as the plugin folder before it could be picked, though never used
+const OLD_DEFAULT_PLUGIN_DIR: &str = "./plugins";
+
+/// The plugin folder picked in the user settings, else the config's
+fn plugin_directory(config: &AppConfig, user_settings: &UserSettings) -> PathBuf {
+    let picked = &user_settings.plugin_directory;
+    if picked.as_os_str().is_empty() || picked == Path::new(OLD_DEFAULT_PLUGIN_DIR) {
+        config.plugin_dir()
+    } else {
+        picked.clone()
+    }
+}
+
+/// The temporary folder for extracted archive files, made on first use
+fn archive_temp_dir(slot: &mut Option<tempfile::TempDir>) -> Result<PathBuf> {
+    if slot.is_none() {
+        *slot = Some(tempfile::Builder::new().prefix("astrofs-archive-").tempdir()?);
+    }
+    Ok(slot.as_ref().map(|dir| dir.path().to_path_buf()).unwrap_or_default())
+}
+
+/// Preview an entry of an archive being browsed: a folder's contents, or a
+/// file extracted to a temporary folder
+fn preview_archive_entry(
+    archive: &ArchiveListing,
+    entry: &FileEntry,
+    temp: &mut Option<tempfile::TempDir>,
+    max_lines: usize,
+    options: &HighlightOptions,
+) -> PreviewContent {
+    let text = |lines: Vec<String>| PreviewContent {
+        lines: lines.into_iter().map(Line::from).collect(),
+        is_binary: false,
+        preview_type: crate::preview::PreviewType::Text,
+    };
+    if entry.is_dir {
+        let children = archive.children(&entry.path, true);
+        let mut lines = vec![format!("📁 {} ({} items, in archive)", entry.name, children.len()), String::new()];
+        lines.extend(children.iter().take(max_lines).map(|child| format!("  {}", child.name)));
+        return text(lines);
+    }
+    if entry.size > ARCHIVE_PREVIEW_LIMIT {
+        return text(vec![
+            format!("📦 {} ({})", entry.name, entry.size_formatted()),
+            String::new(),
+            "Too large to preview inside the archive; Enter extracts and opens it".to_string(),
+        ]);
+    }
+    match archive_temp_dir(temp).and_then(|dir| archive.extract_cached(&entry.path, &dir)) {
+        Ok(extracted) => generate_preview_with(&extracted, max_lines, options),
+        Err(e) => text(vec![format!("⚠️  Cannot extract {}: {}", entry.name, e)]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::sync::Mutex;
+
+    thread_local! {
+        // Each test runs on its own thread, so these go when the test ends
+        static STATE_DIRS: RefCell<Vec<tempfile::TempDir>> = const { RefCell::new(Vec::new()) };
+    }
 
-        self.bookmark_manager.add(name.clone(), path, "📌".to_string())?;
-        self.message = Some(format!("Added bookmark: {}", name));
-        Ok(())
+    /// Fresh folders for an app's settings, bookmarks, history and trash,
+    /// so tests never read or write the user's own
+    fn test_dirs() -> Result<AppDirs> {
+        let root = tempfile::tempdir()?;
+        let dirs = AppDirs::under(root.path());
+        STATE_DIRS.with(|state| state.borrow_mut().push(root));
+        Ok(dirs)
     }
 
-    pub fn goto_bookmark(&mut self, name: &str) -> Result<()> {
-        if let Some(bookmark) = self.bookmark_manager.get(name) {
-            let workspace = self.workspace_manager.active_workspace_mut();
-            workspace.current_dir = bookmark.path.clone();
-            workspace.selected_index = 0;
-            workspace.scroll_offset = 0;
-            self.refresh_workspace()?;
-            self.message = Some(format!("Navigated to bookmark: {}", name));
-        } else {
-            self.error = Some("Bookmark not found".to_string());
+    /// Keeps what was copied instead of touching the system clipboard
+    struct FakeClipboard(Arc<Mutex<Vec<String>>>);
+
+    impl TextClipboard for FakeClipboard {
+        fn set_text(&mut self, text: &str) -> Result<()> {
+            self.0.lock().unwrap().push(text.to_string());
+            Ok(())
         }
+    }
+
+    /// An app browsing `dir` with plugins and auto refresh off and its state
+    /// in `test_dirs`; `tweak` adjusts the config before it starts
+    fn test_app(dir: &Path, tweak: impl FnOnce(&mut AppConfig)) -> Result<App> {
+        let mut config = AppConfig {
+            default_directory: dir.to_string_lossy().to_string(),
+            enable_plugins: false,
+            auto_refresh: false,
+            ..Default::default()
+        };
+        tweak(&mut config);
+        App::with_dirs(config, test_dirs()?)
+    }
+
+    #[test]
+    fn test_directory_changes_fire_on_moves_only() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("a_src"))?;
+        std::fs::write(dir.path().join("b.txt"), "")?;
+        let mut app = test_app(dir.path(), |_| {})?;
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        app.on_directory_changed(Box::new(move |old, new| {
+            recorder.lock().unwrap().push((old.to_path_buf(), new.to_path_buf()));
+        }));
+
+        app.move_down();
+        app.move_up();
+        app.refresh_workspace()?;
+        assert!(seen.lock().unwrap().is_empty(), "moving the selection or refreshing isn't a change");
+
+        assert_eq!(app.get_current_workspace().get_selected_entry().map(|e| e.name.as_str()), Some("a_src"));
+        app.enter_selected()?;
+        assert_eq!(*seen.lock().unwrap(), vec![(dir.path().to_path_buf(), dir.path().join("a_src"))]);
         Ok(())
     }
 
-    // ========== Command Palette ==========
-    pub fn start_command_palette(&mut self) {
-        self.mode = AppMode::CommandPalette;
-        self.command_palette.clear_filter();
-        self.command_search_index = 0;
-        self.message = Some("Command palette (type to filter, ESC to cancel)".to_string());
+    #[test]
+    fn test_export_includes_unsaved_changes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut app = test_app(dir.path(), |_| {})?;
+        let show_hidden = !app.get_current_workspace().show_hidden;
+        app.toggle_hidden()?;
+
+        let backup = dir.path().join("backup.json");
+        app.export_settings(&backup.to_string_lossy())?;
+        let exported = PersistenceManager::read_backup(&backup)?;
+        assert_eq!(exported.show_hidden_files, show_hidden);
+        assert_eq!(exported.current_theme, app.theme_manager.current_theme_name());
+        Ok(())
     }
 
-    pub fn execute_command(&mut self, cmd: &Command) -> Result<()> {
-        match cmd {
-            Command::Copy => self.copy_selected()?,
-            Command::Delete => self.delete_selected()?,
-            Command::CreateFile => {
-                self.mode = AppMode::Input(InputMode::CreateFile);
-                self.input_buffer.clear();
-            }
-            Command::CreateDirectory => {
-                self.mode = AppMode::Input(InputMode::CreateDirectory);
-                self.input_buffer.clear();
-            }
-            Command::ParentDirectory => self.go_back()?,
-            Command::Home => {
-                if let Ok(home_dir) = std::env::var("HOME") {
-                    self.go_to_path(&home_dir)?;
-                }
-            }
-            Command::Root => self.go_to_path("/")?,
-            Command::GoToPath => {
-                self.mode = AppMode::Input(InputMode::GoToPath);
-                self.input_buffer.clear();
-            }
-            Command::Search => self.start_search(),
-            Command::ToggleHidden => self.toggle_hidden()?,
-            Command::NewWorkspace => self.new_workspace()?,
-            Command::NextWorkspace => self.next_workspace(),
-            Command::PrevWorkspace => self.prev_workspace(),
-            Command::AddBookmark => {
-                self.mode = AppMode::Input(InputMode::AddBookmark);
-                self.input_buffer.clear();
-            }
-            Command::OpenWithDefault => {
-                let workspace = self.workspace_manager.active_workspace();
-                if let Some(entry) = workspace.get_selected_entry() {
-                    let _ = that(&entry.path);
-                }
-            }
-            Command::ShowHelp => {
-                self.mode = AppMode::Help;
-            }
-            Command::Quit => self.running = false,
-            _ => {}
+    #[test]
+    fn test_recovery_file_restores_tabs_after_crash() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let (first, second) = (dir.path().join("first"), dir.path().join("second"));
+        std::fs::create_dir(&first)?;
+        std::fs::create_dir(&second)?;
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(second.join(name), "")?;
         }
+        let config = AppConfig {
+            default_directory: first.to_string_lossy().to_string(),
+            enable_plugins: false,
+            auto_refresh: false,
+            ..Default::default()
+        };
+        let recovery_path = dir.path().join("session_recovery.json");
+        let state = test_dirs()?;
 
-        if !matches!(self.mode, AppMode::Input(_)) {
-            self.mode = AppMode::Normal;
+        {
+            let mut app = App::with_dirs(config.clone(), state.clone())?;
+            app.start_session_recovery(RecoveryFile::new(recovery_path.clone()));
+            app.new_workspace()?;
+            app.go_to_path(&second.to_string_lossy())?;
+            app.select_index(2);
+            app.write_recovery();
+
+            // A second instance neither offers the live session nor removes it
+            let mut other = App::with_dirs(config.clone(), state.clone())?;
+            other.start_session_recovery(RecoveryFile::new(recovery_path.clone()));
+            assert_eq!(other.mode, AppMode::Normal);
+            other.quit();
+            assert!(recovery_path.exists());
+            // Dropped without quit(), as when the process is killed
         }
-        
+        assert!(recovery_path.exists());
+
+        let mut app = App::with_dirs(config, state)?;
+        app.start_session_recovery(RecoveryFile::new(recovery_path.clone()));
+        assert_eq!(app.mode, AppMode::ConfirmRestore);
+        app.restore_session()?;
+        let dirs: Vec<_> = app.workspace_manager.workspaces().iter().map(|w| w.current_dir.clone()).collect();
+        assert_eq!(dirs, vec![first, second]);
+        assert_eq!(app.workspace_manager.active_index(), 1);
+        assert_eq!(app.get_current_workspace().selected_index, 2);
         Ok(())
     }
 
-    // ========== Utilities ==========
-    pub fn quit(&mut self) {
-        let _ = self.bookmark_manager.save();
-        let _ = self.search_history.save();
-        let _ = self.config.save();
-        
-        // Save Beast Mode state
-        let _ = PersistenceManager::save_default(&self.user_settings);
-        let _ = self.theme_manager.save_current_theme();
-        
-        // Use describe methods and access fields to eliminate warnings
-        let _theme_info = self.theme_manager.describe_current();
-        let _settings_info = PersistenceManager::describe_settings(&self.user_settings);
-        let _bindings_info = self.playback_controller.bindings.describe_all();
-        let _config_dir = self.persistence_manager.get_config_dir_path();
-        
-        // Access media player fields
-        let _player_state = self.media_player.state.clone();
-        let _player_pos = self.media_player.position;
-        let _player_vol = self.media_player.volume;
-        let _player_speed = self.media_player.speed;
-        let _player_repeat = self.media_player.repeat_mode.clone();
-        let _player_playlist = self.media_player.playlist.clone();
-        let _player_idx = self.media_player.current_index;
-        
-        // Call media player methods
-        let _progress = self.media_player.progress();
-        let _status = self.media_player.status_bar();
-        let _plugin_count = self.api_plugin_manager.count();
-        let _preview_path = self.media_preview.last_path();
-        
-        self.running = false;
+    #[test]
+    fn test_restore_keeps_active_tab_when_earlier_tabs_are_gone() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tab = |name: &str| SessionTab { path: dir.path().join(name), selected_index: 0, scroll_offset: 0, title: None };
+        for name in ["a", "b", "c"] {
+            std::fs::create_dir(dir.path().join(name))?;
+        }
+        let recovery_path = dir.path().join("session_recovery.json");
+        let session = SessionSnapshot {
+            tabs: vec![tab("a"), tab("gone"), tab("b"), tab("c")],
+            active_tab_index: 2,
+            search_query: String::new(),
+        };
+        RecoveryFile::new(recovery_path.clone()).write(&session)?;
+
+        let mut app = test_app(dir.path(), |_| {})?;
+        app.start_session_recovery(RecoveryFile::new(recovery_path));
+        app.restore_session()?;
+        assert_eq!(app.workspace_manager.workspaces().len(), 3);
+        assert_eq!(app.get_current_workspace().current_dir, dir.path().join("b"));
+        Ok(())
     }
 
-    pub fn set_viewport(&mut self, width: usize, height: usize) {
-        self.viewport_width = width;
-        self.viewport_height = height;
+    #[test]
+    fn test_sort_rule_applies_on_entering_directory() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let downloads = dir.path().join("Downloads");
+        std::fs::create_dir(&downloads)?;
+        std::fs::write(downloads.join("a-small.txt"), "1")?;
+        std::fs::write(downloads.join("b-large.txt"), "1234567890")?;
+        std::fs::write(downloads.join("c-medium.txt"), "12345")?;
+        let mut app = test_app(dir.path(), |config| {
+            config.sort_rules = HashMap::from([("**/Downloads".to_string(), "size-desc".to_string())]);
+        })?;
+        let names = |app: &App| -> Vec<String> { app.get_current_workspace().entries.iter().map(|e| e.name.clone()).collect() };
+
+        app.go_to_path(&downloads.to_string_lossy())?;
+        assert_eq!(app.sort_mode(), SortMode::SizeDesc);
+        assert_eq!(names(&app), ["b-large.txt", "c-medium.txt", "a-small.txt"]);
+
+        // `s` overrides the rule for the rest of the session
+        app.cycle_sort()?;
+        assert_eq!(app.sort_mode(), SortMode::Name);
+        assert_eq!(names(&app), ["a-small.txt", "b-large.txt", "c-medium.txt"]);
+        Ok(())
     }
 
-    pub fn get_current_workspace(&self) -> &Workspace {
-        self.workspace_manager.active_workspace()
+    #[test]
+    fn test_entering_empty_directory_clears_selection_and_preview() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("empty"))?;
+        std::fs::write(dir.path().join("notes.txt"), "remember the milk")?;
+        let mut app = test_app(dir.path(), |_| {})?;
+        app.select_index(1);
+        assert_eq!(app.get_current_workspace().get_selected_entry().map(|e| e.name.as_str()), Some("notes.txt"));
+
+        app.select_index(0);
+        app.enter_selected()?;
+        let workspace = app.get_current_workspace();
+        assert!(workspace.entries.is_empty());
+        assert_eq!(workspace.selected_index, 0);
+        assert!(workspace.get_selected_entry().is_none());
+        assert_eq!(workspace.empty, Some(EmptyListing::Empty));
+        assert_eq!(workspace.preview.lines.len(), 1);
+        assert_eq!(workspace.preview.lines[0].to_string(), "(empty directory)");
+
+        // Nothing to act on, and nothing breaks
+        app.move_down();
+        app.enter_selected()?;
+        app.toggle_mark();
+        assert!(app.get_current_workspace().marked.is_empty());
+        Ok(())
     }
 
-    // ========== Media Operations ==========
-    pub fn preview_media(&mut self, path: &PathBuf) -> Result<Option<String>> {
-        self.media_preview.get_metadata(path)
+    #[test]
+    fn test_archive_browses_as_read_only_folder() -> Result<()> {
+        use std::io::Write;
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().join("bundle.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&root)?);
+        zip.start_file("docs/notes.txt", zip::write::FileOptions::default())?;
+        zip.write_all(b"inside the zip")?;
+        zip.finish()?;
+        std::fs::write(dir.path().join("after.txt"), "")?;
+        let mut app = test_app(dir.path(), |_| {})?;
+        app.select_index(1);
+        app.enter_selected()?;
+        assert!(app.in_archive());
+        assert_eq!(app.get_current_workspace().entries[0].path, root.join("docs"));
+
+        app.enter_selected()?;
+        let workspace = app.get_current_workspace();
+        assert_eq!(workspace.get_selected_entry().map(|e| e.size), Some(14));
+        assert_eq!(workspace.preview.lines[0].to_string(), "inside the zip");
+        app.open_quick_look();
+        assert_eq!(app.quick_look.as_ref().map(|q| q.content.lines[0].to_string()).as_deref(), Some("inside the zip"));
+        app.close_quick_look();
+
+        app.delete_selected()?;
+        assert!(app.error.as_deref().is_some_and(|e| e.contains("read-only")));
+        assert!(root.exists());
+
+        app.go_back()?;
+        app.go_back()?;
+        let workspace = app.get_current_workspace();
+        assert!(!app.in_archive());
+        assert_eq!(workspace.current_dir, dir.path());
+        assert_eq!(workspace.get_selected_entry().map(|e| e.path.clone()), Some(root));
+        assert!(workspace.preview.lines.iter().any(|l| l.to_string() == "  docs/notes.txt (14 B)"));
+        Ok(())
     }
 
-    pub fn play_media(&mut self, path: &PathBuf) -> Result<()> {
-        self.media_player.play();
-        self.message = Some(format!("Now playing: {}", path.display()));
+    #[test]
+    fn test_rename_onto_existing_file_asks_first() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("a.txt"), "mine")?;
+        std::fs::write(dir.path().join("b.txt"), "theirs")?;
+        let mut app = test_app(dir.path(), |_| {})?;
+        app.select_index(0);
+        app.rename_selected("b.txt")?;
+        assert_eq!(app.mode, AppMode::ConfirmOverwrite);
+        assert_eq!(std::fs::read_to_string(dir.path().join("b.txt"))?, "theirs");
+
+        app.cancel_overwrite();
+        assert!(dir.path().join("a.txt").exists());
+
+        app.rename_selected("b.txt")?;
+        app.confirm_overwrite()?;
+        assert!(!dir.path().join("a.txt").exists());
+        assert_eq!(std::fs::read_to_string(dir.path().join("b.txt"))?, "mine");
         Ok(())
     }
 
-    pub fn pause_media(&mut self) {
-        self.media_player.pause();
-        self.message = Some("Media paused".to_string());
+    #[test]
+    fn test_bookmark_prompt_suggests_directory_name() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let project = dir.path().join("rocket-engine");
+        std::fs::create_dir(&project)?;
+        let mut app = test_app(&project, |_| {})?;
+
+        app.start_add_bookmark();
+        assert!(matches!(app.mode, AppMode::Input(InputMode::AddBookmark)));
+        assert_eq!(app.input_buffer, "rocket-engine");
+        Ok(())
     }
 
-    pub fn toggle_media_playback(&mut self) {
-        self.media_player.toggle();
+    #[test]
+    fn test_select_index_clamps() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(dir.path().join(name), name)?;
+        }
+        let mut app = test_app(dir.path(), |_| {})?;
+
+        app.select_index(1);
+        assert_eq!(app.selected_index(), 1);
+        assert_eq!(app.get_current_workspace().get_selected_entry().map(|e| e.name.as_str()), Some("b.txt"));
+
+        app.select_index(99);
+        assert_eq!(app.selected_index(), 2);
+        assert_eq!(app.get_current_workspace().get_selected_entry().map(|e| e.name.as_str()), Some("c.txt"));
+        Ok(())
     }
 
-    pub fn media_seek(&mut self, seconds: f32) {
-        let duration = std::time::Duration::from_secs_f32(seconds);
-        self.media_player.seek_forward(duration);
+    #[test]
+    fn test_count_prefix_repeats_movement() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        for i in 0..15 {
+            std::fs::write(dir.path().join(format!("{:02}.txt", i)), "")?;
+        }
+        let mut app = test_app(dir.path(), |_| {})?;
+        app.user_settings.show_sidebar = false;
+
+        assert!(!app.push_count_digit('0'), "a leading 0 isn't a count");
+        for _ in 0..2 {
+            assert!(app.push_count_digit('1') && app.push_count_digit('0'));
+            let count = app.take_count();
+            app.move_down_by(count);
+        }
+        assert_eq!(app.selected_index(), 14, "10j twice stops at the last row");
+        assert_eq!(app.take_count(), 1, "the count is used up");
+
+        for digit in "123456".chars() {
+            app.push_count_digit(digit);
+        }
+        assert_eq!(app.pending_count, Some(MAX_COUNT));
+        Ok(())
     }
 
-    pub fn media_adjust_volume(&mut self, delta: f32) {
-        let new_volume = (self.media_player.volume + delta).clamp(0.0, 1.0);
-        self.media_player.set_volume(new_volume);
+    #[test]
+    fn test_auto_tab_titles_follow_navigation_but_renamed_ones_stay() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("photos"))?;
+        let mut app = test_app(dir.path(), |config| config.tab_title = "name".to_string())?;
+        let title = |app: &App| app.get_current_workspace().title.clone();
+
+        app.select_index(0);
+        app.enter_selected()?;
+        assert_eq!(title(&app), "photos");
+        app.go_back()?;
+        assert_eq!(title(&app), dir.path().file_name().unwrap().to_string_lossy());
+
+        app.rename_workspace("Holiday".to_string());
+        app.enter_selected()?;
+        assert_eq!(title(&app), "Holiday");
+        Ok(())
     }
 
-    pub fn media_adjust_speed(&mut self, delta: f32) {
-        let new_speed = (self.media_player.speed + delta).clamp(0.25, 2.0);
-        self.media_player.set_speed(new_speed);
+    #[test]
+    fn test_copy_file_contents_puts_text_on_the_clipboard() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let notes = dir.path().join("notes.txt");
+        let blob = dir.path().join("blob.bin");
+        std::fs::write(&notes, "snippet\n")?;
+        std::fs::write(&blob, [0u8, 1, 2, 3])?;
+        let large = dir.path().join("large.txt");
+        std::fs::write(&large, "x".repeat(COPY_CONTENTS_LIMIT as usize + 1))?;
+        let mut app = test_app(dir.path(), |_| {})?;
+
+        let copied = Arc::new(Mutex::new(Vec::new()));
+        app.clipboard = Some(Box::new(FakeClipboard(copied.clone())));
+
+        let refused = app.copy_file_contents(&blob).unwrap_err();
+        assert!(refused.to_string().contains("binary"), "{}", refused);
+        let refused = app.copy_file_contents(&large).unwrap_err();
+        assert!(refused.to_string().contains("limit"), "{}", refused);
+        assert!(copied.lock().unwrap().is_empty());
+
+        app.copy_file_contents(&notes)?;
+        assert_eq!(*copied.lock().unwrap(), vec!["snippet\n".to_string()]);
+        assert_eq!(app.message.as_deref(), Some("Copied 1 lines of notes.txt"));
+        Ok(())
     }
 
-    pub fn get_media_status(&self) -> String {
-        self.media_player.status_bar()
+    #[test]
+    fn test_refresh_picks_up_external_files_and_keeps_the_selection() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("b.txt"), "")?;
+        let mut app = test_app(dir.path(), |_| {})?;
+        app.select_index(0);
+
+        std::fs::write(dir.path().join("a.txt"), "")?;
+        assert_eq!(app.get_current_workspace().entries.len(), 1);
+        app.refresh()?;
+        let names: Vec<&str> = app.get_current_workspace().entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["a.txt", "b.txt"]);
+        assert_eq!(app.get_current_workspace().get_selected_entry().map(|e| e.name.as_str()), Some("b.txt"));
+        Ok(())
     }
 
-    // ========== Theme Management ==========
-    pub fn switch_theme(&mut self, theme_name: &str) -> Result<()> {
-        self.theme_manager.set_current(theme_name)?;
-        self.user_settings.current_theme = theme_name.to_string();
-        self.message = Some(format!("Theme changed to: {}", theme_name));
+    #[test]
+    fn test_custom_order_survives_refresh_and_restart() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(dir.path().join(name), "")?;
+        }
+        let dirs = test_dirs()?;
+        let config = AppConfig {
+            default_directory: dir.path().to_string_lossy().to_string(),
+            enable_plugins: false,
+            auto_refresh: true,
+            ..Default::default()
+        };
+        let names = |app: &App| -> Vec<String> {
+            app.get_current_workspace().entries.iter().map(|e| e.name.clone()).collect()
+        };
+
+        let mut app = App::with_dirs(config.clone(), dirs.clone())?;
+        app.select_index(2);
+        app.move_selected_entry(-1);
+        app.move_selected_entry(-1);
+        assert!(app.has_custom_order());
+        assert_eq!(names(&app), ["c.txt", "a.txt", "b.txt"]);
+
+        // The watcher's refresh keeps the order and puts the new file last
+        app.poll_directory_changes()?;
+        std::fs::write(dir.path().join("0.txt"), "")?;
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !names(&app).contains(&"0.txt".to_string()) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            app.poll_directory_changes()?;
+        }
+        assert_eq!(names(&app), ["c.txt", "a.txt", "b.txt", "0.txt"]);
+
+        drop(app);
+        let mut app = App::with_dirs(config, dirs)?;
+        assert_eq!(names(&app), ["c.txt", "a.txt", "b.txt", "0.txt"]);
+
+        app.reset_custom_order()?;
+        assert!(!app.has_custom_order());
+        assert_eq!(names(&app), ["0.txt", "a.txt", "b.txt", "c.txt"]);
         Ok(())
     }
 
-    pub fn list_available_themes(&self) -> Vec<String> {
-        self.theme_manager.list_themes()
+    #[test]
+    fn test_config_data_and_theme_dirs_exist_after_startup() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let state = tempfile::tempdir()?;
+        let config = AppConfig {
+            default_directory: dir.path().to_string_lossy().to_string(),
+            enable_plugins: false,
+            auto_refresh: false,
+            ..Default::default()
+        };
+        let mut app = App::with_dirs(config, AppDirs::under(state.path()))?;
+        let (config_dir, data_dir) = (state.path().join("config"), state.path().join("data"));
+        assert_eq!(app.config_dir(), config_dir);
+        assert_eq!(app.data_dir(), data_dir);
+        assert_eq!(app.theme_dirs(), vec![config_dir.join("themes"), config_dir.join("user_themes")]);
+        assert!(config_dir.is_dir() && data_dir.is_dir());
+        assert!(app.theme_dirs().iter().all(|dir| dir.is_dir()));
+
+        let paths = app.app_paths();
+        assert_eq!(paths[0], ("Config", config_dir.clone()));
+        assert!(paths.contains(&("Config file", config_dir.join("config.json"))));
+        assert!(paths.contains(&("Data", data_dir.clone())));
+        assert!(paths.contains(&("Trash", data_dir.join("Trash"))));
+        assert!(paths.contains(&("Highlight themes", config_dir.join("highlight_themes"))));
+        assert!(paths.contains(&("Log file", config_dir.join("astrofs.log"))));
+        app.open_paths();
+        for _ in 0..paths.len() + 3 {
+            app.paths_next();
+        }
+        assert_eq!(app.paths_index, paths.len() - 1);
+        app.paths_index = 0;
+        app.reveal_selected_app_path()?;
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.get_current_workspace().current_dir, config_dir);
+        Ok(())
     }
 
-    pub fn reload_theme(&mut self) -> Result<()> {
-        let theme_name = self.user_settings.current_theme.clone();
-        self.theme_manager.set_current(&theme_name)?;
-        self.message = Some("Theme reloaded".to_string());
+    #[test]
+    fn test_render_mode_toggles_markup_previews() -> Result<()> {
+        use crate::preview::PreviewType;
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("a.md"), "# Notes\n\n- **one**\n")?;
+        std::fs::write(dir.path().join("b.html"), "<p>Hi &amp; bye</p>\n")?;
+        let mut app = test_app(dir.path(), |_| {})?;
+        let first_line = |app: &App| {
+            let preview = &app.get_current_workspace().preview;
+            let text: String = preview.lines[0].spans.iter().map(|s| s.content.as_ref()).collect();
+            (preview.preview_type.clone(), text.trim_end().to_string())
+        };
+
+        app.select_index(0);
+        assert_eq!(first_line(&app), (PreviewType::Rendered, "Notes".to_string()));
+        app.toggle_render_mode();
+        assert_eq!(first_line(&app), (PreviewType::Code, "# Notes".to_string()));
+        app.toggle_render_mode();
+        assert_eq!(first_line(&app).0, PreviewType::Rendered);
+
+        app.select_index(1);
+        assert_eq!(first_line(&app), (PreviewType::Code, "<p>Hi &amp; bye</p>".to_string()));
+        app.toggle_render_mode();
+        assert_eq!(first_line(&app), (PreviewType::Rendered, "Hi & bye".to_string()));
+
+        // Toggling one file leaves the others at their default
+        app.select_index(0);
+        assert_eq!(first_line(&app).0, PreviewType::Rendered);
         Ok(())
     }
 
-    // ========== Plugin Management ==========
-    pub fn load_plugins(&mut self) -> Result<()> {
-        self.api_plugin_manager.load_all()?;
-        self.message = Some("Plugins loaded".to_string());
+    #[test]
+    fn test_returning_to_a_directory_restores_its_selection() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sub = dir.path().join("sub");
+        std::fs::create_dir(&sub)?;
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(dir.path().join(name), "")?;
+        }
+        std::fs::write(sub.join("inner.txt"), "")?;
+        let mut app = test_app(dir.path(), |config| config.persist_selections = true)?;
+        let selected = |app: &App| app.get_current_workspace().get_selected_entry().map(|e| e.name.clone());
+
+        app.select_index(3);
+        assert_eq!(selected(&app).as_deref(), Some("c.txt"));
+        app.go_to_path(&sub.to_string_lossy())?;
+        assert_eq!(selected(&app).as_deref(), Some("inner.txt"));
+        app.go_to_path(&dir.path().to_string_lossy())?;
+        assert_eq!(selected(&app).as_deref(), Some("c.txt"));
+
+        // Entering a folder remembers it, so going back up selects it
+        app.select_index(0);
+        app.enter_selected()?;
+        app.go_back()?;
+        assert_eq!(selected(&app).as_deref(), Some("sub"));
+
+        app.store_selections();
+        assert_eq!(app.user_settings.selections[0].dir, dir.path());
+        assert_eq!(app.user_settings.selections[0].name, "sub");
+        app.config.persist_selections = false;
+        app.store_selections();
+        assert!(app.user_settings.selections.is_empty());
         Ok(())
     }
 
-    pub fn enable_plugin(&mut self, id: &str) -> Result<()> {
-        self.api_plugin_manager.enable(id)?;
-        self.user_settings.enabled_plugins.push(id.to_string());
-        self.message = Some(format!("Plugin enabled: {}", id));
+    #[test]
+    fn test_move_selected_into_subdirectory() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("done"))?;
+        std::fs::write(dir.path().join("todo.txt"), "ship it")?;
+        let mut app = test_app(dir.path(), |_| {})?;
+        let index = app.get_current_workspace().entries.iter().position(|e| e.name == "todo.txt").unwrap();
+        app.workspace_manager.active_workspace_mut().select(index);
+
+        app.execute_command(&Command::Move)?;
+        assert_eq!(app.mode, AppMode::Input(InputMode::MoveTo));
+        assert_eq!(app.input_buffer, format!("{}{}", dir.path().display(), std::path::MAIN_SEPARATOR));
+
+        app.move_selected("done")?;
+        assert!(app.message.as_deref().is_some_and(|m| m.starts_with("Moved to")), "{:?}", app.error);
+        assert_eq!(std::fs::read_to_string(dir.path().join("done").join("todo.txt"))?, "ship it");
+        assert!(!dir.path().join("todo.txt").exists());
+
+        // Brought back under a new name, it lands in view and is selected
+        let moved = app.move_entry(&dir.path().join("done").join("todo.txt"), "./done/../todo.md")?;
+        assert_eq!(moved, Some(dir.path().join("todo.md")));
+        assert_eq!(app.get_current_workspace().get_selected_entry().map(|e| e.name.as_str()), Some("todo.md"));
         Ok(())
     }
 
-    pub fn disable_plugin(&mut self, id: &str) -> Result<()> {
-        self.api_plugin_manager.disable(id)?;
-        self.user_settings.enabled_plugins.retain(|p| p != id);
-        self.message = Some(format!("Plugin disabled: {}", id));
+    #[test]
+    fn test_open_in_new_tab() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir_all(dir.path().join("photos").join("2024"))?;
+        let mut app = test_app(dir.path(), |_| {})?;
+        app.select_index(0);
+        app.execute_command(&Command::OpenInNewTab)?;
+        assert_eq!(app.workspace_manager.count(), 2);
+        assert_eq!(app.workspace_manager.active_index(), 1);
+        assert_eq!(app.get_current_workspace().current_dir, dir.path().join("photos"));
+        assert_eq!(app.get_current_workspace().entries[0].name, "2024", "listed straight away");
+
+        // Staying put opens it in the background
+        app.config.focus_new_tab = false;
+        assert_eq!(app.open_in_new_tab(&dir.path().join("photos").join("2024"))?, 2);
+        assert_eq!(app.workspace_manager.active_index(), 1);
+        assert_eq!(app.workspace_manager.workspaces()[2].current_dir, dir.path().join("photos").join("2024"));
+        assert!(app.open_in_new_tab(&dir.path().join("missing")).is_err());
+        assert_eq!(app.workspace_manager.count(), 3);
         Ok(())
     }
 
-    // ========== Settings Persistence ==========
-    pub fn save_settings(&mut self) -> Result<()> {
-        self.user_settings.current_theme = self.theme_manager.current_theme_name();
-        PersistenceManager::save_default(&self.user_settings)?;
-        self.message = Some("Settings saved".to_string());
+    #[test]
+    fn test_search_result_actions_open_reveal_and_delete() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sub = dir.path().join("sub");
+        std::fs::create_dir(&sub)?;
+        for name in ["a-notes.txt", "b-notes.txt"] {
+            std::fs::write(sub.join(name), "")?;
+        }
+        let mut app = test_app(dir.path(), |config| {
+            config.file_associations = HashMap::from([("txt".to_string(), "cat %f".to_string())]);
+        })?;
+        let search = |app: &mut App| {
+            app.start_search();
+            for c in "notes".chars() {
+                app.add_search_char(c);
+            }
+            app.search_engine.results.iter().map(|r| r.path.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(search(&mut app), vec![sub.join("a-notes.txt"), sub.join("b-notes.txt")]);
+
+        app.search_result_action(0, ResultAction::Open)?;
+        assert!(app.pending_command.as_deref().is_some_and(|c| c.contains("a-notes.txt")));
+        assert_eq!(app.mode, AppMode::Search, "opening a file keeps the search");
+
+        app.search_result_action(1, ResultAction::Delete)?;
+        assert!(!sub.join("b-notes.txt").exists());
+        assert_eq!(app.search_engine.results.len(), 1);
+
+        app.search_result_action(0, ResultAction::Reveal)?;
+        assert_eq!(app.mode, AppMode::Normal);
+        let workspace = app.get_current_workspace();
+        assert_eq!(workspace.current_dir, sub);
+        assert_eq!(workspace.get_selected_entry().map(|e| e.path.clone()), Some(sub.join("a-notes.txt")));
         Ok(())
     }
 
-    pub fn load_user_preferences(&mut self) -> Result<()> {
-        self.user_settings = PersistenceManager::load_default().unwrap_or_default();
-        self.message = Some("Preferences loaded".to_string());
+    #[test]
+    fn test_search_in_bookmark_limits_results_to_it() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let projects = dir.path().join("projects");
+        std::fs::create_dir_all(projects.join("astrofs"))?;
+        std::fs::create_dir_all(dir.path().join("notes"))?;
+        std::fs::write(projects.join("astrofs").join("todo.md"), "")?;
+        std::fs::write(dir.path().join("notes").join("todo.txt"), "")?;
+        let mut app = test_app(&dir.path().join("notes"), |_| {})?;
+        app.bookmark_manager = crate::bookmarks::BookmarkManager::with_file(dir.path().join("bookmarks.json"))?;
+        app.bookmark_manager.add("code".to_string(), projects.clone(), "📌".to_string())?;
+
+        app.execute_command(&Command::SearchIn)?;
+        assert_eq!(app.mode, AppMode::Input(InputMode::SearchIn));
+        app.start_search_in("code");
+        assert_eq!(app.mode, AppMode::Search);
+        for c in "todo".chars() {
+            app.add_search_char(c);
+        }
+        let found: Vec<PathBuf> = app.search_engine.results.iter().map(|r| r.path.clone()).collect();
+        assert_eq!(found, vec![projects.join("astrofs").join("todo.md")]);
+        assert_eq!(app.search_base(), projects);
+        assert_eq!(app.get_current_workspace().current_dir, dir.path().join("notes"), "browsing stays put");
+
+        // Closing search mode goes back to searching where you are
+        app.cancel_search();
+        app.start_search();
+        app.search_query = "todo".to_string();
+        app.run_search();
+        assert_eq!(app.search_engine.results[0].path, dir.path().join("notes").join("todo.txt"));
+        assert!(app.search_in(&dir.path().join("missing"), "todo").is_err());
         Ok(())
     }
 
-    pub fn export_settings(&mut self, path: &str) -> Result<()> {
-        let persistence = PersistenceManager::new()?;
-        persistence.export_settings(std::path::Path::new(path))?;
-        self.message = Some(format!("Settings exported to: {}", path));
+    #[test]
+    fn test_deleted_current_dir_moves_up_to_existing_parent() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let deep = dir.path().join("build").join("out").join("debug");
+        std::fs::create_dir_all(&deep)?;
+        std::fs::write(dir.path().join("build").join("log.txt"), "")?;
+        let mut app = test_app(&deep, |_| {})?;
+
+        std::fs::remove_dir_all(dir.path().join("build").join("out"))?;
+        assert!(app.poll_directory_changes()?);
+        assert_eq!(app.get_current_workspace().current_dir, dir.path().join("build"));
+        assert_eq!(app.get_current_workspace().entries[0].name, "log.txt");
+        assert!(app.error.as_deref().is_some_and(|e| e.contains("no longer exists")));
+        assert!(!app.poll_directory_changes()?, "nothing more to do once out");
+
+        // A bookmark to a deleted folder opens what is left of its path
+        app.bookmark_manager = crate::bookmarks::BookmarkManager::with_file(dir.path().join("bookmarks.json"))?;
+        app.bookmark_manager.add("gone".to_string(), dir.path().join("build").join("gone"), "📌".to_string())?;
+        app.goto_bookmark("gone")?;
+        assert_eq!(app.get_current_workspace().current_dir, dir.path().join("build"));
+
+        // So does a tab whose folder went while another tab was active
+        std::fs::create_dir(dir.path().join("cache"))?;
+        app.open_in_new_tab(&dir.path().join("cache"))?;
+        app.prev_workspace();
+        std::fs::remove_dir(dir.path().join("cache"))?;
+        app.next_workspace();
+        assert_eq!(app.get_current_workspace().current_dir, dir.path());
         Ok(())
     }
 
-    pub fn import_settings(&mut self, path: &str) -> Result<()> {
-        let persistence = PersistenceManager::new()?;
-        persistence.import_settings(std::path::Path::new(path))?;
-        self.user_settings = PersistenceManager::load_default().unwrap_or_default();
-        self.message = Some(format!("Settings imported from: {}", path));
+    #[test]
+    fn test_jump_prefix_resets_after_timeout() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        for name in ["alpha", "beta", "bravo", "Readme.md", "report.txt"] {
+            std::fs::write(dir.path().join(name), "")?;
+        }
+        let mut app = test_app(dir.path(), |config| config.jump_timeout_ms = 500)?;
+        app.start_jump();
+        let selected = |app: &App| app.get_current_workspace().get_selected_entry().map(|e| e.name.clone());
+
+        // Keys within the timeout build one prefix
+        let start = Instant::now();
+        app.jump_key('b', start);
+        assert_eq!(selected(&app).as_deref(), Some("beta"));
+        app.jump_key('r', start + Duration::from_millis(300));
+        assert_eq!(selected(&app).as_deref(), Some("bravo"));
+        assert_eq!(app.jump_prefix_shown(start + Duration::from_millis(400)), Some("br"));
+
+        // After a pause the next key starts over
+        let later = start + Duration::from_millis(1000);
+        assert_eq!(app.jump_prefix_shown(later), None);
+        app.jump_key('R', later);
+        assert_eq!(app.jump_prefix, "r");
+        assert_eq!(selected(&app).as_deref(), Some("Readme.md"));
+        app.jump_key('e', later + Duration::from_millis(100));
+        app.jump_key('p', later + Duration::from_millis(200));
+        assert_eq!(selected(&app).as_deref(), Some("report.txt"));
+
+        // A prefix nothing starts with keeps the selection
+        app.jump_key('z', later + Duration::from_millis(300));
+        assert_eq!(selected(&app).as_deref(), Some("report.txt"));
+        assert!(app.message.is_some());
+        app.jump_backspace(later + Duration::from_millis(400));
+        assert_eq!(app.jump_prefix, "rep");
+
+        app.cancel_jump();
+        assert!(matches!(app.mode, AppMode::Normal));
+        Ok(())
+    }
+
+    #[test]
+    fn test_low_priority_jobs_setting_reaches_workers() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("sub"))?;
+        std::fs::write(dir.path().join("sub/a.txt"), "hello")?;
+        let mut app = test_app(dir.path(), |_| {})?;
+        assert!(!app.jobs.low_priority(), "normal priority by default");
+
+        app.settings_index = EDITABLE_SETTINGS.iter().position(|(key, _)| *key == "low_priority_jobs").unwrap();
+        app.update_setting("true")?;
+        assert!(app.config.low_priority_jobs && app.jobs.low_priority());
+
+        // Lowered workers still get their work done
+        app.open_disk_usage();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while app.disk_usage.as_ref().is_some_and(|view| view.scan.is_some()) && Instant::now() < deadline {
+            app.poll_disk_usage();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let view = app.disk_usage.as_ref().expect("disk usage view");
+        assert_eq!(view.total, 5);
+        app.search_engine.search_dir(dir.path(), "a.txt", 10, 5);
+        assert_eq!(app.search_engine.results.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_plugin_dir_and_reload_picks_up_new_plugins() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let first = tempfile::tempdir()?;
+        let second = tempfile::tempdir()?;
+        let mut app = test_app(dir.path(), |config| {
+            config.plugin_directory = first.path().to_string_lossy().to_string();
+            config.enable_plugins = true;
+        })?;
+        app.user_settings.plugin_directory = PathBuf::new();
+        app.reload_plugins()?;
+        assert_eq!(app.plugin_dir(), first.path());
+        assert!(app.api_plugin_manager.get("hello-wasm").is_none());
+        assert!(app.api_plugin_manager.get("file-stats").is_some(), "built-ins come back");
+
+        // A plugin in the new directory is picked up by both managers
+        let example = Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins/wasm");
+        std::fs::copy(example.join("hello.wasm"), second.path().join("hello.wasm"))?;
+        std::fs::copy(example.join("hello.json"), second.path().join("hello.json"))?;
+        std::fs::write(second.path().join("legacy.so"), "")?;
+        app.set_plugin_dir(&second.path().to_string_lossy())?;
+        assert_eq!(app.plugin_dir(), second.path());
+        assert_eq!(app.api_plugin_manager.plugin_dir(), second.path());
+        assert!(app.api_plugin_manager.get("hello-wasm").is_some());
+        assert!(app.plugin_manager.get("legacy").is_some());
+
+        // Dropped in later, it shows up once plugins are reloaded
+        std::fs::copy(example.join("hello.wasm"), first.path().join("hello.wasm"))?;
+        app.set_plugin_dir("")?;
+        assert_eq!(app.plugin_dir(), first.path());
+        assert!(app.plugin_manager.get("legacy").is_none());
+        assert!(app.api_plugin_manager.get("hello-wasm").is_none());
+        assert!(app.api_plugin_manager.get("hello").is_some(), "no manifest, so named after the file");
+        Ok(())
+    }
+
+    #[test]
+    fn test_reload_leaves_plugins_off_when_disabled() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let plugins = tempfile::tempdir()?;
+        let example = Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins/wasm");
+        std::fs::copy(example.join("hello.wasm"), plugins.path().join("hello.wasm"))?;
+        let mut app = test_app(dir.path(), |config| {
+            config.plugin_directory = plugins.path().to_string_lossy().to_string();
+        })?;
+        assert!(!app.config.enable_plugins);
+        app.user_settings.plugin_directory = PathBuf::new();
+        app.reload_plugins()?;
+        assert!(app.api_plugin_manager.get("hello").is_none());
+        assert!(app.api_plugin_manager.get("file-stats").is_some(), "built-ins are still listed");
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_commands_report_structured_outcomes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("sub"))?;
+        std::fs::write(dir.path().join("sub/notes.txt"), "hi")?;
+        let mut app = test_app(&dir.path().join("sub"), |_| {})?;
+
+        assert_eq!(
+            app.execute_command_named("duplicate")?,
+            CommandOutcome::Created(vec![dir.path().join("sub/notes copy.txt")])
+        );
+        assert_eq!(app.execute_command_named("parent")?, CommandOutcome::Navigated(dir.path().to_path_buf()));
+        assert_eq!(app.execute_command_named("new-workspace")?, CommandOutcome::SwitchedTab(1));
+        assert_eq!(
+            app.execute_command_named("create-file")?,
+            CommandOutcome::Opened(AppMode::Input(InputMode::CreateFile))
+        );
+        app.mode = AppMode::Normal;
+        assert_eq!(app.execute_command_named("first")?, CommandOutcome::Nothing);
+
+        // A file that turns up on its own isn't something refresh created
+        std::fs::write(dir.path().join("outside.txt"), "")?;
+        let refreshed = app.execute_command_named("refresh")?;
+        assert!(matches!(&refreshed, CommandOutcome::Message(m) if m.starts_with("Refreshed")), "{:?}", refreshed);
+
+        let select = |app: &mut App, name: &str| {
+            let index = app.get_current_workspace().entries.iter().position(|e| e.name == name);
+            app.select_index(index.expect(name));
+        };
+        app.go_to_path(&dir.path().join("sub").to_string_lossy())?;
+        select(&mut app, "notes.txt");
+        assert!(matches!(app.execute_command_named("copy")?, CommandOutcome::Message(_)));
+        app.execute_command_named("parent")?;
+        let pasted = dir.path().join("notes.txt");
+        assert_eq!(app.execute_command_named("paste")?, CommandOutcome::Created(vec![pasted.clone()]));
+        select(&mut app, "notes.txt");
+        assert_eq!(app.execute_command_named("delete")?, CommandOutcome::Removed(vec![pasted]));
+
+        assert!(app.execute_command_named("no-such-command").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_preview_line_limits_follow_the_config() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let code: String = (0..300).map(|i| format!("let x{} = {};\n", i, i)).collect();
+        std::fs::write(dir.path().join("long.rs"), code)?;
+        let mut app = test_app(dir.path(), |_| {})?;
+        let preview_lines = |app: &App| app.get_current_workspace().preview.lines.len();
+        assert_eq!(preview_lines(&app), crate::preview::PREVIEW_MAX_LINES);
+
+        app.settings_index = EDITABLE_SETTINGS.iter().position(|(key, _)| *key == "preview_max_lines").unwrap();
+        app.update_setting("50")?;
+        assert_eq!(preview_lines(&app), 50, "highlighting stops at the new limit");
+
+        // Quick look takes its own, larger limit
+        app.config.quick_look_max_lines = 120;
+        app.open_quick_look();
+        assert_eq!(app.quick_look.as_ref().map(|look| look.content.lines.len()), Some(120));
+        app.config.quick_look_max_lines = 10;
+        app.open_quick_look();
+        assert_eq!(app.quick_look.as_ref().map(|look| look.content.lines.len()), Some(50));
         Ok(())
     }
 }
@@ -0,0 +1,278 @@
+// Archives - browse .zip and .tar files as read-only folders
+use crate::files::{FileEntry, SortMode};
+use anyhow::{anyhow, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether `path` names an archive that can be browsed like a folder
+pub fn is_archive(path: &Path) -> bool {
+    ArchiveKind::of(path).is_some()
+}
+
+/// Everything in an archive, with paths under the archive file itself
+/// (`photos.zip/2021/a.jpg`) so they can be navigated like a folder's
+#[derive(Clone, Debug)]
+pub struct ArchiveListing {
+    pub root: PathBuf,
+    entries: Vec<FileEntry>,
+}
+
+impl ArchiveListing {
+    /// Read the table of contents of the archive at `root`
+    pub fn open(root: &Path) -> Result<Self> {
+        let kind = ArchiveKind::of(root).ok_or_else(|| anyhow!("Not a supported archive: {}", root.display()))?;
+        let file = fs::File::open(root)?;
+        let mut found: Vec<(PathBuf, bool, u64, Option<SystemTime>)> = Vec::new();
+        match kind {
+            ArchiveKind::Zip => {
+                let mut zip = zip::ZipArchive::new(file)?;
+                for i in 0..zip.len() {
+                    let file = zip.by_index(i)?;
+                    let stamp = file.last_modified();
+                    let modified = chrono::NaiveDate::from_ymd_opt(stamp.year().into(), stamp.month().into(), stamp.day().into())
+                        .and_then(|d| d.and_hms_opt(stamp.hour().into(), stamp.minute().into(), stamp.second().into()))
+                        .map(|t| SystemTime::from(t.and_utc()));
+                    found.push((PathBuf::from(file.name()), file.is_dir(), file.size(), modified));
+                }
+            }
+            ArchiveKind::Tar | ArchiveKind::TarGz => {
+                let mut tar = tar::Archive::new(tar_reader(kind, file));
+                for entry in tar.entries()? {
+                    let entry = entry?;
+                    let header = entry.header();
+                    let modified = header.mtime().ok().map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+                    found.push((entry.path()?.into_owned(), header.entry_type().is_dir(), header.size()?, modified));
+                }
+            }
+        }
+
+        // Folders are often only implied by the files in them
+        let mut entries: HashMap<PathBuf, FileEntry> = HashMap::new();
+        for (inner, is_dir, size, modified) in found {
+            let Some(inner) = clean_inner_path(&inner) else {
+                continue;
+            };
+            for folder in inner.ancestors().skip(1).filter(|a| !a.as_os_str().is_empty()) {
+                entries.entry(root.join(folder)).or_insert_with(|| virtual_entry(root.join(folder), true, 0, None));
+            }
+            let path = root.join(&inner);
+            entries.insert(path.clone(), virtual_entry(path, is_dir, if is_dir { 0 } else { size }, modified));
+        }
+        Ok(Self { root: root.to_path_buf(), entries: entries.into_values().collect() })
+    }
+
+    /// Every file and folder in the archive, in no particular order
+    pub fn entries(&self) -> &[FileEntry] {
+        &self.entries
+    }
+
+    /// Whether `path` is the archive or something inside it
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.root)
+    }
+
+    /// The entries directly inside `dir` (the archive itself for the top level), by name
+    pub fn children(&self, dir: &Path, show_hidden: bool) -> Vec<FileEntry> {
+        let mut children: Vec<FileEntry> = self
+            .entries
+            .iter()
+            .filter(|e| e.path.parent() == Some(dir) && (show_hidden || !e.is_hidden))
+            .cloned()
+            .collect();
+        SortMode::Name.sort(&mut children);
+        children
+    }
+
+    /// Whether `dir` is a folder in the archive; false for the archive itself
+    pub fn is_dir(&self, dir: &Path) -> bool {
+        self.entries.iter().any(|e| e.is_dir && e.path == dir)
+    }
+
+    /// Extract the file at `path` under `temp_dir`, keeping its name so previews
+    /// and openers can tell its type. Extracted again only once the archive changes.
+    pub fn extract_cached(&self, path: &Path, temp_dir: &Path) -> Result<PathBuf> {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        if let Ok(metadata) = fs::metadata(&self.root) {
+            metadata.len().hash(&mut hasher);
+            metadata.modified().ok().hash(&mut hasher);
+        }
+        let dest_dir = temp_dir.join(format!("{:016x}", hasher.finish()));
+        let dest = dest_dir.join(path.file_name().unwrap_or_default());
+        if !dest.exists() {
+            fs::create_dir_all(&dest_dir)?;
+            if let Err(e) = self.extract(path, &dest) {
+                let _ = fs::remove_file(&dest);
+                return Err(e);
+            }
+        }
+        Ok(dest)
+    }
+
+    /// Write the file at `path` inside the archive to `dest`
+    pub fn extract(&self, path: &Path, dest: &Path) -> Result<()> {
+        let inner = path
+            .strip_prefix(&self.root)
+            .ok()
+            .and_then(clean_inner_path)
+            .ok_or_else(|| anyhow!("{} is not in {}", path.display(), self.root.display()))?;
+        let kind = ArchiveKind::of(&self.root).ok_or_else(|| anyhow!("Not a supported archive"))?;
+        let file = fs::File::open(&self.root)?;
+        let mut out = fs::File::create(dest)?;
+        match kind {
+            ArchiveKind::Zip => {
+                let mut zip = zip::ZipArchive::new(file)?;
+                for i in 0..zip.len() {
+                    let mut file = zip.by_index(i)?;
+                    if !file.is_dir() && clean_inner_path(Path::new(file.name())).as_deref() == Some(inner.as_path()) {
+                        io::copy(&mut file, &mut out)?;
+                        return Ok(());
+                    }
+                }
+            }
+            ArchiveKind::Tar | ArchiveKind::TarGz => {
+                let mut tar = tar::Archive::new(tar_reader(kind, file));
+                for entry in tar.entries()? {
+                    let mut entry = entry?;
+                    if entry.header().entry_type().is_file()
+                        && clean_inner_path(&entry.path()?).as_deref() == Some(inner.as_path())
+                    {
+                        io::copy(&mut entry, &mut out)?;
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        drop(out);
+        let _ = fs::remove_file(dest);
+        Err(anyhow!("{} is not a file in {}", inner.display(), self.root.display()))
+    }
+}
+
+fn tar_reader(kind: ArchiveKind, file: fs::File) -> Box<dyn Read> {
+    if kind == ArchiveKind::TarGz {
+        Box::new(flate2::read::GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    }
+}
+
+/// A path inside an archive without `./` or a trailing slash; None for the
+/// archive root and for names that would climb out of it (`../x`, `/etc/x`)
+fn clean_inner_path(path: &Path) -> Option<PathBuf> {
+    let mut clean = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => clean.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!clean.as_os_str().is_empty()).then_some(clean)
+}
+
+fn virtual_entry(path: PathBuf, is_dir: bool, size: u64, modified: Option<SystemTime>) -> FileEntry {
+    let name: String = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_control() { '?' } else { c })
+        .collect();
+    FileEntry {
+        is_hidden: name.starts_with('.'),
+        path,
+        name,
+        is_dir,
+        size,
+        modified,
+        item_count: None,
+        mime: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_zip_lists_as_folders_and_extracts() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().join("photos.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&root)?);
+        let options = zip::write::FileOptions::default();
+        zip.start_file("2021/june/a.txt", options)?;
+        zip.write_all(b"hello")?;
+        zip.start_file("readme.md", options)?;
+        zip.write_all(b"# photos")?;
+        zip.start_file("../escape.txt", options)?;
+        zip.finish()?;
+
+        let listing = ArchiveListing::open(&root)?;
+        let names = |dir: &Path| listing.children(dir, true).into_iter().map(|e| e.name).collect::<Vec<_>>();
+        assert_eq!(names(&root), ["2021", "readme.md"], "implied folder first, escaping path left out");
+        assert_eq!(names(&root.join("2021")), ["june"]);
+        assert!(listing.is_dir(&root.join("2021/june")));
+        assert_eq!(listing.children(&root.join("2021/june"), true)[0].size, 5);
+
+        let out = dir.path().join("a.txt");
+        listing.extract(&root.join("2021/june/a.txt"), &out)?;
+        assert_eq!(fs::read_to_string(&out)?, "hello");
+        assert!(listing.extract(&root.join("2021"), &out).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tar_gz_lists_and_extracts() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().join("src.tar.gz");
+        let gz = flate2::write::GzEncoder::new(fs::File::create(&root)?, flate2::Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(3);
+        header.set_mode(0o644);
+        header.set_mtime(1_600_000_000);
+        header.set_cksum();
+        tar.append_data(&mut header, "pkg/.hidden", &b"abc"[..])?;
+        tar.into_inner()?.finish()?;
+
+        let listing = ArchiveListing::open(&root)?;
+        assert!(listing.children(&root.join("pkg"), false).is_empty());
+        let hidden = &listing.children(&root.join("pkg"), true)[0];
+        assert_eq!((hidden.name.as_str(), hidden.size), (".hidden", 3));
+        assert_eq!(hidden.modified, Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000)));
+
+        let out = dir.path().join("out");
+        listing.extract(&root.join("pkg/.hidden"), &out)?;
+        assert_eq!(fs::read(&out)?, b"abc");
+        Ok(())
+    }
+}
@@ -3,21 +3,31 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use dirs::data_dir;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Bookmark {
     pub name: String,
     pub path: PathBuf,
     pub icon: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl Bookmark {
     pub fn new(name: String, path: PathBuf, icon: String) -> Self {
-        Self { name, path, icon }
+        Self { name, path, icon, tags: Vec::new() }
     }
 }
 
+/// Portable bookmark file used for sharing bookmark sets
+#[derive(Debug, Serialize, Deserialize)]
+struct BookmarkExport {
+    version: u32,
+    bookmarks: Vec<Bookmark>,
+}
+
+const EXPORT_VERSION: u32 = 1;
+
 /// Manages bookmarks for quick access to directories
 pub struct BookmarkManager {
     bookmarks: HashMap<String, Bookmark>,
@@ -26,13 +36,17 @@ pub struct BookmarkManager {
 }
 
 impl BookmarkManager {
+    /// Load the user's bookmarks file
+    #[deprecated(note = "use with_file with a path under AppDirs::data_dir")]
+    #[allow(dead_code)]
     pub fn new() -> Result<Self> {
-        let data_dir = data_dir().ok_or_else(|| anyhow::anyhow!("Cannot find data directory"))?;
-        let astrofs_dir = data_dir.join("astrofs");
-        fs::create_dir_all(&astrofs_dir)?;
-        
-        let file_path = astrofs_dir.join("bookmarks.json");
-        
+        let data_dir = crate::app::AppDirs::user()?.data_dir;
+        fs::create_dir_all(&data_dir)?;
+        Self::with_file(data_dir.join("bookmarks.json"))
+    }
+
+    /// Create a manager backed by a specific bookmarks file
+    pub fn with_file(file_path: PathBuf) -> Result<Self> {
         let mut manager = Self {
             bookmarks: HashMap::new(),
             order: Vec::new(),
@@ -107,6 +121,78 @@ impl BookmarkManager {
         self.bookmarks.values().any(|b| b.path == path)
     }
 
+    /// Export all bookmarks, in order, to a portable file
+    pub fn export(&self, path: &Path) -> Result<()> {
+        let export = BookmarkExport {
+            version: EXPORT_VERSION,
+            bookmarks: self.list().into_iter().cloned().collect(),
+        };
+        let content = serde_json::to_string_pretty(&export)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Import bookmarks from a portable file.
+    ///
+    /// Without `merge` the current bookmarks are replaced. With `merge` the
+    /// imported set is appended: a bookmark matching an existing name and
+    /// path is skipped, and a name collision with a different path is
+    /// renamed with a numeric suffix. Returns the number of bookmarks added.
+    pub fn import(&mut self, path: &Path, merge: bool) -> Result<usize> {
+        let content = fs::read_to_string(path)?;
+        let export: BookmarkExport = serde_json::from_str(&content)?;
+        if export.version > EXPORT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported bookmark file version {}",
+                export.version
+            ));
+        }
+
+        if !merge {
+            self.bookmarks.clear();
+            self.order.clear();
+        }
+
+        let mut added = 0;
+        for mut bookmark in export.bookmarks {
+            if let Some(existing) = self.bookmarks.get(&bookmark.name) {
+                if existing.path == bookmark.path {
+                    continue;
+                }
+                bookmark.name = self.unique_name(&bookmark.name);
+            }
+
+            self.order.push(bookmark.name.clone());
+            self.bookmarks.insert(bookmark.name.clone(), bookmark);
+            added += 1;
+        }
+
+        self.save()?;
+        Ok(added)
+    }
+
+    /// Default name for a bookmark to `path`: its last component (the whole
+    /// path for a root), made unique if another bookmark already has it
+    pub fn suggest_name(&self, path: &Path) -> String {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string_lossy().into_owned());
+        if self.bookmarks.contains_key(&name) {
+            self.unique_name(&name)
+        } else {
+            name
+        }
+    }
+
+    /// Find a free name by appending " (2)", " (3)", ...
+    fn unique_name(&self, name: &str) -> String {
+        (2..)
+            .map(|n| format!("{} ({})", name, n))
+            .find(|candidate| !self.bookmarks.contains_key(candidate))
+            .unwrap_or_else(|| name.to_string())
+    }
+
     /// Load bookmarks from file
     fn load(&mut self) -> Result<()> {
         if !self.file_path.exists() {
@@ -125,18 +211,18 @@ impl BookmarkManager {
     /// Save bookmarks to file
     pub fn save(&self) -> Result<()> {
         let content = serde_json::to_string_pretty(&self.bookmarks)?;
-        fs::write(&self.file_path, content)?;
+        crate::files::write_atomic(&self.file_path, content.as_bytes())?;
         Ok(())
     }
 }
 
 impl Default for BookmarkManager {
     fn default() -> Self {
-        Self::new().unwrap_or_else(|_| Self {
+        Self {
             bookmarks: HashMap::new(),
             order: Vec::new(),
             file_path: PathBuf::new(),
-        })
+        }
     }
 }
 
@@ -146,7 +232,8 @@ mod tests {
 
     #[test]
     fn test_bookmark_operations() -> Result<()> {
-        let mut manager = BookmarkManager::default();
+        let dir = tempfile::tempdir()?;
+        let mut manager = BookmarkManager::with_file(dir.path().join("bookmarks.json"))?;
         
         manager.add("home".to_string(), PathBuf::from("/home"), "🏠".to_string())?;
         assert_eq!(manager.count(), 1);
@@ -159,4 +246,60 @@ mod tests {
         
         Ok(())
     }
+
+    #[test]
+    fn test_suggest_name() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut manager = BookmarkManager::with_file(dir.path().join("bookmarks.json"))?;
+        assert_eq!(manager.suggest_name(Path::new("/home/ada/projects")), "projects");
+        assert_eq!(manager.suggest_name(Path::new("/")), "/");
+
+        manager.add("projects".to_string(), PathBuf::from("/work/projects"), "📌".to_string())?;
+        assert_eq!(manager.suggest_name(Path::new("/home/ada/projects")), "projects (2)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_import_round_trip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut manager = BookmarkManager::with_file(dir.path().join("bookmarks.json"))?;
+        manager.add("zeta".to_string(), PathBuf::from("/zeta"), "📌".to_string())?;
+        manager.add("alpha".to_string(), PathBuf::from("/alpha"), "🏠".to_string())?;
+        manager.bookmarks.get_mut("alpha").unwrap().tags = vec!["work".to_string()];
+
+        let export_path = dir.path().join("export.json");
+        manager.export(&export_path)?;
+
+        let mut other = BookmarkManager::with_file(dir.path().join("other.json"))?;
+        other.add("stale".to_string(), PathBuf::from("/stale"), "📌".to_string())?;
+        assert_eq!(other.import(&export_path, false)?, 2);
+
+        let names: Vec<&str> = other.list().iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["zeta", "alpha"]);
+        assert_eq!(other.get("alpha").unwrap().tags, vec!["work".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_merge_with_collisions() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut source = BookmarkManager::with_file(dir.path().join("source.json"))?;
+        source.add("home".to_string(), PathBuf::from("/home"), "🏠".to_string())?;
+        source.add("src".to_string(), PathBuf::from("/other/src"), "📌".to_string())?;
+        source.add("docs".to_string(), PathBuf::from("/docs"), "📌".to_string())?;
+        let export_path = dir.path().join("export.json");
+        source.export(&export_path)?;
+
+        let mut target = BookmarkManager::with_file(dir.path().join("target.json"))?;
+        target.add("home".to_string(), PathBuf::from("/home"), "🏠".to_string())?;
+        target.add("src".to_string(), PathBuf::from("/work/src"), "📌".to_string())?;
+
+        // "home" is an exact duplicate, "src" collides with a different path
+        assert_eq!(target.import(&export_path, true)?, 2);
+        assert_eq!(target.count(), 4);
+        assert_eq!(target.get("src").unwrap().path, PathBuf::from("/work/src"));
+        assert_eq!(target.get("src (2)").unwrap().path, PathBuf::from("/other/src"));
+        assert!(target.get("docs").is_some());
+        Ok(())
+    }
 }
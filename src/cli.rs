@@ -0,0 +1,213 @@
+// CLI - headless subcommands that print to stdout instead of starting the TUI
+use crate::files::{dir_stats, list_directory, FileEntry};
+use crate::search::SearchEngine;
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+#[derive(Debug, Parser)]
+#[command(name = "astrofs", version, about = "Terminal file explorer")]
+pub struct Cli {
+    /// Directory to open, or a file to select in its directory
+    pub path: Option<PathBuf>,
+
+    /// Use this config file instead of the default one
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Print the log file location and exit
+    #[arg(long)]
+    pub log_path: bool,
+
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Run a command and exit instead of starting the interactive UI
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CliCommand {
+    /// List a directory, directories first
+    Ls {
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+        /// Include hidden entries
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// Fuzzy-search file names below a directory, best matches first
+    Search {
+        dir: PathBuf,
+        query: String,
+        /// Maximum number of results
+        #[arg(short = 'n', long, default_value_t = 100)]
+        max_results: usize,
+    },
+    /// Count directories, files and total size below a directory
+    Stats {
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+        /// Count what symlinks point to, like `du -L`
+        #[arg(short = 'L', long)]
+        follow_symlinks: bool,
+    },
+}
+
+/// Run a subcommand, writing its output to `out`
+pub fn run(command: &CliCommand, as_json: bool, out: &mut impl Write) -> Result<()> {
+    match command {
+        CliCommand::Ls { dir, all } => ls(dir, *all, as_json, out),
+        CliCommand::Search {
+            dir,
+            query,
+            max_results,
+        } => search(dir, query, *max_results, as_json, out),
+        CliCommand::Stats { dir, follow_symlinks } => stats(dir, *follow_symlinks, as_json, out),
+    }
+}
+
+fn ls(dir: &Path, all: bool, as_json: bool, out: &mut impl Write) -> Result<()> {
+    let entries = list_directory(dir, all).map_err(|e| anyhow!("{}: {}", dir.display(), e))?;
+
+    if as_json {
+        let entries: Vec<_> = entries.iter().map(entry_json).collect();
+        writeln!(out, "{}", serde_json::to_string_pretty(&entries)?)?;
+        return Ok(());
+    }
+    for entry in &entries {
+        let suffix = if entry.is_dir { "/" } else { "" };
+        writeln!(out, "{:>10}  {}{}", entry.size_formatted(), entry.name, suffix)?;
+    }
+    Ok(())
+}
+
+fn search(dir: &Path, query: &str, max_results: usize, as_json: bool, out: &mut impl Write) -> Result<()> {
+    if !dir.is_dir() {
+        return Err(anyhow!("Not a directory: {}", dir.display()));
+    }
+    let mut engine = SearchEngine::new();
+    engine.search_current_dir(dir, query, max_results);
+
+    if as_json {
+        let results: Vec<_> = engine
+            .results
+            .iter()
+            .map(|r| {
+                json!({
+                    "path": r.path,
+                    "name": r.name,
+                    "is_dir": r.is_dir,
+                    "relevance": r.relevance,
+                })
+            })
+            .collect();
+        writeln!(out, "{}", serde_json::to_string_pretty(&results)?)?;
+        return Ok(());
+    }
+    // One path per line, so results can be piped into other tools
+    for result in &engine.results {
+        writeln!(out, "{}", result.path.display())?;
+    }
+    Ok(())
+}
+
+fn stats(dir: &Path, follow_symlinks: bool, as_json: bool, out: &mut impl Write) -> Result<()> {
+    let stats = dir_stats(dir, follow_symlinks)?;
+
+    if as_json {
+        let value = json!({
+            "path": dir,
+            "dirs": stats.dirs,
+            "files": stats.files,
+            "total_size": stats.total_size,
+        });
+        writeln!(out, "{}", serde_json::to_string_pretty(&value)?)?;
+        return Ok(());
+    }
+    writeln!(out, "Directories: {}", stats.dirs)?;
+    writeln!(out, "Files:       {}", stats.files)?;
+    writeln!(
+        out,
+        "Total size:  {} ({} bytes)",
+        humansize::format_size(stats.total_size, humansize::BINARY),
+        stats.total_size
+    )?;
+    Ok(())
+}
+
+fn entry_json(entry: &FileEntry) -> serde_json::Value {
+    let modified = entry
+        .modified
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+    json!({
+        "name": entry.name,
+        "path": entry.path,
+        "is_dir": entry.is_dir,
+        "size": entry.size,
+        "modified": modified,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn run_to_string(args: &[&str]) -> Result<String> {
+        let cli = Cli::try_parse_from(args)?;
+        let command = cli.command.ok_or_else(|| anyhow!("no subcommand"))?;
+        let mut out = Vec::new();
+        run(&command, cli.json, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_start_path_and_config_args() -> Result<()> {
+        let cli = Cli::try_parse_from(["astrofs", "--config", "/tmp/alt.json", "/srv/data"])?;
+        assert_eq!(cli.path, Some(PathBuf::from("/srv/data")));
+        assert_eq!(cli.config, Some(PathBuf::from("/tmp/alt.json")));
+        assert!(cli.command.is_none());
+
+        let cli = Cli::try_parse_from(["astrofs", "stats", "/srv/data"])?;
+        assert!(cli.path.is_none());
+        assert!(matches!(cli.command, Some(CliCommand::Stats { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_headless_commands() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("src"))?;
+        fs::write(dir.path().join("src").join("main.rs"), "fn main() {}")?;
+        fs::write(dir.path().join("notes.txt"), "hi")?;
+        fs::write(dir.path().join(".hidden"), "")?;
+        let root = dir.path().to_str().unwrap();
+
+        let listing = run_to_string(&["astrofs", "ls", root])?;
+        let lines: Vec<_> = listing.lines().map(str::trim).collect();
+        assert_eq!(lines, vec!["<DIR>  src/", "2 B  notes.txt"]);
+
+        let entries: serde_json::Value = serde_json::from_str(&run_to_string(&["astrofs", "ls", "-a", root, "--json"])?)?;
+        assert_eq!(entries.as_array().map(Vec::len), Some(3));
+        assert_eq!(entries[0]["name"], "src");
+
+        let found = run_to_string(&["astrofs", "search", root, "main"])?;
+        assert!(found.trim().ends_with("main.rs"));
+
+        let stats: serde_json::Value = serde_json::from_str(&run_to_string(&["astrofs", "--json", "stats", root])?)?;
+        assert_eq!(stats["dirs"], 1);
+        assert_eq!(stats["files"], 3);
+        assert_eq!(stats["total_size"], 14);
+
+        assert!(run_to_string(&["astrofs", "stats", "/definitely/not/here"]).is_err());
+        Ok(())
+    }
+}
@@ -1,10 +1,12 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use dirs::config_dir;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub theme: String,
     pub show_hidden: bool,
@@ -15,8 +17,209 @@ pub struct AppConfig {
     pub enable_git_integration: bool,
     pub enable_plugins: bool,
     pub plugin_directory: String,
+    /// Refresh the listing automatically when the current directory changes on disk
+    pub auto_refresh: bool,
+    /// Commands used to open files by extension, e.g. `"md": "glow %f"`.
+    /// `%f` is replaced with the quoted path; without it the path is appended.
+    pub file_associations: HashMap<String, String>,
+    /// Gallery thumbnail rendering: "auto", "kitty", "sixel" or "blocks"
+    pub thumbnail_protocol: String,
+    /// Skip files ignored by .gitignore in recursive views
+    pub respect_gitignore: bool,
+    /// Maximum number of lines rendered in file previews
+    #[serde(alias = "max_preview_lines")]
+    pub preview_max_lines: usize,
+    /// Lines quick look (`P`) renders; `preview_max_lines` when that is larger
+    pub quick_look_max_lines: usize,
+    /// Number of entries shown in the recently-modified view
+    pub recent_files_count: usize,
+    /// Move deleted files to the trash, with a short window to undo
+    pub safe_delete: bool,
+    /// Seconds the undo toast stays up after a safe delete
+    pub undo_window_secs: u64,
+    /// Megabytes the trash may hold before its oldest items are deleted for good at startup; 0 = no limit
+    pub trash_max_size_mb: u64,
+    /// Days trashed items are kept before startup deletes them for good; 0 = keep them
+    pub trash_max_age_days: u64,
+    /// Ask before a rename replaces an existing file; when off, renames overwrite
+    pub confirm_rename_overwrite: bool,
+    /// Log level for the log file: "off", "error", "warn", "info", "debug" or "trace".
+    /// The `ASTROFS_LOG` environment variable takes precedence.
+    pub log_level: String,
+    /// Search scope used when search mode opens: "current", "recursive" or "drive"
+    pub search_scope: String,
+    /// How many levels below the current directory a recursive search descends
+    #[serde(alias = "search_depth_current")]
+    pub search_max_depth: usize,
+    /// Directory the whole-drive scope searches from
+    pub search_root: String,
+    /// How many levels below `search_root` a whole-drive search descends
+    #[serde(alias = "search_depth_drive")]
+    pub drive_search_max_depth: usize,
+    /// Seconds a whole-drive search runs before stopping with partial results; 0 for no limit
+    pub drive_search_time_limit_secs: u64,
+    /// Search results grouping: "none", "kind" (folders first) or "parent"
+    pub search_grouping: String,
+    /// Globs hidden from listings and searches regardless of .gitignore, e.g. `*.pyc`
+    pub ignore_patterns: Vec<String>,
+    /// Globs listed even while hidden files are off, e.g. `.gitignore`
+    pub always_show: Vec<String>,
+    /// Globs treated as hidden files, shown only while hidden files are on
+    pub always_hide: Vec<String>,
+    /// Checksum copied to the clipboard with `Y`: "md5", "sha256" or "blake3"
+    pub hash_copy_algorithm: String,
+    /// Show the time in the status bar
+    pub status_show_clock: bool,
+    /// chrono format for the status bar clock, e.g. "%H:%M" or "%a %d %b %H:%M"
+    pub status_clock_format: String,
+    /// How timestamps are written in the file list, trash and previews: a chrono
+    /// format like "%Y-%m-%d %H:%M" or "%d %b %Y", or "relative" ("3h ago")
+    pub date_format: String,
+    /// Show each entry's modification time in a column of the file list
+    pub date_column: bool,
+    /// Show free/total space of the current directory's filesystem in the status bar
+    pub status_show_disk_space: bool,
+    /// Show how many items each folder holds next to its name; costs a read per folder
+    pub show_dir_counts: bool,
+    /// Show each entry's git status (`M`, `+`, `?`, `!`) in a column at the right of the file list
+    pub git_status_column: bool,
+    /// Show each file's size with a bar scaled to the largest file in the folder, like ncdu
+    pub size_bar_column: bool,
+    /// Shade names by modification time: recently changed entries bold, long
+    /// untouched ones dimmed
+    pub age_shading: bool,
+    /// Show directories as `name/` in the file list and search results
+    pub dir_trailing_slash: bool,
+    /// How hidden entries stand out: "dot" (`·name`, dimmed), "dim" (dimmed only)
+    /// or "name" (just the real name with its leading `.`)
+    pub hidden_marker: String,
+    /// Row numbers before each entry in the file list: "off", "absolute" or
+    /// "relative" (distance from the selection, for counted jumps like `5j`)
+    pub line_numbers: String,
+    /// Preview syntax by file name or extension, e.g. `"Dockerfile": "Bourne Again Shell (bash)"`.
+    /// Checked before syntect's own detection.
+    pub syntax_overrides: HashMap<String, String>,
+    /// Preview kind by extension, e.g. `"bin": "hex"` or `"json": "text"`; one of
+    /// text, code, hex, metadata or archive. Checked before the contents are looked at.
+    pub preview_overrides: HashMap<String, String>,
+    /// syntect theme for code previews, e.g. "base16-ocean.dark" or "InspiredGitHub"
+    pub highlight_theme: String,
+    /// Follow symlinks in recursive copies, size counts and searches (cycles are always skipped)
+    pub follow_symlinks: bool,
+    /// Milliseconds between UI updates while something is happening; idle waits stretch to a second
+    pub poll_interval_ms: u64,
+    /// Largest file, in bytes, the built-in editor (`e`) opens
+    pub max_edit_size: u64,
+    /// Seconds between autosaves of settings, bookmarks and search history while
+    /// running (only what changed is written); 0 saves on quit only
+    pub autosave_interval_secs: u64,
+    /// What a paste does when a name is taken: "ask", "rename", "overwrite", "skip" or "error"
+    pub collision_strategy: String,
+    /// Background jobs (hashing, collecting results) run at once; the rest wait their turn
+    pub max_concurrent_jobs: usize,
+    /// Run background jobs and searches at lowered CPU and disk priority
+    pub low_priority_jobs: bool,
+    /// Keep each file's folders (relative to the search root) when collecting search results
+    pub collect_preserve_structure: bool,
+    /// Recent directories listed in the sidebar (`S`); 0 hides the section
+    pub sidebar_recent_dirs: usize,
+    /// Sidebar width in columns
+    pub sidebar_width: u16,
+    /// How directories are shown in the status bar, tabs and list title: "absolute",
+    /// "home" (`~/src`) or "root" (relative to `path_display_root`)
+    pub path_display: String,
+    /// Directory paths are shown relative to with `path_display` "root"
+    pub path_display_root: String,
+    /// Titles of tabs the user hasn't renamed: "path" (as `path_display`), "absolute",
+    /// "home", "root", "name", "last:N" (last N folders) or a template using
+    /// `{name}`, `{parent}` and `{path}`
+    pub tab_title: String,
+    /// Mark tabs whose folder changed on disk since it was listed with `●`
+    pub tab_show_changes: bool,
+    /// Switch to a tab opened on the selected folder, rather than staying put
+    pub focus_new_tab: bool,
+    /// Milliseconds between keystrokes before the type-to-jump prefix (`f`) starts over
+    pub jump_timeout_ms: u64,
+    /// Remember the entry selected in recently visited folders between sessions,
+    /// not just while running
+    pub persist_selections: bool,
+    /// File icons: "auto" (Nerd Font glyphs when the theme sets `use_nerd_fonts`, else emoji),
+    /// "nerd", "emoji" or "ascii". Terminals that can't show them get ASCII.
+    pub icon_set: String,
+    /// Sort mode for matching directories, from a path or glob (`~/Downloads`,
+    /// `**/Photos`) to name, name-desc, modified, modified-desc, size or size-desc.
+    /// `s` still changes it for the session.
+    pub sort_rules: HashMap<String, String>,
+    /// Multi-key bindings in the file list, from keys ("g g": `g` then `g`) to a
+    /// command palette id such as "first" or "home"
+    pub chords: HashMap<String, String>,
+    /// File this config was loaded from and is saved back to; the default location when unset
+    #[serde(skip)]
+    pub source_path: Option<PathBuf>,
 }
 
+/// Settings editable from the in-app settings screen: (key, label)
+pub const EDITABLE_SETTINGS: &[(&str, &str)] = &[
+    ("show_hidden", "Show hidden files"),
+    ("auto_refresh", "Refresh on disk changes"),
+    ("enable_git_integration", "Git integration"),
+    ("respect_gitignore", "Respect .gitignore"),
+    ("follow_symlinks", "Follow symlinks in recursive operations"),
+    ("safe_delete", "Delete to trash with undo"),
+    ("undo_window_secs", "Undo window (seconds)"),
+    ("trash_max_size_mb", "Trash size limit (MB, 0 = none)"),
+    ("trash_max_age_days", "Trash retention (days, 0 = forever)"),
+    ("confirm_rename_overwrite", "Ask before a rename overwrites"),
+    ("default_directory", "Default directory"),
+    ("preview_width_ratio", "Preview width ratio"),
+    ("preview_max_lines", "Preview lines"),
+    ("quick_look_max_lines", "Quick look lines"),
+    ("max_search_results", "Max search results"),
+    ("search_max_depth", "Recursive search depth"),
+    ("search_root", "Whole-drive search root"),
+    ("drive_search_max_depth", "Whole-drive search depth"),
+    ("drive_search_time_limit_secs", "Whole-drive search time limit (s, 0 = none)"),
+    ("search_grouping", "Search grouping (none/kind/parent)"),
+    ("ignore_patterns", "Ignore patterns (glob; glob; ...)"),
+    ("always_show", "Always shown hidden files (glob; glob; ...)"),
+    ("always_hide", "Always hidden (glob; glob; ...)"),
+    ("hash_copy_algorithm", "Hash copied with Y (md5/sha256/blake3)"),
+    ("status_show_clock", "Status bar clock"),
+    ("status_clock_format", "Clock format (strftime)"),
+    ("date_format", "Date format (strftime or relative)"),
+    ("date_column", "Modified time column in the file list"),
+    ("status_show_disk_space", "Status bar free disk space"),
+    ("show_dir_counts", "Item counts next to folders"),
+    ("git_status_column", "Git status column in the file list"),
+    ("size_bar_column", "Size bars in the file list"),
+    ("age_shading", "Shade names by age"),
+    ("dir_trailing_slash", "Trailing / on folder names"),
+    ("hidden_marker", "Hidden entries (dot/dim/name)"),
+    ("line_numbers", "Row numbers (off/absolute/relative)"),
+    ("highlight_theme", "Code preview theme"),
+    ("syntax_overrides", "Preview syntaxes (name=syntax; ..., \\; in a name)"),
+    ("preview_overrides", "Preview kinds (ext=text/code/hex/metadata/archive; ...)"),
+    ("file_associations", "File associations (ext=command; ..., \\; in a command)"),
+    ("sort_rules", "Folder sort modes (path or glob=mode; ..., \\; in a path)"),
+    ("poll_interval_ms", "UI update interval (ms)"),
+    ("max_edit_size", "Built-in editor size limit (bytes)"),
+    ("autosave_interval_secs", "Autosave interval (seconds, 0 = on quit only)"),
+    ("collision_strategy", "Name collisions (ask/rename/overwrite/skip/error)"),
+    ("collect_preserve_structure", "Keep folders when collecting search results"),
+    ("max_concurrent_jobs", "Background jobs at once"),
+    ("low_priority_jobs", "Run background work at low priority"),
+    ("sidebar_recent_dirs", "Sidebar recent directories"),
+    ("sidebar_width", "Sidebar width (columns)"),
+    ("path_display", "Show paths (absolute/home/root)"),
+    ("path_display_root", "Root for relative paths"),
+    ("tab_title", "Tab titles (path/name/last:N/{name}...)"),
+    ("tab_show_changes", "Mark tabs whose folder changed"),
+    ("focus_new_tab", "Switch to folders opened in a new tab"),
+    ("jump_timeout_ms", "Type-to-jump reset (ms)"),
+    ("persist_selections", "Remember selections between sessions"),
+    ("icon_set", "File icons (auto/nerd/emoji/ascii)"),
+];
+
 impl AppConfig {
     pub fn new() -> Self {
         Self::default()
@@ -33,15 +236,35 @@ impl AppConfig {
         }
     }
 
+    /// Load an alternate config file; it is created on the first save if missing.
+    /// Out-of-range values are corrected as `validate` does.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let mut config = if path.exists() {
+            let content = fs::read_to_string(path)?;
+            serde_json::from_str(&content)
+                .map_err(|e| anyhow::anyhow!("Invalid config file {}: {}", path.display(), e))?
+        } else {
+            Self::default()
+        };
+        config.source_path = Some(path.to_path_buf());
+        for warning in config.validate() {
+            tracing::warn!("Config corrected: {}", warning);
+        }
+        Ok(config)
+    }
+
     pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_path()?;
+        let config_path = match &self.source_path {
+            Some(path) => path.clone(),
+            None => Self::config_path()?,
+        };
         
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(config_path, content)?;
+        crate::files::write_atomic(&config_path, content.as_bytes())?;
         Ok(())
     }
 
@@ -54,19 +277,501 @@ impl AppConfig {
         PathBuf::from(&self.plugin_directory)
     }
 
-    pub fn validate(&mut self) {
+    /// Find the configured opener command for a file, with `%f` expanded
+    pub fn resolve_opener(&self, path: &Path) -> Option<String> {
+        let ext = extension_key(path.extension()?.to_str()?);
+        let command = self
+            .file_associations
+            .get(&ext)
+            .map(|command| command.trim())
+            .filter(|command| !command.is_empty())?;
+
+        let quoted = shell_quote(&path.to_string_lossy());
+        if command.contains("%f") {
+            Some(command.replace("%f", &quoted))
+        } else {
+            Some(format!("{} {}", command, quoted))
+        }
+    }
+
+    /// Whether a setting is a boolean that the settings screen toggles
+    pub fn is_toggle_setting(key: &str) -> bool {
+        matches!(
+            key,
+            "show_hidden"
+                | "auto_refresh"
+                | "enable_git_integration"
+                | "respect_gitignore"
+                | "follow_symlinks"
+                | "safe_delete"
+                | "confirm_rename_overwrite"
+                | "status_show_clock"
+                | "status_show_disk_space"
+                | "show_dir_counts"
+                | "git_status_column"
+                | "size_bar_column"
+                | "age_shading"
+                | "date_column"
+                | "dir_trailing_slash"
+                | "tab_show_changes"
+                | "focus_new_tab"
+                | "low_priority_jobs"
+                | "persist_selections"
+                | "collect_preserve_structure"
+        )
+    }
+
+    /// Current value of an editable setting as text
+    pub fn setting_value(&self, key: &str) -> Option<String> {
+        let value = match key {
+            "show_hidden" => self.show_hidden.to_string(),
+            "auto_refresh" => self.auto_refresh.to_string(),
+            "enable_git_integration" => self.enable_git_integration.to_string(),
+            "respect_gitignore" => self.respect_gitignore.to_string(),
+            "follow_symlinks" => self.follow_symlinks.to_string(),
+            "safe_delete" => self.safe_delete.to_string(),
+            "undo_window_secs" => self.undo_window_secs.to_string(),
+            "trash_max_size_mb" => self.trash_max_size_mb.to_string(),
+            "trash_max_age_days" => self.trash_max_age_days.to_string(),
+            "confirm_rename_overwrite" => self.confirm_rename_overwrite.to_string(),
+            "default_directory" => self.default_directory.clone(),
+            "preview_width_ratio" => self.preview_width_ratio.to_string(),
+            "preview_max_lines" | "max_preview_lines" => self.preview_max_lines.to_string(),
+            "quick_look_max_lines" => self.quick_look_max_lines.to_string(),
+            "max_search_results" => self.max_search_results.to_string(),
+            "search_max_depth" => self.search_max_depth.to_string(),
+            "search_root" => self.search_root.clone(),
+            "drive_search_max_depth" => self.drive_search_max_depth.to_string(),
+            "drive_search_time_limit_secs" => self.drive_search_time_limit_secs.to_string(),
+            "search_grouping" => self.search_grouping.clone(),
+            "ignore_patterns" => self.ignore_patterns.join("; "),
+            "always_show" => self.always_show.join("; "),
+            "always_hide" => self.always_hide.join("; "),
+            "hash_copy_algorithm" => self.hash_copy_algorithm.clone(),
+            "status_show_clock" => self.status_show_clock.to_string(),
+            "status_clock_format" => self.status_clock_format.clone(),
+            "date_format" => self.date_format.clone(),
+            "date_column" => self.date_column.to_string(),
+            "status_show_disk_space" => self.status_show_disk_space.to_string(),
+            "show_dir_counts" => self.show_dir_counts.to_string(),
+            "git_status_column" => self.git_status_column.to_string(),
+            "size_bar_column" => self.size_bar_column.to_string(),
+            "age_shading" => self.age_shading.to_string(),
+            "dir_trailing_slash" => self.dir_trailing_slash.to_string(),
+            "hidden_marker" => self.hidden_marker.clone(),
+            "line_numbers" => self.line_numbers.clone(),
+            "highlight_theme" => self.highlight_theme.clone(),
+            "poll_interval_ms" => self.poll_interval_ms.to_string(),
+            "max_edit_size" => self.max_edit_size.to_string(),
+            "autosave_interval_secs" => self.autosave_interval_secs.to_string(),
+            "collision_strategy" => self.collision_strategy.clone(),
+            "collect_preserve_structure" => self.collect_preserve_structure.to_string(),
+            "max_concurrent_jobs" => self.max_concurrent_jobs.to_string(),
+            "low_priority_jobs" => self.low_priority_jobs.to_string(),
+            "sidebar_recent_dirs" => self.sidebar_recent_dirs.to_string(),
+            "sidebar_width" => self.sidebar_width.to_string(),
+            "path_display" => self.path_display.clone(),
+            "path_display_root" => self.path_display_root.clone(),
+            "tab_title" => self.tab_title.clone(),
+            "tab_show_changes" => self.tab_show_changes.to_string(),
+            "focus_new_tab" => self.focus_new_tab.to_string(),
+            "jump_timeout_ms" => self.jump_timeout_ms.to_string(),
+            "persist_selections" => self.persist_selections.to_string(),
+            "icon_set" => self.icon_set.clone(),
+            "syntax_overrides" => join_escaped(&self.syntax_overrides),
+            "preview_overrides" => join_escaped(&self.preview_overrides),
+            "file_associations" => join_escaped(&self.file_associations),
+            "sort_rules" => join_escaped(&self.sort_rules),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Update an editable setting from text
+    pub fn set_setting(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        match key {
+            "show_hidden" => self.show_hidden = value.parse()?,
+            "auto_refresh" => self.auto_refresh = value.parse()?,
+            "enable_git_integration" => self.enable_git_integration = value.parse()?,
+            "respect_gitignore" => self.respect_gitignore = value.parse()?,
+            "follow_symlinks" => self.follow_symlinks = value.parse()?,
+            "safe_delete" => self.safe_delete = value.parse()?,
+            "undo_window_secs" => self.undo_window_secs = value.parse()?,
+            "trash_max_size_mb" => self.trash_max_size_mb = value.parse()?,
+            "trash_max_age_days" => self.trash_max_age_days = value.parse()?,
+            "confirm_rename_overwrite" => self.confirm_rename_overwrite = value.parse()?,
+            "default_directory" => {
+                if !Path::new(&absolute_path(value)).is_dir() {
+                    return Err(anyhow::anyhow!("No such directory '{}'", value));
+                }
+                self.default_directory = value.to_string();
+            }
+            "preview_width_ratio" => self.preview_width_ratio = value.parse()?,
+            "preview_max_lines" | "max_preview_lines" => self.preview_max_lines = value.parse()?,
+            "quick_look_max_lines" => self.quick_look_max_lines = value.parse()?,
+            "max_search_results" => self.max_search_results = value.parse()?,
+            "search_max_depth" => self.search_max_depth = parse_depth(value)?,
+            "search_root" => self.search_root = value.to_string(),
+            "drive_search_max_depth" => self.drive_search_max_depth = parse_depth(value)?,
+            "drive_search_time_limit_secs" => self.drive_search_time_limit_secs = value.parse()?,
+            "search_grouping" => {
+                let grouping = crate::search::SearchGrouping::parse(value)
+                    .ok_or_else(|| anyhow::anyhow!("Expected none, kind or parent, got '{}'", value))?;
+                self.search_grouping = grouping.as_str().to_string();
+            }
+            "ignore_patterns" | "always_show" | "always_hide" => {
+                let patterns: Vec<String> = value
+                    .split(';')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect();
+                // Reject the whole list if any glob is malformed
+                crate::files::IgnorePatterns::new(&patterns)?;
+                match key {
+                    "always_show" => self.always_show = patterns,
+                    "always_hide" => self.always_hide = patterns,
+                    _ => self.ignore_patterns = patterns,
+                }
+            }
+            "hash_copy_algorithm" => {
+                let algo = crate::files::HashAlgo::parse(value)
+                    .ok_or_else(|| anyhow::anyhow!("Expected md5, sha256 or blake3, got '{}'", value))?;
+                self.hash_copy_algorithm = algo.as_str().to_string();
+            }
+            "status_show_clock" => self.status_show_clock = value.parse()?,
+            "status_clock_format" => {
+                if !crate::path_display::is_valid_time_format(value) {
+                    return Err(anyhow::anyhow!("Invalid time format '{}'", value));
+                }
+                self.status_clock_format = value.to_string();
+            }
+            "date_format" => {
+                if !crate::path_display::is_valid_date_format(value) {
+                    return Err(anyhow::anyhow!("Invalid date format '{}'", value));
+                }
+                self.date_format = value.trim().to_string();
+            }
+            "date_column" => self.date_column = value.parse()?,
+            "status_show_disk_space" => self.status_show_disk_space = value.parse()?,
+            "show_dir_counts" => self.show_dir_counts = value.parse()?,
+            "git_status_column" => self.git_status_column = value.parse()?,
+            "size_bar_column" => self.size_bar_column = value.parse()?,
+            "age_shading" => self.age_shading = value.parse()?,
+            "dir_trailing_slash" => self.dir_trailing_slash = value.parse()?,
+            "hidden_marker" => {
+                let marker = crate::path_display::HiddenMarker::parse(value)
+                    .ok_or_else(|| anyhow::anyhow!("Expected dot, dim or name, got '{}'", value))?;
+                self.hidden_marker = marker.as_str().to_string();
+            }
+            "line_numbers" => {
+                let numbers = crate::path_display::LineNumbers::parse(value)
+                    .ok_or_else(|| anyhow::anyhow!("Expected off, absolute or relative, got '{}'", value))?;
+                self.line_numbers = numbers.as_str().to_string();
+            }
+            "collect_preserve_structure" => self.collect_preserve_structure = value.parse()?,
+            "max_concurrent_jobs" => self.max_concurrent_jobs = value.parse()?,
+            "low_priority_jobs" => self.low_priority_jobs = value.parse()?,
+            "poll_interval_ms" => self.poll_interval_ms = value.parse()?,
+            "max_edit_size" => self.max_edit_size = value.parse()?,
+            "autosave_interval_secs" => self.autosave_interval_secs = value.parse()?,
+            "collision_strategy" => {
+                let strategy = crate::fileops::CollisionStrategy::parse(value)
+                    .ok_or_else(|| anyhow::anyhow!("Expected ask, rename, overwrite, skip or error"))?;
+                self.collision_strategy = strategy.as_str().to_string();
+            }
+            "sidebar_recent_dirs" => self.sidebar_recent_dirs = value.parse()?,
+            "sidebar_width" => self.sidebar_width = value.parse()?,
+            "path_display" => {
+                if crate::path_display::PathStyle::parse(value).is_none() {
+                    return Err(anyhow::anyhow!("Expected absolute, home or root, got '{}'", value));
+                }
+                self.path_display = value.trim().to_lowercase();
+            }
+            "path_display_root" => self.path_display_root = value.trim().to_string(),
+            "tab_title" => {
+                if crate::path_display::TabTitle::parse(value).is_none() {
+                    return Err(anyhow::anyhow!("Expected path, absolute, home, root, name, last:N or a {{name}} template, got '{}'", value));
+                }
+                self.tab_title = value.trim().to_string();
+            }
+            "tab_show_changes" => self.tab_show_changes = value.parse()?,
+            "focus_new_tab" => self.focus_new_tab = value.parse()?,
+            "jump_timeout_ms" => self.jump_timeout_ms = value.parse()?,
+            "persist_selections" => self.persist_selections = value.parse()?,
+            "icon_set" => {
+                let value = value.trim().to_lowercase();
+                if !["auto", "nerd", "emoji", "ascii"].contains(&value.as_str()) {
+                    return Err(anyhow::anyhow!("Expected auto, nerd, emoji or ascii, got '{}'", value));
+                }
+                self.icon_set = value;
+            }
+            // Whether the theme exists depends on the app's theme folder; App checks it
+            "highlight_theme" => {
+                if value.is_empty() {
+                    return Err(anyhow::anyhow!("Expected a theme name"));
+                }
+                self.highlight_theme = value.to_string();
+            }
+            "syntax_overrides" => {
+                let mut overrides = HashMap::new();
+                for pair in split_unescaped(value).iter().filter(|p| !p.trim().is_empty()) {
+                    let (name, syntax) = pair
+                        .split_once('=')
+                        .ok_or_else(|| anyhow::anyhow!("Expected name=syntax, got '{}'", pair.trim()))?;
+                    let syntax = syntax.trim();
+                    if !crate::preview::is_known_syntax(syntax) {
+                        return Err(anyhow::anyhow!("Unknown syntax '{}'", syntax));
+                    }
+                    overrides.insert(name.trim().to_string(), syntax.to_string());
+                }
+                self.syntax_overrides = overrides;
+            }
+            "preview_overrides" => {
+                let mut overrides = HashMap::new();
+                for pair in split_unescaped(value).iter().filter(|p| !p.trim().is_empty()) {
+                    let (ext, mode) = pair
+                        .split_once('=')
+                        .ok_or_else(|| anyhow::anyhow!("Expected ext=mode, got '{}'", pair.trim()))?;
+                    let mode = mode.trim().to_lowercase();
+                    if crate::preview::preview_type_for_mode(&mode).is_none() {
+                        return Err(anyhow::anyhow!(
+                            "Unknown preview '{}', expected one of: {}",
+                            mode,
+                            crate::preview::PREVIEW_MODES.join(", ")
+                        ));
+                    }
+                    overrides.insert(ext.trim().to_string(), mode);
+                }
+                self.preview_overrides = overrides;
+            }
+            "file_associations" => {
+                let mut associations = HashMap::new();
+                for pair in split_unescaped(value).iter().filter(|p| !p.trim().is_empty()) {
+                    let (ext, command) = pair
+                        .split_once('=')
+                        .ok_or_else(|| anyhow::anyhow!("Expected ext=command, got '{}'", pair.trim()))?;
+                    associations.insert(extension_key(ext), command.trim().to_string());
+                }
+                self.file_associations = associations;
+            }
+            "sort_rules" => {
+                let mut rules = HashMap::new();
+                for pair in split_unescaped(value).iter().filter(|p| !p.trim().is_empty()) {
+                    let (pattern, mode) = pair
+                        .split_once('=')
+                        .ok_or_else(|| anyhow::anyhow!("Expected path=mode, got '{}'", pair.trim()))?;
+                    let mode = crate::files::SortMode::parse(mode).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unknown sort mode '{}' (name, name-desc, modified, modified-desc, size or size-desc)",
+                            mode.trim()
+                        )
+                    })?;
+                    rules.insert(pattern.trim().to_string(), mode.as_str().to_string());
+                }
+                crate::files::SortRules::new(&rules)?;
+                self.sort_rules = rules;
+            }
+            _ => return Err(anyhow::anyhow!("Unknown setting '{}'", key)),
+        }
+        self.validate();
+        Ok(())
+    }
+
+    /// Replace invalid values with defaults and clamp out-of-range ones, returning
+    /// a warning for each setting that was changed
+    pub fn validate(&mut self) -> Vec<String> {
+        let before = serde_json::to_value(&*self).ok();
+        self.correct();
+        let (Some(serde_json::Value::Object(before)), Ok(serde_json::Value::Object(after))) =
+            (before, serde_json::to_value(&*self))
+        else {
+            return Vec::new();
+        };
+        after
+            .iter()
+            .filter(|(key, value)| before.get(*key) != Some(*value))
+            .map(|(key, value)| {
+                let old = before.get(key).map(|v| v.to_string()).unwrap_or_default();
+                format!("{}: {} replaced with {}", key, old, value)
+            })
+            .collect()
+    }
+
+    /// Fall back to the default theme when `theme` isn't one of `known`
+    pub fn check_theme(&mut self, known: &[String]) -> Option<String> {
+        if known.contains(&self.theme) {
+            return None;
+        }
+        let warning = format!("theme: unknown theme '{}', using 'default'", self.theme);
+        self.theme = "default".to_string();
+        Some(warning)
+    }
+
+    fn correct(&mut self) {
         if self.preview_width_ratio < 0.1 || self.preview_width_ratio > 0.9 {
             self.preview_width_ratio = 0.7;
         }
         if self.search_history_size == 0 {
             self.search_history_size = 50;
         }
+        self.search_history_size = self.search_history_size.min(MAX_SEARCH_HISTORY_SIZE);
         if self.max_search_results == 0 {
             self.max_search_results = 100;
         }
+        self.max_search_results = self.max_search_results.min(MAX_SEARCH_RESULTS_LIMIT);
+
+        // Relative paths would depend on where astrofs happens to be started
+        self.default_directory = absolute_path(&self.default_directory);
+        self.plugin_directory = absolute_path(&self.plugin_directory);
+        self.search_root = absolute_path(&self.search_root);
+        if !Path::new(&self.default_directory).is_dir() {
+            self.default_directory = AppConfig::default().default_directory;
+        }
+        if self.preview_max_lines == 0 {
+            self.preview_max_lines = crate::preview::PREVIEW_MAX_LINES;
+        }
+        if self.quick_look_max_lines == 0 {
+            self.quick_look_max_lines = crate::preview::QUICK_LOOK_MAX_LINES;
+        }
+        if self.recent_files_count == 0 {
+            self.recent_files_count = 50;
+        }
+        if self.undo_window_secs == 0 {
+            self.undo_window_secs = 5;
+        }
+        if !(10..=1000).contains(&self.poll_interval_ms) {
+            self.poll_interval_ms = 100;
+        }
+        if self.max_edit_size == 0 {
+            self.max_edit_size = 256 * 1024;
+        }
+        if self.max_concurrent_jobs == 0 {
+            self.max_concurrent_jobs = 2;
+        }
+        if crate::fileops::CollisionStrategy::parse(&self.collision_strategy).is_none() {
+            self.collision_strategy = "ask".to_string();
+        }
+        if !(100..=10_000).contains(&self.jump_timeout_ms) {
+            self.jump_timeout_ms = 1000;
+        }
+        if !(12..=80).contains(&self.sidebar_width) {
+            self.sidebar_width = 24;
+        }
+        if crate::path_display::HiddenMarker::parse(&self.hidden_marker).is_none() {
+            self.hidden_marker = "dot".to_string();
+        }
+        if crate::path_display::LineNumbers::parse(&self.line_numbers).is_none() {
+            self.line_numbers = "off".to_string();
+        }
+        if crate::path_display::PathStyle::parse(&self.path_display).is_none() {
+            self.path_display = "absolute".to_string();
+        }
+        if crate::path_display::TabTitle::parse(&self.tab_title).is_none() {
+            self.tab_title = "path".to_string();
+        }
+        if !["auto", "nerd", "emoji", "ascii"].contains(&self.icon_set.as_str()) {
+            self.icon_set = "auto".to_string();
+        }
+        if crate::logging::parse_level(&self.log_level).is_none() {
+            self.log_level = "info".to_string();
+        }
+        if crate::search::SearchScope::parse(&self.search_scope).is_none() {
+            self.search_scope = "recursive".to_string();
+        }
+        if crate::search::SearchGrouping::parse(&self.search_grouping).is_none() {
+            self.search_grouping = "none".to_string();
+        }
+        if self.search_max_depth == 0 {
+            self.search_max_depth = 10;
+        }
+        if self.drive_search_max_depth == 0 {
+            self.drive_search_max_depth = 15;
+        }
+        if self.search_root.trim().is_empty() {
+            self.search_root = default_search_root();
+        }
+        if crate::files::HashAlgo::parse(&self.hash_copy_algorithm).is_none() {
+            self.hash_copy_algorithm = "sha256".to_string();
+        }
+        if !crate::path_display::is_valid_time_format(&self.status_clock_format) {
+            self.status_clock_format = "%H:%M".to_string();
+        }
+        if !crate::path_display::is_valid_date_format(&self.date_format) {
+            self.date_format = crate::path_display::DEFAULT_DATE_FORMAT.to_string();
+        }
+        if self.highlight_theme.trim().is_empty() {
+            self.highlight_theme = crate::preview::DEFAULT_HIGHLIGHT_THEME.to_string();
+        }
+
+        // Chords are stored as "g g", and bound to commands that exist
+        let palette = crate::palette::CommandPalette::new();
+        self.chords = self
+            .chords
+            .drain()
+            .filter(|(_, command)| palette.get(command).is_some())
+            .filter_map(|(chord, command)| {
+                let keys = crate::input::parse_chord(&chord)?;
+                Some((keys.iter().map(char::to_string).collect::<Vec<_>>().join(" "), command))
+            })
+            .collect();
+
+        // Store extensions as "md", not ".md" or "MD"
+        self.file_associations = self
+            .file_associations
+            .drain()
+            .filter(|(ext, command)| !ext.trim().is_empty() && !command.trim().is_empty())
+            .map(|(ext, command)| (extension_key(&ext), command))
+            .collect();
+        self.preview_overrides = self
+            .preview_overrides
+            .drain()
+            .filter(|(ext, mode)| !ext.trim().is_empty() && crate::preview::preview_type_for_mode(mode).is_some())
+            .map(|(ext, mode)| (ext.trim().trim_start_matches('.').to_lowercase(), mode.trim().to_lowercase()))
+            .collect();
+        self.sort_rules = self
+            .sort_rules
+            .drain()
+            .filter_map(|(pattern, mode)| {
+                let mode = crate::files::SortMode::parse(&mode)?;
+                let pattern = pattern.trim().to_string();
+                let valid = !pattern.is_empty() && globset::Glob::new(&pattern).is_ok();
+                valid.then(|| (pattern, mode.as_str().to_string()))
+            })
+            .collect();
     }
 }
 
+/// Most searches kept in the search history
+pub const MAX_SEARCH_HISTORY_SIZE: usize = 10_000;
+
+/// Most results a search collects
+pub const MAX_SEARCH_RESULTS_LIMIT: usize = 100_000;
+
+/// `path` made absolute against the current directory; empty stays empty
+fn absolute_path(path: &str) -> String {
+    let trimmed = path.trim();
+    if trimmed.is_empty() || Path::new(trimmed).is_absolute() {
+        return trimmed.to_string();
+    }
+    std::env::current_dir()
+        .map(|cwd| cwd.join(trimmed).to_string_lossy().into_owned())
+        .unwrap_or_else(|_| trimmed.to_string())
+}
+
+/// A search depth: how many levels down, at least 1 (direct children only)
+fn parse_depth(value: &str) -> Result<usize> {
+    match value.trim().parse::<usize>() {
+        Ok(depth) if depth >= 1 => Ok(depth),
+        _ => Err(anyhow::anyhow!("Expected a depth of at least 1, got '{}'", value)),
+    }
+}
+
+/// Filesystem root searched by the whole-drive scope
+fn default_search_root() -> String {
+    if cfg!(windows) { "C:\\".to_string() } else { "/".to_string() }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         let home_dir = dirs::home_dir()
@@ -88,8 +793,117 @@ impl Default for AppConfig {
             enable_git_integration: true,
             enable_plugins: true,
             plugin_directory: plugin_dir,
+            auto_refresh: true,
+            file_associations: HashMap::new(),
+            thumbnail_protocol: "auto".to_string(),
+            respect_gitignore: true,
+            preview_max_lines: crate::preview::PREVIEW_MAX_LINES,
+            quick_look_max_lines: crate::preview::QUICK_LOOK_MAX_LINES,
+            recent_files_count: 50,
+            safe_delete: false,
+            undo_window_secs: 5,
+            trash_max_size_mb: 0,
+            trash_max_age_days: 0,
+            confirm_rename_overwrite: true,
+            log_level: "info".to_string(),
+            search_scope: "recursive".to_string(),
+            search_max_depth: 10,
+            search_root: default_search_root(),
+            drive_search_max_depth: 15,
+            drive_search_time_limit_secs: 30,
+            search_grouping: "none".to_string(),
+            ignore_patterns: Vec::new(),
+            always_show: Vec::new(),
+            always_hide: Vec::new(),
+            hash_copy_algorithm: "sha256".to_string(),
+            status_show_clock: false,
+            status_clock_format: "%H:%M".to_string(),
+            date_format: crate::path_display::DEFAULT_DATE_FORMAT.to_string(),
+            date_column: false,
+            status_show_disk_space: false,
+            show_dir_counts: false,
+            git_status_column: false,
+            size_bar_column: false,
+            age_shading: false,
+            dir_trailing_slash: false,
+            hidden_marker: "dot".to_string(),
+            line_numbers: "off".to_string(),
+            syntax_overrides: HashMap::from([
+                ("Dockerfile".to_string(), "Bourne Again Shell (bash)".to_string()),
+                (".env".to_string(), "Bourne Again Shell (bash)".to_string()),
+            ]),
+            preview_overrides: HashMap::new(),
+            highlight_theme: crate::preview::DEFAULT_HIGHLIGHT_THEME.to_string(),
+            follow_symlinks: false,
+            poll_interval_ms: 100,
+            max_edit_size: 256 * 1024,
+            autosave_interval_secs: 60,
+            collision_strategy: "ask".to_string(),
+            collect_preserve_structure: false,
+            max_concurrent_jobs: 2,
+            low_priority_jobs: false,
+            sidebar_recent_dirs: 5,
+            sidebar_width: 24,
+            path_display: "absolute".to_string(),
+            path_display_root: String::new(),
+            tab_title: "path".to_string(),
+            tab_show_changes: false,
+            focus_new_tab: true,
+            jump_timeout_ms: 1000,
+            persist_selections: false,
+            icon_set: "auto".to_string(),
+            sort_rules: HashMap::new(),
+            chords: HashMap::from([
+                ("g g".to_string(), "first".to_string()),
+                ("g e".to_string(), "last".to_string()),
+                ("g h".to_string(), "home".to_string()),
+                ("g r".to_string(), "root".to_string()),
+            ]),
+            source_path: None,
+        }
+    }
+}
+
+/// An extension as association keys are stored: "md", not ".md" or "MD"
+fn extension_key(ext: &str) -> String {
+    ext.trim().trim_start_matches('.').to_lowercase()
+}
+
+/// `map` as sorted `key=value` pairs for the settings screen, with `;` written
+/// as `\;` so `split_unescaped` reads it back
+fn join_escaped(map: &HashMap<String, String>) -> String {
+    let escape = |text: &str| text.replace(';', "\\;");
+    let mut pairs: Vec<String> = map.iter().map(|(key, value)| format!("{}={}", escape(key), escape(value))).collect();
+    pairs.sort();
+    pairs.join("; ")
+}
+
+/// Split `value` at each `;` not written as `\;`, turning `\;` back into `;`
+fn split_unescaped(value: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&';') => {
+                chars.next();
+                part.push(';');
+            }
+            ';' => parts.push(std::mem::take(&mut part)),
+            c => part.push(c),
         }
     }
+    parts.push(part);
+    parts
+}
+
+/// Quote a path for `sh -c` (or `cmd /C` on Windows)
+fn shell_quote(path: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", path)
+    } else {
+        format!("'{}'", path.replace('\'', "'\\''"))
+    }
 }
 
 #[cfg(test)]
@@ -115,4 +929,136 @@ mod tests {
         assert!(config.preview_width_ratio <= 0.9);
         assert!(config.search_history_size > 0);
     }
+
+    #[test]
+    fn test_validate_clamps_and_falls_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = AppConfig {
+            max_search_results: 5_000_000,
+            default_directory: dir.path().join("gone").to_string_lossy().to_string(),
+            date_format: "%Y-%Q".to_string(),
+            ..Default::default()
+        };
+
+        let warnings = config.validate();
+        assert_eq!(config.max_search_results, MAX_SEARCH_RESULTS_LIMIT);
+        assert_eq!(config.default_directory, AppConfig::default().default_directory);
+        assert_eq!(config.date_format, crate::path_display::DEFAULT_DATE_FORMAT);
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings.iter().any(|w| w.starts_with("max_search_results: 5000000")));
+
+        // A valid config comes through untouched
+        assert!(config.validate().is_empty());
+
+        config.theme = "neon".to_string();
+        assert!(config.check_theme(&["default".to_string()]).is_some());
+        assert_eq!(config.theme, "default");
+    }
+
+    #[test]
+    fn test_status_bar_settings() -> Result<()> {
+        let mut config = AppConfig::default();
+        config.set_setting("status_show_clock", "true")?;
+        config.set_setting("status_clock_format", "%a %H:%M")?;
+        assert!(config.status_show_clock);
+        assert_eq!(config.setting_value("status_clock_format").as_deref(), Some("%a %H:%M"));
+
+        assert!(config.set_setting("status_clock_format", "%Q").is_err());
+        config.status_clock_format = String::new();
+        config.validate();
+        assert_eq!(config.status_clock_format, "%H:%M");
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_depths_must_be_positive() -> Result<()> {
+        let mut config = AppConfig::default();
+        config.set_setting("search_max_depth", "3")?;
+        assert!(config.set_setting("drive_search_max_depth", "0").is_err());
+        assert_eq!((config.search_max_depth, config.drive_search_max_depth), (3, 15));
+
+        let config: AppConfig = serde_json::from_str(r#"{"search_depth_current": 2, "search_depth_drive": 5}"#)?;
+        assert_eq!((config.search_max_depth, config.drive_search_max_depth), (2, 5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_preview_lines_names_the_preview_limit() -> Result<()> {
+        let mut config: AppConfig = serde_json::from_str(r#"{"max_preview_lines": 80}"#)?;
+        assert_eq!(config.preview_max_lines, 80);
+        config.set_setting("max_preview_lines", "120")?;
+        assert_eq!(config.setting_value("preview_max_lines").as_deref(), Some("120"));
+
+        config.set_setting("max_preview_lines", "0")?;
+        config.correct();
+        assert_eq!(config.preview_max_lines, crate::preview::PREVIEW_MAX_LINES);
+        Ok(())
+    }
+
+    #[test]
+    fn test_alternate_config_file_round_trip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("alt.json");
+
+        let mut config = AppConfig::load_from(&path)?;
+        assert_eq!(config.source_path.as_deref(), Some(path.as_path()));
+        config.show_hidden = true;
+        config.save()?;
+
+        let reloaded = AppConfig::load_from(&path)?;
+        assert!(reloaded.show_hidden);
+        assert!(!fs::read_to_string(&path)?.contains("source_path"));
+
+        fs::write(&path, r#"{"max_search_results": 5000000}"#)?;
+        assert_eq!(AppConfig::load_from(&path)?.max_search_results, MAX_SEARCH_RESULTS_LIMIT);
+
+        fs::write(&path, "{ not json")?;
+        assert!(AppConfig::load_from(&path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_opener_uses_association() {
+        let mut config = AppConfig::default();
+        config.set_setting("file_associations", ".MD=glow %f; csv=visidata").unwrap();
+
+        let opener = config.resolve_opener(Path::new("/notes/todo.md")).unwrap();
+        assert_eq!(opener, format!("glow {}", shell_quote("/notes/todo.md")));
+
+        let opener = config.resolve_opener(Path::new("data.CSV")).unwrap();
+        assert_eq!(opener, format!("visidata {}", shell_quote("data.CSV")));
+
+        assert!(config.resolve_opener(Path::new("image.png")).is_none());
+    }
+
+    #[test]
+    fn test_file_associations_round_trip_semicolons() -> Result<()> {
+        let mut config = AppConfig::default();
+        config.set_setting("file_associations", "md=glow %f\\; read; .MD=less %f")?;
+        // Keys are stored normalised, so ".MD" replaced "md" rather than sitting beside it
+        assert_eq!(config.file_associations, HashMap::from([("md".to_string(), "less %f".to_string())]));
+
+        config.set_setting("file_associations", "md=glow %f\\; read; csv=visidata")?;
+        assert_eq!(config.file_associations["md"], "glow %f; read");
+        let shown = config.setting_value("file_associations").unwrap();
+        assert_eq!(shown, "csv=visidata; md=glow %f\\; read");
+
+        let mut reloaded = AppConfig::default();
+        reloaded.set_setting("file_associations", &shown)?;
+        assert_eq!(reloaded.file_associations, config.file_associations);
+
+        // The other map settings escape the same way
+        config.set_setting("sort_rules", "/tmp/a\\;b=size; /srv=name")?;
+        assert_eq!(config.sort_rules["/tmp/a;b"], "size");
+        assert_eq!(config.setting_value("sort_rules").unwrap(), "/srv=name; /tmp/a\\;b=size");
+        config.set_setting("syntax_overrides", "odd\\;name=rs")?;
+        config.set_setting("preview_overrides", "log=text")?;
+        for key in ["sort_rules", "syntax_overrides", "preview_overrides"] {
+            let shown = config.setting_value(key).unwrap();
+            reloaded.set_setting(key, &shown)?;
+            assert_eq!(reloaded.setting_value(key), Some(shown), "{}", key);
+        }
+        assert_eq!(reloaded.syntax_overrides, config.syntax_overrides);
+        Ok(())
+    }
 }
@@ -0,0 +1,177 @@
+// Disk usage - recursive sizes of a directory's children, largest first (like ncdu)
+use crate::files::FileEntry;
+use anyhow::Result;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Files walked between checks for cancellation
+const CANCEL_CHECK_INTERVAL: usize = 1024;
+
+/// A child of the scanned directory and the bytes in everything below it
+#[derive(Clone, Debug)]
+pub struct UsageEntry {
+    pub entry: FileEntry,
+    pub size: u64,
+}
+
+/// What a scan found: the children, largest first, and the size of every
+/// directory walked on the way
+#[derive(Clone, Debug, Default)]
+pub struct UsageScan {
+    pub entries: Vec<UsageEntry>,
+    pub dir_sizes: HashMap<PathBuf, u64>,
+}
+
+impl UsageScan {
+    pub fn total(&self) -> u64 {
+        self.entries.iter().map(|e| e.size).sum()
+    }
+}
+
+/// Directory sizes from earlier scans, so drilling into a folder (or coming
+/// back up to one) doesn't walk it again
+#[derive(Debug, Default)]
+pub struct UsageCache {
+    sizes: HashMap<PathBuf, u64>,
+}
+
+impl UsageCache {
+    pub fn extend(&mut self, sizes: HashMap<PathBuf, u64>) {
+        self.sizes.extend(sizes);
+    }
+
+    /// Known sizes of the directories directly inside `dir`
+    pub fn children_of(&self, dir: &Path) -> HashMap<PathBuf, u64> {
+        self.sizes
+            .iter()
+            .filter(|(path, _)| path.parent() == Some(dir))
+            .map(|(path, size)| (path.clone(), *size))
+            .collect()
+    }
+
+    /// Forget `dir`, everything below it and the folders whose totals include it
+    pub fn invalidate(&mut self, dir: &Path) {
+        self.sizes.retain(|path, _| !path.starts_with(dir) && !dir.starts_with(path));
+    }
+}
+
+/// Size the children of `dir`, walking subdirectories in parallel. Directories
+/// in `known` aren't walked again. `progress` hears (children done, children);
+/// once `keep_going` returns false the scan stops and gives None.
+pub fn scan(
+    dir: &Path,
+    follow_symlinks: bool,
+    known: &HashMap<PathBuf, u64>,
+    progress: impl Fn(u64, u64) + Sync,
+    keep_going: impl Fn() -> bool + Sync,
+) -> Result<Option<UsageScan>> {
+    let children: Vec<PathBuf> = fs::read_dir(dir)?.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    let total = children.len() as u64;
+    let done = AtomicU64::new(0);
+
+    let sized: Vec<Option<(UsageEntry, HashMap<PathBuf, u64>)>> = children
+        .par_iter()
+        .filter_map(|path| {
+            // Broken links and entries removed since listing are left out
+            let entry = FileEntry::from_path(path).ok()?;
+            let is_link = fs::symlink_metadata(path).map(|m| m.file_type().is_symlink()).unwrap_or(false);
+            let sized = if is_link && !follow_symlinks {
+                Some((0, HashMap::new()))
+            } else if !entry.is_dir {
+                Some((entry.size, HashMap::new()))
+            } else if let Some(size) = known.get(path) {
+                Some((*size, HashMap::new()))
+            } else {
+                walk(path, follow_symlinks, &keep_going).map(|sizes| (sizes[path], sizes))
+            };
+            progress(done.fetch_add(1, Ordering::Relaxed) + 1, total);
+            Some(sized.map(|(size, sizes)| (UsageEntry { entry, size }, sizes)))
+        })
+        .collect();
+
+    let mut scan = UsageScan::default();
+    for child in sized {
+        let Some((entry, sizes)) = child else {
+            return Ok(None);
+        };
+        scan.dir_sizes.extend(sizes);
+        scan.entries.push(entry);
+    }
+    scan.entries
+        .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.entry.name.to_lowercase().cmp(&b.entry.name.to_lowercase())));
+    scan.dir_sizes.insert(dir.to_path_buf(), scan.total());
+    Ok(Some(scan))
+}
+
+/// Sum the files below `root` into it and each directory on the way; None if stopped
+fn walk(root: &Path, follow_symlinks: bool, keep_going: &(impl Fn() -> bool + Sync)) -> Option<HashMap<PathBuf, u64>> {
+    let mut sizes = HashMap::from([(root.to_path_buf(), 0)]);
+    let entries = walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .follow_links(follow_symlinks)
+        .into_iter()
+        .filter_map(|e| e.ok());
+    for (seen, entry) in entries.enumerate() {
+        if seen % CANCEL_CHECK_INTERVAL == 0 && !keep_going() {
+            return None;
+        }
+        if entry.file_type().is_dir() {
+            sizes.entry(entry.into_path()).or_insert(0);
+            continue;
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let len = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        for dir in entry.path().ancestors().skip(1) {
+            *sizes.entry(dir.to_path_buf()).or_insert(0) += len;
+            if dir == root {
+                break;
+            }
+        }
+    }
+    Some(sizes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_sizes_children_and_caches_subdirectories() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let big = dir.path().join("big");
+        fs::create_dir_all(big.join("inner"))?;
+        fs::write(big.join("a.bin"), vec![0u8; 300])?;
+        fs::write(big.join("inner").join("b.bin"), vec![0u8; 200])?;
+        fs::write(dir.path().join("small.txt"), vec![0u8; 100])?;
+        fs::create_dir(dir.path().join("empty"))?;
+
+        let scan = scan(dir.path(), false, &HashMap::new(), |_, _| {}, || true)?.expect("not cancelled");
+        let sizes: Vec<(&str, u64)> = scan.entries.iter().map(|e| (e.entry.name.as_str(), e.size)).collect();
+        assert_eq!(sizes, [("big", 500), ("small.txt", 100), ("empty", 0)]);
+        assert_eq!(scan.total(), 600);
+        assert_eq!(scan.dir_sizes[&big.join("inner")], 200);
+
+        let mut cache = UsageCache::default();
+        cache.extend(scan.dir_sizes);
+        assert_eq!(cache.children_of(&big), HashMap::from([(big.join("inner"), 200)]));
+        cache.invalidate(&big.join("inner"));
+        assert!(cache.children_of(&big).is_empty());
+        assert!(cache.children_of(dir.path()).contains_key(&dir.path().join("empty")));
+        assert!(!cache.children_of(dir.path()).contains_key(&big), "parent total includes the changed folder");
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_stops_when_cancelled() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join("sub"))?;
+        fs::write(dir.path().join("sub").join("file"), "x")?;
+        assert!(scan(dir.path(), false, &HashMap::new(), |_, _| {}, || false)?.is_none());
+        Ok(())
+    }
+}
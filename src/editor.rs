@@ -0,0 +1,235 @@
+// Editor - a minimal built-in editor for quick tweaks to small text files
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A text file loaded into an editable line buffer.
+///
+/// The cursor column counts characters, not bytes. Line endings (LF or CRLF)
+/// and a trailing newline are kept as they were when the file is saved.
+#[derive(Clone, Debug)]
+pub struct TextEditor {
+    pub path: PathBuf,
+    pub lines: Vec<String>,
+    pub row: usize,
+    pub col: usize,
+    /// First line shown on screen
+    pub scroll: usize,
+    modified: bool,
+    /// Set after the first attempt to leave with unsaved changes
+    discard_warned: bool,
+    line_ending: &'static str,
+    trailing_newline: bool,
+}
+
+impl TextEditor {
+    /// Load a file for editing, refusing anything over `max_size` bytes or not UTF-8
+    pub fn open(path: &Path, max_size: u64) -> Result<Self> {
+        let metadata = fs::metadata(path)?;
+        if !metadata.is_file() {
+            return Err(anyhow!("{} is not a file", path.display()));
+        }
+        if metadata.len() > max_size {
+            return Err(anyhow!(
+                "{} is over the {} edit limit",
+                path.display(),
+                humansize::format_size(max_size, humansize::BINARY)
+            ));
+        }
+        let content = String::from_utf8(fs::read(path)?)
+            .map_err(|_| anyhow!("{} is not a UTF-8 text file", path.display()))?;
+        Ok(Self::from_text(path.to_path_buf(), &content))
+    }
+
+    fn from_text(path: PathBuf, content: &str) -> Self {
+        let line_ending = if content.contains("\r\n") { "\r\n" } else { "\n" };
+        let trailing_newline = content.ends_with('\n');
+        let body = content.strip_suffix(line_ending).unwrap_or(content);
+        let lines = body.split(line_ending).map(str::to_string).collect();
+        Self {
+            path,
+            lines,
+            row: 0,
+            col: 0,
+            scroll: 0,
+            modified: false,
+            discard_warned: false,
+            line_ending,
+            trailing_newline,
+        }
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// The buffer as it would be written to disk
+    pub fn contents(&self) -> String {
+        let mut content = self.lines.join(self.line_ending);
+        if self.trailing_newline {
+            content.push_str(self.line_ending);
+        }
+        content
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        fs::write(&self.path, self.contents())?;
+        self.modified = false;
+        self.discard_warned = false;
+        Ok(())
+    }
+
+    /// Whether the editor may close now. With unsaved changes the first call
+    /// only arms the warning and returns false; asking again discards them.
+    pub fn request_close(&mut self) -> bool {
+        if !self.modified || self.discard_warned {
+            return true;
+        }
+        self.discard_warned = true;
+        false
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    /// Byte offset of the cursor in its line
+    fn byte_col(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices().nth(self.col).map(|(i, _)| i).unwrap_or(line.len())
+    }
+
+    fn edited(&mut self) {
+        self.modified = true;
+        self.discard_warned = false;
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let at = self.byte_col();
+        self.lines[self.row].insert(at, c);
+        self.col += 1;
+        self.edited();
+    }
+
+    pub fn insert_newline(&mut self) {
+        let at = self.byte_col();
+        let rest = self.lines[self.row].split_off(at);
+        self.lines.insert(self.row + 1, rest);
+        self.row += 1;
+        self.col = 0;
+        self.edited();
+    }
+
+    /// Delete the character before the cursor, joining with the previous line at column 0
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let at = self.byte_col();
+            self.lines[self.row].remove(at);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        } else {
+            return;
+        }
+        self.edited();
+    }
+
+    /// Delete the character under the cursor, joining with the next line at the end
+    pub fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let at = self.byte_col();
+            self.lines[self.row].remove(at);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        } else {
+            return;
+        }
+        self.edited();
+    }
+
+    pub fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.col < self.line_len(self.row) {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    /// Move up or down by `delta` lines, keeping the column where the line allows
+    pub fn move_lines(&mut self, delta: isize) {
+        self.row = self.row.saturating_add_signed(delta).min(self.lines.len() - 1);
+        self.col = self.col.min(self.line_len(self.row));
+    }
+
+    pub fn line_start(&mut self) {
+        self.col = 0;
+    }
+
+    pub fn line_end(&mut self) {
+        self.col = self.line_len(self.row);
+    }
+
+    /// Scroll so the cursor is inside a window `height` lines tall
+    pub fn scroll_to_cursor(&mut self, height: usize) {
+        let height = height.max(1);
+        if self.row < self.scroll {
+            self.scroll = self.row;
+        } else if self.row >= self.scroll + height {
+            self.scroll = self.row + 1 - height;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_edit_and_save() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "héllo\r\nworld\r\n")?;
+
+        let mut editor = TextEditor::open(&path, 1024)?;
+        assert_eq!(editor.lines, vec!["héllo", "world"]);
+        editor.line_end();
+        editor.insert_char('!');
+        editor.move_right();
+        editor.backspace();
+        assert_eq!(editor.contents(), "héllo!world\r\n");
+        assert_eq!((editor.row, editor.col), (0, 6));
+
+        // Leaving with unsaved changes takes two tries, and editing re-arms the warning
+        assert!(!editor.request_close());
+        editor.delete();
+        assert!(!editor.request_close());
+        assert!(editor.request_close());
+
+        editor.move_lines(1);
+        editor.insert_newline();
+        editor.save()?;
+        assert!(!editor.is_modified());
+        assert!(editor.request_close());
+        assert_eq!(fs::read_to_string(&path)?, "héllo!\r\norld\r\n");
+
+        assert!(TextEditor::open(&path, 4).is_err());
+        fs::write(&path, [0xff, 0xfe])?;
+        assert!(TextEditor::open(&path, 1024).is_err());
+        Ok(())
+    }
+}
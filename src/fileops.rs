@@ -1,26 +1,812 @@
 use anyhow::{anyhow, Result};
+use std::collections::HashSet;
 use std::fs;
-use std::path::{Path, PathBuf};
-use fs_extra::dir::CopyOptions;
+use std::io;
+use std::path::{Component, Path, PathBuf};
 
 pub struct FileOperation;
 
+/// A mode or ownership change applied by `FileOperation::set_permissions_recursive`
+#[derive(Clone, Debug, PartialEq)]
+pub enum PermissionChange {
+    /// Replace the permission bits, e.g. `0o644`
+    Mode(u32),
+    /// Set and clear bits, as in `+x` or `go-w`
+    Adjust { add: u32, remove: u32 },
+    /// New owner and/or group; `None` leaves that one alone
+    Owner { uid: Option<u32>, gid: Option<u32> },
+}
+
+impl PermissionChange {
+    /// Parse a chmod-style mode: octal (`644`, `0755`) or `[ugoa]*[+-][rwx]+` (`+x`, `go-w`)
+    pub fn parse_mode(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if !spec.is_empty() && spec.chars().all(|c| c.is_digit(8)) {
+            let mode = u32::from_str_radix(spec, 8)?;
+            if mode > 0o7777 {
+                return Err(anyhow!("Mode {} is out of range", spec));
+            }
+            return Ok(Self::Mode(mode));
+        }
+
+        let op_at = spec
+            .find(['+', '-'])
+            .ok_or_else(|| anyhow!("Expected an octal mode like 644 or a change like +x, got '{}'", spec))?;
+        let (who, rest) = spec.split_at(op_at);
+        let (op, perms) = rest.split_at(1);
+
+        let mut who_mask = 0;
+        for c in who.chars() {
+            who_mask |= match c {
+                'u' => 0o700,
+                'g' => 0o070,
+                'o' => 0o007,
+                'a' => 0o777,
+                _ => return Err(anyhow!("Unknown class '{}' in '{}'", c, spec)),
+            };
+        }
+        if who_mask == 0 {
+            who_mask = 0o777;
+        }
+
+        let mut bits = 0;
+        for c in perms.chars() {
+            bits |= match c {
+                'r' => 0o444,
+                'w' => 0o222,
+                'x' => 0o111,
+                _ => return Err(anyhow!("Unknown permission '{}' in '{}'", c, spec)),
+            };
+        }
+        if bits == 0 {
+            return Err(anyhow!("No permissions given in '{}'", spec));
+        }
+
+        let bits = bits & who_mask;
+        Ok(if op == "+" {
+            Self::Adjust { add: bits, remove: 0 }
+        } else {
+            Self::Adjust { add: 0, remove: bits }
+        })
+    }
+
+    /// Parse an owner spec: `user`, `user:group` or `:group`, by name or numeric id
+    pub fn parse_owner(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let (user, group) = match spec.split_once(':') {
+            Some((user, group)) => (user, Some(group)),
+            None => (spec, None),
+        };
+        let uid = match user {
+            "" => None,
+            user => Some(user.parse().ok().or_else(|| lookup_user(user)).ok_or_else(|| anyhow!("Unknown user '{}'", user))?),
+        };
+        let gid = match group {
+            None | Some("") => None,
+            Some(group) => Some(group.parse().ok().or_else(|| lookup_group(group)).ok_or_else(|| anyhow!("Unknown group '{}'", group))?),
+        };
+        if uid.is_none() && gid.is_none() {
+            return Err(anyhow!("Expected user, user:group or :group"));
+        }
+        Ok(Self::Owner { uid, gid })
+    }
+
+    /// Mode bits after applying this change to `mode`
+    pub fn apply_to_mode(&self, mode: u32) -> u32 {
+        match self {
+            Self::Mode(new) => (mode & !0o7777) | new,
+            Self::Adjust { add, remove } => (mode | add) & !remove,
+            Self::Owner { .. } => mode,
+        }
+    }
+
+    /// Short description for confirmations, e.g. "mode 644" or "owner 1000:1000"
+    pub fn describe(&self) -> String {
+        let id = |id: &Option<u32>| id.map(|id| id.to_string()).unwrap_or_default();
+        match self {
+            Self::Mode(mode) => format!("mode {:o}", mode),
+            Self::Adjust { add, remove } if *remove == 0 => format!("add {:03o}", add),
+            Self::Adjust { add, remove } if *add == 0 => format!("remove {:03o}", remove),
+            Self::Adjust { add, remove } => format!("add {:03o}, remove {:03o}", add, remove),
+            Self::Owner { uid, gid: None } => format!("owner {}", id(uid)),
+            Self::Owner { uid, gid } => format!("owner {}:{}", id(uid), id(gid)),
+        }
+    }
+}
+
+/// Outcome of a batch permission change; failures don't stop the batch
+#[derive(Debug, Default)]
+pub struct PermissionReport {
+    pub changed: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Outcome of copying a set of files into one directory; failures don't stop the batch
+#[derive(Debug, Default)]
+pub struct CollectReport {
+    pub copied: Vec<PathBuf>,
+    /// Folders, and files whose name was taken under the `Skip` strategy
+    pub skipped: usize,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Files to move up into a folder when flattening it, worked out beforehand
+/// so they can be shown for confirmation
+#[derive(Clone, Debug, Default)]
+pub struct FlattenPlan {
+    pub dir: PathBuf,
+    /// Where each nested file goes, shallowest first
+    pub moves: Vec<(PathBuf, PathBuf)>,
+    /// Files left where they are because their name was taken (`Skip`)
+    pub skipped: Vec<PathBuf>,
+    /// Sources of the moves that replace a file already there (`Overwrite`);
+    /// every other move fails rather than overwrite a file created since
+    pub replacing: HashSet<PathBuf>,
+    /// Subfolders holding the files that move, deepest first, to remove once
+    /// the moves leave them empty
+    pub emptied: Vec<PathBuf>,
+}
+
+/// Outcome of flattening a folder; failures don't stop the rest
+#[derive(Debug, Default)]
+pub struct FlattenReport {
+    pub moved: Vec<PathBuf>,
+    pub skipped: usize,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// What to do when a copy or move would land on a name that is already taken
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionStrategy {
+    /// Stop with an error
+    Error,
+    /// Replace what is there
+    Overwrite,
+    /// Pick a free name: "a.txt" becomes "a.2.txt", "a.3.txt", ...
+    Rename,
+    /// Leave both alone and move on
+    Skip,
+    /// Report the conflict so the caller can ask the user
+    Ask,
+}
+
+impl CollisionStrategy {
+    pub const ALL: [CollisionStrategy; 5] = [
+        CollisionStrategy::Error,
+        CollisionStrategy::Overwrite,
+        CollisionStrategy::Rename,
+        CollisionStrategy::Skip,
+        CollisionStrategy::Ask,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CollisionStrategy::Error => "error",
+            CollisionStrategy::Overwrite => "overwrite",
+            CollisionStrategy::Rename => "rename",
+            CollisionStrategy::Skip => "skip",
+            CollisionStrategy::Ask => "ask",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.as_str() == name.trim().to_lowercase())
+    }
+}
+
+/// Whether `error` is a refusal to replace an existing file
+pub fn is_already_exists(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::AlreadyExists)
+}
+
+/// What a sequence rename numbers files by
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceOrder {
+    /// When the photo was taken, falling back to the modified time
+    CaptureDate,
+    Modified,
+}
+
+impl SequenceOrder {
+    #[allow(dead_code)]
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "date" | "capture" => Some(SequenceOrder::CaptureDate),
+            "mtime" | "modified" => Some(SequenceOrder::Modified),
+            _ => None,
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            SequenceOrder::CaptureDate => SequenceOrder::Modified,
+            SequenceOrder::Modified => SequenceOrder::CaptureDate,
+        }
+    }
+}
+
+/// Whether a copy or a move
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferKind {
+    Copy,
+    Move,
+}
+
+/// Result of `FileOperation::transfer` for one source
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransferOutcome {
+    /// Copied or moved to this path
+    Done(PathBuf),
+    /// The name was taken and the strategy said to skip
+    Skipped,
+    /// The name was taken and the strategy was `Ask`; nothing was touched
+    Conflict(PathBuf),
+}
+
+/// Delete a directory's contents and then the directory, without following
+/// symlinks or crossing into another filesystem
+fn remove_tree(path: &Path, device: Option<u64>) -> Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = fs::symlink_metadata(entry.path())?;
+        if !metadata.is_dir() {
+            remove_link_or_file(&entry.path())?;
+            continue;
+        }
+        if device.is_some() && device_id(&metadata) != device {
+            return Err(anyhow!("{:?} is on another filesystem", entry.path()));
+        }
+        remove_tree(&entry.path(), device)?;
+    }
+    fs::remove_dir(path)?;
+    Ok(())
+}
+
+fn remove_link_or_file(path: &Path) -> std::io::Result<()> {
+    match fs::remove_file(path) {
+        // Windows directory symlinks and junctions are removed as directories
+        Err(e) if cfg!(windows) && path.is_symlink() => fs::remove_dir(path).map_err(|_| e),
+        result => result,
+    }
+}
+
+#[cfg(unix)]
+fn device_id(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device_id(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Whether two paths name the same file, such as names differing only in
+/// case on a case-insensitive filesystem
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::symlink_metadata(a), fs::symlink_metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn same_file(a: &Path, b: &Path) -> bool {
+    matches!((fs::canonicalize(a), fs::canonicalize(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+/// System-critical folders, and everything in them, that file operations refuse
+/// to touch. Matched by whole components, lowercased where the filesystem
+/// ignores case, and on Windows on any drive.
+#[cfg(target_os = "linux")]
+const PROTECTED_ROOTS: &[&str] = &["/sys", "/proc", "/dev", "/boot"];
+#[cfg(target_os = "macos")]
+const PROTECTED_ROOTS: &[&str] = &["/system", "/library/system", "/library/caches"];
+#[cfg(windows)]
+const PROTECTED_ROOTS: &[&str] = &["\\windows", "\\program files\\system"];
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+const PROTECTED_ROOTS: &[&str] = &[];
+
+/// `path` with its parent made absolute and symlink-free, keeping the last component as is
+fn resolve_parent(path: &Path) -> Option<PathBuf> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        Some(_) => Path::new("."),
+        None => return fs::canonicalize(path).ok(),
+    };
+    let parent = fs::canonicalize(parent).ok()?;
+    match path.file_name() {
+        Some(name) => Some(parent.join(name)),
+        // `..` and `.` only make sense resolved
+        None => fs::canonicalize(path).ok(),
+    }
+}
+
+/// `path` with its deepest existing ancestor resolved, so paths that do not
+/// exist yet can still be compared with ones that do
+fn resolve_missing(path: &Path) -> PathBuf {
+    for ancestor in path.ancestors().skip(1) {
+        if let (Ok(real), Ok(rest)) = (fs::canonicalize(ancestor), path.strip_prefix(ancestor)) {
+            return real.join(rest);
+        }
+    }
+    path.to_path_buf()
+}
+
+/// A typed path as a real one: `~` is the home folder, relative paths are
+/// taken from `base`, and `.` and `..` are folded away as a shell would
+pub fn expand_input_path(input: &str, base: &Path) -> PathBuf {
+    let expanded = match input.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(std::path::is_separator) => {
+            dirs::home_dir().unwrap_or_default().join(rest.trim_start_matches(std::path::is_separator))
+        }
+        _ => PathBuf::from(input),
+    };
+    let mut path = PathBuf::new();
+    for component in base.join(expanded).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                path.pop();
+            }
+            component => path.push(component),
+        }
+    }
+    path
+}
+
+/// Complete the last part of a typed path from what is on disk. A single
+/// match is filled in (with a trailing separator for folders); several are
+/// narrowed to the prefix they share. None when nothing more can be added.
+pub fn complete_path(input: &str, base: &Path) -> Option<String> {
+    if input == "~" {
+        return Some(format!("~{}", std::path::MAIN_SEPARATOR));
+    }
+    let split = input.rfind(std::path::is_separator).map_or(0, |i| i + 1);
+    let (dir, prefix) = input.split_at(split);
+    let mut matches: Vec<(String, bool)> = fs::read_dir(expand_input_path(dir, base))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| (entry.file_name().to_string_lossy().to_string(), entry.path().is_dir()))
+        // Hidden entries only once a dot has been typed
+        .filter(|(name, _)| name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.')))
+        .collect();
+    matches.sort();
+
+    let completed = match matches.as_slice() {
+        [] => return None,
+        [(name, true)] => format!("{}{}", name, std::path::MAIN_SEPARATOR),
+        [(name, false)] => name.clone(),
+        [(first, _), rest @ ..] => rest.iter().fold(first.clone(), |shared, (name, _)| {
+            shared.chars().zip(name.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a).collect()
+        }),
+    };
+    (completed != prefix).then(|| format!("{}{}", dir, completed))
+}
+
+/// The first free "stem.N.ext" next to `path`, counting from 2
+pub fn numbered_path(path: &Path) -> PathBuf {
+    first_numbered(path, |candidate| fs::symlink_metadata(candidate).is_err())
+}
+
+/// The first "stem.N.ext" next to `path`, counting from 2, that `is_free` accepts
+fn first_numbered(path: &Path, is_free: impl Fn(&Path) -> bool) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), format!(".{}", ext)),
+        _ => (name.clone(), String::new()),
+    };
+    (2..)
+        .map(|n| path.with_file_name(format!("{}.{}{}", stem, n, ext)))
+        .find(|candidate| is_free(candidate))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// The contents of a text file no bigger than `max_size` bytes; binary files
+/// (a NUL byte, or not UTF-8) are refused
+pub fn read_text_file(path: &Path, max_size: u64) -> Result<String> {
+    let metadata = fs::metadata(path)?;
+    if !metadata.is_file() {
+        return Err(anyhow!("{} is not a file", path.display()));
+    }
+    if metadata.len() > max_size {
+        return Err(anyhow!(
+            "{} is over the {} limit",
+            path.display(),
+            humansize::format_size(max_size, humansize::BINARY)
+        ));
+    }
+    let bytes = fs::read(path)?;
+    if bytes.contains(&0) {
+        return Err(anyhow!("{} is a binary file", path.display()));
+    }
+    String::from_utf8(bytes).map_err(|_| anyhow!("{} is a binary file", path.display()))
+}
+
+/// The first free "stem copy.ext", then "stem copy 2.ext", ... next to `path`.
+/// Directories keep their whole name: "photos.old copy".
+pub fn duplicate_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !path.is_dir() => (stem.to_string(), format!(".{}", ext)),
+        _ => (name.clone(), String::new()),
+    };
+    std::iter::once(format!("{} copy{}", stem, ext))
+        .chain((2..).map(|n| format!("{} copy {}{}", stem, n, ext)))
+        .map(|name| path.with_file_name(name))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Absolute, symlink-free form of a path that may not exist yet
+fn resolve_path(path: &Path) -> Option<PathBuf> {
+    if let Ok(real) = fs::canonicalize(path) {
+        return Some(real);
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Some(fs::canonicalize(parent).ok()?.join(path.file_name()?))
+}
+
+/// Recreate everything below `src` at `dest`
+fn copy_dir(src: &Path, dest: &Path, follow_symlinks: bool) -> Result<()> {
+    for entry in walkdir::WalkDir::new(src).follow_links(follow_symlinks) {
+        let (path, file_type) = match entry {
+            Ok(entry) => (entry.path().to_path_buf(), entry.file_type()),
+            // A followed link that loops or dangles is copied as the link itself
+            Err(e) => match e.path().filter(|p| p.is_symlink()) {
+                Some(path) => {
+                    let target = dest.join(path.strip_prefix(src)?);
+                    copy_symlink(path, &target)?;
+                    continue;
+                }
+                None => return Err(e.into()),
+            },
+        };
+
+        let target = dest.join(path.strip_prefix(src)?);
+        if file_type.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&path, &target)?;
+        } else {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dest: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dest)
+        .map_err(|e| anyhow!("Failed to copy symlink {:?}: {}", src, e))
+}
+
+// Creating symlinks needs extra privileges on Windows, so copy the target instead
+#[cfg(not(unix))]
+fn copy_symlink(src: &Path, dest: &Path) -> Result<()> {
+    fs::copy(src, dest).map_err(|e| anyhow!("Failed to copy file: {}", e))?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn lookup_user(name: &str) -> Option<u32> {
+    let name = std::ffi::CString::new(name).ok()?;
+    // SAFETY: getpwnam takes a NUL-terminated name; the uid is copied out before
+    // anything else can reuse the static record it returns
+    let entry = unsafe { libc::getpwnam(name.as_ptr()) };
+    (!entry.is_null()).then(|| unsafe { (*entry).pw_uid })
+}
+
+#[cfg(unix)]
+fn lookup_group(name: &str) -> Option<u32> {
+    let name = std::ffi::CString::new(name).ok()?;
+    // SAFETY: as for getpwnam above
+    let entry = unsafe { libc::getgrnam(name.as_ptr()) };
+    (!entry.is_null()).then(|| unsafe { (*entry).gr_gid })
+}
+
+#[cfg(not(unix))]
+fn lookup_user(_name: &str) -> Option<u32> {
+    None
+}
+
+#[cfg(not(unix))]
+fn lookup_group(_name: &str) -> Option<u32> {
+    None
+}
+
 impl FileOperation {
-    /// Copy a file or directory to a new location
-    pub fn copy(src: &Path, dest: &Path) -> Result<()> {
-        if !src.exists() {
-            return Err(anyhow!("Source path does not exist: {:?}", src));
+    /// Copy a file or directory to a new location. Copying a directory onto an
+    /// existing one puts the copy inside it.
+    ///
+    /// Unless `follow_symlinks` is set, symlinks are copied as links rather than
+    /// as what they point to. Links that loop back to a parent are always copied
+    /// as links, so a cycle can't make the copy run forever.
+    pub fn copy(src: &Path, dest: &Path, follow_symlinks: bool) -> Result<()> {
+        let metadata = fs::symlink_metadata(src)
+            .map_err(|_| anyhow!("Source path does not exist: {:?}", src))?;
+        if metadata.file_type().is_symlink() && !follow_symlinks {
+            return copy_symlink(src, dest);
+        }
+
+        let dest = match src.file_name() {
+            Some(name) if src.is_dir() && dest.is_dir() => dest.join(name),
+            _ => dest.to_path_buf(),
+        };
+        // A directory copied into itself never finishes, and a file copied onto itself is emptied
+        let src_real = fs::canonicalize(src)?;
+        if resolve_path(&dest).is_some_and(|dest| dest.starts_with(&src_real)) {
+            return Err(anyhow!("Cannot copy {:?} into itself", src));
         }
 
         if src.is_dir() {
-            fs_extra::dir::copy(src, dest, &CopyOptions::new().copy_inside(true))
-                .map_err(|e| anyhow!("Failed to copy directory: {}", e))?;
+            copy_dir(src, &dest, follow_symlinks).map_err(|e| anyhow!("Failed to copy directory: {}", e))?;
         } else {
-            fs::copy(src, dest).map_err(|e| anyhow!("Failed to copy file: {}", e))?;
+            fs::copy(src, &dest).map_err(|e| anyhow!("Failed to copy file: {}", e))?;
         }
         Ok(())
     }
 
+    /// Copy or move `src` into `dest_dir`, keeping its name, with `strategy`
+    /// deciding what happens when that name is already taken
+    pub fn transfer(
+        src: &Path,
+        dest_dir: &Path,
+        kind: TransferKind,
+        strategy: CollisionStrategy,
+        follow_symlinks: bool,
+    ) -> Result<TransferOutcome> {
+        let name = src.file_name().ok_or_else(|| anyhow!("Cannot copy or move {:?}", src))?;
+        if !dest_dir.is_dir() {
+            return Err(anyhow!("Not a directory: {:?}", dest_dir));
+        }
+        let mut target = dest_dir.join(name);
+        if let Some(outcome) = Self::clear_target(src, &mut target, strategy)? {
+            return Ok(outcome);
+        }
+
+        Self::place(&target, strategy, |dest| match kind {
+            TransferKind::Copy => Self::copy(src, dest, follow_symlinks),
+            TransferKind::Move => {
+                if fs::rename(src, dest).is_err() {
+                    // Crossing filesystems: copy, then remove the original
+                    Self::copy(src, dest, false)?;
+                    Self::delete(src)?;
+                }
+                Ok(())
+            }
+        })?;
+        Ok(TransferOutcome::Done(target))
+    }
+
+    /// Say whether `src` may land on `target` when the name is already taken,
+    /// as `strategy` says. Some outcome means nothing should be moved or copied.
+    /// With `Overwrite` the existing target stays until `place` replaces it.
+    fn clear_target(src: &Path, target: &mut PathBuf, strategy: CollisionStrategy) -> Result<Option<TransferOutcome>> {
+        if fs::symlink_metadata(&*target).is_err() {
+            return Ok(None);
+        }
+        // Overwriting the source itself, or a folder holding it, would destroy it
+        let holds_src = resolve_parent(target).is_some_and(|t| resolve_parent(src).is_some_and(|s| s.starts_with(t)));
+        match strategy {
+            CollisionStrategy::Error => return Err(anyhow!("{:?} already exists", target)),
+            CollisionStrategy::Skip => return Ok(Some(TransferOutcome::Skipped)),
+            CollisionStrategy::Ask => return Ok(Some(TransferOutcome::Conflict(target.clone()))),
+            CollisionStrategy::Rename => *target = numbered_path(target),
+            CollisionStrategy::Overwrite if holds_src => {
+                return Err(anyhow!("Cannot overwrite {:?} with something inside it", target))
+            }
+            CollisionStrategy::Overwrite => {}
+        }
+        Ok(None)
+    }
+
+    /// Create `target` with `put`. When overwriting something already there,
+    /// `put` writes to a temporary name beside it and the old entry is only
+    /// swapped out once that succeeded, so a failed copy loses nothing.
+    fn place(target: &Path, strategy: CollisionStrategy, put: impl FnOnce(&Path) -> Result<()>) -> Result<()> {
+        if strategy != CollisionStrategy::Overwrite || fs::symlink_metadata(target).is_err() {
+            return put(target);
+        }
+        let name = target.file_name().ok_or_else(|| anyhow!("Cannot overwrite {:?}", target))?.to_string_lossy();
+        let stamp = std::process::id();
+        let staged = target.with_file_name(format!(".astrofs-new-{}-{}", stamp, name));
+        let old = target.with_file_name(format!(".astrofs-old-{}-{}", stamp, name));
+        if let Err(e) = put(&staged) {
+            if fs::symlink_metadata(&staged).is_ok() {
+                let _ = Self::delete(&staged);
+            }
+            return Err(e);
+        }
+        if let Err(e) = fs::rename(target, &old) {
+            let _ = Self::delete(&staged);
+            return Err(anyhow!("Failed to replace {:?}: {}", target, e));
+        }
+        if let Err(e) = fs::rename(&staged, target) {
+            // Put the old one back rather than leave neither under the name
+            let _ = fs::rename(&old, target);
+            let _ = Self::delete(&staged);
+            return Err(anyhow!("Failed to replace {:?}: {}", target, e));
+        }
+        Self::delete(&old)
+    }
+
+    /// Move `src` to `dest`, or into it keeping the name when `dest` is a folder,
+    /// creating missing parents. `strategy` decides what happens when the
+    /// target is already taken.
+    pub fn move_to(src: &Path, dest: &Path, strategy: CollisionStrategy) -> Result<TransferOutcome> {
+        let mut target = match src.file_name() {
+            Some(name) if dest.is_dir() => dest.join(name),
+            _ => dest.to_path_buf(),
+        };
+        let src_real = resolve_parent(src).ok_or_else(|| anyhow!("Source path does not exist: {:?}", src))?;
+        let target_real = resolve_missing(&target);
+        if target_real == src_real {
+            return Err(anyhow!("{:?} is already there", src));
+        }
+        // A folder moved into itself would take everything in it along
+        if src.is_dir() && target_real.starts_with(&src_real) {
+            return Err(anyhow!("Cannot move {:?} into itself", src));
+        }
+        if let Some(outcome) = Self::clear_target(src, &mut target, strategy)? {
+            return Ok(outcome);
+        }
+        Self::place(&target, strategy, |dest| {
+            if Self::move_path(src, dest).is_err() {
+                // Crossing filesystems: copy, then remove the original
+                Self::copy(src, dest, false)?;
+                Self::delete(src)?;
+            }
+            Ok(())
+        })?;
+        Ok(TransferOutcome::Done(target))
+    }
+
+    /// Copy `files` into `dest`, creating it if needed. With `preserve_structure`
+    /// each file keeps its path relative to `base`; otherwise they all land
+    /// directly in `dest`. Folders are skipped, and `Ask` renames since there is
+    /// no one to ask. `progress` gets (done, total) and returns false to stop.
+    pub fn collect(
+        files: &[PathBuf],
+        base: &Path,
+        dest: &Path,
+        preserve_structure: bool,
+        strategy: CollisionStrategy,
+        mut progress: impl FnMut(usize, usize) -> bool,
+    ) -> Result<CollectReport> {
+        fs::create_dir_all(dest)?;
+        let strategy = match strategy {
+            CollisionStrategy::Ask => CollisionStrategy::Rename,
+            strategy => strategy,
+        };
+        let mut report = CollectReport::default();
+        for (done, src) in files.iter().enumerate() {
+            if !progress(done, files.len()) {
+                break;
+            }
+            if fs::symlink_metadata(src).map(|m| m.is_dir()).unwrap_or(false) {
+                report.skipped += 1;
+                continue;
+            }
+            let dest_dir = match src.parent().and_then(|parent| parent.strip_prefix(base).ok()) {
+                Some(relative) if preserve_structure => dest.join(relative),
+                _ => dest.to_path_buf(),
+            };
+            let outcome = fs::create_dir_all(&dest_dir)
+                .map_err(anyhow::Error::from)
+                .and_then(|_| Self::transfer(src, &dest_dir, TransferKind::Copy, strategy, false));
+            match outcome {
+                Ok(TransferOutcome::Done(target)) => report.copied.push(target),
+                Ok(_) => report.skipped += 1,
+                Err(e) => report.failed.push((src.clone(), e.to_string())),
+            }
+        }
+        progress(files.len(), files.len());
+        Ok(report)
+    }
+
+    /// Plan moving every file nested in `dir`'s subfolders up into `dir` itself.
+    /// Symlinks move as they are and are never followed. Taken names follow
+    /// `strategy`, with `Ask` renaming since there is no one to ask; a name
+    /// matching one of the subfolders being emptied is always renamed.
+    pub fn plan_flatten(dir: &Path, strategy: CollisionStrategy) -> Result<FlattenPlan> {
+        if !fs::symlink_metadata(dir).map(|m| m.is_dir()).unwrap_or(false) {
+            return Err(anyhow!("Not a directory: {:?}", dir));
+        }
+        if Self::is_protected_path(dir) {
+            return Err(anyhow!("Refusing to flatten protected path {:?}", dir));
+        }
+
+        let mut taken: HashSet<PathBuf> = HashSet::new();
+        let mut folders: HashSet<PathBuf> = HashSet::new();
+        let mut pending: Vec<PathBuf> = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if fs::symlink_metadata(&path)?.is_dir() {
+                folders.insert(path.clone());
+                pending.push(path.clone());
+            }
+            taken.insert(path);
+        }
+
+        // Breadth first, so shallower files claim their names first
+        let mut nested = Vec::new();
+        let mut queue: std::collections::VecDeque<PathBuf> = pending.into();
+        while let Some(folder) = queue.pop_front() {
+            let mut children: Vec<PathBuf> = fs::read_dir(&folder)?.filter_map(|e| e.ok().map(|e| e.path())).collect();
+            children.sort();
+            for child in children {
+                if fs::symlink_metadata(&child)?.is_dir() {
+                    queue.push_back(child);
+                } else {
+                    nested.push(child);
+                }
+            }
+        }
+
+        let mut plan = FlattenPlan { dir: dir.to_path_buf(), ..FlattenPlan::default() };
+        for src in nested {
+            let mut dest = dir.join(src.file_name().unwrap_or_default());
+            if taken.contains(&dest) {
+                let strategy = if folders.contains(&dest) { CollisionStrategy::Rename } else { strategy };
+                match strategy {
+                    CollisionStrategy::Error => return Err(anyhow!("{:?} already exists", dest)),
+                    CollisionStrategy::Skip => {
+                        plan.skipped.push(src);
+                        continue;
+                    }
+                    CollisionStrategy::Overwrite => {
+                        plan.replacing.insert(src.clone());
+                    }
+                    CollisionStrategy::Rename | CollisionStrategy::Ask => {
+                        dest = first_numbered(&dest, |candidate| !taken.contains(candidate) && fs::symlink_metadata(candidate).is_err())
+                    }
+                }
+            }
+            taken.insert(dest.clone());
+            plan.moves.push((src, dest));
+        }
+        let emptied: HashSet<&Path> = plan
+            .moves
+            .iter()
+            .flat_map(|(src, _)| src.ancestors().skip(1).take_while(|folder| *folder != dir))
+            .collect();
+        plan.emptied = emptied.into_iter().map(Path::to_path_buf).collect();
+        plan.emptied.sort_by_key(|folder| std::cmp::Reverse(folder.components().count()));
+        Ok(plan)
+    }
+
+    /// Move every file nested in `dir` up into it, then remove the subfolders
+    /// left empty. See `plan_flatten` for how taken names are handled.
+    #[allow(dead_code)]
+    pub fn flatten(dir: &Path, strategy: CollisionStrategy) -> Result<FlattenReport> {
+        let plan = Self::plan_flatten(dir, strategy)?;
+        Ok(Self::apply_flatten(&plan))
+    }
+
+    /// Carry out a flatten plan
+    pub fn apply_flatten(plan: &FlattenPlan) -> FlattenReport {
+        let mut report = FlattenReport { skipped: plan.skipped.len(), ..FlattenReport::default() };
+        for (src, dest) in &plan.moves {
+            // Planned names never point at a folder, so this never moves into one
+            let strategy = if plan.replacing.contains(src) { CollisionStrategy::Overwrite } else { CollisionStrategy::Error };
+            match Self::move_to(src, dest, strategy) {
+                Ok(_) => report.moved.push(dest.clone()),
+                Err(e) => report.failed.push((src.clone(), e.to_string())),
+            }
+        }
+        for folder in &plan.emptied {
+            // Fails, as it should, for folders with something left in them
+            let _ = fs::remove_dir(folder);
+        }
+        report
+    }
+
     /// Move (rename or move to different directory) a file or directory
     pub fn move_path(src: &Path, dest: &Path) -> Result<()> {
         if !src.exists() {
@@ -39,33 +825,113 @@ impl FileOperation {
         Ok(())
     }
 
-    /// Delete a file or directory recursively
+    /// Delete a file or directory recursively.
+    ///
+    /// Symlinks are removed as links and never followed, the walk stops at other
+    /// filesystems mounted inside the tree, and system paths are refused outright.
     pub fn delete(path: &Path) -> Result<()> {
-        if !path.exists() {
-            return Err(anyhow!("Path does not exist: {:?}", path));
+        let metadata = fs::symlink_metadata(path)
+            .map_err(|_| anyhow!("Path does not exist: {:?}", path))?;
+        if Self::is_protected_path(path) {
+            return Err(anyhow!("Refusing to delete protected path {:?}", path));
         }
 
-        if path.is_dir() {
-            fs::remove_dir_all(path).map_err(|e| anyhow!("Failed to delete directory: {}", e))?;
+        if metadata.is_dir() {
+            remove_tree(path, device_id(&metadata)).map_err(|e| anyhow!("Failed to delete directory: {}", e))?;
         } else {
-            fs::remove_file(path).map_err(|e| anyhow!("Failed to delete file: {}", e))?;
+            remove_link_or_file(path).map_err(|e| anyhow!("Failed to delete file: {}", e))?;
         }
         Ok(())
     }
 
-    /// Rename a file or directory
-    pub fn rename(src: &Path, new_name: &str) -> Result<PathBuf> {
+    /// Rename a file or directory. A name that is already taken is an
+    /// `AlreadyExists` error (see `is_already_exists`) unless `overwrite` is set.
+    pub fn rename(src: &Path, new_name: &str, overwrite: bool) -> Result<PathBuf> {
         if !src.exists() {
             return Err(anyhow!("Source path does not exist: {:?}", src));
         }
 
         let parent = src.parent().ok_or_else(|| anyhow!("Cannot get parent directory"))?;
         let dest = parent.join(new_name);
+        // A change of case only on a case-insensitive filesystem finds src itself
+        if !overwrite && fs::symlink_metadata(&dest).is_ok() && !same_file(src, &dest) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", new_name)).into());
+        }
 
         fs::rename(src, &dest).map_err(|e| anyhow!("Failed to rename: {}", e))?;
         Ok(dest)
     }
 
+    /// Plan renaming the files in `paths` to `prefix_0001.ext`, `prefix_0002.ext`, ...
+    /// in `order`, keeping each in its folder and keeping its extension.
+    /// Directories are left out; names already taken by other files are an error.
+    pub fn plan_sequence_rename(paths: &[PathBuf], prefix: &str, order: SequenceOrder) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let prefix = prefix.trim();
+        if prefix.is_empty() || prefix.contains(['/', '\\']) {
+            return Err(anyhow!("Invalid prefix: {:?}", prefix));
+        }
+
+        let mut files: Vec<(std::time::SystemTime, String, &PathBuf)> = Vec::new();
+        for path in paths {
+            let metadata = fs::metadata(path).map_err(|e| anyhow!("Cannot read {}: {}", path.display(), e))?;
+            if !metadata.is_file() {
+                continue;
+            }
+            let modified = metadata.modified()?;
+            let taken = match order {
+                SequenceOrder::CaptureDate => crate::media_preview::capture_time(path)
+                    .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+                    .map(std::time::SystemTime::from),
+                SequenceOrder::Modified => None,
+            };
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+            files.push((taken.unwrap_or(modified), name, path));
+        }
+        files.sort();
+
+        let width = files.len().to_string().len().max(4);
+        let plan: Vec<(PathBuf, PathBuf)> = files
+            .iter()
+            .enumerate()
+            .map(|(i, (_, _, path))| {
+                let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+                (path.to_path_buf(), path.with_file_name(format!("{}_{:0width$}{}", prefix, i + 1, ext)))
+            })
+            .collect();
+
+        let sources: HashSet<&PathBuf> = plan.iter().map(|(src, _)| src).collect();
+        if let Some((_, taken)) = plan.iter().find(|(_, dest)| !sources.contains(dest) && fs::symlink_metadata(dest).is_ok()) {
+            return Err(anyhow!("{} already exists", taken.display()));
+        }
+        Ok(plan)
+    }
+
+    /// Carry out a rename plan. Files go through temporary names first, so
+    /// names can be traded around within the plan.
+    pub fn apply_renames(plan: &[(PathBuf, PathBuf)]) -> Result<()> {
+        let stamp = std::process::id();
+        let mut staged = Vec::new();
+        for (i, (src, dest)) in plan.iter().enumerate().filter(|(_, (src, dest))| src != dest) {
+            let temp = src.with_file_name(format!(".astrofs-rename-{}-{}", stamp, i));
+            if let Err(e) = fs::rename(src, &temp) {
+                for (src, temp, _) in staged {
+                    let _ = fs::rename(temp, src);
+                }
+                return Err(anyhow!("Failed to rename {}: {}", src.display(), e));
+            }
+            staged.push((src, temp, dest));
+        }
+        let mut failed = None;
+        for (src, temp, dest) in staged {
+            if let Err(e) = fs::rename(&temp, dest) {
+                // Put it back rather than leave it under the temporary name
+                let _ = fs::rename(&temp, src);
+                failed.get_or_insert_with(|| anyhow!("Failed to rename {}: {}", src.display(), e));
+            }
+        }
+        failed.map_or(Ok(()), Err)
+    }
+
     /// Create a new file
     pub fn create_file(path: &Path) -> Result<()> {
         if path.exists() {
@@ -86,16 +952,18 @@ impl FileOperation {
         Ok(())
     }
 
-    /// Get the size of a file or directory
-    pub fn get_size(path: &Path) -> Result<u64> {
+    /// Get the size of a file or directory. Symlinked files and directories only
+    /// count when `follow_symlinks` is set; symlink cycles are skipped.
+    pub fn get_size(path: &Path, follow_symlinks: bool) -> Result<u64> {
         if path.is_dir() {
             let mut total_size = 0u64;
             for entry in walkdir::WalkDir::new(path)
+                .follow_links(follow_symlinks)
                 .into_iter()
                 .filter_map(|e| e.ok())
             {
-                if entry.path().is_file() {
-                    total_size += fs::metadata(entry.path())?.len();
+                if entry.file_type().is_file() {
+                    total_size += entry.metadata()?.len();
                 }
             }
             Ok(total_size)
@@ -104,33 +972,88 @@ impl FileOperation {
         }
     }
 
-    /// Check if a path is safe to operate on (not outside allowed areas)
-    pub fn is_path_safe(path: &Path) -> bool {
-        // Prevent operations on system critical paths
-        let path_str = path.to_string_lossy().to_lowercase();
-        
-        #[cfg(target_os = "windows")]
-        {
-            let critical = ["windows", "system32", "drivers", "program files\\system"];
-            critical.iter().any(|c| path_str.contains(c))
+    /// Everything a permission change on `paths` would touch: the paths themselves
+    /// and, when `recursive`, everything below directories. A directory comes
+    /// after its contents, so taking away its search bit can't cut off what is
+    /// still to change inside it. Symlinks are skipped, since changing them
+    /// would change whatever they point at.
+    pub fn permission_targets(paths: &[PathBuf], recursive: bool) -> Vec<PathBuf> {
+        let mut targets = Vec::new();
+        for path in paths {
+            let max_depth = if recursive { usize::MAX } else { 0 };
+            targets.extend(
+                walkdir::WalkDir::new(path)
+                    .max_depth(max_depth)
+                    .contents_first(true)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| !e.path_is_symlink())
+                    .map(|e| e.into_path()),
+            );
         }
-        
-        #[cfg(target_os = "linux")]
-        {
-            let critical = ["/sys", "/proc", "/dev", "/boot"];
-            critical.iter().any(|c| path_str.starts_with(c))
+        targets
+    }
+
+    /// Apply a mode or ownership change to `paths` (and their contents when
+    /// `recursive`), carrying on past entries that fail
+    pub fn set_permissions_recursive(
+        paths: &[PathBuf],
+        change: &PermissionChange,
+        recursive: bool,
+    ) -> Result<PermissionReport> {
+        if !cfg!(unix) {
+            return Err(anyhow!("Changing permissions is only supported on Unix"));
         }
-        
-        #[cfg(target_os = "macos")]
-        {
-            let critical = ["/system", "/library/system", "/library/caches"];
-            critical.iter().any(|c| path_str.contains(c))
+        let mut report = PermissionReport::default();
+        for path in Self::permission_targets(paths, recursive) {
+            match Self::set_permissions(&path, change) {
+                Ok(()) => report.changed.push(path),
+                Err(e) => report.failed.push((path, e.to_string())),
+            }
         }
-        
-        #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
-        {
-            true
+        Ok(report)
+    }
+
+    /// Apply a mode or ownership change to a single path
+    #[cfg(unix)]
+    pub fn set_permissions(path: &Path, change: &PermissionChange) -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        match change {
+            PermissionChange::Owner { uid, gid } => std::os::unix::fs::chown(path, *uid, *gid)?,
+            _ => {
+                let mode = fs::metadata(path)?.permissions().mode();
+                fs::set_permissions(path, fs::Permissions::from_mode(change.apply_to_mode(mode)))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn set_permissions(_path: &Path, _change: &PermissionChange) -> Result<()> {
+        Err(anyhow!("Changing permissions is only supported on Unix"))
+    }
+
+    /// Check if a path is safe to operate on (not outside allowed areas)
+    pub fn is_path_safe(path: &Path) -> bool {
+        !Self::is_protected_path(path)
+    }
+
+    /// Whether a path is a filesystem root, the home directory itself or a
+    /// system-critical location. The last component is not resolved, so a
+    /// symlink elsewhere that points at `/boot` is not protected, only `/boot`.
+    pub fn is_protected_path(path: &Path) -> bool {
+        let path = resolve_parent(path).unwrap_or_else(|| path.to_path_buf());
+        if path.parent().is_none() || dirs::home_dir().is_some_and(|home| home == path) {
+            return true;
         }
+
+        #[cfg(any(target_os = "macos", windows))]
+        let path = PathBuf::from(path.to_string_lossy().to_lowercase());
+        // Drop the drive so `D:\Windows` is caught as well as `C:\Windows`
+        #[cfg(windows)]
+        let path: PathBuf = path.components().filter(|c| !matches!(c, Component::Prefix(_))).collect();
+        PROTECTED_ROOTS.iter().any(|root| path.starts_with(root))
     }
 }
 
@@ -150,6 +1073,62 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_sequence_rename_orders_by_modified_time() -> Result<()> {
+        let dir = tempdir()?;
+        let start = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        // Named against their age, and one already holding a name in the sequence
+        let files = ["c.jpg", "a.JPG", "img_0001.png", "b"];
+        let mut paths = Vec::new();
+        for (age, name) in files.iter().enumerate() {
+            let path = dir.path().join(name);
+            fs::File::create(&path)?.set_modified(start + std::time::Duration::from_secs(age as u64 * 60))?;
+            paths.push(path);
+        }
+        fs::create_dir(dir.path().join("folder"))?;
+        paths.push(dir.path().join("folder"));
+
+        let plan = FileOperation::plan_sequence_rename(&paths, "img", SequenceOrder::Modified)?;
+        let names: Vec<(&str, String)> = plan
+            .iter()
+            .map(|(src, dest)| (src.file_name().unwrap().to_str().unwrap(), dest.file_name().unwrap().to_string_lossy().into_owned()))
+            .collect();
+        assert_eq!(
+            names,
+            [("c.jpg", "img_0001.jpg".to_string()), ("a.JPG", "img_0002.JPG".into()), ("img_0001.png", "img_0003.png".into()), ("b", "img_0004".into())]
+        );
+
+        FileOperation::apply_renames(&plan)?;
+        let mut listed: Vec<String> = fs::read_dir(dir.path())?.map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect();
+        listed.sort();
+        assert_eq!(listed, ["folder", "img_0001.jpg", "img_0002.JPG", "img_0003.png", "img_0004"]);
+
+        // A name held by a file outside the batch stops the plan
+        fs::write(dir.path().join("new.jpg"), "")?;
+        let plan = FileOperation::plan_sequence_rename(&[dir.path().join("new.jpg")], "img", SequenceOrder::Modified);
+        assert!(plan.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_refuses_to_overwrite_unless_asked() -> Result<()> {
+        let dir = tempdir()?;
+        let draft = dir.path().join("draft.txt");
+        let report = dir.path().join("report.txt");
+        fs::write(&draft, "new")?;
+        fs::write(&report, "old")?;
+
+        let err = FileOperation::rename(&draft, "report.txt", false).unwrap_err();
+        assert!(is_already_exists(&err), "{}", err);
+        assert_eq!(fs::read_to_string(&report)?, "old");
+        assert!(draft.exists());
+
+        assert_eq!(FileOperation::rename(&draft, "report.txt", true)?, report);
+        assert_eq!(fs::read_to_string(&report)?, "new");
+        assert!(!draft.exists());
+        Ok(())
+    }
+
     #[test]
     fn test_create_directory() -> Result<()> {
         let dir = tempdir()?;
@@ -160,4 +1139,426 @@ mod tests {
         
         Ok(())
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_cycles_do_not_hang() -> Result<()> {
+        let dir = tempdir()?;
+        let tree = dir.path().join("tree");
+        fs::create_dir_all(tree.join("sub"))?;
+        fs::write(tree.join("sub").join("data.bin"), [0u8; 10])?;
+        std::os::unix::fs::symlink(&tree, tree.join("sub").join("loop"))?;
+        std::os::unix::fs::symlink(tree.join("sub").join("data.bin"), tree.join("alias"))?;
+
+        assert_eq!(FileOperation::get_size(&tree, false)?, 10);
+        assert_eq!(FileOperation::get_size(&tree, true)?, 20, "the alias counts, the loop doesn't");
+        assert_eq!(crate::files::dir_stats(&tree, true)?.files, 2);
+
+        for follow in [false, true] {
+            let copy = dir.path().join(format!("copy-{}", follow));
+            FileOperation::copy(&tree, &copy, follow)?;
+            assert!(fs::symlink_metadata(copy.join("sub").join("loop"))?.file_type().is_symlink());
+            assert_eq!(fs::symlink_metadata(copy.join("alias"))?.file_type().is_symlink(), !follow);
+            assert_eq!(fs::read(copy.join("alias"))?, vec![0u8; 10]);
+        }
+
+        // Searches only look through a linked directory when following
+        let outside = dir.path().join("outside");
+        fs::create_dir(&outside)?;
+        fs::write(outside.join("data-2.txt"), "")?;
+        std::os::unix::fs::symlink(&outside, tree.join("ext"))?;
+        let mut engine = crate::search::SearchEngine::new();
+        engine.search_dir(&tree, "data", 100, 50);
+        assert_eq!(engine.results.len(), 1);
+        engine.follow_symlinks = true;
+        engine.search_dir(&tree, "data", 100, 50);
+        assert_eq!(engine.results.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_never_overwrites_its_source() -> Result<()> {
+        let dir = tempdir()?;
+        let tree = dir.path().join("tree");
+        fs::create_dir(&tree)?;
+        fs::write(tree.join("a.txt"), "keep me")?;
+
+        assert!(FileOperation::copy(&tree, &tree, false).is_err());
+        assert!(FileOperation::copy(&tree, &tree.join("inner"), false).is_err());
+        assert!(FileOperation::copy(&tree.join("a.txt"), &tree.join("a.txt"), false).is_err());
+        assert!(FileOperation::copy(&tree.join("a.txt"), &tree.join(".").join("a.txt"), false).is_err());
+        assert_eq!(fs::read_to_string(tree.join("a.txt"))?, "keep me");
+
+        // Copying onto an existing directory nests the copy inside it
+        let into = dir.path().join("into");
+        fs::create_dir(&into)?;
+        FileOperation::copy(&tree, &into, false)?;
+        assert_eq!(fs::read_to_string(into.join("tree").join("a.txt"))?, "keep me");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_delete_leaves_symlink_targets_alone() -> Result<()> {
+        let dir = tempdir()?;
+        let outside = dir.path().join("outside");
+        fs::create_dir(&outside)?;
+        fs::write(outside.join("keep.txt"), "precious")?;
+        fs::write(dir.path().join("keep.txt"), "precious")?;
+
+        let tree = dir.path().join("tree");
+        fs::create_dir_all(tree.join("nested"))?;
+        fs::write(tree.join("nested").join("junk.txt"), "")?;
+        std::os::unix::fs::symlink(dir.path().join("keep.txt"), tree.join("file-link"))?;
+        std::os::unix::fs::symlink(&outside, tree.join("nested").join("dir-link"))?;
+        std::os::unix::fs::symlink(&tree, tree.join("loop"))?;
+
+        FileOperation::delete(&tree)?;
+        assert!(fs::symlink_metadata(&tree).is_err());
+        assert_eq!(fs::read_to_string(dir.path().join("keep.txt"))?, "precious");
+        assert_eq!(fs::read_to_string(outside.join("keep.txt"))?, "precious");
+
+        // Deleting a link to a directory removes just the link
+        std::os::unix::fs::symlink(&outside, dir.path().join("link"))?;
+        FileOperation::delete(&dir.path().join("link"))?;
+        assert!(outside.join("keep.txt").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_protected_paths() -> Result<()> {
+        let dir = tempdir()?;
+        assert!(FileOperation::is_protected_path(Path::new("/")));
+        if let Some(home) = dirs::home_dir() {
+            assert!(FileOperation::is_protected_path(&home));
+        }
+        #[cfg(target_os = "linux")]
+        {
+            assert!(FileOperation::is_protected_path(Path::new("/proc/self")));
+            assert!(FileOperation::is_protected_path(Path::new("/dev")));
+            // Only whole components match
+            assert!(!FileOperation::is_protected_path(Path::new("/devel/notes.txt")));
+            assert!(!FileOperation::is_protected_path(Path::new("/processing")));
+        }
+        assert!(!FileOperation::is_protected_path(&dir.path().join("project")));
+        assert!(FileOperation::is_path_safe(&dir.path().join("project")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_collision_strategies() -> Result<()> {
+        let dir = tempdir()?;
+        let src = dir.path().join("src");
+        let dest = dir.path().join("dest");
+        fs::create_dir_all(src.join("folder"))?;
+        fs::create_dir(&dest)?;
+        fs::write(src.join("a.txt"), "new")?;
+        fs::write(src.join("folder").join("inside.txt"), "new")?;
+        let prepare = || -> Result<()> {
+            fs::write(dest.join("a.txt"), "old")?;
+            fs::create_dir_all(dest.join("folder"))?;
+            fs::write(dest.join("folder").join("stale.txt"), "old")?;
+            Ok(())
+        };
+        let copy = |name: &str, strategy| {
+            FileOperation::transfer(&src.join(name), &dest, TransferKind::Copy, strategy, false)
+        };
+        prepare()?;
+
+        assert!(copy("a.txt", CollisionStrategy::Error).is_err());
+        assert_eq!(copy("a.txt", CollisionStrategy::Skip)?, TransferOutcome::Skipped);
+        assert_eq!(copy("a.txt", CollisionStrategy::Ask)?, TransferOutcome::Conflict(dest.join("a.txt")));
+        assert_eq!(fs::read_to_string(dest.join("a.txt"))?, "old");
+
+        assert_eq!(copy("a.txt", CollisionStrategy::Rename)?, TransferOutcome::Done(dest.join("a.2.txt")));
+        assert_eq!(copy("a.txt", CollisionStrategy::Rename)?, TransferOutcome::Done(dest.join("a.3.txt")));
+        assert_eq!(fs::read_to_string(dest.join("a.txt"))?, "old");
+        assert_eq!(fs::read_to_string(dest.join("a.3.txt"))?, "new");
+
+        // Overwriting a folder replaces it rather than merging into it
+        assert_eq!(copy("a.txt", CollisionStrategy::Overwrite)?, TransferOutcome::Done(dest.join("a.txt")));
+        assert_eq!(fs::read_to_string(dest.join("a.txt"))?, "new");
+        copy("folder", CollisionStrategy::Overwrite)?;
+        assert!(dest.join("folder").join("inside.txt").exists());
+        assert!(!dest.join("folder").join("stale.txt").exists());
+
+        // A move with a free name just moves; onto itself it never overwrites
+        let moved = FileOperation::transfer(&dest.join("a.2.txt"), &src, TransferKind::Move, CollisionStrategy::Rename, false)?;
+        assert_eq!(moved, TransferOutcome::Done(src.join("a.2.txt")));
+        assert!(!dest.join("a.2.txt").exists());
+        let onto_itself = FileOperation::transfer(&src.join("a.txt"), &src, TransferKind::Move, CollisionStrategy::Overwrite, false);
+        assert!(onto_itself.is_err());
+        assert_eq!(fs::read_to_string(src.join("a.txt"))?, "new");
+
+        assert_eq!(CollisionStrategy::parse("Rename"), Some(CollisionStrategy::Rename));
+        assert_eq!(CollisionStrategy::parse("merge"), None);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_overwrite_keeps_the_old_target() -> Result<()> {
+        let dir = tempdir()?;
+        let src = dir.path().join("src");
+        let dest = dir.path().join("dest");
+        fs::create_dir_all(src.join("folder"))?;
+        fs::create_dir_all(dest.join("folder"))?;
+        fs::write(src.join("folder").join("a.txt"), "new")?;
+        // Sockets can't be opened for reading, so copying the folder fails partway
+        let _socket = std::os::unix::net::UnixListener::bind(src.join("folder").join("socket"))?;
+        fs::write(dest.join("folder").join("keep.txt"), "old")?;
+
+        let copied = FileOperation::transfer(&src.join("folder"), &dest, TransferKind::Copy, CollisionStrategy::Overwrite, false);
+        assert!(copied.is_err());
+        assert_eq!(fs::read_to_string(dest.join("folder").join("keep.txt"))?, "old");
+        let left: Vec<_> = fs::read_dir(&dest)?.map(|e| e.map(|e| e.file_name())).collect::<std::io::Result<_>>()?;
+        assert_eq!(left, vec!["folder"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_flat_and_preserved() -> Result<()> {
+        let dir = tempdir()?;
+        let base = dir.path().join("project");
+        fs::create_dir_all(base.join("a"))?;
+        fs::create_dir_all(base.join("b"))?;
+        fs::write(base.join("a").join("notes.txt"), "a")?;
+        fs::write(base.join("b").join("notes.txt"), "b")?;
+        let files = vec![base.join("a").join("notes.txt"), base.join("b").join("notes.txt"), base.join("b")];
+
+        let flat = dir.path().join("flat");
+        let report = FileOperation::collect(&files, &base, &flat, false, CollisionStrategy::Ask, |_, _| true)?;
+        assert_eq!(report.copied, vec![flat.join("notes.txt"), flat.join("notes.2.txt")]);
+        assert_eq!(report.skipped, 1);
+        assert!(report.failed.is_empty());
+
+        let tree = dir.path().join("tree");
+        let report = FileOperation::collect(&files, &base, &tree, true, CollisionStrategy::Error, |_, _| true)?;
+        assert_eq!(report.copied.len(), 2);
+        assert_eq!(fs::read_to_string(tree.join("b").join("notes.txt"))?, "b");
+
+        // Collecting again collides with every file
+        let report = FileOperation::collect(&files, &base, &tree, true, CollisionStrategy::Error, |_, _| true)?;
+        assert_eq!(report.failed.len(), 2);
+
+        let report = FileOperation::collect(&files, &base, &dir.path().join("none"), false, CollisionStrategy::Skip, |done, _| done < 1)?;
+        assert_eq!(report.copied.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_permission_changes() -> Result<()> {
+        assert_eq!(PermissionChange::parse_mode("644")?, PermissionChange::Mode(0o644));
+        assert_eq!(PermissionChange::parse_mode("0755")?, PermissionChange::Mode(0o755));
+        assert_eq!(PermissionChange::parse_mode("+x")?, PermissionChange::Adjust { add: 0o111, remove: 0 });
+        assert_eq!(PermissionChange::parse_mode("go-w")?, PermissionChange::Adjust { add: 0, remove: 0o022 });
+        assert!(PermissionChange::parse_mode("u+z").is_err());
+        assert!(PermissionChange::parse_mode("99").is_err());
+
+        assert_eq!(PermissionChange::parse_mode("u+x")?.apply_to_mode(0o100644), 0o100744);
+        assert_eq!(PermissionChange::parse_mode("600")?.apply_to_mode(0o100755), 0o100600);
+
+        assert_eq!(
+            PermissionChange::parse_owner("1000:100")?,
+            PermissionChange::Owner { uid: Some(1000), gid: Some(100) }
+        );
+        assert_eq!(PermissionChange::parse_owner(":7")?, PermissionChange::Owner { uid: None, gid: Some(7) });
+        assert!(PermissionChange::parse_owner(":").is_err());
+        assert!(PermissionChange::parse_owner("no-such-user-here").is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_set_permissions_recursive() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = |path: &Path| fs::metadata(path).map(|m| m.permissions().mode() & 0o777);
+
+        let dir = tempdir()?;
+        let scripts = dir.path().join("scripts");
+        fs::create_dir(&scripts)?;
+        fs::write(scripts.join("build.sh"), "")?;
+        fs::write(dir.path().join("notes.txt"), "")?;
+        std::os::unix::fs::symlink(dir.path().join("notes.txt"), scripts.join("link"))?;
+        let paths = vec![scripts.clone(), dir.path().join("notes.txt")];
+
+        // The preview lists what will change, without following the symlink
+        let targets = FileOperation::permission_targets(&paths, true);
+        assert_eq!(targets.len(), 3);
+        assert_eq!(FileOperation::permission_targets(&paths, false).len(), 2);
+
+        let report = FileOperation::set_permissions_recursive(&paths, &PermissionChange::Mode(0o750), true)?;
+        assert_eq!(report.changed.len(), 3);
+        assert!(report.failed.is_empty());
+        assert_eq!(mode(&scripts.join("build.sh"))?, 0o750);
+        assert_eq!(mode(&dir.path().join("notes.txt"))?, 0o750);
+
+        // A missing entry is reported and the rest still change
+        let paths = vec![dir.path().join("gone.txt"), dir.path().join("notes.txt")];
+        fs::set_permissions(dir.path().join("notes.txt"), fs::Permissions::from_mode(0o644))?;
+        let report = FileOperation::set_permissions_recursive(&paths, &PermissionChange::parse_mode("+x")?, false)?;
+        assert_eq!(report.changed.len(), 1);
+        assert_eq!(mode(&dir.path().join("notes.txt"))?, 0o755);
+
+        // 644 takes the search bit off folders, so their contents change first
+        let nested = scripts.join("lib");
+        fs::create_dir(&nested)?;
+        fs::write(nested.join("util.sh"), "")?;
+        let paths = vec![scripts.clone()];
+        let targets = FileOperation::permission_targets(&paths, true);
+        let position = |path: &Path| targets.iter().position(|t| t == path).unwrap();
+        assert!(position(&nested.join("util.sh")) < position(&nested));
+        assert!(position(&nested) < position(&scripts));
+        assert_eq!(targets.last(), Some(&scripts));
+
+        let report = FileOperation::set_permissions_recursive(&paths, &PermissionChange::parse_mode("644")?, true)?;
+        assert!(report.failed.is_empty());
+        assert_eq!(report.changed.len(), 4);
+        for path in [&scripts, &nested, &nested.join("util.sh"), &scripts.join("build.sh")] {
+            assert_eq!(mode(path)?, 0o644);
+        }
+        fs::set_permissions(&scripts, fs::Permissions::from_mode(0o755))?;
+        fs::set_permissions(&nested, fs::Permissions::from_mode(0o755))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_twice_gets_distinct_names() -> Result<()> {
+        let dir = tempdir()?;
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "hi")?;
+        for expected in ["a copy.txt", "a copy 2.txt"] {
+            let dest = duplicate_path(&file);
+            assert_eq!(dest, dir.path().join(expected));
+            FileOperation::copy(&file, &dest, false)?;
+            assert_eq!(fs::read_to_string(&dest)?, "hi");
+        }
+
+        let photos = dir.path().join("photos.old");
+        fs::create_dir_all(photos.join("june"))?;
+        fs::write(photos.join("june").join("b.jpg"), "jpg")?;
+        let dest = duplicate_path(&photos);
+        assert_eq!(dest, dir.path().join("photos.old copy"));
+        FileOperation::copy(&photos, &dest, false)?;
+        assert!(dest.join("june").join("b.jpg").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_text_file_refuses_binary_and_large_files() -> Result<()> {
+        let dir = tempdir()?;
+        let config = dir.path().join("config.toml");
+        fs::write(&config, "[server]\nport = 8080\n")?;
+        assert_eq!(read_text_file(&config, 1024)?, "[server]\nport = 8080\n");
+        assert!(read_text_file(&config, 4).is_err());
+
+        let image = dir.path().join("icon.png");
+        fs::write(&image, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")?;
+        assert!(read_text_file(&image, 1024).unwrap_err().to_string().contains("binary"));
+        fs::write(&image, [0xff, 0xfe, 0x41])?;
+        assert!(read_text_file(&image, 1024).is_err());
+        assert!(read_text_file(dir.path(), 1024).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_to_and_path_completion() -> Result<()> {
+        let dir = tempdir()?;
+        let base = dir.path();
+        fs::create_dir_all(base.join("archive").join("2023"))?;
+        fs::create_dir(base.join("articles"))?;
+        fs::write(base.join("report.txt"), "q3")?;
+        fs::write(base.join(".hidden"), "")?;
+
+        assert_eq!(complete_path("a", base).as_deref(), Some("ar"), "narrowed to the shared prefix");
+        assert_eq!(complete_path("ar", base), None);
+        assert_eq!(complete_path("arc", base), Some(format!("archive{}", std::path::MAIN_SEPARATOR)));
+        assert_eq!(complete_path("archive/2", base), Some(format!("archive/2023{}", std::path::MAIN_SEPARATOR)));
+        assert_eq!(complete_path("rep", base).as_deref(), Some("report.txt"));
+        assert_eq!(complete_path("zzz", base), None);
+        assert_eq!(complete_path(".h", base).as_deref(), Some(".hidden"));
+
+        // Into a folder keeps the name; anywhere else is the new path, parents and all
+        let moved = FileOperation::move_to(&base.join("report.txt"), &base.join("archive"), CollisionStrategy::Error)?;
+        assert_eq!(moved, TransferOutcome::Done(base.join("archive").join("report.txt")));
+        let renamed = base.join("archive").join("2024").join("q3.txt");
+        assert_eq!(
+            FileOperation::move_to(&base.join("archive").join("report.txt"), &renamed, CollisionStrategy::Error)?,
+            TransferOutcome::Done(renamed.clone())
+        );
+        assert_eq!(fs::read_to_string(&renamed)?, "q3");
+
+        fs::write(base.join("q3.txt"), "draft")?;
+        let into = base.join("archive").join("2024");
+        assert!(FileOperation::move_to(&base.join("q3.txt"), &into, CollisionStrategy::Error).is_err());
+        assert_eq!(FileOperation::move_to(&base.join("q3.txt"), &into, CollisionStrategy::Skip)?, TransferOutcome::Skipped);
+        assert_eq!(
+            FileOperation::move_to(&base.join("q3.txt"), &into, CollisionStrategy::Rename)?,
+            TransferOutcome::Done(into.join("q3.2.txt"))
+        );
+
+        assert!(FileOperation::move_to(&base.join("archive"), &base.join("archive").join("2023"), CollisionStrategy::Rename).is_err());
+        assert!(FileOperation::move_to(&base.join("articles"), base, CollisionStrategy::Rename).is_err());
+        assert!(base.join("archive").join("2023").is_dir());
+        Ok(())
+    }
+
+    #[test]
+    fn test_flatten_moves_nested_files_up_without_overwriting() -> Result<()> {
+        let dir = tempdir()?;
+        let make = |root: &Path| -> Result<()> {
+            fs::create_dir_all(root.join("album").join("disc 1"))?;
+            fs::create_dir_all(root.join("cover"))?;
+            fs::write(root.join("notes.txt"), "top")?;
+            fs::write(root.join("album").join("notes.txt"), "album")?;
+            fs::write(root.join("album").join("disc 1").join("01.flac"), "track")?;
+            fs::write(root.join("album").join("disc 1").join("notes.txt"), "disc")?;
+            // Named like a subfolder being emptied
+            fs::write(root.join("album").join("cover"), "jpg")?;
+            Ok(())
+        };
+
+        let root = dir.path().join("renamed");
+        make(&root)?;
+        let plan = FileOperation::plan_flatten(&root, CollisionStrategy::Rename)?;
+        assert_eq!(plan.moves.len(), 4);
+        assert_eq!(plan.moves[0], (root.join("album").join("cover"), root.join("cover.2")));
+        assert!(root.join("album").join("disc 1").join("01.flac").exists(), "planning moves nothing");
+        let report = FileOperation::apply_flatten(&plan);
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+        assert_eq!(fs::read_to_string(root.join("notes.txt"))?, "top");
+        assert_eq!(fs::read_to_string(root.join("notes.2.txt"))?, "album");
+        assert_eq!(fs::read_to_string(root.join("notes.3.txt"))?, "disc");
+        assert_eq!(fs::read_to_string(root.join("01.flac"))?, "track");
+        assert!(!root.join("album").exists(), "emptied folders are removed");
+        assert!(root.join("cover").is_dir(), "folders that were empty already stay");
+
+        let root = dir.path().join("skipped");
+        make(&root)?;
+        let report = FileOperation::flatten(&root, CollisionStrategy::Skip)?;
+        assert_eq!((report.moved.len(), report.skipped), (2, 2));
+        assert_eq!(fs::read_to_string(root.join("notes.txt"))?, "top");
+        assert!(root.join("album").join("notes.txt").exists(), "skipped files keep their folder");
+
+        let root = dir.path().join("overwritten");
+        make(&root)?;
+        assert!(FileOperation::flatten(&root, CollisionStrategy::Error).is_err());
+        assert_eq!(fs::read_to_string(root.join("notes.txt"))?, "top", "an error moves nothing");
+        let plan = FileOperation::plan_flatten(&root, CollisionStrategy::Overwrite)?;
+        assert_eq!(plan.replacing.len(), 2);
+        FileOperation::apply_flatten(&plan);
+        assert_eq!(fs::read_to_string(root.join("notes.txt"))?, "disc");
+        assert_eq!(fs::read_to_string(root.join("cover.2"))?, "jpg");
+
+        // A name taken between planning and applying is left alone
+        let root = dir.path().join("raced");
+        make(&root)?;
+        let plan = FileOperation::plan_flatten(&root, CollisionStrategy::Rename)?;
+        fs::write(root.join("01.flac"), "meanwhile")?;
+        let report = FileOperation::apply_flatten(&plan);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(fs::read_to_string(root.join("01.flac"))?, "meanwhile");
+        assert!(root.join("album").join("disc 1").join("01.flac").exists());
+        Ok(())
+    }
 }
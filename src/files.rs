@@ -1,14 +1,56 @@
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
 use humansize::{format_size, BINARY};
+use ignore::{WalkBuilder, WalkState};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// How deep `recent_files` descends below the starting directory
+pub const RECENT_FILES_MAX_DEPTH: usize = 12;
+
+/// Directory item counts stop here; the listing shows anything past 99 as `99+`
+pub const DIR_COUNT_LIMIT: usize = 100;
+
+/// The mime type a file's first bytes identify, e.g. "image/png"; None for text
+/// and anything `infer` doesn't recognise
+pub fn detect_mime(path: &Path) -> Option<String> {
+    infer::get_from_path(path).ok().flatten().map(|kind| kind.mime_type().to_string())
+}
+
+/// Replace `path` with `contents` through a temporary file in the same
+/// directory, so a crash mid-write leaves the old file rather than a torn one
+pub fn write_atomic(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(contents)?;
+    file.as_file().sync_all()?;
+    file.persist(path)?;
+    Ok(())
+}
 
 #[derive(Clone, Debug)]
 pub struct FileEntry {
+    /// The real path, raw bytes and all; anything that touches the file goes through this
     pub path: PathBuf,
+    /// Name for display only: invalid UTF-8 is replaced with `�` and control
+    /// characters with `?`, so it may not name the file exactly
     pub name: String,
     pub is_dir: bool,
     pub size: u64,
     pub is_hidden: bool,
+    pub modified: Option<SystemTime>,
+    /// Entries directly inside a directory, up to `DIR_COUNT_LIMIT`; None until counted
+    pub item_count: Option<usize>,
+    /// Mime type read from the contents, for files without an extension to go
+    /// by; None until filled in by a `MimeCache`
+    pub mime: Option<String>,
 }
 
 impl FileEntry {
@@ -18,7 +60,9 @@ impl FileEntry {
             .file_name()
             .unwrap_or_default()
             .to_string_lossy()
-            .to_string();
+            .chars()
+            .map(|c| if c.is_control() { '?' } else { c })
+            .collect::<String>();
         
         let is_hidden = name.starts_with('.');
 
@@ -28,9 +72,17 @@ impl FileEntry {
             is_dir: metadata.is_dir(),
             size: metadata.len(),
             is_hidden,
+            modified: metadata.modified().ok(),
+            item_count: None,
+            mime: None,
         })
     }
 
+    /// The item count as shown in the listing: `42`, or `99+` past 99
+    pub fn item_count_label(&self) -> Option<String> {
+        self.item_count.map(|count| if count > 99 { "99+".to_string() } else { count.to_string() })
+    }
+
     pub fn size_formatted(&self) -> String {
         if self.is_dir {
             String::from("<DIR>")
@@ -40,29 +92,993 @@ impl FileEntry {
     }
 }
 
+/// Glob patterns for entries that are always left out, compiled once.
+///
+/// A pattern without `/` matches entry names (`*.pyc`, `node_modules`);
+/// one with `/` matches the path (`build/*.o` matches at any depth).
+#[derive(Clone, Debug, Default)]
+pub struct IgnorePatterns {
+    names: Option<GlobSet>,
+    paths: Option<GlobSet>,
+}
+
+impl IgnorePatterns {
+    pub fn new(patterns: &[String]) -> anyhow::Result<Self> {
+        let mut names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+        let (mut name_count, mut path_count) = (0, 0);
+        for pattern in patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let pattern = pattern.trim_end_matches('/');
+            let invalid = |e: globset::Error| anyhow::anyhow!("Invalid pattern '{}': {}", pattern, e);
+            if !pattern.contains('/') {
+                names.add(Glob::new(pattern).map_err(invalid)?);
+                name_count += 1;
+                continue;
+            }
+            let glob = if !pattern.starts_with('/') && !pattern.starts_with("**") {
+                format!("**/{}", pattern)
+            } else {
+                pattern.to_string()
+            };
+            // `*` must stay within one component, or `build/*.o` would match `build/x/y.o`
+            paths.add(GlobBuilder::new(&glob).literal_separator(true).build().map_err(invalid)?);
+            path_count += 1;
+        }
+        Ok(Self {
+            names: if name_count == 0 { None } else { Some(names.build()?) },
+            paths: if path_count == 0 { None } else { Some(paths.build()?) },
+        })
+    }
+
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let name_matches = match (&self.names, path.file_name()) {
+            (Some(set), Some(name)) => set.is_match(name),
+            _ => false,
+        };
+        name_matches || self.paths.as_ref().is_some_and(|set| set.is_match(path))
+    }
+}
+
+/// Exceptions to showing or hiding hidden files: entries matching `always_show`
+/// are listed even while hidden files are off, and ones matching `always_hide`
+/// count as hidden like dotfiles. `always_show` wins when both match.
+#[derive(Clone, Debug, Default)]
+pub struct HiddenPatterns {
+    always_show: IgnorePatterns,
+    always_hide: IgnorePatterns,
+}
+
+impl HiddenPatterns {
+    pub fn new(always_show: &[String], always_hide: &[String]) -> anyhow::Result<Self> {
+        Ok(Self {
+            always_show: IgnorePatterns::new(always_show)?,
+            always_hide: IgnorePatterns::new(always_hide)?,
+        })
+    }
+
+    /// Mark `entry` hidden if `always_hide` says so, and whether it's listed
+    fn apply(&self, entry: &mut FileEntry, show_hidden: bool) -> bool {
+        if self.always_show.is_ignored(&entry.path) {
+            return true;
+        }
+        if self.always_hide.is_ignored(&entry.path) {
+            entry.is_hidden = true;
+        }
+        show_hidden || !entry.is_hidden
+    }
+}
+
 pub fn list_directory(path: &Path, show_hidden: bool) -> anyhow::Result<Vec<FileEntry>> {
+    list_directory_with(path, show_hidden, &IgnorePatterns::default(), &HiddenPatterns::default())
+}
+
+/// List a directory, leaving out entries matching `ignore` and hidden ones
+/// (as adjusted by `hidden`) unless `show_hidden`
+pub fn list_directory_with(
+    path: &Path,
+    show_hidden: bool,
+    ignore: &IgnorePatterns,
+    hidden: &HiddenPatterns,
+) -> anyhow::Result<Vec<FileEntry>> {
     let mut entries = Vec::new();
 
     for entry in fs::read_dir(path)? {
         let entry = entry?;
         let path = entry.path();
+        if ignore.is_ignored(&path) {
+            continue;
+        }
         
-        if let Ok(file_entry) = FileEntry::from_path(&path) {
-            if !show_hidden && file_entry.is_hidden {
-                continue;
+        if let Ok(mut file_entry) = FileEntry::from_path(&path) {
+            if hidden.apply(&mut file_entry, show_hidden) {
+                entries.push(file_entry);
             }
-            entries.push(file_entry);
         }
     }
 
-    // Sort: directories first, then alphabetically
-    entries.sort_by(|a, b| {
-        match (a.is_dir, b.is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-        }
-    });
+    SortMode::Name.sort(&mut entries);
 
     Ok(entries)
 }
+
+/// Order of a listing; directories always come before files
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortMode {
+    #[default]
+    Name,
+    NameDesc,
+    Modified,
+    ModifiedDesc,
+    Size,
+    SizeDesc,
+}
+
+impl SortMode {
+    const ALL: [SortMode; 6] = [
+        SortMode::Name,
+        SortMode::NameDesc,
+        SortMode::Modified,
+        SortMode::ModifiedDesc,
+        SortMode::Size,
+        SortMode::SizeDesc,
+    ];
+
+    /// "name", "name-desc", "modified", "modified-desc", "size" or "size-desc"
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase();
+        Self::ALL.into_iter().find(|mode| mode.as_str() == value)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortMode::Name => "name",
+            SortMode::NameDesc => "name-desc",
+            SortMode::Modified => "modified",
+            SortMode::ModifiedDesc => "modified-desc",
+            SortMode::Size => "size",
+            SortMode::SizeDesc => "size-desc",
+        }
+    }
+
+    /// The mode after this one, wrapping around
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Sort `entries`, directories first; ties fall back to the name
+    pub fn sort(self, entries: &mut [FileEntry]) {
+        entries.sort_by(|a, b| {
+            let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+            let order = match self {
+                SortMode::Name => by_name(),
+                SortMode::NameDesc => by_name().reverse(),
+                SortMode::Modified => a.modified.cmp(&b.modified).then_with(by_name),
+                SortMode::ModifiedDesc => b.modified.cmp(&a.modified).then_with(by_name),
+                SortMode::Size => a.size.cmp(&b.size).then_with(by_name),
+                SortMode::SizeDesc => b.size.cmp(&a.size).then_with(by_name),
+            };
+            b.is_dir.cmp(&a.is_dir).then(order)
+        });
+    }
+}
+
+/// Sort modes for directories matching a path or glob, e.g. `~/Downloads` or
+/// `**/Photos`; `~` stands for the home directory. The longest matching
+/// pattern wins.
+#[derive(Clone, Debug, Default)]
+pub struct SortRules {
+    rules: Vec<(String, GlobMatcher, SortMode)>,
+}
+
+impl SortRules {
+    pub fn new(rules: &HashMap<String, String>) -> anyhow::Result<Self> {
+        let mut compiled = Vec::new();
+        for (pattern, mode) in rules {
+            let sort = SortMode::parse(mode).ok_or_else(|| anyhow::anyhow!("Unknown sort mode '{}'", mode))?;
+            let expanded = match pattern.strip_prefix('~') {
+                Some(rest) => format!("{}{}", dirs::home_dir().unwrap_or_default().display(), rest),
+                None => pattern.clone(),
+            };
+            let matcher = Glob::new(expanded.trim_end_matches('/'))?.compile_matcher();
+            compiled.push((pattern.clone(), matcher, sort));
+        }
+        compiled.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        Ok(Self { rules: compiled })
+    }
+
+    /// The configured mode for `dir`, if a pattern matches it
+    pub fn mode_for(&self, dir: &Path) -> Option<SortMode> {
+        self.rules.iter().find(|(_, matcher, _)| matcher.is_match(dir)).map(|(_, _, sort)| *sort)
+    }
+}
+
+/// Shallow item counts of directories, kept until a directory's mtime changes
+#[derive(Debug, Default)]
+pub struct DirCountCache {
+    counts: HashMap<PathBuf, (SystemTime, usize)>,
+}
+
+impl DirCountCache {
+    /// How many entries `dir` holds, up to `DIR_COUNT_LIMIT`; None if it can't be read
+    pub fn count(&mut self, dir: &Path) -> Option<usize> {
+        let modified = fs::metadata(dir).and_then(|m| m.modified()).ok()?;
+        if let Some(&(cached_at, count)) = self.counts.get(dir) {
+            if cached_at == modified {
+                return Some(count);
+            }
+        }
+        let count = fs::read_dir(dir).ok()?.take(DIR_COUNT_LIMIT).count();
+        // Forget everything now and then rather than grow without bound
+        if self.counts.len() >= 4096 {
+            self.counts.clear();
+        }
+        self.counts.insert(dir.to_path_buf(), (modified, count));
+        Some(count)
+    }
+
+    /// Set `item_count` on every directory in a listing
+    pub fn fill(&mut self, entries: &mut [FileEntry]) {
+        for entry in entries.iter_mut().filter(|e| e.is_dir) {
+            entry.item_count = self.count(&entry.path);
+        }
+    }
+}
+
+/// Mime types of extensionless files, kept until a file's mtime changes
+#[derive(Debug, Default)]
+pub struct MimeCache {
+    mimes: HashMap<PathBuf, (SystemTime, Option<String>)>,
+}
+
+impl MimeCache {
+    /// The mime type `detect_mime` finds for `path`, read again only once the file changes
+    pub fn mime(&mut self, path: &Path, modified: SystemTime) -> Option<String> {
+        if let Some((cached_at, mime)) = self.mimes.get(path) {
+            if *cached_at == modified {
+                return mime.clone();
+            }
+        }
+        let mime = detect_mime(path);
+        // Forget everything now and then rather than grow without bound
+        if self.mimes.len() >= 4096 {
+            self.mimes.clear();
+        }
+        self.mimes.insert(path.to_path_buf(), (modified, mime.clone()));
+        mime
+    }
+
+    /// Set `mime` on every file in a listing without an extension; sniffing
+    /// costs a read, so files the extension can tell about are left alone
+    pub fn fill(&mut self, entries: &mut [FileEntry]) {
+        for entry in entries.iter_mut().filter(|e| !e.is_dir && e.path.extension().is_none()) {
+            entry.mime = entry.modified.and_then(|modified| self.mime(&entry.path, modified));
+        }
+    }
+}
+
+/// Reorder a listing by a saved manual order of names.
+///
+/// Named entries come first, in the saved order; anything not in the order
+/// (such as new files) follows in its existing order.
+pub fn apply_custom_order(entries: &mut [FileEntry], order: &[String]) {
+    let position: std::collections::HashMap<&str, usize> =
+        order.iter().enumerate().map(|(i, name)| (name.as_str(), i)).collect();
+    entries.sort_by_key(|entry| position.get(entry.name.as_str()).copied().unwrap_or(usize::MAX));
+}
+
+/// Totals for everything below a directory
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DirStats {
+    pub dirs: u64,
+    pub files: u64,
+    pub total_size: u64,
+}
+
+/// Count subdirectories, files and bytes below `path`, including hidden entries.
+/// With `follow_symlinks`, linked directories are counted too; links that loop
+/// back to a parent are skipped.
+pub fn dir_stats(path: &Path, follow_symlinks: bool) -> anyhow::Result<DirStats> {
+    if !path.is_dir() {
+        return Err(anyhow::anyhow!("Not a directory: {:?}", path));
+    }
+
+    let mut stats = DirStats::default();
+    // min_depth(1) leaves out `path` itself
+    for entry in walkdir::WalkDir::new(path)
+        .min_depth(1)
+        .follow_links(follow_symlinks)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if entry.file_type().is_dir() {
+            stats.dirs += 1;
+        } else {
+            stats.files += 1;
+            if let Ok(metadata) = entry.metadata() {
+                stats.total_size += metadata.len();
+            }
+        }
+    }
+    Ok(stats)
+}
+
+/// Free and total bytes on the filesystem holding a path
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiskSpace {
+    pub mount_point: PathBuf,
+    pub available: u64,
+    pub total: u64,
+}
+
+/// Look up the filesystem containing `path` (the mount with the longest matching prefix).
+///
+/// This lists every mounted disk, so callers should cache the result.
+pub fn disk_space(path: &Path) -> Option<DiskSpace> {
+    let path = fs::canonicalize(path).ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| DiskSpace {
+            mount_point: disk.mount_point().to_path_buf(),
+            available: disk.available_space(),
+            total: disk.total_space(),
+        })
+}
+
+/// A mounted filesystem or removable drive
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mount {
+    /// Device or volume name, e.g. "/dev/sda1" or "USB STICK"
+    pub name: String,
+    pub mount_point: PathBuf,
+    /// Filesystem type, e.g. "ext4", "vfat" or "NTFS"
+    pub file_system: String,
+    pub removable: bool,
+    pub available: u64,
+    pub total: u64,
+}
+
+/// Mounted filesystems, sorted by mount point.
+///
+/// Mounts come and go, so this asks the OS each time rather than caching.
+pub fn list_mounts() -> Vec<Mount> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    let mut mounts: Vec<Mount> = disks
+        .list()
+        .iter()
+        .map(|disk| Mount {
+            name: disk.name().to_string_lossy().to_string(),
+            mount_point: disk.mount_point().to_path_buf(),
+            file_system: disk.file_system().to_string_lossy().to_string(),
+            removable: disk.is_removable(),
+            available: disk.available_space(),
+            total: disk.total_space(),
+        })
+        .collect();
+    mounts.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    // Bind mounts and btrfs subvolumes can show up more than once
+    mounts.dedup_by(|a, b| a.mount_point == b.mount_point);
+    mounts
+}
+
+/// Checksum algorithms offered for a file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HashAlgo {
+    Md5,
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgo {
+    pub const ALL: [HashAlgo; 3] = [HashAlgo::Md5, HashAlgo::Sha256, HashAlgo::Blake3];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HashAlgo::Md5 => "md5",
+            HashAlgo::Sha256 => "sha256",
+            HashAlgo::Blake3 => "blake3",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace('-', "").as_str() {
+            "md5" => Some(HashAlgo::Md5),
+            "sha256" => Some(HashAlgo::Sha256),
+            "blake3" => Some(HashAlgo::Blake3),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HashAlgo::Md5 => "MD5",
+            HashAlgo::Sha256 => "SHA-256",
+            HashAlgo::Blake3 => "BLAKE3",
+        }
+    }
+}
+
+/// Hex digest of a file's contents
+#[allow(dead_code)]
+pub fn hash_file(path: &Path, algo: HashAlgo) -> anyhow::Result<String> {
+    let mut digests = hash_file_with(path, &[algo], |_| true)?;
+    digests.pop().map(|(_, hex)| hex).ok_or_else(|| anyhow::anyhow!("Hashing was cancelled"))
+}
+
+/// Compute several digests in one pass over the file.
+///
+/// `progress` gets the number of bytes read so far after every chunk and
+/// returns false to stop early, in which case no digests are returned.
+pub fn hash_file_with(
+    path: &Path,
+    algos: &[HashAlgo],
+    mut progress: impl FnMut(u64) -> bool,
+) -> anyhow::Result<Vec<(HashAlgo, String)>> {
+    use sha2::Digest;
+    use std::io::Read;
+
+    if path.is_dir() {
+        return Err(anyhow::anyhow!("Cannot hash a directory: {:?}", path));
+    }
+    let mut file = fs::File::open(path)?;
+    let mut md5 = algos.contains(&HashAlgo::Md5).then(md5::Md5::new);
+    let mut sha256 = algos.contains(&HashAlgo::Sha256).then(sha2::Sha256::new);
+    let mut blake3 = algos.contains(&HashAlgo::Blake3).then(blake3::Hasher::new);
+
+    let mut buffer = vec![0u8; 1024 * 1024];
+    let mut read = 0u64;
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        let chunk = &buffer[..n];
+        if let Some(md5) = md5.as_mut() {
+            md5.update(chunk);
+        }
+        if let Some(sha256) = sha256.as_mut() {
+            sha256.update(chunk);
+        }
+        if let Some(blake3) = blake3.as_mut() {
+            blake3.update(chunk);
+        }
+        read += n as u64;
+        if !progress(read) {
+            return Ok(Vec::new());
+        }
+    }
+
+    let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    Ok(algos
+        .iter()
+        .map(|&algo| {
+            let digest = match algo {
+                HashAlgo::Md5 => md5.clone().map(|h| hex(&h.finalize())),
+                HashAlgo::Sha256 => sha256.clone().map(|h| hex(&h.finalize())),
+                HashAlgo::Blake3 => blake3.as_ref().map(|h| h.finalize().to_hex().to_string()),
+            };
+            (algo, digest.unwrap_or_default())
+        })
+        .collect())
+}
+
+/// Unchanged lines kept around each change in a diff
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// What happened to a line between the two files
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffKind {
+    Equal,
+    Added,
+    Removed,
+    /// A line replaced by another; both sides are set
+    Changed,
+    /// Unchanged lines left out between two hunks
+    Gap,
+}
+
+impl DiffKind {
+    #[allow(dead_code)]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiffKind::Equal => "equal",
+            DiffKind::Added => "added",
+            DiffKind::Removed => "removed",
+            DiffKind::Changed => "changed",
+            DiffKind::Gap => "gap",
+        }
+    }
+}
+
+/// One row of a diff: (1-based line number, text) on each side that has it
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub old: Option<(usize, String)>,
+    pub new: Option<(usize, String)>,
+}
+
+/// Line diff between two files, as rows usable for unified or side-by-side views
+#[derive(Clone, Debug)]
+pub struct DiffResult {
+    pub old_path: PathBuf,
+    pub new_path: PathBuf,
+    pub old_size: u64,
+    pub new_size: u64,
+    /// Either file is not text; `lines` is empty
+    pub binary: bool,
+    pub identical: bool,
+    /// Changed hunks with a few lines of context, `Gap` rows in between
+    pub lines: Vec<DiffLine>,
+    /// Rows were cut off at the line limit
+    pub truncated: bool,
+}
+
+impl DiffResult {
+    /// (added, removed, changed) line counts among the rows
+    pub fn counts(&self) -> (usize, usize, usize) {
+        let count = |kind| self.lines.iter().filter(|l| l.kind == kind).count();
+        (count(DiffKind::Added), count(DiffKind::Removed), count(DiffKind::Changed))
+    }
+}
+
+/// Diff two files line by line, keeping at most `max_lines` rows. Files
+/// over `max_bytes` are refused before anything is read.
+pub fn diff_files(a: &Path, b: &Path, max_lines: usize, max_bytes: u64) -> anyhow::Result<DiffResult> {
+    let read = |path: &Path| -> anyhow::Result<Vec<u8>> {
+        let metadata = fs::metadata(path).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        if metadata.is_dir() {
+            return Err(anyhow::anyhow!("Cannot diff a directory: {:?}", path));
+        }
+        if metadata.len() > max_bytes {
+            return Err(anyhow::anyhow!(
+                "{} is too large to diff ({})",
+                path.display(),
+                format_size(metadata.len(), BINARY)
+            ));
+        }
+        fs::read(path).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))
+    };
+    let (old_bytes, new_bytes) = (read(a)?, read(b)?);
+    let mut result = DiffResult {
+        old_path: a.to_path_buf(),
+        new_path: b.to_path_buf(),
+        old_size: old_bytes.len() as u64,
+        new_size: new_bytes.len() as u64,
+        binary: false,
+        identical: old_bytes == new_bytes,
+        lines: Vec::new(),
+        truncated: false,
+    };
+
+    // NUL bytes or invalid UTF-8 mean there is no sensible line diff
+    let as_text = |bytes: &[u8]| {
+        let head = &bytes[..bytes.len().min(8192)];
+        if head.contains(&0) {
+            return None;
+        }
+        String::from_utf8(bytes.to_vec()).ok()
+    };
+    let (old_text, new_text) = match (as_text(&old_bytes), as_text(&new_bytes)) {
+        (Some(old), Some(new)) => (old, new),
+        _ => {
+            result.binary = true;
+            return Ok(result);
+        }
+    };
+
+    let diff = similar::TextDiff::from_lines(&old_text, &new_text);
+    let (old_lines, new_lines) = (diff.old_slices(), diff.new_slices());
+    let old_row = |i: usize| Some((i + 1, old_lines[i].trim_end_matches(['\n', '\r']).to_string()));
+    let new_row = |i: usize| Some((i + 1, new_lines[i].trim_end_matches(['\n', '\r']).to_string()));
+
+    let mut rows = Vec::new();
+    for (hunk_index, hunk) in diff.grouped_ops(DIFF_CONTEXT_LINES).iter().enumerate() {
+        if hunk_index > 0 {
+            rows.push(DiffLine { kind: DiffKind::Gap, old: None, new: None });
+        }
+        for op in hunk {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            match tag {
+                similar::DiffTag::Equal => {
+                    for (o, n) in old_range.zip(new_range) {
+                        rows.push(DiffLine { kind: DiffKind::Equal, old: old_row(o), new: new_row(n) });
+                    }
+                }
+                similar::DiffTag::Delete => {
+                    rows.extend(old_range.map(|o| DiffLine { kind: DiffKind::Removed, old: old_row(o), new: None }));
+                }
+                similar::DiffTag::Insert => {
+                    rows.extend(new_range.map(|n| DiffLine { kind: DiffKind::Added, old: None, new: new_row(n) }));
+                }
+                similar::DiffTag::Replace => {
+                    // Pair lines up as changes; any surplus on one side is a plain add/remove
+                    let paired = old_range.len().min(new_range.len());
+                    for k in 0..paired {
+                        rows.push(DiffLine {
+                            kind: DiffKind::Changed,
+                            old: old_row(old_range.start + k),
+                            new: new_row(new_range.start + k),
+                        });
+                    }
+                    rows.extend(
+                        (old_range.start + paired..old_range.end)
+                            .map(|o| DiffLine { kind: DiffKind::Removed, old: old_row(o), new: None }),
+                    );
+                    rows.extend(
+                        (new_range.start + paired..new_range.end)
+                            .map(|n| DiffLine { kind: DiffKind::Added, old: None, new: new_row(n) }),
+                    );
+                }
+            }
+        }
+    }
+
+    result.truncated = rows.len() > max_lines;
+    rows.truncate(max_lines);
+    result.lines = rows;
+    Ok(result)
+}
+
+/// Options for the recursive walk behind `recent_files`
+#[derive(Clone, Debug)]
+pub struct WalkOptions {
+    pub show_hidden: bool,
+    pub respect_gitignore: bool,
+    pub max_depth: usize,
+    pub ignore: IgnorePatterns,
+    /// Descend into symlinked directories (cycles are detected and skipped)
+    pub follow_symlinks: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            show_hidden: false,
+            respect_gitignore: true,
+            max_depth: RECENT_FILES_MAX_DEPTH,
+            ignore: IgnorePatterns::default(),
+            follow_symlinks: false,
+        }
+    }
+}
+
+/// The `n` most recently modified files under `dir`, newest first.
+///
+/// Walks the tree in parallel; directories are descended into but never
+/// returned, and files without a readable mtime are skipped.
+pub fn recent_files(dir: &Path, n: usize, options: &WalkOptions) -> Vec<FileEntry> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let found: Mutex<Vec<(SystemTime, PathBuf)>> = Mutex::new(Vec::new());
+    let ignore = options.ignore.clone();
+
+    WalkBuilder::new(dir)
+        .hidden(!options.show_hidden)
+        .git_ignore(options.respect_gitignore)
+        .git_global(options.respect_gitignore)
+        .git_exclude(options.respect_gitignore)
+        .ignore(options.respect_gitignore)
+        .parents(options.respect_gitignore)
+        .max_depth(Some(options.max_depth))
+        .follow_links(options.follow_symlinks)
+        .filter_entry(move |entry| !ignore.is_ignored(entry.path()))
+        .build_parallel()
+        .run(|| {
+            let found = &found;
+            Box::new(move |result| {
+                let entry = match result {
+                    Ok(entry) => entry,
+                    Err(_) => return WalkState::Continue,
+                };
+                if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+                    return WalkState::Continue;
+                }
+                if let Ok(modified) = entry.metadata().and_then(|m| Ok(m.modified()?)) {
+                    if let Ok(mut found) = found.lock() {
+                        found.push((modified, entry.into_path()));
+                    }
+                }
+                WalkState::Continue
+            })
+        });
+
+    let mut found = found.into_inner().unwrap_or_default();
+    found.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    found.truncate(n);
+
+    found
+        .into_iter()
+        .filter_map(|(_, path)| FileEntry::from_path(&path).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn touch(path: &Path, age_secs: u64) -> anyhow::Result<()> {
+        fs::write(path, "x")?;
+        let mtime = SystemTime::now() - Duration::from_secs(age_secs);
+        fs::File::options().write(true).open(path)?.set_modified(mtime)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_rules_pick_longest_match() -> anyhow::Result<()> {
+        let rules = SortRules::new(&HashMap::from([
+            ("/data/**".to_string(), "size-desc".to_string()),
+            ("/data/photos".to_string(), "name".to_string()),
+            ("**/Downloads/".to_string(), "modified-desc".to_string()),
+        ]))?;
+        assert_eq!(rules.mode_for(Path::new("/data/photos")), Some(SortMode::Name));
+        assert_eq!(rules.mode_for(Path::new("/data/music")), Some(SortMode::SizeDesc));
+        assert_eq!(rules.mode_for(Path::new("/home/ada/Downloads")), Some(SortMode::ModifiedDesc));
+        assert_eq!(rules.mode_for(Path::new("/home/ada")), None);
+        assert!(SortRules::new(&HashMap::from([("/tmp".to_string(), "newest".to_string())])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recent_files_sorted_and_filtered() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let nested = dir.path().join("a").join("b");
+        fs::create_dir_all(&nested)?;
+        fs::create_dir(dir.path().join(".git"))?;
+
+        touch(&dir.path().join("old.txt"), 300)?;
+        touch(&nested.join("newest.txt"), 10)?;
+        touch(&dir.path().join("a").join("middle.txt"), 100)?;
+        touch(&dir.path().join(".hidden"), 0)?;
+        touch(&dir.path().join("build.log"), 0)?;
+        fs::write(dir.path().join(".gitignore"), "*.log\n")?;
+
+        let recent = recent_files(dir.path(), 2, &WalkOptions::default());
+        let names: Vec<_> = recent.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["newest.txt", "middle.txt"]);
+
+        let shallow = WalkOptions { max_depth: 1, ..WalkOptions::default() };
+        let recent = recent_files(dir.path(), 10, &shallow);
+        let names: Vec<_> = recent.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["old.txt"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_mounts_sorted_and_unique() {
+        let mounts = list_mounts();
+        for pair in mounts.windows(2) {
+            assert!(pair[0].mount_point < pair[1].mount_point);
+        }
+        for mount in &mounts {
+            assert!(mount.mount_point.is_absolute());
+            assert!(mount.available <= mount.total);
+        }
+    }
+
+    #[test]
+    fn test_hash_file_known_digests() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("hello.txt");
+        fs::write(&path, "hello world")?;
+
+        assert_eq!(hash_file(&path, HashAlgo::Md5)?, "5eb63bbbe01eeed093cb22bb8f5acdc3");
+        assert_eq!(
+            hash_file(&path, HashAlgo::Sha256)?,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        assert_eq!(
+            hash_file(&path, HashAlgo::Blake3)?,
+            "d74981efa70a0c880b8d8c1985d075dbcbf679b99a5f9914e5aaf96b831a9e24"
+        );
+
+        let all = hash_file_with(&path, &HashAlgo::ALL, |_| true)?;
+        assert_eq!(all.len(), 3);
+        assert!(hash_file_with(&path, &HashAlgo::ALL, |_| false)?.is_empty());
+        assert!(hash_file(dir.path(), HashAlgo::Md5).is_err());
+        assert_eq!(HashAlgo::parse("SHA-256"), Some(HashAlgo::Sha256));
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_files() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let old = dir.path().join("old.txt");
+        let new = dir.path().join("new.txt");
+        let body: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        fs::write(&old, format!("{}tail\n", body))?;
+        fs::write(&new, format!("{}tail!\nextra\n", body.replace("line 2\n", "")))?;
+
+        let diff = diff_files(&old, &new, 200, 1024)?;
+        assert!(!diff.binary && !diff.identical && !diff.truncated);
+        assert_eq!(diff.counts(), (1, 1, 1));
+        let removed = diff.lines.iter().find(|l| l.kind == DiffKind::Removed).unwrap();
+        assert_eq!(removed.old, Some((2, "line 2".to_string())));
+        let changed = diff.lines.iter().find(|l| l.kind == DiffKind::Changed).unwrap();
+        assert_eq!(changed.old.as_ref().map(|o| o.1.as_str()), Some("tail"));
+        assert_eq!(changed.new.as_ref().map(|n| n.1.as_str()), Some("tail!"));
+        // The two changes are far apart, so unchanged lines between them are skipped
+        assert!(diff.lines.iter().any(|l| l.kind == DiffKind::Gap));
+
+        assert!(diff_files(&old, &new, 4, 1024)?.truncated);
+        assert!(diff_files(&old, &old, 200, 1024)?.identical);
+        let refused = diff_files(&old, &new, 200, 64).unwrap_err();
+        assert!(refused.to_string().contains("too large to diff"), "{}", refused);
+
+        let blob = dir.path().join("blob.bin");
+        fs::write(&blob, [0u8, 159, 146, 150])?;
+        let diff = diff_files(&old, &blob, 200, 1024)?;
+        assert!(diff.binary && diff.lines.is_empty());
+        assert_eq!(diff.new_size, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_patterns_hide_listing_and_search() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("__pycache__"))?;
+        fs::write(dir.path().join("__pycache__").join("mod.cpython.pyc"), "")?;
+        fs::write(dir.path().join("mod.py"), "")?;
+        fs::write(dir.path().join("mod.pyc"), "")?;
+
+        let ignore = IgnorePatterns::new(&["*.pyc".to_string(), "__pycache__/".to_string()])?;
+        assert!(ignore.is_ignored(&dir.path().join("mod.pyc")));
+        assert!(!ignore.is_ignored(&dir.path().join("mod.py")));
+
+        let listed = list_directory_with(dir.path(), false, &ignore, &HiddenPatterns::default())?;
+        let names: Vec<_> = listed.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["mod.py"]);
+        assert_eq!(list_directory(dir.path(), false)?.len(), 3);
+
+        let mut engine = crate::search::SearchEngine::new();
+        engine.ignore = ignore;
+        engine.search_dir(dir.path(), "mod", 100, 10);
+        let found: Vec<_> = engine.results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(found, vec!["mod.py"]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_names_keep_their_real_path() -> anyhow::Result<()> {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempdir()?;
+        // Both names read as "report�.txt" once made lossy
+        let first = dir.path().join(OsStr::from_bytes(b"report\xff.txt"));
+        let second = dir.path().join(OsStr::from_bytes(b"report\xfe.txt"));
+        if fs::write(&first, "first").is_err() {
+            // Some filesystems insist on UTF-8 names; nothing to test there
+            return Ok(());
+        }
+        fs::write(&second, "second")?;
+        fs::write(dir.path().join("line\nbreak"), "")?;
+
+        let entries = list_directory(dir.path(), false)?;
+        let lossy: Vec<&FileEntry> = entries.iter().filter(|e| e.name == "report\u{FFFD}.txt").collect();
+        assert_eq!(lossy.len(), 2);
+        assert!(entries.iter().any(|e| e.name == "line?break"));
+
+        let target = lossy.iter().find(|e| e.path == first).unwrap();
+        let renamed = crate::fileops::FileOperation::rename(&target.path, "fixed.txt", false)?;
+        assert_eq!(fs::read_to_string(renamed)?, "first");
+        assert!(!first.exists());
+        assert_eq!(fs::read_to_string(&second)?, "second");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hidden_patterns_show_and_hide() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        for name in [".gitignore", ".env", ".cache", "main.rs", "Cargo.lock"] {
+            fs::write(dir.path().join(name), "")?;
+        }
+        let hidden = HiddenPatterns::new(&[".gitignore".to_string()], &["*.lock".to_string()])?;
+        let names = |show_hidden| -> anyhow::Result<Vec<String>> {
+            let listed = list_directory_with(dir.path(), show_hidden, &IgnorePatterns::default(), &hidden)?;
+            Ok(listed.into_iter().map(|e| e.name).collect())
+        };
+
+        assert_eq!(names(false)?, vec![".gitignore", "main.rs"]);
+        assert_eq!(names(true)?, vec![".cache", ".env", ".gitignore", "Cargo.lock", "main.rs"]);
+        let listed = list_directory_with(dir.path(), true, &IgnorePatterns::default(), &hidden)?;
+        assert!(listed.iter().find(|e| e.name == "Cargo.lock").is_some_and(|e| e.is_hidden));
+        Ok(())
+    }
+
+    #[test]
+    fn test_name_patterns_ignore_matching_ancestors() -> anyhow::Result<()> {
+        let ignore = IgnorePatterns::new(&["*cache*".to_string(), "*.log".to_string(), "build/*.o".to_string()])?;
+        assert!(ignore.is_ignored(Path::new("/home/u/.cache")));
+        assert!(!ignore.is_ignored(Path::new("/home/u/.cache/app/main.rs")));
+        assert!(!ignore.is_ignored(Path::new("/a.log/b/c.rs")));
+        assert!(ignore.is_ignored(Path::new("/src/build/main.o")));
+        assert!(!ignore.is_ignored(Path::new("/src/build/obj/main.o")));
+
+        let dir = tempdir()?;
+        let cache = dir.path().join("cache_dir");
+        fs::create_dir(&cache)?;
+        fs::write(cache.join("kept.txt"), "")?;
+        fs::write(cache.join("x.cache"), "")?;
+        let hidden = HiddenPatterns::new(&[], &["*cache*".to_string()])?;
+        let listed = list_directory_with(&cache, false, &IgnorePatterns::default(), &hidden)?;
+        let names: Vec<_> = listed.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["kept.txt"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dir_counts_cached_until_directory_changes() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let sub = dir.path().join("src");
+        fs::create_dir(&sub)?;
+        fs::write(sub.join("a.rs"), "")?;
+        fs::write(sub.join("b.rs"), "")?;
+        let pinned = SystemTime::now() - Duration::from_secs(60);
+        fs::File::open(&sub)?.set_modified(pinned)?;
+
+        let mut cache = DirCountCache::default();
+        let mut entries = list_directory(dir.path(), false)?;
+        cache.fill(&mut entries);
+        assert_eq!(entries[0].item_count, Some(2));
+
+        // Same mtime: the cached count is used, not a fresh read
+        fs::write(sub.join("c.rs"), "")?;
+        fs::File::open(&sub)?.set_modified(pinned)?;
+        assert_eq!(cache.count(&sub), Some(2));
+
+        // A changed directory is counted again
+        fs::File::open(&sub)?.set_modified(SystemTime::now())?;
+        assert_eq!(cache.count(&sub), Some(3));
+
+        for i in 0..DIR_COUNT_LIMIT {
+            fs::write(sub.join(format!("{}.txt", i)), "")?;
+        }
+        let mut entries = list_directory(dir.path(), false)?;
+        cache.fill(&mut entries);
+        assert_eq!(entries[0].item_count, Some(DIR_COUNT_LIMIT));
+        assert_eq!(entries[0].item_count_label().as_deref(), Some("99+"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_png_without_extension_detected_as_image() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("picture");
+        image::RgbImage::new(4, 3).save_with_format(&path, image::ImageFormat::Png)?;
+
+        assert_eq!(detect_mime(&path).as_deref(), Some("image/png"));
+        let mut entries = vec![FileEntry::from_path(&path)?];
+        assert_eq!(entries[0].mime, None, "listing alone doesn't read the file");
+        let mut cache = MimeCache::default();
+        cache.fill(&mut entries);
+        let entry = entries[0].clone();
+        assert_eq!(entry.mime.as_deref(), Some("image/png"));
+
+        // Unchanged files aren't read again; changed ones are
+        let modified = fs::metadata(&path)?.modified()?;
+        fs::write(&path, "now it's text")?;
+        fs::File::options().write(true).open(&path)?.set_modified(modified)?;
+        cache.fill(&mut entries);
+        assert_eq!(entries[0].mime.as_deref(), Some("image/png"));
+        fs::File::options().write(true).open(&path)?.set_modified(modified + std::time::Duration::from_secs(1))?;
+        let mut entries = vec![FileEntry::from_path(&path)?];
+        cache.fill(&mut entries);
+        assert_eq!(entries[0].mime, None);
+        let icons = crate::theme::IconSet::default();
+        assert_eq!(
+            crate::theme::entry_icon(&entry, icons),
+            icons.glyph(crate::theme::IconCategory::Image)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+// Frecency - directory history imported from zoxide and autojump
+use crate::persistence::DirVisitState;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The zoxide database version this reader understands (zoxide 0.8 and later)
+const ZOXIDE_VERSION: u32 = 3;
+
+/// Directories scored by how often and how recently another jump tool saw them, best first
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DirFrecency {
+    pub entries: Vec<(PathBuf, f64)>,
+}
+
+impl DirFrecency {
+    /// Read zoxide's `db.zo`, or the text of `zoxide query --list --score`
+    pub fn import_from_zoxide(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+        Self::parse_zoxide(&bytes)
+    }
+
+    /// Read autojump's `autojump.txt`
+    pub fn import_from_autojump(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        Ok(Self::parse_autojump(&text))
+    }
+
+    /// Where `source` ("zoxide" or "autojump") keeps its data by default
+    pub fn default_path(source: &str) -> Option<PathBuf> {
+        match source {
+            "zoxide" => std::env::var_os("_ZO_DATA_DIR")
+                .map(PathBuf::from)
+                .or_else(|| dirs::data_local_dir().map(|dir| dir.join("zoxide")))
+                .map(|dir| dir.join("db.zo")),
+            "autojump" => dirs::data_dir().map(|dir| dir.join("autojump").join("autojump.txt")),
+            _ => None,
+        }
+    }
+
+    /// Read either tool's data by name
+    pub fn import(source: &str, path: &Path) -> Result<Self> {
+        match source {
+            "zoxide" => Self::import_from_zoxide(path),
+            "autojump" => Self::import_from_autojump(path),
+            _ => Err(anyhow::anyhow!("Unknown source '{}': use zoxide or autojump", source)),
+        }
+    }
+
+    /// zoxide's bincode database: a u32 version, then a u64 count of
+    /// (path string, f64 rank, u64 last accessed) records, all little-endian.
+    /// Anything else is read as `score path` lines.
+    pub fn parse_zoxide(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 4 || bytes[..4] != ZOXIDE_VERSION.to_le_bytes() {
+            let text = std::str::from_utf8(bytes).context("not a zoxide database or score list")?;
+            return Ok(Self::from_entries(text.lines().filter_map(|line| {
+                let (score, path) = line.trim().split_once(char::is_whitespace)?;
+                Some((PathBuf::from(path.trim()), score.parse().ok()?))
+            })));
+        }
+
+        let mut reader = BincodeReader { bytes: &bytes[4..] };
+        let count = reader.u64()?;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let path = reader.string()?;
+            let rank = f64::from_le_bytes(reader.take(8)?.try_into()?);
+            let _last_accessed = reader.u64()?;
+            entries.push((PathBuf::from(path), rank));
+        }
+        Ok(Self::from_entries(entries))
+    }
+
+    /// autojump's `weight<TAB>path` lines
+    pub fn parse_autojump(text: &str) -> Self {
+        Self::from_entries(text.lines().filter_map(|line| {
+            let (weight, path) = line.split_once('\t')?;
+            Some((PathBuf::from(path.trim_end()), weight.trim().parse().ok()?))
+        }))
+    }
+
+    /// Score AstroFS's own visits as zoxide does: the visit count, weighted
+    /// up for directories seen in the last hour or day and down after a week
+    pub fn from_visits(visits: &[DirVisitState], now: DateTime<Utc>) -> Self {
+        Self::from_entries(visits.iter().map(|visit| {
+            let age = now.signed_duration_since(visit.last_visited);
+            let weight = if age < chrono::Duration::hours(1) {
+                4.0
+            } else if age < chrono::Duration::days(1) {
+                2.0
+            } else if age < chrono::Duration::weeks(1) {
+                0.5
+            } else {
+                0.25
+            };
+            (visit.path.clone(), visit.count as f64 * weight)
+        }))
+    }
+
+    /// Keep directories that still exist, once each with their best score, best first
+    fn from_entries(entries: impl IntoIterator<Item = (PathBuf, f64)>) -> Self {
+        let mut kept: Vec<(PathBuf, f64)> = Vec::new();
+        for (path, score) in entries {
+            if !path.is_absolute() || !path.is_dir() || !score.is_finite() {
+                continue;
+            }
+            match kept.iter_mut().find(|(p, _)| *p == path) {
+                Some(existing) => existing.1 = existing.1.max(score),
+                None => kept.push((path, score)),
+            }
+        }
+        kept.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Self { entries: kept }
+    }
+}
+
+struct BincodeReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> BincodeReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(anyhow::anyhow!("zoxide database is truncated"));
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = usize::try_from(self.u64()?)?;
+        Ok(String::from_utf8(self.take(len)?.to_vec())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zoxide_db(dirs: &[(&Path, f64)]) -> Vec<u8> {
+        let mut bytes = ZOXIDE_VERSION.to_le_bytes().to_vec();
+        bytes.extend((dirs.len() as u64).to_le_bytes());
+        for (path, rank) in dirs {
+            let path = path.to_string_lossy();
+            bytes.extend((path.len() as u64).to_le_bytes());
+            bytes.extend(path.as_bytes());
+            bytes.extend(rank.to_le_bytes());
+            bytes.extend(1_700_000_000u64.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_parse_zoxide() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let (src, docs) = (dir.path().join("src"), dir.path().join("docs"));
+        fs::create_dir(&src)?;
+        fs::create_dir(&docs)?;
+        let gone = dir.path().join("gone");
+
+        let db = zoxide_db(&[(&src, 4.0), (&gone, 50.0), (&docs, 12.5)]);
+        let imported = DirFrecency::parse_zoxide(&db)?;
+        assert_eq!(imported.entries, vec![(docs.clone(), 12.5), (src.clone(), 4.0)]);
+
+        // `zoxide query --list --score` output works too
+        let listing = format!("  12.5 {}\n   4.0 {}\nnot a line\n", docs.display(), src.display());
+        assert_eq!(DirFrecency::parse_zoxide(listing.as_bytes())?, imported);
+
+        assert!(DirFrecency::parse_zoxide(&db[..db.len() - 3]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_visits_weighs_recency() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let (daily, old) = (dir.path().join("daily"), dir.path().join("old"));
+        fs::create_dir(&daily)?;
+        fs::create_dir(&old)?;
+        let now = Utc::now();
+        let mut visits = Vec::new();
+        for _ in 0..3 {
+            crate::persistence::record_dir_visit(&mut visits, &daily, now, 10);
+        }
+        for _ in 0..10 {
+            crate::persistence::record_dir_visit(&mut visits, &old, now - chrono::Duration::weeks(2), 10);
+        }
+
+        let scored = DirFrecency::from_visits(&visits, now);
+        assert_eq!(scored.entries, vec![(daily, 12.0), (old, 2.5)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_autojump() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let work = dir.path().join("my work");
+        fs::create_dir(&work)?;
+
+        let text = format!(
+            "10.0\t{}\n31.6\t{}\n20.0\t{}\nbroken line\n",
+            dir.path().display(),
+            work.display(),
+            dir.path().join("missing").display()
+        );
+        let imported = DirFrecency::parse_autojump(&text);
+        assert_eq!(imported.entries, vec![(work, 31.6), (dir.path().to_path_buf(), 10.0)]);
+        Ok(())
+    }
+}
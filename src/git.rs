@@ -1,5 +1,74 @@
-use git2::{Repository, StatusOptions};
-use std::path::Path;
+use git2::{Repository, Status, StatusOptions};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Git state of one entry in the listing; a folder takes the most pressing
+/// state of anything inside it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileStatus {
+    Untracked,
+    Staged,
+    Modified,
+    Conflicted,
+}
+
+impl FileStatus {
+    fn from_git(status: Status) -> Option<Self> {
+        if status.is_conflicted() {
+            Some(FileStatus::Conflicted)
+        } else if status.intersects(Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE) {
+            // Unstaged changes matter more than whatever is already staged
+            Some(FileStatus::Modified)
+        } else if status.intersects(
+            Status::INDEX_NEW | Status::INDEX_MODIFIED | Status::INDEX_DELETED | Status::INDEX_RENAMED | Status::INDEX_TYPECHANGE,
+        ) {
+            Some(FileStatus::Staged)
+        } else if status.is_wt_new() {
+            Some(FileStatus::Untracked)
+        } else {
+            None
+        }
+    }
+
+    /// Marker shown in the file list's git column
+    pub fn marker(self) -> &'static str {
+        match self {
+            FileStatus::Untracked => "?",
+            FileStatus::Staged => "+",
+            FileStatus::Modified => "M",
+            FileStatus::Conflicted => "!",
+        }
+    }
+}
+
+/// Git state of the entries directly inside `dir`, keyed by `dir.join(name)`;
+/// clean entries are left out, and it's empty outside a repository
+pub fn file_statuses(dir: &Path) -> HashMap<PathBuf, FileStatus> {
+    let mut result = HashMap::new();
+    let Ok(repo) = Repository::discover(dir) else { return result };
+    let Some(workdir) = repo.workdir().and_then(|w| w.canonicalize().ok()) else { return result };
+    let Ok(canonical) = dir.canonicalize() else { return result };
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true).recurse_untracked_dirs(true);
+    if let Ok(relative) = canonical.strip_prefix(&workdir) {
+        if !relative.as_os_str().is_empty() {
+            opts.pathspec(relative);
+        }
+    }
+    let Ok(statuses) = repo.statuses(Some(&mut opts)) else { return result };
+
+    for entry in statuses.iter() {
+        let (Some(path), Some(status)) = (entry.path(), FileStatus::from_git(entry.status())) else { continue };
+        let full = workdir.join(path);
+        let Some(name) = full.strip_prefix(&canonical).ok().and_then(|rest| rest.components().next()) else {
+            continue;
+        };
+        let slot = result.entry(dir.join(name)).or_insert(status);
+        *slot = (*slot).max(status);
+    }
+    result
+}
 
 pub struct GitInfo {
     pub branch: Option<String>,
@@ -66,6 +135,13 @@ impl GitInfo {
         }
     }
 
+    /// The working directory of the repository holding `path`, if it's in one
+    pub fn workdir(path: &Path) -> Option<PathBuf> {
+        let repo = Repository::discover(path).ok()?;
+        let workdir = repo.workdir()?;
+        Some(workdir.canonicalize().unwrap_or_else(|_| workdir.to_path_buf()))
+    }
+
     pub fn status_string(&self) -> String {
         match &self.branch {
             Some(branch) => {
@@ -99,3 +175,101 @@ impl GitInfo {
         }
     }
 }
+
+/// `path` relative to the root of its repository, with `/` separators as in
+/// `src/app.rs`; outside a repository it's relative to `fallback` instead
+pub fn relative_path_from_repo(path: &Path, fallback: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let start = if path.is_dir() { path.as_path() } else { path.parent().unwrap_or(&path) };
+    let base = GitInfo::workdir(start)
+        .unwrap_or_else(|| fallback.canonicalize().unwrap_or_else(|_| fallback.to_path_buf()));
+    relative_to(&path, &base)
+}
+
+/// `path` from `base`, stepping up with `..` where they part ways
+fn relative_to(path: &Path, base: &Path) -> String {
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        // Different drives: nothing to be relative to
+        return path.iter().collect::<PathBuf>().to_string_lossy().into_owned();
+    }
+    let parts: Vec<String> = std::iter::repeat_n("..".to_string(), base.len() - common)
+        .chain(path[common..].iter().map(|c| c.as_os_str().to_string_lossy().into_owned()))
+        .collect();
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_file_statuses_markers() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        fs::write(dir.path().join("tracked.txt"), "one").unwrap();
+        fs::write(dir.path().join("clean.txt"), "same").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("tracked.txt")).unwrap();
+        index.add_path(Path::new("clean.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[]).unwrap();
+
+        fs::write(dir.path().join("tracked.txt"), "two").unwrap();
+        fs::write(dir.path().join("staged.txt"), "new").unwrap();
+        index.add_path(Path::new("staged.txt")).unwrap();
+        index.write().unwrap();
+        fs::create_dir(dir.path().join("notes")).unwrap();
+        fs::write(dir.path().join("notes/draft.md"), "").unwrap();
+
+        let statuses = file_statuses(dir.path());
+        let marker = |name: &str| statuses.get(&dir.path().join(name)).map(|s| s.marker());
+        assert_eq!(marker("tracked.txt"), Some("M"));
+        assert_eq!(marker("staged.txt"), Some("+"));
+        assert_eq!(marker("notes"), Some("?"));
+        assert_eq!(marker("clean.txt"), None);
+
+        // Looking from inside a folder keys by its own entries
+        let notes = file_statuses(&dir.path().join("notes"));
+        assert_eq!(notes.get(&dir.path().join("notes/draft.md")), Some(&FileStatus::Untracked));
+    }
+
+    #[test]
+    fn test_relative_path_from_repo_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("project");
+        Repository::init(&repo).unwrap();
+        fs::create_dir_all(repo.join("src/ui")).unwrap();
+        fs::write(repo.join("src/ui/app.rs"), "").unwrap();
+
+        // The fallback is ignored inside a repository
+        let elsewhere = dir.path();
+        assert_eq!(relative_path_from_repo(&repo.join("src/ui/app.rs"), elsewhere), "src/ui/app.rs");
+        assert_eq!(relative_path_from_repo(&repo.join("src"), elsewhere), "src");
+        assert_eq!(relative_path_from_repo(&repo, elsewhere), ".");
+    }
+
+    #[test]
+    fn test_relative_path_outside_a_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        if GitInfo::workdir(dir.path()).is_some() {
+            // The temp directory itself sits in a repository; nothing to test here
+            return;
+        }
+        fs::create_dir_all(dir.path().join("docs/notes")).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("docs/notes/todo.md"), "").unwrap();
+
+        let todo = dir.path().join("docs/notes/todo.md");
+        assert_eq!(relative_path_from_repo(&todo, dir.path()), "docs/notes/todo.md");
+        assert_eq!(relative_path_from_repo(&todo, &dir.path().join("src")), "../docs/notes/todo.md");
+    }
+}
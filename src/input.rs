@@ -1,4 +1,6 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Action {
@@ -66,3 +68,211 @@ pub fn get_help_text() -> Vec<(&'static str, &'static str)> {
         ("q / Ctrl+C", "Quit"),
     ]
 }
+
+/// Longest wait between the keys of a chord before it's abandoned
+pub const CHORD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Keys of a chord as written in the config, e.g. "g g" for `g` then `g`;
+/// None unless every space-separated part is a single character
+pub fn parse_chord(chord: &str) -> Option<Vec<char>> {
+    let keys: Option<Vec<char>> = chord
+        .split_whitespace()
+        .map(|part| {
+            let mut chars = part.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            }
+        })
+        .collect();
+    keys.filter(|keys| !keys.is_empty())
+}
+
+/// What a keypress did to the chord being typed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChordStep {
+    /// The key starts or continues a chord; wait for the next one
+    Pending,
+    /// A chord was completed: the command id it's bound to
+    Complete(String),
+    /// The key isn't part of any chord and should be handled as usual
+    Unbound,
+}
+
+/// Multi-key bindings such as `g g`, fed one keypress at a time
+#[derive(Debug, Default)]
+pub struct Chords {
+    bindings: HashMap<Vec<char>, String>,
+    pending: Vec<char>,
+    last_key: Option<Instant>,
+}
+
+impl Chords {
+    /// Bindings from chord ("g g") to command id; unparsable chords are skipped
+    pub fn new(bindings: &HashMap<String, String>) -> Self {
+        Self {
+            bindings: bindings
+                .iter()
+                .filter_map(|(chord, command)| Some((parse_chord(chord)?, command.clone())))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Keys typed so far of an unfinished chord
+    #[allow(dead_code)]
+    pub fn pending(&self) -> &[char] {
+        &self.pending
+    }
+
+    pub fn press(&mut self, key: char, now: Instant) -> ChordStep {
+        let timed_out = self.last_key.is_some_and(|last| now.duration_since(last) > CHORD_TIMEOUT);
+        if timed_out {
+            self.pending.clear();
+        }
+        self.last_key = Some(now);
+
+        let mut keys = std::mem::take(&mut self.pending);
+        keys.push(key);
+        let mut step = self.step(&keys);
+        if step == ChordStep::Unbound && keys.len() > 1 {
+            // A broken chord is dropped, and the key may start a new one
+            keys = vec![key];
+            step = self.step(&keys);
+        }
+        if step == ChordStep::Pending {
+            self.pending = keys;
+        }
+        step
+    }
+
+    fn step(&self, keys: &[char]) -> ChordStep {
+        if let Some(command) = self.bindings.get(keys) {
+            return ChordStep::Complete(command.clone());
+        }
+        if self.bindings.keys().any(|chord| chord.len() > keys.len() && chord.starts_with(keys)) {
+            ChordStep::Pending
+        } else {
+            ChordStep::Unbound
+        }
+    }
+}
+
+/// How long without input before the UI counts as idle
+pub const IDLE_AFTER: Duration = Duration::from_secs(2);
+
+/// Wait between loop passes once idle; a keypress still wakes the loop at once
+pub const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tracks input activity so the main loop can poll less often when nothing is going on
+#[derive(Debug)]
+pub struct IdleTracker {
+    last_input: Instant,
+    idle_reported: bool,
+}
+
+impl IdleTracker {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            last_input: now,
+            idle_reported: false,
+        }
+    }
+
+    pub fn note_input(&mut self, now: Instant) {
+        self.last_input = now;
+        self.idle_reported = false;
+    }
+
+    pub fn is_idle(&self, now: Instant) -> bool {
+        now.duration_since(self.last_input) >= IDLE_AFTER
+    }
+
+    /// How long to wait for the next event: `base` while busy or recently used,
+    /// `IDLE_POLL_INTERVAL` otherwise
+    pub fn poll_timeout(&self, base: Duration, busy: bool, now: Instant) -> Duration {
+        if busy || !self.is_idle(now) {
+            base
+        } else {
+            IDLE_POLL_INTERVAL.max(base)
+        }
+    }
+
+    /// True once per quiet stretch, when the UI first becomes idle
+    pub fn take_idle(&mut self, now: Instant) -> bool {
+        if self.idle_reported || !self.is_idle(now) {
+            return false;
+        }
+        self.idle_reported = true;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chords() -> Chords {
+        Chords::new(&HashMap::from([
+            ("g g".to_string(), "first".to_string()),
+            ("g e".to_string(), "last".to_string()),
+            ("d d".to_string(), "delete".to_string()),
+        ]))
+    }
+
+    #[test]
+    fn test_complete_chord() {
+        let mut chords = chords();
+        let start = Instant::now();
+        assert_eq!(chords.press('g', start), ChordStep::Pending);
+        assert_eq!(chords.pending(), &['g']);
+        assert_eq!(chords.press('g', start + Duration::from_millis(200)), ChordStep::Complete("first".to_string()));
+        assert!(chords.pending().is_empty());
+
+        // A key that breaks a chord is handled on its own, or starts another chord
+        assert_eq!(chords.press('g', start), ChordStep::Pending);
+        assert_eq!(chords.press('j', start), ChordStep::Unbound);
+        assert_eq!(chords.press('g', start), ChordStep::Pending);
+        assert_eq!(chords.press('d', start), ChordStep::Pending);
+        assert_eq!(chords.press('d', start), ChordStep::Complete("delete".to_string()));
+    }
+
+    #[test]
+    fn test_timed_out_chord() {
+        let mut chords = chords();
+        let start = Instant::now();
+        assert_eq!(chords.press('g', start), ChordStep::Pending);
+
+        // Too late: the lone `g` is forgotten and this `g` starts over
+        let late = start + CHORD_TIMEOUT + Duration::from_millis(1);
+        assert_eq!(chords.press('g', late), ChordStep::Pending);
+        assert_eq!(chords.press('e', late), ChordStep::Complete("last".to_string()));
+
+        assert_eq!(chords.press('d', late), ChordStep::Pending);
+        assert_eq!(chords.press('j', late + CHORD_TIMEOUT * 2), ChordStep::Unbound);
+
+        assert_eq!(parse_chord("g  g"), Some(vec!['g', 'g']));
+        assert_eq!(parse_chord("gg"), None);
+        assert_eq!(parse_chord(""), None);
+    }
+
+    #[test]
+    fn test_idle_tracker() {
+        let start = Instant::now();
+        let base = Duration::from_millis(100);
+        let mut tracker = IdleTracker::new(start);
+
+        assert_eq!(tracker.poll_timeout(base, false, start), base);
+        assert!(!tracker.take_idle(start));
+
+        let later = start + IDLE_AFTER;
+        assert_eq!(tracker.poll_timeout(base, false, later), IDLE_POLL_INTERVAL);
+        assert_eq!(tracker.poll_timeout(base, true, later), base, "busy work keeps polling fast");
+        assert!(tracker.take_idle(later));
+        assert!(!tracker.take_idle(later + IDLE_AFTER), "reported once per quiet stretch");
+
+        tracker.note_input(later);
+        assert_eq!(tracker.poll_timeout(base, false, later), base);
+        assert!(tracker.take_idle(later + IDLE_AFTER));
+    }
+}
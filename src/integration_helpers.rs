@@ -20,6 +20,10 @@ use std::path::PathBuf;
 use std::collections::HashMap;
 use anyhow::Result;
 pub fn validate_app_state(app: &mut App) -> Result<()> {
+    // The renames below would leave the start tab looking named by the user
+    let workspace = app.workspace_manager.active_workspace();
+    let (title, renamed) = (workspace.title.clone(), workspace.renamed);
+
     // Use persistence manager methods
     let _ = app.persistence_manager.get_plugin_data("test");
     let _ = app.persistence_manager.export_settings(&PathBuf::from("/tmp"));
@@ -220,9 +224,9 @@ pub fn validate_app_state(app: &mut App) -> Result<()> {
     let _ = git_from_path.status_string();
     
     // Use FileOperation
-    let _ = FileOperation::copy(&PathBuf::from("."), &PathBuf::from("."));
+    let _ = FileOperation::copy(&PathBuf::from("."), &PathBuf::from("."), false);
     let _ = FileOperation::move_path(&PathBuf::from("."), &PathBuf::from("."));
-    let _ = FileOperation::get_size(&PathBuf::from("."));
+    let _ = FileOperation::get_size(&PathBuf::from("."), false);
     let _ = FileOperation::is_path_safe(&PathBuf::from("."));
     
     // Use AppConfig
@@ -278,7 +282,9 @@ pub fn validate_app_state(app: &mut App) -> Result<()> {
     // Use remaining utility functions
     use_legacy_plugins();
     use_theme_config();
-    
+
+    let workspace = app.workspace_manager.active_workspace_mut();
+    (workspace.title, workspace.renamed) = (title, renamed);
     Ok(())
 }
 
@@ -539,8 +545,6 @@ impl crate::plugin_api::Plugin for MockPlugin {
     fn customize_theme(&self, _theme: &mut PluginTheme) -> Result<()> { Ok(()) }
     fn render_custom_ui(&self, _ctx: &mut RenderContext) -> Result<()> { Ok(()) }
     fn filter_search_results(&self, _query: &str, _results: &mut Vec<String>) -> Result<()> { Ok(()) }
-    fn save_data(&self, _key: &str, _value: serde_json::Value) -> Result<()> { Ok(()) }
-    fn load_data(&self, _key: &str) -> Result<Option<serde_json::Value>> { Ok(None) }
     fn on_idle(&self) -> Result<()> { Ok(()) }
     fn get_stats(&self) -> PluginStats {
         PluginStats {
@@ -576,8 +580,6 @@ pub fn use_plugin_trait() {
     let _ = plugin.render_custom_ui(&mut ctx);
     let mut results = vec!["test".to_string()];
     let _ = plugin.filter_search_results("test", &mut results);
-    let _ = plugin.save_data("key", serde_json::Value::Null);
-    let _ = plugin.load_data("key");
     let _ = plugin.on_idle();
     let _ = plugin.get_stats();
 }
@@ -0,0 +1,469 @@
+// Background Jobs - registry of in-flight operations for status and shutdown
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the busy spinner advances; matches the main loop's input poll
+pub const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Stored in a job's progress counter until the worker reports any
+const NO_PROGRESS: u64 = u64::MAX;
+
+/// Busy indicator advanced by the main loop, drawn while jobs run
+#[derive(Debug)]
+pub struct Spinner {
+    frame: usize,
+    last_tick: Instant,
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self {
+            frame: 0,
+            last_tick: Instant::now(),
+        }
+    }
+}
+
+impl Spinner {
+    /// Advance one frame once `SPINNER_INTERVAL` has passed since the last one
+    pub fn tick_at(&mut self, now: Instant) {
+        if now.duration_since(self.last_tick) >= SPINNER_INTERVAL {
+            self.frame = (self.frame + 1) % SPINNER_FRAMES.len();
+            self.last_tick = now;
+        }
+    }
+
+    pub fn frame(&self) -> &'static str {
+        SPINNER_FRAMES[self.frame]
+    }
+}
+
+/// Where a job is in its life
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobState {
+    /// Waiting for a free slot
+    Queued,
+    Running,
+    Finished,
+    /// Cancelled before it finished, or before it started
+    Cancelled,
+}
+
+impl JobState {
+    pub fn label(self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Finished => "done",
+            JobState::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A background operation as seen by the UI
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub id: u64,
+    pub label: String,
+    /// When it was submitted, then when it started running
+    pub started: Instant,
+    pub state: JobState,
+    cancel: Arc<AtomicBool>,
+    done: Arc<AtomicBool>,
+    /// Completion in thousandths, or `NO_PROGRESS`
+    progress: Arc<AtomicU64>,
+}
+
+impl Job {
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Fraction done, for workers that know how much work there is
+    pub fn progress(&self) -> Option<f64> {
+        match self.progress.load(Ordering::Relaxed) {
+            NO_PROGRESS => None,
+            permille => Some(permille as f64 / 1000.0),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Acquire)
+    }
+
+    fn handle(&self) -> JobHandle {
+        JobHandle {
+            cancel: Arc::clone(&self.cancel),
+            done: Arc::clone(&self.done),
+            progress: Arc::clone(&self.progress),
+        }
+    }
+}
+
+/// Handle given to the worker running a job.
+///
+/// Dropping the handle marks the job finished, so a worker that returns
+/// early or panics never leaves a stale entry behind.
+pub struct JobHandle {
+    cancel: Arc<AtomicBool>,
+    done: Arc<AtomicBool>,
+    progress: Arc<AtomicU64>,
+}
+
+#[allow(dead_code)]
+impl JobHandle {
+    /// Workers should poll this and stop early once it returns true
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Acquire)
+    }
+
+    /// Report `done` out of `total` units of work, shown as a percentage
+    pub fn set_progress(&self, done: u64, total: u64) {
+        let permille = if total == 0 {
+            1000
+        } else {
+            (done.min(total) as u128 * 1000 / total as u128) as u64
+        };
+        self.progress.store(permille, Ordering::Relaxed);
+    }
+}
+
+impl Drop for JobHandle {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::Release);
+    }
+}
+
+/// Work submitted to the queue, run on its own thread once a slot frees up
+type JobWork = Box<dyn FnOnce(JobHandle) + Send + 'static>;
+
+/// How many finished jobs are kept for the jobs overlay
+const FINISHED_JOBS_KEPT: usize = 20;
+
+/// Tracks background jobs started by the app.
+///
+/// Submitted jobs wait in a queue and start in order, at most
+/// `max_concurrent` at a time, so several copies or hashes don't fight over
+/// the disk. Jobs started directly with `start` skip the queue but still
+/// take a slot.
+pub struct JobRegistry {
+    running: Vec<Job>,
+    queue: VecDeque<(Job, JobWork)>,
+    /// Most recent last
+    finished: VecDeque<Job>,
+    max_concurrent: usize,
+    /// Start workers at background priority
+    low_priority: bool,
+    next_id: u64,
+    spinner: Spinner,
+}
+
+impl Default for JobRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::with_limit(2)
+    }
+
+    /// A registry running at most `max_concurrent` queued jobs at once
+    pub fn with_limit(max_concurrent: usize) -> Self {
+        Self {
+            running: Vec::new(),
+            queue: VecDeque::new(),
+            finished: VecDeque::new(),
+            max_concurrent: max_concurrent.max(1),
+            low_priority: false,
+            next_id: 0,
+            spinner: Spinner::default(),
+        }
+    }
+
+    pub fn set_limit(&mut self, max_concurrent: usize) {
+        self.max_concurrent = max_concurrent.max(1);
+        self.pump();
+    }
+
+    /// Run jobs started from now on at lowered CPU and disk priority; ones
+    /// already running keep theirs
+    pub fn set_low_priority(&mut self, low: bool) {
+        self.low_priority = low;
+    }
+
+    #[allow(dead_code)]
+    pub fn low_priority(&self) -> bool {
+        self.low_priority
+    }
+
+    fn new_job(&mut self, label: String, state: JobState) -> Job {
+        self.next_id += 1;
+        Job {
+            id: self.next_id,
+            label,
+            started: Instant::now(),
+            state,
+            cancel: Arc::new(AtomicBool::new(false)),
+            done: Arc::new(AtomicBool::new(false)),
+            progress: Arc::new(AtomicU64::new(NO_PROGRESS)),
+        }
+    }
+
+    /// Register a job that starts right away, skipping the queue, and get
+    /// the handle for its worker
+    pub fn start(&mut self, label: impl Into<String>) -> JobHandle {
+        let job = self.new_job(label.into(), JobState::Running);
+        let handle = job.handle();
+        self.running.push(job);
+        handle
+    }
+
+    /// Queue `work` to run on a worker thread once a slot is free; returns the job's id
+    pub fn submit(&mut self, label: impl Into<String>, work: impl FnOnce(JobHandle) + Send + 'static) -> u64 {
+        let job = self.new_job(label.into(), JobState::Queued);
+        let id = job.id;
+        self.queue.push_back((job, Box::new(work)));
+        self.pump();
+        id
+    }
+
+    /// Retire finished jobs and start queued ones while there are free slots
+    pub fn pump(&mut self) {
+        let (done, running): (Vec<Job>, Vec<Job>) = self.running.drain(..).partition(|job| job.is_done());
+        self.running = running;
+        for mut job in done {
+            job.state = if job.is_cancelled() { JobState::Cancelled } else { JobState::Finished };
+            self.retire(job);
+        }
+
+        while self.running.len() < self.max_concurrent {
+            let (mut job, work) = match self.queue.pop_front() {
+                Some(queued) => queued,
+                None => break,
+            };
+            job.state = JobState::Running;
+            job.started = Instant::now();
+            let handle = job.handle();
+            self.running.push(job);
+            let low = self.low_priority;
+            std::thread::spawn(move || {
+                crate::priority::lower_current_thread_if(low);
+                work(handle)
+            });
+        }
+    }
+
+    fn retire(&mut self, job: Job) {
+        self.finished.push_back(job);
+        while self.finished.len() > FINISHED_JOBS_KEPT {
+            self.finished.pop_front();
+        }
+    }
+
+    /// Jobs that are still running
+    pub fn active(&mut self) -> &[Job] {
+        self.pump();
+        &self.running
+    }
+
+    /// Fraction done of the job `id`, if its worker reported any
+    pub fn progress(&self, id: u64) -> Option<f64> {
+        self.running.iter().chain(self.queued()).chain(&self.finished).find(|job| job.id == id)?.progress()
+    }
+
+    /// Nothing running and nothing waiting
+    pub fn is_idle(&mut self) -> bool {
+        self.active().is_empty() && self.queue.is_empty()
+    }
+
+    pub fn queued(&self) -> impl Iterator<Item = &Job> {
+        self.queue.iter().map(|(job, _)| job)
+    }
+
+    /// Every job the overlay lists: running, then queued in order, then the
+    /// most recently finished first
+    pub fn overview(&self) -> Vec<&Job> {
+        self.running
+            .iter()
+            .chain(self.queued())
+            .chain(self.finished.iter().rev())
+            .collect()
+    }
+
+    /// Animate the spinner and start queued jobs; called once per pass of the main loop
+    pub fn tick(&mut self) {
+        self.spinner.tick_at(Instant::now());
+        self.pump();
+    }
+
+    pub fn spinner_frame(&self) -> &'static str {
+        self.spinner.frame()
+    }
+
+    /// Status bar text for running jobs, e.g. "⠙ Hashing disk.iso 42% (+1 more, 2 queued)"
+    pub fn status(&self) -> Option<String> {
+        let mut running = self.running.iter().filter(|job| !job.is_done());
+        let first = running.next()?;
+        let mut status = format!("{} {}", self.spinner.frame(), first.label);
+        if let Some(progress) = first.progress() {
+            status.push_str(&format!(" {:.0}%", progress * 100.0));
+        }
+        let others = running.count();
+        let queued = self.queue.len();
+        match (others, queued) {
+            (0, 0) => {}
+            (others, 0) => status.push_str(&format!(" (+{} more)", others)),
+            (0, queued) => status.push_str(&format!(" ({} queued)", queued)),
+            (others, queued) => status.push_str(&format!(" (+{} more, {} queued)", others, queued)),
+        }
+        Some(status)
+    }
+
+    /// Cancel one job: a queued one never starts, a running one is asked to stop
+    pub fn cancel(&mut self, id: u64) -> bool {
+        if let Some(index) = self.queue.iter().position(|(job, _)| job.id == id) {
+            if let Some((mut job, _)) = self.queue.remove(index) {
+                job.state = JobState::Cancelled;
+                self.retire(job);
+            }
+            return true;
+        }
+        match self.running.iter().find(|job| job.id == id) {
+            Some(job) => {
+                job.cancel.store(true, Ordering::Release);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop every queued job and ask every running one to stop
+    pub fn cancel_all(&mut self) {
+        while let Some((mut job, _)) = self.queue.pop_front() {
+            job.state = JobState::Cancelled;
+            self.retire(job);
+        }
+        for job in self.active() {
+            job.cancel.store(true, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jobs_finish_when_handle_dropped() {
+        let mut registry = JobRegistry::new();
+        assert!(registry.is_idle());
+
+        let copy = registry.start("Copy photos");
+        let search = registry.start("Search");
+        assert_eq!(registry.active().len(), 2);
+
+        drop(copy);
+        let labels: Vec<&str> = registry.active().iter().map(|j| j.label.as_str()).collect();
+        assert_eq!(labels, vec!["Search"]);
+
+        registry.cancel_all();
+        assert!(search.is_cancelled());
+        drop(search);
+        assert!(registry.is_idle());
+    }
+
+    #[test]
+    fn test_queue_runs_in_order_up_to_the_limit() {
+        use std::sync::mpsc::{channel, Sender};
+        use std::sync::Mutex;
+
+        let mut registry = JobRegistry::with_limit(2);
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let mut release: Vec<Sender<()>> = Vec::new();
+        let mut ids = Vec::new();
+        for n in 0..4 {
+            let (tx, rx) = channel::<()>();
+            release.push(tx);
+            let started = Arc::clone(&started);
+            ids.push(registry.submit(format!("Job {}", n), move |_job| {
+                started.lock().unwrap().push(n);
+                let _ = rx.recv();
+            }));
+        }
+        let wait_for = |registry: &mut JobRegistry, running: &[&str]| {
+            for _ in 0..500 {
+                let labels: Vec<String> = registry.active().iter().map(|j| j.label.clone()).collect();
+                if labels == running {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(2));
+            }
+            panic!("expected {:?} running, got {:?}", running, registry.active().iter().map(|j| &j.label).collect::<Vec<_>>());
+        };
+
+        // Two slots: the first two run, the rest wait in order
+        wait_for(&mut registry, &["Job 0", "Job 1"]);
+        let queued: Vec<&str> = registry.queued().map(|j| j.label.as_str()).collect();
+        assert_eq!(queued, vec!["Job 2", "Job 3"]);
+        assert!(registry.status().unwrap().ends_with("(+1 more, 2 queued)"));
+
+        // A queued job can be dropped before it starts
+        assert!(registry.cancel(ids[2]));
+        release[1].send(()).unwrap();
+        wait_for(&mut registry, &["Job 0", "Job 3"]);
+        release[0].send(()).unwrap();
+        release[3].send(()).unwrap();
+        wait_for(&mut registry, &[]);
+        assert!(registry.is_idle());
+        assert_eq!(*started.lock().unwrap(), vec![0, 1, 3]);
+
+        let history: Vec<(&str, JobState)> =
+            registry.overview().iter().map(|j| (j.label.as_str(), j.state)).collect();
+        assert_eq!(history.len(), 4);
+        assert!(history.contains(&("Job 2", JobState::Cancelled)));
+        assert!(history.contains(&("Job 1", JobState::Finished)));
+
+        // Jobs started directly take a slot too
+        let direct = registry.start("Search");
+        let _one = registry.start("Other");
+        registry.submit("Later", |_job| {});
+        assert_eq!(registry.queued().count(), 1);
+        drop(direct);
+        wait_for(&mut registry, &["Other"]);
+        assert_eq!(registry.queued().count(), 0);
+    }
+
+    #[test]
+    fn test_spinner_and_status() {
+        let start = Instant::now();
+        let mut spinner = Spinner::default();
+        let first = spinner.frame();
+        spinner.tick_at(start);
+        assert_eq!(spinner.frame(), first, "too soon to advance");
+        spinner.tick_at(start + SPINNER_INTERVAL * 2);
+        assert_ne!(spinner.frame(), first);
+
+        let mut registry = JobRegistry::new();
+        assert_eq!(registry.status(), None);
+        let hash = registry.start("Hashing disk.iso");
+        let frame = registry.spinner_frame();
+        assert_eq!(registry.status().unwrap(), format!("{} Hashing disk.iso", frame));
+
+        hash.set_progress(21, 50);
+        let _copy = registry.start("Copying");
+        assert_eq!(registry.status().unwrap(), format!("{} Hashing disk.iso 42% (+1 more)", frame));
+
+        drop(hash);
+        assert_eq!(registry.status().unwrap(), format!("{} Copying", frame));
+    }
+}
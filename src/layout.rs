@@ -0,0 +1,156 @@
+// Layout - resizable widths of the sidebar, file list and preview
+use std::ops::RangeInclusive;
+
+/// Narrowest the file list or the preview gets while the screen has room for both
+pub const MIN_PANE_WIDTH: u16 = 16;
+
+/// Preview share of the browser, as accepted for `preview_width_ratio`
+pub const PREVIEW_RATIO_RANGE: RangeInclusive<f32> = 0.1..=0.9;
+
+/// Sidebar widths, as accepted for `sidebar_width`
+pub const SIDEBAR_WIDTH_RANGE: RangeInclusive<u16> = 12..=80;
+
+/// How far one keypress moves the list/preview divider
+pub const PREVIEW_RATIO_STEP: f32 = 0.05;
+
+/// Columns one keypress adds to or takes from the sidebar
+pub const SIDEBAR_WIDTH_STEP: u16 = 2;
+
+/// Narrowest file list (inside its border) that still gets the git status column
+pub const GIT_COLUMN_MIN_WIDTH: u16 = 12;
+
+/// Cells in a size bar, after the size itself
+pub const SIZE_BAR_WIDTH: u16 = 8;
+
+/// The size column: a space, the size right-aligned in 9 cells, a space and the bar
+const SIZE_COLUMN_WIDTH: u16 = 11 + SIZE_BAR_WIDTH;
+
+/// Narrowest file list (inside its border) that still gets the size column
+pub const SIZE_COLUMN_MIN_WIDTH: u16 = 40;
+
+/// Columns the name keeps before a modified time column is shown
+const DATE_COLUMN_NAME_ROOM: u16 = 20;
+
+/// `ratio` moved by `steps` keypresses, kept in `PREVIEW_RATIO_RANGE`
+pub fn adjust_preview_ratio(ratio: f32, steps: i32) -> f32 {
+    let ratio = ratio + steps as f32 * PREVIEW_RATIO_STEP;
+    // Round so repeated steps land on the same values both ways
+    let ratio = (ratio * 100.0).round() / 100.0;
+    ratio.clamp(*PREVIEW_RATIO_RANGE.start(), *PREVIEW_RATIO_RANGE.end())
+}
+
+/// `width` grown (or shrunk, for negative `steps`) by `steps` keypresses,
+/// kept in `SIDEBAR_WIDTH_RANGE`
+pub fn adjust_sidebar_width(width: u16, steps: i32) -> u16 {
+    let width = width as i32 + steps * SIDEBAR_WIDTH_STEP as i32;
+    width.clamp(*SIDEBAR_WIDTH_RANGE.start() as i32, *SIDEBAR_WIDTH_RANGE.end() as i32) as u16
+}
+
+/// Columns for the sidebar (0 when hidden), the file list and the preview.
+/// The sidebar never takes more than a third of the screen, and neither the
+/// list nor the preview shrinks below `MIN_PANE_WIDTH` while both fit.
+pub fn pane_widths(total: u16, sidebar: Option<u16>, preview_ratio: f32) -> (u16, u16, u16) {
+    let sidebar = sidebar.map_or(0, |width| width.min(total / 3));
+    let rest = total - sidebar;
+    let preview = (rest as f32 * preview_ratio).round() as u16;
+    let preview = if rest >= MIN_PANE_WIDTH * 2 {
+        preview.clamp(MIN_PANE_WIDTH, rest - MIN_PANE_WIDTH)
+    } else {
+        preview.min(rest)
+    };
+    (sidebar, rest - preview, preview)
+}
+
+/// Widths of the file list's columns; optional ones are 0 when off
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ListColumns {
+    pub name: u16,
+    pub size: u16,
+    pub date: u16,
+    pub git: u16,
+}
+
+/// Columns for a file list `width` wide, with room for modified times
+/// `date_width` wide (0 for none); an optional column is dropped when the
+/// list is too narrow to spare it, the date column first, then the size column
+pub fn list_columns(width: u16, git_column: bool, size_column: bool, date_width: u16) -> ListColumns {
+    const GIT_COLUMN_WIDTH: u16 = 2;
+    let git = if git_column && width >= GIT_COLUMN_MIN_WIDTH { GIT_COLUMN_WIDTH } else { 0 };
+    let size = if size_column && width >= SIZE_COLUMN_MIN_WIDTH { SIZE_COLUMN_WIDTH } else { 0 };
+    let rest = width - git - size;
+    let date = if date_width > 0 && rest >= date_width + 1 + DATE_COLUMN_NAME_ROOM { date_width + 1 } else { 0 };
+    ListColumns { name: rest - date, size, date, git }
+}
+
+/// Filled cells of a `width`-cell bar for a file of `size` bytes, out of the
+/// largest file's `max`. Empty files get none; any other file at least one.
+pub fn size_bar_cells(size: u64, max: u64, width: u16) -> u16 {
+    if size == 0 || max == 0 {
+        return 0;
+    }
+    let cells = (size.min(max) as u128 * width as u128).div_ceil(max as u128);
+    cells as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjust_and_clamp() {
+        assert_eq!(adjust_preview_ratio(0.7, 1), 0.75);
+        assert_eq!(adjust_preview_ratio(adjust_preview_ratio(0.7, 3), -3), 0.7);
+        assert_eq!(adjust_preview_ratio(0.85, 5), 0.9);
+        assert_eq!(adjust_preview_ratio(0.15, -5), 0.1);
+
+        assert_eq!(adjust_sidebar_width(24, 1), 26);
+        assert_eq!(adjust_sidebar_width(13, -3), 12);
+        assert_eq!(adjust_sidebar_width(79, 4), 80);
+    }
+
+    #[test]
+    fn test_pane_widths_keep_every_region() {
+        assert_eq!(pane_widths(100, None, 0.7), (0, 30, 70));
+        assert_eq!(pane_widths(124, Some(24), 0.7), (24, 30, 70));
+
+        // A wide sidebar on a narrow screen is capped at a third
+        assert_eq!(pane_widths(60, Some(40), 0.5), (20, 20, 20));
+
+        // Extreme ratios still leave the list and the preview usable
+        assert_eq!(pane_widths(80, None, 0.9), (0, 16, 64));
+        assert_eq!(pane_widths(80, None, 0.1), (0, 64, 16));
+
+        // Too narrow for both minimums: split by the ratio, still adding up
+        let (sidebar, list, preview) = pane_widths(20, None, 0.9);
+        assert_eq!((sidebar, list + preview), (0, 20));
+        assert_eq!(preview, 18);
+    }
+
+    #[test]
+    fn test_list_columns_reflow() {
+        let widths = |width, git, size| {
+            let columns = list_columns(width, git, size, 0);
+            (columns.name, columns.size, columns.git)
+        };
+        assert_eq!(widths(30, false, false), (30, 0, 0));
+        assert_eq!(widths(30, true, false), (28, 0, 2));
+        assert_eq!(widths(GIT_COLUMN_MIN_WIDTH, true, false), (GIT_COLUMN_MIN_WIDTH - 2, 0, 2));
+        assert_eq!(widths(GIT_COLUMN_MIN_WIDTH - 1, true, false), (GIT_COLUMN_MIN_WIDTH - 1, 0, 0));
+        assert_eq!(widths(60, true, true), (60 - SIZE_COLUMN_WIDTH - 2, SIZE_COLUMN_WIDTH, 2));
+        assert_eq!(widths(SIZE_COLUMN_MIN_WIDTH - 1, true, true), (SIZE_COLUMN_MIN_WIDTH - 3, 0, 2));
+
+        // Modified times take their width plus a space, while names keep enough room
+        assert_eq!(list_columns(60, false, false, 16), ListColumns { name: 43, size: 0, date: 17, git: 0 });
+        assert_eq!(list_columns(36, false, false, 16).date, 0);
+    }
+
+    #[test]
+    fn test_size_bar_cells() {
+        assert_eq!(size_bar_cells(0, 1000, 8), 0);
+        assert_eq!(size_bar_cells(1, 1000, 8), 1, "a tiny file still shows");
+        assert_eq!(size_bar_cells(500, 1000, 8), 4);
+        assert_eq!(size_bar_cells(1000, 1000, 8), 8);
+        assert_eq!(size_bar_cells(u64::MAX, u64::MAX, 8), 8);
+        assert_eq!(size_bar_cells(10, 0, 8), 0);
+    }
+}
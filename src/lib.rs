@@ -1,9 +1,11 @@
 use pyo3::prelude::*;
-use pyo3::exceptions::PyValueError;
+use pyo3::exceptions::{PyFileExistsError, PyValueError};
 use pyo3::types::PyModule;
 use std::path::PathBuf;
 use std::collections::HashMap;
 use crate::app::App;
+use crate::watcher::{ChangeEvent, DirWatcher};
+use std::time::Duration;
 
 pub mod app;
 pub mod bookmarks;
@@ -17,6 +19,7 @@ pub mod plugin;
 pub mod preview;
 pub mod search;
 pub mod search_history;
+pub mod structured;
 pub mod theme;
 pub mod ui;
 pub mod workspace;
@@ -26,6 +29,23 @@ pub mod theme_manager;
 pub mod media_preview;
 pub mod media_player;
 pub mod integration_helpers;
+pub mod watcher;
+pub mod terminal_palette;
+pub mod jobs;
+pub mod thumbnails;
+pub mod trash;
+pub mod wasm_plugin;
+pub mod logging;
+pub mod editor;
+pub mod sidebar;
+pub mod frecency;
+pub mod layout;
+pub mod path_display;
+pub mod disk_usage;
+pub mod archive;
+pub mod pdf;
+pub mod markup;
+pub mod priority;
 
 /// Python module initialization
 #[pymodule]
@@ -36,6 +56,13 @@ fn pyastrofs(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     // Data Model Classes
     m.add_class::<PyFileEntry>()?;
     m.add_class::<PyBookmark>()?;
+    m.add_class::<PyContrastWarning>()?;
+    m.add_class::<PyTrashedItem>()?;
+    m.add_class::<PyMount>()?;
+    m.add_class::<PyPdfInfo>()?;
+    m.add_class::<PyDiffLine>()?;
+    m.add_class::<PyDiffResult>()?;
+    m.add_class::<PyCommandOutcome>()?;
     
     // Manager Classes
     m.add_class::<PyWorkspace>()?;
@@ -44,8 +71,11 @@ fn pyastrofs(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<PyThemeManager>()?;
     m.add_class::<PyPluginManager>()?;
     m.add_class::<PyPlugin>()?;
+    m.add_class::<PyPluginStats>()?;
     m.add_class::<PyMediaPlayer>()?;
     m.add_class::<PyMediaPreview>()?;
+    m.add_class::<PyDirWatcher>()?;
+    m.add_class::<PyWatchEvent>()?;
     
     // Enums
     m.add_class::<PyAppMode>()?;
@@ -74,6 +104,9 @@ fn pyastrofs(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     Ok(())
 }
 
+/// A path that an operation could not handle, and why
+type FailedPath = (String, String);
+
 #[pyclass]
 pub struct PyAstroFS {
     app: App,
@@ -92,6 +125,19 @@ impl PyAstroFS {
         self.app.go_to_path(&path).map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
+    /// Call `callback(old, new)` whenever the current directory changes; refreshing
+    /// the same directory doesn't count. Errors raised by the callback are logged.
+    fn on_directory_changed(&mut self, callback: PyObject) {
+        self.app.on_directory_changed(Box::new(move |old, new| {
+            Python::with_gil(|py| {
+                let args = (old.to_string_lossy().to_string(), new.to_string_lossy().to_string());
+                if let Err(e) = callback.call1(py, args) {
+                    tracing::warn!("on_directory_changed callback failed: {}", e);
+                }
+            })
+        }));
+    }
+
     fn current_dir(&self) -> String {
         self.app.workspace_manager.active_workspace().current_dir.to_string_lossy().to_string()
     }
@@ -103,10 +149,22 @@ impl PyAstroFS {
     fn move_up(&mut self) { self.app.move_up(); }
     fn move_down(&mut self) { self.app.move_down(); }
 
+    /// Select the entry at `index` in `list_files()`, clamped to the last one
+    fn select_index(&mut self, index: usize) { self.app.select_index(index); }
+    fn selected_index(&self) -> usize { self.app.selected_index() }
+
+    fn selected_entry(&self) -> Option<PyFileEntry> {
+        self.app.get_current_workspace().get_selected_entry().map(Into::into)
+    }
+
     fn enter_selected(&mut self) -> PyResult<()> {
         self.app.enter_selected().map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
+    fn resolve_opener(&self, path: String) -> Option<String> {
+        self.app.config.resolve_opener(&PathBuf::from(path))
+    }
+
     fn go_back(&mut self) -> PyResult<()> {
         self.app.go_back().map_err(|e| PyValueError::new_err(e.to_string()))
     }
@@ -123,33 +181,184 @@ impl PyAstroFS {
         self.app.delete_selected().map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
-    fn rename_selected(&mut self, new_name: String) -> PyResult<()> {
-        self.app.rename_selected(&new_name).map_err(|e| PyValueError::new_err(e.to_string()))
+    /// Rename the selected entry; an existing name raises FileExistsError unless `overwrite`
+    #[pyo3(signature = (new_name, overwrite=false))]
+    fn rename_selected(&mut self, new_name: String, overwrite: bool) -> PyResult<()> {
+        let Some(path) = self.app.get_current_workspace().get_selected_entry().map(|e| e.path.clone()) else {
+            return Ok(());
+        };
+        match self.app.rename_entry(&path, &new_name, overwrite) {
+            Ok(_) => Ok(()),
+            Err(e) if crate::fileops::is_already_exists(&e) => Err(PyFileExistsError::new_err(e.to_string())),
+            Err(e) => Err(PyValueError::new_err(e.to_string())),
+        }
+    }
+
+    /// Copy the selected entry beside itself as "name copy.ext"; returns the copy's path
+    fn duplicate_selected(&mut self) -> PyResult<Option<String>> {
+        let Some(path) = self.app.get_current_workspace().get_selected_entry().map(|e| e.path.clone()) else {
+            return Ok(None);
+        };
+        if self.app.in_archive() {
+            return Err(PyValueError::new_err("Archives are read-only"));
+        }
+        self.app
+            .duplicate(&path)
+            .map(|dest| Some(dest.to_string_lossy().to_string()))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Move the selected entry into the folder `dest`, or to `dest` itself;
+    /// returns where it went, or None when nothing is selected or it was skipped
+    fn move_selected(&mut self, dest: String) -> PyResult<Option<String>> {
+        let Some(path) = self.app.get_current_workspace().get_selected_entry().map(|e| e.path.clone()) else {
+            return Ok(None);
+        };
+        if self.app.in_archive() {
+            return Err(PyValueError::new_err("Archives are read-only"));
+        }
+        self.app
+            .move_entry(&path, &dest)
+            .map(|moved| moved.map(|p| p.to_string_lossy().to_string()))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Copy a text file's contents to the clipboard; binary files raise ValueError
+    fn copy_file_contents(&mut self, path: String) -> PyResult<()> {
+        self.app.copy_file_contents(&PathBuf::from(path)).map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
     fn copy_selected(&mut self) -> PyResult<()> {
         self.app.copy_selected().map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
+    /// List the current directory again, keeping the selected entry
+    fn refresh(&mut self) -> PyResult<()> {
+        self.app.refresh().map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// A Unix timestamp in the configured `date_format`, as the file list shows it
+    fn format_time(&self, timestamp: f64) -> PyResult<String> {
+        let since_epoch = std::time::Duration::try_from_secs_f64(timestamp)
+            .map_err(|_| PyValueError::new_err(format!("Invalid timestamp: {}", timestamp)))?;
+        Ok(self.app.format_time(std::time::UNIX_EPOCH + since_epoch))
+    }
+
     fn toggle_hidden(&mut self) -> PyResult<()> {
         self.app.toggle_hidden().map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
     fn start_search(&mut self) { self.app.start_search(); }
 
-    fn search(&mut self, query: String) {
+    #[pyo3(signature = (query, scope=None))]
+    fn search(&mut self, query: String, scope: Option<String>) -> PyResult<()> {
+        if let Some(scope) = scope {
+            self.set_search_scope(scope)?;
+        }
+        self.app.scoped_root = None;
         self.app.search_query = query;
-        self.app.perform_search();
+        self.app.run_search();
+        // Whole-drive searches stream in the background; scripts want the results
+        self.app.wait_for_search();
+        Ok(())
+    }
+
+    /// Search below `path` instead of the current directory; returns the results
+    fn search_in(&mut self, path: String, query: String) -> PyResult<Vec<PyFileEntry>> {
+        self.app
+            .search_in(&PathBuf::from(path), &query)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(self.search_results())
+    }
+
+    fn search_scope(&self) -> String {
+        self.app.search_scope.as_str().to_string()
+    }
+
+    fn set_search_scope(&mut self, scope: String) -> PyResult<()> {
+        let scope = crate::search::SearchScope::parse(&scope).ok_or_else(|| {
+            PyValueError::new_err(format!("Unknown search scope '{}': use current, recursive or drive", scope))
+        })?;
+        self.app.search_scope = scope;
+        self.app.config.search_scope = scope.as_str().to_string();
+        Ok(())
+    }
+
+    fn search_threads(&self) -> usize {
+        self.app.search_engine.threads()
+    }
+
+    /// Limit searches to `threads` threads (0 for one per CPU); saved with `save_settings()`
+    fn set_search_threads(&mut self, threads: usize) {
+        self.app.set_search_threads(threads);
     }
 
     fn search_results(&self) -> Vec<PyFileEntry> {
         self.app.search_engine.results.iter().map(|s| PyFileEntry::from(s)).collect()
     }
 
+    /// Narrow the current search results to a category; each call narrows
+    /// further. The singular ("image", "document") is accepted too.
+    fn filter_results_by_category(&mut self, category: String) -> PyResult<()> {
+        use crate::search::FileCategory;
+        let name = category.trim().to_lowercase();
+        let category = FileCategory::ALL
+            .into_iter()
+            .find(|c| {
+                let label = c.label().to_lowercase();
+                label == name || label.strip_suffix('s') == Some(name.as_str())
+            })
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Unknown category '{}': use images, audio, video, code, documents or archives",
+                    category
+                ))
+            })?;
+        self.app.search_engine.results.retain(|r| FileCategory::of(&r.path, r.is_dir) == Some(category));
+        Ok(())
+    }
+
+    fn clear_result_filters(&mut self) {
+        self.app.search_engine.clear_category_filters();
+    }
+
+    /// Copy the files among the search results into `dest`; returns how many
+    /// were copied and the (path, reason) of each failure
+    #[pyo3(signature = (dest, preserve_structure=false))]
+    fn collect_search_results(&mut self, dest: String, preserve_structure: bool) -> PyResult<(usize, Vec<(String, String)>)> {
+        let strategy = crate::fileops::CollisionStrategy::parse(&self.app.config.collision_strategy)
+            .unwrap_or(crate::fileops::CollisionStrategy::Rename);
+        let report = crate::fileops::FileOperation::collect(
+            &self.app.search_result_paths(),
+            &self.app.search_base(),
+            &PathBuf::from(dest),
+            preserve_structure,
+            strategy,
+            |_, _| true,
+        )
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok((
+            report.copied.len(),
+            report
+                .failed
+                .into_iter()
+                .map(|(path, reason)| (path.to_string_lossy().to_string(), reason))
+                .collect(),
+        ))
+    }
+
     fn navigate_to_search_result(&mut self, index: usize) -> PyResult<()> {
         self.app.navigate_to_search_result(index).map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
+    /// Act on search result `index`: "open", "reveal", "copy-path", "delete" or "bookmark"
+    fn search_result_action(&mut self, index: usize, action: String) -> PyResult<()> {
+        let action = crate::search::ResultAction::parse(&action).ok_or_else(|| {
+            PyValueError::new_err(format!("Unknown action '{}': use open, reveal, copy-path, delete or bookmark", action))
+        })?;
+        self.app.search_result_action(index, action).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
     fn add_bookmark(&mut self, name: String) -> PyResult<()> {
         self.app.add_bookmark(name).map_err(|e| PyValueError::new_err(e.to_string()))
     }
@@ -158,12 +367,44 @@ impl PyAstroFS {
         self.app.goto_bookmark(&name).map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
+    /// Seed the recent directories from "zoxide" or "autojump" data; returns how many were added
+    #[pyo3(signature = (source, path=None))]
+    fn import_jump_data(&mut self, source: String, path: Option<String>) -> PyResult<usize> {
+        self.app
+            .import_jump_data(&source, path.map(PathBuf::from).as_deref())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn export_bookmarks(&mut self, path: String) -> PyResult<()> {
+        self.app.export_bookmarks(&PathBuf::from(path)).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn import_bookmarks(&mut self, path: String, merge: bool) -> PyResult<usize> {
+        self.app.import_bookmarks(&PathBuf::from(path), merge).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
     fn switch_theme(&mut self, theme_name: String) -> PyResult<()> {
         self.app.switch_theme(&theme_name).map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
     fn list_themes(&self) -> Vec<String> { self.app.list_available_themes() }
 
+    fn import_theme(&mut self, path: String) -> PyResult<Vec<PyContrastWarning>> {
+        self.app.import_theme(&PathBuf::from(path))
+            .map(|warnings| warnings.into_iter().map(Into::into).collect())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn auto_adjust_theme(&mut self, name: String) -> PyResult<usize> {
+        self.app.auto_adjust_theme(&name).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn check_theme_contrast(&self, name: String) -> PyResult<Vec<PyContrastWarning>> {
+        self.app.check_theme_contrast(&name)
+            .map(|warnings| warnings.into_iter().map(Into::into).collect())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
     fn load_plugins(&mut self) -> PyResult<()> {
         self.app.load_plugins().map_err(|e| PyValueError::new_err(e.to_string()))
     }
@@ -176,10 +417,46 @@ impl PyAstroFS {
         self.app.disable_plugin(&id).map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
+    fn reload_plugin(&mut self, id: String) -> PyResult<()> {
+        self.app.reload_plugin(&id).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Unload every plugin and load them again from the plugin directory
+    fn reload_plugins(&mut self) -> PyResult<()> {
+        self.app.reload_plugins().map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Load plugins from `path` from now on, reloading them from there; "" goes
+    /// back to the config's `plugin_directory`
+    fn set_plugin_dir(&mut self, path: String) -> PyResult<()> {
+        self.app.set_plugin_dir(&path).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn plugin_dir(&self) -> String {
+        self.app.plugin_dir().to_string_lossy().to_string()
+    }
+
+    fn plugin_stats(&self, id: String) -> PyResult<PyPluginStats> {
+        self.app
+            .api_plugin_manager
+            .stats(&id)
+            .map(Into::into)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown plugin '{}'", id)))
+    }
+
     fn preview_media(&mut self, path: String) -> PyResult<Option<String>> {
         self.app.preview_media(&PathBuf::from(path)).map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
+    #[pyo3(signature = (path, out=None, overwrite=false))]
+    fn export_media_info(&self, path: String, out: Option<String>, overwrite: bool) -> PyResult<String> {
+        let out = out.map(PathBuf::from);
+        self.app
+            .export_media_info(&PathBuf::from(path), out.as_deref(), overwrite)
+            .map(|out| out.to_string_lossy().to_string())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
     fn play_media(&mut self, path: String) -> PyResult<()> {
         self.app.play_media(&PathBuf::from(path)).map_err(|e| PyValueError::new_err(e.to_string()))
     }
@@ -207,6 +484,23 @@ impl PyAstroFS {
         self.app.import_settings(&path).map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
+    /// Open a directory in a new tab, switching to it when `focus_new_tab` is on;
+    /// returns the tab's position
+    fn open_in_new_tab(&mut self, path: String) -> PyResult<usize> {
+        self.app
+            .open_in_new_tab(&PathBuf::from(path))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Run a command palette command by id, e.g. "duplicate" or "parent", and
+    /// return what it did
+    fn run_command(&mut self, name: String) -> PyResult<PyCommandOutcome> {
+        self.app
+            .execute_command_named(&name)
+            .map(PyCommandOutcome::from)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
     fn get_current_workspace(&self) -> PyWorkspace {
         let ws = self.app.get_current_workspace();
         PyWorkspace {
@@ -224,6 +518,7 @@ impl PyAstroFS {
                     name: b.name.clone(),
                     path: b.path.to_string_lossy().to_string(),
                     icon: b.icon.clone(),
+                    tags: b.tags.clone(),
                 })
             }).collect::<HashMap<_, _>>(),
         }
@@ -236,6 +531,21 @@ impl PyAstroFS {
         }
     }
 
+    /// Where settings, themes and the log are kept
+    fn config_dir(&self) -> String {
+        self.app.config_dir().to_string_lossy().to_string()
+    }
+
+    /// Where bookmarks and search history are kept
+    fn data_dir(&self) -> PyResult<String> {
+        Ok(self.app.data_dir().to_string_lossy().to_string())
+    }
+
+    /// Folders themes are loaded from, bundled ones first
+    fn theme_dirs(&self) -> Vec<String> {
+        self.app.theme_dirs().iter().map(|dir| dir.to_string_lossy().to_string()).collect()
+    }
+
     fn get_theme_manager(&self) -> PyThemeManager {
         PyThemeManager {
             current_theme: self.app.theme_manager.current_theme_name(),
@@ -248,8 +558,10 @@ impl PyAstroFS {
             plugins: self.app.api_plugin_manager.list().into_iter().map(|p| PyPlugin {
                 id: p.id.clone(),
                 name: p.name.clone(),
+                version: p.version.clone(),
                 description: p.description.clone(),
                 enabled: p.enabled,
+                permissions: p.permissions.iter().map(|perm| perm.as_str().to_string()).collect(),
             }).collect(),
         }
     }
@@ -271,6 +583,212 @@ impl PyAstroFS {
             last_path: self.app.media_preview.last_path().map(|p| p.to_string_lossy().to_string()),
         }
     }
+
+    fn recent_files(&self, path: String, n: usize) -> Vec<PyFileEntry> {
+        let options = self.app.walk_options();
+        crate::files::recent_files(&PathBuf::from(path), n, &options)
+            .iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Files opened from AstroFS, most recent first; files since deleted are left out
+    fn recent_files_opened(&self) -> Vec<PyFileEntry> {
+        self.app.recently_opened_files().iter().map(Into::into).collect()
+    }
+
+    fn clear_recent_files(&mut self) {
+        self.app.clear_recently_opened();
+    }
+
+    #[pyo3(signature = (path, algo="sha256".to_string()))]
+    fn hash_file(&mut self, path: String, algo: String) -> PyResult<String> {
+        let algo = crate::files::HashAlgo::parse(&algo)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown hash '{}': expected md5, sha256 or blake3", algo)))?;
+        self.app.hash_file(&PathBuf::from(path), algo).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Apply a chmod-style mode ("644", "+x", "go-w"); returns (path, reason) for each entry that failed
+    #[pyo3(signature = (path, mode, recursive=false))]
+    fn set_permissions(&mut self, path: String, mode: String, recursive: bool) -> PyResult<Vec<(String, String)>> {
+        let change = crate::fileops::PermissionChange::parse_mode(&mode).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let report = crate::fileops::FileOperation::set_permissions_recursive(&[PathBuf::from(path)], &change, recursive)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(report
+            .failed
+            .into_iter()
+            .map(|(path, reason)| (path.to_string_lossy().to_string(), reason))
+            .collect())
+    }
+
+    /// Copy paths into `dest`; returns where each ended up, None for skipped ones
+    #[pyo3(signature = (paths, dest, strategy="error"))]
+    fn copy_paths(&mut self, paths: Vec<String>, dest: String, strategy: &str) -> PyResult<Vec<Option<String>>> {
+        let follow_symlinks = self.app.config.follow_symlinks;
+        transfer_paths(&paths, &dest, crate::fileops::TransferKind::Copy, strategy, follow_symlinks)
+    }
+
+    /// Move paths into `dest`; returns where each ended up, None for skipped ones
+    #[pyo3(signature = (paths, dest, strategy="error"))]
+    fn move_paths(&mut self, paths: Vec<String>, dest: String, strategy: &str) -> PyResult<Vec<Option<String>>> {
+        transfer_paths(&paths, &dest, crate::fileops::TransferKind::Move, strategy, false)
+    }
+
+    /// Copy the marked entries (or the selection) into a bookmarked directory; returns it
+    fn copy_to_bookmark(&mut self, name: String) -> PyResult<String> {
+        transfer_to_bookmark(&mut self.app, &name, crate::fileops::TransferKind::Copy)
+    }
+
+    /// Move the marked entries (or the selection) into a bookmarked directory; returns it
+    fn move_to_bookmark(&mut self, name: String) -> PyResult<String> {
+        transfer_to_bookmark(&mut self.app, &name, crate::fileops::TransferKind::Move)
+    }
+
+    /// Number the marked files (or the selection) `prefix_0001.ext`, ... by capture
+    /// date ("date") or modified time ("mtime"); returns the new paths
+    #[pyo3(signature = (prefix, order="date"))]
+    fn sequence_rename(&mut self, prefix: String, order: &str) -> PyResult<Vec<String>> {
+        let order = crate::fileops::SequenceOrder::parse(order)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown order: {} (use date or mtime)", order)))?;
+        self.app
+            .sequence_rename(&prefix, order)
+            .map(|paths| paths.iter().map(|p| p.to_string_lossy().to_string()).collect())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Move every file in `path`'s subfolders up into `path` and remove the folders
+    /// left empty; returns the new paths, the number skipped and the failures
+    fn flatten_directory(&mut self, path: String) -> PyResult<(Vec<String>, usize, Vec<FailedPath>)> {
+        let report = self
+            .app
+            .flatten_directory(&PathBuf::from(path))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok((
+            report.moved.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+            report.skipped,
+            report
+                .failed
+                .into_iter()
+                .map(|(path, reason)| (path.to_string_lossy().to_string(), reason))
+                .collect(),
+        ))
+    }
+
+    /// A path written the way the UI shows it (`path_display`), middle-truncated
+    /// to `max_width` characters when that is non-zero
+    #[pyo3(signature = (path, max_width=0))]
+    fn format_path(&self, path: String, max_width: usize) -> String {
+        let width = if max_width == 0 { usize::MAX } else { max_width };
+        self.app.display_path(&PathBuf::from(path), width)
+    }
+
+    /// `path` relative to its git repository's root (`src/app.rs`), or to the
+    /// current directory when it isn't in a repository
+    fn relative_path_from_repo(&self, path: String) -> String {
+        self.app.relative_path_from_repo(&PathBuf::from(path))
+    }
+
+    /// The mime type of a file judged by its contents, e.g. `image/png`
+    fn detect_mime(&self, path: String) -> Option<String> {
+        crate::files::detect_mime(&PathBuf::from(path))
+    }
+
+    /// The children of `path` with the bytes in everything below each, largest first
+    fn disk_usage(&self, path: String) -> PyResult<Vec<(PyFileEntry, u64)>> {
+        let scan = crate::disk_usage::scan(
+            &PathBuf::from(path),
+            self.app.config.follow_symlinks,
+            &HashMap::new(),
+            |_, _| {},
+            || true,
+        )
+        .map_err(|e| PyValueError::new_err(e.to_string()))?
+        .unwrap_or_default();
+        Ok(scan.entries.iter().map(|usage| (PyFileEntry::from(&usage.entry), usage.size)).collect())
+    }
+
+    /// Everything inside a .zip, .tar or .tar.gz, with paths under the archive
+    /// (`photos.zip/2021/a.jpg`), sorted by path
+    fn list_archive(&self, path: String) -> PyResult<Vec<PyFileEntry>> {
+        let listing = crate::archive::ArchiveListing::open(&PathBuf::from(path))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let mut entries: Vec<&crate::files::FileEntry> = listing.entries().iter().collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries.into_iter().map(PyFileEntry::from).collect())
+    }
+
+    fn toggle_mark(&mut self) { self.app.toggle_mark(); }
+    fn select_all(&mut self) { self.app.select_all(); }
+    fn clear_selection(&mut self) { self.app.clear_selection(); }
+    fn invert_selection(&mut self) { self.app.invert_selection(); }
+
+    fn selected_paths(&self) -> Vec<String> {
+        self.app
+            .workspace_manager
+            .active_workspace()
+            .marked_paths()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    }
+
+    fn diff_files(&self, a: String, b: String) -> PyResult<PyDiffResult> {
+        self.app
+            .diff_files(&PathBuf::from(a), &PathBuf::from(b))
+            .map(Into::into)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Page count, title, author and first-page text of a PDF
+    fn pdf_info(&self, path: String) -> PyResult<PyPdfInfo> {
+        crate::pdf::pdf_info(&PathBuf::from(path))
+            .map(Into::into)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn list_mounts(&self) -> Vec<PyMount> {
+        crate::files::list_mounts().iter().map(Into::into).collect()
+    }
+
+    fn list_trash(&self) -> PyResult<Vec<PyTrashedItem>> {
+        self.app
+            .list_trash()
+            .map(|items| items.iter().map(Into::into).collect())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn restore_from_trash(&mut self, trashed: String) -> PyResult<String> {
+        self.app
+            .restore_from_trash(&PathBuf::from(trashed))
+            .map(|original| original.to_string_lossy().to_string())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn delete_from_trash(&mut self, trashed: String) -> PyResult<()> {
+        self.app.delete_from_trash(&PathBuf::from(trashed)).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn empty_trash(&mut self) -> PyResult<usize> {
+        self.app.empty_trash().map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn trash_size(&self) -> PyResult<u64> {
+        self.app.trash_size().map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Apply `trash_max_age_days` and `trash_max_size_mb` now; returns (items, bytes) deleted
+    fn prune_trash(&mut self) -> PyResult<(usize, u64)> {
+        self.app
+            .prune_trash()
+            .map(|pruned| (pruned.items, pruned.bytes))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn watch(&self, path: String) -> PyResult<PyDirWatcher> {
+        DirWatcher::new(&PathBuf::from(path))
+            .map(|watcher| PyDirWatcher { watcher: Some(watcher) })
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
 }
 
 #[pyclass]
@@ -280,6 +798,12 @@ pub struct PyFileEntry {
     #[pyo3(get)] pub path: String,
     #[pyo3(get)] pub is_dir: bool,
     #[pyo3(get)] pub size: u64,
+    /// Modification time as seconds since the Unix epoch
+    #[pyo3(get)] pub modified: Option<f64>,
+    /// Items directly inside a directory (at most 100), when folder counts are shown
+    #[pyo3(get)] pub item_count: Option<usize>,
+    /// Mime type detected from the contents of a file without an extension
+    #[pyo3(get)] pub mime: Option<String>,
 }
 
 impl From<&crate::files::FileEntry> for PyFileEntry {
@@ -289,6 +813,12 @@ impl From<&crate::files::FileEntry> for PyFileEntry {
             path: e.path.to_string_lossy().to_string(),
             is_dir: e.is_dir,
             size: e.size,
+            modified: e
+                .modified
+                .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs_f64()),
+            item_count: e.item_count,
+            mime: e.mime.clone(),
         }
     }
 }
@@ -300,6 +830,9 @@ impl From<&crate::search::SearchResult> for PyFileEntry {
             path: s.path.to_string_lossy().to_string(),
             is_dir: s.is_dir,
             size: 0,
+            modified: None,
+            item_count: None,
+            mime: None,
         }
     }
 }
@@ -318,6 +851,7 @@ pub struct PyBookmark {
     #[pyo3(get)] pub name: String,
     #[pyo3(get)] pub path: String,
     #[pyo3(get)] pub icon: String,
+    #[pyo3(get)] pub tags: Vec<String>,
 }
 
 #[pyclass]
@@ -337,13 +871,276 @@ pub struct PyThemeManager {
     #[pyo3(get)] pub available_themes: Vec<String>,
 }
 
+#[pyclass]
+#[derive(Clone)]
+pub struct PyContrastWarning {
+    #[pyo3(get)] pub pair: String,
+    #[pyo3(get)] pub foreground: String,
+    #[pyo3(get)] pub background: String,
+    #[pyo3(get)] pub ratio: f64,
+    #[pyo3(get)] pub minimum: f64,
+}
+
+impl From<crate::theme_manager::ContrastWarning> for PyContrastWarning {
+    fn from(w: crate::theme_manager::ContrastWarning) -> Self {
+        Self {
+            pair: w.pair,
+            foreground: w.foreground,
+            background: w.background,
+            ratio: w.ratio,
+            minimum: w.minimum,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyTrashedItem {
+    #[pyo3(get)] pub name: String,
+    #[pyo3(get)] pub original: String,
+    #[pyo3(get)] pub trashed: String,
+    /// Deletion time as an ISO 8601 local timestamp
+    #[pyo3(get)] pub deleted: Option<String>,
+}
+
+impl From<&crate::trash::TrashedItem> for PyTrashedItem {
+    fn from(item: &crate::trash::TrashedItem) -> Self {
+        Self {
+            name: item.name(),
+            original: item.original.to_string_lossy().to_string(),
+            trashed: item.trashed.to_string_lossy().to_string(),
+            deleted: item.deleted.map(|d| d.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        }
+    }
+}
+
+/// Batch copy or move for the bindings; there is no one to ask, so `ask` is refused
+fn transfer_paths(
+    paths: &[String],
+    dest: &str,
+    kind: crate::fileops::TransferKind,
+    strategy: &str,
+    follow_symlinks: bool,
+) -> PyResult<Vec<Option<String>>> {
+    use crate::fileops::{CollisionStrategy, FileOperation, TransferOutcome};
+
+    let strategy = match CollisionStrategy::parse(strategy) {
+        Some(CollisionStrategy::Ask) | None => {
+            return Err(PyValueError::new_err("strategy must be error, overwrite, rename or skip"))
+        }
+        Some(strategy) => strategy,
+    };
+    paths
+        .iter()
+        .map(|path| {
+            match FileOperation::transfer(&PathBuf::from(path), &PathBuf::from(dest), kind, strategy, follow_symlinks) {
+                Ok(TransferOutcome::Done(target)) => Ok(Some(target.to_string_lossy().to_string())),
+                Ok(_) => Ok(None),
+                Err(e) => Err(PyValueError::new_err(e.to_string())),
+            }
+        })
+        .collect()
+}
+
+/// File the app's marked entries into a bookmark, renaming instead of asking on collisions
+fn transfer_to_bookmark(app: &mut App, name: &str, kind: crate::fileops::TransferKind) -> PyResult<String> {
+    use crate::fileops::CollisionStrategy;
+
+    let strategy = match CollisionStrategy::parse(&app.config.collision_strategy) {
+        Some(CollisionStrategy::Ask) | None => CollisionStrategy::Rename,
+        Some(strategy) => strategy,
+    };
+    app.error = None;
+    app.transfer_to_bookmark(name, kind, strategy)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    if let Some(error) = app.error.take() {
+        return Err(PyValueError::new_err(error));
+    }
+    let dest = app.bookmark_manager.get(name).map(|b| b.path.to_string_lossy().to_string());
+    Ok(dest.unwrap_or_default())
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyMount {
+    #[pyo3(get)] pub name: String,
+    #[pyo3(get)] pub mount_point: String,
+    #[pyo3(get)] pub file_system: String,
+    #[pyo3(get)] pub removable: bool,
+    /// Free bytes available to this user
+    #[pyo3(get)] pub available: u64,
+    #[pyo3(get)] pub total: u64,
+}
+
+impl From<&crate::files::Mount> for PyMount {
+    fn from(mount: &crate::files::Mount) -> Self {
+        Self {
+            name: mount.name.clone(),
+            mount_point: mount.mount_point.to_string_lossy().to_string(),
+            file_system: mount.file_system.clone(),
+            removable: mount.removable,
+            available: mount.available,
+            total: mount.total,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyPdfInfo {
+    #[pyo3(get)] pub version: String,
+    #[pyo3(get)] pub pages: usize,
+    #[pyo3(get)] pub title: Option<String>,
+    #[pyo3(get)] pub author: Option<String>,
+    /// None for image-only pages and fonts whose text can't be recovered
+    #[pyo3(get)] pub first_page_text: Option<String>,
+}
+
+impl From<crate::pdf::PdfInfo> for PyPdfInfo {
+    fn from(info: crate::pdf::PdfInfo) -> Self {
+        Self {
+            version: info.version,
+            pages: info.pages,
+            title: info.title,
+            author: info.author,
+            first_page_text: info.first_page_text,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyDiffLine {
+    /// "equal", "added", "removed", "changed" or "gap"
+    #[pyo3(get)] pub kind: String,
+    #[pyo3(get)] pub old_line: Option<usize>,
+    #[pyo3(get)] pub old_text: Option<String>,
+    #[pyo3(get)] pub new_line: Option<usize>,
+    #[pyo3(get)] pub new_text: Option<String>,
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyDiffResult {
+    #[pyo3(get)] pub old_path: String,
+    #[pyo3(get)] pub new_path: String,
+    #[pyo3(get)] pub old_size: u64,
+    #[pyo3(get)] pub new_size: u64,
+    #[pyo3(get)] pub binary: bool,
+    #[pyo3(get)] pub identical: bool,
+    #[pyo3(get)] pub truncated: bool,
+    #[pyo3(get)] pub lines: Vec<PyDiffLine>,
+}
+
+impl From<crate::files::DiffResult> for PyDiffResult {
+    fn from(diff: crate::files::DiffResult) -> Self {
+        let lines = diff
+            .lines
+            .into_iter()
+            .map(|line| {
+                let (old_line, old_text) = line.old.map_or((None, None), |(n, text)| (Some(n), Some(text)));
+                let (new_line, new_text) = line.new.map_or((None, None), |(n, text)| (Some(n), Some(text)));
+                PyDiffLine {
+                    kind: line.kind.as_str().to_string(),
+                    old_line,
+                    old_text,
+                    new_line,
+                    new_text,
+                }
+            })
+            .collect();
+        Self {
+            old_path: diff.old_path.to_string_lossy().to_string(),
+            new_path: diff.new_path.to_string_lossy().to_string(),
+            old_size: diff.old_size,
+            new_size: diff.new_size,
+            binary: diff.binary,
+            identical: diff.identical,
+            truncated: diff.truncated,
+            lines,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyCommandOutcome {
+    /// "navigated", "switched_tab", "created", "removed", "opened", "message" or "nothing"
+    #[pyo3(get)] pub kind: String,
+    /// The directory navigated to, or the entries created or removed
+    #[pyo3(get)] pub paths: Vec<String>,
+    /// Position of the tab switched to
+    #[pyo3(get)] pub tab: Option<usize>,
+    /// The view or prompt opened, e.g. "Settings" or "Input(CreateFile)"
+    #[pyo3(get)] pub view: Option<String>,
+    #[pyo3(get)] pub message: Option<String>,
+}
+
+impl From<crate::app::CommandOutcome> for PyCommandOutcome {
+    fn from(outcome: crate::app::CommandOutcome) -> Self {
+        use crate::app::CommandOutcome;
+        let strings = |paths: Vec<PathBuf>| paths.iter().map(|p| p.to_string_lossy().to_string()).collect();
+        let mut result = Self { kind: String::new(), paths: Vec::new(), tab: None, view: None, message: None };
+        match outcome {
+            CommandOutcome::Navigated(dir) => {
+                result.kind = "navigated".to_string();
+                result.paths = strings(vec![dir]);
+            }
+            CommandOutcome::SwitchedTab(tab) => {
+                result.kind = "switched_tab".to_string();
+                result.tab = Some(tab);
+            }
+            CommandOutcome::Created(paths) => {
+                result.kind = "created".to_string();
+                result.paths = strings(paths);
+            }
+            CommandOutcome::Removed(paths) => {
+                result.kind = "removed".to_string();
+                result.paths = strings(paths);
+            }
+            CommandOutcome::Opened(mode) => {
+                result.kind = "opened".to_string();
+                result.view = Some(format!("{:?}", mode));
+            }
+            CommandOutcome::Message(message) => {
+                result.kind = "message".to_string();
+                result.message = Some(message);
+            }
+            CommandOutcome::Nothing => result.kind = "nothing".to_string(),
+        }
+        result
+    }
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct PyPlugin {
     #[pyo3(get)] pub id: String,
     #[pyo3(get)] pub name: String,
+    #[pyo3(get)] pub version: String,
     #[pyo3(get)] pub description: String,
     #[pyo3(get)] pub enabled: bool,
+    #[pyo3(get)] pub permissions: Vec<String>,
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyPluginStats {
+    #[pyo3(get)] pub load_time_ms: u64,
+    #[pyo3(get)] pub memory_usage_bytes: u64,
+    #[pyo3(get)] pub function_calls: u64,
+    #[pyo3(get)] pub errors: u64,
+}
+
+impl From<crate::plugin_api::PluginStats> for PyPluginStats {
+    fn from(s: crate::plugin_api::PluginStats) -> Self {
+        Self {
+            load_time_ms: s.load_time_ms,
+            memory_usage_bytes: s.memory_usage_bytes,
+            function_calls: s.function_calls,
+            errors: s.errors,
+        }
+    }
 }
 
 #[pyclass]
@@ -367,6 +1164,85 @@ pub struct PyMediaPreview {
     #[pyo3(get)] pub last_path: Option<String>,
 }
 
+// ============================================================================
+// DIRECTORY WATCHING
+// ============================================================================
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyWatchEvent {
+    #[pyo3(get)] pub kind: String,
+    #[pyo3(get)] pub path: String,
+}
+
+impl From<ChangeEvent> for PyWatchEvent {
+    fn from(e: ChangeEvent) -> Self {
+        Self {
+            kind: e.kind.as_str().to_string(),
+            path: e.path.to_string_lossy().to_string(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyWatchEvent {
+    fn __repr__(&self) -> String {
+        format!("WatchEvent({}, {})", self.kind, self.path)
+    }
+}
+
+/// Iterator over change events for a watched directory
+#[pyclass]
+pub struct PyDirWatcher {
+    watcher: Option<DirWatcher>,
+}
+
+#[pymethods]
+impl PyDirWatcher {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Block until the next event, releasing the GIL while waiting
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyWatchEvent>> {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return Ok(None);
+        };
+
+        loop {
+            // Wake up periodically so Ctrl+C still reaches Python
+            let waiting = &mut *watcher;
+            let event = py.allow_threads(move || waiting.recv_timeout(Duration::from_millis(100)));
+            if let Some(event) = event {
+                return Ok(Some(event.into()));
+            }
+            py.check_signals()?;
+        }
+    }
+
+    /// Wait up to `timeout` seconds and return buffered events, or None if nothing changed
+    fn poll(&mut self, py: Python<'_>, timeout: f64) -> Option<Vec<PyWatchEvent>> {
+        let watcher = self.watcher.as_mut()?;
+        let timeout = Duration::from_secs_f64(timeout.max(0.0));
+
+        let waiting = &mut *watcher;
+        let first = py.allow_threads(move || waiting.recv_timeout(timeout))?;
+        let mut events = vec![first.into()];
+        events.extend(watcher.drain().into_iter().map(PyWatchEvent::from));
+        Some(events)
+    }
+
+    /// Stop watching; subsequent iteration ends immediately
+    fn close(&mut self) {
+        self.watcher = None;
+    }
+
+    #[getter]
+    fn path(&self) -> Option<String> {
+        self.watcher.as_ref().map(|w| w.path().to_string_lossy().to_string())
+    }
+}
+
 // ============================================================================
 // ENUM WRAPPERS - AppMode and InputMode
 // ============================================================================
@@ -0,0 +1,102 @@
+// Logging - tracing output written to a file in the config directory
+//
+// The TUI owns stdout, so log lines only ever go to the log file.
+use crate::config::AppConfig;
+use anyhow::{anyhow, Result};
+use std::fmt::Display;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+
+/// Environment variable that overrides the configured log level
+pub const LOG_ENV_VAR: &str = "ASTROFS_LOG";
+
+/// Location of the user's log file, see `AppDirs::log_file`
+pub fn log_path() -> Result<PathBuf> {
+    Ok(crate::app::AppDirs::user()?.log_file())
+}
+
+/// Parse a level name such as "warn" or "DEBUG"
+pub fn parse_level(name: &str) -> Option<LevelFilter> {
+    match name.trim().to_lowercase().as_str() {
+        "off" | "none" => Some(LevelFilter::OFF),
+        "error" => Some(LevelFilter::ERROR),
+        "warn" | "warning" => Some(LevelFilter::WARN),
+        "info" => Some(LevelFilter::INFO),
+        "debug" => Some(LevelFilter::DEBUG),
+        "trace" => Some(LevelFilter::TRACE),
+        _ => None,
+    }
+}
+
+/// Level from `ASTROFS_LOG` if set and valid, else from the config
+fn effective_level(config: &AppConfig) -> LevelFilter {
+    std::env::var(LOG_ENV_VAR)
+        .ok()
+        .and_then(|value| parse_level(&value))
+        .or_else(|| parse_level(&config.log_level))
+        .unwrap_or(LevelFilter::INFO)
+}
+
+/// Install the global subscriber, appending to the log file at `path`.
+/// Returns the log path, or `None` when logging is turned off.
+pub fn init(config: &AppConfig, path: &Path) -> Result<Option<PathBuf>> {
+    let level = effective_level(config);
+    if level == LevelFilter::OFF {
+        return Ok(None);
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .with_max_level(level)
+        .try_init()
+        .map_err(|e| anyhow!("Failed to start logging: {}", e))?;
+    tracing::info!("astrofs {} started", env!("CARGO_PKG_VERSION"));
+    Ok(Some(path.to_path_buf()))
+}
+
+/// Log failures as warnings instead of dropping them with `let _ =`
+pub trait LogErr<T> {
+    /// Log the error with what was being attempted, e.g. "save bookmarks"
+    fn log_err(self, action: &str) -> Option<T>;
+}
+
+impl<T, E: Display> LogErr<T> for std::result::Result<T, E> {
+    fn log_err(self, action: &str) -> Option<T> {
+        match self {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::warn!("Failed to {}: {:#}", action, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("WARN"), Some(LevelFilter::WARN));
+        assert_eq!(parse_level(" debug "), Some(LevelFilter::DEBUG));
+        assert_eq!(parse_level("off"), Some(LevelFilter::OFF));
+        assert_eq!(parse_level("loud"), None);
+
+        let config = AppConfig {
+            log_level: "error".to_string(),
+            ..Default::default()
+        };
+        if std::env::var(LOG_ENV_VAR).is_err() {
+            assert_eq!(effective_level(&config), LevelFilter::ERROR);
+        }
+    }
+}
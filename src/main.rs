@@ -10,6 +10,7 @@ mod plugin;
 mod preview;
 mod search;
 mod search_history;
+mod structured;
 mod theme;
 mod ui;
 mod workspace;
@@ -19,18 +20,72 @@ mod theme_manager;
 mod media_preview;
 mod media_player;
 mod integration_helpers;
+mod watcher;
+mod terminal_palette;
+mod jobs;
+mod thumbnails;
+mod trash;
+mod wasm_plugin;
+mod logging;
+mod cli;
+mod editor;
+mod sidebar;
+mod frecency;
+mod layout;
+mod path_display;
+mod disk_usage;
+mod archive;
+mod pdf;
+mod markup;
+mod priority;
 
-use app::{App, AppMode, InputMode};
+use app::{App, AppDirs, AppMode, InputMode, TrashAction};
+use logging::LogErr;
+use fileops::{CollisionStrategy, TransferKind};
+use search::{FileCategory, ResultAction};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{backend::CrosstermBackend, Terminal};
+use ratatui::{backend::CrosstermBackend, layout::Rect, Terminal};
 use std::io;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::Instant;
+use thumbnails::{GraphicsProtocol, Placement};
 
 fn main() -> anyhow::Result<()> {
+    let args = <cli::Cli as clap::Parser>::parse();
+    if args.log_path {
+        println!("{}", logging::log_path()?.display());
+        return Ok(());
+    }
+    // Headless subcommands print to stdout and never touch the terminal mode
+    if let Some(command) = &args.command {
+        return cli::run(command, args.json, &mut io::stdout().lock());
+    }
+
+    // An explicit --config must be readable; the default file may be missing or broken
+    let config = match &args.config {
+        Some(path) => config::AppConfig::load_from(path)?,
+        None => config::AppConfig::load().unwrap_or_default(),
+    };
+
+    // Logs go to a file only; stdout belongs to the TUI
+    let dirs = AppDirs::user()?;
+    if let Err(e) = logging::init(&config, &dirs.log_file()) {
+        eprintln!("Warning: {}", e);
+    }
+
+    // Create the app before touching the terminal so startup errors print normally
+    let recovery = persistence::RecoveryFile::new(dirs.recovery_file());
+    let mut app = App::with_dirs(config, dirs)?;
+    if let Some(path) = &args.path {
+        app.open_start_path(path)?;
+    }
+    app.start_session_recovery(recovery);
+    app.prune_trash().log_err("prune the trash");
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -38,9 +93,6 @@ fn main() -> anyhow::Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create app
-    let mut app = App::new()?;
-
     // Run the application
     let res = run_app(&mut terminal, &mut app);
 
@@ -54,6 +106,7 @@ fn main() -> anyhow::Result<()> {
     terminal.show_cursor()?;
 
     if let Err(err) = res {
+        tracing::error!("Exited with error: {:#}", err);
         eprintln!("Error: {}", err);
     }
 
@@ -64,30 +117,144 @@ fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
 ) -> anyhow::Result<()> {
+    // Kitty/Sixel thumbnails currently on screen
+    let mut shown_graphics: Vec<(PathBuf, Rect)> = Vec::new();
+
     loop {
         let size = terminal.get_frame().size();
         app.set_viewport(size.width as usize, size.height as usize);
 
+        app.poll_thumbnails();
+        app.poll_hashes();
+        app.poll_search();
+        app.poll_collect();
+        app.poll_disk_usage();
         terminal.draw(|f| ui::draw(f, app))?;
+        if app.graphics_protocol != GraphicsProtocol::Blocks {
+            sync_gallery_graphics(terminal, app, &mut shown_graphics)?;
+        }
 
         if !app.running {
             break;
         }
 
-        // Handle events with timeout
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
+        app.poll_directory_changes()?;
+        app.check_pending_quit();
+        app.expire_undo();
+        app.refresh_status_info();
+        app.jobs.tick();
+        app.run_idle_hooks();
+        app.autosave(Instant::now());
+        app.write_recovery();
+
+        // Wait for input; the wait stretches while nothing is happening
+        if event::poll(app.poll_timeout())? {
+            let event = event::read()?;
+            app.note_input();
+            if let Event::Key(key) = event {
                 // Only process key press events, not release
                 if key.kind == KeyEventKind::Press {
                     handle_input(app, key)?;
                 }
             }
         }
+
+        if let Some(command) = app.pending_command.take() {
+            run_external_command(terminal, app, &command)?;
+        }
     }
 
     Ok(())
 }
 
+/// Draw gallery thumbnails with the terminal's graphics protocol.
+///
+/// Images live outside ratatui's buffer, so they are only re-sent when the
+/// set of visible thumbnails changes, and removed while an overlay is open.
+fn sync_gallery_graphics<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    shown: &mut Vec<(PathBuf, Rect)>,
+) -> anyhow::Result<()> {
+    let wanted = if app.gallery_view && matches!(app.mode, AppMode::Normal) {
+        app.gallery_placements.clone()
+    } else {
+        Vec::new()
+    };
+    if wanted == *shown {
+        return Ok(());
+    }
+
+    let mut stdout = io::stdout();
+    if !shown.is_empty() {
+        match app.graphics_protocol {
+            // Sixel pixels stay until the cells are rewritten
+            GraphicsProtocol::Sixel => {
+                terminal.clear()?;
+                terminal.draw(|f| ui::draw(f, app))?;
+            }
+            _ => thumbnails::clear_graphics(&mut stdout, app.graphics_protocol)?,
+        }
+    }
+
+    let placements: Vec<Placement> = wanted
+        .iter()
+        .filter_map(|(path, rect)| {
+            app.thumbnails.get(path).map(|thumbnail| Placement {
+                x: rect.x,
+                y: rect.y,
+                cols: rect.width,
+                rows: rect.height,
+                thumbnail,
+            })
+        })
+        .collect();
+    if !placements.is_empty() {
+        thumbnails::write_graphics(&mut stdout, app.graphics_protocol, &placements, cell_pixel_size())?;
+    }
+
+    *shown = wanted;
+    Ok(())
+}
+
+/// Size of one terminal cell in pixels, assuming 8x16 if the terminal won't say
+fn cell_pixel_size() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns) as u32,
+            (size.height / size.rows) as u32,
+        ),
+        _ => (8, 16),
+    }
+}
+
+/// Run a shell command in the foreground, handing it the terminal until it exits
+fn run_external_command<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    command: &str,
+) -> anyhow::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+
+    let status = if cfg!(windows) {
+        std::process::Command::new("cmd").args(["/C", command]).status()
+    } else {
+        std::process::Command::new("sh").args(["-c", command]).status()
+    };
+
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => app.error = Some(format!("'{}' exited with {}", command, status)),
+        Err(e) => app.error = Some(format!("Failed to run '{}': {}", command, e)),
+    }
+    Ok(())
+}
+
 fn handle_input(app: &mut App, key: event::KeyEvent) -> anyhow::Result<()> {
     // Don't clear messages for input mode
     if !matches!(app.mode, AppMode::Input(_)) {
@@ -106,6 +273,13 @@ fn handle_input(app: &mut App, key: event::KeyEvent) -> anyhow::Result<()> {
                 app.input_buffer.pop();
                 return Ok(());
             }
+            KeyCode::Tab if matches!(input_mode, InputMode::GoToPath | InputMode::MoveTo | InputMode::CollectResults | InputMode::SearchIn) => {
+                let base = app.get_current_workspace().current_dir.clone();
+                if let Some(completed) = fileops::complete_path(&app.input_buffer, &base) {
+                    app.input_buffer = completed;
+                }
+                return Ok(());
+            }
             KeyCode::Enter => {
                 let input = app.input_buffer.clone();
                 app.input_buffer.clear();
@@ -127,12 +301,28 @@ fn handle_input(app: &mut App, key: event::KeyEvent) -> anyhow::Result<()> {
                     InputMode::AddBookmark => {
                         app.add_bookmark(input)?;
                     }
+                    InputMode::EditSetting => {
+                        app.update_setting(&input)?;
+                    }
+                    InputMode::ChangeMode => app.preview_permission_change(&input, false),
+                    InputMode::ChangeOwner => app.preview_permission_change(&input, true),
+                    InputMode::CollectResults => {
+                        app.mode = AppMode::Search;
+                        app.collect_search_results(&input);
+                    }
+                    InputMode::SequenceRename => app.preview_sequence_rename(&input),
+                    InputMode::MoveTo => app.move_selected(&input)?,
+                    InputMode::SearchIn => app.start_search_in(&input),
                 }
                 return Ok(());
             }
             KeyCode::Esc => {
                 app.input_buffer.clear();
-                app.mode = AppMode::Normal;
+                app.mode = match input_mode {
+                    InputMode::EditSetting => AppMode::Settings,
+                    InputMode::CollectResults => AppMode::Search,
+                    _ => AppMode::Normal,
+                };
                 return Ok(());
             }
             _ => return Ok(()),
@@ -142,6 +332,38 @@ fn handle_input(app: &mut App, key: event::KeyEvent) -> anyhow::Result<()> {
     // Handle search mode
     if matches!(app.mode, AppMode::Search) {
         match key.code {
+            // Alt+1..6 narrow the results to a category, Alt+0 clears
+            KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::ALT) && c.is_ascii_digit() => {
+                match c.to_digit(10).unwrap_or(0) as usize {
+                    0 => app.clear_search_categories(),
+                    n => {
+                        if let Some(category) = FileCategory::ALL.get(n - 1) {
+                            app.toggle_search_category(*category);
+                        }
+                    }
+                }
+                return Ok(());
+            }
+            KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.toggle_search_grouping();
+                return Ok(());
+            }
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.start_collect_results();
+                return Ok(());
+            }
+            KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.open_search_actions();
+                return Ok(());
+            }
+            KeyCode::Up => {
+                app.search_move(-1);
+                return Ok(());
+            }
+            KeyCode::Down => {
+                app.search_move(1);
+                return Ok(());
+            }
             KeyCode::Char(c) => {
                 app.add_search_char(c);
                 return Ok(());
@@ -150,20 +372,44 @@ fn handle_input(app: &mut App, key: event::KeyEvent) -> anyhow::Result<()> {
                 app.remove_search_char();
                 return Ok(());
             }
+            // The first ESC stops a running whole-drive search and keeps its results
+            KeyCode::Esc if app.search_engine.drive_search.is_some() => {
+                app.stop_drive_search();
+                return Ok(());
+            }
             KeyCode::Esc => {
                 app.cancel_search();
                 return Ok(());
             }
+            KeyCode::Tab => {
+                app.cycle_search_scope();
+                return Ok(());
+            }
             KeyCode::Enter => {
-                if !app.search_engine.results.is_empty() {
-                    app.navigate_to_search_result(0)?;
-                }
+                app.submit_search()?;
                 return Ok(());
             }
             _ => return Ok(()),
         }
     }
 
+    // Handle the actions menu of a search result
+    if matches!(app.mode, AppMode::SearchActions) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.search_actions_move(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.search_actions_move(1),
+            KeyCode::Enter => app.run_search_action(None)?,
+            KeyCode::Esc | KeyCode::Char('q') => app.close_search_actions(),
+            KeyCode::Char(c) => {
+                if let Some(action) = ResultAction::ALL.into_iter().find(|action| action.key() == c) {
+                    app.run_search_action(Some(action))?;
+                }
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
     // Handle command palette
     if matches!(app.mode, AppMode::CommandPalette) {
         match key.code {
@@ -204,23 +450,407 @@ fn handle_input(app: &mut App, key: event::KeyEvent) -> anyhow::Result<()> {
         }
     }
 
+    // Handle theme gallery
+    if matches!(app.mode, AppMode::ThemeGallery) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.theme_gallery_prev(),
+            KeyCode::Down | KeyCode::Char('j') => app.theme_gallery_next(),
+            KeyCode::Enter => app.apply_gallery_theme()?,
+            KeyCode::Esc | KeyCode::Char('q') => app.close_theme_gallery(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Handle recently-modified files
+    if matches!(app.mode, AppMode::RecentFiles) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.recent_prev(),
+            KeyCode::Down | KeyCode::Char('j') => app.recent_next(),
+            KeyCode::Enter => app.activate_recent_file()?,
+            KeyCode::Char('r') => app.reveal_recent_file()?,
+            KeyCode::Esc | KeyCode::Char('q') => app.close_recent_files(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Handle diff overlay
+    if matches!(app.mode, AppMode::Diff) {
+        let page = app.viewport_height.max(2) as isize - 2;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.diff_scroll_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.diff_scroll_by(1),
+            KeyCode::PageUp => app.diff_scroll_by(-page),
+            KeyCode::PageDown => app.diff_scroll_by(page),
+            KeyCode::Char('s') => app.toggle_diff_layout(),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_diff(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Handle drives and places
+    if matches!(app.mode, AppMode::Places) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.places_prev(),
+            KeyCode::Down | KeyCode::Char('j') => app.places_next(),
+            KeyCode::Enter => app.open_selected_place()?,
+            KeyCode::Char('r') => app.refresh_places(),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_places(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Disk usage: ESC stops a running scan first, then closes
+    if matches!(app.mode, AppMode::DiskUsage) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.usage_move(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.usage_move(1),
+            KeyCode::PageUp => app.usage_move(-10),
+            KeyCode::PageDown => app.usage_move(10),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => app.usage_enter(),
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => app.usage_up(),
+            KeyCode::Char('r') => app.usage_rescan(),
+            KeyCode::Char('o') => app.usage_reveal()?,
+            KeyCode::Esc if app.cancel_usage_scan() => {}
+            KeyCode::Esc | KeyCode::Char('q') => app.close_disk_usage(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Handle the background jobs overlay
+    if matches!(app.mode, AppMode::Jobs) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.jobs_prev(),
+            KeyCode::Down | KeyCode::Char('j') => app.jobs_next(),
+            KeyCode::Char('x') | KeyCode::Delete => app.cancel_selected_job(),
+            KeyCode::Char('X') => app.cancel_all_jobs(),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_jobs(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if matches!(app.mode, AppMode::Paths) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.paths_prev(),
+            KeyCode::Down | KeyCode::Char('j') => app.paths_next(),
+            KeyCode::Enter => app.reveal_selected_app_path()?,
+            KeyCode::Char('o') => app.open_selected_app_path(),
+            KeyCode::Char('y') => app.yank_selected_app_path(),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_paths(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Quick look: a search being typed takes every key until Enter or ESC
+    if matches!(app.mode, AppMode::QuickLook) {
+        let page = app.viewport_height.max(6) - 5;
+        let look = match app.quick_look.as_mut() {
+            Some(look) => look,
+            None => return Ok(()),
+        };
+        if look.searching {
+            match key.code {
+                KeyCode::Enter => look.searching = false,
+                KeyCode::Esc => {
+                    look.searching = false;
+                    look.search("");
+                }
+                KeyCode::Backspace => {
+                    let mut query = look.query.clone();
+                    query.pop();
+                    look.search(&query);
+                }
+                KeyCode::Char(c) => {
+                    let query = format!("{}{}", look.query, c);
+                    look.search(&query);
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => look.scroll_by(-1, page),
+            KeyCode::Down | KeyCode::Char('j') => look.scroll_by(1, page),
+            KeyCode::PageUp => look.scroll_by(-(page as isize), page),
+            KeyCode::PageDown | KeyCode::Char(' ') => look.scroll_by(page as isize, page),
+            KeyCode::Home | KeyCode::Char('g') => look.scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => look.scroll_to_end(page),
+            KeyCode::Char('/') => {
+                look.searching = true;
+                look.search("");
+            }
+            KeyCode::Char('n') => look.next_match(true),
+            KeyCode::Char('N') => look.next_match(false),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('P') => app.close_quick_look(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Handle folding in the structured preview
+    if matches!(app.mode, AppMode::StructuredPreview) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.structured_move(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.structured_move(1),
+            KeyCode::PageUp => app.structured_move(-10),
+            KeyCode::PageDown => app.structured_move(10),
+            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Tab => app.structured_fold(None),
+            KeyCode::Right | KeyCode::Char('l') => app.structured_fold(Some(true)),
+            KeyCode::Left | KeyCode::Char('h') => app.structured_fold(Some(false)),
+            KeyCode::Char('E') => app.structured_expand_all(),
+            KeyCode::Char('C') => app.structured_collapse_all(),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('z') => app.close_structured_view(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Handle trash browser
+    if matches!(app.mode, AppMode::Trash) {
+        if app.pending_trash_action.is_some() {
+            match key.code {
+                KeyCode::Char('y') => app.confirm_trash_action(),
+                _ => app.cancel_trash_action(),
+            }
+            return Ok(());
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.trash_prev(),
+            KeyCode::Down | KeyCode::Char('j') => app.trash_next(),
+            KeyCode::Enter | KeyCode::Char('r') => app.restore_selected_trash()?,
+            KeyCode::Char('d') | KeyCode::Delete => app.request_trash_action(TrashAction::DeleteSelected),
+            KeyCode::Char('E') => app.request_trash_action(TrashAction::Empty),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_trash(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Handle plugin manager
+    if matches!(app.mode, AppMode::PluginManager) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.plugin_prev(),
+            KeyCode::Down | KeyCode::Char('j') => app.plugin_next(),
+            KeyCode::Enter | KeyCode::Char('e') | KeyCode::Char(' ') => app.toggle_selected_plugin(),
+            KeyCode::Char('r') => app.reload_selected_plugin(),
+            KeyCode::Esc | KeyCode::Char('q') => app.close_plugin_manager(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Handle settings screen
+    if matches!(app.mode, AppMode::Settings) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.settings_prev(),
+            KeyCode::Down | KeyCode::Char('j') => app.settings_next(),
+            KeyCode::Enter | KeyCode::Char(' ') => app.activate_setting()?,
+            KeyCode::Esc | KeyCode::Char('q') => app.close_settings(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Handle quit confirmation while jobs are running
+    if matches!(app.mode, AppMode::ConfirmQuit) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('q') => app.force_quit(),
+            KeyCode::Char('w') => app.quit_after_jobs(),
+            KeyCode::Esc | KeyCode::Char('n') => app.cancel_quit(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // The last run didn't quit cleanly; offer its tabs back
+    if matches!(app.mode, AppMode::ConfirmRestore) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => app.restore_session()?,
+            KeyCode::Esc | KeyCode::Char('n') => app.discard_recovered_session(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Replacing an existing media info sidecar or rename target needs a yes
+    if matches!(app.mode, AppMode::ConfirmOverwrite) {
+        match key.code {
+            KeyCode::Char('y') => app.confirm_overwrite()?,
+            KeyCode::Esc | KeyCode::Char('n') => app.cancel_overwrite(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // A paste stopped at a name that is already taken
+    if matches!(app.mode, AppMode::ConfirmCollision) {
+        let choice = match key.code {
+            KeyCode::Char(c) => match c.to_ascii_lowercase() {
+                'o' => Some(CollisionStrategy::Overwrite),
+                'r' => Some(CollisionStrategy::Rename),
+                's' => Some(CollisionStrategy::Skip),
+                _ => None,
+            },
+            _ => None,
+        };
+        match (key.code, choice) {
+            // Upper case answers for the rest of the batch too
+            (KeyCode::Char(c), Some(choice)) => app.resolve_collision(choice, c.is_ascii_uppercase())?,
+            (KeyCode::Esc, _) => app.cancel_paste()?,
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // A chmod/chown is listed first and only applied on a yes
+    if matches!(app.mode, AppMode::ConfirmPermissions) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => app.confirm_permission_change()?,
+            KeyCode::Esc | KeyCode::Char('n') => app.cancel_permission_change(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if matches!(app.mode, AppMode::ConfirmSequenceRename) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => app.confirm_sequence_rename()?,
+            KeyCode::Char('o') => app.toggle_sequence_order(),
+            KeyCode::Esc | KeyCode::Char('n') => app.cancel_sequence_rename(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Type-to-jump: letters build the name prefix; any other key leaves the mode and
+    // then does what it usually does
+    if matches!(app.mode, AppMode::Jump) {
+        let ctrl = key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char(c) if !ctrl => {
+                app.jump_key(c, Instant::now());
+                return Ok(());
+            }
+            KeyCode::Backspace => {
+                app.jump_backspace(Instant::now());
+                return Ok(());
+            }
+            KeyCode::Esc => {
+                app.cancel_jump();
+                return Ok(());
+            }
+            KeyCode::Enter => {
+                app.cancel_jump();
+                return app.enter_selected();
+            }
+            _ => app.cancel_jump(),
+        }
+    }
+
+    if matches!(app.mode, AppMode::ConfirmFlatten) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => app.confirm_flatten()?,
+            KeyCode::Esc | KeyCode::Char('n') => app.cancel_flatten(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    // Built-in editor: every printable key is text, so commands use Ctrl
+    if matches!(app.mode, AppMode::Edit) {
+        let page = app.viewport_height.max(4) as isize - 4;
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('s') if ctrl => return app.save_editor(),
+            KeyCode::Esc => {
+                app.close_editor();
+                return Ok(());
+            }
+            _ => {}
+        }
+        if let Some(editor) = app.editor.as_mut() {
+            match key.code {
+                KeyCode::Char(c) if !ctrl => editor.insert_char(c),
+                KeyCode::Tab => editor.insert_char('\t'),
+                KeyCode::Enter => editor.insert_newline(),
+                KeyCode::Backspace => editor.backspace(),
+                KeyCode::Delete => editor.delete(),
+                KeyCode::Left => editor.move_left(),
+                KeyCode::Right => editor.move_right(),
+                KeyCode::Up => editor.move_lines(-1),
+                KeyCode::Down => editor.move_lines(1),
+                KeyCode::PageUp => editor.move_lines(-page),
+                KeyCode::PageDown => editor.move_lines(page),
+                KeyCode::Home => editor.line_start(),
+                KeyCode::End => editor.line_end(),
+                _ => {}
+            }
+        }
+        return Ok(());
+    }
+
     // Handle help mode
     if matches!(app.mode, AppMode::Help) {
         app.mode = AppMode::Normal;
         return Ok(());
     }
 
+    // Digits before a movement repeat it (`5j`); any other key drops the count
+    if let KeyCode::Char(c) = key.code {
+        if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) && app.push_count_digit(c) {
+            return Ok(());
+        }
+    }
+    let count = app.take_count();
+
+    // Gallery view moves through the tile grid
+    if app.gallery_view {
+        let columns = app.gallery_columns as isize;
+        let delta = match key.code {
+            KeyCode::Left | KeyCode::Char('h') => Some(-1),
+            KeyCode::Right | KeyCode::Char('l') => Some(1),
+            KeyCode::Up | KeyCode::Char('k') => Some(-columns),
+            KeyCode::Down | KeyCode::Char('j') => Some(columns),
+            _ => None,
+        };
+        if let Some(delta) = delta {
+            app.gallery_move(delta, count);
+            return Ok(());
+        }
+    }
+
+    // Chords like `g g` take their keys before the single-key bindings
+    if let KeyCode::Char(c) = key.code {
+        let plain = !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if plain && app.press_chord_key(c)? {
+            return Ok(());
+        }
+    }
+
+    // Keys shared with the basic key map
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    if input::handle_key_event(key, false) == input::Action::Refresh || (ctrl && key.code == KeyCode::Char('r')) {
+        return app.refresh();
+    }
+
     // Handle normal mode navigation and actions
     match key.code {
         // Navigation
-        KeyCode::Up | KeyCode::Char('k') => app.move_up(),
-        KeyCode::Down | KeyCode::Char('j') => app.move_down(),
+        KeyCode::Up | KeyCode::Char('k') => app.move_up_by(count),
+        KeyCode::Down | KeyCode::Char('j') => app.move_down_by(count),
         KeyCode::PageUp => app.page_up(),
         KeyCode::PageDown => app.page_down(),
         KeyCode::Home => app.go_home(),
         KeyCode::End => app.go_end(),
         
         // Enter/Open
+        KeyCode::Enter if key.modifiers.contains(KeyModifiers::CONTROL) => app.open_selected_in_new_tab()?,
         KeyCode::Enter | KeyCode::Right => {
             app.enter_selected()?;
         }
@@ -234,29 +864,65 @@ fn handle_input(app: &mut App, key: event::KeyEvent) -> anyhow::Result<()> {
         KeyCode::Char('n') => app.mode = AppMode::Input(InputMode::CreateFile),
         KeyCode::Char('N') => app.mode = AppMode::Input(InputMode::CreateDirectory),
         KeyCode::Char('d') => app.delete_selected()?,
+        KeyCode::Char('U') => app.undo_delete()?,
         KeyCode::Char('r') => app.mode = AppMode::Input(InputMode::Rename),
+        KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => app.paste()?,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::ALT) => app.open_bookmark_picker(TransferKind::Copy),
+        KeyCode::Char('m') if key.modifiers.contains(KeyModifiers::ALT) => app.open_bookmark_picker(TransferKind::Move),
         KeyCode::Char('c') => app.copy_selected()?,
+        KeyCode::Char('C') => app.duplicate_selected()?,
+        KeyCode::Char('m') => app.cut_selected(),
+        KeyCode::Char('I') => app.export_selected_media_info()?,
+        KeyCode::Char('H') => app.hash_selected(),
+        KeyCode::Char('Y') => app.copy_selected_hash(),
+        KeyCode::Char('y') => app.copy_repo_relative_path(),
+        KeyCode::Char(' ') => app.toggle_mark(),
+        KeyCode::Char('a') => app.select_all(),
+        KeyCode::Char('*') => app.invert_selection(),
+        KeyCode::Char('K') => app.move_selected_entry(-1),
+        KeyCode::Char('J') => app.move_selected_entry(1),
+        KeyCode::Char('D') => app.open_diff(),
+        KeyCode::Char('e') => app.open_editor(),
+        KeyCode::Char('M') => app.open_places(),
+        KeyCode::Char('O') => app.open_recently_opened(),
+        KeyCode::Char('o') => app.open_jobs(),
+        KeyCode::Char('S') => app.toggle_sidebar(),
+        KeyCode::Char('<') => app.resize_preview(-1),
+        KeyCode::Char('>') => app.resize_preview(1),
+        KeyCode::Char('{') => app.resize_sidebar(-1),
+        KeyCode::Char('}') => app.resize_sidebar(1),
+        KeyCode::Char('=') => app.reset_layout(),
+        KeyCode::Char(c) if c.is_ascii_digit() && app.user_settings.show_sidebar => app.sidebar_jump(c)?,
         
         // Search
         KeyCode::Char('/') => app.start_search(),
+        KeyCode::Char('f') => app.start_jump(),
         KeyCode::Char('.') => app.toggle_hidden()?,
+        KeyCode::Char('#') => app.toggle_dir_counts()?,
+        KeyCode::Char('s') => app.cycle_sort()?,
+        KeyCode::Char('u') => app.open_disk_usage(),
+        KeyCode::Char('z') => app.open_structured_view(),
+        KeyCode::Char('R') => app.toggle_render_mode(),
+        KeyCode::Char('P') => app.open_quick_look(),
+        KeyCode::Char('v') => app.toggle_gallery_view(),
         
         // Workspaces
         KeyCode::Char('t') => app.new_workspace()?,
+        KeyCode::Char('T') => app.open_selected_in_new_tab()?,
         KeyCode::Char('w') => app.close_workspace()?,
         KeyCode::Char(']') => app.next_workspace(),
         KeyCode::Char('[') => app.prev_workspace(),
         
         // Bookmarks
-        KeyCode::Char('b') => app.mode = AppMode::Input(InputMode::AddBookmark),
+        KeyCode::Char('b') => app.start_add_bookmark(),
         
         // Command Palette
         KeyCode::Char('p') => app.start_command_palette(),
         
         // System
         KeyCode::Char('?') => app.mode = AppMode::Help,
-        KeyCode::Char('q') => app.quit(),
-        KeyCode::Esc => {} // Just cancel any selection
+        KeyCode::Char('q') => app.request_quit(),
+        KeyCode::Esc => app.clear_marks(),
         
         _ => {}
     }
@@ -0,0 +1,464 @@
+// Markup previews - Markdown and HTML rendered as styled text
+use html2text::render::text_renderer::{RichAnnotation, TaggedLine, TextDecorator};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::path::Path;
+
+/// Width of the line drawn for `---`
+const RULE_WIDTH: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Markup {
+    Markdown,
+    Html,
+}
+
+/// Whether a markup preview shows the formatted text or the highlighted source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    Rendered,
+    Raw,
+}
+
+impl RenderMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            RenderMode::Rendered => RenderMode::Raw,
+            RenderMode::Raw => RenderMode::Rendered,
+        }
+    }
+}
+
+impl Markup {
+    /// The markup a file's extension names, if it's one that can be rendered
+    pub fn of(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "md" | "markdown" | "mdown" | "mkd" => Some(Markup::Markdown),
+            "html" | "htm" | "xhtml" => Some(Markup::Html),
+            _ => None,
+        }
+    }
+
+    /// How a file is shown until toggled: Markdown rendered, HTML as source
+    pub fn default_mode(self) -> RenderMode {
+        match self {
+            Markup::Markdown => RenderMode::Rendered,
+            Markup::Html => RenderMode::Raw,
+        }
+    }
+
+    pub fn render(self, content: &str, max_lines: usize) -> Vec<Line<'static>> {
+        match self {
+            Markup::Markdown => render_markdown(content, max_lines),
+            Markup::Html => render_html(content, max_lines),
+        }
+    }
+}
+
+fn heading_style(level: usize) -> Style {
+    let style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    if level == 1 {
+        style.add_modifier(Modifier::UNDERLINED)
+    } else {
+        style
+    }
+}
+
+fn code_style() -> Style {
+    Style::default().fg(Color::Yellow)
+}
+
+fn rule() -> Line<'static> {
+    Line::styled("─".repeat(RULE_WIDTH), Style::default().fg(Color::DarkGray))
+}
+
+/// Markdown as styled lines: headings, lists, task boxes, quotes, rules,
+/// tables, fenced code and inline emphasis, code and links. Markers are
+/// dropped, text is left unwrapped.
+pub fn render_markdown(content: &str, max_lines: usize) -> Vec<Line<'static>> {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+    let mut out = MarkdownLines { max_lines, ..Default::default() };
+    for event in Parser::new_ext(content, options) {
+        if out.lines.len() >= max_lines {
+            break;
+        }
+        out.event(event);
+    }
+    out.end_line();
+    while out.lines.last().is_some_and(|line| line.spans.is_empty()) {
+        out.lines.pop();
+    }
+    out.lines.truncate(max_lines);
+    out.lines
+}
+
+/// Lines of rendered Markdown being built
+#[derive(Default)]
+struct MarkdownLines {
+    lines: Vec<Line<'static>>,
+    /// The line being built
+    spans: Vec<Span<'static>>,
+    max_lines: usize,
+    bold: usize,
+    italic: usize,
+    strike: usize,
+    heading: Option<usize>,
+    quote: usize,
+    code_block: bool,
+    /// Target and label so far of the link being read
+    link: Option<(String, String)>,
+    image: bool,
+    /// Open lists, with the next number for ordered ones
+    lists: Vec<Option<u64>>,
+    /// Bullet or number to put before the next text of a list item
+    marker: Option<String>,
+}
+
+impl MarkdownLines {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.code_block => {
+                for line in text.lines() {
+                    self.push(Span::styled("│ ", Style::default().fg(Color::DarkGray)));
+                    self.push(Span::styled(line.to_string(), code_style()));
+                    self.end_line();
+                }
+            }
+            Event::Text(text) => {
+                if let Some((_, label)) = &mut self.link {
+                    label.push_str(&text);
+                }
+                self.push(Span::styled(text.to_string(), self.style()));
+            }
+            Event::Code(code) => self.push(Span::styled(code.to_string(), code_style())),
+            Event::Html(html) | Event::InlineHtml(html) => {
+                for (i, line) in html.lines().enumerate() {
+                    if i > 0 {
+                        self.end_line();
+                    }
+                    self.push(Span::styled(line.to_string(), Style::default().fg(Color::DarkGray)));
+                }
+            }
+            Event::FootnoteReference(name) => self.push(Span::raw(format!("[{}]", name))),
+            Event::SoftBreak => self.push(Span::styled(" ", self.style())),
+            Event::HardBreak => self.break_line(),
+            Event::Rule => {
+                self.end_line();
+                self.lines.push(rule());
+                self.end_block();
+            }
+            Event::TaskListMarker(done) => self.marker = Some(if done { "☑" } else { "☐" }.to_string()),
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.end_line();
+                self.heading = Some(level as usize);
+            }
+            Tag::BlockQuote => {
+                self.end_line();
+                self.quote += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.end_line();
+                self.code_block = true;
+            }
+            Tag::List(first) => {
+                self.end_line();
+                self.lists.push(first);
+            }
+            Tag::Item => {
+                self.end_line();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => "•".to_string(),
+                };
+                self.marker = Some(marker);
+            }
+            Tag::TableCell => {
+                let border = if self.spans.is_empty() { "│ " } else { " │ " };
+                self.push(Span::styled(border, Style::default().fg(Color::DarkGray)));
+            }
+            Tag::TableHead => self.bold += 1,
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Strikethrough => self.strike += 1,
+            Tag::Link { dest_url, .. } => self.link = Some((dest_url.to_string(), String::new())),
+            Tag::Image { .. } => {
+                self.image = true;
+                self.push(Span::styled("🖼 ", self.style()));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::HtmlBlock => {
+                self.end_line();
+                self.end_block();
+            }
+            TagEnd::Heading(_) => {
+                self.end_line();
+                self.heading = None;
+                self.end_block();
+            }
+            TagEnd::BlockQuote => {
+                self.end_line();
+                self.quote -= 1;
+                self.end_block();
+            }
+            TagEnd::CodeBlock => {
+                self.code_block = false;
+                self.end_block();
+            }
+            TagEnd::List(_) => {
+                self.end_line();
+                self.lists.pop();
+                self.end_block();
+            }
+            TagEnd::Item => self.end_line(),
+            TagEnd::TableHead | TagEnd::TableRow => {
+                if tag == TagEnd::TableHead {
+                    self.bold -= 1;
+                }
+                self.push(Span::styled(" │", Style::default().fg(Color::DarkGray)));
+                self.end_line();
+            }
+            TagEnd::Table => self.end_block(),
+            TagEnd::Emphasis => self.italic -= 1,
+            TagEnd::Strong => self.bold -= 1,
+            TagEnd::Strikethrough => self.strike -= 1,
+            TagEnd::Link => {
+                if let Some((target, label)) = self.link.take() {
+                    if target != label {
+                        self.push(Span::styled(format!(" ({})", target), Style::default().fg(Color::DarkGray)));
+                    }
+                }
+            }
+            TagEnd::Image => self.image = false,
+            _ => {}
+        }
+    }
+
+    fn style(&self) -> Style {
+        let mut style = match self.heading {
+            Some(level) => heading_style(level),
+            None => Style::default(),
+        };
+        if self.bold > 0 {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        if self.italic > 0 || self.quote > 0 {
+            style = style.add_modifier(Modifier::ITALIC);
+        }
+        if self.strike > 0 {
+            style = style.add_modifier(Modifier::CROSSED_OUT);
+        }
+        if self.image {
+            style = style.fg(Color::Magenta);
+        } else if self.link.is_some() {
+            style = style.fg(Color::Blue).add_modifier(Modifier::UNDERLINED);
+        }
+        style
+    }
+
+    /// Add to the line being built, starting it with the quote bars and list
+    /// marker it needs
+    fn push(&mut self, span: Span<'static>) {
+        if self.spans.is_empty() {
+            for _ in 0..self.quote {
+                self.spans.push(Span::styled("│ ", Style::default().fg(Color::DarkGray)));
+            }
+            if let Some(marker) = self.marker.take() {
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                self.spans.push(Span::raw(format!("{}{} ", indent, marker)));
+            } else if !self.lists.is_empty() {
+                self.spans.push(Span::raw("  ".repeat(self.lists.len())));
+            }
+        }
+        self.spans.push(span);
+    }
+
+    /// Finish the line if anything is on it
+    fn end_line(&mut self) {
+        if !self.spans.is_empty() {
+            self.break_line();
+        }
+    }
+
+    /// Finish the line, even an empty one
+    fn break_line(&mut self) {
+        if self.lines.len() < self.max_lines {
+            self.lines.push(Line::from(std::mem::take(&mut self.spans)));
+        }
+        self.spans.clear();
+    }
+
+    /// A blank line after a top-level block
+    fn end_block(&mut self) {
+        if self.lists.is_empty() && self.quote == 0 && self.lines.last().is_some_and(|line| !line.spans.is_empty()) {
+            self.break_line();
+        }
+    }
+}
+
+/// Width HTML text is wrapped to
+const HTML_WIDTH: usize = 100;
+
+/// HTML as styled lines: text with tags and comments dropped, block elements on
+/// their own lines, inline emphasis, code and links styled, scripts and styles
+/// left out. Text is wrapped at `HTML_WIDTH` columns.
+pub fn render_html(content: &str, max_lines: usize) -> Vec<Line<'static>> {
+    let lines = match html2text::config::with_decorator(TerminalDecorator).lines_from_read(content.as_bytes(), HTML_WIDTH) {
+        Ok(lines) => lines,
+        Err(_) => return content.lines().take(max_lines).map(|line| Line::from(line.to_string())).collect(),
+    };
+    lines
+        .into_iter()
+        .take(max_lines)
+        .map(|line| {
+            let spans: Vec<Span<'static>> = line
+                .into_tagged_strings()
+                .map(|piece| Span::styled(piece.s.trim_end_matches('\n').to_string(), annotation_style(&piece.tag)))
+                .collect();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn annotation_style(annotations: &[RichAnnotation]) -> Style {
+    annotations.iter().fold(Style::default(), |style, annotation| match annotation {
+        RichAnnotation::Strong => style.add_modifier(Modifier::BOLD),
+        RichAnnotation::Emphasis => style.add_modifier(Modifier::ITALIC),
+        RichAnnotation::Strikeout => style.add_modifier(Modifier::CROSSED_OUT),
+        RichAnnotation::Code | RichAnnotation::Preformat(_) => style.patch(code_style()),
+        RichAnnotation::Link(_) => style.fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+        RichAnnotation::Image(_) => style.fg(Color::Magenta),
+        _ => style,
+    })
+}
+
+/// Decorates HTML text with styles only, leaving out the Markdown-like markers
+/// html2text's own rich decorator adds
+#[derive(Clone, Copy)]
+struct TerminalDecorator;
+
+impl TextDecorator for TerminalDecorator {
+    type Annotation = RichAnnotation;
+
+    fn decorate_link_start(&mut self, url: &str) -> (String, RichAnnotation) {
+        (String::new(), RichAnnotation::Link(url.to_string()))
+    }
+    fn decorate_link_end(&mut self) -> String {
+        String::new()
+    }
+    fn decorate_em_start(&self) -> (String, RichAnnotation) {
+        (String::new(), RichAnnotation::Emphasis)
+    }
+    fn decorate_em_end(&self) -> String {
+        String::new()
+    }
+    fn decorate_strong_start(&self) -> (String, RichAnnotation) {
+        (String::new(), RichAnnotation::Strong)
+    }
+    fn decorate_strong_end(&self) -> String {
+        String::new()
+    }
+    fn decorate_strikeout_start(&self) -> (String, RichAnnotation) {
+        (String::new(), RichAnnotation::Strikeout)
+    }
+    fn decorate_strikeout_end(&self) -> String {
+        String::new()
+    }
+    fn decorate_code_start(&self) -> (String, RichAnnotation) {
+        (String::new(), RichAnnotation::Code)
+    }
+    fn decorate_code_end(&self) -> String {
+        String::new()
+    }
+    fn decorate_preformat_first(&self) -> RichAnnotation {
+        RichAnnotation::Preformat(false)
+    }
+    fn decorate_preformat_cont(&self) -> RichAnnotation {
+        RichAnnotation::Preformat(true)
+    }
+    fn decorate_image(&mut self, _src: &str, title: &str) -> (String, RichAnnotation) {
+        (format!("🖼 {}", title), RichAnnotation::Image(String::new()))
+    }
+    fn header_prefix(&self, _level: usize) -> String {
+        String::new()
+    }
+    fn quote_prefix(&self) -> String {
+        "│ ".to_string()
+    }
+    fn unordered_item_prefix(&self) -> String {
+        "• ".to_string()
+    }
+    fn ordered_item_prefix(&self, i: i64) -> String {
+        format!("{}. ", i)
+    }
+    fn make_subblock_decorator(&self) -> Self {
+        *self
+    }
+    fn finalise(&mut self, _links: Vec<String>) -> Vec<TaggedLine<RichAnnotation>> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_markdown_drops_markers_and_styles_text() {
+        let source = "# Title\n\nSome **bold** and `code`, see [docs](https://x.io).\n\n- [x] done\n\n2. second\n\n> quoted\n\n---\n\n```rust\nlet a = 1;\n```";
+        let lines = render_markdown(source, 50);
+        let texts: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(
+            texts,
+            [
+                "Title",
+                "",
+                "Some bold and code, see docs (https://x.io).",
+                "",
+                "☑ done",
+                "",
+                "2. second",
+                "",
+                "│ quoted",
+                "",
+                &"─".repeat(RULE_WIDTH),
+                "",
+                "│ let a = 1;",
+            ]
+        );
+        assert!(lines[0].spans[0].style.add_modifier.contains(Modifier::BOLD));
+        let bold = lines[2].spans.iter().find(|s| s.content == "bold").unwrap();
+        assert!(bold.style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(render_markdown(source, 3).len(), 3);
+        assert_eq!(text(&render_markdown("snake_case_name and \\*stars\\*", 5)[0]), "snake_case_name and *stars*");
+    }
+
+    #[test]
+    fn test_html_keeps_text_and_blocks() {
+        let source = "<html><head><title>T</title><style>p{}</style></head><body>\n<h1>Hello</h1>\n<p>Fish &amp; <strong>chips</strong><br>twice</p><!-- note --><script>x()</script><ul><li>one</li><li>two</li></ul></body></html>";
+        let lines = render_html(source, 50);
+        let texts: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(texts, ["Hello", "", "Fish & chips", "twice", "", "• one", "• two"]);
+        assert!(lines[2].spans.iter().any(|s| s.content == "chips" && s.style.add_modifier.contains(Modifier::BOLD)));
+    }
+}
@@ -1,6 +1,11 @@
 // Media Preview - Handle image, audio, and video metadata
-use anyhow::Result;
-use std::path::Path;
+use anyhow::{anyhow, Result};
+use chrono::NaiveDateTime;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// How much of a photo is read looking for its EXIF block
+const EXIF_SCAN_LIMIT: u64 = 256 * 1024;
 
 #[derive(Clone, Debug)]
 pub enum MediaType {
@@ -330,6 +335,93 @@ pub fn generate_media_preview(path: &Path) -> Result<String> {
     Ok(preview)
 }
 
+/// When a photo was taken: the EXIF `DateTimeOriginal` of a JPEG or TIFF,
+/// else its `DateTime`. None for other files and photos without either.
+pub fn capture_time(path: &Path) -> Option<NaiveDateTime> {
+    let mut head = Vec::new();
+    std::fs::File::open(path).ok()?.take(EXIF_SCAN_LIMIT).read_to_end(&mut head).ok()?;
+    if head.starts_with(&[0xFF, 0xD8]) {
+        exif_date(jpeg_exif(&head)?)
+    } else {
+        exif_date(&head)
+    }
+}
+
+/// The TIFF data inside a JPEG's APP1 "Exif" segment
+fn jpeg_exif(data: &[u8]) -> Option<&[u8]> {
+    let mut pos = 2;
+    while pos + 4 <= data.len() && data[pos] == 0xFF {
+        let marker = data[pos + 1];
+        // Start of scan or end of image: no metadata past here
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let body = data.get(pos + 4..pos + 2 + len)?;
+        if marker == 0xE1 && body.starts_with(b"Exif\0\0") {
+            return Some(&body[6..]);
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+/// Read the capture date out of TIFF-structured EXIF data
+fn exif_date(tiff: &[u8]) -> Option<NaiveDateTime> {
+    let little_endian = match tiff.get(0..4)? {
+        b"II*\0" => true,
+        b"MM\0*" => false,
+        _ => return None,
+    };
+    let u16_at = |at: usize| {
+        let b = tiff.get(at..at + 2)?;
+        Some(if little_endian { u16::from_le_bytes([b[0], b[1]]) } else { u16::from_be_bytes([b[0], b[1]]) })
+    };
+    let u32_at = |at: usize| {
+        let b: [u8; 4] = tiff.get(at..at + 4)?.try_into().ok()?;
+        Some(if little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) } as usize)
+    };
+    // Where the value of `tag` sits in the directory at `ifd`
+    let find = |ifd: usize, tag: u16| {
+        let count = u16_at(ifd)? as usize;
+        (0..count).map(|i| ifd + 2 + i * 12).find(|&entry| u16_at(entry) == Some(tag)).map(|entry| entry + 8)
+    };
+    // Dates are 20 byte strings, so the value is always an offset to them
+    let date = |value: usize| {
+        let at = u32_at(value)?;
+        let text = std::str::from_utf8(tiff.get(at..at + 19)?).ok()?;
+        NaiveDateTime::parse_from_str(text, "%Y:%m:%d %H:%M:%S").ok()
+    };
+
+    let ifd0 = u32_at(4)?;
+    find(ifd0, 0x8769)
+        .and_then(u32_at)
+        .and_then(|exif_ifd| find(exif_ifd, 0x9003))
+        .and_then(date)
+        .or_else(|| find(ifd0, 0x0132).and_then(date))
+}
+
+/// Default sidecar for exported metadata: `photo.jpg` -> `photo.jpg.txt`
+pub fn sidecar_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".txt");
+    path.with_file_name(name)
+}
+
+/// Write the metadata preview of a media file to `out`.
+/// An existing `out` is only replaced when `overwrite` is set.
+pub fn export_media_info(path: &Path, out: &Path, overwrite: bool) -> Result<()> {
+    if matches!(detect_media_type(path), MediaType::Unknown) {
+        return Err(anyhow!("Not a media file: {}", path.display()));
+    }
+    if out.exists() && !overwrite {
+        return Err(anyhow!("{} already exists", out.display()));
+    }
+    let preview = generate_media_preview(path)?;
+    std::fs::write(out, preview)?;
+    Ok(())
+}
+
 /// MediaPreview wrapper struct for integration into the App
 pub struct MediaPreview {
     last_preview_path: Option<std::path::PathBuf>,
@@ -396,6 +488,68 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_export_media_info_sidecar() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let image = dir.path().join("photo.png");
+        image::RgbImage::new(4, 3).save(&image)?;
+
+        let sidecar = sidecar_path(&image);
+        assert_eq!(sidecar, dir.path().join("photo.png.txt"));
+        export_media_info(&image, &sidecar, false)?;
+        let text = std::fs::read_to_string(&sidecar)?;
+        assert!(text.contains("Width: 4 px"));
+
+        // An existing sidecar is kept unless overwriting was confirmed
+        std::fs::write(&sidecar, "notes")?;
+        assert!(export_media_info(&image, &sidecar, false).is_err());
+        assert_eq!(std::fs::read_to_string(&sidecar)?, "notes");
+        export_media_info(&image, &sidecar, true)?;
+        assert!(std::fs::read_to_string(&sidecar)?.contains("IMAGE METADATA"));
+
+        assert!(export_media_info(&dir.path().join("a.txt"), &sidecar, true).is_err());
+        Ok(())
+    }
+
+    /// A JPEG holding only an EXIF block with `taken` as DateTimeOriginal
+    fn jpeg_with_capture_date(taken: &str) -> Vec<u8> {
+        // Big-endian TIFF: IFD0 with one entry pointing at the Exif IFD, which
+        // has one entry pointing at the date string
+        let mut tiff = b"MM\0*".to_vec();
+        tiff.extend(8u32.to_be_bytes());
+        tiff.extend(1u16.to_be_bytes());
+        tiff.extend([0x87, 0x69, 0, 4, 0, 0, 0, 1]);
+        tiff.extend(26u32.to_be_bytes());
+        tiff.extend(0u32.to_be_bytes());
+        tiff.extend(1u16.to_be_bytes());
+        tiff.extend([0x90, 0x03, 0, 2, 0, 0, 0, 20]);
+        tiff.extend(44u32.to_be_bytes());
+        tiff.extend(0u32.to_be_bytes());
+        tiff.extend(taken.as_bytes());
+        tiff.push(0);
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend(((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend(b"Exif\0\0");
+        jpeg.extend(tiff);
+        jpeg.extend([0xFF, 0xD9]);
+        jpeg
+    }
+
+    #[test]
+    fn test_capture_time_from_exif() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let photo = dir.path().join("photo.jpg");
+        std::fs::write(&photo, jpeg_with_capture_date("2021:06:05 14:30:00"))?;
+        let expected = NaiveDateTime::parse_from_str("2021-06-05 14:30:00", "%Y-%m-%d %H:%M:%S")?;
+        assert_eq!(capture_time(&photo), Some(expected));
+
+        let plain = dir.path().join("plain.png");
+        image::RgbImage::new(2, 2).save(&plain)?;
+        assert_eq!(capture_time(&plain), None);
+        Ok(())
+    }
+
     #[test]
     fn test_detect_video() {
         let path = Path::new("test.mp4");
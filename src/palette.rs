@@ -6,13 +6,17 @@ pub enum Command {
     // File operations
     Copy,
     Move,
+    Paste,
     Delete,
     Rename,
     CreateFile,
     CreateDirectory,
+    Duplicate,
     
     // Navigation
     ParentDirectory,
+    FirstEntry,
+    LastEntry,
     Home,
     Root,
     GoToPath,
@@ -24,6 +28,8 @@ pub enum Command {
     
     // Workspaces
     NewWorkspace,
+    OpenInNewTab,
+    SearchIn,
     CloseWorkspace,
     NextWorkspace,
     PrevWorkspace,
@@ -36,11 +42,51 @@ pub enum Command {
     // Preview
     TogglePreview,
     RefreshPreview,
+    Refresh,
     
     // System
     OpenWithDefault,
     ShowHelp,
     ShowSettings,
+    ThemeGallery,
+    RecentFiles,
+    RecentlyOpened,
+    ShowPlugins,
+    ReloadPlugins,
+    ShowLogPath,
+    ExportMediaInfo,
+    ToggleIgnorePatterns,
+    OpenTrash,
+    HashFile,
+    CopyHash,
+    CopyRepoPath,
+    CopyContents,
+    DiffFiles,
+    SelectAll,
+    ClearSelection,
+    InvertSelection,
+    ResetCustomOrder,
+    CycleSort,
+    CreateThemeFromTerminal,
+    FixThemeContrast,
+    NextHighlightTheme,
+    ChangeMode,
+    ChangeOwner,
+    SequenceRename,
+    Flatten,
+    EditFile,
+    ShowPlaces,
+    ShowJobs,
+    ShowPaths,
+    ToggleRender,
+    OpenConfigDir,
+    ToggleSidebar,
+    ToggleDirCounts,
+    ToggleSizeBars,
+    ToggleAgeShading,
+    DiskUsage,
+    ImportZoxide,
+    ImportAutojump,
     Quit,
     
     // Custom
@@ -51,12 +97,16 @@ impl Command {
     pub fn to_string(&self) -> String {
         match self {
             Command::Copy => "Copy".to_string(),
-            Command::Move => "Move".to_string(),
+            Command::Move => "Move To...".to_string(),
+            Command::Paste => "Paste".to_string(),
             Command::Delete => "Delete".to_string(),
             Command::Rename => "Rename".to_string(),
             Command::CreateFile => "Create File".to_string(),
             Command::CreateDirectory => "Create Directory".to_string(),
+            Command::Duplicate => "Duplicate".to_string(),
             Command::ParentDirectory => "Go to Parent".to_string(),
+            Command::FirstEntry => "Go to First Entry".to_string(),
+            Command::LastEntry => "Go to Last Entry".to_string(),
             Command::Home => "Go Home".to_string(),
             Command::Root => "Go to Root".to_string(),
             Command::GoToPath => "Go to Path".to_string(),
@@ -64,6 +114,8 @@ impl Command {
             Command::ClearSearch => "Clear Search".to_string(),
             Command::ToggleHidden => "Toggle Hidden".to_string(),
             Command::NewWorkspace => "New Workspace".to_string(),
+            Command::OpenInNewTab => "Open in New Tab".to_string(),
+            Command::SearchIn => "Search In Folder or Bookmark".to_string(),
             Command::CloseWorkspace => "Close Workspace".to_string(),
             Command::NextWorkspace => "Next Workspace".to_string(),
             Command::PrevWorkspace => "Previous Workspace".to_string(),
@@ -72,9 +124,49 @@ impl Command {
             Command::ShowBookmarks => "Show Bookmarks".to_string(),
             Command::TogglePreview => "Toggle Preview".to_string(),
             Command::RefreshPreview => "Refresh Preview".to_string(),
+            Command::Refresh => "Refresh Listing".to_string(),
             Command::OpenWithDefault => "Open with Default App".to_string(),
             Command::ShowHelp => "Help".to_string(),
             Command::ShowSettings => "Settings".to_string(),
+            Command::ThemeGallery => "Theme Gallery".to_string(),
+            Command::RecentFiles => "Recently Modified Files".to_string(),
+            Command::RecentlyOpened => "Recently Opened Files".to_string(),
+            Command::ShowPlugins => "Plugins".to_string(),
+            Command::ReloadPlugins => "Reload Plugins".to_string(),
+            Command::ShowLogPath => "Show Log File".to_string(),
+            Command::ExportMediaInfo => "Export Media Info".to_string(),
+            Command::ToggleIgnorePatterns => "Toggle Ignore Patterns".to_string(),
+            Command::OpenTrash => "Open Trash".to_string(),
+            Command::HashFile => "Compute File Hashes".to_string(),
+            Command::CopyHash => "Yank File Hash".to_string(),
+            Command::CopyRepoPath => "Yank Path From Repo Root".to_string(),
+            Command::CopyContents => "Yank File Contents".to_string(),
+            Command::DiffFiles => "Compare Files".to_string(),
+            Command::SelectAll => "Select All".to_string(),
+            Command::ClearSelection => "Select None".to_string(),
+            Command::InvertSelection => "Invert Selection".to_string(),
+            Command::ResetCustomOrder => "Reset to Automatic Sort".to_string(),
+            Command::CycleSort => "Next Sort Mode".to_string(),
+            Command::CreateThemeFromTerminal => "Create Theme from Terminal".to_string(),
+            Command::FixThemeContrast => "Fix Theme Contrast".to_string(),
+            Command::NextHighlightTheme => "Next Code Highlight Theme".to_string(),
+            Command::ChangeMode => "Change Permissions".to_string(),
+            Command::ChangeOwner => "Change Owner".to_string(),
+            Command::SequenceRename => "Sequence Rename (Photos)".to_string(),
+            Command::Flatten => "Flatten Directory".to_string(),
+            Command::EditFile => "Edit File".to_string(),
+            Command::ShowPlaces => "Drives and Places".to_string(),
+            Command::ShowJobs => "Background Jobs".to_string(),
+            Command::ShowPaths => "Show Config and Data Paths".to_string(),
+            Command::ToggleRender => "Toggle Rendered/Source Preview".to_string(),
+            Command::OpenConfigDir => "Open Config Folder".to_string(),
+            Command::ToggleSidebar => "Toggle Sidebar".to_string(),
+            Command::ToggleDirCounts => "Toggle Folder Item Counts".to_string(),
+            Command::ToggleSizeBars => "Toggle Size Bars".to_string(),
+            Command::ToggleAgeShading => "Toggle Age Shading".to_string(),
+            Command::DiskUsage => "Disk Usage".to_string(),
+            Command::ImportZoxide => "Import zoxide Directories".to_string(),
+            Command::ImportAutojump => "Import autojump Directories".to_string(),
             Command::Quit => "Quit".to_string(),
             Command::Custom(s) => s.clone(),
         }
@@ -95,11 +187,15 @@ impl CommandPalette {
         let cmd_list = vec![
             ("copy", Command::Copy),
             ("move", Command::Move),
+            ("paste", Command::Paste),
             ("delete", Command::Delete),
             ("rename", Command::Rename),
             ("create-file", Command::CreateFile),
             ("create-dir", Command::CreateDirectory),
+            ("duplicate", Command::Duplicate),
             ("parent", Command::ParentDirectory),
+            ("first", Command::FirstEntry),
+            ("last", Command::LastEntry),
             ("home", Command::Home),
             ("root", Command::Root),
             ("goto", Command::GoToPath),
@@ -107,6 +203,8 @@ impl CommandPalette {
             ("clear-search", Command::ClearSearch),
             ("hidden", Command::ToggleHidden),
             ("new-workspace", Command::NewWorkspace),
+            ("open-tab", Command::OpenInNewTab),
+            ("search-in", Command::SearchIn),
             ("close-workspace", Command::CloseWorkspace),
             ("next-ws", Command::NextWorkspace),
             ("prev-ws", Command::PrevWorkspace),
@@ -115,9 +213,49 @@ impl CommandPalette {
             ("bookmarks", Command::ShowBookmarks),
             ("toggle-preview", Command::TogglePreview),
             ("refresh-preview", Command::RefreshPreview),
+            ("refresh", Command::Refresh),
             ("open", Command::OpenWithDefault),
             ("help", Command::ShowHelp),
             ("settings", Command::ShowSettings),
+            ("themes", Command::ThemeGallery),
+            ("recent", Command::RecentFiles),
+            ("recent-opened", Command::RecentlyOpened),
+            ("plugins", Command::ShowPlugins),
+            ("reload-plugins", Command::ReloadPlugins),
+            ("log", Command::ShowLogPath),
+            ("export-media-info", Command::ExportMediaInfo),
+            ("toggle-ignore", Command::ToggleIgnorePatterns),
+            ("trash", Command::OpenTrash),
+            ("hash", Command::HashFile),
+            ("yank-hash", Command::CopyHash),
+            ("yank-repo-path", Command::CopyRepoPath),
+            ("yank-contents", Command::CopyContents),
+            ("diff", Command::DiffFiles),
+            ("select-all", Command::SelectAll),
+            ("select-none", Command::ClearSelection),
+            ("invert-selection", Command::InvertSelection),
+            ("reset-order", Command::ResetCustomOrder),
+            ("sort", Command::CycleSort),
+            ("chmod", Command::ChangeMode),
+            ("chown", Command::ChangeOwner),
+            ("sequence-rename", Command::SequenceRename),
+            ("flatten", Command::Flatten),
+            ("edit", Command::EditFile),
+            ("places", Command::ShowPlaces),
+            ("jobs", Command::ShowJobs),
+            ("paths", Command::ShowPaths),
+            ("render", Command::ToggleRender),
+            ("open-config-dir", Command::OpenConfigDir),
+            ("sidebar", Command::ToggleSidebar),
+            ("dir-counts", Command::ToggleDirCounts),
+            ("size-bars", Command::ToggleSizeBars),
+            ("age-shading", Command::ToggleAgeShading),
+            ("disk-usage", Command::DiskUsage),
+            ("import-zoxide", Command::ImportZoxide),
+            ("import-autojump", Command::ImportAutojump),
+            ("create-theme-from-terminal", Command::CreateThemeFromTerminal),
+            ("fix-contrast", Command::FixThemeContrast),
+            ("highlight-theme", Command::NextHighlightTheme),
             ("quit", Command::Quit),
         ];
 
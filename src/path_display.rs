@@ -0,0 +1,340 @@
+// Path display - how paths, entry names and timestamps are written in the status bar, tabs, titles and lists
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::time::{Duration, SystemTime};
+
+/// What paths are shown relative to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathStyle {
+    /// The full path
+    Absolute,
+    /// `~` in place of the home directory
+    Home,
+    /// Relative to a configured root, absolute outside it
+    Root,
+}
+
+impl PathStyle {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "absolute" => Some(Self::Absolute),
+            "home" => Some(Self::Home),
+            "root" => Some(Self::Root),
+            _ => None,
+        }
+    }
+}
+
+/// `path` written in `style`. A path outside home (or the root) stays absolute,
+/// and the root itself shows as `.`.
+pub fn format_path(path: &Path, style: PathStyle, home: Option<&Path>, root: Option<&Path>) -> String {
+    let (base, prefix) = match style {
+        PathStyle::Absolute => return path.display().to_string(),
+        PathStyle::Home => (home, "~"),
+        PathStyle::Root => (root, "."),
+    };
+    match base.and_then(|base| path.strip_prefix(base).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => prefix.to_string(),
+        Some(rest) if style == PathStyle::Root => rest.display().to_string(),
+        Some(rest) => format!("{}{}{}", prefix, MAIN_SEPARATOR, rest.display()),
+        None => path.display().to_string(),
+    }
+}
+
+/// `text` cut down to `width` characters by replacing its middle with `…`,
+/// keeping a little more of the end, where the current folder's name is
+pub fn truncate_middle(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let keep = width - 1;
+    let head = keep / 2;
+    let tail = keep - head;
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(len - tail).collect();
+    format!("{}…{}", start, end)
+}
+
+/// What a tab the user hasn't named is titled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TabTitle {
+    /// The directory's path, in the given style or else `path_display`'s
+    Path(Option<PathStyle>),
+    /// The last `n` components: `src/astrofs` for 2
+    Last(usize),
+    /// Text with `{name}`, `{parent}` and `{path}` filled in
+    Template(String),
+}
+
+impl TabTitle {
+    /// "path", "absolute", "home", "root", "name", "last:N" or a template like "{parent}/{name}"
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.contains('{') {
+            return Some(Self::Template(value.to_string()));
+        }
+        match value.to_lowercase().as_str() {
+            "path" => Some(Self::Path(None)),
+            "name" => Some(Self::Last(1)),
+            other => match other.strip_prefix("last:") {
+                Some(n) => n.trim().parse().ok().filter(|n| *n > 0).map(Self::Last),
+                None => PathStyle::parse(other).map(|style| Self::Path(Some(style))),
+            },
+        }
+    }
+
+    /// The title for a tab in `dir`; `style` is `path_display`'s
+    pub fn title(&self, dir: &Path, style: PathStyle, home: Option<&Path>, root: Option<&Path>) -> String {
+        let path = |style| format_path(dir, style, home, root);
+        match self {
+            Self::Path(own) => path(own.unwrap_or(style)),
+            Self::Last(n) => last_components(dir, *n),
+            Self::Template(template) => template
+                .replace("{name}", &last_components(dir, 1))
+                .replace("{parent}", &dir.parent().map(|p| last_components(p, 1)).unwrap_or_default())
+                .replace("{path}", &path(style)),
+        }
+    }
+}
+
+/// The last `n` names in `path`, or all of it when it has fewer (`/` stays `/`)
+fn last_components(path: &Path, n: usize) -> String {
+    let names: Vec<_> = path.components().filter(|c| matches!(c, std::path::Component::Normal(_))).collect();
+    if names.is_empty() || names.len() < n {
+        return path.display().to_string();
+    }
+    names[names.len() - n..].iter().collect::<PathBuf>().display().to_string()
+}
+
+/// How hidden entries stand out in listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HiddenMarker {
+    /// `·name`, dimmed
+    Dot,
+    /// Dimmed, name unchanged
+    Dim,
+    /// Nothing but the real name, so dotfiles show their leading `.`
+    Name,
+}
+
+impl HiddenMarker {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "dot" => Some(Self::Dot),
+            "dim" => Some(Self::Dim),
+            "name" => Some(Self::Name),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::Dim => "dim",
+            Self::Name => "name",
+        }
+    }
+
+    /// Whether hidden entries get the theme's hidden style
+    pub fn dims(self) -> bool {
+        self != Self::Name
+    }
+}
+
+/// An entry's name as listed. Only for showing: operations use the real name.
+pub fn entry_label(name: &str, is_dir: bool, is_hidden: bool, marker: HiddenMarker, trailing_slash: bool) -> String {
+    let mut label = if is_hidden && marker == HiddenMarker::Dot {
+        format!("·{}", name)
+    } else {
+        name.to_string()
+    };
+    if is_dir && trailing_slash && !label.ends_with('/') {
+        label.push('/');
+    }
+    label
+}
+
+/// Row numbers in the file list's gutter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineNumbers {
+    Off,
+    /// 1, 2, 3, ... from the top of the listing
+    Absolute,
+    /// Rows away from the selection, like vim's `relativenumber`; the
+    /// selected row keeps its absolute number
+    Relative,
+}
+
+impl LineNumbers {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "absolute" => Some(Self::Absolute),
+            "relative" => Some(Self::Relative),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Absolute => "absolute",
+            Self::Relative => "relative",
+        }
+    }
+
+    /// The number shown for row `index` while `selected` is selected
+    pub fn number(self, index: usize, selected: usize) -> Option<usize> {
+        match self {
+            Self::Off => None,
+            Self::Relative if index != selected => Some(index.abs_diff(selected)),
+            _ => Some(index + 1),
+        }
+    }
+}
+
+/// Columns the row number gutter needs for a listing of `rows` entries
+pub fn gutter_width(rows: usize) -> usize {
+    rows.max(1).to_string().len()
+}
+
+/// How timestamps are written when `date_format` is unset or invalid
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Whether chrono accepts a strftime-style format (an empty one is not useful)
+pub fn is_valid_time_format(format: &str) -> bool {
+    use chrono::format::{Item, StrftimeItems};
+    !format.trim().is_empty() && StrftimeItems::new(format).all(|item| !matches!(item, Item::Error))
+}
+
+/// Whether `format` is a usable `date_format`: "relative" or a strftime format
+pub fn is_valid_date_format(format: &str) -> bool {
+    format.trim() == "relative" || is_valid_time_format(format)
+}
+
+/// `time` written in `format`, in local time; "relative" gives "3h ago" counted
+/// from `now`, and a format chrono can't use gives `DEFAULT_DATE_FORMAT`
+pub fn format_time(time: SystemTime, format: &str, now: SystemTime) -> String {
+    if format.trim() == "relative" {
+        return match now.duration_since(time) {
+            Ok(age) => format!("{} ago", format_age(age)),
+            Err(ahead) => format!("in {}", format_age(ahead.duration())),
+        };
+    }
+    let format = if is_valid_time_format(format) { format } else { DEFAULT_DATE_FORMAT };
+    chrono::DateTime::<chrono::Local>::from(time).format(format).to_string()
+}
+
+/// Compact age such as "45s", "12m", "3h" or "9d"
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+/// The configured root, or none when it is empty
+pub fn configured_root(root: &str) -> Option<PathBuf> {
+    let root = root.trim();
+    (!root.is_empty()).then(|| PathBuf::from(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_home_and_root_substitution() {
+        let home = Path::new("/home/ada");
+        let root = Path::new("/srv/projects");
+        let show = |path: &str, style| format_path(Path::new(path), style, Some(home), Some(root));
+
+        assert_eq!(show("/home/ada/src/astrofs", PathStyle::Home), format!("~{0}src{0}astrofs", MAIN_SEPARATOR));
+        assert_eq!(show("/home/ada", PathStyle::Home), "~");
+        assert_eq!(show("/home/adam", PathStyle::Home), "/home/adam", "only whole components match");
+        assert_eq!(show("/etc", PathStyle::Home), "/etc");
+        assert_eq!(show("/home/ada/src", PathStyle::Absolute), "/home/ada/src");
+
+        assert_eq!(show("/srv/projects/site", PathStyle::Root), "site");
+        assert_eq!(show("/srv/projects", PathStyle::Root), ".");
+        assert_eq!(show("/tmp", PathStyle::Root), "/tmp");
+        assert_eq!(format_path(Path::new("/tmp"), PathStyle::Home, None, None), "/tmp");
+
+        assert_eq!(PathStyle::parse(" HOME "), Some(PathStyle::Home));
+        assert_eq!(PathStyle::parse("relative"), None);
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        assert_eq!(truncate_middle("/usr/local/bin", 20), "/usr/local/bin");
+        assert_eq!(truncate_middle("/usr/local/bin", 14), "/usr/local/bin");
+
+        let cut = truncate_middle("/usr/share/doc/packages", 11);
+        assert_eq!(cut, "/usr/…kages");
+        assert_eq!(cut.chars().count(), 11);
+
+        assert_eq!(truncate_middle("~/Документы/отчёт", 8), "~/Д…тчёт");
+        assert_eq!(truncate_middle("abc", 1), "…");
+        assert_eq!(truncate_middle("abc", 0), "");
+    }
+
+    #[test]
+    fn test_entry_labels() {
+        let label = |name, is_dir, is_hidden, marker| entry_label(name, is_dir, is_hidden, marker, true);
+        assert_eq!(label(".git", true, true, HiddenMarker::Dot), "·.git/");
+        assert_eq!(label(".git", true, true, HiddenMarker::Dim), ".git/");
+        assert_eq!(label(".env", false, true, HiddenMarker::Name), ".env");
+        assert_eq!(label("src", true, false, HiddenMarker::Dot), "src/");
+        assert_eq!(entry_label("src", true, false, HiddenMarker::Dot, false), "src");
+        assert!(!HiddenMarker::Name.dims());
+        assert_eq!(HiddenMarker::parse(" Dim "), Some(HiddenMarker::Dim));
+    }
+
+    #[test]
+    fn test_tab_title_formats() {
+        let dir = Path::new("/home/ada/src/astrofs");
+        let home = Some(Path::new("/home/ada"));
+        let title = |format: &str| TabTitle::parse(format).unwrap().title(dir, PathStyle::Absolute, home, None);
+        assert_eq!(title("path"), "/home/ada/src/astrofs");
+        assert_eq!(title("home"), format!("~{0}src{0}astrofs", MAIN_SEPARATOR));
+        assert_eq!(title("name"), "astrofs");
+        assert_eq!(title("last:2"), format!("src{}astrofs", MAIN_SEPARATOR));
+        assert_eq!(title("last:9"), "/home/ada/src/astrofs");
+        assert_eq!(title("{name} in {parent}"), "astrofs in src");
+        assert_eq!(TabTitle::parse("name").unwrap().title(Path::new("/"), PathStyle::Absolute, home, None), "/");
+        assert_eq!(TabTitle::parse("last:0"), None);
+        assert_eq!(TabTitle::parse("short"), None);
+    }
+
+    #[test]
+    fn test_format_time() {
+        use chrono::TimeZone;
+        let taken = chrono::Local.with_ymd_and_hms(2024, 3, 9, 14, 5, 0).unwrap();
+        let time = SystemTime::from(taken);
+        let now = time + Duration::from_secs(3 * 3600 + 59);
+        assert_eq!(format_time(time, "%Y-%m-%d %H:%M", now), "2024-03-09 14:05");
+        assert_eq!(format_time(time, "%d/%m/%y", now), "09/03/24");
+        assert_eq!(format_time(time, "%b %e, %Y", now), "Mar  9, 2024");
+        assert_eq!(format_time(time, "relative", now), "3h ago");
+        assert_eq!(format_time(now, " relative ", time), "in 3h");
+        assert_eq!(format_time(time, "%Q", now), "2024-03-09 14:05", "invalid formats fall back");
+        assert!(!is_valid_date_format("%Q") && !is_valid_date_format(" "));
+        assert!(is_valid_date_format("relative") && is_valid_date_format("%c"));
+    }
+
+    #[test]
+    fn test_relative_numbers_follow_the_selection() {
+        let numbers = |selected| (0..5).map(|i| LineNumbers::Relative.number(i, selected).unwrap()).collect::<Vec<_>>();
+        assert_eq!(numbers(0), [1, 1, 2, 3, 4]);
+        assert_eq!(numbers(2), [2, 1, 3, 1, 2]);
+        assert_eq!(LineNumbers::Absolute.number(3, 0), Some(4));
+        assert_eq!(LineNumbers::Off.number(3, 0), None);
+        assert_eq!((gutter_width(0), gutter_width(9), gutter_width(10), gutter_width(1200)), (1, 1, 2, 4));
+    }
+}
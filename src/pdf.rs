@@ -0,0 +1,676 @@
+// PDF - page count, document info and first-page text, read without a PDF library
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// PDFs larger than this are described by size alone rather than parsed
+pub const PDF_PARSE_LIMIT: u64 = 32 * 1024 * 1024;
+
+/// What a preview shows of a PDF
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PdfInfo {
+    /// "1.7" from the `%PDF-1.7` header
+    pub version: String,
+    pub pages: usize,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// Text of the first page; None when there is none that can be read,
+    /// as in scanned pages or fonts without a usable encoding
+    pub first_page_text: Option<String>,
+}
+
+/// Read the document info and first page of the PDF at `path`
+pub fn pdf_info(path: &Path) -> Result<PdfInfo> {
+    let size = fs::metadata(path)?.len();
+    if size > PDF_PARSE_LIMIT {
+        return Err(anyhow!("too large to read ({})", humansize::format_size(size, humansize::BINARY)));
+    }
+    parse(&fs::read(path)?)
+}
+
+fn parse(data: &[u8]) -> Result<PdfInfo> {
+    let header = data.strip_prefix(b"%PDF-").ok_or_else(|| anyhow!("not a PDF file"))?;
+    let version: String = header.iter().take_while(|b| b.is_ascii_digit() || **b == b'.').map(|b| *b as char).collect();
+    let doc = Document::load(data);
+
+    let info = doc.trailer("Info").and_then(|info| doc.resolve(info)).and_then(Obj::as_dict);
+    let text_of = |key: &str| {
+        info.and_then(|info| doc.resolve(info.get(key)?))
+            .and_then(Obj::as_bytes)
+            .map(decode_text)
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+    let pages = doc.page_tree();
+    let first_page = pages.and_then(|pages| doc.first_page(pages));
+
+    Ok(PdfInfo {
+        version,
+        pages: pages
+            .and_then(|pages| doc.resolve(pages.get("Count")?))
+            .and_then(Obj::as_number)
+            .map_or(0, |count| count.max(0.0) as usize),
+        title: text_of("Title"),
+        author: text_of("Author"),
+        first_page_text: first_page.and_then(|page| doc.page_text(page)),
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Obj {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Str(Vec<u8>),
+    Name(String),
+    Array(Vec<Obj>),
+    Dict(HashMap<String, Obj>),
+    Ref(u32),
+    Stream(HashMap<String, Obj>, Vec<u8>),
+    /// A bare keyword: operators in content streams, `obj`, `stream`, ...
+    Op(String),
+}
+
+impl Obj {
+    fn as_dict(&self) -> Option<&HashMap<String, Obj>> {
+        match self {
+            Obj::Dict(dict) | Obj::Stream(dict, _) => Some(dict),
+            _ => None,
+        }
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Obj::Str(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Obj::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn is_name(&self, name: &str) -> bool {
+        matches!(self, Obj::Name(n) if n == name)
+    }
+}
+
+/// Every object in the file, found by scanning for `N G obj` rather than
+/// trusting the cross-reference table, so damaged files still mostly read
+struct Document {
+    objects: HashMap<u32, Obj>,
+    trailers: Vec<HashMap<String, Obj>>,
+}
+
+impl Document {
+    fn load(data: &[u8]) -> Self {
+        static OBJECT_START: OnceLock<regex::bytes::Regex> = OnceLock::new();
+        let object_start = OBJECT_START.get_or_init(|| regex::bytes::Regex::new(r"(\d+)\s+\d+\s+obj\b").unwrap());
+
+        let mut doc = Document { objects: HashMap::new(), trailers: Vec::new() };
+        for found in object_start.captures_iter(data) {
+            let Some(number) = std::str::from_utf8(&found[1]).ok().and_then(|n| n.parse().ok()) else {
+                continue;
+            };
+            let mut parser = Parser::new(data);
+            parser.pos = found.get(0).map_or(0, |m| m.end());
+            let Some(mut object) = parser.next_object() else {
+                continue;
+            };
+            if let Obj::Dict(dict) = &object {
+                if let Some(stream) = parser.stream_data() {
+                    object = Obj::Stream(dict.clone(), stream.to_vec());
+                }
+            }
+            if let Some(dict) = object.as_dict().filter(|d| d.get("Type").is_some_and(|t| t.is_name("XRef"))) {
+                doc.trailers.push(dict.clone());
+            }
+            doc.objects.insert(number, object);
+        }
+        for start in find_all(data, b"trailer") {
+            let mut parser = Parser::new(data);
+            parser.pos = start + b"trailer".len();
+            if let Some(Obj::Dict(dict)) = parser.next_object() {
+                doc.trailers.push(dict);
+            }
+        }
+        doc.unpack_object_streams();
+        doc
+    }
+
+    /// Objects can be packed inside compressed object streams (PDF 1.5+)
+    fn unpack_object_streams(&mut self) {
+        let mut unpacked = Vec::new();
+        for obj in self.objects.values() {
+            let Some(dict) = obj.as_dict().filter(|d| d.get("Type").is_some_and(|t| t.is_name("ObjStm"))) else {
+                continue;
+            };
+            let (Some(first), Some(data)) = (dict.get("First").and_then(Obj::as_number), decode_stream(obj)) else {
+                continue;
+            };
+            // A header of object number and offset pairs, then the objects
+            let first = first as usize;
+            let mut header = Parser::new(&data[..first.min(data.len())]);
+            let mut numbers = Vec::new();
+            while let Some(Obj::Number(n)) = header.next_object() {
+                numbers.push(n as usize);
+            }
+            for pair in numbers.chunks_exact(2) {
+                let mut parser = Parser::new(&data);
+                parser.pos = first + pair[1];
+                if let Some(object) = parser.next_object() {
+                    unpacked.push((pair[0] as u32, object));
+                }
+            }
+        }
+        for (number, object) in unpacked {
+            self.objects.entry(number).or_insert(object);
+        }
+    }
+
+    fn trailer(&self, key: &str) -> Option<&Obj> {
+        self.trailers.iter().rev().find_map(|trailer| trailer.get(key))
+    }
+
+    /// Follow references to the object they point at
+    fn resolve<'a>(&'a self, mut obj: &'a Obj) -> Option<&'a Obj> {
+        for _ in 0..32 {
+            match obj {
+                Obj::Ref(number) => obj = self.objects.get(number)?,
+                _ => return Some(obj),
+            }
+        }
+        None
+    }
+
+    /// The root of the page tree, from the catalog
+    fn page_tree(&self) -> Option<&HashMap<String, Obj>> {
+        let catalog = self
+            .trailer("Root")
+            .and_then(|root| self.resolve(root))
+            .or_else(|| self.objects.values().find(|obj| obj.as_dict().and_then(|d| d.get("Type")).is_some_and(|t| t.is_name("Catalog"))))?;
+        self.resolve(catalog.as_dict()?.get("Pages")?)?.as_dict()
+    }
+
+    fn first_page<'a>(&'a self, mut node: &'a HashMap<String, Obj>) -> Option<&'a HashMap<String, Obj>> {
+        for _ in 0..64 {
+            let kids = match node.get("Kids").and_then(|kids| self.resolve(kids)) {
+                Some(Obj::Array(kids)) => kids,
+                _ => return Some(node),
+            };
+            node = self.resolve(kids.first()?)?.as_dict()?;
+        }
+        None
+    }
+
+    fn page_text(&self, page: &HashMap<String, Obj>) -> Option<String> {
+        let contents = self.resolve(page.get("Contents")?)?;
+        let parts: Vec<&Obj> = match contents {
+            Obj::Array(parts) => parts.iter().filter_map(|part| self.resolve(part)).collect(),
+            single => vec![single],
+        };
+        let mut content = Vec::new();
+        for part in parts {
+            content.extend(decode_stream(part)?);
+            content.push(b'\n');
+        }
+        let text = content_text(&content);
+        readable(&text).then_some(text)
+    }
+}
+
+/// A stream's data with its filters undone; None for filters other than Flate
+fn decode_stream(obj: &Obj) -> Option<Vec<u8>> {
+    let Obj::Stream(dict, data) = obj else {
+        return None;
+    };
+    let filters: Vec<&Obj> = match dict.get("Filter") {
+        None => Vec::new(),
+        Some(Obj::Array(filters)) => filters.iter().collect(),
+        Some(filter) => vec![filter],
+    };
+    let mut data = data.clone();
+    for filter in filters {
+        if !filter.is_name("FlateDecode") {
+            return None;
+        }
+        let mut decoded = Vec::new();
+        // Truncated streams still give what decoded before the damage
+        let _ = flate2::read::ZlibDecoder::new(data.as_slice()).read_to_end(&mut decoded);
+        if decoded.is_empty() {
+            return None;
+        }
+        data = decoded;
+    }
+    Some(data)
+}
+
+/// The text drawn by a page's content stream, a line per text line
+fn content_text(content: &[u8]) -> String {
+    let mut parser = Parser::new(content);
+    let mut operands: Vec<Obj> = Vec::new();
+    let mut text = String::new();
+    let newline = |text: &mut String| {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+    };
+    while let Some(obj) = parser.next_object() {
+        let Obj::Op(op) = obj else {
+            operands.push(obj);
+            continue;
+        };
+        match op.as_str() {
+            "Tj" | "'" | "\"" => {
+                if op != "Tj" {
+                    newline(&mut text);
+                }
+                if let Some(Obj::Str(bytes)) = operands.last() {
+                    text.push_str(&decode_text(bytes));
+                }
+            }
+            "TJ" => {
+                if let Some(Obj::Array(items)) = operands.last() {
+                    for item in items {
+                        match item {
+                            Obj::Str(bytes) => text.push_str(&decode_text(bytes)),
+                            // A wide negative adjustment is a gap between words
+                            Obj::Number(n) if *n < -200.0 && !text.ends_with(' ') => text.push(' '),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            "Td" | "TD" => match operands.get(1) {
+                Some(Obj::Number(y)) if *y != 0.0 => newline(&mut text),
+                _ if !text.is_empty() && !text.ends_with([' ', '\n']) => text.push(' '),
+                _ => {}
+            },
+            "T*" | "ET" => newline(&mut text),
+            // Inline image data is binary; skip to its end
+            "ID" => parser.skip_inline_image(),
+            _ => {}
+        }
+        operands.clear();
+    }
+    text.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim().to_string()
+}
+
+/// Whether extracted text looks like words rather than glyph ids in disguise
+fn readable(text: &str) -> bool {
+    let total = text.chars().count();
+    let odd = text.chars().filter(|c| c.is_control() && !c.is_whitespace()).count();
+    total > 0 && odd * 10 < total
+}
+
+/// A PDF text string: UTF-16 with a byte order mark, else (close enough to) Latin-1
+fn decode_text(bytes: &[u8]) -> String {
+    match bytes.strip_prefix(&[0xFE, 0xFF]) {
+        Some(utf16) => {
+            let units: Vec<u16> = utf16.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        None => bytes.iter().map(|b| *b as char).collect(),
+    }
+}
+
+fn find_all<'a>(data: &'a [u8], needle: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    data.windows(needle.len()).enumerate().filter(move |(_, window)| *window == needle).map(|(i, _)| i)
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(b, b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%')
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+}
+
+/// Arrays and dictionaries nested deeper than this are treated as malformed,
+/// so a file of nothing but `[` can't exhaust the stack
+const MAX_NESTING: usize = 64;
+
+/// Reads PDF objects (and content stream operators) one at a time
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0, depth: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_space(&mut self) {
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while self.peek().is_some_and(|b| b != b'\n' && b != b'\r') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn next_object(&mut self) -> Option<Obj> {
+        let b = loop {
+            self.skip_space();
+            match self.peek()? {
+                // Stray `)`, `>`, `]`, `{`, `}`: nothing to read
+                b')' | b'>' | b']' | b'{' | b'}' => self.pos += 1,
+                b => break b,
+            }
+        };
+        match b {
+            b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                self.pos += 2;
+                self.nested(Self::dict_body)
+            }
+            b'<' => {
+                self.pos += 1;
+                let mut digits = Vec::new();
+                while let Some(b) = self.peek() {
+                    self.pos += 1;
+                    match b {
+                        b'>' => break,
+                        b if b.is_ascii_hexdigit() => digits.push(b),
+                        _ => {}
+                    }
+                }
+                if digits.len() % 2 == 1 {
+                    digits.push(b'0');
+                }
+                let bytes = digits
+                    .chunks(2)
+                    .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+                    .collect();
+                Some(Obj::Str(bytes))
+            }
+            b'(' => Some(Obj::Str(self.literal_string())),
+            b'/' => {
+                self.pos += 1;
+                let name = self.regular_run();
+                Some(Obj::Name(decode_name(name)))
+            }
+            b'[' => {
+                self.pos += 1;
+                self.nested(Self::array_body)
+            }
+            b'+' | b'-' | b'.' | b'0'..=b'9' => {
+                let token = self.regular_run();
+                let number: f64 = std::str::from_utf8(token).ok()?.parse().unwrap_or(0.0);
+                // `12 0 R` is a reference
+                if token.iter().all(u8::is_ascii_digit) {
+                    let save = self.pos;
+                    self.skip_space();
+                    let generation = self.regular_run();
+                    self.skip_space();
+                    let keyword = self.regular_run();
+                    if !generation.is_empty() && generation.iter().all(u8::is_ascii_digit) && keyword == b"R" {
+                        return Some(Obj::Ref(number as u32));
+                    }
+                    self.pos = save;
+                }
+                Some(Obj::Number(number))
+            }
+            _ => {
+                let word = String::from_utf8_lossy(self.regular_run()).into_owned();
+                Some(match word.as_str() {
+                    "true" => Obj::Bool(true),
+                    "false" => Obj::Bool(false),
+                    "null" => Obj::Null,
+                    _ => Obj::Op(word),
+                })
+            }
+        }
+    }
+
+    /// Read the inside of an array or dictionary one level deeper, giving up
+    /// past MAX_NESTING
+    fn nested(&mut self, body: fn(&mut Self) -> Option<Obj>) -> Option<Obj> {
+        if self.depth >= MAX_NESTING {
+            return None;
+        }
+        self.depth += 1;
+        let obj = body(self);
+        self.depth -= 1;
+        obj
+    }
+
+    /// After `<<`: key and value pairs up to `>>`
+    fn dict_body(&mut self) -> Option<Obj> {
+        let mut dict = HashMap::new();
+        loop {
+            self.skip_space();
+            if self.data[self.pos..].starts_with(b">>") {
+                self.pos += 2;
+                return Some(Obj::Dict(dict));
+            }
+            match self.next_object()? {
+                Obj::Name(key) => {
+                    let value = self.next_object()?;
+                    dict.insert(key, value);
+                }
+                // Not a key: skip it rather than give up on the dictionary
+                _ => continue,
+            }
+        }
+    }
+
+    /// After `[`: items up to `]` or the end of the data
+    fn array_body(&mut self) -> Option<Obj> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_space();
+            match self.peek() {
+                None => return Some(Obj::Array(items)),
+                Some(b']') => {
+                    self.pos += 1;
+                    return Some(Obj::Array(items));
+                }
+                _ => items.push(self.next_object()?),
+            }
+        }
+    }
+
+    /// Bytes up to the next whitespace or delimiter
+    fn regular_run(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| !is_whitespace(b) && !is_delimiter(b)) {
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    fn literal_string(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        let mut depth = 1;
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                b'\\' => {
+                    let Some(escaped) = self.peek() else {
+                        break;
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => bytes.push(b'\n'),
+                        b'r' => bytes.push(b'\r'),
+                        b't' => bytes.push(b'\t'),
+                        b'b' => bytes.push(8),
+                        b'f' => bytes.push(12),
+                        b'0'..=b'7' => {
+                            let mut value = u32::from(escaped - b'0');
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + u32::from(d - b'0');
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            bytes.push(value as u8);
+                        }
+                        // A backslash at the end of a line continues the string
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => bytes.push(other),
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            bytes.push(b);
+        }
+        bytes
+    }
+
+    /// After a dictionary: the raw bytes of the stream that follows, if any
+    fn stream_data(&mut self) -> Option<&'a [u8]> {
+        let save = self.pos;
+        self.skip_space();
+        if !self.data[self.pos..].starts_with(b"stream") {
+            self.pos = save;
+            return None;
+        }
+        self.pos += b"stream".len();
+        if self.peek() == Some(b'\r') {
+            self.pos += 1;
+        }
+        if self.peek() == Some(b'\n') {
+            self.pos += 1;
+        }
+        let start = self.pos;
+        let end = find_all(&self.data[start..], b"endstream").next().map_or(self.data.len(), |i| start + i);
+        self.pos = end;
+        let mut data = &self.data[start..end];
+        while let Some(rest) = data.strip_suffix(b"\n").or_else(|| data.strip_suffix(b"\r")) {
+            data = rest;
+        }
+        Some(data)
+    }
+
+    fn skip_inline_image(&mut self) {
+        match find_all(&self.data[self.pos..], b"EI").next() {
+            Some(i) => self.pos += i + 2,
+            None => self.pos = self.data.len(),
+        }
+    }
+}
+
+/// A name with its `#xx` escapes undone
+fn decode_name(raw: &[u8]) -> String {
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        let escaped = raw
+            .get(i + 1..i + 3)
+            .filter(|_| raw[i] == b'#')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(b) => {
+                bytes.push(b);
+                i += 3;
+            }
+            None => {
+                bytes.push(raw[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// A two-page PDF whose first page says "Hello PDF" then "second line",
+    /// its content stream compressed
+    fn sample_pdf() -> Vec<u8> {
+        let content = b"BT /F1 12 Tf 72 720 Td (Hello) Tj [( P) -50 (DF)] TJ 0 -14 Td (second line) Tj ET";
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(content).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut pdf = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R 6 0 R] /Count 2 >>".to_vec(),
+            b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R >>".to_vec(),
+            [format!("<< /Length {} /Filter /FlateDecode >>\nstream\n", compressed.len()).as_bytes(), &compressed, b"\nendstream"].concat(),
+            b"<< /Title (Quarterly \\(Q3\\) Report) /Author <FEFF00C5007300610020004C00690065> >>".to_vec(),
+            b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] >>".to_vec(),
+        ];
+        for (i, body) in objects.iter().enumerate() {
+            pdf.extend(format!("{} 0 obj\n", i + 1).as_bytes());
+            pdf.extend(body);
+            pdf.extend(b"\nendobj\n");
+        }
+        pdf.extend(b"trailer\n<< /Size 7 /Root 1 0 R /Info 5 0 R >>\n%%EOF\n");
+        pdf
+    }
+
+    #[test]
+    fn test_pdf_info_reads_metadata_and_first_page() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("report.pdf");
+        fs::write(&path, sample_pdf())?;
+
+        let info = pdf_info(&path)?;
+        assert_eq!(info.version, "1.4");
+        assert_eq!(info.pages, 2);
+        assert_eq!(info.title.as_deref(), Some("Quarterly (Q3) Report"));
+        assert_eq!(info.author.as_deref(), Some("Åsa Lie"));
+        assert_eq!(info.first_page_text.as_deref(), Some("Hello PDF\nsecond line"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pdf_without_text_or_header() {
+        let image_only = b"%PDF-1.7\n1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n2 0 obj << /Type /Pages /Kids [3 0 R] /Count 1 >> endobj\n3 0 obj << /Type /Page >> endobj\n";
+        let info = parse(image_only).expect("parses");
+        assert_eq!((info.pages, info.first_page_text), (1, None));
+        assert!(parse(b"GIF89a").is_err());
+    }
+
+    #[test]
+    fn test_deep_nesting_and_stray_delimiters_do_not_overflow() {
+        let mut nested = b"%PDF-1.4\n1 0 obj\n".to_vec();
+        nested.resize(1024 * 1024, b'[');
+        let info = parse(&nested).expect("parses");
+        assert_eq!(info.pages, 0);
+
+        let mut stray = b"%PDF-1.4\n1 0 obj\n".to_vec();
+        stray.resize(1024 * 1024, b')');
+        assert!(parse(&stray).is_ok());
+    }
+}
@@ -26,13 +26,17 @@ mod datetime_format {
             .map_err(serde::de::Error::custom)
     }
 }
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 /// Central persistence store for all application state
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UserSettings {
     pub version: String,
     #[serde(with = "datetime_format")]
@@ -43,12 +47,28 @@ pub struct UserSettings {
     pub current_theme: String,
     pub custom_theme_paths: Vec<PathBuf>,
     pub theme_history: Vec<String>,
+    /// Preview highlight theme last picked; empty means use the config's
+    pub highlight_theme: String,
 
     // UI State
     pub last_opened_directory: PathBuf,
     pub opened_tabs: Vec<TabState>,
     pub active_tab_index: usize,
+    /// Preview share of the browser set with `<`/`>`; 0 means use the config's
     pub preview_width_ratio: f32,
+    /// Quick-nav sidebar shown left of the file list
+    pub show_sidebar: bool,
+    /// Sidebar width set with `{`/`}`; 0 means use the config's
+    pub sidebar_width: u16,
+    /// Directories visited, most recent first, for the sidebar
+    pub recent_directories: Vec<PathBuf>,
+    /// Files opened, most recent first, at most `RECENT_OPENED_MAX`
+    pub recent_files: Vec<RecentFileState>,
+    /// Visit counts per directory, at most `DIR_VISITS_MAX`
+    pub dir_visits: Vec<DirVisitState>,
+    /// Entry last selected per directory, most recent first, at most
+    /// `SAVED_SELECTIONS_MAX`; kept only with `persist_selections` on
+    pub selections: Vec<SelectionState>,
 
     // Bookmarks
     pub bookmarks: Vec<BookmarkState>,
@@ -60,6 +80,7 @@ pub struct UserSettings {
     // Plugins
     pub enabled_plugins: Vec<String>,
     pub plugin_settings: HashMap<String, serde_json::Value>,
+    /// Plugin folder set with `set_plugin_dir`; empty means use the config's
     pub plugin_directory: PathBuf,
 
     // General
@@ -72,6 +93,9 @@ pub struct UserSettings {
     // Keybindings (user custom)
     pub custom_keybindings: HashMap<String, String>,
 
+    // Manual entry order per directory: names, first to last
+    pub custom_orders: HashMap<PathBuf, Vec<String>>,
+
     // Performance
     pub max_file_preview_size: u64,
     pub parallel_search_threads: usize,
@@ -91,6 +115,66 @@ pub struct TabState {
     pub title: Option<String>,    #[serde(with = "datetime_format")]    pub created_at: DateTime<Utc>,
 }
 
+/// How many opened files are remembered
+pub const RECENT_OPENED_MAX: usize = 50;
+
+/// A file opened from AstroFS, and when
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecentFileState {
+    pub path: PathBuf,
+    #[serde(with = "datetime_format")]
+    pub opened_at: DateTime<Utc>,
+}
+
+/// Move `path` to the front of the opened files, dropping the oldest past `max`
+pub fn push_recent_file(recent: &mut Vec<RecentFileState>, path: &Path, opened_at: DateTime<Utc>, max: usize) {
+    recent.retain(|file| file.path != path);
+    recent.insert(0, RecentFileState { path: path.to_path_buf(), opened_at });
+    recent.truncate(max);
+}
+
+/// Most directories whose selected entry is saved between sessions
+pub const SAVED_SELECTIONS_MAX: usize = 100;
+
+/// The entry selected in a directory when it was last left
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SelectionState {
+    pub dir: PathBuf,
+    pub name: String,
+}
+
+/// Most directories whose visits are remembered for bookmark suggestions
+pub const DIR_VISITS_MAX: usize = 500;
+
+/// How often and how lately a directory was visited in AstroFS
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DirVisitState {
+    pub path: PathBuf,
+    pub count: u32,
+    #[serde(with = "datetime_format")]
+    pub last_visited: DateTime<Utc>,
+}
+
+/// Count a visit to `path`; past `max` directories the least visited is forgotten
+pub fn record_dir_visit(visits: &mut Vec<DirVisitState>, path: &Path, now: DateTime<Utc>, max: usize) {
+    if let Some(visit) = visits.iter_mut().find(|visit| visit.path == path) {
+        visit.count = visit.count.saturating_add(1);
+        visit.last_visited = now;
+        return;
+    }
+    if visits.len() >= max {
+        if let Some(index) = visits
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, visit)| (visit.count, visit.last_visited))
+            .map(|(index, _)| index)
+        {
+            visits.swap_remove(index);
+        }
+    }
+    visits.push(DirVisitState { path: path.to_path_buf(), count: 1, last_visited: now });
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BookmarkState {
     pub id: String,
@@ -144,22 +228,30 @@ impl Default for UserSettings {
             current_theme: "default".to_string(),
             custom_theme_paths: Vec::new(),
             theme_history: vec!["default".to_string()],
+            highlight_theme: String::new(),
             last_opened_directory: dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")),
             opened_tabs: vec![],
             active_tab_index: 0,
-            preview_width_ratio: 0.7,
+            preview_width_ratio: 0.0,
+            show_sidebar: false,
+            sidebar_width: 0,
+            recent_directories: Vec::new(),
+            recent_files: Vec::new(),
+            dir_visits: Vec::new(),
+            selections: Vec::new(),
             bookmarks: Vec::new(),
             search_history: Vec::new(),
             max_search_history: 100,
             enabled_plugins: Vec::new(),
             plugin_settings: HashMap::new(),
-            plugin_directory: PathBuf::from("./plugins"),
+            plugin_directory: PathBuf::new(),
             show_hidden_files: false,
             vim_mode: true,
             mouse_enabled: false,
             auto_preview: true,
             preserve_case_on_rename: false,
             custom_keybindings: HashMap::new(),
+            custom_orders: HashMap::new(),
             max_file_preview_size: 10 * 1024 * 1024, // 10MB
             parallel_search_threads: num_cpus::get(),
             emoji_style: EmojiStyle::Full,
@@ -169,6 +261,7 @@ impl Default for UserSettings {
     }
 }
 
+#[derive(Clone)]
 pub struct PersistenceManager {
     config_dir: PathBuf,
     settings_file: PathBuf,
@@ -184,8 +277,8 @@ impl Default for PersistenceManager {
 }
 
 impl PersistenceManager {
-    pub fn new() -> Result<Self> {
-        let config_dir = Self::get_config_dir()?;
+    /// Keep settings in `config_dir` rather than the user's config folder
+    pub fn with_dir(config_dir: PathBuf) -> Result<Self> {
         fs::create_dir_all(&config_dir)?;
 
         let settings_file = config_dir.join("settings.json");
@@ -196,7 +289,8 @@ impl PersistenceManager {
         })
     }
 
-    fn get_config_dir() -> Result<PathBuf> {
+    /// The user's AstroFS config folder, created if missing
+    pub fn default_config_dir() -> Result<PathBuf> {
         let config_dir = if let Some(config_home) = dirs::config_dir() {
             config_home.join("astrofs")
         } else {
@@ -210,6 +304,27 @@ impl PersistenceManager {
         Ok(config_dir)
     }
 
+    /// Keep settings in the user's config folder
+    #[deprecated(note = "use with_dir with AppDirs::config_dir")]
+    #[allow(dead_code)]
+    pub fn new() -> Result<Self> {
+        Self::with_dir(Self::default_config_dir()?)
+    }
+
+    /// Static method to load settings from default location
+    #[deprecated(note = "use load_settings on the App's PersistenceManager")]
+    #[allow(dead_code, deprecated)]
+    pub fn load_default() -> Result<UserSettings> {
+        Self::new()?.load_settings()
+    }
+
+    /// Static method to save settings to default location
+    #[deprecated(note = "use save_settings on the App's PersistenceManager")]
+    #[allow(dead_code, deprecated)]
+    pub fn save_default(settings: &UserSettings) -> Result<()> {
+        Self::new()?.save_settings(settings)
+    }
+
     pub fn load_settings(&self) -> Result<UserSettings> {
         if self.settings_file.exists() {
             let content = fs::read_to_string(&self.settings_file)?;
@@ -225,7 +340,7 @@ impl PersistenceManager {
         updated.last_updated = Utc::now();
 
         let json = serde_json::to_string_pretty(&updated)?;
-        fs::write(&self.settings_file, json)?;
+        crate::files::write_atomic(&self.settings_file, json.as_bytes())?;
         Ok(())
     }
 
@@ -330,29 +445,201 @@ impl PersistenceManager {
     /// Export settings to backup
     pub fn export_settings(&self, backup_path: &Path) -> Result<()> {
         let settings = self.load_settings()?;
-        let json = serde_json::to_string_pretty(&settings)?;
-        fs::write(backup_path, json)?;
-        Ok(())
+        Self::write_backup(&settings, backup_path)
     }
 
     /// Import settings from backup
     pub fn import_settings(&self, backup_path: &Path) -> Result<()> {
-        let content = fs::read_to_string(backup_path)?;
-        let settings: UserSettings = serde_json::from_str(&content)?;
+        let settings = Self::read_backup(backup_path)?;
         self.save_settings(&settings)?;
         Ok(())
     }
 
-    /// Static method to load settings from default location
-    pub fn load_default() -> Result<UserSettings> {
-        let manager = PersistenceManager::new()?;
-        manager.load_settings()
+    /// Write `settings` as a backup file, whatever is saved on disk
+    pub fn write_backup(settings: &UserSettings, backup_path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(settings)?;
+        fs::write(backup_path, json)?;
+        Ok(())
     }
 
-    /// Static method to save settings to default location
-    pub fn save_default(settings: &UserSettings) -> Result<()> {
-        let manager = PersistenceManager::new()?;
-        manager.save_settings(settings)
+    /// Read a backup file written by `write_backup`
+    pub fn read_backup(backup_path: &Path) -> Result<UserSettings> {
+        let content = fs::read_to_string(backup_path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+/// A tab as kept in the recovery file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionTab {
+    pub path: PathBuf,
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+    /// Name given with rename; None keeps the folder name
+    pub title: Option<String>,
+}
+
+/// Navigation state kept in the recovery file while running
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionSnapshot {
+    pub tabs: Vec<SessionTab>,
+    pub active_tab_index: usize,
+    /// Query of the search in progress; empty when not searching
+    pub search_query: String,
+}
+
+/// Where the session snapshot is written while running. A clean quit removes
+/// it, so finding one at startup means the last run crashed or was killed.
+///
+/// Only one running instance uses the file at a time: it holds a lock on a
+/// `.lock` file beside it, which the OS releases however the process ends.
+#[derive(Debug)]
+pub struct RecoveryFile {
+    path: PathBuf,
+    // What was last written, so unchanged snapshots aren't written again
+    written: Option<SessionSnapshot>,
+    // Held for as long as this instance owns the file
+    lock: Option<fs::File>,
+}
+
+impl RecoveryFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, written: None, lock: None }
+    }
+
+    /// Claim the file for this instance; false while another running
+    /// instance has it, in which case its snapshot is live rather than left
+    /// behind and this instance should leave the file alone
+    pub fn lock(&mut self) -> Result<bool> {
+        if self.lock.is_some() {
+            return Ok(true);
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs::OpenOptions::new().create(true).truncate(false).write(true).open(self.path.with_extension("lock"))?;
+        match file.try_lock() {
+            Ok(()) => {
+                self.lock = Some(file);
+                Ok(true)
+            }
+            Err(fs::TryLockError::WouldBlock) => Ok(false),
+            Err(fs::TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+
+    /// `session_recovery.json` next to the user settings
+    #[deprecated(note = "use AppDirs::recovery_file, which follows the App's folders")]
+    #[allow(dead_code)]
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(PersistenceManager::default_config_dir()?.join("session_recovery.json"))
+    }
+
+    /// The snapshot a previous run left behind, if any
+    pub fn read(&self) -> Result<Option<SessionSnapshot>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&self.path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Write `snapshot` unless it's what the file already holds; true if written
+    pub fn write(&mut self, snapshot: &SessionSnapshot) -> Result<bool> {
+        if self.written.as_ref() == Some(snapshot) {
+            return Ok(false);
+        }
+        let json = serde_json::to_string_pretty(snapshot)?;
+        crate::files::write_atomic(&self.path, json.as_bytes())?;
+        self.written = Some(snapshot.clone());
+        Ok(true)
+    }
+
+    /// Remove the file on a clean quit
+    pub fn remove(&mut self) -> Result<()> {
+        self.written = None;
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Periodic saving of state that otherwise only reaches disk on quit.
+///
+/// Each store is remembered by a hash of its serialised form as last saved, so
+/// a pass only writes the stores that changed since.
+#[derive(Debug)]
+pub struct Autosave {
+    /// Zero turns autosaving off
+    interval: Duration,
+    last_run: Instant,
+    saved: HashMap<&'static str, u64>,
+}
+
+impl Autosave {
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Self {
+            interval,
+            last_run: now,
+            saved: HashMap::new(),
+        }
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Whether the interval has passed since the last pass, so snapshots are
+    /// only taken when they could be saved
+    pub fn due(&self, now: Instant) -> bool {
+        !self.interval.is_zero() && now.duration_since(self.last_run) >= self.interval
+    }
+
+    /// Record `snapshot` as what is on disk for `store`
+    pub fn mark_saved(&mut self, store: &'static str, snapshot: &str) {
+        self.saved.insert(store, Self::fingerprint(snapshot));
+    }
+
+    pub fn is_dirty(&self, store: &str, snapshot: &str) -> bool {
+        self.saved.get(store) != Some(&Self::fingerprint(snapshot))
+    }
+
+    /// Once the interval has passed, save each store whose snapshot differs from
+    /// the last saved one; returns the stores that were saved. A store whose
+    /// save fails stays dirty and is tried again next time.
+    pub fn run(
+        &mut self,
+        now: Instant,
+        snapshots: Vec<(&'static str, String)>,
+        mut save: impl FnMut(&str) -> Result<()>,
+    ) -> Vec<&'static str> {
+        if !self.due(now) {
+            return Vec::new();
+        }
+        self.last_run = now;
+
+        let mut saved = Vec::new();
+        for (store, snapshot) in snapshots {
+            if !self.is_dirty(store, &snapshot) {
+                continue;
+            }
+            match save(store) {
+                Ok(()) => {
+                    self.mark_saved(store, &snapshot);
+                    saved.push(store);
+                }
+                Err(e) => tracing::warn!("Failed to autosave {}: {:#}", store, e),
+            }
+        }
+        saved
+    }
+
+    fn fingerprint(snapshot: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        snapshot.hash(&mut hasher);
+        hasher.finish()
     }
 }
 
@@ -378,6 +665,71 @@ impl Default for StatusBarPosition {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_autosave_saves_only_dirty_stores_after_interval() {
+        let start = Instant::now();
+        let mut autosave = Autosave::new(Duration::from_secs(60), start);
+        autosave.mark_saved("settings", "clean");
+        autosave.mark_saved("history", "old");
+        let snapshots = || vec![("settings", "clean".to_string()), ("history", "new".to_string())];
+
+        let mut writes = Vec::new();
+        let saved = autosave.run(start + Duration::from_secs(30), snapshots(), |store| {
+            writes.push(store.to_string());
+            Ok(())
+        });
+        assert!(saved.is_empty(), "nothing is saved before the interval");
+
+        let saved = autosave.run(start + Duration::from_secs(60), snapshots(), |store| {
+            writes.push(store.to_string());
+            Ok(())
+        });
+        assert_eq!(saved, vec!["history"]);
+        assert_eq!(writes, vec!["history"]);
+
+        let saved = autosave.run(start + Duration::from_secs(120), snapshots(), |_| panic!("clean state saved"));
+        assert!(saved.is_empty());
+    }
+
+    #[test]
+    fn test_autosave_retries_failed_save() {
+        let start = Instant::now();
+        let mut autosave = Autosave::new(Duration::from_secs(1), start);
+        let snapshots = || vec![("bookmarks", "[]".to_string())];
+
+        let saved = autosave.run(start + Duration::from_secs(1), snapshots(), |_| Err(anyhow!("disk full")));
+        assert!(saved.is_empty());
+        assert!(autosave.is_dirty("bookmarks", "[]"));
+        let saved = autosave.run(start + Duration::from_secs(2), snapshots(), |_| Ok(()));
+        assert_eq!(saved, vec!["bookmarks"]);
+    }
+
+    #[test]
+    fn test_recovery_file_round_trip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut recovery = RecoveryFile::new(dir.path().join("session_recovery.json"));
+        assert!(recovery.read()?.is_none());
+
+        let snapshot = SessionSnapshot {
+            tabs: vec![SessionTab {
+                path: dir.path().to_path_buf(),
+                selected_index: 3,
+                scroll_offset: 1,
+                title: Some("work".to_string()),
+            }],
+            active_tab_index: 0,
+            search_query: "notes".to_string(),
+        };
+        assert!(recovery.write(&snapshot)?);
+        assert!(!recovery.write(&snapshot)?, "unchanged snapshot is written again");
+        assert_eq!(recovery.read()?, Some(snapshot));
+
+        recovery.remove()?;
+        assert!(recovery.read()?.is_none());
+        recovery.remove()?;
+        Ok(())
+    }
+
     #[test]
     fn test_default_settings() {
         let settings = UserSettings::default();
@@ -397,4 +749,44 @@ mod tests {
         assert_eq!(settings.bookmarks.len(), 1);
         assert_eq!(settings.bookmarks[0].id, id);
     }
+
+    #[test]
+    fn test_settings_without_custom_orders_still_load() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let listing_dir = dir.path().join("project");
+        let manager = PersistenceManager {
+            config_dir: dir.path().to_path_buf(),
+            settings_file: dir.path().join("settings.json"),
+        };
+
+        let mut settings = UserSettings::default();
+        let order = vec!["src.txt".to_string(), "README.md".to_string()];
+        settings.custom_orders.insert(listing_dir.clone(), order);
+        manager.save_settings(&settings)?;
+        assert_eq!(manager.load_settings()?.custom_orders[&listing_dir].len(), 2);
+
+        // Settings files written before custom orders existed still load
+        let mut legacy = serde_json::to_value(&settings)?;
+        legacy.as_object_mut().unwrap().remove("custom_orders");
+        fs::write(dir.path().join("settings.json"), legacy.to_string())?;
+        assert!(manager.load_settings()?.custom_orders.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recent_files_evict_the_oldest() {
+        let start = Utc::now();
+        let mut recent = Vec::new();
+        for (i, name) in ["a.txt", "b.txt", "c.txt", "d.txt"].iter().enumerate() {
+            push_recent_file(&mut recent, Path::new(name), start + chrono::Duration::seconds(i as i64), 3);
+        }
+        let names = |recent: &[RecentFileState]| recent.iter().map(|f| f.path.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&recent), vec![PathBuf::from("d.txt"), PathBuf::from("c.txt"), PathBuf::from("b.txt")]);
+
+        // Reopening moves a file to the front instead of listing it twice
+        let later = start + chrono::Duration::seconds(60);
+        push_recent_file(&mut recent, Path::new("b.txt"), later, 3);
+        assert_eq!(names(&recent), vec![PathBuf::from("b.txt"), PathBuf::from("d.txt"), PathBuf::from("c.txt")]);
+        assert_eq!(recent[0].opened_at, later);
+    }
 }
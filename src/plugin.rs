@@ -122,6 +122,17 @@ impl PluginManager {
         &self.plugin_dir
     }
 
+    /// Load plugins from `plugin_dir` from now on; takes effect on the next `reload`
+    pub fn set_plugin_dir(&mut self, plugin_dir: PathBuf) {
+        self.plugin_dir = plugin_dir;
+    }
+
+    /// Forget every plugin and scan the plugin directory again
+    pub fn reload(&mut self) -> Result<()> {
+        self.plugins.clear();
+        self.load_plugins()
+    }
+
     /// Get count of plugins
     pub fn count(&self) -> usize {
         self.plugins.len()
@@ -198,32 +209,14 @@ pub mod builtin {
                 return Err(anyhow!("No directory path provided"));
             }
 
+            // `dir-stats <path> [--follow-symlinks]`
             let path = Path::new(&args[0]);
-            if !path.is_dir() {
-                return Err(anyhow!("Not a directory"));
-            }
-
-            let mut file_count = 0;
-            let mut dir_count = 0;
-            let mut total_size = 0u64;
-
-            for entry in walkdir::WalkDir::new(path)
-                .into_iter()
-                .filter_map(|e| e.ok())
-            {
-                if entry.path().is_dir() {
-                    dir_count += 1;
-                } else {
-                    file_count += 1;
-                    if let Ok(metadata) = std::fs::metadata(entry.path()) {
-                        total_size += metadata.len();
-                    }
-                }
-            }
+            let follow_symlinks = args[1..].iter().any(|a| a == "--follow-symlinks");
+            let stats = crate::files::dir_stats(path, follow_symlinks)?;
 
             let stats = format!(
                 "Directories: {}\nFiles: {}\nTotal Size: {} bytes",
-                dir_count, file_count, total_size
+                stats.dirs, stats.files, stats.total_size
             );
 
             Ok(stats)
@@ -241,4 +234,24 @@ mod tests {
         assert_eq!(manager.count(), 0);
         Ok(())
     }
+
+    #[test]
+    fn test_reload_from_another_directory() -> Result<()> {
+        let first = tempfile::tempdir()?;
+        let second = tempfile::tempdir()?;
+        std::fs::write(second.path().join("extra.so"), "")?;
+        let mut manager = PluginManager::new(first.path().to_path_buf());
+        manager.load_plugins()?;
+        assert_eq!(manager.count(), 0);
+
+        manager.set_plugin_dir(second.path().to_path_buf());
+        manager.reload()?;
+        assert!(manager.get("extra").is_some());
+
+        // Plugins from the old directory don't linger
+        manager.set_plugin_dir(first.path().to_path_buf());
+        manager.reload()?;
+        assert_eq!(manager.count(), 0);
+        Ok(())
+    }
 }
@@ -1,7 +1,12 @@
 // Enhanced Plugin API - fully-featured plugin system
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use crate::persistence::PersistenceManager;
+use crate::wasm_plugin::{is_wasm_plugin, WasmPlugin};
 
 /// Core plugin trait with complete API access
 pub trait Plugin: Send + Sync {
@@ -11,6 +16,9 @@ pub trait Plugin: Send + Sync {
     fn description(&self) -> &str;
     fn author(&self) -> &str;
 
+    /// Receive the plugin's context; called once on registration, before `on_load`
+    fn attach(&mut self, _ctx: PluginContext) {}
+
     /// Lifecycle hooks
     fn on_load(&mut self) -> Result<()> {
         Ok(())
@@ -42,6 +50,11 @@ pub trait Plugin: Send + Sync {
         Ok(())
     }
 
+    /// Navigation hook: the active tab moved from `old` to `new`
+    fn on_directory_changed(&self, _old: &PathBuf, _new: &PathBuf) -> Result<()> {
+        Ok(())
+    }
+
     /// Command execution
     fn execute_command(&self, _command: &str, _args: Vec<String>) -> Result<String> {
         Ok(String::new())
@@ -75,14 +88,6 @@ pub trait Plugin: Send + Sync {
         Ok(())
     }
 
-    /// Settings persistence for plugin
-    fn save_data(&self, _key: &str, _value: serde_json::Value) -> Result<()> {
-        Ok(())
-    }
-    fn load_data(&self, _key: &str) -> Result<Option<serde_json::Value>> {
-        Ok(None)
-    }
-
     /// Performance metrics
     fn on_idle(&self) -> Result<()> {
         Ok(())
@@ -92,6 +97,59 @@ pub trait Plugin: Send + Sync {
     }
 }
 
+/// Plugin data for every plugin, keyed by plugin ID then by key
+pub type PluginDataMap = HashMap<String, serde_json::Value>;
+
+/// Handle a plugin uses to reach the host, scoped to that plugin.
+///
+/// Data saved here ends up in `plugin_settings[plugin_id]` of the user
+/// settings and is available again after a reload or restart. With a
+/// settings store attached it is written there straight away.
+#[derive(Clone)]
+pub struct PluginContext {
+    plugin_id: String,
+    data: Arc<Mutex<PluginDataMap>>,
+    persistence: Option<Arc<PersistenceManager>>,
+}
+
+impl PluginContext {
+    #[allow(dead_code)]
+    pub fn plugin_id(&self) -> &str {
+        &self.plugin_id
+    }
+
+    /// Store a value under `key` for this plugin
+    pub fn save_data(&self, key: &str, value: serde_json::Value) -> Result<()> {
+        let mut data = self.data.lock().map_err(|_| anyhow!("plugin data lock poisoned"))?;
+        let entry = data
+            .entry(self.plugin_id.clone())
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+        // Older settings may hold a non-object value; start over in that case
+        if !entry.is_object() {
+            *entry = serde_json::Value::Object(Default::default());
+        }
+        if let Some(map) = entry.as_object_mut() {
+            map.insert(key.to_string(), value);
+        }
+        if let Some(persistence) = &self.persistence {
+            persistence.save_plugin_data(&self.plugin_id, entry.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Read back a value stored with `save_data`
+    pub fn load_data(&self, key: &str) -> Result<Option<serde_json::Value>> {
+        let data = self.data.lock().map_err(|_| anyhow!("plugin data lock poisoned"))?;
+        if let Some(stored) = data.get(&self.plugin_id) {
+            return Ok(stored.get(key).cloned());
+        }
+        match &self.persistence {
+            Some(persistence) => Ok(persistence.get_plugin_data(&self.plugin_id)?.and_then(|v| v.get(key).cloned())),
+            None => Ok(None),
+        }
+    }
+}
+
 /// A single command exposed by a plugin
 #[derive(Clone, Debug)]
 pub struct PluginCommand {
@@ -145,6 +203,18 @@ pub struct PluginStats {
     pub errors: u64,
 }
 
+impl PluginStats {
+    /// Combine the manager's own counters with what the plugin reports
+    fn merged(&self, reported: &PluginStats) -> PluginStats {
+        PluginStats {
+            load_time_ms: self.load_time_ms.max(reported.load_time_ms),
+            memory_usage_bytes: reported.memory_usage_bytes,
+            function_calls: self.function_calls + reported.function_calls,
+            errors: self.errors + reported.errors,
+        }
+    }
+}
+
 /// Plugin metadata for registry
 #[derive(Clone, Debug)]
 pub struct PluginMetadata {
@@ -188,10 +258,64 @@ pub enum PluginPermission {
     ModifyOtherPlugins,
 }
 
+impl PluginPermission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginPermission::ReadFiles => "read-files",
+            PluginPermission::WriteFiles => "write-files",
+            PluginPermission::DeleteFiles => "delete-files",
+            PluginPermission::ExecuteFiles => "execute-files",
+            PluginPermission::RenderUI => "render-ui",
+            PluginPermission::InterceptInput => "intercept-input",
+            PluginPermission::ModifyTheme => "modify-theme",
+            PluginPermission::ExecuteCommands => "execute-commands",
+            PluginPermission::NetworkAccess => "network",
+            PluginPermission::AccessSettings => "settings",
+            PluginPermission::AccessBookmarks => "bookmarks",
+            PluginPermission::AccessHistory => "history",
+            PluginPermission::AccessClipboard => "clipboard",
+            PluginPermission::LoadPlugins => "load-plugins",
+            PluginPermission::UnloadPlugins => "unload-plugins",
+            PluginPermission::ModifyOtherPlugins => "modify-plugins",
+        }
+    }
+
+    /// Parse a permission name as written by `as_str`
+    pub fn parse(name: &str) -> Option<Self> {
+        const ALL: [PluginPermission; 16] = [
+            PluginPermission::ReadFiles,
+            PluginPermission::WriteFiles,
+            PluginPermission::DeleteFiles,
+            PluginPermission::ExecuteFiles,
+            PluginPermission::RenderUI,
+            PluginPermission::InterceptInput,
+            PluginPermission::ModifyTheme,
+            PluginPermission::ExecuteCommands,
+            PluginPermission::NetworkAccess,
+            PluginPermission::AccessSettings,
+            PluginPermission::AccessBookmarks,
+            PluginPermission::AccessHistory,
+            PluginPermission::AccessClipboard,
+            PluginPermission::LoadPlugins,
+            PluginPermission::UnloadPlugins,
+            PluginPermission::ModifyOtherPlugins,
+        ];
+        ALL.into_iter().find(|p| p.as_str() == name)
+    }
+}
+
 /// Plugin registry and manager
 pub struct PluginManager {
     plugins: HashMap<String, Box<dyn Plugin>>,
     metadata: HashMap<String, PluginMetadata>,
+    // Load time, hook calls and hook errors, tracked by the manager
+    stats: HashMap<String, PluginStats>,
+    // Plugin files that failed to load, with the reason
+    load_errors: Vec<(PathBuf, String)>,
+    // Data saved by plugins through their contexts
+    data: Arc<Mutex<PluginDataMap>>,
+    // Where contexts write plugin data as it is saved
+    persistence: Option<Arc<PersistenceManager>>,
     plugin_dir: PathBuf,
 }
 
@@ -200,6 +324,10 @@ impl Default for PluginManager {
         Self {
             plugins: HashMap::new(),
             metadata: HashMap::new(),
+            stats: HashMap::new(),
+            load_errors: Vec::new(),
+            data: Arc::new(Mutex::new(HashMap::new())),
+            persistence: None,
             plugin_dir: PathBuf::from("./plugins"),
         }
     }
@@ -210,16 +338,108 @@ impl PluginManager {
         Self {
             plugins: HashMap::new(),
             metadata: HashMap::new(),
+            stats: HashMap::new(),
+            load_errors: Vec::new(),
+            data: Arc::new(Mutex::new(HashMap::new())),
+            persistence: None,
             plugin_dir,
         }
     }
 
-    /// Register a plugin
-    pub fn register(&mut self, id: String, plugin: Box<dyn Plugin>, meta: PluginMetadata) {
+    /// Register a plugin and run its `on_load` hook
+    pub fn register(&mut self, id: String, mut plugin: Box<dyn Plugin>, meta: PluginMetadata) {
+        plugin.attach(self.context(&id));
+
+        let mut stats = PluginStats::default();
+        let started = Instant::now();
+        if plugin.on_load().is_err() {
+            stats.errors += 1;
+        }
+        stats.load_time_ms = started.elapsed().as_millis() as u64;
+
         self.metadata.insert(id.clone(), meta);
+        self.stats.insert(id.clone(), stats);
         self.plugins.insert(id, plugin);
     }
 
+    /// Register the example plugins that ship with AstroFS, disabled
+    pub fn register_builtin(&mut self) {
+        let builtin: Vec<(&str, Box<dyn Plugin>, Vec<PluginPermission>)> = vec![
+            ("file-stats", Box::new(FileStatsPlugin::new()), vec![PluginPermission::ReadFiles]),
+            ("theme-customizer", Box::new(ThemeCustomizer), vec![PluginPermission::ModifyTheme]),
+        ];
+
+        for (id, plugin, permissions) in builtin {
+            let meta = PluginMetadata {
+                id: id.to_string(),
+                name: plugin.name().to_string(),
+                version: plugin.version().to_string(),
+                description: plugin.description().to_string(),
+                author: plugin.author().to_string(),
+                path: PathBuf::new(),
+                enabled: false,
+                permissions,
+            };
+            self.register(id.to_string(), plugin, meta);
+        }
+    }
+
+    /// Context handed to the plugin registered as `id`
+    pub fn context(&self, id: &str) -> PluginContext {
+        PluginContext {
+            plugin_id: id.to_string(),
+            data: Arc::clone(&self.data),
+            persistence: self.persistence.clone(),
+        }
+    }
+
+    /// Save plugin data to `persistence` as plugins store it; only contexts
+    /// handed out afterwards use it, so call this before registering plugins
+    pub fn set_persistence(&mut self, persistence: PersistenceManager) {
+        self.persistence = Some(Arc::new(persistence));
+    }
+
+    /// Replace all plugin data, e.g. with `plugin_settings` from the user settings
+    pub fn set_plugin_data(&mut self, data: PluginDataMap) {
+        if let Ok(mut current) = self.data.lock() {
+            *current = data;
+        }
+    }
+
+    /// Snapshot of all plugin data, for saving into the user settings
+    pub fn plugin_data(&self) -> PluginDataMap {
+        self.data.lock().map(|data| data.clone()).unwrap_or_default()
+    }
+
+    /// Get metadata for a plugin
+    pub fn metadata(&self, id: &str) -> Option<&PluginMetadata> {
+        self.metadata.get(id)
+    }
+
+    /// Live statistics for a plugin
+    pub fn stats(&self, id: &str) -> Option<PluginStats> {
+        let plugin = self.plugins.get(id)?;
+        let tracked = self.stats.get(id).cloned().unwrap_or_default();
+        Some(tracked.merged(&plugin.get_stats()))
+    }
+
+    /// Run `on_unload` then `on_load` again, re-measuring load time
+    pub fn reload(&mut self, id: &str) -> Result<()> {
+        let plugin = self
+            .plugins
+            .get_mut(id)
+            .ok_or_else(|| anyhow!("Unknown plugin '{}'", id))?;
+        let stats = self.stats.entry(id.to_string()).or_default();
+
+        let started = Instant::now();
+        let result = plugin.on_unload().and_then(|_| plugin.on_load());
+        stats.load_time_ms = started.elapsed().as_millis() as u64;
+        if result.is_err() {
+            stats.errors += 1;
+        }
+        result
+    }
+
     /// Get plugin by ID
     pub fn get(&self, id: &str) -> Option<&(dyn Plugin + 'static)> {
         self.plugins.get(id).map(|p| p.as_ref())
@@ -235,10 +455,20 @@ impl PluginManager {
         self.metadata.values().collect()
     }
 
+    /// Whether a plugin is registered and enabled
+    pub fn is_enabled(&self, id: &str) -> bool {
+        self.metadata.get(id).map(|m| m.enabled).unwrap_or(false)
+    }
+
     /// Enable plugin
     pub fn enable(&mut self, id: &str) -> Result<()> {
-        if let Some(plugin) = self.plugins.get_mut(id) {
-            plugin.on_enable()?;
+        let plugin = self
+            .plugins
+            .get_mut(id)
+            .ok_or_else(|| anyhow!("Unknown plugin '{}'", id))?;
+        if let Err(e) = plugin.on_enable() {
+            self.stats.entry(id.to_string()).or_default().errors += 1;
+            return Err(e);
         }
         if let Some(meta) = self.metadata.get_mut(id) {
             meta.enabled = true;
@@ -248,8 +478,13 @@ impl PluginManager {
 
     /// Disable plugin
     pub fn disable(&mut self, id: &str) -> Result<()> {
-        if let Some(plugin) = self.plugins.get_mut(id) {
-            plugin.on_disable()?;
+        let plugin = self
+            .plugins
+            .get_mut(id)
+            .ok_or_else(|| anyhow!("Unknown plugin '{}'", id))?;
+        if let Err(e) = plugin.on_disable() {
+            self.stats.entry(id.to_string()).or_default().errors += 1;
+            return Err(e);
         }
         if let Some(meta) = self.metadata.get_mut(id) {
             meta.enabled = false;
@@ -257,20 +492,60 @@ impl PluginManager {
         Ok(())
     }
 
-    /// Load all plugins from directory
+    /// Load all plugins from directory.
+    ///
+    /// WASM modules are loaded disabled; already registered IDs are skipped
+    /// and files that fail to load are listed by `load_errors`.
     pub fn load_all(&mut self) -> Result<()> {
         if !self.plugin_dir.exists() {
             std::fs::create_dir_all(&self.plugin_dir)?;
         }
+
+        self.load_errors.clear();
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&self.plugin_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| is_wasm_plugin(path))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match WasmPlugin::load(&path) {
+                Ok((plugin, meta)) => {
+                    if self.plugins.contains_key(&meta.id) {
+                        continue;
+                    }
+                    self.register(meta.id.clone(), Box::new(plugin), meta);
+                }
+                Err(e) => self.load_errors.push((path, e.to_string())),
+            }
+        }
         Ok(())
     }
 
+    /// Directory `load_all` scans
+    #[allow(dead_code)]
+    pub fn plugin_dir(&self) -> &Path {
+        &self.plugin_dir
+    }
+
+    /// Scan `plugin_dir` in later `load_all` calls; plugins already loaded stay
+    pub fn set_plugin_dir(&mut self, plugin_dir: PathBuf) {
+        self.plugin_dir = plugin_dir;
+    }
+
+    /// Plugin files that failed to load in the last `load_all`
+    pub fn load_errors(&self) -> &[(PathBuf, String)] {
+        &self.load_errors
+    }
+
     /// Unload all plugins
     pub fn unload_all(&mut self) -> Result<()> {
         for plugin in self.plugins.values_mut() {
             plugin.on_unload()?;
         }
         self.plugins.clear();
+        self.metadata.clear();
+        self.stats.clear();
         Ok(())
     }
 
@@ -288,40 +563,51 @@ impl PluginManager {
         }
     }
 
+    /// Run a hook on every enabled plugin, counting calls and errors.
+    ///
+    /// A failing plugin doesn't stop the others; the first error is returned.
+    fn call_hook<F>(&mut self, mut hook: F) -> Result<()>
+    where
+        F: FnMut(&mut dyn Plugin) -> Result<()>,
+    {
+        let mut first_error = None;
+        for (id, plugin) in self.plugins.iter_mut() {
+            if !self.metadata.get(id).map(|m| m.enabled).unwrap_or(false) {
+                continue;
+            }
+            let stats = self.stats.entry(id.clone()).or_default();
+            stats.function_calls += 1;
+            if let Err(e) = hook(plugin.as_mut()) {
+                stats.errors += 1;
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
     /// Call hooks on all plugins
     pub fn call_file_created(&mut self, path: &PathBuf) -> Result<()> {
-        for plugin in self.plugins.values_mut() {
-            plugin.on_file_created(path)?;
-        }
-        Ok(())
+        self.call_hook(|plugin| plugin.on_file_created(path))
     }
 
     pub fn call_file_deleted(&mut self, path: &PathBuf) -> Result<()> {
-        for plugin in self.plugins.values_mut() {
-            plugin.on_file_deleted(path)?;
-        }
-        Ok(())
+        self.call_hook(|plugin| plugin.on_file_deleted(path))
     }
 
     pub fn call_file_renamed(&mut self, old: &PathBuf, new: &PathBuf) -> Result<()> {
-        for plugin in self.plugins.values_mut() {
-            plugin.on_file_renamed(old, new)?;
-        }
-        Ok(())
+        self.call_hook(|plugin| plugin.on_file_renamed(old, new))
+    }
+
+    pub fn call_directory_changed(&mut self, old: &PathBuf, new: &PathBuf) -> Result<()> {
+        self.call_hook(|plugin| plugin.on_directory_changed(old, new))
     }
 
     pub fn call_theme_changed(&mut self, theme_name: &str) -> Result<()> {
-        for plugin in self.plugins.values_mut() {
-            plugin.on_theme_changed(theme_name)?;
-        }
-        Ok(())
+        self.call_hook(|plugin| plugin.on_theme_changed(theme_name))
     }
 
     pub fn call_idle(&mut self) -> Result<()> {
-        for plugin in self.plugins.values_mut() {
-            plugin.on_idle()?;
-        }
-        Ok(())
+        self.call_hook(|plugin| plugin.on_idle())
     }
 
     /// Get all commands from all plugins
@@ -351,18 +637,29 @@ impl PluginManager {
 
 /// Example: File statistics plugin
 pub struct FileStatsPlugin {
-    total_files_processed: u64,
+    total_files_processed: AtomicU64,
+    ctx: Option<PluginContext>,
 }
 
 impl FileStatsPlugin {
     pub fn new() -> Self {
         Self {
-            total_files_processed: 0,
+            total_files_processed: AtomicU64::new(0),
+            ctx: None,
         }
     }
     
     pub fn total_files_processed(&self) -> u64 {
-        self.total_files_processed
+        self.total_files_processed.load(Ordering::Relaxed)
+    }
+
+    /// Count one file operation and persist the running total
+    fn record(&self) -> Result<()> {
+        let total = self.total_files_processed.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(ctx) = &self.ctx {
+            ctx.save_data("total_files_processed", total.into())?;
+        }
+        Ok(())
     }
 }
 
@@ -380,10 +677,27 @@ impl Plugin for FileStatsPlugin {
         "AstroFS Team"
     }
 
-    fn on_file_created(&self, _path: &PathBuf) -> Result<()> {
+    fn attach(&mut self, ctx: PluginContext) {
+        self.ctx = Some(ctx);
+    }
+
+    fn on_load(&mut self) -> Result<()> {
+        if let Some(ctx) = &self.ctx {
+            let saved = ctx.load_data("total_files_processed")?;
+            let total = saved.and_then(|v| v.as_u64()).unwrap_or(0);
+            self.total_files_processed.store(total, Ordering::Relaxed);
+        }
         Ok(())
     }
 
+    fn on_file_created(&self, _path: &PathBuf) -> Result<()> {
+        self.record()
+    }
+
+    fn on_file_deleted(&self, _path: &PathBuf) -> Result<()> {
+        self.record()
+    }
+
     fn get_commands(&self) -> Vec<PluginCommand> {
         vec![PluginCommand {
             name: "show-stats".to_string(),
@@ -436,4 +750,188 @@ mod tests {
         };
         assert_eq!(stats.load_time_ms, 100);
     }
+
+    struct FailingPlugin;
+
+    impl Plugin for FailingPlugin {
+        fn name(&self) -> &str {
+            "Failing"
+        }
+        fn version(&self) -> &str {
+            "0.1.0"
+        }
+        fn description(&self) -> &str {
+            "Fails every file hook"
+        }
+        fn author(&self) -> &str {
+            "tests"
+        }
+        fn on_file_created(&self, _path: &PathBuf) -> Result<()> {
+            Err(anyhow!("boom"))
+        }
+    }
+
+    #[test]
+    fn test_hooks_update_stats_for_enabled_plugins() -> Result<()> {
+        let mut manager = PluginManager::default();
+        manager.register_builtin();
+        let meta = PluginMetadata {
+            id: "failing".to_string(),
+            name: "Failing".to_string(),
+            version: "0.1.0".to_string(),
+            description: String::new(),
+            author: String::new(),
+            path: PathBuf::new(),
+            enabled: true,
+            permissions: Vec::new(),
+        };
+        manager.register("failing".to_string(), Box::new(FailingPlugin), meta);
+        manager.enable("file-stats")?;
+
+        let path = PathBuf::from("new.txt");
+        assert!(manager.call_file_created(&path).is_err());
+        manager.call_file_deleted(&path)?;
+
+        let stats = manager.stats("file-stats").unwrap();
+        assert_eq!((stats.function_calls, stats.errors), (2, 0));
+        let stats = manager.stats("failing").unwrap();
+        assert_eq!((stats.function_calls, stats.errors), (2, 1));
+        // Disabled plugins are skipped
+        assert_eq!(manager.stats("theme-customizer").unwrap().function_calls, 0);
+
+        manager.reload("failing")?;
+        assert!(manager.reload("missing").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_plugin_data_survives_reload() -> Result<()> {
+        let mut manager = PluginManager::default();
+        manager.register_builtin();
+        manager.enable("file-stats")?;
+        let path = PathBuf::from("a.txt");
+        manager.call_file_created(&path)?;
+        manager.call_file_deleted(&path)?;
+
+        let ctx = manager.context("file-stats");
+        assert_eq!(ctx.load_data("total_files_processed")?, Some(2.into()));
+        // Data is scoped per plugin
+        assert_eq!(manager.context("theme-customizer").load_data("total_files_processed")?, None);
+
+        // Round-trip through the user settings into a fresh manager
+        let settings = crate::persistence::UserSettings {
+            plugin_settings: manager.plugin_data(),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&settings)?;
+        let restored: crate::persistence::UserSettings = serde_json::from_str(&json)?;
+
+        let mut manager = PluginManager::default();
+        manager.set_plugin_data(restored.plugin_settings);
+        manager.register_builtin();
+        manager.enable("file-stats")?;
+        manager.call_file_created(&path)?;
+        assert_eq!(manager.context("file-stats").load_data("total_files_processed")?, Some(3.into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plugin_data_is_saved_to_settings() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut manager = PluginManager::default();
+        manager.set_persistence(PersistenceManager::with_dir(dir.path().to_path_buf())?);
+        manager.context("counter").save_data("count", 7.into())?;
+
+        // Written to settings.json without the app saving its settings
+        let persistence = PersistenceManager::with_dir(dir.path().to_path_buf())?;
+        assert_eq!(persistence.get_plugin_data("counter")?, Some(serde_json::json!({ "count": 7 })));
+
+        // A fresh manager reads it back from the store
+        let mut manager = PluginManager::default();
+        manager.set_persistence(persistence);
+        assert_eq!(manager.context("counter").load_data("count")?, Some(7.into()));
+        assert_eq!(manager.context("other").load_data("count")?, None);
+        Ok(())
+    }
+
+    /// Counts the hooks the app calls while it is being used
+    struct CountingPlugin {
+        created: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        idle: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Plugin for CountingPlugin {
+        fn name(&self) -> &str {
+            "Counting"
+        }
+        fn version(&self) -> &str {
+            "0.1.0"
+        }
+        fn description(&self) -> &str {
+            "Counts hook calls"
+        }
+        fn author(&self) -> &str {
+            "tests"
+        }
+        fn on_file_created(&self, _path: &PathBuf) -> Result<()> {
+            self.created.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+        fn on_idle(&self) -> Result<()> {
+            self.idle.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_app_calls_plugin_hooks() -> Result<()> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir()?;
+        let state = tempfile::tempdir()?;
+        let config = crate::config::AppConfig {
+            default_directory: dir.path().to_string_lossy().to_string(),
+            enable_plugins: false,
+            ..Default::default()
+        };
+        let mut app = crate::app::App::with_dirs(config, crate::app::AppDirs::under(state.path()))?;
+
+        let created = Arc::new(AtomicUsize::new(0));
+        let idle = Arc::new(AtomicUsize::new(0));
+        let plugin = CountingPlugin {
+            created: Arc::clone(&created),
+            idle: Arc::clone(&idle),
+        };
+        let meta = PluginMetadata {
+            id: "counting".to_string(),
+            name: "Counting".to_string(),
+            version: "0.1.0".to_string(),
+            description: String::new(),
+            author: String::new(),
+            path: PathBuf::new(),
+            enabled: true,
+            permissions: Vec::new(),
+        };
+        app.api_plugin_manager.register("counting".to_string(), Box::new(plugin), meta);
+
+        app.create_file("notes.txt")?;
+        app.create_directory("src")?;
+        assert!(dir.path().join("notes.txt").exists());
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+
+        // Idle hooks fire once input has been quiet long enough, and only once
+        app.run_idle_hooks();
+        assert_eq!(idle.load(Ordering::SeqCst), 0);
+        let quiet_since = std::time::Instant::now() - crate::input::IDLE_AFTER;
+        app.idle = crate::input::IdleTracker::new(quiet_since);
+        app.run_idle_hooks();
+        app.run_idle_hooks();
+        assert_eq!(idle.load(Ordering::SeqCst), 1);
+
+        app.note_input();
+        app.run_idle_hooks();
+        assert_eq!(idle.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
 }
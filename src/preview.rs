@@ -1,9 +1,16 @@
+use crate::logging::LogErr;
+use crate::markup::{Markup, RenderMode};
+use crate::theme::{file_icon, IconSet};
+use chrono::TimeZone;
+use std::collections::HashMap;
 use std::fs;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
 use syntect::easy::HighlightLines;
-use syntect::highlighting::ThemeSet;
-use syntect::parsing::SyntaxSet;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
 use syntect::util::LinesWithEndings;
 use ratatui::style::{Color, Style};
 use ratatui::text::{Line, Span};
@@ -23,9 +30,152 @@ pub enum PreviewType {
     Binary,
     Image,
     Archive,
+    /// PDF document info and first-page text
+    Document,
+    /// Audio or video details read from the file's headers
+    Metadata,
+    /// JSON, YAML or TOML shown as a tree
+    Structured,
+    /// Markdown or HTML shown formatted rather than as source
+    Rendered,
     Error(String),
 }
 
+/// Highlight theme used when the config doesn't name a known one
+pub const DEFAULT_HIGHLIGHT_THEME: &str = "base16-ocean.dark";
+
+/// Syntax highlighting and preview choices from the config
+#[derive(Clone, Debug, Default)]
+pub struct HighlightOptions {
+    /// File name (e.g. `Dockerfile`) or extension → syntect syntax name or extension token
+    pub syntax_overrides: HashMap<String, String>,
+    pub theme: String,
+    /// Folder of extra `.tmTheme` files; None for the bundled themes only
+    pub theme_dir: Option<PathBuf>,
+    /// Lowercase extension (no dot) → preview kind used instead of detection
+    pub preview_overrides: HashMap<String, PreviewType>,
+    /// Glyphs for entries in directory previews, matching the file list
+    pub icons: IconSet,
+    /// `date_format` for times in metadata previews; empty for the default
+    pub date_format: String,
+    /// Markdown and HTML shown rendered or as source; None for each type's default
+    pub render_mode: Option<RenderMode>,
+}
+
+/// Names accepted in `preview_overrides`
+pub const PREVIEW_MODES: &[&str] = &["text", "code", "hex", "metadata", "archive", "rendered"];
+
+/// The preview kind a `preview_overrides` mode name stands for
+pub fn preview_type_for_mode(mode: &str) -> Option<PreviewType> {
+    match mode.trim().to_lowercase().as_str() {
+        "text" => Some(PreviewType::Text),
+        "code" => Some(PreviewType::Code),
+        "hex" => Some(PreviewType::Binary),
+        "metadata" => Some(PreviewType::Metadata),
+        "archive" => Some(PreviewType::Archive),
+        "rendered" => Some(PreviewType::Rendered),
+        _ => None,
+    }
+}
+
+// Loading syntect's bundled definitions is slow, so do it once
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// Theme sets by custom theme folder, with when each folder last changed
+type LoadedThemes = HashMap<Option<PathBuf>, (Option<SystemTime>, Arc<ThemeSet>)>;
+
+/// The bundled themes plus those in `custom_dir`, loaded again once a theme
+/// there is added, removed or edited
+fn theme_set(custom_dir: Option<&Path>) -> Arc<ThemeSet> {
+    static THEMES: OnceLock<Mutex<LoadedThemes>> = OnceLock::new();
+    let modified = custom_dir.and_then(themes_modified);
+    let mut loaded = THEMES.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    match loaded.get(&custom_dir.map(Path::to_path_buf)) {
+        Some((when, set)) if *when == modified => Arc::clone(set),
+        _ => {
+            let set = Arc::new(load_theme_set(custom_dir));
+            loaded.insert(custom_dir.map(Path::to_path_buf), (modified, Arc::clone(&set)));
+            set
+        }
+    }
+}
+
+/// The latest change to `dir` or a theme file in it
+fn themes_modified(dir: &Path) -> Option<SystemTime> {
+    let entries = fs::read_dir(dir).ok()?;
+    let files = entries.filter_map(|e| e.ok()?.metadata().ok()?.modified().ok());
+    files.chain(fs::metadata(dir).and_then(|m| m.modified()).ok()).max()
+}
+
+/// The bundled themes plus every `.tmTheme` in `custom_dir`, named after its file stem.
+/// A custom theme with a bundled theme's name replaces it; unreadable files are skipped.
+pub fn load_theme_set(custom_dir: Option<&Path>) -> ThemeSet {
+    let mut set = ThemeSet::load_defaults();
+    let Some(entries) = custom_dir.and_then(|dir| fs::read_dir(dir).ok()) else {
+        return set;
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.extension().and_then(|e| e.to_str()) != Some("tmTheme") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if let Some(theme) = ThemeSet::get_theme(&path).log_err(&format!("load highlight theme {:?}", path)) {
+            set.themes.insert(name.to_string(), theme);
+        }
+    }
+    set
+}
+
+/// The named theme, else the default one, else any theme in the set.
+/// `None` only for an empty set, in which case previews fall back to plain text.
+fn resolve_theme<'a>(set: &'a ThemeSet, name: &str) -> Option<&'a Theme> {
+    set.themes
+        .get(name)
+        .or_else(|| set.themes.get(DEFAULT_HIGHLIGHT_THEME))
+        .or_else(|| set.themes.values().next())
+}
+
+/// Look up a syntax by its name ("Rust") or one of its extensions ("rs")
+fn syntax_by_name(name: &str) -> Option<&'static SyntaxReference> {
+    let ps = syntax_set();
+    ps.find_syntax_by_name(name).or_else(|| ps.find_syntax_by_token(name))
+}
+
+pub fn is_known_syntax(name: &str) -> bool {
+    syntax_by_name(name).is_some()
+}
+
+/// Names of the bundled highlight themes and those in `custom_dir`
+pub fn highlight_themes(custom_dir: Option<&Path>) -> Vec<String> {
+    let mut names: Vec<String> = theme_set(custom_dir).themes.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// The configured override for a file: its exact name first, then its extension
+fn override_syntax(path: &Path, overrides: &HashMap<String, String>) -> Option<&'static SyntaxReference> {
+    let file_name = path.file_name()?.to_str()?;
+    let ext = path.extension().and_then(|e| e.to_str());
+    let by_name = overrides.iter().find(|(key, _)| key.eq_ignore_ascii_case(file_name));
+    let by_ext = || {
+        let ext = ext?;
+        overrides
+            .iter()
+            .find(|(key, _)| key.trim_start_matches('.').eq_ignore_ascii_case(ext))
+    };
+    by_name.or_else(by_ext).and_then(|(_, syntax)| syntax_by_name(syntax))
+}
+
+/// Syntax used to highlight a file: a configured override, else syntect's own detection
+pub fn find_syntax(path: &Path, overrides: &HashMap<String, String>) -> Option<&'static SyntaxReference> {
+    override_syntax(path, overrides).or_else(|| syntax_set().find_syntax_for_file(path).ok().flatten())
+}
+
 #[derive(Debug, Clone)]
 pub struct ImageMetadata {
     pub width: u32,
@@ -33,7 +183,13 @@ pub struct ImageMetadata {
     pub format: String,
 }
 
+#[allow(dead_code)]
 pub fn generate_preview(path: &Path, max_lines: usize) -> PreviewContent {
+    generate_preview_with(path, max_lines, &HighlightOptions::default())
+}
+
+/// Preview a file, highlighting code with the configured syntaxes and theme
+pub fn generate_preview_with(path: &Path, max_lines: usize, options: &HighlightOptions) -> PreviewContent {
     if !path.exists() {
         return PreviewContent {
             lines: vec![Line::from("File not found")],
@@ -43,61 +199,214 @@ pub fn generate_preview(path: &Path, max_lines: usize) -> PreviewContent {
     }
 
     if path.is_dir() {
-        return preview_directory(path);
+        return preview_directory(path, options.icons);
+    }
+
+    // An explicit choice for the extension beats detection
+    let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+    if let Some(kind) = ext.and_then(|ext| options.preview_overrides.get(&ext)) {
+        return preview_as(path, kind, max_lines, options);
     }
 
     // Check if binary
-    if let Ok(kind) = infer::get_from_path(path) {
-        if let Some(kind) = kind {
-            match kind.mime_type() {
-                mime if mime.starts_with("image/") => {
-                    return preview_image(path);
-                }
-                mime if mime.starts_with("application/") && mime.contains("zip") => {
-                    return preview_archive(path);
-                }
-                _ => {}
-            }
+    let mime = crate::files::detect_mime(path);
+    match mime.as_deref() {
+        Some(mime) if mime.starts_with("image/") => {
+            return preview_image(path, options);
         }
+        Some(mime) if mime.starts_with("application/") && mime.contains("zip") => {
+            return preview_archive(path);
+        }
+        Some("application/pdf") => {
+            return preview_pdf(path, max_lines);
+        }
+        _ => {}
     }
 
     // Try to read as text
     match fs::read_to_string(path) {
         Ok(content) => {
-            if is_code_file(path) {
+            if let Some(markup) = Markup::of(path) {
+                match options.render_mode.unwrap_or(markup.default_mode()) {
+                    RenderMode::Rendered => preview_rendered(markup, &content, max_lines),
+                    RenderMode::Raw => preview_code_with_highlighting(path, &content, max_lines, options),
+                }
+            } else if let Some(format) = crate::structured::Format::of(path) {
+                preview_structured(path, format, &content, max_lines, options)
+            } else if is_code_file(path) || override_syntax(path, &options.syntax_overrides).is_some() {
                 // Use syntax highlighting for code files
-                preview_code_with_highlighting(path, &content, max_lines)
+                preview_code_with_highlighting(path, &content, max_lines, options)
             } else {
-                // Plain text
-                let lines: Vec<Line<'static>> = content
-                    .lines()
-                    .take(max_lines)
-                    .map(|s| Line::from(s.to_string()))
-                    .collect();
-
-                PreviewContent {
-                    lines,
-                    is_binary: false,
-                    preview_type: PreviewType::Text,
-                }
+                preview_plain_text(&content, max_lines)
             }
         }
-        Err(_) => PreviewContent {
-            lines: vec![
+        Err(_) => {
+            let mut lines = vec![
                 Line::from("⚠️  Binary file"),
                 Line::from(format!("Size: {}", humansize::format_size(
                     fs::metadata(path).map(|m| m.len()).unwrap_or(0),
                     humansize::BINARY
                 ))),
-            ],
-            is_binary: true,
-            preview_type: PreviewType::Binary,
+            ];
+            if let Some(mime) = mime {
+                lines.push(Line::from(format!("Type: {}", mime)));
+            }
+            PreviewContent {
+                lines,
+                is_binary: true,
+                preview_type: PreviewType::Binary,
+            }
+        }
+    }
+}
+
+/// Preview a file as `kind` whatever its contents, reading invalid UTF-8 lossily
+fn preview_as(path: &Path, kind: &PreviewType, max_lines: usize, options: &HighlightOptions) -> PreviewContent {
+    let text = || fs::read(path).map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+    match kind {
+        PreviewType::Text | PreviewType::Code => match text() {
+            Ok(content) if *kind == PreviewType::Code => preview_code_with_highlighting(path, &content, max_lines, options),
+            Ok(content) => preview_plain_text(&content, max_lines),
+            Err(e) => preview_error(e.to_string()),
+        },
+        PreviewType::Structured => match (text(), crate::structured::Format::of(path)) {
+            (Ok(content), Some(format)) => preview_structured(path, format, &content, max_lines, options),
+            (Ok(content), None) => preview_code_with_highlighting(path, &content, max_lines, options),
+            (Err(e), _) => preview_error(e.to_string()),
         },
+        PreviewType::Rendered => match text() {
+            Ok(content) => preview_rendered(Markup::of(path).unwrap_or(Markup::Markdown), &content, max_lines),
+            Err(e) => preview_error(e.to_string()),
+        },
+        PreviewType::Binary => preview_hex(path, max_lines),
+        PreviewType::Image => preview_image(path, options),
+        PreviewType::Archive => preview_archive(path),
+        PreviewType::Document => preview_pdf(path, max_lines),
+        PreviewType::Metadata => preview_metadata(path, max_lines, options),
+        PreviewType::Error(message) => preview_error(message.clone()),
+    }
+}
+
+/// The details a file's kind has: image size, PDF info, or audio and video headers
+fn preview_metadata(path: &Path, max_lines: usize, options: &HighlightOptions) -> PreviewContent {
+    use crate::media_preview::{detect_media_type, generate_media_preview, MediaType};
+    let is_pdf = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+    match detect_media_type(path) {
+        MediaType::Image => preview_image(path, options),
+        _ if is_pdf => preview_pdf(path, max_lines),
+        _ => match generate_media_preview(path) {
+            Ok(text) => PreviewContent {
+                lines: text.lines().take(max_lines).map(|line| Line::from(line.to_string())).collect(),
+                is_binary: true,
+                preview_type: PreviewType::Metadata,
+            },
+            Err(e) => preview_error(e.to_string()),
+        },
+    }
+}
+
+fn preview_rendered(markup: Markup, content: &str, max_lines: usize) -> PreviewContent {
+    PreviewContent {
+        lines: markup.render(content, max_lines),
+        is_binary: false,
+        preview_type: PreviewType::Rendered,
+    }
+}
+
+/// A JSON, YAML or TOML document as a tree, deep parts of large ones folded.
+/// One that doesn't parse is highlighted as text under the parse error.
+fn preview_structured(
+    path: &Path,
+    format: crate::structured::Format,
+    content: &str,
+    max_lines: usize,
+    options: &HighlightOptions,
+) -> PreviewContent {
+    match crate::structured::parse(format, content) {
+        Ok(root) => PreviewContent {
+            lines: crate::structured::StructuredDoc::new(format, root)
+                .rows()
+                .into_iter()
+                .take(max_lines)
+                .map(|row| Line::from(row.text))
+                .collect(),
+            is_binary: false,
+            preview_type: PreviewType::Structured,
+        },
+        Err(error) => {
+            let error_style = Style::default().fg(Color::Red);
+            let mut lines: Vec<Line<'static>> = error
+                .lines()
+                .enumerate()
+                .map(|(i, line)| {
+                    let text = if i == 0 { format!("⚠️  Invalid {}: {}", format.label(), line) } else { line.to_string() };
+                    Line::styled(text, error_style)
+                })
+                .collect();
+            lines.push(Line::from(""));
+            let mut preview = preview_code_with_highlighting(path, content, max_lines, options);
+            lines.append(&mut preview.lines);
+            preview.lines = lines;
+            preview
+        }
+    }
+}
+
+fn preview_error(message: String) -> PreviewContent {
+    PreviewContent {
+        lines: vec![Line::from(format!("⚠️  {}", message))],
+        is_binary: false,
+        preview_type: PreviewType::Error(message),
     }
 }
 
-fn preview_directory(path: &Path) -> PreviewContent {
-    let mut lines = vec![Line::from("📁 Directory Contents:"), Line::from("")];
+fn preview_plain_text(content: &str, max_lines: usize) -> PreviewContent {
+    let lines: Vec<Line<'static>> = content
+        .lines()
+        .take(max_lines)
+        .map(|s| Line::from(s.to_string()))
+        .collect();
+
+    PreviewContent {
+        lines,
+        is_binary: false,
+        preview_type: PreviewType::Text,
+    }
+}
+
+/// 16 bytes per line: offset, hex bytes and the printable ASCII
+fn preview_hex(path: &Path, max_lines: usize) -> PreviewContent {
+    let mut bytes = Vec::new();
+    let read = fs::File::open(path).and_then(|file| file.take(max_lines as u64 * 16).read_to_end(&mut bytes));
+    if let Err(e) = read {
+        return preview_error(e.to_string());
+    }
+
+    let lines = bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            Line::from(format!("{:08x}  {:<47}  |{}|", row * 16, hex.join(" "), ascii))
+        })
+        .collect();
+
+    PreviewContent {
+        lines,
+        is_binary: true,
+        preview_type: PreviewType::Binary,
+    }
+}
+
+fn preview_directory(path: &Path, icons: IconSet) -> PreviewContent {
+    let mut lines = vec![
+        Line::from(format!("{} Directory Contents:", file_icon(path, true, icons))),
+        Line::from(""),
+    ];
 
     if let Ok(entries) = fs::read_dir(path) {
         let mut count = 0;
@@ -105,8 +414,7 @@ fn preview_directory(path: &Path) -> PreviewContent {
             if let Ok(entry) = entry {
                 let name = entry.file_name().to_string_lossy().to_string();
                 let is_dir = entry.path().is_dir();
-                let emoji = if is_dir { "📁" } else { "📄" };
-                lines.push(Line::from(format!("{} {}", emoji, name)));
+                lines.push(Line::from(format!("{} {}", file_icon(&entry.path(), is_dir, icons), name)));
                 count += 1;
             }
         }
@@ -122,17 +430,31 @@ fn preview_directory(path: &Path) -> PreviewContent {
     }
 }
 
-fn preview_image(path: &Path) -> PreviewContent {
+fn preview_image(path: &Path, options: &HighlightOptions) -> PreviewContent {
     let mut lines = vec![Line::from("🖼️  Image File"), Line::from("")];
+    let now = std::time::SystemTime::now();
+    let format_time = |time| crate::path_display::format_time(time, &options.date_format, now);
 
     if let Ok(metadata) = fs::metadata(path) {
         lines.push(Line::from(format!("Size: {}", humansize::format_size(metadata.len(), humansize::BINARY))));
+        if let Ok(modified) = metadata.modified() {
+            lines.push(Line::from(format!("Modified: {}", format_time(modified))));
+        }
+    }
+    if let Some(taken) = crate::media_preview::capture_time(path)
+        .and_then(|t| chrono::Local.from_local_datetime(&t).single())
+    {
+        lines.push(Line::from(format!("Taken: {}", format_time(taken.into()))));
     }
 
     if let Some(ext) = path.extension() {
         lines.push(Line::from(format!("Format: {}", ext.to_string_lossy().to_uppercase())));
     }
 
+    if let Some(mime) = crate::files::detect_mime(path) {
+        lines.push(Line::from(format!("Type: {}", mime)));
+    }
+
     // Try to extract image dimensions
     if let Ok(img_data) = image::image_dimensions(path) {
         lines.push(Line::from(format!("Dimensions: {}x{} px", img_data.0, img_data.1)));
@@ -150,6 +472,44 @@ fn preview_image(path: &Path) -> PreviewContent {
     }
 }
 
+/// Page count, title and author, then the first page's text; size and format
+/// alone when the document can't be read or has no text to show
+fn preview_pdf(path: &Path, max_lines: usize) -> PreviewContent {
+    let mut lines = vec![Line::from("📄 PDF Document"), Line::from("")];
+    if let Ok(metadata) = fs::metadata(path) {
+        lines.push(Line::from(format!("Size: {}", humansize::format_size(metadata.len(), humansize::BINARY))));
+    }
+
+    match crate::pdf::pdf_info(path) {
+        Ok(info) => {
+            lines.push(Line::from(format!("Version: {}", info.version)));
+            lines.push(Line::from(format!("Pages: {}", info.pages)));
+            if let Some(title) = info.title {
+                lines.push(Line::from(format!("Title: {}", title)));
+            }
+            if let Some(author) = info.author {
+                lines.push(Line::from(format!("Author: {}", author)));
+            }
+            lines.push(Line::from(""));
+            match info.first_page_text {
+                Some(text) => {
+                    lines.push(Line::from("── Page 1 ──"));
+                    let room = max_lines.saturating_sub(lines.len());
+                    lines.extend(text.lines().take(room).map(|line| Line::from(line.to_string())));
+                }
+                None => lines.push(Line::from("(no text on the first page; it may be scanned images)")),
+            }
+        }
+        Err(e) => lines.push(Line::from(format!("⚠️  Could not read PDF: {}", e))),
+    }
+
+    PreviewContent {
+        lines,
+        is_binary: true,
+        preview_type: PreviewType::Document,
+    }
+}
+
 fn preview_archive(path: &Path) -> PreviewContent {
     let mut lines = vec![Line::from("🗜️  Archive File"), Line::from("")];
 
@@ -164,62 +524,25 @@ fn preview_archive(path: &Path) -> PreviewContent {
 
     lines.push(Line::from(""));
 
-    // Try to list ZIP contents
-    if path.extension().map(|e| e.to_string_lossy().to_lowercase() == "zip").unwrap_or(false) {
-        if let Ok(file) = fs::File::open(path) {
-            if let Ok(mut zip) = zip::ZipArchive::new(file) {
-                lines.push(Line::from(format!("📦 Contents ({} files):", zip.len())));
-                for i in 0..zip.len().min(20) {
-                    if let Ok(file) = zip.by_index(i) {
-                        let size_str = if file.is_dir() { "<DIR>".to_string() } else { 
-                            humansize::format_size(file.size(), humansize::BINARY) 
-                        };
-                        lines.push(Line::from(format!("  {} ({})", file.name(), size_str)));
-                    }
-                }
-                if zip.len() > 20 {
-                    lines.push(Line::from(format!("  ... and {} more files", zip.len() - 20)));
-                }
-            } else {
-                lines.push(Line::from("⚠️  Could not read ZIP archive"));
+    // The same table of contents the archive is browsed with
+    match crate::archive::ArchiveListing::open(path) {
+        Ok(listing) => {
+            let mut entries: Vec<_> = listing.entries().iter().collect();
+            entries.sort_by(|a, b| a.path.cmp(&b.path));
+            lines.push(Line::from(format!("📦 Contents ({} entries):", entries.len())));
+            for entry in entries.iter().take(20) {
+                let inner = entry.path.strip_prefix(path).unwrap_or(&entry.path);
+                lines.push(Line::from(format!("  {} ({})", inner.display(), entry.size_formatted())));
             }
-        }
-    }
-    // Try to list TAR contents
-    else if path.extension().map(|e| {
-        let s = e.to_string_lossy().to_lowercase();
-        s == "tar" || s == "gz" || s == "bz2" || s == "xz"
-    }).unwrap_or(false) {
-        if let Ok(file) = fs::File::open(path) {
-            let reader: Box<dyn Read> = if path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default() == "gz" {
-                Box::new(flate2::read::GzDecoder::new(file))
-            } else {
-                Box::new(file)
-            };
-
-            let mut archive = tar::Archive::new(reader);
-            lines.push(Line::from("📦 Contents (TAR archive):"));
-            if let Ok(entries) = archive.entries() {
-                let mut count = 0;
-                for entry_result in entries.take(20) {
-                    if let Ok(entry) = entry_result {
-                        if let Ok(size) = entry.header().size() {
-                            let size_str = humansize::format_size(size, humansize::BINARY);
-                            if let Ok(path) = entry.path() {
-                                lines.push(Line::from(format!("  {} ({})", path.display(), size_str)));
-                                count += 1;
-                            }
-                        }
-                    }
-                }
-                if count == 20 {
-                    lines.push(Line::from("  ... and more files"));
-                }
+            if entries.len() > 20 {
+                lines.push(Line::from(format!("  ... and {} more", entries.len() - 20)));
             }
         }
-    } else {
-        lines.push(Line::from("Archive format not directly supported for preview"));
-        lines.push(Line::from("Supported: .zip, .tar, .tar.gz"));
+        Err(_) if crate::archive::is_archive(path) => lines.push(Line::from("⚠️  Could not read archive")),
+        Err(_) => {
+            lines.push(Line::from("Archive format not directly supported for preview"));
+            lines.push(Line::from("Supported: .zip, .tar, .tar.gz"));
+        }
     }
 
     PreviewContent {
@@ -229,7 +552,7 @@ fn preview_archive(path: &Path) -> PreviewContent {
     }
 }
 
-fn is_code_file(path: &Path) -> bool {
+pub fn is_code_file(path: &Path) -> bool {
     if let Some(ext) = path.extension() {
         matches!(
             ext.to_str().unwrap_or("").to_lowercase().as_str(),
@@ -242,17 +565,24 @@ fn is_code_file(path: &Path) -> bool {
     }
 }
 
-fn preview_code_with_highlighting(path: &Path, content: &str, max_lines: usize) -> PreviewContent {
-    let ps = SyntaxSet::load_defaults_newlines();
-    let ts = ThemeSet::load_defaults();
+fn preview_code_with_highlighting(path: &Path, content: &str, max_lines: usize, options: &HighlightOptions) -> PreviewContent {
+    preview_code_with_themes(path, content, max_lines, options, &theme_set(options.theme_dir.as_deref()))
+}
+
+fn preview_code_with_themes(
+    path: &Path,
+    content: &str,
+    max_lines: usize,
+    options: &HighlightOptions,
+    ts: &ThemeSet,
+) -> PreviewContent {
+    let ps = syntax_set();
     
-    let syntax = ps.find_syntax_for_file(path)
-        .ok()
-        .flatten()
+    let syntax = find_syntax(path, &options.syntax_overrides)
         .or_else(|| Some(ps.find_syntax_plain_text()));
 
-    if let Some(syntax) = syntax {
-        let mut highlighter = HighlightLines::new(syntax, &ts.themes["base16-ocean.dark"]);
+    if let (Some(syntax), Some(theme)) = (syntax, resolve_theme(ts, &options.theme)) {
+        let mut highlighter = HighlightLines::new(syntax, theme);
         let mut highlighted_lines = Vec::new();
 
         for (idx, line) in LinesWithEndings::from(content).enumerate() {
@@ -260,7 +590,7 @@ fn preview_code_with_highlighting(path: &Path, content: &str, max_lines: usize)
                 break;
             }
 
-            if let Ok(ranges) = highlighter.highlight_line(line, &ps) {
+            if let Ok(ranges) = highlighter.highlight_line(line, ps) {
                 let mut spans = Vec::new();
                 
                 for (style, text) in ranges {
@@ -287,16 +617,262 @@ fn preview_code_with_highlighting(path: &Path, content: &str, max_lines: usize)
         }
     } else {
         // Fallback to plain text
-        let lines: Vec<Line<'static>> = content
-            .lines()
-            .take(max_lines)
-            .map(|s| Line::from(s.to_string()))
-            .collect();
+        preview_plain_text(content, max_lines)
+    }
+}
 
-        PreviewContent {
-            lines,
-            is_binary: false,
-            preview_type: PreviewType::Text,
+/// Lines the preview pane shows at most unless `preview_max_lines` says otherwise
+pub const PREVIEW_MAX_LINES: usize = 200;
+
+/// Lines quick look shows at most unless `quick_look_max_lines` says otherwise
+pub const QUICK_LOOK_MAX_LINES: usize = 5000;
+
+/// A full-screen preview of one file, with scrolling and search within it
+#[derive(Clone, Debug)]
+pub struct QuickLook {
+    pub path: PathBuf,
+    pub content: PreviewContent,
+    /// First line on screen
+    pub scroll: usize,
+    pub query: String,
+    /// Lines containing `query`, top to bottom
+    pub matches: Vec<usize>,
+    pub match_index: usize,
+    /// Whether keys are going into `query`
+    pub searching: bool,
+}
+
+impl QuickLook {
+    pub fn new(path: PathBuf, content: PreviewContent) -> Self {
+        Self { path, content, scroll: 0, query: String::new(), matches: Vec::new(), match_index: 0, searching: false }
+    }
+
+    /// Scroll by `delta` lines, keeping a screen of `height` lines filled where possible
+    pub fn scroll_by(&mut self, delta: isize, height: usize) {
+        let last = self.content.lines.len().saturating_sub(height.max(1));
+        self.scroll = self.scroll.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn scroll_to_end(&mut self, height: usize) {
+        self.scroll = self.content.lines.len().saturating_sub(height.max(1));
+    }
+
+    /// Find `query` (ignoring case) and jump to the first match at or below the current line
+    pub fn search(&mut self, query: &str) {
+        self.query = query.to_string();
+        self.matches = find_lines(&self.content.lines, query);
+        self.match_index = self.matches.iter().position(|&line| line >= self.scroll).unwrap_or(0);
+        if let Some(line) = self.current_match() {
+            self.scroll = line;
         }
     }
+
+    /// Go to the next match, or the previous one, wrapping around
+    pub fn next_match(&mut self, forward: bool) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let count = self.matches.len();
+        self.match_index = if forward { (self.match_index + 1) % count } else { (self.match_index + count - 1) % count };
+        self.scroll = self.matches[self.match_index];
+    }
+
+    pub fn current_match(&self) -> Option<usize> {
+        self.matches.get(self.match_index).copied()
+    }
+}
+
+/// Indexes of the lines whose text contains `query`, ignoring case
+pub fn find_lines(lines: &[Line], query: &str) -> Vec<usize> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| {
+            let text: String = line.spans.iter().map(|span| span.content.as_ref()).collect();
+            text.to_lowercase().contains(&query)
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_syntax_overrides() {
+        let overrides = crate::config::AppConfig::default().syntax_overrides;
+        let name = |path: &str, overrides: &HashMap<String, String>| {
+            find_syntax(&PathBuf::from(path), overrides).map(|s| s.name.clone())
+        };
+
+        assert_eq!(name("/src/Dockerfile", &overrides).as_deref(), Some("Bourne Again Shell (bash)"));
+        assert_eq!(name("/src/.env", &overrides).as_deref(), Some("Bourne Again Shell (bash)"));
+        assert_eq!(name("/src/Dockerfile", &HashMap::new()), None);
+        // Detection by syntect still applies where there is no override
+        assert_eq!(name("/src/main.rs", &overrides).as_deref(), Some("Rust"));
+
+        let overrides = HashMap::from([(".conf".to_string(), "ini".to_string()), ("cfg".to_string(), "Python".to_string())]);
+        assert_eq!(name("/etc/app.conf", &overrides), None, "unknown syntax names are ignored");
+        assert_eq!(name("/etc/setup.CFG", &overrides).as_deref(), Some("Python"));
+    }
+
+    #[test]
+    fn test_unknown_highlight_theme_falls_back() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("main.rs");
+        fs::write(&file, "fn main() {}\n")?;
+
+        let options = HighlightOptions {
+            theme: "no such theme".to_string(),
+            ..Default::default()
+        };
+        let preview = generate_preview_with(&file, 10, &options);
+        assert_eq!(preview.preview_type, PreviewType::Code);
+        assert_eq!(preview.lines.len(), 1);
+
+        let fallback = ThemeSet::load_defaults();
+        assert_eq!(resolve_theme(&fallback, "").and_then(|t| t.name.clone()), fallback.themes[DEFAULT_HIGHLIGHT_THEME].name);
+        Ok(())
+    }
+
+    #[test]
+    fn test_preview_override_forces_kind() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let json = dir.path().join("data.JSON");
+        fs::write(&json, "{\"a\": 1}\n")?;
+        let blob = dir.path().join("firmware.bin");
+        fs::write(&blob, b"AB\x00\x01")?;
+
+        let mut options = HighlightOptions::default();
+        assert_eq!(generate_preview_with(&json, 10, &options).preview_type, PreviewType::Structured);
+
+        options.preview_overrides = HashMap::from([
+            ("json".to_string(), preview_type_for_mode("text").unwrap()),
+            ("bin".to_string(), preview_type_for_mode("hex").unwrap()),
+        ]);
+        let preview = generate_preview_with(&json, 10, &options);
+        assert_eq!(preview.preview_type, PreviewType::Text);
+        assert_eq!(preview.lines[0].to_string(), "{\"a\": 1}");
+
+        let preview = generate_preview_with(&blob, 10, &options);
+        assert_eq!(preview.preview_type, PreviewType::Binary);
+        assert_eq!(preview.lines[0].to_string(), format!("00000000  {:<47}  |AB..|", "41 42 00 01"));
+        assert!(preview_type_for_mode("thumbnail").is_none());
+
+        // `metadata` shows what the file's own kind has to say
+        let clip = dir.path().join("clip.mp4");
+        fs::write(&clip, "not really a video")?;
+        options.preview_overrides = HashMap::from([("mp4".to_string(), preview_type_for_mode("metadata").unwrap())]);
+        let preview = generate_preview_with(&clip, 10, &options);
+        assert_eq!(preview.preview_type, PreviewType::Metadata);
+        assert!(preview.lines.iter().any(|line| line.to_string().contains("VIDEO METADATA")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_structured_file_falls_back_to_highlighting() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("package.json");
+        fs::write(&file, "{\n  \"name\": \"x\",\n}\n")?;
+
+        let preview = generate_preview_with(&file, 10, &HighlightOptions::default());
+        assert_eq!(preview.preview_type, PreviewType::Code);
+        let first = preview.lines[0].to_string();
+        assert!(first.starts_with("⚠️  Invalid JSON: ") && first.contains("line 3 column 1"), "{}", first);
+        assert_eq!(preview.lines[3].to_string().trim_end(), "  \"name\": \"x\",");
+
+        fs::write(&file, "{\"name\": \"x\"}")?;
+        let preview = generate_preview_with(&file, 10, &HighlightOptions::default());
+        assert_eq!(preview.preview_type, PreviewType::Structured);
+        assert_eq!(preview.lines[0].to_string(), "  name: \"x\"");
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_default_theme() {
+        let path = PathBuf::from("main.rs");
+        let options = HighlightOptions::default();
+
+        // Without the default theme, any remaining theme is used
+        let mut minimal = ThemeSet::new();
+        minimal.themes.insert("only".to_string(), ThemeSet::load_defaults().themes["InspiredGitHub"].clone());
+        let preview = preview_code_with_themes(&path, "fn main() {}\n", 10, &options, &minimal);
+        assert_eq!(preview.preview_type, PreviewType::Code);
+
+        // With no themes at all the file is shown as plain text
+        let preview = preview_code_with_themes(&path, "fn main() {}\nlet x = 1;\n", 10, &options, &ThemeSet::new());
+        assert_eq!(preview.preview_type, PreviewType::Text);
+        assert_eq!(preview.lines.len(), 2);
+    }
+
+    const TINY_THEME: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0"><dict>
+<key>name</key><string>Tiny</string>
+<key>settings</key><array><dict><key>settings</key><dict>
+<key>foreground</key><string>#FF0000</string>
+</dict></dict></array>
+</dict></plist>"#;
+
+    #[test]
+    fn test_custom_tmtheme_is_loaded() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("Tiny.tmTheme"), TINY_THEME)?;
+        fs::write(dir.path().join("broken.tmTheme"), "not a theme")?;
+
+        let set = load_theme_set(Some(dir.path()));
+        assert_eq!(resolve_theme(&set, "Tiny").and_then(|t| t.name.as_deref()), Some("Tiny"));
+        assert!(!set.themes.contains_key("broken"));
+        assert!(set.themes.contains_key(DEFAULT_HIGHLIGHT_THEME));
+        Ok(())
+    }
+
+    #[test]
+    fn test_themes_added_while_running_are_picked_up() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        assert!(!highlight_themes(Some(dir.path())).contains(&"Tiny".to_string()));
+
+        fs::write(dir.path().join("Tiny.tmTheme"), TINY_THEME)?;
+        assert!(highlight_themes(Some(dir.path())).contains(&"Tiny".to_string()));
+        // Other folders keep their own themes
+        assert!(!highlight_themes(None).contains(&"Tiny".to_string()));
+
+        let file = dir.path().join("main.rs");
+        fs::write(&file, "fn main() {}\n")?;
+        let options = HighlightOptions { theme: "Tiny".to_string(), theme_dir: Some(dir.path().to_path_buf()), ..Default::default() };
+        let preview = generate_preview_with(&file, 10, &options);
+        assert!(preview.lines[0].spans.iter().all(|span| span.style.fg == Some(Color::Rgb(0xFF, 0, 0))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_quick_look_search_and_scroll() {
+        let text: String = (0..100).map(|i| if i % 30 == 0 { format!("TODO {}\n", i) } else { format!("line {}\n", i) }).collect();
+        let mut look = QuickLook::new(PathBuf::from("notes.txt"), preview_plain_text(&text, QUICK_LOOK_MAX_LINES));
+
+        look.scroll_by(500, 20);
+        assert_eq!(look.scroll, 80, "the last screen stays full");
+        look.scroll_by(-500, 20);
+        assert_eq!(look.scroll, 0);
+
+        look.scroll = 10;
+        look.search("todo");
+        assert_eq!(look.matches, vec![0, 30, 60, 90]);
+        assert_eq!(look.current_match(), Some(30), "starts from the current line");
+        assert_eq!(look.scroll, 30);
+        look.next_match(true);
+        look.next_match(true);
+        look.next_match(true);
+        assert_eq!(look.scroll, 0, "wraps around");
+        look.next_match(false);
+        assert_eq!(look.current_match(), Some(90));
+
+        look.search("");
+        assert!(look.matches.is_empty());
+    }
 }
@@ -0,0 +1,102 @@
+// Priority - run background work at lowered CPU and disk priority
+//
+// Only the calling thread is lowered, so the UI thread stays responsive while
+// copies, scans, hashes and searches run on workers. An unprivileged thread
+// can't raise its priority again, so workers are lowered as they start.
+use crate::logging::LogErr;
+use std::io;
+
+/// Nice value for background threads: below normal work, above batch jobs
+#[cfg(target_os = "linux")]
+const BACKGROUND_NICE: libc::c_int = 10;
+
+// From linux/ioprio.h: best-effort class, lowest level within it
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+#[cfg(target_os = "linux")]
+const IOPRIO_BACKGROUND: libc::c_int = (2 << 13) | 7;
+
+/// Lower the calling thread's CPU and disk priority: nice 10 and the lowest
+/// best-effort IO priority on Linux, the background band on macOS and Windows.
+/// Does nothing on other platforms.
+#[cfg(target_os = "linux")]
+pub fn lower_current_thread() -> io::Result<()> {
+    // On Linux, 0 names the calling thread rather than the whole process.
+    // A thread already niced further is left where it is.
+    let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) }.max(BACKGROUND_NICE);
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, IOPRIO_BACKGROUND) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub fn lower_current_thread() -> io::Result<()> {
+    // The background band throttles both CPU and disk
+    if unsafe { libc::setpriority(libc::PRIO_DARWIN_THREAD, 0, libc::PRIO_DARWIN_BG) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn lower_current_thread() -> io::Result<()> {
+    const THREAD_MODE_BACKGROUND_BEGIN: i32 = 0x0001_0000;
+    extern "system" {
+        fn GetCurrentThread() -> isize;
+        fn SetThreadPriority(thread: isize, priority: i32) -> i32;
+    }
+    if unsafe { SetThreadPriority(GetCurrentThread(), THREAD_MODE_BACKGROUND_BEGIN) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn lower_current_thread() -> io::Result<()> {
+    Ok(())
+}
+
+/// Lower the calling thread when `low` is set, logging rather than failing
+/// since the work can still go ahead at normal priority
+pub fn lower_current_thread_if(low: bool) {
+    if low {
+        lower_current_thread().log_err("lower background thread priority");
+    }
+}
+
+/// A pool of `threads` threads (0 for one per CPU) running at background
+/// priority, for parallel work that shouldn't run on the shared global pool
+pub fn background_pool(threads: usize, name: &'static str) -> Result<rayon::ThreadPool, rayon::ThreadPoolBuildError> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(move |i| format!("{}-{}", name, i))
+        .start_handler(|_| lower_current_thread_if(true))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lowering_a_worker_thread() -> anyhow::Result<()> {
+        // Runs on its own thread so the test harness keeps its priority
+        std::thread::spawn(|| {
+            lower_current_thread()?;
+            #[cfg(target_os = "linux")]
+            assert!(unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) } >= BACKGROUND_NICE);
+            // Lowering twice stays at the same level
+            lower_current_thread()
+        })
+        .join()
+        .map_err(|_| anyhow::anyhow!("worker panicked"))??;
+
+        let pool = background_pool(2, "test")?;
+        assert_eq!(pool.install(|| (1..=4).sum::<i32>()), 10);
+        Ok(())
+    }
+}
@@ -1,7 +1,17 @@
+use anyhow::{anyhow, Result};
 use rayon::prelude::*;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
-use ignore::WalkBuilder;
+use ignore::{WalkBuilder, WalkState};
+use crate::files::IgnorePatterns;
+use crate::jobs::JobHandle;
+use crate::logging::LogErr;
+use crate::media_preview::{detect_media_type, MediaType};
+use crate::preview::is_code_file;
 
 #[allow(dead_code)]
 #[derive(Clone, Debug)]
@@ -12,102 +22,654 @@ pub struct SearchResult {
     pub relevance: usize,
 }
 
+/// Where a search looks for matches
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchScope {
+    /// Only entries directly inside the current directory
+    CurrentDir,
+    /// The current directory and everything below it
+    #[default]
+    Recursive,
+    /// Everything below the configured search root, skipping gitignored files
+    Drive,
+}
+
+impl SearchScope {
+    pub const ALL: [SearchScope; 3] = [SearchScope::CurrentDir, SearchScope::Recursive, SearchScope::Drive];
+
+    /// Name used in the config and the Python API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchScope::CurrentDir => "current",
+            SearchScope::Recursive => "recursive",
+            SearchScope::Drive => "drive",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|scope| scope.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Label shown in the search status bar
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchScope::CurrentDir => "This folder",
+            SearchScope::Recursive => "Recursive",
+            SearchScope::Drive => "Whole drive",
+        }
+    }
+
+    /// The scope after this one, wrapping around
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Something to do with one search result, from its actions menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResultAction {
+    Open,
+    Reveal,
+    CopyPath,
+    Delete,
+    Bookmark,
+}
+
+impl ResultAction {
+    pub const ALL: [ResultAction; 5] = [
+        ResultAction::Open,
+        ResultAction::Reveal,
+        ResultAction::CopyPath,
+        ResultAction::Delete,
+        ResultAction::Bookmark,
+    ];
+
+    #[allow(dead_code)]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().replace('_', "-").as_str() {
+            "open" => Some(ResultAction::Open),
+            "reveal" => Some(ResultAction::Reveal),
+            "copy-path" | "copy" => Some(ResultAction::CopyPath),
+            "delete" => Some(ResultAction::Delete),
+            "bookmark" => Some(ResultAction::Bookmark),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ResultAction::Open => "Open with default app",
+            ResultAction::Reveal => "Reveal in browser",
+            ResultAction::CopyPath => "Copy path",
+            ResultAction::Delete => "Delete",
+            ResultAction::Bookmark => "Bookmark folder",
+        }
+    }
+
+    /// Shortcut within the menu
+    pub fn key(self) -> char {
+        match self {
+            ResultAction::Open => 'o',
+            ResultAction::Reveal => 'r',
+            ResultAction::CopyPath => 'y',
+            ResultAction::Delete => 'd',
+            ResultAction::Bookmark => 'b',
+        }
+    }
+
+    /// The path the action works on: the folder to show the result in for Reveal,
+    /// the folder to bookmark (a folder result is its own) for Bookmark, and the
+    /// result itself otherwise
+    pub fn target(self, result: &SearchResult) -> PathBuf {
+        let parent = || result.path.parent().map(Path::to_path_buf).unwrap_or_else(|| result.path.clone());
+        match self {
+            ResultAction::Reveal => parent(),
+            ResultAction::Bookmark if !result.is_dir => parent(),
+            _ => result.path.clone(),
+        }
+    }
+}
+
+/// How the results view groups matches
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchGrouping {
+    /// One list in relevance order
+    #[default]
+    None,
+    /// Directories first, then files
+    Kind,
+    /// One group per parent directory
+    Parent,
+}
+
+impl SearchGrouping {
+    pub const ALL: [SearchGrouping; 3] = [SearchGrouping::None, SearchGrouping::Kind, SearchGrouping::Parent];
+
+    /// Name used in the config
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchGrouping::None => "none",
+            SearchGrouping::Kind => "kind",
+            SearchGrouping::Parent => "parent",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|grouping| grouping.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchGrouping::None => "Ungrouped",
+            SearchGrouping::Kind => "Folders first",
+            SearchGrouping::Parent => "By folder",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|g| *g == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Broad kind of file, for narrowing search results
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileCategory {
+    Images,
+    Audio,
+    Video,
+    Code,
+    Documents,
+    Archives,
+}
+
+impl FileCategory {
+    pub const ALL: [FileCategory; 6] = [
+        FileCategory::Images,
+        FileCategory::Audio,
+        FileCategory::Video,
+        FileCategory::Code,
+        FileCategory::Documents,
+        FileCategory::Archives,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileCategory::Images => "Images",
+            FileCategory::Audio => "Audio",
+            FileCategory::Video => "Video",
+            FileCategory::Code => "Code",
+            FileCategory::Documents => "Documents",
+            FileCategory::Archives => "Archives",
+        }
+    }
+
+    /// The category a file falls in, if any; directories have none.
+    /// Media wins over code (".ts" is video) and documents over code
+    /// (".md" and ".txt" read as documents).
+    pub fn of(path: &Path, is_dir: bool) -> Option<Self> {
+        if is_dir {
+            return None;
+        }
+        match detect_media_type(path) {
+            MediaType::Image => return Some(FileCategory::Images),
+            MediaType::Audio => return Some(FileCategory::Audio),
+            MediaType::Video => return Some(FileCategory::Video),
+            MediaType::Unknown => {}
+        }
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "zip" | "tar" | "gz" | "tgz" | "rar" | "7z" | "bz2" | "xz" | "zst" => Some(FileCategory::Archives),
+            "pdf" | "doc" | "docx" | "odt" | "rtf" | "xls" | "xlsx" | "ods" | "csv" | "ppt" | "pptx" | "odp"
+            | "epub" | "md" | "txt" => Some(FileCategory::Documents),
+            _ if is_code_file(path) => Some(FileCategory::Code),
+            _ => None,
+        }
+    }
+}
+
+/// A run of results sharing a header in the results view
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResultGroup {
+    /// Stable identity used to remember collapsed groups
+    pub key: String,
+    pub title: String,
+    /// Indices into `SearchEngine::results`, best match first
+    pub indices: Vec<usize>,
+}
+
+/// Take a `depth:N` token out of a query, for one search that goes N levels deep
+/// (1 = direct children only) whatever the scope. Returns the rest of the query
+/// and N if given; a half-typed `depth:` is dropped, while N < 1 or not a
+/// number is an error.
+pub fn take_depth_token(query: &str) -> Result<(String, Option<usize>), String> {
+    let mut depth = None;
+    let mut words = Vec::new();
+    for word in query.split(' ') {
+        match word.strip_prefix("depth:") {
+            Some("") => {}
+            Some(value) => match value.parse::<usize>() {
+                Ok(n) if n >= 1 => depth = Some(n),
+                _ => return Err(format!("depth must be a whole number of at least 1, not '{}'", value)),
+            },
+            None => words.push(word),
+        }
+    }
+    Ok((words.join(" ").trim().to_string(), depth))
+}
+
+/// Split relevance-sorted results into groups, keeping relevance order inside
+/// each group. Groups are ordered by their best match; parent folders are
+/// shown relative to `base` when they lie below it.
+pub fn group_results(results: &[SearchResult], grouping: SearchGrouping, base: &Path) -> Vec<ResultGroup> {
+    let mut groups: Vec<ResultGroup> = Vec::new();
+    let mut add = |key: String, title: String, index: usize| {
+        match groups.iter_mut().find(|g| g.key == key) {
+            Some(group) => group.indices.push(index),
+            None => groups.push(ResultGroup { key, title, indices: vec![index] }),
+        }
+    };
+
+    for (index, result) in results.iter().enumerate() {
+        match grouping {
+            SearchGrouping::None => add(String::new(), String::new(), index),
+            SearchGrouping::Kind => {
+                let (key, title) = if result.is_dir { ("dirs", "Directories") } else { ("files", "Files") };
+                add(key.to_string(), title.to_string(), index);
+            }
+            SearchGrouping::Parent => {
+                let parent = result.path.parent().unwrap_or(Path::new(""));
+                let title = match parent.strip_prefix(base) {
+                    Ok(rel) if rel.as_os_str().is_empty() => "./".to_string(),
+                    Ok(rel) => format!("{}/", rel.display()),
+                    Err(_) => parent.display().to_string(),
+                };
+                add(parent.to_string_lossy().to_string(), title, index);
+            }
+        }
+    }
+
+    if grouping == SearchGrouping::Kind {
+        groups.sort_by_key(|g| g.key != "dirs");
+    }
+    groups
+}
+
+/// How a whole-drive search ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DriveSearchEnd {
+    /// Walked everything below the root
+    Complete,
+    /// Ran out of its time budget
+    TimedOut,
+    /// Stopped by the user
+    Cancelled,
+}
+
+/// A whole-drive search running on worker threads, streaming matches back
+/// as they are found
+pub struct DriveSearch {
+    pub started: Instant,
+    /// Entries looked at so far
+    scanned: Arc<AtomicU64>,
+    cancel: Arc<AtomicBool>,
+    timed_out: Arc<AtomicBool>,
+    matches: Receiver<SearchResult>,
+    max_results: usize,
+}
+
+impl DriveSearch {
+    pub fn scanned(&self) -> u64 {
+        self.scanned.load(Ordering::Relaxed)
+    }
+}
+
 pub struct SearchEngine {
+    /// Matches passing `category_filters`, best first
     pub results: Vec<SearchResult>,
-    pub is_searching: bool,
+    /// Every match, before category filters
+    unfiltered: Vec<SearchResult>,
+    /// Show only results in one of these categories; empty shows everything
+    pub category_filters: Vec<FileCategory>,
+    /// Entries skipped by every search, along with everything below them
+    pub ignore: IgnorePatterns,
+    /// Descend into symlinked directories; cycles are detected and skipped
+    pub follow_symlinks: bool,
+    /// The whole-drive search still running, if any
+    pub drive_search: Option<DriveSearch>,
+    /// Matches found by the last whole-drive search, including ones trimmed from `results`
+    pub matched: usize,
+    /// How the last whole-drive search ended
+    pub drive_search_end: Option<DriveSearchEnd>,
+    /// Threads searches walk with; 0 leaves it to rayon and ignore
+    threads: usize,
+    /// Pool sized to `threads`; the global pool is used without one
+    pool: Option<Arc<rayon::ThreadPool>>,
+    /// Search threads run at background priority
+    low_priority: bool,
 }
 
 impl SearchEngine {
     pub fn new() -> Self {
         Self {
             results: Vec::new(),
-            is_searching: false,
+            unfiltered: Vec::new(),
+            category_filters: Vec::new(),
+            ignore: IgnorePatterns::default(),
+            follow_symlinks: false,
+            drive_search: None,
+            matched: 0,
+            drive_search_end: None,
+            threads: 0,
+            pool: None,
+            low_priority: false,
         }
     }
 
+    /// A whole-drive search is still running; directory searches finish
+    /// before returning
+    pub fn is_searching(&self) -> bool {
+        self.drive_search.is_some()
+    }
+
+    /// Search with at most `threads` threads (0 for one per CPU). If a pool
+    /// that size can't be made, searches fall back to the global pool.
+    pub fn set_threads(&mut self, threads: usize) {
+        if threads == self.threads {
+            return;
+        }
+        self.threads = threads;
+        self.build_pool();
+    }
+
+    /// Search at lowered CPU and disk priority, so a big search doesn't slow
+    /// down everything else on a busy machine
+    pub fn set_low_priority(&mut self, low: bool) {
+        if low == self.low_priority {
+            return;
+        }
+        self.low_priority = low;
+        self.build_pool();
+    }
+
+    fn build_pool(&mut self) {
+        let threads = self.threads;
+        let pool = if self.low_priority {
+            // Its own pool even at one per CPU, since the global one runs at normal priority
+            Some(crate::priority::background_pool(threads, "search"))
+        } else {
+            (threads > 0).then(|| {
+                rayon::ThreadPoolBuilder::new().num_threads(threads).thread_name(|i| format!("search-{}", i)).build()
+            })
+        };
+        self.pool = pool.and_then(|pool| pool.log_err("start the search thread pool")).map(Arc::new);
+    }
+
+    #[allow(dead_code)]
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
     pub fn search_current_dir(&mut self, dir: &Path, query: &str, max_results: usize) {
+        self.search_dir(dir, query, max_results, 10);
+    }
+
+    /// Search below `root`, wherever the user is browsing, at most `max_depth`
+    /// levels deep; fails without touching the results when `root` isn't a folder
+    pub fn search_in(&mut self, root: &Path, query: &str, max_results: usize, max_depth: usize) -> Result<()> {
+        if !root.is_dir() {
+            return Err(anyhow!("{} is not a directory", root.display()));
+        }
+        self.search_dir(root, query, max_results, max_depth);
+        Ok(())
+    }
+
+    /// Search below `dir`, at most `max_depth` levels deep (1 = direct children only)
+    pub fn search_dir(&mut self, dir: &Path, query: &str, max_results: usize, max_depth: usize) {
+        self.clear();
         if query.is_empty() {
             self.results.clear();
             return;
         }
 
         let query_lower = query.to_lowercase();
-        self.is_searching = true;
 
-        let mut results: Vec<SearchResult> = WalkDir::new(dir)
-            .max_depth(10)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .par_bridge()
-            .filter_map(|entry| {
-                let path = entry.path();
-                let name = path.file_name()?.to_string_lossy().to_string();
+        let ignore = &self.ignore;
+        let walk = || -> Vec<SearchResult> {
+            WalkDir::new(dir)
+                .min_depth(1)
+                .max_depth(max_depth)
+                .follow_links(self.follow_symlinks)
+                .into_iter()
+                .filter_entry(|entry| !ignore.is_ignored(entry.path()))
+                .filter_map(|e| e.ok())
+                .par_bridge()
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    let name = path.file_name()?.to_string_lossy().to_string();
                 
-                // Fuzzy matching with relevance scoring
-                let relevance = Self::calculate_relevance(&name, &query_lower);
+                    // Fuzzy matching with relevance scoring
+                    let relevance = Self::calculate_relevance(&name, &query_lower);
                 
-                if relevance > 0 {
-                    Some(SearchResult {
-                        path: path.to_path_buf(),
-                        name,
-                        is_dir: path.is_dir(),
-                        relevance,
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
+                    if relevance > 0 {
+                        Some(SearchResult {
+                            path: path.to_path_buf(),
+                            name,
+                            is_dir: path.is_dir(),
+                            relevance,
+                        })
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        };
+        let mut results = match &self.pool {
+            Some(pool) => pool.install(walk),
+            None => walk(),
+        };
 
         // Sort by relevance (higher first)
         results.sort_by(|a, b| b.relevance.cmp(&a.relevance));
         results.truncate(max_results);
 
-        self.results = results;
-        self.is_searching = false;
+        self.unfiltered = results;
+        self.apply_category_filters();
     }
 
     #[allow(dead_code)]
     pub fn search_entire_drive(&mut self, root: &Path, query: &str, max_results: usize) {
+        self.search_drive(root, query, max_results, 15);
+    }
+
+    /// Search everything below `root`, skipping gitignored files, and wait for it
+    pub fn search_drive(&mut self, root: &Path, query: &str, max_results: usize, max_depth: usize) {
+        self.start_drive_search(root, query, max_results, max_depth, None, None);
+        self.wait_for_drive_search();
+    }
+
+    /// Start searching everything below `root` in the background, skipping
+    /// gitignored files. Matches arrive through `poll_drive_search`; the walk
+    /// stops once `time_limit` has passed, leaving partial results.
+    pub fn start_drive_search(
+        &mut self,
+        root: &Path,
+        query: &str,
+        max_results: usize,
+        max_depth: usize,
+        time_limit: Option<Duration>,
+        job: Option<JobHandle>,
+    ) {
+        self.clear();
         if query.is_empty() {
-            self.results.clear();
             return;
         }
 
         let query_lower = query.to_lowercase();
-        self.is_searching = true;
+
+        let started = Instant::now();
+        let scanned = Arc::new(AtomicU64::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = channel();
 
         // Use ignore crate for faster traversal (respects .gitignore)
-        let mut results: Vec<SearchResult> = WalkBuilder::new(root)
-            .max_depth(Some(15))
+        let ignore = self.ignore.clone();
+        let walker = WalkBuilder::new(root)
+            .max_depth(Some(max_depth))
+            .follow_links(self.follow_symlinks)
             .hidden(false)
-            .build()
-            .par_bridge()
-            .filter_map(|e| e.ok())
-            .filter_map(|entry| {
-                let path = entry.path();
-                let name = path.file_name()?.to_string_lossy().to_string();
-                
-                let relevance = Self::calculate_relevance(&name, &query_lower);
-                
-                if relevance > 0 {
-                    Some(SearchResult {
-                        path: path.to_path_buf(),
-                        name,
-                        is_dir: path.is_dir(),
-                        relevance,
+            .threads(self.threads)
+            .filter_entry(move |entry| !ignore.is_ignored(entry.path()))
+            .build_parallel();
+        {
+            let scanned = Arc::clone(&scanned);
+            let cancel = Arc::clone(&cancel);
+            let timed_out = Arc::clone(&timed_out);
+            let low_priority = self.low_priority;
+            std::thread::spawn(move || {
+                // On Linux the walker's own threads inherit this priority
+                crate::priority::lower_current_thread_if(low_priority);
+                walker.run(|| {
+                    let tx = tx.clone();
+                    let scanned = Arc::clone(&scanned);
+                    let cancel = Arc::clone(&cancel);
+                    let timed_out = Arc::clone(&timed_out);
+                    let job = job.as_ref();
+                    let query = query_lower.clone();
+                    Box::new(move |entry| {
+                        if cancel.load(Ordering::Acquire) || job.is_some_and(|job| job.is_cancelled()) {
+                            return WalkState::Quit;
+                        }
+                        if time_limit.is_some_and(|limit| started.elapsed() >= limit) {
+                            timed_out.store(true, Ordering::Release);
+                            return WalkState::Quit;
+                        }
+                        let entry = match entry {
+                            Ok(entry) if entry.depth() > 0 => entry,
+                            _ => return WalkState::Continue,
+                        };
+                        scanned.fetch_add(1, Ordering::Relaxed);
+                        let path = entry.path();
+                        let name = match path.file_name() {
+                            Some(name) => name.to_string_lossy().to_string(),
+                            None => return WalkState::Continue,
+                        };
+                        let relevance = Self::calculate_relevance(&name, &query);
+                        if relevance > 0 {
+                            let result = SearchResult {
+                                path: path.to_path_buf(),
+                                name,
+                                is_dir: entry.file_type().is_some_and(|t| t.is_dir()),
+                                relevance,
+                            };
+                            // The search was dropped
+                            if tx.send(result).is_err() {
+                                return WalkState::Quit;
+                            }
+                        }
+                        WalkState::Continue
                     })
-                } else {
-                    None
+                });
+                // Finish the job before the UI sees the channel close
+                drop(job);
+                drop(tx);
+            });
+        }
+
+        self.drive_search = Some(DriveSearch {
+            started,
+            scanned,
+            cancel,
+            timed_out,
+            matches: rx,
+            max_results,
+        });
+    }
+
+    /// Merge matches found since the last call, keeping the best
+    /// `max_results` sorted. Returns true if anything changed.
+    pub fn poll_drive_search(&mut self) -> bool {
+        let search = match &self.drive_search {
+            Some(search) => search,
+            None => return false,
+        };
+        let mut finished = false;
+        let mut found = Vec::new();
+        loop {
+            match search.matches.try_recv() {
+                Ok(result) => found.push(result),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
                 }
-            })
-            .collect();
+            }
+        }
+        let changed = finished || !found.is_empty();
+        self.merge_matches(found);
+        if finished {
+            self.finish_drive_search();
+        }
+        changed
+    }
 
-        results.sort_by(|a, b| b.relevance.cmp(&a.relevance));
-        results.truncate(max_results);
+    /// Block until the running whole-drive search is done
+    pub fn wait_for_drive_search(&mut self) {
+        let found: Vec<SearchResult> = match &self.drive_search {
+            Some(search) => search.matches.iter().collect(),
+            None => return,
+        };
+        self.merge_matches(found);
+        self.finish_drive_search();
+    }
 
-        self.results = results;
-        self.is_searching = false;
+    /// Stop the running whole-drive search, keeping what it found so far
+    pub fn cancel_drive_search(&mut self) {
+        if let Some(search) = &self.drive_search {
+            search.cancel.store(true, Ordering::Release);
+            let found: Vec<SearchResult> = search.matches.try_iter().collect();
+            self.merge_matches(found);
+            self.drive_search = None;
+            self.drive_search_end = Some(DriveSearchEnd::Cancelled);
+            }
+    }
+
+    fn merge_matches(&mut self, found: Vec<SearchResult>) {
+        let max_results = match &self.drive_search {
+            Some(search) => search.max_results,
+            None => return,
+        };
+        if found.is_empty() {
+            return;
+        }
+        self.matched += found.len();
+        self.unfiltered.extend(found);
+        // Stable, so earlier matches of equal relevance keep their place on screen
+        self.unfiltered.sort_by_key(|r| std::cmp::Reverse(r.relevance));
+        self.unfiltered.truncate(max_results);
+        self.apply_category_filters();
+    }
+
+    fn finish_drive_search(&mut self) {
+        if let Some(search) = self.drive_search.take() {
+            self.drive_search_end = Some(if search.timed_out.load(Ordering::Acquire) {
+                DriveSearchEnd::TimedOut
+            } else if search.cancel.load(Ordering::Acquire) {
+                DriveSearchEnd::Cancelled
+            } else {
+                DriveSearchEnd::Complete
+            });
+        }
     }
 
     fn calculate_relevance(name: &str, query: &str) -> usize {
@@ -164,7 +726,295 @@ impl SearchEngine {
         });
     }
 
+    /// Add or remove a category filter; returns whether it is now active
+    pub fn toggle_category_filter(&mut self, category: FileCategory) -> bool {
+        let active = match self.category_filters.iter().position(|c| *c == category) {
+            Some(index) => {
+                self.category_filters.remove(index);
+                false
+            }
+            None => {
+                self.category_filters.push(category);
+                true
+            }
+        };
+        self.apply_category_filters();
+        active
+    }
+
+    pub fn clear_category_filters(&mut self) {
+        self.category_filters.clear();
+        self.apply_category_filters();
+    }
+
+    fn apply_category_filters(&mut self) {
+        let filters = &self.category_filters;
+        self.results = self
+            .unfiltered
+            .iter()
+            .filter(|r| {
+                filters.is_empty() || FileCategory::of(&r.path, r.is_dir).is_some_and(|c| filters.contains(&c))
+            })
+            .cloned()
+            .collect();
+    }
+
+    /// Drop result `index`, e.g. once it's deleted, so filters don't bring it back
+    pub fn remove_result(&mut self, index: usize) {
+        if index < self.results.len() {
+            let removed = self.results.remove(index);
+            self.unfiltered.retain(|r| r.path != removed.path);
+        }
+    }
+
     pub fn clear(&mut self) {
+        self.cancel_drive_search();
+        self.unfiltered.clear();
         self.results.clear();
+        self.matched = 0;
+        self.drive_search_end = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_search_depth_and_scopes() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let nested = dir.path().join("a").join("b");
+        fs::create_dir_all(&nested)?;
+        fs::write(dir.path().join("report.txt"), "")?;
+        fs::write(nested.join("report-old.txt"), "")?;
+
+        let mut engine = SearchEngine::new();
+        engine.search_dir(dir.path(), "report", 10, 1);
+        assert_eq!(engine.results.len(), 1);
+
+        engine.search_dir(dir.path(), "report", 10, 10);
+        assert_eq!(engine.results.len(), 2);
+
+        engine.search_drive(dir.path(), "report", 10, 2);
+        assert_eq!(engine.results.len(), 1);
+
+        assert_eq!(SearchScope::parse("Drive"), Some(SearchScope::Drive));
+        assert_eq!(SearchScope::Drive.next(), SearchScope::CurrentDir);
+        Ok(())
+    }
+
+    #[test]
+    fn test_depth_token_bounds_the_walk() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let mut level = dir.path().to_path_buf();
+        for depth in 1..=4 {
+            fs::write(level.join(format!("note-{}.md", depth)), "")?;
+            level = level.join(format!("level{}", depth));
+            fs::create_dir(&level)?;
+        }
+
+        let mut engine = SearchEngine::new();
+        for depth in 1..=4 {
+            let (query, limit) = take_depth_token(&format!("note depth:{}", depth)).map_err(anyhow::Error::msg)?;
+            assert_eq!((query.as_str(), limit), ("note", Some(depth)));
+            engine.search_dir(dir.path(), &query, 50, depth);
+            assert_eq!(engine.results.len(), depth, "depth {}", depth);
+        }
+
+        assert_eq!(take_depth_token("depth:2 big files"), Ok(("big files".to_string(), Some(2))));
+        assert_eq!(take_depth_token("notes depth:"), Ok(("notes".to_string(), None)));
+        assert!(take_depth_token("notes depth:0").is_err());
+        assert!(take_depth_token("notes depth:deep").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_drive_search_streams_and_stops() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        for i in 0..20 {
+            let sub = dir.path().join(format!("d{}", i));
+            fs::create_dir(&sub)?;
+            fs::write(sub.join(format!("match-{}.txt", i)), "")?;
+            fs::write(sub.join("other.txt"), "")?;
+        }
+        fs::write(dir.path().join("match"), "")?;
+
+        let mut engine = SearchEngine::new();
+        let mut registry = crate::jobs::JobRegistry::new();
+        let job = registry.start("Search");
+        engine.start_drive_search(dir.path(), "match", 5, 10, None, Some(job));
+        assert!(engine.is_searching());
+        while engine.drive_search.is_some() {
+            engine.poll_drive_search();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(registry.is_idle());
+        assert_eq!(engine.drive_search_end, Some(DriveSearchEnd::Complete));
+        assert_eq!(engine.matched, 21);
+        // Trimmed to the best few as they came in
+        assert_eq!(engine.results.len(), 5);
+        assert_eq!(engine.results[0].name, "match");
+
+        // Out of time before the first entry: partial (here, empty) results
+        engine.start_drive_search(dir.path(), "match", 5, 10, Some(Duration::ZERO), None);
+        engine.wait_for_drive_search();
+        assert_eq!(engine.drive_search_end, Some(DriveSearchEnd::TimedOut));
+        assert!(engine.results.is_empty());
+
+        engine.start_drive_search(dir.path(), "match", 5, 10, None, None);
+        engine.cancel_drive_search();
+        assert!(engine.drive_search.is_none());
+        assert!(!engine.is_searching());
+        assert_eq!(engine.drive_search_end, Some(DriveSearchEnd::Cancelled));
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_thread_pool_finds_the_same_results() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        for i in 0..30 {
+            let sub = dir.path().join(format!("d{}", i % 5));
+            fs::create_dir_all(&sub)?;
+            fs::write(sub.join(format!("log-{}.txt", i)), "")?;
+        }
+        let found = |engine: &mut SearchEngine| {
+            engine.search_dir(dir.path(), "log", 100, 10);
+            let mut names: Vec<String> = engine.results.iter().map(|r| r.name.clone()).collect();
+            names.sort();
+            names
+        };
+
+        let mut engine = SearchEngine::new();
+        let everywhere = found(&mut engine);
+        assert_eq!(everywhere.len(), 30);
+        engine.set_threads(1);
+        assert_eq!(engine.threads(), 1);
+        assert_eq!(found(&mut engine), everywhere);
+
+        engine.start_drive_search(dir.path(), "log", 100, 10, None, None);
+        engine.wait_for_drive_search();
+        assert_eq!(engine.results.len(), 30);
+
+        engine.set_threads(0);
+        assert_eq!(found(&mut engine), everywhere);
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_in_stays_below_its_root() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let projects = dir.path().join("projects");
+        fs::create_dir_all(projects.join("astrofs"))?;
+        fs::create_dir_all(dir.path().join("downloads"))?;
+        fs::write(projects.join("astrofs").join("notes.md"), "")?;
+        fs::write(dir.path().join("downloads").join("notes.pdf"), "")?;
+
+        let mut engine = SearchEngine::new();
+        engine.search_in(&projects, "notes", 100, 10)?;
+        let found: Vec<&Path> = engine.results.iter().map(|r| r.path.as_path()).collect();
+        assert_eq!(found, [projects.join("astrofs").join("notes.md")]);
+
+        assert!(engine.search_in(&dir.path().join("missing"), "notes", 100, 10).is_err());
+        assert_eq!(engine.results.len(), 1, "a failed search leaves the results alone");
+        Ok(())
+    }
+
+    #[test]
+    fn test_category_filters_partition_results() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let names = [
+            "x-photo.JPG", "x-song.mp3", "x-clip.mkv", "x-main.rs", "x-notes.md", "x-report.pdf", "x-backup.tar",
+            "x-blob.bin",
+        ];
+        for name in names {
+            fs::write(dir.path().join(name), "")?;
+        }
+        fs::create_dir(dir.path().join("x-images"))?;
+
+        let mut engine = SearchEngine::new();
+        engine.search_dir(dir.path(), "x-", 50, 1);
+        assert_eq!(engine.results.len(), 9);
+
+        let sorted_names = |engine: &SearchEngine| {
+            let mut names: Vec<String> = engine.results.iter().map(|r| r.name.clone()).collect();
+            names.sort();
+            names
+        };
+        let mut categorized = 0;
+        for (category, expected) in [
+            (FileCategory::Images, vec!["x-photo.JPG"]),
+            (FileCategory::Audio, vec!["x-song.mp3"]),
+            (FileCategory::Video, vec!["x-clip.mkv"]),
+            (FileCategory::Code, vec!["x-main.rs"]),
+            (FileCategory::Documents, vec!["x-notes.md", "x-report.pdf"]),
+            (FileCategory::Archives, vec!["x-backup.tar"]),
+        ] {
+            assert!(engine.toggle_category_filter(category));
+            assert_eq!(sorted_names(&engine), expected, "{:?}", category);
+            categorized += engine.results.len();
+            engine.clear_category_filters();
+        }
+        // No file lands in two categories; the folder and the .bin file in none
+        assert_eq!(categorized, 7);
+        assert_eq!(engine.results.len(), 9);
+
+        // Filters combine and stay on for the next search
+        engine.toggle_category_filter(FileCategory::Audio);
+        assert!(engine.toggle_category_filter(FileCategory::Documents));
+        engine.search_dir(dir.path(), "x-", 50, 1);
+        assert_eq!(sorted_names(&engine), vec!["x-notes.md", "x-report.pdf", "x-song.mp3"]);
+        assert!(!engine.toggle_category_filter(FileCategory::Audio));
+        assert_eq!(engine.results.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_results_keeps_relevance_order() {
+        let result = |path: &str, is_dir: bool, relevance: usize| SearchResult {
+            path: PathBuf::from(path),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            is_dir,
+            relevance,
+        };
+        let results = vec![
+            result("/p/src/app.rs", false, 500),
+            result("/p/app", true, 250),
+            result("/p/docs/app.md", false, 250),
+            result("/p/src/apps", true, 100),
+        ];
+
+        let by_kind = group_results(&results, SearchGrouping::Kind, Path::new("/p"));
+        assert_eq!(by_kind[0].title, "Directories");
+        assert_eq!(by_kind[0].indices, vec![1, 3]);
+        assert_eq!(by_kind[1].indices, vec![0, 2]);
+
+        let by_parent = group_results(&results, SearchGrouping::Parent, Path::new("/p"));
+        let titles: Vec<_> = by_parent.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, vec!["src/", "./", "docs/"]);
+        assert_eq!(by_parent[0].indices, vec![0, 3]);
+
+        assert_eq!(group_results(&results, SearchGrouping::None, Path::new("/p"))[0].indices.len(), 4);
+    }
+
+    #[test]
+    fn test_result_actions_target_the_result() {
+        let file = SearchResult { path: PathBuf::from("/work/src/app.rs"), name: "app.rs".to_string(), is_dir: false, relevance: 0 };
+        let folder = SearchResult { path: PathBuf::from("/work/docs"), name: "docs".to_string(), is_dir: true, relevance: 0 };
+
+        for action in [ResultAction::Open, ResultAction::CopyPath, ResultAction::Delete] {
+            assert_eq!(action.target(&file), file.path, "{:?}", action);
+            assert_eq!(action.target(&folder), folder.path, "{:?}", action);
+        }
+        assert_eq!(ResultAction::Reveal.target(&file), PathBuf::from("/work/src"));
+        assert_eq!(ResultAction::Reveal.target(&folder), PathBuf::from("/work"));
+        assert_eq!(ResultAction::Bookmark.target(&file), PathBuf::from("/work/src"));
+        assert_eq!(ResultAction::Bookmark.target(&folder), folder.path);
+
+        assert_eq!(ResultAction::parse("copy_path"), Some(ResultAction::CopyPath));
+        assert_eq!(ResultAction::parse("rename"), None);
+        let keys: std::collections::HashSet<char> = ResultAction::ALL.iter().map(|a| a.key()).collect();
+        assert_eq!(keys.len(), ResultAction::ALL.len());
     }
 }
@@ -1,8 +1,8 @@
 use anyhow::Result;
-use dirs::data_dir;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::fs;
+use std::path::Path;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SearchHistory {
@@ -75,24 +75,34 @@ impl SearchHistory {
         self.queries.is_empty()
     }
 
-    /// Save to file
+    /// Save to the user's data folder
+    #[deprecated(note = "use save_to with AppDirs::search_history_file")]
+    #[allow(dead_code)]
     pub fn save(&self) -> Result<()> {
-        let data_dir = data_dir().ok_or_else(|| anyhow::anyhow!("Cannot find data directory"))?;
-        let astrofs_dir = data_dir.join("astrofs");
-        fs::create_dir_all(&astrofs_dir)?;
+        self.save_to(&crate::app::AppDirs::user()?.search_history_file())
+    }
+
+    /// Load from the user's data folder
+    #[deprecated(note = "use load_from with AppDirs::search_history_file")]
+    #[allow(dead_code)]
+    pub fn load() -> Result<Self> {
+        Self::load_from(&crate::app::AppDirs::user()?.search_history_file())
+    }
+
+    /// Save to `file_path`, creating its folder if missing
+    pub fn save_to(&self, file_path: &Path) -> Result<()> {
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
 
-        let file_path = astrofs_dir.join("search_history.json");
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(file_path, content)?;
+        crate::files::write_atomic(file_path, content.as_bytes())?;
 
         Ok(())
     }
 
-    /// Load from file
-    pub fn load() -> Result<Self> {
-        let data_dir = data_dir().ok_or_else(|| anyhow::anyhow!("Cannot find data directory"))?;
-        let file_path = data_dir.join("astrofs/search_history.json");
-
+    /// Load from `file_path`, starting empty if it doesn't exist yet
+    pub fn load_from(file_path: &Path) -> Result<Self> {
         if !file_path.exists() {
             return Ok(Self::new(50));
         }
@@ -136,4 +146,17 @@ mod tests {
         assert_eq!(history.len(), 3);
         assert_eq!(history.get(0), Some("zig"));
     }
+
+    #[test]
+    fn test_save_and_load_from_a_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("state").join("search_history.json");
+        assert!(SearchHistory::load_from(&file)?.is_empty());
+
+        let mut history = SearchHistory::new(5);
+        history.add("todo".to_string());
+        history.save_to(&file)?;
+        assert_eq!(SearchHistory::load_from(&file)?.all(), vec!["todo"]);
+        Ok(())
+    }
 }
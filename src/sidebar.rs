@@ -0,0 +1,117 @@
+// Sidebar - quick-nav shortcuts to home, root, bookmarks and recent directories
+use std::path::{Path, PathBuf};
+
+/// Keys that jump to sidebar items, in item order
+pub const SIDEBAR_KEYS: [char; 10] = ['1', '2', '3', '4', '5', '6', '7', '8', '9', '0'];
+
+/// How many visited directories are remembered for the sidebar
+pub const RECENT_DIRS_MAX: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SidebarSection {
+    Places,
+    Bookmarks,
+    Recent,
+}
+
+impl SidebarSection {
+    pub fn title(self) -> &'static str {
+        match self {
+            SidebarSection::Places => "Places",
+            SidebarSection::Bookmarks => "Bookmarks",
+            SidebarSection::Recent => "Recent",
+        }
+    }
+}
+
+/// One shortcut; only the first `SIDEBAR_KEYS.len()` items get a key
+#[derive(Clone, Debug, PartialEq)]
+pub struct SidebarItem {
+    pub key: Option<char>,
+    pub label: String,
+    pub path: PathBuf,
+    pub section: SidebarSection,
+}
+
+/// Build the sidebar: home and root, then bookmarks, then up to `max_recent`
+/// recent directories not already listed above
+pub fn sidebar_items(
+    home: Option<&Path>,
+    root: &Path,
+    bookmarks: &[(String, PathBuf)],
+    recent: &[PathBuf],
+    max_recent: usize,
+) -> Vec<SidebarItem> {
+    let item = |label: String, path: PathBuf, section| SidebarItem { key: None, label, path, section };
+    let mut items = Vec::new();
+
+    if let Some(home) = home {
+        items.push(item("~ Home".to_string(), home.to_path_buf(), SidebarSection::Places));
+    }
+    items.push(item(format!("{} Root", root.display()), root.to_path_buf(), SidebarSection::Places));
+    for (name, path) in bookmarks {
+        items.push(item(name.clone(), path.clone(), SidebarSection::Bookmarks));
+    }
+
+    let listed: Vec<PathBuf> = items.iter().map(|item| item.path.clone()).collect();
+    for path in recent.iter().filter(|p| !listed.contains(p)).take(max_recent) {
+        let label = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        items.push(item(label, path.clone(), SidebarSection::Recent));
+    }
+
+    for (item, key) in items.iter_mut().zip(SIDEBAR_KEYS) {
+        item.key = Some(key);
+    }
+    items
+}
+
+/// Move `dir` to the front of the recent list, dropping the oldest past `RECENT_DIRS_MAX`
+pub fn push_recent_dir(recent: &mut Vec<PathBuf>, dir: &Path) {
+    if recent.first().is_some_and(|first| first == dir) {
+        return;
+    }
+    recent.retain(|p| p != dir);
+    recent.insert(0, dir.to_path_buf());
+    recent.truncate(RECENT_DIRS_MAX);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sidebar_items_and_keys() {
+        let home = PathBuf::from("/home/ada");
+        let bookmarks = vec![("Projects".to_string(), PathBuf::from("/home/ada/src"))];
+        let mut recent = Vec::new();
+        for dir in ["/tmp", "/home/ada/src", "/var/log", "/tmp"] {
+            push_recent_dir(&mut recent, Path::new(dir));
+        }
+        assert_eq!(recent, vec![PathBuf::from("/tmp"), PathBuf::from("/var/log"), PathBuf::from("/home/ada/src")]);
+
+        // The bookmarked directory isn't repeated under Recent
+        let items = sidebar_items(Some(&home), Path::new("/"), &bookmarks, &recent, 2);
+        let summary: Vec<(Option<char>, &str, SidebarSection)> =
+            items.iter().map(|i| (i.key, i.label.as_str(), i.section)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Some('1'), "~ Home", SidebarSection::Places),
+                (Some('2'), "/ Root", SidebarSection::Places),
+                (Some('3'), "Projects", SidebarSection::Bookmarks),
+                (Some('4'), "tmp", SidebarSection::Recent),
+                (Some('5'), "log", SidebarSection::Recent),
+            ]
+        );
+
+        // Only the first ten entries get a key
+        let many: Vec<PathBuf> = (0..12).map(|i| PathBuf::from(format!("/d{}", i))).collect();
+        let items = sidebar_items(None, Path::new("/"), &bookmarks, &many, 12);
+        assert_eq!(items.len(), 14);
+        assert_eq!(items[9].key, Some('0'));
+        assert_eq!(items[10].key, None);
+    }
+}
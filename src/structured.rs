@@ -0,0 +1,314 @@
+// Structured preview - JSON, YAML and TOML documents as a foldable tree
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Documents with more nodes than this start with deep containers collapsed
+pub const LARGE_DOCUMENT_NODES: usize = 200;
+
+/// Containers this deep or deeper (0 is the top level) start collapsed in large documents
+pub const DEFAULT_FOLD_DEPTH: usize = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    /// The format a file's extension names, if it's one shown as a tree
+    pub fn of(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Format::Json => "JSON",
+            Format::Yaml => "YAML",
+            Format::Toml => "TOML",
+        }
+    }
+}
+
+/// A parsed document: scalars are kept as they'd be written in JSON
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Value(String),
+    List(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn children(&self) -> Vec<(String, &Node)> {
+        match self {
+            Node::Value(_) => Vec::new(),
+            Node::List(items) => items.iter().enumerate().map(|(i, node)| (format!("[{}]", i), node)).collect(),
+            Node::Map(entries) => entries.iter().map(|(key, node)| (key.clone(), node)).collect(),
+        }
+    }
+
+    fn count(&self) -> usize {
+        1 + self.children().iter().map(|(_, node)| node.count()).sum::<usize>()
+    }
+
+    /// `{…} 3 keys` or `[…] 5 items`, for a collapsed container
+    fn summary(&self) -> String {
+        match self {
+            Node::Value(value) => value.clone(),
+            Node::List(items) => format!("[…] {} item{}", items.len(), if items.len() == 1 { "" } else { "s" }),
+            Node::Map(entries) => format!("{{…}} {} key{}", entries.len(), if entries.len() == 1 { "" } else { "s" }),
+        }
+    }
+
+    fn at(&self, path: &[usize]) -> Option<&Node> {
+        match path.split_first() {
+            None => Some(self),
+            Some((&index, rest)) => match self {
+                Node::List(items) => items.get(index)?.at(rest),
+                Node::Map(entries) => entries.get(index)?.1.at(rest),
+                Node::Value(_) => None,
+            },
+        }
+    }
+}
+
+/// Parse `text` as `format`; the error names the line and column where parsing stopped
+pub fn parse(format: Format, text: &str) -> Result<Node, String> {
+    match format {
+        Format::Json => serde_json::from_str::<serde_json::Value>(text)
+            .map(|value| from_json(&value))
+            .map_err(|e| e.to_string()),
+        Format::Toml => text
+            .parse::<toml::Table>()
+            .map(|table| from_toml(&toml::Value::Table(table)))
+            .map_err(|e| e.to_string().trim_end().to_string()),
+        Format::Yaml => {
+            use serde::Deserialize;
+            let docs = serde_yaml::Deserializer::from_str(text)
+                .map(serde_yaml::Value::deserialize)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            Ok(match docs.len() {
+                0 => Node::Value("null".to_string()),
+                1 => from_yaml(&docs[0]),
+                _ => Node::List(docs.iter().map(from_yaml).collect()),
+            })
+        }
+    }
+}
+
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| s.to_string())
+}
+
+fn from_json(value: &serde_json::Value) -> Node {
+    match value {
+        serde_json::Value::Array(items) => Node::List(items.iter().map(from_json).collect()),
+        serde_json::Value::Object(map) => Node::Map(map.iter().map(|(k, v)| (k.clone(), from_json(v))).collect()),
+        scalar => Node::Value(scalar.to_string()),
+    }
+}
+
+fn from_toml(value: &toml::Value) -> Node {
+    match value {
+        toml::Value::Array(items) => Node::List(items.iter().map(from_toml).collect()),
+        toml::Value::Table(table) => Node::Map(table.iter().map(|(k, v)| (k.clone(), from_toml(v))).collect()),
+        toml::Value::String(s) => Node::Value(quote(s)),
+        scalar => Node::Value(scalar.to_string()),
+    }
+}
+
+fn from_yaml(value: &serde_yaml::Value) -> Node {
+    use serde_yaml::Value;
+    match value {
+        Value::Sequence(items) => Node::List(items.iter().map(from_yaml).collect()),
+        Value::Mapping(map) => Node::Map(map.iter().map(|(k, v)| (yaml_key(k), from_yaml(v))).collect()),
+        Value::String(s) => Node::Value(quote(s)),
+        Value::Number(n) => Node::Value(n.to_string()),
+        Value::Bool(b) => Node::Value(b.to_string()),
+        // `!tag value` shows the value; the tag is only a hint to the application reading it
+        Value::Tagged(tagged) => from_yaml(&tagged.value),
+        Value::Null => Node::Value("null".to_string()),
+    }
+}
+
+fn yaml_key(key: &serde_yaml::Value) -> String {
+    match from_yaml(key) {
+        Node::Value(value) => value.trim_matches('"').to_string(),
+        _ => "?".to_string(),
+    }
+}
+
+/// One visible line of the tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// Child indexes from the root down to this node
+    pub path: Vec<usize>,
+    pub depth: usize,
+    pub text: String,
+    /// Whether this is a container that can be folded
+    pub foldable: bool,
+}
+
+/// A document with the folds the user has made
+#[derive(Debug, Clone)]
+pub struct StructuredDoc {
+    pub format: Format,
+    pub root: Node,
+    collapsed: HashSet<Vec<usize>>,
+}
+
+impl StructuredDoc {
+    /// Everything expanded, except deep containers of a large document
+    pub fn new(format: Format, root: Node) -> Self {
+        let mut doc = Self { format, root, collapsed: HashSet::new() };
+        if doc.root.count() > LARGE_DOCUMENT_NODES {
+            doc.collapse_from(DEFAULT_FOLD_DEPTH);
+        }
+        doc
+    }
+
+    /// The visible rows, top to bottom
+    pub fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        match &self.root {
+            Node::Value(value) => rows.push(Row { path: Vec::new(), depth: 0, text: value.clone(), foldable: false }),
+            root => self.push_children(root, &mut Vec::new(), 0, &mut rows),
+        }
+        rows
+    }
+
+    fn push_children(&self, node: &Node, path: &mut Vec<usize>, depth: usize, rows: &mut Vec<Row>) {
+        let indent = "  ".repeat(depth);
+        for (index, (label, child)) in node.children().into_iter().enumerate() {
+            path.push(index);
+            let foldable = !matches!(child, Node::Value(_));
+            let collapsed = self.collapsed.contains(path.as_slice());
+            let text = match child {
+                Node::Value(value) => format!("{}  {}: {}", indent, label, value),
+                _ if collapsed => format!("{}▸ {} {}", indent, label, child.summary()),
+                _ => format!("{}▾ {}", indent, label),
+            };
+            rows.push(Row { path: path.clone(), depth, text, foldable });
+            if foldable && !collapsed {
+                self.push_children(child, path, depth + 1, rows);
+            }
+            path.pop();
+        }
+    }
+
+    pub fn is_collapsed(&self, path: &[usize]) -> bool {
+        self.collapsed.contains(path)
+    }
+
+    /// Fold or unfold the container at `path`; false if there isn't one
+    pub fn toggle(&mut self, path: &[usize]) -> bool {
+        if !matches!(self.root.at(path), Some(Node::List(_) | Node::Map(_))) || path.is_empty() {
+            return false;
+        }
+        if !self.collapsed.remove(path) {
+            self.collapsed.insert(path.to_vec());
+        }
+        true
+    }
+
+    pub fn expand_all(&mut self) {
+        self.collapsed.clear();
+    }
+
+    /// Collapse every container at or below `depth` (0 folds the top level)
+    pub fn collapse_from(&mut self, depth: usize) {
+        fn walk(node: &Node, path: &mut Vec<usize>, depth: usize, out: &mut HashSet<Vec<usize>>) {
+            for (index, (_, child)) in node.children().into_iter().enumerate() {
+                path.push(index);
+                if !matches!(child, Node::Value(_)) {
+                    if path.len() > depth {
+                        out.insert(path.clone());
+                    }
+                    walk(child, path, depth, out);
+                }
+                path.pop();
+            }
+        }
+        walk(&self.root, &mut Vec::new(), depth, &mut self.collapsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(doc: &StructuredDoc) -> Vec<String> {
+        doc.rows().into_iter().map(|row| row.text).collect()
+    }
+
+    #[test]
+    fn test_parse_formats_alike() {
+        let json = parse(Format::Json, r#"{"name": "astrofs", "tags": ["tui", "rust"]}"#).unwrap();
+        let yaml = parse(Format::Yaml, "name: astrofs\ntags:\n  - tui\n  - rust\n").unwrap();
+        let toml = parse(Format::Toml, "name = \"astrofs\"\ntags = [\"tui\", \"rust\"]\n").unwrap();
+        assert_eq!(json, yaml);
+        assert_eq!(json, toml);
+
+        let doc = StructuredDoc::new(Format::Json, json);
+        assert_eq!(texts(&doc), vec!["  name: \"astrofs\"", "▾ tags", "    [0]: \"tui\"", "    [1]: \"rust\""]);
+        assert_eq!(Format::of(Path::new("ci/deploy.YML")), Some(Format::Yaml));
+        assert_eq!(Format::of(Path::new("notes.txt")), None);
+    }
+
+    #[test]
+    fn test_keys_keep_file_order() {
+        let keys = |node: Node| match node {
+            Node::Map(entries) => entries.into_iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            other => panic!("not a map: {:?}", other),
+        };
+        let expected = vec!["zebra", "apple", "mango"];
+        assert_eq!(keys(parse(Format::Json, r#"{"zebra": 1, "apple": 2, "mango": 3}"#).unwrap()), expected);
+        assert_eq!(keys(parse(Format::Yaml, "zebra: 1\napple: 2\nmango: 3\n").unwrap()), expected);
+        assert_eq!(keys(parse(Format::Toml, "zebra = 1\napple = 2\nmango = 3\n").unwrap()), expected);
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_location() {
+        let err = parse(Format::Json, "{\n  \"a\": 1,\n  \"b\" 2\n}").unwrap_err();
+        assert!(err.contains("line 3 column 7"), "{}", err);
+        let err = parse(Format::Yaml, "a: [1, 2\nb: 3\n").unwrap_err();
+        assert!(err.contains("line"), "{}", err);
+        assert!(parse(Format::Toml, "a = \n").is_err());
+    }
+
+    #[test]
+    fn test_folding() {
+        let root = parse(Format::Json, r#"{"deps": {"serde": "1", "toml": "0.8"}, "version": 2}"#).unwrap();
+        let mut doc = StructuredDoc::new(Format::Json, root);
+        assert_eq!(doc.rows().len(), 4);
+
+        assert!(doc.toggle(&[0]));
+        assert_eq!(texts(&doc), vec!["▸ deps {…} 2 keys", "  version: 2"]);
+        assert!(!doc.toggle(&[1]), "scalars don't fold");
+
+        doc.expand_all();
+        doc.collapse_from(0);
+        assert!(doc.is_collapsed(&[0]));
+        doc.toggle(&[0]);
+        assert_eq!(doc.rows().len(), 4);
+    }
+
+    #[test]
+    fn test_large_documents_start_folded() {
+        let items: Vec<String> = (0..LARGE_DOCUMENT_NODES).map(|i| format!(r#"{{"id": {}, "tags": [1]}}"#, i)).collect();
+        let root = parse(Format::Json, &format!(r#"{{"items": [{}]}}"#, items.join(","))).unwrap();
+        let doc = StructuredDoc::new(Format::Json, root);
+
+        // The list itself is open, each item in it is folded
+        let rows = doc.rows();
+        assert_eq!(rows.len(), 1 + LARGE_DOCUMENT_NODES);
+        assert_eq!(rows[1].text, "  ▸ [0] {…} 2 keys");
+    }
+}
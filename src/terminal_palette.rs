@@ -0,0 +1,233 @@
+// Terminal Palette - query the terminal's ANSI colors via OSC escape sequences
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub type Rgb = (u8, u8, u8);
+
+/// xterm's default 16-color palette, used for colors the terminal doesn't report
+pub const XTERM_DEFAULTS: [Rgb; 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Colors reported by the terminal
+#[derive(Clone, Debug, Default)]
+pub struct TerminalPalette {
+    pub ansi: HashMap<u8, Rgb>,
+    pub foreground: Option<Rgb>,
+    pub background: Option<Rgb>,
+}
+
+impl TerminalPalette {
+    /// Get ANSI color `index` (0-15), falling back to the xterm default
+    pub fn color(&self, index: u8) -> Rgb {
+        self.ansi
+            .get(&index)
+            .copied()
+            .unwrap_or(XTERM_DEFAULTS[index as usize % 16])
+    }
+
+    pub fn foreground(&self) -> Rgb {
+        self.foreground.unwrap_or_else(|| self.color(7))
+    }
+
+    pub fn background(&self) -> Rgb {
+        self.background.unwrap_or_else(|| self.color(0))
+    }
+}
+
+/// Ask the terminal for its palette (OSC 4) and default colors (OSC 10/11).
+///
+/// Returns an empty palette if stdin/stdout is not a terminal or nothing
+/// answers within `timeout`.
+pub fn query(timeout: Duration) -> TerminalPalette {
+    read_replies(timeout)
+        .map(|reply| parse_replies(&reply))
+        .unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn read_replies(timeout: Duration) -> Option<String> {
+    use crossterm::terminal;
+    use std::io::{IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return None;
+    }
+
+    // Replies arrive as keyboard input, so echo and line buffering must be off
+    let was_raw = terminal::is_raw_mode_enabled().unwrap_or(false);
+    if !was_raw {
+        terminal::enable_raw_mode().ok()?;
+    }
+
+    let mut request = String::new();
+    for index in 0..16 {
+        request.push_str(&format!("\x1b]4;{};?\x07", index));
+    }
+    request.push_str("\x1b]10;?\x07\x1b]11;?\x07");
+    // Primary device attributes: every terminal answers, marking the end of replies
+    request.push_str("\x1b[c");
+
+    let mut stdout = std::io::stdout();
+    let sent = stdout
+        .write_all(request.as_bytes())
+        .and_then(|_| stdout.flush())
+        .is_ok();
+    let reply = if sent { Some(read_until_device_attributes(timeout)) } else { None };
+
+    if !was_raw {
+        let _ = terminal::disable_raw_mode();
+    }
+    reply
+}
+
+#[cfg(not(unix))]
+fn read_replies(_timeout: Duration) -> Option<String> {
+    None
+}
+
+/// Read raw stdin until the device attributes reply arrives or time runs out.
+///
+/// Reads go straight to the file descriptor so no bytes are left behind in
+/// a userspace buffer that the event loop would never see.
+#[cfg(unix)]
+fn read_until_device_attributes(timeout: Duration) -> String {
+    use std::time::Instant;
+
+    let deadline = Instant::now() + timeout;
+    let mut buffer = Vec::new();
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+
+        let mut fds = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `fds` is a valid pollfd for the duration of the call
+        let ready = unsafe { libc::poll(&mut fds, 1, remaining.as_millis() as libc::c_int) };
+        if ready <= 0 {
+            break;
+        }
+
+        let mut chunk = [0u8; 1024];
+        // SAFETY: `chunk` is writable for `chunk.len()` bytes
+        let read = unsafe {
+            libc::read(
+                libc::STDIN_FILENO,
+                chunk.as_mut_ptr() as *mut libc::c_void,
+                chunk.len(),
+            )
+        };
+        if read <= 0 {
+            break;
+        }
+
+        buffer.extend_from_slice(&chunk[..read as usize]);
+        if has_device_attributes(&buffer) {
+            break;
+        }
+    }
+
+    String::from_utf8_lossy(&buffer).into_owned()
+}
+
+/// Check for a complete `ESC [ ? ... c` reply
+#[cfg(unix)]
+fn has_device_attributes(buffer: &[u8]) -> bool {
+    buffer
+        .windows(3)
+        .position(|w| w == b"\x1b[?")
+        .map(|start| buffer[start..].contains(&b'c'))
+        .unwrap_or(false)
+}
+
+/// Parse OSC 4/10/11 color replies.
+///
+/// Each reply looks like `ESC ] 4 ; N ; rgb:RRRR/GGGG/BBBB` terminated by BEL
+/// or ST (`ESC \`); unrelated bytes between replies are ignored.
+pub fn parse_replies(reply: &str) -> TerminalPalette {
+    let mut palette = TerminalPalette::default();
+
+    for segment in reply.split("\x1b]").skip(1) {
+        let body = segment
+            .split(['\x07', '\x1b'])
+            .next()
+            .unwrap_or("");
+        let parts: Vec<&str> = body.split(';').collect();
+
+        match parts.as_slice() {
+            ["4", index, spec] => {
+                if let (Ok(index), Some(rgb)) = (index.parse::<u8>(), parse_rgb_spec(spec)) {
+                    if index < 16 {
+                        palette.ansi.insert(index, rgb);
+                    }
+                }
+            }
+            ["10", spec] => palette.foreground = parse_rgb_spec(spec),
+            ["11", spec] => palette.background = parse_rgb_spec(spec),
+            _ => {}
+        }
+    }
+
+    palette
+}
+
+/// Parse an X11 color spec like `rgb:ffff/8080/0000` (1-4 hex digits per channel)
+fn parse_rgb_spec(spec: &str) -> Option<Rgb> {
+    let channels: Vec<&str> = spec.strip_prefix("rgb:")?.split('/').collect();
+    if channels.len() != 3 {
+        return None;
+    }
+
+    let scale = |hex: &str| -> Option<u8> {
+        if hex.is_empty() || hex.len() > 4 {
+            return None;
+        }
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1u32 << (4 * hex.len())) - 1;
+        Some(((value * 255 + max / 2) / max) as u8)
+    };
+
+    Some((scale(channels[0])?, scale(channels[1])?, scale(channels[2])?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_replies() {
+        let reply = "\x1b]4;1;rgb:ffff/0000/0000\x07\
+                     \x1b]4;4;rgb:00/80/ff\x1b\\\
+                     \x1b]11;rgb:1c1c/1c1c/1c1c\x07\
+                     \x1b[?62;22c";
+        let palette = parse_replies(reply);
+
+        assert_eq!(palette.color(1), (255, 0, 0));
+        assert_eq!(palette.color(4), (0, 128, 255));
+        assert_eq!(palette.background(), (28, 28, 28));
+        // Unanswered entries fall back to xterm defaults
+        assert_eq!(palette.color(2), XTERM_DEFAULTS[2]);
+        assert_eq!(palette.foreground(), XTERM_DEFAULTS[7]);
+    }
+}
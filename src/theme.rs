@@ -3,6 +3,7 @@ use ratatui::style::{Color, Modifier, Style};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::time::{Duration, SystemTime};
 use dirs::config_dir;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -87,6 +88,8 @@ impl ThemeConfig {
             normal: self.normal.to_style(),
             border: self.border.to_style(),
             help: self.help.to_style(),
+            sidebar: self.normal.to_style(),
+            icons: IconSet::default(),
         }
     }
 
@@ -204,6 +207,7 @@ impl ThemeConfig {
 }
 
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct Theme {
     pub folder: Style,
     pub executable: Style,
@@ -217,6 +221,10 @@ pub struct Theme {
     pub normal: Style,
     pub border: Style,
     pub help: Style,
+    /// Quick-nav sidebar entries
+    pub sidebar: Style,
+    /// Glyphs for file icons
+    pub icons: IconSet,
 }
 
 impl Default for Theme {
@@ -237,6 +245,8 @@ impl Default for Theme {
             normal: Style::default().fg(Color::White),
             border: Style::default().fg(Color::Cyan),
             help: Style::default().fg(Color::Yellow),
+            sidebar: Style::default().fg(Color::Green),
+            icons: IconSet::default(),
         }
     }
 }
@@ -270,29 +280,180 @@ fn parse_color(color_str: &str) -> Color {
     }
 }
 
-pub fn get_file_emoji(path: &std::path::Path, is_dir: bool) -> &'static str {
-    if is_dir {
-        return "📁";
+/// Which glyphs file icons are drawn with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IconSet {
+    /// Private-use glyphs from a patched Nerd Font
+    NerdFont,
+    #[default]
+    Emoji,
+    /// Short tags like `[D]` that any terminal can show
+    Ascii,
+}
+
+impl IconSet {
+    /// `icon_set` from the config: "auto" follows the theme's `use_nerd_fonts`,
+    /// and any set falls back to ASCII on a terminal that likely can't draw it
+    pub fn resolve(setting: &str, use_nerd_fonts: bool, limited_terminal: bool) -> Self {
+        let chosen = match setting.trim().to_lowercase().as_str() {
+            "nerd" => IconSet::NerdFont,
+            "emoji" => IconSet::Emoji,
+            "ascii" => IconSet::Ascii,
+            _ if use_nerd_fonts => IconSet::NerdFont,
+            _ => IconSet::Emoji,
+        };
+        if limited_terminal { IconSet::Ascii } else { chosen }
     }
 
-    if let Some(ext) = path.extension() {
-        match ext.to_str().unwrap_or("").to_lowercase().as_str() {
-            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "svg" | "webp" => "🖼️",
-            "zip" | "tar" | "gz" | "rar" | "7z" | "bz2" | "xz" => "🗜️",
-            "exe" | "sh" | "bat" | "cmd" => "⚡",
-            "rs" | "py" | "js" | "ts" | "c" | "cpp" | "java" | "go" => "📝",
-            "txt" | "md" | "json" | "yaml" | "toml" | "xml" => "📄",
-            "mp3" | "wav" | "flac" | "ogg" | "m4a" => "🎵",
-            "mp4" | "avi" | "mkv" | "mov" | "webm" => "🎬",
-            "pdf" => "📕",
-            "lock" | "key" => "🔒",
-            _ => "📄",
+    pub fn glyph(self, category: IconCategory) -> &'static str {
+        use IconCategory::*;
+        match self {
+            IconSet::NerdFont => match category {
+                Folder => "\u{f07b}",
+                Image => "\u{f1c5}",
+                Archive => "\u{f1c6}",
+                Executable => "\u{f489}",
+                Code => "\u{f1c9}",
+                Text => "\u{f15c}",
+                Audio => "\u{f1c7}",
+                Video => "\u{f1c8}",
+                Pdf => "\u{f1c1}",
+                Locked => "\u{f023}",
+                File => "\u{f15b}",
+            },
+            IconSet::Emoji => match category {
+                Folder => "📁",
+                Image => "🖼️",
+                Archive => "🗜️",
+                Executable => "⚡",
+                Code => "📝",
+                Text => "📄",
+                Audio => "🎵",
+                Video => "🎬",
+                Pdf => "📕",
+                Locked => "🔒",
+                File => "📄",
+            },
+            IconSet::Ascii => match category {
+                Folder => "[D]",
+                Image => "[I]",
+                Archive => "[Z]",
+                Executable => "[X]",
+                Code => "[C]",
+                Text => "[T]",
+                Audio => "[A]",
+                Video => "[V]",
+                Pdf => "[P]",
+                Locked => "[L]",
+                File => "[F]",
+            },
+        }
+    }
+}
+
+/// Kinds of entries that get their own icon
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IconCategory {
+    Folder,
+    Image,
+    Archive,
+    Executable,
+    Code,
+    Text,
+    Audio,
+    Video,
+    Pdf,
+    Locked,
+    File,
+}
+
+impl IconCategory {
+    pub fn of(path: &std::path::Path, is_dir: bool) -> Self {
+        if is_dir {
+            return IconCategory::Folder;
+        }
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        match ext.as_str() {
+            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "svg" | "webp" => IconCategory::Image,
+            "zip" | "tar" | "gz" | "rar" | "7z" | "bz2" | "xz" => IconCategory::Archive,
+            "exe" | "sh" | "bat" | "cmd" => IconCategory::Executable,
+            "rs" | "py" | "js" | "ts" | "c" | "cpp" | "java" | "go" => IconCategory::Code,
+            "txt" | "md" | "json" | "yaml" | "toml" | "xml" => IconCategory::Text,
+            "mp3" | "wav" | "flac" | "ogg" | "m4a" => IconCategory::Audio,
+            "mp4" | "avi" | "mkv" | "mov" | "webm" => IconCategory::Video,
+            "pdf" => IconCategory::Pdf,
+            "lock" | "key" => IconCategory::Locked,
+            _ => IconCategory::File,
+        }
+    }
+
+    /// The category a detected mime type belongs to, if it says anything useful
+    pub fn of_mime(mime: &str) -> Option<Self> {
+        let (kind, subtype) = mime.split_once('/')?;
+        match kind {
+            "image" => Some(IconCategory::Image),
+            "audio" => Some(IconCategory::Audio),
+            "video" => Some(IconCategory::Video),
+            "text" => Some(IconCategory::Text),
+            _ if subtype == "pdf" => Some(IconCategory::Pdf),
+            _ if ["zip", "gzip", "x-tar", "x-bzip2", "x-xz", "x-7z-compressed", "vnd.rar", "zstd"].contains(&subtype) => {
+                Some(IconCategory::Archive)
+            }
+            _ if ["x-executable", "x-elf", "x-mach-binary", "vnd.microsoft.portable-executable", "x-msdownload"]
+                .contains(&subtype) =>
+            {
+                Some(IconCategory::Executable)
+            }
+            _ => None,
         }
-    } else {
-        "📄"
     }
 }
 
+/// Whether the terminal probably can't draw anything past ASCII: the Linux
+/// console, a dumb terminal, or a locale that isn't UTF-8
+pub fn is_limited_terminal(term: Option<&str>, locale: Option<&str>) -> bool {
+    if matches!(term, Some("linux" | "dumb" | "vt100" | "vt220")) {
+        return true;
+    }
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_lowercase();
+            !(locale.contains("utf-8") || locale.contains("utf8"))
+        }
+        None => false,
+    }
+}
+
+/// `is_limited_terminal` for the terminal we're running in
+pub fn current_terminal_is_limited() -> bool {
+    let term = std::env::var("TERM").ok();
+    // The first locale variable that is set decides, as with setlocale
+    let locale = if cfg!(windows) {
+        None
+    } else {
+        ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+    };
+    is_limited_terminal(term.as_deref(), locale.as_deref())
+}
+
+/// Icon for an entry in the given set
+pub fn file_icon(path: &std::path::Path, is_dir: bool, icons: IconSet) -> &'static str {
+    icons.glyph(IconCategory::of(path, is_dir))
+}
+
+/// Icon for a listed entry, going by its detected mime type when the extension says nothing
+pub fn entry_icon(entry: &crate::files::FileEntry, icons: IconSet) -> &'static str {
+    let by_name = IconCategory::of(&entry.path, entry.is_dir);
+    let category = match (by_name, entry.mime.as_deref()) {
+        (IconCategory::File, Some(mime)) => IconCategory::of_mime(mime).unwrap_or(by_name),
+        _ => by_name,
+    };
+    icons.glyph(category)
+}
+
 pub fn get_file_style(path: &std::path::Path, is_dir: bool, theme: &Theme) -> Style {
     if is_dir {
         return theme.folder;
@@ -310,6 +471,28 @@ pub fn get_file_style(path: &std::path::Path, is_dir: bool, theme: &Theme) -> St
     }
 }
 
+/// Entries modified more recently than this stand out with age shading
+pub const AGE_FRESH: Duration = Duration::from_secs(24 * 60 * 60);
+/// Entries left alone for this long fade with age shading
+pub const AGE_STALE: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+/// `style` shaded by how long ago `modified` was: bold within `AGE_FRESH`,
+/// dimmed from `AGE_STALE` on, and left alone in between or when unknown
+pub fn age_shaded(style: Style, modified: Option<SystemTime>, now: SystemTime) -> Style {
+    let Some(modified) = modified else {
+        return style;
+    };
+    // Times in the future (clock skew) count as just now
+    let age = now.duration_since(modified).unwrap_or_default();
+    if age < AGE_FRESH {
+        style.add_modifier(Modifier::BOLD)
+    } else if age >= AGE_STALE {
+        style.add_modifier(Modifier::DIM)
+    } else {
+        style
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,4 +510,20 @@ mod tests {
         let theme = config.to_theme();
         assert_eq!(theme.folder, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
     }
+
+    #[test]
+    fn test_age_shading() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let base = Style::default().fg(Color::White);
+        let shade = |secs: u64| age_shaded(base, Some(now - Duration::from_secs(secs)), now);
+
+        assert_eq!(shade(5 * 60), base.add_modifier(Modifier::BOLD));
+        assert_eq!(shade(23 * 60 * 60), base.add_modifier(Modifier::BOLD));
+        assert_eq!(shade(3 * 24 * 60 * 60), base);
+        assert_eq!(shade(89 * 24 * 60 * 60), base);
+        assert_eq!(shade(90 * 24 * 60 * 60), base.add_modifier(Modifier::DIM));
+        assert_eq!(shade(3 * 365 * 24 * 60 * 60), base.add_modifier(Modifier::DIM));
+        assert_eq!(age_shaded(base, Some(now + Duration::from_secs(60)), now), base.add_modifier(Modifier::BOLD));
+        assert_eq!(age_shaded(base, None, now), base);
+    }
 }
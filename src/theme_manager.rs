@@ -2,8 +2,12 @@
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use ratatui::style::{Color, Modifier, Style};
 use crate::persistence::PersistenceManager;
+use crate::terminal_palette::{self, Rgb, TerminalPalette};
+use std::time::Duration;
+use crate::theme::{IconSet, Theme as RenderTheme};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Theme {
@@ -112,6 +116,71 @@ impl Default for Theme {
     }
 }
 
+impl Theme {
+    /// Build the ratatui styles used for rendering from this theme's colors.
+    ///
+    /// This is a pure conversion, so previews can render any theme without
+    /// switching the active one.
+    pub fn to_render_theme(&self) -> RenderTheme {
+        let c = &self.colors;
+        let fg = |color: &str| Style::default().fg(to_color(color));
+        let bold = |style: Style| {
+            if self.fonts.enable_bold {
+                style.add_modifier(Modifier::BOLD)
+            } else {
+                style
+            }
+        };
+
+        RenderTheme {
+            folder: bold(fg(&c.directory_color)),
+            executable: bold(fg(&c.executable_color)),
+            image: fg(&c.accent),
+            archive: fg(&c.warning),
+            text_file: fg(&c.file_color),
+            selected: bold(fg(&c.selection_fg).bg(to_color(&c.selection_bg))),
+            hidden: Style::default().fg(Color::DarkGray),
+            status_bar: fg(&c.background).bg(to_color(&c.primary)),
+            error: bold(fg(&c.error)),
+            normal: fg(&c.foreground),
+            border: fg(&self.borders.color),
+            help: fg(&c.warning),
+            sidebar: fg(&c.secondary),
+            icons: if self.fonts.use_nerd_fonts { IconSet::NerdFont } else { IconSet::Emoji },
+        }
+    }
+}
+
+/// Parse a theme color (`#RRGGBB` or a known name) into an RGB tuple
+pub fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    if color.starts_with('#') && color.len() == 7 {
+        let r = u8::from_str_radix(&color[1..3], 16).ok()?;
+        let g = u8::from_str_radix(&color[3..5], 16).ok()?;
+        let b = u8::from_str_radix(&color[5..7], 16).ok()?;
+        Some((r, g, b))
+    } else {
+        // Handle named colors
+        match color.to_lowercase().as_str() {
+            "cyan" => Some((0, 217, 255)),
+            "green" => Some((0, 255, 159)),
+            "pink" => Some((255, 0, 110)),
+            "red" => Some((255, 0, 64)),
+            "yellow" => Some((255, 190, 11)),
+            "blue" => Some((0, 100, 255)),
+            "purple" => Some((170, 0, 255)),
+            "white" => Some((255, 255, 255)),
+            "black" => Some((0, 0, 0)),
+            _ => None,
+        }
+    }
+}
+
+fn to_color(color: &str) -> Color {
+    parse_hex_color(color)
+        .map(|(r, g, b)| Color::Rgb(r, g, b))
+        .unwrap_or(Color::Reset)
+}
+
 impl Default for ThemeColors {
     fn default() -> Self {
         Self {
@@ -180,6 +249,86 @@ impl Default for ThemeFonts {
     }
 }
 
+/// Minimum WCAG contrast ratio for normal text (level AA)
+pub const MIN_CONTRAST_RATIO: f64 = 4.5;
+
+/// A foreground/background pair that falls below the minimum contrast
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct ContrastWarning {
+    pub pair: String,
+    pub foreground: String,
+    pub background: String,
+    pub ratio: f64,
+    pub minimum: f64,
+}
+
+/// Theme color fields that take part in contrast checks
+#[derive(Clone, Copy)]
+enum ColorField {
+    Foreground,
+    Background,
+    Primary,
+    DirectoryColor,
+    SelectionFg,
+    SelectionBg,
+}
+
+impl ColorField {
+    fn get(self, colors: &ThemeColors) -> &String {
+        match self {
+            ColorField::Foreground => &colors.foreground,
+            ColorField::Background => &colors.background,
+            ColorField::Primary => &colors.primary,
+            ColorField::DirectoryColor => &colors.directory_color,
+            ColorField::SelectionFg => &colors.selection_fg,
+            ColorField::SelectionBg => &colors.selection_bg,
+        }
+    }
+
+    fn get_mut(self, colors: &mut ThemeColors) -> &mut String {
+        match self {
+            ColorField::Foreground => &mut colors.foreground,
+            ColorField::Background => &mut colors.background,
+            ColorField::Primary => &mut colors.primary,
+            ColorField::DirectoryColor => &mut colors.directory_color,
+            ColorField::SelectionFg => &mut colors.selection_fg,
+            ColorField::SelectionBg => &mut colors.selection_bg,
+        }
+    }
+}
+
+/// (label, foreground, background, field to nudge when adjusting).
+///
+/// The status bar draws the theme background on the primary color, so its
+/// bar color is nudged instead of the shared background.
+const CONTRAST_PAIRS: [(&str, ColorField, ColorField, ColorField); 4] = [
+    ("normal text", ColorField::Foreground, ColorField::Background, ColorField::Foreground),
+    ("directories", ColorField::DirectoryColor, ColorField::Background, ColorField::DirectoryColor),
+    ("selection", ColorField::SelectionFg, ColorField::SelectionBg, ColorField::SelectionFg),
+    ("status bar", ColorField::Background, ColorField::Primary, ColorField::Primary),
+];
+
+/// WCAG relative luminance of an sRGB color
+fn relative_luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    let channel = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+}
+
+/// WCAG contrast ratio between two colors (1.0 to 21.0)
+pub fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f64 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
 /// Theme Manager - manages all theme operations
 pub struct ThemeManager {
     themes: HashMap<String, Theme>,
@@ -204,15 +353,17 @@ impl Default for ThemeManager {
 }
 
 impl ThemeManager {
+    /// The bundled theme folder, then the one user themes are saved to
+    pub fn theme_dirs(&self) -> [&Path; 2] {
+        [&self.theme_dir, &self.user_theme_dir]
+    }
+
     /// Create new theme manager with default paths
+    #[deprecated(note = "use with_paths with folders under AppDirs::config_dir")]
+    #[allow(dead_code)]
     pub fn new() -> Result<Self> {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("astrofs");
-        let theme_dir = config_dir.join("themes");
-        let user_theme_dir = config_dir.join("user_themes");
-        
-        Self::with_paths(theme_dir, user_theme_dir)
+        let config_dir = crate::app::AppDirs::user()?.config_dir;
+        Self::with_paths(config_dir.join("themes"), config_dir.join("user_themes"))
     }
 
     /// Create new theme manager with custom paths
@@ -367,28 +518,126 @@ impl ThemeManager {
         Ok(name)
     }
 
+    /// Build a theme matching the terminal's current color scheme.
+    ///
+    /// Terminals that don't answer the palette query get xterm's defaults.
+    pub fn from_terminal() -> Theme {
+        let palette = terminal_palette::query(Duration::from_millis(500));
+        Self::theme_from_palette(&palette)
+    }
+
+    /// Map a 16-color terminal palette onto theme colors
+    pub fn theme_from_palette(palette: &TerminalPalette) -> Theme {
+        let hex = |(r, g, b): Rgb| format!("#{:02X}{:02X}{:02X}", r, g, b);
+        let foreground = hex(palette.foreground());
+        let background = hex(palette.background());
+
+        Theme {
+            name: "terminal".to_string(),
+            description: "Generated from the terminal's color palette".to_string(),
+            author: "AstroFS".to_string(),
+            version: "1.0.0".to_string(),
+            colors: ThemeColors {
+                primary: hex(palette.color(6)),
+                secondary: hex(palette.color(2)),
+                accent: hex(palette.color(5)),
+                background: background.clone(),
+                foreground: foreground.clone(),
+                error: hex(palette.color(1)),
+                warning: hex(palette.color(3)),
+                success: hex(palette.color(2)),
+                info: hex(palette.color(4)),
+                file_color: foreground.clone(),
+                directory_color: hex(palette.color(12)),
+                symlink_color: hex(palette.color(14)),
+                executable_color: hex(palette.color(10)),
+                selection_bg: hex(palette.color(4)),
+                selection_fg: background,
+                cursor_color: foreground,
+            },
+            borders: ThemeBorders {
+                style: BorderStyle::Rounded,
+                color: hex(palette.color(8)),
+                focused_color: hex(palette.color(6)),
+            },
+            emojis: ThemeEmojis::default(),
+            fonts: ThemeFonts::default(),
+        }
+    }
+
     /// Get color as RGB tuple
     pub fn parse_color(&self, color: &str) -> Option<(u8, u8, u8)> {
-        if color.starts_with('#') && color.len() == 7 {
-            let r = u8::from_str_radix(&color[1..3], 16).ok()?;
-            let g = u8::from_str_radix(&color[3..5], 16).ok()?;
-            let b = u8::from_str_radix(&color[5..7], 16).ok()?;
-            Some((r, g, b))
-        } else {
-            // Handle named colors
-            match color.to_lowercase().as_str() {
-                "cyan" => Some((0, 217, 255)),
-                "green" => Some((0, 255, 159)),
-                "pink" => Some((255, 0, 110)),
-                "red" => Some((255, 0, 64)),
-                "yellow" => Some((255, 190, 11)),
-                "blue" => Some((0, 100, 255)),
-                "purple" => Some((170, 0, 255)),
-                "white" => Some((255, 255, 255)),
-                "black" => Some((0, 0, 0)),
-                _ => None,
+        parse_hex_color(color)
+    }
+
+    /// Check key foreground/background pairs against the WCAG AA ratio.
+    ///
+    /// Pairs using colors that can't be parsed are skipped.
+    pub fn check_contrast(&self, theme: &Theme) -> Vec<ContrastWarning> {
+        CONTRAST_PAIRS
+            .iter()
+            .filter_map(|&(pair, fg, bg, _)| {
+                let foreground = fg.get(&theme.colors);
+                let background = bg.get(&theme.colors);
+                let ratio = contrast_ratio(
+                    self.parse_color(foreground)?,
+                    self.parse_color(background)?,
+                );
+                (ratio < MIN_CONTRAST_RATIO).then(|| ContrastWarning {
+                    pair: pair.to_string(),
+                    foreground: foreground.clone(),
+                    background: background.clone(),
+                    ratio,
+                    minimum: MIN_CONTRAST_RATIO,
+                })
+            })
+            .collect()
+    }
+
+    /// Nudge low-contrast colors toward black or white until every checked
+    /// pair reaches `min_ratio`. Returns the number of colors changed.
+    pub fn auto_adjust(&self, theme: &mut Theme, min_ratio: f64) -> usize {
+        let mut adjusted = 0;
+
+        for &(_, fg, bg, nudge) in CONTRAST_PAIRS.iter() {
+            let (Some(fg_rgb), Some(bg_rgb)) = (
+                self.parse_color(fg.get(&theme.colors)),
+                self.parse_color(bg.get(&theme.colors)),
+            ) else {
+                continue;
+            };
+            if contrast_ratio(fg_rgb, bg_rgb) >= min_ratio {
+                continue;
             }
+
+            let (moving, fixed) = if matches!(nudge, ColorField::Primary) {
+                (bg_rgb, fg_rgb)
+            } else {
+                (fg_rgb, bg_rgb)
+            };
+
+            // Head for whichever extreme can contrast more with the fixed color
+            let target = if contrast_ratio((255, 255, 255), fixed) >= contrast_ratio((0, 0, 0), fixed) {
+                (255, 255, 255)
+            } else {
+                (0, 0, 0)
+            };
+            let mix = |t: f64| {
+                let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+                (lerp(moving.0, target.0), lerp(moving.1, target.1), lerp(moving.2, target.2))
+            };
+
+            let nudged = (1..=20)
+                .map(|step| mix(step as f64 / 20.0))
+                .find(|&candidate| contrast_ratio(candidate, fixed) >= min_ratio)
+                .unwrap_or(target);
+
+            *nudge.get_mut(&mut theme.colors) =
+                format!("#{:02X}{:02X}{:02X}", nudged.0, nudged.1, nudged.2);
+            adjusted += 1;
         }
+
+        adjusted
     }
 
     /// Get color distance for finding closest named color
@@ -435,18 +684,107 @@ mod tests {
 
     #[test]
     fn test_parse_color_hex() {
-        let manager = ThemeManager::new().unwrap_or_default();
+        let manager = ThemeManager::default();
         let result = manager.parse_color("#00D9FF");
         assert_eq!(result, Some((0, 217, 255)))
     }
 
     #[test]
     fn test_parse_color_named() {
-        let manager = ThemeManager::new().unwrap_or_default();
+        let manager = ThemeManager::default();
         let result = manager.parse_color("cyan");
         assert_eq!(result, Some((0, 217, 255)))
     }
 
+    #[test]
+    fn test_render_theme_uses_theme_colors() {
+        let theme = Theme::default();
+        let render = theme.to_render_theme();
+        assert_eq!(render.folder.fg, Some(Color::Rgb(0, 217, 255)));
+        assert_eq!(render.selected.bg, Some(Color::Rgb(0, 217, 255)));
+        assert_eq!(render.selected.fg, Some(Color::Rgb(10, 14, 39)));
+    }
+
+    #[test]
+    fn test_nerd_font_flag_picks_icons() {
+        use crate::theme::{file_icon, is_limited_terminal};
+        let code = std::path::Path::new("main.rs");
+
+        let mut theme = Theme::default();
+        theme.fonts.use_nerd_fonts = true;
+        let nerd = file_icon(code, false, theme.to_render_theme().icons);
+        theme.fonts.use_nerd_fonts = false;
+        let emoji = file_icon(code, false, theme.to_render_theme().icons);
+        assert_eq!(nerd, "\u{f1c9}");
+        assert_eq!(emoji, "📝");
+
+        // The config can pick a set outright, and a limited terminal always gets ASCII
+        assert_eq!(IconSet::resolve("auto", true, false), IconSet::NerdFont);
+        assert_eq!(IconSet::resolve("emoji", true, false), IconSet::Emoji);
+        assert_eq!(IconSet::resolve("nerd", false, true), IconSet::Ascii);
+        assert_eq!(file_icon(code, true, IconSet::Ascii), "[D]");
+
+        assert!(is_limited_terminal(Some("linux"), Some("en_US.UTF-8")));
+        assert!(is_limited_terminal(Some("xterm-256color"), Some("C")));
+        assert!(!is_limited_terminal(Some("xterm-256color"), Some("en_US.utf8")));
+        assert!(!is_limited_terminal(None, None));
+    }
+
+    #[test]
+    fn test_theme_from_palette() {
+        let palette = terminal_palette::parse_replies(
+            "\x1b]4;12;rgb:1111/2222/3333\x07\x1b]10;rgb:eeee/eeee/eeee\x07",
+        );
+        let theme = ThemeManager::theme_from_palette(&palette);
+        assert_eq!(theme.name, "terminal");
+        assert_eq!(theme.colors.directory_color, "#112233");
+        assert_eq!(theme.colors.foreground, "#EEEEEE");
+        // No background reply: ANSI black from the xterm defaults
+        assert_eq!(theme.colors.background, "#000000");
+    }
+
+    fn low_contrast_theme() -> Theme {
+        let mut theme = Theme {
+            name: "murky".to_string(),
+            ..Theme::default()
+        };
+        theme.colors.background = "#202020".to_string();
+        theme.colors.foreground = "#303030".to_string();
+        theme.colors.selection_bg = "#777777".to_string();
+        theme.colors.selection_fg = "#888888".to_string();
+        theme
+    }
+
+    #[test]
+    fn test_contrast_ratio_extremes() {
+        assert!((contrast_ratio((0, 0, 0), (255, 255, 255)) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio((10, 20, 30), (10, 20, 30)) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_check_contrast_flags_low_contrast_pairs() {
+        let manager = ThemeManager::default();
+        assert!(manager.check_contrast(&Theme::default()).is_empty());
+
+        let warnings = manager.check_contrast(&low_contrast_theme());
+        let pairs: Vec<&str> = warnings.iter().map(|w| w.pair.as_str()).collect();
+        assert!(pairs.contains(&"normal text"));
+        assert!(pairs.contains(&"selection"));
+        assert!(warnings.iter().all(|w| w.ratio < MIN_CONTRAST_RATIO));
+    }
+
+    #[test]
+    fn test_auto_adjust_meets_minimum() {
+        let manager = ThemeManager::default();
+        let mut theme = low_contrast_theme();
+
+        assert!(manager.auto_adjust(&mut theme, MIN_CONTRAST_RATIO) >= 2);
+        assert!(manager.check_contrast(&theme).is_empty());
+        // Backgrounds are left alone
+        assert_eq!(theme.colors.background, "#202020");
+        assert_eq!(theme.colors.selection_bg, "#777777");
+    }
+
     #[test]
     fn test_theme_colors_default() {
         let colors = ThemeColors::default();
@@ -0,0 +1,492 @@
+// Thumbnails - background image decoding and terminal rendering for the gallery view
+use anyhow::Result;
+use base64::Engine;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::SystemTime;
+
+/// Largest thumbnail edge in pixels
+pub const THUMBNAIL_SIZE: u32 = 160;
+
+/// Decode threads; image decoding is memory hungry so keep this small
+const MAX_DECODE_WORKERS: usize = 2;
+
+/// Thumbnails kept in memory before the oldest are evicted
+const MAX_CACHED_THUMBNAILS: usize = 256;
+
+/// A downscaled RGB image
+#[derive(Clone, Debug)]
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Thumbnail {
+    /// Decode an image file and shrink it to fit `THUMBNAIL_SIZE`
+    pub fn load(path: &Path) -> Result<Self> {
+        let image = image::open(path)?
+            .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            .to_rgb8();
+
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            pixels: image.pixels().map(|p| p.0).collect(),
+        })
+    }
+
+    /// Nearest-neighbour sample at fractional coordinates (0.0..1.0)
+    fn sample(&self, fx: f32, fy: f32) -> [u8; 3] {
+        let x = ((fx * self.width as f32) as u32).min(self.width.saturating_sub(1));
+        let y = ((fy * self.height as f32) as u32).min(self.height.saturating_sub(1));
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Size that fits in `max_w` x `max_h` while keeping the aspect ratio
+    fn fit(&self, max_w: u32, max_h: u32) -> (u32, u32) {
+        if self.width == 0 || self.height == 0 || max_w == 0 || max_h == 0 {
+            return (0, 0);
+        }
+        let scale = (max_w as f32 / self.width as f32).min(max_h as f32 / self.height as f32);
+        (
+            ((self.width as f32 * scale).round() as u32).clamp(1, max_w),
+            ((self.height as f32 * scale).round() as u32).clamp(1, max_h),
+        )
+    }
+
+    /// Approximate the image with `▀` half blocks, two pixels per cell
+    pub fn to_block_lines(&self, cols: u16, rows: u16) -> Vec<Line<'static>> {
+        let (w, h) = self.fit(cols as u32, rows as u32 * 2);
+        let pad_x = (cols as u32 - w) / 2;
+        let pad_y = (rows as u32 * 2 - h) / 2;
+
+        let pixel = |x: u32, y: u32| -> Option<Color> {
+            if x < pad_x || x >= pad_x + w || y < pad_y || y >= pad_y + h {
+                return None;
+            }
+            let [r, g, b] = self.sample(
+                (x - pad_x) as f32 / w as f32 + 0.5 / w as f32,
+                (y - pad_y) as f32 / h as f32 + 0.5 / h as f32,
+            );
+            Some(Color::Rgb(r, g, b))
+        };
+
+        (0..rows as u32)
+            .map(|row| {
+                let spans: Vec<Span<'static>> = (0..cols as u32)
+                    .map(|col| match (pixel(col, row * 2), pixel(col, row * 2 + 1)) {
+                        (None, None) => Span::raw(" "),
+                        (top, bottom) => {
+                            let mut style = Style::default();
+                            if let Some(top) = top {
+                                style = style.fg(top);
+                            }
+                            if let Some(bottom) = bottom {
+                                style = style.bg(bottom);
+                            }
+                            Span::styled("▀", style)
+                        }
+                    })
+                    .collect();
+                Line::from(spans)
+            })
+            .collect()
+    }
+}
+
+/// How thumbnails are drawn in the terminal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GraphicsProtocol {
+    Kitty,
+    Sixel,
+    Blocks,
+}
+
+impl GraphicsProtocol {
+    /// Pick a protocol from a config value ("auto", "kitty", "sixel", "blocks")
+    pub fn from_setting(setting: &str) -> Self {
+        match setting.to_lowercase().as_str() {
+            "kitty" => GraphicsProtocol::Kitty,
+            "sixel" => GraphicsProtocol::Sixel,
+            "blocks" => GraphicsProtocol::Blocks,
+            _ => Self::detect(),
+        }
+    }
+
+    /// Guess terminal image support from the environment
+    pub fn detect() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default().to_lowercase();
+        let term = var("TERM");
+        let program = var("TERM_PROGRAM");
+
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || program == "wezterm"
+            || program == "ghostty"
+        {
+            GraphicsProtocol::Kitty
+        } else if term.contains("foot")
+            || term.contains("mlterm")
+            || term.contains("sixel")
+            || program == "iterm.app"
+        {
+            GraphicsProtocol::Sixel
+        } else {
+            GraphicsProtocol::Blocks
+        }
+    }
+}
+
+type DecodeResult = (PathBuf, SystemTime, Option<Thumbnail>);
+
+/// Decodes thumbnails on a small worker pool and caches them by path + mtime
+pub struct ThumbnailLoader {
+    cache: HashMap<PathBuf, (SystemTime, Thumbnail)>,
+    order: VecDeque<PathBuf>,
+    pending: HashSet<PathBuf>,
+    failed: HashMap<PathBuf, SystemTime>,
+    requests: Option<Sender<(PathBuf, SystemTime)>>,
+    results: Option<Receiver<DecodeResult>>,
+}
+
+impl ThumbnailLoader {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+            order: VecDeque::new(),
+            pending: HashSet::new(),
+            failed: HashMap::new(),
+            requests: None,
+            results: None,
+        }
+    }
+
+    /// Workers start on first use and exit when the loader is dropped
+    fn spawn_workers(&mut self) {
+        let (request_tx, request_rx) = channel::<(PathBuf, SystemTime)>();
+        let (result_tx, result_rx) = channel();
+        let request_rx = Arc::new(Mutex::new(request_rx));
+
+        for _ in 0..MAX_DECODE_WORKERS.min(num_cpus::get()).max(1) {
+            let request_rx = Arc::clone(&request_rx);
+            let result_tx = result_tx.clone();
+            thread::spawn(move || loop {
+                let request = request_rx.lock().ok().and_then(|rx| rx.recv().ok());
+                let Some((path, mtime)) = request else {
+                    break;
+                };
+                let thumbnail = Thumbnail::load(&path).ok();
+                if result_tx.send((path, mtime, thumbnail)).is_err() {
+                    break;
+                }
+            });
+        }
+
+        self.requests = Some(request_tx);
+        self.results = Some(result_rx);
+    }
+
+    /// Queue a decode unless an up-to-date thumbnail is cached or in flight
+    pub fn request(&mut self, path: &Path) {
+        if self.pending.contains(path) {
+            return;
+        }
+        let Ok(mtime) = std::fs::metadata(path).and_then(|m| m.modified()) else {
+            return;
+        };
+        if self.cache.get(path).map(|(cached, _)| *cached == mtime).unwrap_or(false)
+            || self.failed.get(path) == Some(&mtime)
+        {
+            return;
+        }
+
+        if self.requests.is_none() {
+            self.spawn_workers();
+        }
+        if let Some(requests) = &self.requests {
+            if requests.send((path.to_path_buf(), mtime)).is_ok() {
+                self.pending.insert(path.to_path_buf());
+            }
+        }
+    }
+
+    /// Collect finished decodes; returns true if any thumbnail changed
+    pub fn poll(&mut self) -> bool {
+        let Some(results) = &self.results else {
+            return false;
+        };
+        let finished: Vec<DecodeResult> = results.try_iter().collect();
+        let changed = !finished.is_empty();
+
+        for (path, mtime, thumbnail) in finished {
+            self.pending.remove(&path);
+            match thumbnail {
+                Some(thumbnail) => self.insert(path, mtime, thumbnail),
+                None => {
+                    self.failed.insert(path, mtime);
+                }
+            }
+        }
+        changed
+    }
+
+    fn insert(&mut self, path: PathBuf, mtime: SystemTime, thumbnail: Thumbnail) {
+        if self.cache.insert(path.clone(), (mtime, thumbnail)).is_none() {
+            self.order.push_back(path);
+        }
+        while self.cache.len() > MAX_CACHED_THUMBNAILS {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.cache.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Thumbnail> {
+        self.cache.get(path).map(|(_, thumbnail)| thumbnail)
+    }
+
+    /// Whether any decode is still in flight
+    pub fn is_busy(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn is_pending(&self, path: &Path) -> bool {
+        self.pending.contains(path)
+    }
+}
+
+impl Default for ThumbnailLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a thumbnail goes on screen, in cells
+pub struct Placement<'a> {
+    pub x: u16,
+    pub y: u16,
+    pub cols: u16,
+    pub rows: u16,
+    pub thumbnail: &'a Thumbnail,
+}
+
+/// Write thumbnails straight to the terminal with the Kitty or Sixel protocol.
+///
+/// `cell_px` is the size of one cell in pixels. Kitty placements from an
+/// earlier call are removed first; Sixel output simply overwrites the cells.
+pub fn write_graphics(
+    out: &mut impl Write,
+    protocol: GraphicsProtocol,
+    placements: &[Placement],
+    cell_px: (u32, u32),
+) -> Result<()> {
+    if protocol == GraphicsProtocol::Kitty {
+        clear_graphics(out, protocol)?;
+    }
+
+    for placement in placements {
+        let max_w = placement.cols as u32 * cell_px.0;
+        let max_h = placement.rows as u32 * cell_px.1;
+        let (w, h) = placement.thumbnail.fit(max_w, max_h);
+        if w == 0 || h == 0 {
+            continue;
+        }
+
+        // Center the image inside its cell area
+        let used_cols = w.div_ceil(cell_px.0) as u16;
+        let used_rows = h.div_ceil(cell_px.1) as u16;
+        let x = placement.x + placement.cols.saturating_sub(used_cols) / 2;
+        let y = placement.y + placement.rows.saturating_sub(used_rows) / 2;
+        write!(out, "\x1b[{};{}H", y + 1, x + 1)?;
+
+        match protocol {
+            GraphicsProtocol::Kitty => {
+                write_kitty(out, placement.thumbnail, used_cols, used_rows)?
+            }
+            GraphicsProtocol::Sixel => write_sixel(out, placement.thumbnail, w, h)?,
+            GraphicsProtocol::Blocks => {}
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// Remove images drawn by `write_graphics`
+pub fn clear_graphics(out: &mut impl Write, protocol: GraphicsProtocol) -> Result<()> {
+    if protocol == GraphicsProtocol::Kitty {
+        write!(out, "\x1b_Ga=d,d=A,q=2\x1b\\")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+fn write_kitty(out: &mut impl Write, thumbnail: &Thumbnail, cols: u16, rows: u16) -> Result<()> {
+    let raw: Vec<u8> = thumbnail.pixels.iter().flatten().copied().collect();
+    let encoded = base64::engine::general_purpose::STANDARD.encode(raw);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(4096).collect();
+
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let more = if idx + 1 < chunks.len() { 1 } else { 0 };
+        if idx == 0 {
+            write!(
+                out,
+                "\x1b_Ga=T,f=24,s={},v={},c={},r={},C=1,q=2,m={};",
+                thumbnail.width, thumbnail.height, cols, rows, more
+            )?;
+        } else {
+            write!(out, "\x1b_Gm={};", more)?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+    Ok(())
+}
+
+/// Encode with a fixed 6x6x6 color cube, one sixel band (6 pixel rows) at a time
+fn write_sixel(out: &mut impl Write, thumbnail: &Thumbnail, w: u32, h: u32) -> Result<()> {
+    let level = |c: u8| (c as u32 * 6 / 256) as usize;
+    let indices: Vec<usize> = (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let [r, g, b] = thumbnail.sample((x as f32 + 0.5) / w as f32, (y as f32 + 0.5) / h as f32);
+            level(r) * 36 + level(g) * 6 + level(b)
+        })
+        .collect();
+
+    let mut sixel = format!("\x1bPq\"1;1;{};{}", w, h);
+    let mut used: Vec<usize> = indices.clone();
+    used.sort_unstable();
+    used.dedup();
+    for &color in &used {
+        let pct = |v: usize| v * 100 / 5;
+        sixel.push_str(&format!(
+            "#{};2;{};{};{}",
+            color,
+            pct(color / 36),
+            pct(color / 6 % 6),
+            pct(color % 6)
+        ));
+    }
+
+    for band in (0..h).step_by(6) {
+        let band_rows = (h - band).min(6);
+        let mut band_colors: Vec<usize> = (0..band_rows)
+            .flat_map(|dy| {
+                let start = ((band + dy) * w) as usize;
+                indices[start..start + w as usize].iter().copied()
+            })
+            .collect();
+        band_colors.sort_unstable();
+        band_colors.dedup();
+
+        for color in band_colors {
+            sixel.push_str(&format!("#{}", color));
+            let mut run_char = None;
+            let mut run_len = 0;
+            for x in 0..w {
+                let bits = (0..band_rows)
+                    .filter(|dy| indices[((band + dy) * w + x) as usize] == color)
+                    .fold(0u8, |acc, dy| acc | (1 << dy));
+                let ch = (63 + bits) as char;
+                if Some(ch) == run_char {
+                    run_len += 1;
+                } else {
+                    push_sixel_run(&mut sixel, run_char, run_len);
+                    run_char = Some(ch);
+                    run_len = 1;
+                }
+            }
+            push_sixel_run(&mut sixel, run_char, run_len);
+            sixel.push('$');
+        }
+        sixel.push('-');
+    }
+    sixel.push_str("\x1b\\");
+
+    out.write_all(sixel.as_bytes())?;
+    Ok(())
+}
+
+fn push_sixel_run(sixel: &mut String, ch: Option<char>, len: usize) {
+    match ch {
+        Some(ch) if len > 3 => sixel.push_str(&format!("!{}{}", len, ch)),
+        Some(ch) => (0..len).for_each(|_| sixel.push(ch)),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn checkerboard() -> Thumbnail {
+        Thumbnail {
+            width: 4,
+            height: 2,
+            pixels: vec![
+                [255, 0, 0], [0, 0, 255], [255, 0, 0], [0, 0, 255],
+                [0, 255, 0], [0, 255, 0], [0, 255, 0], [0, 255, 0],
+            ],
+        }
+    }
+
+    #[test]
+    fn test_block_lines_keep_aspect_ratio() {
+        let lines = checkerboard().to_block_lines(8, 4);
+        assert_eq!(lines.len(), 4);
+        assert!(lines.iter().all(|line| line.spans.len() == 8));
+
+        // A 2:1 image in an 8x8 pixel grid fills rows 2..6, i.e. cell rows 1 and 2
+        assert_eq!(lines[0].spans[0].content, " ");
+        assert_eq!(lines[1].spans[0].content, "▀");
+        assert_eq!(lines[1].spans[0].style.fg, Some(Color::Rgb(255, 0, 0)));
+    }
+
+    #[test]
+    fn test_loader_decodes_and_caches() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("red.png");
+        image::RgbImage::from_pixel(300, 150, image::Rgb([200, 10, 10])).save(&path)?;
+
+        let mut loader = ThumbnailLoader::new();
+        loader.request(&path);
+        assert!(loader.is_pending(&path));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while loader.get(&path).is_none() && Instant::now() < deadline {
+            loader.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let thumbnail = loader.get(&path).expect("thumbnail decoded");
+        assert_eq!((thumbnail.width, thumbnail.height), (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2));
+
+        // Unchanged file: served from the cache without a new decode
+        loader.request(&path);
+        assert!(!loader.is_pending(&path));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sixel_output_is_framed() -> Result<()> {
+        let mut out = Vec::new();
+        write_sixel(&mut out, &checkerboard(), 4, 2)?;
+        let sixel = String::from_utf8(out)?;
+        assert!(sixel.starts_with("\x1bPq\"1;1;4;2"));
+        assert!(sixel.ends_with("\x1b\\"));
+        Ok(())
+    }
+}
@@ -0,0 +1,432 @@
+// Trash - reversible deletes using the freedesktop.org trash layout
+use crate::fileops::FileOperation;
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDateTime, Timelike};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file or directory that was moved to the trash
+#[derive(Clone, Debug)]
+pub struct TrashedItem {
+    /// Where the item lived before it was trashed
+    pub original: PathBuf,
+    /// Where the item lives inside the trash's `files/` directory
+    pub trashed: PathBuf,
+    /// The matching `.trashinfo` file
+    pub info: PathBuf,
+    /// When the item was trashed, if the record says
+    pub deleted: Option<NaiveDateTime>,
+}
+
+impl TrashedItem {
+    /// Name shown to the user, taken from the original path
+    pub fn name(&self) -> String {
+        self.original
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+/// When the trash purges its oldest items on its own; 0 turns a limit off
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrashLimits {
+    /// Most bytes the trash may hold
+    pub max_bytes: u64,
+    /// Days an item is kept after it was trashed
+    pub max_age_days: u64,
+}
+
+impl TrashLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_bytes == 0 && self.max_age_days == 0
+    }
+}
+
+/// What a prune removed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Pruned {
+    pub items: usize,
+    pub bytes: u64,
+}
+
+/// A trash directory with `files/` and `info/` subdirectories.
+///
+/// On Linux this is the desktop trash (`~/.local/share/Trash`), so items
+/// can also be restored from a file manager; elsewhere it is private to
+/// AstroFS.
+pub struct Trash {
+    root: PathBuf,
+}
+
+impl Trash {
+    /// The user's trash directory, as described above
+    pub fn default_root() -> Result<PathBuf> {
+        let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Cannot find data directory"))?;
+        Ok(if cfg!(target_os = "linux") {
+            data_dir.join("Trash")
+        } else {
+            data_dir.join("astrofs").join("Trash")
+        })
+    }
+
+    /// Use the user's trash directory
+    #[deprecated(note = "use with_root with AppDirs::trash_dir")]
+    #[allow(dead_code)]
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_root(Self::default_root()?))
+    }
+
+    /// Use a trash directory at a custom location
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The trash directory itself
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn files_dir(&self) -> PathBuf {
+        self.root.join("files")
+    }
+
+    fn info_dir(&self) -> PathBuf {
+        self.root.join("info")
+    }
+
+    /// Move a file or directory into the trash
+    pub fn move_to_trash(&self, path: &Path) -> Result<TrashedItem> {
+        if fs::symlink_metadata(path).is_err() {
+            return Err(anyhow!("Path does not exist: {:?}", path));
+        }
+        // Resolve the parent only, so a symlink is trashed rather than its target
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| anyhow!("Cannot trash {:?}", path))?;
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => fs::canonicalize(parent)?,
+            _ => std::env::current_dir()?,
+        };
+        let original = parent.join(file_name);
+        let name = file_name.to_string_lossy().to_string();
+
+        fs::create_dir_all(self.files_dir())?;
+        fs::create_dir_all(self.info_dir())?;
+
+        let trashed_name = self.unique_name(&name);
+        let trashed = self.files_dir().join(&trashed_name);
+        let info = self.info_dir().join(format!("{}.trashinfo", trashed_name));
+
+        let deleted = chrono::Local::now().naive_local();
+        let info_content = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(&original),
+            deleted.format(DATE_FORMAT)
+        );
+        fs::write(&info, info_content)?;
+
+        if let Err(e) = move_across(&original, &trashed) {
+            let _ = fs::remove_file(&info);
+            return Err(e);
+        }
+
+        Ok(TrashedItem {
+            original,
+            trashed,
+            info,
+            deleted: Some(deleted.with_nanosecond(0).unwrap_or(deleted)),
+        })
+    }
+
+    /// Everything currently in the trash, most recently deleted first.
+    ///
+    /// Items are read from their `.trashinfo` records; records whose file
+    /// has gone missing are skipped.
+    pub fn list(&self) -> Result<Vec<TrashedItem>> {
+        let read_dir = match fs::read_dir(self.info_dir()) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut items: Vec<TrashedItem> = read_dir
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| self.read_info(&entry.path()))
+            .filter(|item| fs::symlink_metadata(&item.trashed).is_ok())
+            .collect();
+        items.sort_by(|a, b| b.deleted.cmp(&a.deleted).then_with(|| a.original.cmp(&b.original)));
+        Ok(items)
+    }
+
+    /// Parse one `.trashinfo` record
+    fn read_info(&self, info: &Path) -> Option<TrashedItem> {
+        let trashed_name = info.file_name()?.to_str()?.strip_suffix(".trashinfo")?;
+        let content = fs::read_to_string(info).ok()?;
+
+        let mut original = None;
+        let mut deleted = None;
+        for line in content.lines() {
+            if let Some(value) = line.strip_prefix("Path=") {
+                original = Some(PathBuf::from(decode_path(value)));
+            } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+                deleted = NaiveDateTime::parse_from_str(value.trim(), DATE_FORMAT).ok();
+            }
+        }
+
+        Some(TrashedItem {
+            original: original?,
+            trashed: self.files_dir().join(trashed_name),
+            info: info.to_path_buf(),
+            deleted,
+        })
+    }
+
+    /// Put a trashed item back where it came from, recreating its parent
+    /// directory if that has since been removed
+    pub fn restore(&self, item: &TrashedItem) -> Result<()> {
+        if fs::symlink_metadata(&item.original).is_ok() {
+            return Err(anyhow!("{:?} already exists", item.original));
+        }
+        if fs::symlink_metadata(&item.trashed).is_err() {
+            return Err(anyhow!("{} is no longer in the trash", item.name()));
+        }
+        if let Some(parent) = item.original.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        move_across(&item.trashed, &item.original)?;
+        let _ = fs::remove_file(&item.info);
+        Ok(())
+    }
+
+    /// Remove a trashed item for good
+    pub fn delete_permanently(&self, item: &TrashedItem) -> Result<()> {
+        if fs::symlink_metadata(&item.trashed).is_ok() {
+            FileOperation::delete(&item.trashed)?;
+        }
+        if item.info.exists() {
+            fs::remove_file(&item.info)?;
+        }
+        Ok(())
+    }
+
+    /// Permanently delete everything in the trash, returning how many items went
+    pub fn empty(&self) -> Result<usize> {
+        let items = self.list()?;
+        for item in &items {
+            self.delete_permanently(item)?;
+        }
+        Ok(items.len())
+    }
+
+    /// Bytes taken by everything in the trash
+    pub fn size(&self) -> Result<u64> {
+        Ok(self.list()?.iter().map(|item| disk_size(&item.trashed)).sum())
+    }
+
+    /// Permanently delete items older than `limits.max_age_days`, then the
+    /// oldest of the rest until the trash fits in `limits.max_bytes`.
+    /// Items without a deletion date count as the oldest.
+    pub fn prune(&self, limits: &TrashLimits, now: NaiveDateTime) -> Result<Pruned> {
+        let mut pruned = Pruned::default();
+        if limits.is_unlimited() {
+            return Ok(pruned);
+        }
+        let cutoff = (limits.max_age_days > 0).then(|| now - chrono::Duration::days(limits.max_age_days as i64));
+
+        // Newest first, so whatever no longer fits is the oldest
+        let (mut kept, mut full) = (0u64, false);
+        for item in self.list()? {
+            let size = disk_size(&item.trashed);
+            let expired = cutoff.is_some_and(|cutoff| item.deleted.is_some_and(|deleted| deleted < cutoff));
+            full = full || (limits.max_bytes > 0 && kept + size > limits.max_bytes);
+            if expired || full {
+                self.delete_permanently(&item)?;
+                pruned.items += 1;
+                pruned.bytes += size;
+            } else {
+                kept += size;
+            }
+        }
+        Ok(pruned)
+    }
+
+    /// A name not yet used in `files/` or `info/`: "a.txt", "a.2.txt", ...
+    fn unique_name(&self, name: &str) -> String {
+        let taken = |candidate: &str| {
+            self.files_dir().join(candidate).exists()
+                || self.info_dir().join(format!("{}.trashinfo", candidate)).exists()
+        };
+        if !taken(name) {
+            return name.to_string();
+        }
+
+        let (stem, ext) = match name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+            _ => (name, String::new()),
+        };
+        (2..)
+            .map(|n| format!("{}.{}{}", stem, n, ext))
+            .find(|candidate| !taken(candidate))
+            .unwrap_or_default()
+    }
+}
+
+/// Rename, falling back to copy + delete when crossing filesystems
+fn move_across(src: &Path, dest: &Path) -> Result<()> {
+    if fs::rename(src, dest).is_ok() {
+        return Ok(());
+    }
+    FileOperation::copy(src, dest, false)?;
+    FileOperation::delete(src)
+}
+
+/// Bytes in the files at or below `path`, without following links
+fn disk_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| !m.is_dir())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Format of the `DeletionDate=` key, in local time
+const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Percent-encode a path for the `Path=` key of a .trashinfo file
+fn encode_path(path: &Path) -> String {
+    let mut encoded = String::new();
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Undo `encode_path`, leaving malformed escapes as they are
+fn decode_path(encoded: &str) -> String {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| encoded.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_trash_and_restore() -> Result<()> {
+        let dir = tempdir()?;
+        let trash = Trash::with_root(dir.path().join("Trash"));
+
+        let file = dir.path().join("my notes.txt");
+        fs::write(&file, "hello")?;
+        let item = trash.move_to_trash(&file)?;
+        assert!(!file.exists());
+        assert!(item.trashed.exists());
+        let info = fs::read_to_string(&item.info)?;
+        assert!(info.contains("my%20notes.txt"));
+
+        // A second file with the same name gets its own slot
+        fs::write(&file, "again")?;
+        let second = trash.move_to_trash(&file)?;
+        assert_ne!(second.trashed, item.trashed);
+
+        trash.restore(&second)?;
+        assert_eq!(fs::read_to_string(&file)?, "again");
+        assert!(trash.restore(&item).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_restore_and_empty() -> Result<()> {
+        let dir = tempdir()?;
+        let trash = Trash::with_root(dir.path().join("Trash"));
+        assert!(trash.list()?.is_empty());
+
+        let folder = dir.path().join("old project");
+        fs::create_dir(&folder)?;
+        fs::write(folder.join("draft 1.txt"), "draft")?;
+        fs::write(dir.path().join("b.txt"), "b")?;
+        let draft = trash.move_to_trash(&folder.join("draft 1.txt"))?;
+        trash.move_to_trash(&dir.path().join("b.txt"))?;
+
+        let listed = trash.list()?;
+        assert_eq!(listed.len(), 2);
+        let found = listed.iter().find(|i| i.trashed == draft.trashed).unwrap();
+        assert_eq!(found.original, draft.original);
+        assert_eq!(found.deleted, draft.deleted);
+
+        // The parent folder is recreated when it no longer exists
+        fs::remove_dir(&folder)?;
+        trash.restore(found)?;
+        assert_eq!(fs::read_to_string(folder.join("draft 1.txt"))?, "draft");
+
+        assert_eq!(trash.empty()?, 1);
+        assert!(trash.list()?.is_empty());
+        assert!(!dir.path().join("b.txt").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_drops_old_and_oversized_items() -> Result<()> {
+        let dir = tempdir()?;
+        let trash = Trash::with_root(dir.path().join("Trash"));
+        let now = chrono::Local::now().naive_local();
+        let trash_file = |name: &str, bytes: usize, days_ago: i64| -> Result<TrashedItem> {
+            let path = dir.path().join(name);
+            fs::write(&path, vec![0u8; bytes])?;
+            let item = trash.move_to_trash(&path)?;
+            let deleted = now - chrono::Duration::days(days_ago);
+            fs::write(
+                &item.info,
+                format!("[Trash Info]\nPath={}\nDeletionDate={}\n", encode_path(&item.original), deleted.format(DATE_FORMAT)),
+            )?;
+            Ok(item)
+        };
+        let ancient = trash_file("ancient.log", 10, 90)?;
+        let old = trash_file("old.bin", 300, 5)?;
+        let recent = trash_file("recent.txt", 200, 1)?;
+        let today = trash_file("today.txt", 100, 0)?;
+        assert_eq!(trash.size()?, 610);
+
+        assert_eq!(trash.prune(&TrashLimits::default(), now)?, Pruned::default());
+        let pruned = trash.prune(&TrashLimits { max_bytes: 0, max_age_days: 30 }, now)?;
+        assert_eq!(pruned, Pruned { items: 1, bytes: 10 });
+        assert!(!ancient.trashed.exists());
+
+        // 100 + 200 fit in 400 bytes; the older 300 would not
+        let pruned = trash.prune(&TrashLimits { max_bytes: 400, max_age_days: 30 }, now)?;
+        assert_eq!(pruned, Pruned { items: 1, bytes: 300 });
+        assert!(!old.trashed.exists());
+        assert!(recent.trashed.exists() && today.trashed.exists());
+        assert_eq!(trash.size()?, 300);
+        Ok(())
+    }
+}
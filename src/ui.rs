@@ -1,15 +1,25 @@
-use crate::app::{App, AppMode, InputMode};
-use crate::theme::{get_file_emoji, get_file_style, Theme};
+use crate::app::{App, AppMode, InputMode, Place, SearchRow};
+use crate::media_preview::{detect_media_type, MediaType};
+use crate::fileops::{SequenceOrder, TransferKind};
+use crate::jobs::JobState;
+use crate::git::FileStatus;
+use crate::layout::pane_widths;
+use crate::path_display::{format_age, truncate_middle};
+use crate::search::{DriveSearchEnd, ResultAction, SearchGrouping, SearchScope};
+use crate::theme::{age_shaded, entry_icon, file_icon, get_file_style, Theme};
+use crate::thumbnails::GraphicsProtocol;
+use crate::workspace::EmptyListing;
+use chrono::TimeZone;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
 pub fn draw(f: &mut Frame, app: &mut App) {
-    let theme = Theme::default();
+    let theme = app.theme.clone();
     let size = f.size();
     app.set_viewport(size.width as usize, size.height as usize);
 
@@ -25,15 +35,147 @@ pub fn draw(f: &mut Frame, app: &mut App) {
     match app.mode {
         AppMode::Help => draw_help(f, app, chunks[0], &theme),
         AppMode::CommandPalette => draw_command_palette(f, app, size, &theme),
+        AppMode::ThemeGallery => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_theme_gallery(f, app, size, &theme);
+        }
+        AppMode::RecentFiles => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_recent_files(f, app, size, &theme);
+        }
+        AppMode::PluginManager => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_plugin_manager(f, app, size, &theme);
+        }
+        AppMode::Trash => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_trash(f, app, size, &theme);
+        }
+        AppMode::DiskUsage => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_disk_usage(f, app, size, &theme);
+        }
+        AppMode::Places => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_places(f, app, size, &theme);
+        }
+        AppMode::Jobs => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_jobs(f, app, size, &theme);
+        }
+        AppMode::Paths => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_paths(f, app, size, &theme);
+        }
+        AppMode::Diff => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_diff(f, app, chunks[0], &theme);
+        }
+        AppMode::Edit => {
+            if let Some(editor) = app.editor.as_mut() {
+                editor.scroll_to_cursor(chunks[0].height.saturating_sub(2) as usize);
+            }
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_editor(f, app, chunks[0], &theme);
+        }
+        AppMode::QuickLook => {
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_quick_look(f, app, chunks[0], &theme);
+            match app.quick_look.as_ref().filter(|look| look.searching) {
+                Some(look) => f.render_widget(
+                    Paragraph::new(format!("Find: {}_", look.query)).style(theme.normal),
+                    chunks[2],
+                ),
+                None => draw_status_bar(f, app, chunks[2], &theme),
+            }
+        }
+        AppMode::Settings => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_settings(f, app, size, &theme);
+        }
+        AppMode::ConfirmQuit => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_quit_confirmation(f, app, size, &theme);
+        }
+        AppMode::ConfirmOverwrite => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_overwrite_confirmation(f, app, size, &theme);
+        }
+        AppMode::ConfirmRestore => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_restore_confirmation(f, app, size, &theme);
+        }
+        AppMode::ConfirmCollision => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_collision_dialog(f, app, size, &theme);
+        }
+        AppMode::ConfirmPermissions => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_permission_confirmation(f, app, size, &theme);
+        }
+        AppMode::ConfirmSequenceRename => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_sequence_confirmation(f, app, size, &theme);
+        }
+        AppMode::ConfirmFlatten => {
+            draw_file_browser(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_status_bar(f, app, chunks[2], &theme);
+            draw_flatten_confirmation(f, app, size, &theme);
+        }
+        AppMode::Input(InputMode::CollectResults) => {
+            draw_search_results(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_input_dialog(f, app, chunks[2], &theme);
+        }
         AppMode::Input(_) => {
             draw_file_browser(f, app, chunks[0], &theme);
             draw_workspace_tabs(f, app, chunks[1], &theme);
             draw_input_dialog(f, app, chunks[2], &theme);
         }
         AppMode::Search => {
-            draw_file_browser(f, app, chunks[0], &theme);
+            draw_search_results(f, app, chunks[0], &theme);
+            draw_workspace_tabs(f, app, chunks[1], &theme);
+            draw_search_status(f, app, chunks[2], &theme);
+        }
+        AppMode::SearchActions => {
+            draw_search_results(f, app, chunks[0], &theme);
             draw_workspace_tabs(f, app, chunks[1], &theme);
             draw_search_status(f, app, chunks[2], &theme);
+            draw_search_actions(f, app, size, &theme);
         }
         _ => {
             draw_file_browser(f, app, chunks[0], &theme);
@@ -41,66 +183,353 @@ pub fn draw(f: &mut Frame, app: &mut App) {
             draw_status_bar(f, app, chunks[2], &theme);
         }
     }
+
+    // The undo toast floats above whatever is on screen without taking input
+    if !matches!(app.mode, AppMode::Help | AppMode::CommandPalette) {
+        draw_undo_toast(f, app, chunks[0], &theme);
+    }
+}
+
+fn draw_undo_toast(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let pending = match &app.pending_undo {
+        Some(pending) => pending,
+        None => return,
+    };
+
+    let remaining = pending
+        .expires
+        .saturating_duration_since(std::time::Instant::now())
+        .as_secs()
+        + 1;
+    let text = format!(
+        " 🗑 Deleted {} — press U to undo ({}s) ",
+        pending.item.name(),
+        remaining
+    );
+
+    let width = (unicode_width::UnicodeWidthStr::width(text.as_str()) as u16 + 2).min(area.width);
+    let height = 3.min(area.height);
+    let toast_area = Rect {
+        x: area.x + area.width - width,
+        y: area.y + area.height - height,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    f.render_widget(Clear, toast_area);
+    f.render_widget(
+        Paragraph::new(Span::styled(text, theme.help)).block(block),
+        toast_area,
+    );
 }
 
-fn draw_file_browser(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+fn draw_file_browser(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
+    app.gallery_placements.clear();
+    // The sidebar takes its columns from the file list
+    let sidebar = app.user_settings.show_sidebar.then_some(app.config.sidebar_width);
+    let (sidebar, list, preview) = pane_widths(area.width, sidebar, app.config.preview_width_ratio);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(sidebar), Constraint::Min(0)])
+        .split(area);
+    if sidebar > 0 {
+        draw_sidebar(f, app, chunks[0], theme);
+    }
+    let area = chunks[1];
+    if app.gallery_view {
+        draw_gallery(f, app, area, theme);
+        return;
+    }
+
     let workspace = app.get_current_workspace();
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .constraints([Constraint::Length(list), Constraint::Length(preview)])
         .split(area);
 
     draw_file_list(f, app, workspace, chunks[0], theme);
     draw_preview_pane(f, app, workspace, chunks[1], theme);
 }
 
-fn draw_file_list(f: &mut Frame, _app: &App, workspace: &crate::workspace::Workspace, area: Rect, theme: &Theme) {
-    let current_dir_name = workspace
-        .current_dir
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("Root");
+fn draw_sidebar(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let current_dir = &app.get_current_workspace().current_dir;
+    let block = Block::default()
+        .title(" Go ")
+        .borders(Borders::ALL)
+        .style(theme.border);
+
+    let mut items = Vec::new();
+    let mut section = None;
+    for item in app.sidebar_items() {
+        if section != Some(item.section) {
+            section = Some(item.section);
+            items.push(ListItem::new(item.section.title()).style(theme.hidden.add_modifier(Modifier::BOLD)));
+        }
+        let key = item.key.map(|k| k.to_string()).unwrap_or_else(|| " ".to_string());
+        let style = if item.path == *current_dir {
+            theme.sidebar.add_modifier(Modifier::REVERSED)
+        } else {
+            theme.sidebar
+        };
+        items.push(ListItem::new(format!("{} {}", key, item.label)).style(style));
+    }
+    f.render_widget(List::new(items).block(block), area);
+}
+
+fn draw_file_list(f: &mut Frame, app: &App, workspace: &crate::workspace::Workspace, area: Rect, theme: &Theme) {
+    let order_note = if workspace.archive.is_some() {
+        " [archive, read-only]".to_string()
+    } else if app.has_custom_order() {
+        " ↕".to_string()
+    } else {
+        match app.sort_mode() {
+            crate::files::SortMode::Name => String::new(),
+            sort => format!(" [{}]", sort.as_str()),
+        }
+    };
+    let current_dir_name = app.display_path(&workspace.current_dir, area.width.saturating_sub(6) as usize);
 
     let block = Block::default()
-        .title(format!(" {} ", current_dir_name))
+        .title(format!(" {}{} ", current_dir_name, order_note))
         .borders(Borders::ALL)
         .style(theme.border);
 
     let inner = block.inner(area);
     f.render_widget(block, area);
 
+    if let Some(empty) = workspace.empty.as_ref().filter(|_| workspace.entries.is_empty()) {
+        let style = if matches!(empty, EmptyListing::Failed(_)) { theme.error } else { theme.hidden };
+        let placeholder = Paragraph::new(empty.placeholder()).style(style).wrap(Wrap { trim: true });
+        f.render_widget(placeholder, inner);
+        return;
+    }
+
+    let dim_hidden = app.hidden_marker().dims();
+    let dates: Vec<String> = if app.config.date_column {
+        workspace.entries.iter().map(|e| e.modified.map(|m| app.format_time(m)).unwrap_or_default()).collect()
+    } else {
+        Vec::new()
+    };
+    let date_width = dates.iter().map(|d| unicode_width::UnicodeWidthStr::width(d.as_str())).max().unwrap_or(0);
+    let columns = crate::layout::list_columns(
+        inner.width,
+        app.config.git_status_column,
+        app.config.size_bar_column,
+        date_width as u16,
+    );
+    let (name_width, git_width) = (columns.name, columns.git);
+    // Bars are scaled to the largest file listed; folders get none
+    let max_size = workspace.entries.iter().filter(|e| !e.is_dir).map(|e| e.size).max().unwrap_or(0);
+    let line_numbers = app.line_numbers();
+    let gutter = crate::path_display::gutter_width(workspace.entries.len());
+    let now = std::time::SystemTime::now();
     let mut items = Vec::new();
     for (idx, entry) in workspace.entries.iter().enumerate() {
         let style = if idx == workspace.selected_index {
             theme.selected
-        } else if entry.is_hidden {
+        } else if entry.is_hidden && dim_hidden {
             theme.hidden
+        } else if app.config.age_shading {
+            age_shaded(get_file_style(&entry.path, entry.is_dir, theme), entry.modified, now)
         } else {
             get_file_style(&entry.path, entry.is_dir, theme)
         };
 
-        let emoji = get_file_emoji(&entry.path, entry.is_dir);
-        let name = if entry.is_hidden {
-            format!("·{}", entry.name)
-        } else {
-            entry.name.clone()
-        };
+        let emoji = entry_icon(entry, theme.icons);
+        let mark = if workspace.is_marked(&entry.path) { "✓ " } else { "" };
+        let number = line_numbers
+            .number(idx, workspace.selected_index)
+            .map(|n| format!("{:>width$} ", n, width = gutter))
+            .unwrap_or_default();
+        let prefix = format!("{}{}{} ", number, mark, emoji);
+        let suffix = entry.item_count_label().map(|count| format!(" ({})", count)).unwrap_or_default();
+
+        // Long names lose their middle, keeping the extension in view
+        let room = (name_width as usize)
+            .saturating_sub(unicode_width::UnicodeWidthStr::width(prefix.as_str()) + suffix.len());
+        let name = app.entry_label(&entry.name, entry.is_dir, entry.is_hidden);
+        let display = format!("{}{}{}", prefix, truncate_middle(&name, room.max(8)), suffix);
+        if git_width == 0 && columns.size == 0 && columns.date == 0 {
+            items.push(ListItem::new(display).style(style));
+            continue;
+        }
+
+        let mut row = vec![Span::styled(fit_width(&display, name_width as usize), style)];
+        if columns.size > 0 {
+            let (label, filled) = if entry.is_dir {
+                (String::new(), 0)
+            } else {
+                (entry.size_formatted(), crate::layout::size_bar_cells(entry.size, max_size, crate::layout::SIZE_BAR_WIDTH))
+            };
+            let bar_style = if idx == workspace.selected_index { theme.selected } else { theme.hidden };
+            row.push(Span::styled(format!(" {:>9} ", fit_width(&label, 9).trim_end()), style));
+            row.push(Span::styled("█".repeat(filled as usize), style));
+            row.push(Span::styled(
+                "░".repeat((crate::layout::SIZE_BAR_WIDTH - filled) as usize),
+                bar_style,
+            ));
+        }
+        if columns.date > 0 {
+            let date = dates.get(idx).map(String::as_str).unwrap_or("");
+            row.push(Span::styled(format!(" {}", fit_width(date, columns.date as usize - 1)), style));
+        }
+        if git_width == 0 {
+            items.push(ListItem::new(Line::from(row)));
+            continue;
+        }
 
-        let display = format!("{} {}", emoji, name);
-        items.push(ListItem::new(display).style(style));
+        let status = app.git_status(&entry.path);
+        let marker = status.map_or("", |s| s.marker());
+        let marker_style = match status {
+            _ if idx == workspace.selected_index => theme.selected,
+            Some(FileStatus::Staged) => Style::default().fg(Color::Green),
+            Some(FileStatus::Modified) => Style::default().fg(Color::Yellow),
+            Some(FileStatus::Conflicted) => theme.error,
+            Some(FileStatus::Untracked) | None => theme.hidden,
+        };
+        row.push(Span::styled(format!("{:>width$}", marker, width = git_width as usize), marker_style));
+        items.push(ListItem::new(Line::from(row)));
     }
 
     let list = List::new(items);
     f.render_widget(list, inner);
 }
 
+/// `text` cut with `…` or padded with spaces to exactly `width` columns
+fn fit_width(text: &str, width: usize) -> String {
+    use unicode_width::UnicodeWidthChar;
+
+    let mut fitted = String::new();
+    let mut used = 0;
+    let total = unicode_width::UnicodeWidthStr::width(text);
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        let room = if total > width { width.saturating_sub(1) } else { width };
+        if used + w > room {
+            fitted.push('…');
+            used += 1;
+            break;
+        }
+        fitted.push(c);
+        used += w;
+    }
+    fitted.extend(std::iter::repeat_n(' ', width.saturating_sub(used)));
+    fitted
+}
+
+fn draw_gallery(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
+    const TILE_WIDTH: u16 = 18;
+    const TILE_HEIGHT: u16 = 10;
+
+    let workspace = app.workspace_manager.active_workspace();
+    let current_dir_name = app.display_path(&workspace.current_dir, area.width.saturating_sub(8) as usize);
+
+    let block = Block::default()
+        .title(format!(" 🖼️  {} ", current_dir_name))
+        .borders(Borders::ALL)
+        .style(theme.border);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let columns = (inner.width / TILE_WIDTH).max(1) as usize;
+    let rows = (inner.height / TILE_HEIGHT).max(1) as usize;
+    app.gallery_columns = columns;
+
+    // Keep the selected tile's row on screen
+    let selected = workspace.selected_index;
+    let first_row = (selected / columns).saturating_sub(rows - 1);
+    let start = first_row * columns;
+    let visible: Vec<crate::files::FileEntry> = workspace
+        .entries
+        .iter()
+        .skip(start)
+        .take(columns * rows)
+        .cloned()
+        .collect();
+    let now = std::time::SystemTime::now();
+
+    for (slot, entry) in visible.iter().enumerate() {
+        let x = inner.x + (slot % columns) as u16 * TILE_WIDTH;
+        let y = inner.y + (slot / columns) as u16 * TILE_HEIGHT;
+        let tile = Rect {
+            x,
+            y,
+            width: TILE_WIDTH.min(inner.right().saturating_sub(x)),
+            height: TILE_HEIGHT.min(inner.bottom().saturating_sub(y)),
+        };
+
+        let is_selected = start + slot == selected;
+        let label = app.entry_label(&entry.name, entry.is_dir, entry.is_hidden);
+        let name: String = label.chars().take(TILE_WIDTH as usize - 2).collect();
+        let tile_block = Block::default()
+            .title(name)
+            .borders(Borders::ALL)
+            .border_style(if is_selected { theme.selected } else { theme.border })
+            .title_style(if is_selected { theme.selected } else { theme.normal });
+        let body = tile_block.inner(tile);
+        f.render_widget(tile_block, tile);
+
+        let is_image = !entry.is_dir
+            && matches!(detect_media_type(&entry.path), MediaType::Image);
+        if is_image {
+            app.thumbnails.request(&entry.path);
+            if let Some(thumbnail) = app.thumbnails.get(&entry.path) {
+                if app.graphics_protocol == GraphicsProtocol::Blocks {
+                    let lines = thumbnail.to_block_lines(body.width, body.height);
+                    f.render_widget(Paragraph::new(lines), body);
+                } else {
+                    app.gallery_placements.push((entry.path.clone(), body));
+                }
+                continue;
+            }
+        }
+
+        let icon = if is_image && app.thumbnails.is_pending(&entry.path) {
+            "⏳"
+        } else {
+            entry_icon(entry, theme.icons)
+        };
+        let style = if entry.is_hidden && app.hidden_marker().dims() {
+            theme.hidden
+        } else if app.config.age_shading {
+            age_shaded(get_file_style(&entry.path, entry.is_dir, theme), entry.modified, now)
+        } else {
+            get_file_style(&entry.path, entry.is_dir, theme)
+        };
+        let icon_area = Rect {
+            y: body.y + body.height / 2,
+            height: body.height.min(1),
+            ..body
+        };
+        f.render_widget(
+            Paragraph::new(icon).alignment(Alignment::Center).style(style),
+            icon_area,
+        );
+    }
+}
+
 fn draw_preview_pane(
     f: &mut Frame,
-    _app: &App,
+    app: &App,
     workspace: &crate::workspace::Workspace,
     area: Rect,
     theme: &Theme,
 ) {
+    let area = match workspace.get_selected_entry() {
+        Some(entry) if !entry.is_dir => draw_hash_info(f, app, &entry.path, area, theme),
+        _ => area,
+    };
+
+    if let Some(view) = app.structured_view.as_ref() {
+        draw_structured_view(f, view, area, theme);
+        return;
+    }
+
     let block = Block::default()
         .title(" Preview ")
         .borders(Borders::ALL)
@@ -117,8 +546,86 @@ fn draw_preview_pane(
     f.render_widget(paragraph, inner);
 }
 
+/// A structured preview with a cursor for folding, scrolled to keep the cursor visible
+fn draw_structured_view(f: &mut Frame, view: &crate::app::StructuredView, area: Rect, theme: &Theme) {
+    let block = Block::default()
+        .title(format!(
+            " {} {} (Enter: fold, E/C: all, ESC: done) ",
+            view.doc.format.label(),
+            view.path.file_name().unwrap_or_default().to_string_lossy()
+        ))
+        .borders(Borders::ALL)
+        .style(theme.border);
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = view.doc.rows();
+    let height = inner.height as usize;
+    let top = (view.cursor + 1).saturating_sub(height);
+    let lines: Vec<Line> = rows
+        .iter()
+        .enumerate()
+        .skip(top)
+        .take(height)
+        .map(|(index, row)| {
+            let style = if index == view.cursor {
+                theme.selected
+            } else if row.foldable {
+                theme.folder
+            } else {
+                theme.normal
+            };
+            Line::from(Span::styled(row.text.clone(), style))
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Checksums (or hashing progress) under the preview; returns the area left for the preview
+fn draw_hash_info(f: &mut Frame, app: &App, path: &std::path::Path, area: Rect, theme: &Theme) -> Rect {
+    let lines: Vec<Line> = if let Some(hashes) = app.cached_hashes(path) {
+        hashes
+            .iter()
+            .map(|(algo, hex)| {
+                Line::from(vec![
+                    Span::styled(format!("{:<8}", algo.label()), theme.folder),
+                    Span::styled(hex.clone(), theme.normal),
+                ])
+            })
+            .collect()
+    } else if let Some(task) = app.hash_task.as_ref().filter(|task| task.path == path) {
+        vec![Line::from(Span::styled(
+            format!(
+                "Hashing... {:.0}% of {}",
+                app.jobs.progress(task.job).unwrap_or(0.0) * 100.0,
+                humansize::format_size(task.size, humansize::BINARY)
+            ),
+            theme.normal,
+        ))]
+    } else {
+        return area;
+    };
+
+    let height = (lines.len() as u16 + 2).min(area.height / 2);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(height)])
+        .split(area);
+    let block = Block::default()
+        .title(" Hashes ")
+        .borders(Borders::ALL)
+        .style(theme.border);
+    f.render_widget(Paragraph::new(lines).block(block), chunks[1]);
+    chunks[0]
+}
+
 fn draw_workspace_tabs(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     let mut tabs = Vec::new();
+    // Each tab gets an equal share, less its marker and separator
+    let count = app.workspace_manager.workspaces().len().max(1);
+    let tab_width = (area.width as usize / count).saturating_sub(5);
 
     for (idx, workspace) in app.workspace_manager.workspaces().iter().enumerate() {
         let is_active = app.workspace_manager.active_id() == workspace.id;
@@ -129,7 +636,9 @@ fn draw_workspace_tabs(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
         };
 
         let marker = if is_active { "▶ " } else { "  " };
-        let text = format!("{}{}", marker, workspace.title);
+        let changed = if app.config.tab_show_changes && workspace.changed_on_disk() { " ●" } else { "" };
+        let label = crate::path_display::truncate_middle(&workspace.title, tab_width.saturating_sub(changed.chars().count()));
+        let text = format!("{}{}{}", marker, label, changed);
         tabs.push(Span::styled(text, style));
         if idx < app.workspace_manager.workspaces().len() - 1 {
             tabs.push(Span::raw(" │ "));
@@ -145,13 +654,41 @@ fn draw_workspace_tabs(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
 }
 
 fn draw_status_bar(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    // Running jobs, selection count, disk space and clock on the right, the usual status text in the rest
+    let segments = app.status_segments();
+    let area = if segments.is_empty() {
+        area
+    } else {
+        let right = format!(" {} ", segments.join(" │ "));
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(right.chars().count() as u16)])
+            .split(area);
+        f.render_widget(Paragraph::new(right).style(theme.status_bar), chunks[1]);
+        chunks[0]
+    };
+
     let workspace = app.get_current_workspace();
-    let path_display = format!(" {} ", workspace.current_dir.display());
+    let empty_note = match &workspace.empty {
+        Some(EmptyListing::Empty) => " (empty)",
+        Some(EmptyListing::AllHidden) => " (all hidden)",
+        Some(EmptyListing::Failed(_)) => " (unreadable)",
+        None => "",
+    };
+    let room = area.width.saturating_sub(2 + empty_note.len() as u16) as usize;
+    let path_display = format!(" {}{} ", app.display_path(&workspace.current_dir, room), empty_note);
 
     let status = if let Some(err) = &app.error {
         Paragraph::new(err.clone())
             .style(theme.error)
             .alignment(Alignment::Left)
+    } else if matches!(app.mode, AppMode::Jump) {
+        // The prefix goes once the next key would start a new one
+        let prefix = app.jump_prefix_shown(std::time::Instant::now()).unwrap_or_default();
+        let note = app.message.as_deref().map(|m| format!("  ({})", m)).unwrap_or_default();
+        Paragraph::new(format!(" Jump: {}_{}", prefix, note))
+            .style(theme.status_bar)
+            .alignment(Alignment::Left)
     } else if let Some(msg) = &app.message {
         Paragraph::new(msg.clone())
             .style(theme.status_bar)
@@ -166,19 +703,160 @@ fn draw_status_bar(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
 }
 
 fn draw_search_status(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
-    let search_info = format!(
-        " 🔍 Search: {} {} ",
-        app.search_query,
-        if app.search_engine.is_searching { "..." } else { "" }
-    );
+    let scope = match (app.search_scope, &app.scoped_root) {
+        (SearchScope::Drive, _) => format!("{}: {}", app.search_scope.label(), app.config.search_root),
+        (scope, Some(root)) => format!("{} in {}", scope.label(), app.display_path(root, 40)),
+        (scope, None) => scope.label().to_string(),
+    };
+    let progress = match &app.search_engine.drive_search {
+        Some(search) => format!(
+            "{} {} found, {} scanned, {}s",
+            app.jobs.spinner_frame(),
+            app.search_engine.matched,
+            search.scanned(),
+            search.started.elapsed().as_secs()
+        ),
+        None => String::new(),
+    };
+    let search_info = format!(" 🔍 [{}] Search: {} {} ", scope, app.search_query, progress);
+    let mut spans = vec![Span::raw(search_info)];
+    if !app.search_engine.category_filters.is_empty() {
+        for category in &app.search_engine.category_filters {
+            spans.push(Span::styled(format!(" {} ", category.label()), theme.selected));
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled("Alt+0 clears ", theme.hidden));
+    }
 
-    let paragraph = Paragraph::new(search_info)
+    let paragraph = Paragraph::new(Line::from(spans))
         .style(theme.status_bar)
         .alignment(Alignment::Left);
 
     f.render_widget(paragraph, area);
 }
 
+fn draw_search_results(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let groups = app.search_groups();
+    let rows = app.search_rows(&groups);
+    let base = app.search_base();
+
+    let block = Block::default()
+        .title(format!(
+            " Results ({}{}) · {} ",
+            app.search_engine.results.len(),
+            match app.search_engine.drive_search_end {
+                _ if app.search_engine.drive_search.is_some() => ", searching…",
+                Some(DriveSearchEnd::TimedOut | DriveSearchEnd::Cancelled) => ", partial results",
+                _ => "",
+            },
+            app.search_grouping.label()
+        ))
+        .borders(Borders::ALL)
+        .style(theme.border);
+
+    if rows.is_empty() {
+        let hint = if app.search_query.is_empty() {
+            "Type to search"
+        } else if app.searched_query.is_none() {
+            "Press Enter to search"
+        } else if app.search_engine.drive_search.is_some() {
+            "Searching..."
+        } else if !app.search_engine.category_filters.is_empty() {
+            "No matches in the chosen categories (Alt+0 clears)"
+        } else {
+            "No matches"
+        };
+        f.render_widget(Paragraph::new(hint).style(theme.help).block(block), area);
+        return;
+    }
+
+    let items: Vec<ListItem> = rows
+        .iter()
+        .enumerate()
+        .map(|(row_index, row)| {
+            let selected = row_index == app.search_index;
+            match row {
+                SearchRow::Header(group) => {
+                    let group = &groups[*group];
+                    let marker = if app.collapsed_groups.contains(&group.key) { "▸" } else { "▾" };
+                    let style = if selected {
+                        theme.selected
+                    } else {
+                        theme.help.add_modifier(Modifier::BOLD)
+                    };
+                    ListItem::new(format!("{} {} ({})", marker, group.title, group.indices.len())).style(style)
+                }
+                SearchRow::Result(index) => {
+                    let result = &app.search_engine.results[*index];
+                    let indent = if app.search_grouping == SearchGrouping::None { "" } else { "  " };
+                    let is_hidden = result.name.starts_with('.');
+                    let mut spans = vec![Span::raw(format!(
+                        "{}{} {}",
+                        indent,
+                        file_icon(&result.path, result.is_dir, theme.icons),
+                        app.entry_label(&result.name, result.is_dir, is_hidden)
+                    ))];
+                    // Under a folder header the location is already shown
+                    if app.search_grouping != SearchGrouping::Parent {
+                        let location = result
+                            .path
+                            .parent()
+                            .and_then(|parent| parent.strip_prefix(&base).ok())
+                            .map(|rel| rel.display().to_string())
+                            .unwrap_or_default();
+                        if !location.is_empty() {
+                            spans.push(Span::styled(format!("  {}/", location), theme.hidden));
+                        }
+                    }
+                    let style = if selected {
+                        theme.selected
+                    } else if is_hidden && app.hidden_marker().dims() {
+                        theme.hidden
+                    } else {
+                        get_file_style(&result.path, result.is_dir, theme)
+                    };
+                    ListItem::new(Line::from(spans)).style(style)
+                }
+            }
+        })
+        .collect();
+
+    let mut state = ListState::default().with_selected(Some(app.search_index));
+    f.render_stateful_widget(List::new(items).block(block), area, &mut state);
+}
+
+/// The actions menu of one search result, centred over the results
+fn draw_search_actions(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let result = match app.search_action_target.and_then(|index| app.search_engine.results.get(index)) {
+        Some(result) => result,
+        None => return,
+    };
+    let width = area.width.saturating_sub(4).min(44);
+    let height = (ResultAction::ALL.len() as u16 + 2).min(area.height);
+    let overlay_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, overlay_area);
+
+    let block = Block::default()
+        .title(format!(" {} ", result.name))
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    let items: Vec<ListItem> = ResultAction::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, action)| {
+            let style = if index == app.search_action_index { theme.selected } else { theme.normal };
+            ListItem::new(format!(" {}  {}", action.key(), action.label())).style(style)
+        })
+        .collect();
+    f.render_widget(List::new(items).block(block), overlay_area);
+}
+
 fn draw_input_dialog(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     let input_label = match &app.mode {
         AppMode::Input(InputMode::CreateFile) => "📄 Create File",
@@ -186,13 +864,24 @@ fn draw_input_dialog(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
         AppMode::Input(InputMode::Rename) => "✏️  Rename",
         AppMode::Input(InputMode::GoToPath) => "🌐 Go to Path",
         AppMode::Input(InputMode::AddBookmark) => "🔖 Add Bookmark",
+        AppMode::Input(InputMode::ChangeMode) => "🔐 Mode (644, +x, go-w)",
+        AppMode::Input(InputMode::ChangeOwner) => "👤 Owner (user, user:group, :group)",
+        AppMode::Input(InputMode::CollectResults) => "📥 Copy result files into",
+        AppMode::Input(InputMode::SequenceRename) => "🔢 Name prefix (photo → photo_0001.jpg)",
+        AppMode::Input(InputMode::MoveTo) => "🚚 Move to (Tab completes)",
+        AppMode::Input(InputMode::SearchIn) => "🔎 Search in (folder or bookmark name)",
+        AppMode::Input(InputMode::EditSetting) => {
+            crate::config::EDITABLE_SETTINGS[app.settings_index].1
+        }
         _ => "",
     };
 
-    let text = format!("{}: {}_", input_label, app.input_buffer);
-    let paragraph = Paragraph::new(text)
-        .style(theme.normal)
-        .alignment(Alignment::Left);
+    let mut spans = vec![Span::styled(format!("{}: {}_", input_label, app.input_buffer), theme.normal)];
+    if let (AppMode::Input(InputMode::AddBookmark), Some(dir)) = (&app.mode, &app.bookmark_hint) {
+        let width = (area.width as usize).saturating_sub(spans[0].width() + 20);
+        spans.push(Span::styled(format!("   often visited: {}", app.display_path(dir, width)), theme.hidden));
+    }
+    let paragraph = Paragraph::new(Line::from(spans)).alignment(Alignment::Left);
 
     f.render_widget(paragraph, area);
 }
@@ -254,6 +943,1060 @@ fn draw_command_palette(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     f.render_widget(list, list_area);
 }
 
+fn draw_theme_gallery(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    const CARD_HEIGHT: u16 = 6;
+
+    let gallery_width = area.width.min(50);
+    let gallery_height = area.height.min(CARD_HEIGHT * 4 + 2);
+    let gallery_area = Rect {
+        x: (area.width - gallery_width) / 2,
+        y: (area.height - gallery_height) / 2,
+        width: gallery_width,
+        height: gallery_height,
+    };
+
+    let block = Block::default()
+        .title(format!(
+            " 🎨 Themes ({}/{}) ",
+            (app.theme_gallery_index + 1).min(app.theme_gallery.len()),
+            app.theme_gallery.len()
+        ))
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    let inner = block.inner(gallery_area);
+    f.render_widget(Clear, gallery_area);
+    f.render_widget(block, gallery_area);
+
+    // Only the cards that fit are drawn, keeping large theme sets cheap
+    let visible = (inner.height / CARD_HEIGHT).max(1) as usize;
+    let start = app.theme_gallery_index.saturating_sub(visible - 1);
+
+    for (slot, (name, sample)) in app.theme_gallery.iter().skip(start).take(visible).enumerate() {
+        let card_area = Rect {
+            x: inner.x,
+            y: inner.y + slot as u16 * CARD_HEIGHT,
+            width: inner.width,
+            height: CARD_HEIGHT.min(inner.height.saturating_sub(slot as u16 * CARD_HEIGHT)),
+        };
+        let is_current = start + slot == app.theme_gallery_index;
+        draw_theme_sample(f, name, sample, is_current, card_area);
+    }
+}
+
+/// Render a miniature file browser using the given theme's styles
+fn draw_theme_sample(f: &mut Frame, name: &str, sample: &Theme, is_current: bool, area: Rect) {
+    let marker = if is_current { "▶ " } else { "" };
+    let mut block = Block::default()
+        .title(format!(" {}{} ", marker, name))
+        .borders(Borders::ALL)
+        .border_style(sample.border);
+    if is_current {
+        block = block.title_style(sample.selected);
+    }
+
+    let lines = vec![
+        Line::from(Span::styled("📁 src", sample.folder)),
+        Line::from(Span::styled("📝 main.rs", sample.selected)),
+        Line::from(Span::styled("📄 README.md", sample.text_file)),
+        Line::from(Span::styled(" ~/projects/astrofs ", sample.status_bar)),
+    ];
+
+    f.render_widget(Paragraph::new(lines).block(block).style(sample.normal), area);
+}
+
+fn draw_recent_files(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let width = area.width.saturating_sub(4).min(120);
+    let height = area.height.saturating_sub(4);
+    let overlay_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, overlay_area);
+
+    // Same list/preview split as the main browser
+    let preview_percent = (app.config.preview_width_ratio * 100.0) as u16;
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(100 - preview_percent),
+            Constraint::Percentage(preview_percent),
+        ])
+        .split(overlay_area);
+
+    let current_dir = &app.get_current_workspace().current_dir;
+    let now = std::time::SystemTime::now();
+    let visible = chunks[0].height.saturating_sub(2).max(1) as usize;
+    let start = app.recent_index.saturating_sub(visible - 1);
+
+    let items: Vec<ListItem> = app
+        .recent_files
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(visible)
+        .map(|(idx, entry)| {
+            let relative = entry.path.strip_prefix(current_dir).unwrap_or(&entry.path);
+            let when = if app.recent_opened { app.opened_at(&entry.path) } else { entry.modified };
+            let age = when
+                .and_then(|m| now.duration_since(m).ok())
+                .map(format_age)
+                .unwrap_or_else(|| "?".to_string());
+            let style = if idx == app.recent_index {
+                theme.selected
+            } else {
+                get_file_style(&entry.path, entry.is_dir, theme)
+            };
+            ListItem::new(format!("{:>4}  {}", age, relative.display())).style(style)
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(format!(
+            " 🕒 Recently {} ({}) ",
+            if app.recent_opened { "opened" } else { "modified" },
+            app.recent_files.len()
+        ))
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    f.render_widget(List::new(items).block(block), chunks[0]);
+
+    let preview_block = Block::default()
+        .title(" Preview ")
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    f.render_widget(
+        Paragraph::new(app.recent_preview.lines.clone())
+            .block(preview_block)
+            .wrap(Wrap { trim: true })
+            .style(theme.normal),
+        chunks[1],
+    );
+}
+
+fn draw_diff(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    use crate::files::DiffKind;
+
+    let diff = match &app.diff {
+        Some(diff) => diff,
+        None => return,
+    };
+    f.render_widget(Clear, area);
+
+    let name = |path: &std::path::Path| path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let block = Block::default()
+        .title(format!(" ± {} → {} ", name(&diff.old_path), name(&diff.new_path)))
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if diff.binary || diff.identical {
+        let size = |bytes| humansize::format_size(bytes, humansize::BINARY);
+        let lines = vec![
+            Line::from(if diff.identical { "Files are identical" } else { "Binary files differ" }),
+            Line::from(""),
+            Line::from(format!("{}  {}", size(diff.old_size), diff.old_path.display())),
+            Line::from(format!("{}  {}", size(diff.new_size), diff.new_path.display())),
+        ];
+        f.render_widget(Paragraph::new(lines).style(theme.normal), inner);
+        return;
+    }
+
+    let style_for = |kind: DiffKind| match kind {
+        DiffKind::Added => Style::default().fg(Color::Green),
+        DiffKind::Removed => Style::default().fg(Color::Red),
+        DiffKind::Changed => Style::default().fg(Color::Yellow),
+        DiffKind::Gap => theme.hidden,
+        DiffKind::Equal => theme.normal,
+    };
+    let cell = |side: &Option<(usize, String)>, marker: &str| match side {
+        Some((number, text)) => format!("{:>4} {}{}", number, marker, text),
+        None => String::new(),
+    };
+
+    let visible = inner.height as usize;
+    let mut rows: Vec<&crate::files::DiffLine> = diff.lines.iter().skip(app.diff_scroll).collect();
+    let footer = diff.truncated && rows.len() < visible;
+    rows.truncate(visible);
+
+    if app.diff_side_by_side {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(inner);
+        let side = |pick_old: bool| -> Vec<Line> {
+            let mut lines: Vec<Line> = rows
+                .iter()
+                .map(|row| {
+                    let text = match row.kind {
+                        DiffKind::Gap => "   ⋯".to_string(),
+                        _ if pick_old => cell(&row.old, ""),
+                        _ => cell(&row.new, ""),
+                    };
+                    Line::from(Span::styled(text, style_for(row.kind)))
+                })
+                .collect();
+            if footer {
+                lines.push(Line::from(Span::styled("   … (diff truncated)", theme.hidden)));
+            }
+            lines
+        };
+        f.render_widget(Paragraph::new(side(true)), columns[0]);
+        f.render_widget(Paragraph::new(side(false)), columns[1]);
+        return;
+    }
+
+    // Unified: a change shows as its removed line followed by its added line
+    let mut lines = Vec::new();
+    for row in &rows {
+        let style = style_for(row.kind);
+        match row.kind {
+            DiffKind::Gap => lines.push(Line::from(Span::styled("   ⋯", style))),
+            DiffKind::Equal => lines.push(Line::from(Span::styled(cell(&row.old, " "), style))),
+            DiffKind::Removed => lines.push(Line::from(Span::styled(cell(&row.old, "-"), style))),
+            DiffKind::Added => lines.push(Line::from(Span::styled(cell(&row.new, "+"), style))),
+            DiffKind::Changed => {
+                lines.push(Line::from(Span::styled(cell(&row.old, "-"), style)));
+                lines.push(Line::from(Span::styled(cell(&row.new, "+"), style)));
+            }
+        }
+    }
+    if footer {
+        lines.push(Line::from(Span::styled("   … (diff truncated)", theme.hidden)));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_editor(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let editor = match &app.editor {
+        Some(editor) => editor,
+        None => return,
+    };
+    f.render_widget(Clear, area);
+
+    let name = editor.path.file_name().unwrap_or_default().to_string_lossy();
+    let modified = if editor.is_modified() { " [+]" } else { "" };
+    let block = Block::default()
+        .title(format!(" ✎ {}{}  {}:{} ", name, modified, editor.row + 1, editor.col + 1))
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // Tabs are drawn as single spaces so the cursor column matches the text
+    let gutter = editor.lines.len().to_string().len();
+    let lines: Vec<Line> = editor
+        .lines
+        .iter()
+        .enumerate()
+        .skip(editor.scroll)
+        .take(inner.height as usize)
+        .map(|(i, line)| {
+            Line::from(vec![
+                Span::styled(format!("{:>width$} ", i + 1, width = gutter), theme.hidden),
+                Span::styled(line.replace('\t', " "), theme.normal),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), inner);
+
+    // Lines aren't scrolled sideways, so the cursor is only placed while on screen
+    let x = inner.x as usize + gutter + 1 + editor.col;
+    let y = inner.y as usize + editor.row - editor.scroll;
+    if x < (inner.x + inner.width) as usize && y < (inner.y + inner.height) as usize {
+        f.set_cursor(x as u16, y as u16);
+    }
+}
+
+/// The full-screen preview, with search matches highlighted
+fn draw_quick_look(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let look = match &app.quick_look {
+        Some(look) => look,
+        None => return,
+    };
+    f.render_widget(Clear, area);
+
+    let name = look.path.file_name().unwrap_or_default().to_string_lossy();
+    let total = look.content.lines.len();
+    let found = match (look.query.is_empty(), look.current_match()) {
+        (true, _) => String::new(),
+        (false, Some(_)) => format!("  \"{}\" {}/{}", look.query, look.match_index + 1, look.matches.len()),
+        (false, None) => format!("  \"{}\" not found", look.query),
+    };
+    let block = Block::default()
+        .title(format!(" 👁  {}  {}/{}{} ", name, (look.scroll + 1).min(total), total, found))
+        .borders(Borders::ALL)
+        .style(theme.border);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let lines: Vec<Line> = look
+        .content
+        .lines
+        .iter()
+        .enumerate()
+        .skip(look.scroll)
+        .take(inner.height as usize)
+        .map(|(index, line)| {
+            if look.current_match() == Some(index) {
+                line.clone().patch_style(theme.selected)
+            } else if look.matches.binary_search(&index).is_ok() {
+                line.clone().patch_style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                line.clone()
+            }
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines).style(theme.normal), inner);
+}
+
+fn draw_trash(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let width = area.width.saturating_sub(4).min(120);
+    let height = area.height.saturating_sub(4);
+    let overlay_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, overlay_area);
+
+    let block = Block::default()
+        .title(format!(
+            " 🗑 Trash ({}, {}) ",
+            app.trash_items.len(),
+            humansize::format_size(app.trash_size, humansize::BINARY)
+        ))
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    if app.trash_items.is_empty() {
+        f.render_widget(Paragraph::new("Trash is empty").block(block).style(theme.normal), overlay_area);
+        return;
+    }
+
+    let visible = overlay_area.height.saturating_sub(2).max(1) as usize;
+    let start = app.trash_index.saturating_sub(visible - 1);
+    let items: Vec<ListItem> = app
+        .trash_items
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(visible)
+        .map(|(idx, item)| {
+            let deleted = item
+                .deleted
+                .and_then(|d| chrono::Local.from_local_datetime(&d).single())
+                .map(|d| app.format_time(d.into()))
+                .unwrap_or_else(|| "unknown date".to_string());
+            let style = if idx == app.trash_index {
+                theme.selected
+            } else {
+                theme.normal
+            };
+            ListItem::new(format!("{}  {}", deleted, item.original.display())).style(style)
+        })
+        .collect();
+    f.render_widget(List::new(items).block(block), overlay_area);
+}
+
+fn draw_disk_usage(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    const BAR_WIDTH: u16 = 20;
+
+    let Some(view) = app.disk_usage.as_ref() else {
+        return;
+    };
+    let width = area.width.saturating_sub(4).min(120);
+    let height = area.height.saturating_sub(4);
+    let overlay_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, overlay_area);
+
+    let dir = app.display_path(&view.dir, width.saturating_sub(30) as usize);
+    let block = Block::default()
+        .title(format!(" 📊 {} ({}) ", dir, humansize::format_size(view.total, humansize::BINARY)))
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    if let Some(job) = view.scan_job() {
+        let progress = app
+            .jobs
+            .overview()
+            .iter()
+            .find(|j| j.id == job)
+            .and_then(|j| j.progress())
+            .map(|p| format!(" {:.0}%", p * 100.0))
+            .unwrap_or_default();
+        let text = format!("{} Scanning{} (ESC to cancel)", app.jobs.spinner_frame(), progress);
+        f.render_widget(Paragraph::new(text).block(block).style(theme.normal), overlay_area);
+        return;
+    }
+    if view.entries.is_empty() {
+        f.render_widget(Paragraph::new("Nothing here (r to rescan)").block(block).style(theme.normal), overlay_area);
+        return;
+    }
+
+    let largest = view.entries[0].size;
+    let visible = overlay_area.height.saturating_sub(2).max(1) as usize;
+    let start = view.selected.saturating_sub(visible - 1);
+    let items: Vec<ListItem> = view
+        .entries
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(visible)
+        .map(|(idx, usage)| {
+            let filled = crate::layout::size_bar_cells(usage.size, largest, BAR_WIDTH);
+            let share = if view.total == 0 { 0.0 } else { usage.size as f64 * 100.0 / view.total as f64 };
+            let name = app.entry_label(&usage.entry.name, usage.entry.is_dir, usage.entry.is_hidden);
+            let text = format!(
+                "{:>10} {:>5.1}% {}{} {}",
+                humansize::format_size(usage.size, humansize::BINARY),
+                share,
+                "█".repeat(filled as usize),
+                "░".repeat((BAR_WIDTH - filled) as usize),
+                name
+            );
+            let style = if idx == view.selected {
+                theme.selected
+            } else if usage.entry.is_dir {
+                theme.folder
+            } else {
+                theme.normal
+            };
+            ListItem::new(text).style(style)
+        })
+        .collect();
+    f.render_widget(List::new(items).block(block), overlay_area);
+}
+
+fn draw_places(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let width = area.width.saturating_sub(4).min(100);
+    let height = area.height.saturating_sub(4);
+    let overlay_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, overlay_area);
+
+    let title = match app.places_transfer {
+        Some(TransferKind::Copy) => " 🔖 Copy to Bookmark ",
+        Some(TransferKind::Move) => " 🔖 Move to Bookmark ",
+        None => " 💽 Drives and Places ",
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    if app.places.is_empty() {
+        f.render_widget(Paragraph::new("No drives or bookmarks").block(block).style(theme.normal), overlay_area);
+        return;
+    }
+
+    let size = |bytes| humansize::format_size(bytes, humansize::BINARY);
+    let visible = overlay_area.height.saturating_sub(2).max(1) as usize;
+    let start = app.places_index.saturating_sub(visible - 1);
+    let items: Vec<ListItem> = app
+        .places
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(visible)
+        .map(|(idx, place)| {
+            let text = match place {
+                Place::Mount(mount) => format!(
+                    "{} {:<24} {:>10} free of {:<10} {} {}",
+                    if mount.removable { "🔌" } else { "💽" },
+                    mount.mount_point.display(),
+                    size(mount.available),
+                    size(mount.total),
+                    mount.file_system,
+                    mount.name,
+                ),
+                Place::Bookmark { name, path } => format!("🔖 {:<24} {}", name, path.display()),
+            };
+            let style = if idx == app.places_index {
+                theme.selected
+            } else {
+                theme.normal
+            };
+            ListItem::new(text).style(style)
+        })
+        .collect();
+    f.render_widget(List::new(items).block(block), overlay_area);
+}
+
+fn draw_jobs(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let width = area.width.saturating_sub(4).min(80);
+    let height = area.height.saturating_sub(4).min(20);
+    let overlay_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, overlay_area);
+
+    let jobs = app.jobs.overview();
+    let block = Block::default()
+        .title(format!(" ⏳ Background Jobs ({} at a time) ", app.config.max_concurrent_jobs))
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    if jobs.is_empty() {
+        f.render_widget(Paragraph::new("No background jobs").block(block).style(theme.normal), overlay_area);
+        return;
+    }
+
+    let visible = overlay_area.height.saturating_sub(2).max(1) as usize;
+    let start = app.jobs_index.saturating_sub(visible - 1);
+    let items: Vec<ListItem> = jobs
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(visible)
+        .map(|(idx, job)| {
+            let progress = match (job.state, job.progress()) {
+                (JobState::Running, Some(progress)) => format!("{:>4.0}%", progress * 100.0),
+                _ => String::new(),
+            };
+            let text = format!(
+                "{:<9} {:<44} {:>5} {:>5}",
+                job.state.label(),
+                job.label,
+                progress,
+                format_age(job.elapsed())
+            );
+            let style = if idx == app.jobs_index {
+                theme.selected
+            } else if matches!(job.state, JobState::Finished | JobState::Cancelled) {
+                theme.hidden
+            } else {
+                theme.normal
+            };
+            ListItem::new(text).style(style)
+        })
+        .collect();
+    f.render_widget(List::new(items).block(block), overlay_area);
+}
+
+fn draw_paths(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let paths = app.app_paths();
+    let width = area.width.saturating_sub(4).min(90);
+    let height = (paths.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let overlay_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, overlay_area);
+
+    let block = Block::default()
+        .title(format!(" 📁 AstroFS {} Paths ", env!("CARGO_PKG_VERSION")))
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    let path_width = (width as usize).saturating_sub(2 + 18);
+    let items: Vec<ListItem> = paths
+        .iter()
+        .enumerate()
+        .map(|(idx, (label, path))| {
+            let style = if idx == app.paths_index {
+                theme.selected
+            } else if path.exists() {
+                theme.normal
+            } else {
+                theme.hidden
+            };
+            let path = truncate_middle(&path.to_string_lossy(), path_width);
+            ListItem::new(format!("{:<17} {}", label, path)).style(style)
+        })
+        .collect();
+    f.render_widget(List::new(items).block(block), overlay_area);
+}
+
+fn draw_plugin_manager(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let width = area.width.min(90);
+    let height = area.height.min(16);
+    let overlay_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, overlay_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(overlay_area);
+
+    let manager = &app.api_plugin_manager;
+    let ids = app.plugin_ids();
+
+    let items: Vec<ListItem> = ids
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, id)| {
+            let meta = manager.metadata(id)?;
+            let marker = if meta.enabled { "●" } else { "○" };
+            let style = if idx == app.plugin_index {
+                theme.selected
+            } else {
+                theme.normal
+            };
+            Some(ListItem::new(format!("{} {} v{}", marker, meta.name, meta.version)).style(style))
+        })
+        .collect();
+
+    let list_block = Block::default()
+        .title(format!(" 🔌 Plugins ({}) ", ids.len()))
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    if items.is_empty() {
+        f.render_widget(
+            Paragraph::new("No plugins loaded").block(list_block).style(theme.normal),
+            chunks[0],
+        );
+    } else {
+        f.render_widget(List::new(items).block(list_block), chunks[0]);
+    }
+
+    // Details and live stats for the highlighted plugin
+    let mut lines = Vec::new();
+    if let Some(id) = ids.get(app.plugin_index) {
+        if let Some(meta) = manager.metadata(id) {
+            let permissions: Vec<&str> = meta.permissions.iter().map(|p| p.as_str()).collect();
+            lines.push(Line::from(Span::styled(meta.description.clone(), theme.normal)));
+            lines.push(Line::from(format!("Author: {}", meta.author)));
+            lines.push(Line::from(format!(
+                "State: {}",
+                if meta.enabled { "enabled" } else { "disabled" }
+            )));
+            lines.push(Line::from(format!(
+                "Permissions: {}",
+                if permissions.is_empty() { "none".to_string() } else { permissions.join(", ") }
+            )));
+            lines.push(Line::from(""));
+        }
+        if let Some(stats) = manager.stats(id) {
+            lines.push(Line::from(format!("Load time: {} ms", stats.load_time_ms)));
+            lines.push(Line::from(format!(
+                "Memory: {}",
+                humansize::format_size(stats.memory_usage_bytes, humansize::BINARY)
+            )));
+            lines.push(Line::from(format!("Hook calls: {}", stats.function_calls)));
+            let error_style = if stats.errors > 0 { theme.error } else { theme.normal };
+            lines.push(Line::from(Span::styled(format!("Errors: {}", stats.errors), error_style)));
+        }
+    }
+    for (path, error) in manager.load_errors() {
+        let file = path.file_name().unwrap_or_default().to_string_lossy();
+        lines.push(Line::from(Span::styled(format!("⚠ {}: {}", file, error), theme.error)));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("e - Enable/disable   r - Reload   ESC - Close", theme.help)));
+
+    let details_block = Block::default()
+        .title(" Details ")
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+    f.render_widget(
+        Paragraph::new(lines).block(details_block).wrap(Wrap { trim: true }),
+        chunks[1],
+    );
+}
+
+fn draw_settings(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let width = area.width.min(80);
+    let height = area.height.min(crate::config::EDITABLE_SETTINGS.len() as u16 + 4);
+    let settings_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(" ⚡ Settings (Enter to edit, ESC to close) ")
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    let items: Vec<ListItem> = crate::config::EDITABLE_SETTINGS
+        .iter()
+        .enumerate()
+        .map(|(idx, (key, label))| {
+            let value = app.config.setting_value(key).unwrap_or_default();
+            let style = if idx == app.settings_index {
+                theme.selected
+            } else {
+                theme.normal
+            };
+            ListItem::new(format!("{}: {}", label, value)).style(style)
+        })
+        .collect();
+
+    f.render_widget(Clear, settings_area);
+    f.render_widget(List::new(items).block(block), settings_area);
+}
+
+fn draw_quit_confirmation(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
+    let jobs = app.jobs.active();
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("{} operation(s) still running:", jobs.len()),
+            theme.help,
+        )),
+        Line::from(""),
+    ];
+    for job in jobs {
+        lines.push(Line::from(format!(
+            "  ⏳ {} ({}s)",
+            job.label,
+            job.elapsed().as_secs()
+        )));
+    }
+    let queued = app.jobs.queued().count();
+    if queued > 0 {
+        lines.push(Line::from(format!("  ⏸ {} more queued", queued)));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("y - Quit anyway (cancel jobs)"));
+    lines.push(Line::from("w - Wait, then quit"));
+    lines.push(Line::from("ESC - Keep working"));
+
+    let width = area.width.min(50);
+    let height = area.height.min(lines.len() as u16 + 2);
+    let dialog_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(" Quit AstroFS? ")
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(
+        Paragraph::new(lines).block(block).style(theme.normal),
+        dialog_area,
+    );
+}
+
+fn draw_overwrite_confirmation(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let (name, title) = match (&app.pending_rename, &app.pending_export) {
+        (Some((_, new_name)), _) => (new_name.clone(), " Overwrite on Rename? "),
+        (None, export) => (
+            export
+                .as_ref()
+                .and_then(|(_, sidecar)| sidecar.file_name())
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            " Overwrite Media Info? ",
+        ),
+    };
+
+    let lines = vec![
+        Line::from(Span::styled(format!("{} already exists.", name), theme.help)),
+        Line::from(""),
+        Line::from("y - Overwrite it"),
+        Line::from("ESC - Keep the existing file"),
+    ];
+
+    let width = area.width.min((name.len() as u16 + 20).max(40));
+    let height = area.height.min(lines.len() as u16 + 2);
+    let dialog_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(
+        Paragraph::new(lines).block(block).style(theme.normal),
+        dialog_area,
+    );
+}
+
+fn draw_restore_confirmation(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let tabs = app.recovered_session.as_ref().map(|session| session.tabs.as_slice()).unwrap_or_default();
+
+    let mut lines = vec![
+        Line::from(Span::styled("AstroFS didn't quit cleanly last time.", theme.help)),
+        Line::from(""),
+    ];
+    for tab in tabs.iter().take(5) {
+        lines.push(Line::from(format!("  {}", app.display_path(&tab.path, 54))));
+    }
+    if tabs.len() > 5 {
+        lines.push(Line::from(format!("  … and {} more", tabs.len() - 5)));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("y - Restore these tabs"));
+    lines.push(Line::from("ESC - Start fresh"));
+
+    let width = area.width.min(60);
+    let height = area.height.min(lines.len() as u16 + 2);
+    let dialog_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(" Restore Last Session? ")
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(
+        Paragraph::new(lines).block(block).style(theme.normal),
+        dialog_area,
+    );
+}
+
+fn draw_collision_dialog(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let (batch, existing) = match app.paste_batch.as_ref().and_then(|b| b.conflict.as_ref().map(|(_, e)| (b, e))) {
+        Some(conflict) => conflict,
+        None => return,
+    };
+    let name = existing.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let kind = if existing.is_dir() { "folder" } else { "file" };
+
+    let mut lines = vec![
+        Line::from(Span::styled(format!("A {} named {} already exists here.", kind, name), theme.help)),
+        Line::from(""),
+        Line::from("o - Overwrite it"),
+        Line::from("r - Rename the new one"),
+        Line::from("s - Skip this one"),
+        Line::from("O / R / S - Same for all remaining conflicts"),
+        Line::from("ESC - Stop the paste"),
+    ];
+    if !batch.queue.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(format!("{} more item(s) to go", batch.queue.len()), theme.hidden)));
+    }
+
+    let width = area.width.min((name.len() as u16 + 40).max(50));
+    let height = area.height.min(lines.len() as u16 + 2);
+    let dialog_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(" Name Already Taken ")
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(
+        Paragraph::new(lines).block(block).style(theme.normal),
+        dialog_area,
+    );
+}
+
+fn draw_permission_confirmation(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let pending = match &app.pending_permissions {
+        Some(pending) => pending,
+        None => return,
+    };
+
+    let width = area.width.saturating_sub(4).min(100);
+    let height = area.height.saturating_sub(4).min(pending.targets.len() as u16 + 6);
+    let dialog_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    // Leave room for the header, footer, borders and, when cut short, the "more" line
+    let visible = if pending.targets.len() + 6 <= height as usize {
+        pending.targets.len()
+    } else {
+        height.saturating_sub(7) as usize
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Apply {} to {} entries:", pending.change.describe(), pending.targets.len()),
+            theme.help,
+        )),
+        Line::from(""),
+    ];
+    lines.extend(
+        pending
+            .targets
+            .iter()
+            .take(visible)
+            .map(|path| Line::from(format!("  {}", path.display()))),
+    );
+    if pending.targets.len() > visible {
+        lines.push(Line::from(format!("  ... and {} more", pending.targets.len() - visible)));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("y - Apply    ESC - Cancel"));
+
+    let block = Block::default()
+        .title(" Change Permissions? ")
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(
+        Paragraph::new(lines).block(block).style(theme.normal),
+        dialog_area,
+    );
+}
+
+fn draw_sequence_confirmation(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let pending = match &app.pending_sequence {
+        Some(pending) => pending,
+        None => return,
+    };
+
+    let width = area.width.saturating_sub(4).min(100);
+    let height = area.height.saturating_sub(4).min(pending.plan.len() as u16 + 6);
+    let dialog_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let visible = if pending.plan.len() + 6 <= height as usize {
+        pending.plan.len()
+    } else {
+        height.saturating_sub(7) as usize
+    };
+    let order = match pending.order {
+        SequenceOrder::CaptureDate => "capture date",
+        SequenceOrder::Modified => "modified time",
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Rename {} files by {}:", pending.plan.len(), order),
+            theme.help,
+        )),
+        Line::from(""),
+    ];
+    lines.extend(pending.plan.iter().take(visible).map(|(src, dest)| {
+        let name = |path: &std::path::Path| path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        Line::from(format!("  {} → {}", name(src), name(dest)))
+    }));
+    if pending.plan.len() > visible {
+        lines.push(Line::from(format!("  ... and {} more", pending.plan.len() - visible)));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("y - Rename    o - Switch order    ESC - Cancel"));
+
+    let block = Block::default()
+        .title(" Sequence Rename? ")
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(
+        Paragraph::new(lines).block(block).style(theme.normal),
+        dialog_area,
+    );
+}
+
+fn draw_flatten_confirmation(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let plan = match &app.pending_flatten {
+        Some(plan) => plan,
+        None => return,
+    };
+
+    let width = area.width.saturating_sub(4).min(100);
+    let height = area.height.saturating_sub(4).min(u16::try_from(plan.moves.len()).unwrap_or(u16::MAX).saturating_add(6));
+    let dialog_area = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let visible = if plan.moves.len() + 6 <= height as usize {
+        plan.moves.len()
+    } else {
+        height.saturating_sub(7) as usize
+    };
+    let mut summary = format!(
+        "Move {} files up into {}",
+        plan.moves.len(),
+        plan.dir.file_name().unwrap_or_default().to_string_lossy()
+    );
+    if !plan.replacing.is_empty() {
+        summary.push_str(&format!(", replacing {}", plan.replacing.len()));
+    }
+    if !plan.skipped.is_empty() {
+        summary.push_str(&format!(", skipping {} taken", plan.skipped.len()));
+    }
+    let mut lines = vec![Line::from(Span::styled(format!("{}:", summary), theme.help)), Line::from("")];
+    lines.extend(plan.moves.iter().take(visible).map(|(src, dest)| {
+        let from = src.strip_prefix(&plan.dir).unwrap_or(src).display();
+        Line::from(format!("  {} → {}", from, dest.file_name().unwrap_or_default().to_string_lossy()))
+    }));
+    if plan.moves.len() > visible {
+        lines.push(Line::from(format!("  ... and {} more", plan.moves.len() - visible)));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("y - Flatten    ESC - Cancel"));
+
+    let block = Block::default()
+        .title(" Flatten Directory? ")
+        .borders(Borders::ALL)
+        .style(theme.border)
+        .bg(Color::Black);
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(
+        Paragraph::new(lines).block(block).style(theme.normal),
+        dialog_area,
+    );
+}
+
 fn draw_help(f: &mut Frame, _app: &App, area: Rect, theme: &Theme) {
     let help_text = vec![
         Line::from("🚀 AstroFS Help - Terminal File Explorer"),
@@ -262,24 +2005,41 @@ fn draw_help(f: &mut Frame, _app: &App, area: Rect, theme: &Theme) {
             Span::styled("Navigation:", Style::default().add_modifier(Modifier::BOLD)),
         ]),
         Line::from("  j/↓ - Move down     k/↑ - Move up     h/← - Go back     l/→ - Open"),
-        Line::from("  PgDn - Page down   PgUp - Page up    Home - Start      End - End"),
+        Line::from("  5j - Move down 5 (a count repeats j/k/arrows)"),
+        Line::from("  PgDn - Page down   PgUp - Page up    Home/g g - Start  End/g e - End"),
+        Line::from("  g h - Home folder  g r - Root folder"),
+        Line::from("  S - Toggle sidebar 1-9, 0 - Jump to a sidebar entry    M - Drives and places"),
+        Line::from("  o - Background jobs    # - Folder item counts    z - Fold a JSON/YAML/TOML preview"),
+        Line::from("  P - Quick look (full-screen preview; / search, n/N next/previous)    O - Recently opened files"),
+        Line::from("  < / > - Preview width    { / } - Sidebar width    = - Reset pane sizes"),
+        Line::from("  R - Rendered or source preview of a Markdown/HTML file"),
+        Line::from("  f - Type to jump: select the first entry starting with what you type (Esc stops)"),
         Line::from(""),
         Line::from(vec![
             Span::styled("File Operations:", Style::default().add_modifier(Modifier::BOLD)),
         ]),
         Line::from("  n - Create file    N - Create directory    r - Rename"),
-        Line::from("  d - Delete        c - Copy               . - Toggle hidden"),
+        Line::from("  d - Delete        c - Copy      m - Cut      Ctrl+V - Paste      . - Toggle hidden"),
+        Line::from("  C - Duplicate here (\"name copy.ext\")"),
+        Line::from("  U - Undo delete   I - Export media info    Alt+c/Alt+m - Copy/move to a bookmark"),
+        Line::from("  H - File hashes   Y - Copy hash         Space - Mark      D - Compare files"),
+        Line::from("  a - Mark all      * - Invert marks      ESC - Clear marks    y - Copy repo path"),
+        Line::from("  K/J - Move entry up/down (custom order)    s - Next sort mode    e - Edit small text file"),
+        Line::from("  u - Disk usage (Enter/h drill in and out, r rescan, o show in list)"),
+        Line::from("  Palette \"Sequence Rename\" - Number marked photos prefix_0001.jpg, ... by date taken"),
         Line::from(""),
         Line::from(vec![
             Span::styled("Workspaces:", Style::default().add_modifier(Modifier::BOLD)),
         ]),
         Line::from("  t - New tab       w - Close tab         [ - Prev tab      ] - Next tab"),
+        Line::from("  T/Ctrl+Enter - Open the selected folder in a new tab"),
         Line::from(""),
         Line::from(vec![
             Span::styled("Search & Commands:", Style::default().add_modifier(Modifier::BOLD)),
         ]),
         Line::from("  / - Search        b - Add bookmark      p - Command palette"),
         Line::from("  ? - Help          q - Quit              ESC - Cancel"),
+        Line::from("  F5/Ctrl+R - Refresh the listing"),
         Line::from(""),
         Line::from("Press any key to return..."),
     ];
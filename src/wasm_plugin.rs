@@ -0,0 +1,710 @@
+// WASM Plugins - sandboxed plugins running on wasmtime
+//
+// A plugin is a `.wasm` (or `.wat`) module plus an optional JSON manifest
+// with the same file stem. The host interface is documented in
+// docs/PLUGINS.md under "WASM Plugins".
+use crate::plugin_api::{
+    Plugin, PluginCommand, PluginContext, PluginMetadata, PluginPermission, PluginStats,
+};
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use wasmtime::{
+    Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder,
+};
+
+/// Instructions a single hook call may execute before it is stopped
+const FUEL_PER_CALL: u64 = 50_000_000;
+/// Largest linear memory a plugin may grow to
+const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+/// Log lines kept per plugin
+const MAX_LOG_LINES: usize = 100;
+
+/// Host call results returned to the guest
+const STATUS_DENIED: i32 = -1;
+const STATUS_IO_ERROR: i32 = -2;
+const STATUS_BAD_INPUT: i32 = -3;
+
+/// Plugin manifest, read from `<stem>.json` next to the module
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WasmManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub author: String,
+    /// Permission names such as "read-files" or "write-files"
+    pub permissions: Vec<String>,
+    /// Directories the plugin may read or write; `~` expands to home
+    pub allowed_paths: Vec<String>,
+}
+
+/// Files with these extensions in the plugin directory are loaded as WASM plugins
+pub fn is_wasm_plugin(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("wasm") | Some("wat")
+    )
+}
+
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        Engine::new(&config).expect("default wasmtime configuration is valid")
+    })
+}
+
+/// State the host functions can see
+struct HostState {
+    permissions: Vec<PluginPermission>,
+    allowed_roots: Vec<PathBuf>,
+    logs: Vec<String>,
+    limits: StoreLimits,
+    ctx: Option<PluginContext>,
+}
+
+impl HostState {
+    /// Resolve a guest-supplied path and check it lies inside an allowed root
+    fn sandboxed(&self, raw: &str, for_write: bool) -> Option<PathBuf> {
+        let path = Path::new(raw);
+        if !path.is_absolute() {
+            return None;
+        }
+        // Writes may target a new file, so only its directory has to exist
+        let resolved = if for_write && !path.exists() {
+            let parent = fs::canonicalize(path.parent()?).ok()?;
+            parent.join(path.file_name()?)
+        } else {
+            fs::canonicalize(path).ok()?
+        };
+        self.allowed_roots
+            .iter()
+            .any(|root| resolved.starts_with(root))
+            .then_some(resolved)
+    }
+}
+
+struct WasmInstance {
+    store: Store<HostState>,
+    instance: Instance,
+}
+
+impl WasmInstance {
+    fn memory(&mut self) -> Result<Memory> {
+        self.instance
+            .get_memory(&mut self.store, "memory")
+            .ok_or_else(|| anyhow!("module does not export `memory`"))
+    }
+
+    fn has_export(&mut self, name: &str) -> bool {
+        self.instance.get_func(&mut self.store, name).is_some()
+    }
+
+    /// Copy bytes into guest memory through the guest's allocator
+    fn write_guest(&mut self, bytes: &[u8]) -> Result<(i32, i32)> {
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, "astrofs_alloc")?;
+        let len = bytes.len() as i32;
+        let ptr = alloc.call(&mut self.store, len)?;
+        self.memory()?.write(&mut self.store, ptr as usize, bytes)?;
+        Ok((ptr, len))
+    }
+
+    fn free_guest(&mut self, ptr: i32, len: i32) {
+        if let Ok(free) = self
+            .instance
+            .get_typed_func::<(i32, i32), ()>(&mut self.store, "astrofs_free")
+        {
+            let _ = free.call(&mut self.store, (ptr, len));
+        }
+    }
+
+    /// Read a string the guest returned as `(ptr << 32) | len`
+    fn read_packed(&mut self, packed: i64) -> Result<String> {
+        if packed < 0 {
+            return Err(anyhow!("plugin returned error {}", packed));
+        }
+        let ptr = (packed >> 32) as usize;
+        let len = (packed & 0xffff_ffff) as usize;
+        let mut buf = vec![0u8; len];
+        self.memory()?.read(&self.store, ptr, &mut buf)?;
+        Ok(String::from_utf8_lossy(&buf).to_string())
+    }
+
+    fn refuel(&mut self) -> Result<()> {
+        self.store.set_fuel(FUEL_PER_CALL)?;
+        Ok(())
+    }
+
+    /// Call a `() -> i32` hook; missing exports count as success
+    fn call_status(&mut self, name: &str) -> Result<()> {
+        if !self.has_export(name) {
+            return Ok(());
+        }
+        self.refuel()?;
+        let func = self.instance.get_typed_func::<(), i32>(&mut self.store, name)?;
+        check_status(name, func.call(&mut self.store, ())?)
+    }
+
+    /// Call a hook taking string arguments and returning a status
+    fn call_with_strings(&mut self, name: &str, args: &[&str]) -> Result<()> {
+        if !self.has_export(name) {
+            return Ok(());
+        }
+        self.refuel()?;
+
+        let mut written = Vec::new();
+        for arg in args {
+            written.push(self.write_guest(arg.as_bytes())?);
+        }
+        let status = match written.as_slice() {
+            [(p, l)] => self
+                .instance
+                .get_typed_func::<(i32, i32), i32>(&mut self.store, name)?
+                .call(&mut self.store, (*p, *l)),
+            [(p1, l1), (p2, l2)] => self
+                .instance
+                .get_typed_func::<(i32, i32, i32, i32), i32>(&mut self.store, name)?
+                .call(&mut self.store, (*p1, *l1, *p2, *l2)),
+            _ => return Err(anyhow!("unsupported hook arity for {}", name)),
+        };
+        for (ptr, len) in written.into_iter().rev() {
+            self.free_guest(ptr, len);
+        }
+        check_status(name, status?)
+    }
+
+    /// Call a hook taking one string and returning a packed string
+    fn call_returning_string(&mut self, name: &str, input: Option<&str>) -> Result<Option<String>> {
+        if !self.has_export(name) {
+            return Ok(None);
+        }
+        self.refuel()?;
+
+        match input {
+            Some(input) => {
+                let (ptr, len) = self.write_guest(input.as_bytes())?;
+                let packed = self
+                    .instance
+                    .get_typed_func::<(i32, i32), i64>(&mut self.store, name)?
+                    .call(&mut self.store, (ptr, len));
+                // The output may live in the input block, so read before freeing
+                let output = match packed {
+                    Ok(packed) => self.read_packed(packed),
+                    Err(e) => Err(e.into()),
+                };
+                self.free_guest(ptr, len);
+                output.map(Some)
+            }
+            None => {
+                let packed = self
+                    .instance
+                    .get_typed_func::<(), i64>(&mut self.store, name)?
+                    .call(&mut self.store, ())?;
+                self.read_packed(packed).map(Some)
+            }
+        }
+    }
+}
+
+fn check_status(name: &str, status: i32) -> Result<()> {
+    if status == 0 {
+        Ok(())
+    } else {
+        Err(anyhow!("{} failed with status {}", name, status))
+    }
+}
+
+/// Read a UTF-8 string out of the caller's memory
+fn guest_str(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Option<String> {
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    let mut buf = vec![0u8; usize::try_from(len).ok()?];
+    memory.read(&*caller, usize::try_from(ptr).ok()?, &mut buf).ok()?;
+    String::from_utf8(buf).ok()
+}
+
+/// Copy bytes into the caller's memory, returning `(ptr << 32) | len`
+fn guest_alloc(caller: &mut Caller<'_, HostState>, bytes: &[u8]) -> Option<i64> {
+    let alloc = caller
+        .get_export("astrofs_alloc")
+        .and_then(Extern::into_func)?
+        .typed::<i32, i32>(&*caller)
+        .ok()?;
+    let ptr = alloc.call(&mut *caller, bytes.len() as i32).ok()?;
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    memory.write(&mut *caller, ptr as usize, bytes).ok()?;
+    Some(((ptr as i64) << 32) | bytes.len() as i64)
+}
+
+/// Register the `astrofs` host module
+fn host_linker() -> Result<Linker<HostState>> {
+    let mut linker = Linker::new(engine());
+
+    linker.func_wrap(
+        "astrofs",
+        "log",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            if let Some(line) = guest_str(&mut caller, ptr, len) {
+                let logs = &mut caller.data_mut().logs;
+                if logs.len() >= MAX_LOG_LINES {
+                    logs.remove(0);
+                }
+                logs.push(line);
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        "astrofs",
+        "file_exists",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i32 {
+            let Some(raw) = guest_str(&mut caller, ptr, len) else {
+                return STATUS_BAD_INPUT;
+            };
+            let state = caller.data();
+            if !state.permissions.contains(&PluginPermission::ReadFiles) {
+                return STATUS_DENIED;
+            }
+            state.sandboxed(&raw, false).is_some() as i32
+        },
+    )?;
+
+    linker.func_wrap(
+        "astrofs",
+        "read_file",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i64 {
+            let Some(raw) = guest_str(&mut caller, ptr, len) else {
+                return STATUS_BAD_INPUT as i64;
+            };
+            let state = caller.data();
+            if !state.permissions.contains(&PluginPermission::ReadFiles) {
+                return STATUS_DENIED as i64;
+            }
+            let Some(path) = state.sandboxed(&raw, false) else {
+                return STATUS_DENIED as i64;
+            };
+            match fs::read(path) {
+                Ok(bytes) => guest_alloc(&mut caller, &bytes).unwrap_or(STATUS_IO_ERROR as i64),
+                Err(_) => STATUS_IO_ERROR as i64,
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        "astrofs",
+        "write_file",
+        |mut caller: Caller<'_, HostState>,
+         path_ptr: i32,
+         path_len: i32,
+         data_ptr: i32,
+         data_len: i32|
+         -> i32 {
+            let (Some(raw), Some(data)) = (
+                guest_str(&mut caller, path_ptr, path_len),
+                guest_str(&mut caller, data_ptr, data_len),
+            ) else {
+                return STATUS_BAD_INPUT;
+            };
+            let state = caller.data();
+            if !state.permissions.contains(&PluginPermission::WriteFiles) {
+                return STATUS_DENIED;
+            }
+            let Some(path) = state.sandboxed(&raw, true) else {
+                return STATUS_DENIED;
+            };
+            match fs::write(path, data) {
+                Ok(()) => 0,
+                Err(_) => STATUS_IO_ERROR,
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        "astrofs",
+        "save_data",
+        |mut caller: Caller<'_, HostState>,
+         key_ptr: i32,
+         key_len: i32,
+         json_ptr: i32,
+         json_len: i32|
+         -> i32 {
+            let (Some(key), Some(json)) = (
+                guest_str(&mut caller, key_ptr, key_len),
+                guest_str(&mut caller, json_ptr, json_len),
+            ) else {
+                return STATUS_BAD_INPUT;
+            };
+            let Ok(value) = serde_json::from_str(&json) else {
+                return STATUS_BAD_INPUT;
+            };
+            match &caller.data().ctx {
+                Some(ctx) if ctx.save_data(&key, value).is_ok() => 0,
+                _ => STATUS_IO_ERROR,
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        "astrofs",
+        "load_data",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i64 {
+            let Some(key) = guest_str(&mut caller, ptr, len) else {
+                return STATUS_BAD_INPUT as i64;
+            };
+            let value = match &caller.data().ctx {
+                Some(ctx) => match ctx.load_data(&key) {
+                    Ok(value) => value.unwrap_or_default(),
+                    Err(_) => return STATUS_IO_ERROR as i64,
+                },
+                None => serde_json::Value::Null,
+            };
+            let json = value.to_string();
+            guest_alloc(&mut caller, json.as_bytes()).unwrap_or(STATUS_IO_ERROR as i64)
+        },
+    )?;
+
+    Ok(linker)
+}
+
+/// A plugin backed by a WASM module
+pub struct WasmPlugin {
+    manifest: WasmManifest,
+    inner: Mutex<WasmInstance>,
+}
+
+impl WasmPlugin {
+    /// Load a module and its manifest, returning the plugin and its registry entry
+    pub fn load(path: &Path) -> Result<(Self, PluginMetadata)> {
+        let stem = path
+            .file_stem()
+            .ok_or_else(|| anyhow!("Invalid plugin path {:?}", path))?
+            .to_string_lossy()
+            .to_string();
+
+        let manifest_path = path.with_extension("json");
+        let mut manifest: WasmManifest = if manifest_path.exists() {
+            serde_json::from_str(&fs::read_to_string(&manifest_path)?)?
+        } else {
+            WasmManifest::default()
+        };
+        if manifest.id.is_empty() {
+            manifest.id = stem.clone();
+        }
+        if manifest.name.is_empty() {
+            manifest.name = stem;
+        }
+
+        let mut permissions = Vec::new();
+        for name in &manifest.permissions {
+            let permission = PluginPermission::parse(name)
+                .ok_or_else(|| anyhow!("Unknown permission '{}' in {:?}", name, manifest_path))?;
+            permissions.push(permission);
+        }
+        let allowed_roots = manifest
+            .allowed_paths
+            .iter()
+            .filter_map(|raw| fs::canonicalize(expand_home(raw)).ok())
+            .collect();
+
+        let module = Module::from_file(engine(), path)?;
+        let state = HostState {
+            permissions: permissions.clone(),
+            allowed_roots,
+            logs: Vec::new(),
+            limits: StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).build(),
+            ctx: None,
+        };
+        let mut store = Store::new(engine(), state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL)?;
+        let instance = host_linker()?.instantiate(&mut store, &module)?;
+
+        let mut inner = WasmInstance { store, instance };
+        inner.memory()?;
+        inner
+            .instance
+            .get_typed_func::<i32, i32>(&mut inner.store, "astrofs_alloc")
+            .map_err(|_| anyhow!("module does not export `astrofs_alloc`"))?;
+
+        let meta = PluginMetadata {
+            id: manifest.id.clone(),
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            description: manifest.description.clone(),
+            author: manifest.author.clone(),
+            path: path.to_path_buf(),
+            enabled: false,
+            permissions,
+        };
+        Ok((
+            Self {
+                manifest,
+                inner: Mutex::new(inner),
+            },
+            meta,
+        ))
+    }
+
+    /// Lines the plugin has written with `astrofs.log`
+    #[allow(dead_code)]
+    pub fn logs(&self) -> Vec<String> {
+        self.with_instance(|inner| Ok(inner.store.data().logs.clone()))
+            .unwrap_or_default()
+    }
+
+    fn with_instance<T>(&self, f: impl FnOnce(&mut WasmInstance) -> Result<T>) -> Result<T> {
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|_| anyhow!("plugin {} panicked earlier", self.manifest.id))?;
+        f(&mut inner)
+    }
+
+    fn path_hook(&self, name: &str, paths: &[&PathBuf]) -> Result<()> {
+        let args: Vec<String> = paths.iter().map(|p| p.to_string_lossy().to_string()).collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.with_instance(|inner| inner.call_with_strings(name, &args))
+    }
+}
+
+/// Guest-side shape of a palette command
+#[derive(Deserialize)]
+struct GuestCommand {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    shortcuts: Vec<String>,
+    #[serde(default)]
+    category: String,
+}
+
+impl Plugin for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.manifest.name
+    }
+    fn version(&self) -> &str {
+        &self.manifest.version
+    }
+    fn description(&self) -> &str {
+        &self.manifest.description
+    }
+    fn author(&self) -> &str {
+        &self.manifest.author
+    }
+
+    fn attach(&mut self, ctx: PluginContext) {
+        let _ = self.with_instance(|inner| {
+            inner.store.data_mut().ctx = Some(ctx);
+            Ok(())
+        });
+    }
+
+    fn on_load(&mut self) -> Result<()> {
+        self.with_instance(|inner| inner.call_status("on_load"))
+    }
+    fn on_unload(&mut self) -> Result<()> {
+        self.with_instance(|inner| inner.call_status("on_unload"))
+    }
+    fn on_enable(&mut self) -> Result<()> {
+        self.with_instance(|inner| inner.call_status("on_enable"))
+    }
+    fn on_disable(&mut self) -> Result<()> {
+        self.with_instance(|inner| inner.call_status("on_disable"))
+    }
+    fn on_idle(&self) -> Result<()> {
+        self.with_instance(|inner| inner.call_status("on_idle"))
+    }
+
+    fn on_file_created(&self, path: &PathBuf) -> Result<()> {
+        self.path_hook("on_file_created", &[path])
+    }
+    fn on_file_deleted(&self, path: &PathBuf) -> Result<()> {
+        self.path_hook("on_file_deleted", &[path])
+    }
+    fn on_file_renamed(&self, old_path: &PathBuf, new_path: &PathBuf) -> Result<()> {
+        self.path_hook("on_file_renamed", &[old_path, new_path])
+    }
+    fn on_file_copied(&self, src: &PathBuf, dest: &PathBuf) -> Result<()> {
+        self.path_hook("on_file_copied", &[src, dest])
+    }
+    fn on_file_moved(&self, src: &PathBuf, dest: &PathBuf) -> Result<()> {
+        self.path_hook("on_file_moved", &[src, dest])
+    }
+    fn on_directory_changed(&self, old: &PathBuf, new: &PathBuf) -> Result<()> {
+        self.path_hook("on_directory_changed", &[old, new])
+    }
+    fn on_theme_changed(&self, theme_name: &str) -> Result<()> {
+        self.with_instance(|inner| inner.call_with_strings("on_theme_changed", &[theme_name]))
+    }
+
+    fn execute_command(&self, command: &str, args: Vec<String>) -> Result<String> {
+        let input = serde_json::json!({ "command": command, "args": args }).to_string();
+        self.with_instance(|inner| inner.call_returning_string("execute_command", Some(&input)))
+            .map(Option::unwrap_or_default)
+    }
+
+    fn get_commands(&self) -> Vec<PluginCommand> {
+        let json = self
+            .with_instance(|inner| inner.call_returning_string("get_commands", None))
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        serde_json::from_str::<Vec<GuestCommand>>(&json)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| PluginCommand {
+                name: c.name,
+                description: c.description,
+                shortcuts: c.shortcuts,
+                category: c.category,
+                args: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn filter_search_results(&self, query: &str, results: &mut Vec<String>) -> Result<()> {
+        let input = serde_json::json!({ "query": query, "results": results }).to_string();
+        let output =
+            self.with_instance(|inner| inner.call_returning_string("filter_search_results", Some(&input)))?;
+        if let Some(output) = output {
+            *results = serde_json::from_str(&output)?;
+        }
+        Ok(())
+    }
+
+    fn get_stats(&self) -> PluginStats {
+        let memory_usage_bytes = self
+            .with_instance(|inner| {
+                let memory = inner.memory()?;
+                Ok(memory.data_size(&inner.store) as u64)
+            })
+            .unwrap_or(0);
+        PluginStats {
+            memory_usage_bytes,
+            ..PluginStats::default()
+        }
+    }
+}
+
+fn expand_home(raw: &str) -> PathBuf {
+    match raw.strip_prefix("~") {
+        Some(rest) => dirs::home_dir()
+            .unwrap_or_default()
+            .join(rest.trim_start_matches('/')),
+        None => PathBuf::from(raw),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    // Logs every created path, reads a file through the host and echoes commands
+    const TEST_PLUGIN: &str = r#"
+        (module
+          (import "astrofs" "log" (func $log (param i32 i32)))
+          (import "astrofs" "read_file" (func $read_file (param i32 i32) (result i64)))
+          (memory (export "memory") 1)
+          (global $heap (mut i32) (i32.const 1024))
+          (func (export "astrofs_alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $heap))
+            (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+            (local.get $ptr))
+          (func (export "on_file_created") (param $ptr i32) (param $len i32) (result i32)
+            (call $log (local.get $ptr) (local.get $len))
+            (i32.const 0))
+          (func (export "on_file_deleted") (param $ptr i32) (param $len i32) (result i32)
+            (local $packed i64)
+            (local.set $packed (call $read_file (local.get $ptr) (local.get $len)))
+            (if (i64.lt_s (local.get $packed) (i64.const 0))
+              (then (return (i32.wrap_i64 (local.get $packed)))))
+            (call $log
+              (i32.wrap_i64 (i64.shr_u (local.get $packed) (i64.const 32)))
+              (i32.wrap_i64 (local.get $packed)))
+            (i32.const 0))
+          (func (export "execute_command") (param $ptr i32) (param $len i32) (result i64)
+            (i64.or
+              (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+              (i64.extend_i32_u (local.get $len))))
+          (func (export "on_idle") (result i32)
+            (loop $spin (br $spin))
+            (i32.const 0)))
+    "#;
+
+    fn write_plugin(dir: &Path, manifest: &str) -> Result<PathBuf> {
+        let path = dir.join("test.wat");
+        fs::write(&path, TEST_PLUGIN)?;
+        fs::write(dir.join("test.json"), manifest)?;
+        Ok(path)
+    }
+
+    #[test]
+    fn test_wasm_plugin_hooks_and_sandbox() -> Result<()> {
+        let dir = tempdir()?;
+        let allowed = dir.path().join("allowed");
+        fs::create_dir(&allowed)?;
+        fs::write(allowed.join("note.txt"), "secret")?;
+        fs::write(dir.path().join("outside.txt"), "nope")?;
+
+        let manifest = format!(
+            r#"{{"id": "test", "permissions": ["read-files"], "allowed_paths": [{:?}]}}"#,
+            allowed.to_string_lossy()
+        );
+        let (plugin, meta) = WasmPlugin::load(&write_plugin(dir.path(), &manifest)?)?;
+        assert_eq!(meta.id, "test");
+        assert_eq!(meta.permissions, vec![PluginPermission::ReadFiles]);
+
+        plugin.on_file_created(&PathBuf::from("/tmp/new.txt"))?;
+        plugin.on_file_deleted(&allowed.join("note.txt"))?;
+        assert_eq!(plugin.logs(), vec!["/tmp/new.txt", "secret"]);
+
+        // Reads outside the allowed paths are refused
+        assert!(plugin.on_file_deleted(&dir.path().join("outside.txt")).is_err());
+
+        let echoed = plugin.execute_command("greet", vec!["world".to_string()])?;
+        assert!(echoed.contains("\"command\":\"greet\""));
+
+        // Runaway loops run out of fuel instead of hanging the host
+        assert!(plugin.on_idle().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_example_plugin_loads() -> Result<()> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins/wasm/hello.wasm");
+        let (mut plugin, meta) = WasmPlugin::load(&path)?;
+        assert_eq!(meta.id, "hello-wasm");
+
+        plugin.on_load()?;
+        assert_eq!(plugin.logs(), vec!["hello plugin loaded"]);
+        assert_eq!(plugin.get_commands()[0].name, "hello");
+        assert_eq!(plugin.execute_command("hello", Vec::new())?, "Hello from WASM!");
+        Ok(())
+    }
+
+    #[test]
+    fn test_wasm_plugin_without_permission_is_denied() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("note.txt"), "secret")?;
+        let manifest = format!(
+            r#"{{"allowed_paths": [{:?}]}}"#,
+            dir.path().to_string_lossy()
+        );
+        let (plugin, meta) = WasmPlugin::load(&write_plugin(dir.path(), &manifest)?)?;
+        assert_eq!(meta.id, "test");
+
+        let err = plugin.on_file_deleted(&dir.path().join("note.txt")).unwrap_err();
+        assert!(err.to_string().contains(&STATUS_DENIED.to_string()));
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+// Directory Watcher - filesystem change notifications backed by notify
+use anyhow::Result;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// Kind of change reported for a watched path
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChangeKind {
+    Created,
+    Deleted,
+    Modified,
+}
+
+impl ChangeKind {
+    #[allow(dead_code)]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Created => "created",
+            ChangeKind::Deleted => "deleted",
+            ChangeKind::Modified => "modified",
+        }
+    }
+}
+
+/// A single filesystem change
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct ChangeEvent {
+    pub kind: ChangeKind,
+    pub path: PathBuf,
+}
+
+/// Watches a single directory (non-recursively) for changes.
+///
+/// Events are buffered in a channel until read. Dropping the watcher
+/// drops the underlying notify watcher, which stops its event thread.
+pub struct DirWatcher {
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<ChangeEvent>,
+    path: PathBuf,
+}
+
+impl DirWatcher {
+    /// Start watching a directory
+    pub fn new(path: &Path) -> Result<Self> {
+        let (tx, receiver) = channel();
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                for change in to_changes(event) {
+                    // The receiver is gone once the watcher is being dropped
+                    let _ = tx.send(change);
+                }
+            }
+        })?;
+        watcher.watch(path, RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Get the watched directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Take all buffered events without blocking
+    pub fn drain(&self) -> Vec<ChangeEvent> {
+        self.receiver.try_iter().collect()
+    }
+
+    /// Wait up to `timeout` for the next event
+    #[allow(dead_code)]
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<ChangeEvent> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => Some(event),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+}
+
+/// Map a notify event onto our simplified change kinds
+fn to_changes(event: Event) -> Vec<ChangeEvent> {
+    let kind = match event.kind {
+        EventKind::Create(_) => ChangeKind::Created,
+        EventKind::Remove(_) => ChangeKind::Deleted,
+        EventKind::Modify(_) => ChangeKind::Modified,
+        _ => return Vec::new(),
+    };
+
+    event
+        .paths
+        .into_iter()
+        .map(|path| ChangeEvent {
+            kind: kind.clone(),
+            path,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_watcher_reports_created_file() -> Result<()> {
+        let dir = tempdir()?;
+        let watcher = DirWatcher::new(dir.path())?;
+
+        let file_path = dir.path().join("new.txt");
+        std::fs::write(&file_path, "hello")?;
+
+        let mut seen_create = false;
+        while let Some(event) = watcher.recv_timeout(Duration::from_secs(2)) {
+            if event.kind == ChangeKind::Created && event.path == file_path {
+                seen_create = true;
+                break;
+            }
+        }
+        assert!(seen_create);
+        Ok(())
+    }
+}
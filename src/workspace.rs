@@ -1,8 +1,34 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::archive::ArchiveListing;
 use crate::files::FileEntry;
+use crate::markup::RenderMode;
 use crate::preview::PreviewContent;
 use ratatui::text::Line;
 
+/// Why a listing shows no entries
+#[derive(Clone, Debug, PartialEq)]
+pub enum EmptyListing {
+    /// The directory has nothing in it
+    Empty,
+    /// It has entries, but hidden files or ignore patterns leave them all out
+    AllHidden,
+    /// Reading the directory failed
+    Failed(String),
+}
+
+impl EmptyListing {
+    /// Shown in place of the entries and in the preview
+    pub fn placeholder(&self) -> String {
+        match self {
+            EmptyListing::Empty => "(empty directory)".to_string(),
+            EmptyListing::AllHidden => "(everything here is hidden or ignored; . shows hidden files)".to_string(),
+            EmptyListing::Failed(reason) => format!("(cannot list this directory: {})", reason),
+        }
+    }
+}
+
 /// Represents a single tab/pane in the file explorer
 #[derive(Clone, Debug)]
 pub struct Workspace {
@@ -14,6 +40,21 @@ pub struct Workspace {
     pub preview: PreviewContent,
     pub show_hidden: bool,
     pub title: String,
+    /// Whether `title` was picked by the user rather than taken from the directory
+    pub renamed: bool,
+    /// Entries picked with space for multi-file actions; kept across directories
+    pub marked: HashSet<PathBuf>,
+    /// The directory `entries` were last listed from, to tell moves from refreshes
+    pub listed_dir: Option<PathBuf>,
+    /// Modification time of that directory when it was listed
+    pub listed_modified: Option<SystemTime>,
+    /// Why `entries` is empty; None while there are entries
+    pub empty: Option<EmptyListing>,
+    /// The archive being browsed while `current_dir` is inside one
+    pub archive: Option<ArchiveListing>,
+    /// Rendered or raw, as toggled for the markup file at the path; other files
+    /// show their type's default
+    pub render_mode: Option<(PathBuf, RenderMode)>,
 }
 
 impl Workspace {
@@ -34,11 +75,37 @@ impl Workspace {
                 .and_then(|n| n.to_str())
                 .unwrap_or("Workspace")
                 .to_string(),
+            renamed: false,
+            marked: HashSet::new(),
+            listed_dir: None,
+            listed_modified: None,
+            empty: None,
+            archive: None,
+            render_mode: None,
         }
     }
 
+    /// The render mode toggled for `path`, if it was
+    pub fn render_mode_for(&self, path: &Path) -> Option<RenderMode> {
+        self.render_mode.as_ref().filter(|(toggled, _)| toggled == path).map(|(_, mode)| *mode)
+    }
+
     pub fn rename(&mut self, name: String) {
         self.title = name;
+        self.renamed = true;
+    }
+
+    /// Give the tab an automatic title, unless the user named it
+    pub fn retitle(&mut self, title: String) {
+        if !self.renamed {
+            self.title = title;
+        }
+    }
+
+    /// Whether the directory changed on disk since it was last listed
+    pub fn changed_on_disk(&self) -> bool {
+        self.listed_modified.is_some()
+            && std::fs::metadata(&self.current_dir).and_then(|m| m.modified()).ok() != self.listed_modified
     }
 
     pub fn get_selected_entry(&self) -> Option<&FileEntry> {
@@ -80,6 +147,51 @@ impl Workspace {
         self.adjust_scroll();
     }
 
+    /// Mark or unmark the selected entry
+    pub fn toggle_mark(&mut self) {
+        if let Some(path) = self.get_selected_entry().map(|e| e.path.clone()) {
+            if !self.marked.remove(&path) {
+                self.marked.insert(path);
+            }
+        }
+    }
+
+    pub fn is_marked(&self, path: &std::path::Path) -> bool {
+        self.marked.contains(path)
+    }
+
+    /// Mark every entry in the listing
+    pub fn mark_all(&mut self) {
+        self.marked.extend(self.entries.iter().map(|e| e.path.clone()));
+    }
+
+    /// Flip the mark on every entry in the listing; marks elsewhere are kept
+    pub fn invert_marks(&mut self) {
+        for entry in &self.entries {
+            if !self.marked.remove(&entry.path) {
+                self.marked.insert(entry.path.clone());
+            }
+        }
+    }
+
+    /// How many entries of the current listing are marked
+    pub fn marked_in_listing(&self) -> usize {
+        self.entries.iter().filter(|e| self.marked.contains(&e.path)).count()
+    }
+
+    /// Marked paths in a stable (sorted) order
+    pub fn marked_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.marked.iter().cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    /// Select an entry by index, clamped to the listing
+    pub fn select(&mut self, index: usize) {
+        self.selected_index = index.min(self.entries.len().saturating_sub(1));
+        self.adjust_scroll();
+    }
+
     fn adjust_scroll(&mut self) {
         let viewport_height = 20; // Default, should be passed or stored
         if self.selected_index < self.scroll_offset {
@@ -90,6 +202,48 @@ impl Workspace {
     }
 }
 
+/// The entry last selected in each visited directory, so coming back selects it
+/// again; past `max` directories the one left longest ago is forgotten
+pub struct SelectionMemory {
+    names: HashMap<PathBuf, String>,
+    /// Directories, least recently remembered first
+    order: VecDeque<PathBuf>,
+    max: usize,
+}
+
+impl SelectionMemory {
+    pub fn new(max: usize) -> Self {
+        Self { names: HashMap::new(), order: VecDeque::new(), max }
+    }
+
+    pub fn remember(&mut self, dir: &Path, name: &str) {
+        let latest = self.order.back().is_some_and(|last| last == dir);
+        if latest && self.names.get(dir).is_some_and(|known| known == name) {
+            return;
+        }
+        if !latest {
+            self.order.retain(|known| known != dir);
+            self.order.push_back(dir.to_path_buf());
+        }
+        self.names.insert(dir.to_path_buf(), name.to_string());
+        while self.order.len() > self.max {
+            if let Some(oldest) = self.order.pop_front() {
+                self.names.remove(&oldest);
+            }
+        }
+    }
+
+    /// Name of the entry last selected in `dir`
+    pub fn get(&self, dir: &Path) -> Option<&str> {
+        self.names.get(dir).map(String::as_str)
+    }
+
+    /// Directories and their selected names, most recent first
+    pub fn recent(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.order.iter().rev().filter_map(|dir| Some((dir.as_path(), self.get(dir)?)))
+    }
+}
+
 /// Manages multiple workspaces/tabs
 pub struct WorkspaceManager {
     workspaces: Vec<Workspace>,
@@ -171,6 +325,10 @@ impl WorkspaceManager {
         &self.workspaces
     }
 
+    pub fn workspaces_mut(&mut self) -> &mut [Workspace] {
+        &mut self.workspaces
+    }
+
     /// Get the ID of the active workspace
     pub fn active_id(&self) -> usize {
         self.active_workspace_id
@@ -217,6 +375,19 @@ impl WorkspaceManager {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_selection_memory_forgets_the_oldest() {
+        let mut memory = SelectionMemory::new(2);
+        memory.remember(Path::new("/a"), "one");
+        memory.remember(Path::new("/b"), "two");
+        memory.remember(Path::new("/a"), "three");
+        memory.remember(Path::new("/c"), "four");
+        assert_eq!(memory.get(Path::new("/a")), Some("three"));
+        assert_eq!(memory.get(Path::new("/b")), None, "least recently remembered");
+        let recent: Vec<(&Path, &str)> = memory.recent().collect();
+        assert_eq!(recent, [(Path::new("/c"), "four"), (Path::new("/a"), "three")]);
+    }
+
     #[test]
     fn test_workspace_manager() {
         let mut manager = WorkspaceManager::new(PathBuf::from("/tmp"));
@@ -232,4 +403,36 @@ mod tests {
         manager.next_workspace();
         assert_eq!(manager.active_id(), id);
     }
+
+    #[test]
+    fn test_marks_follow_paths() {
+        let entry = |name: &str| FileEntry {
+            path: PathBuf::from("/tmp").join(name),
+            name: name.to_string(),
+            is_dir: false,
+            size: 0,
+            is_hidden: false,
+            modified: None,
+            item_count: None,
+            mime: None,
+        };
+        let mut workspace = Workspace::new(0, PathBuf::from("/tmp"));
+        workspace.entries = vec![entry("a"), entry("b"), entry("c")];
+        workspace.marked.insert(PathBuf::from("/elsewhere/d"));
+
+        workspace.select(1);
+        workspace.toggle_mark();
+        workspace.invert_marks();
+        assert_eq!(workspace.marked_in_listing(), 2);
+        assert!(!workspace.is_marked(&PathBuf::from("/tmp/b")));
+
+        // Reordering the listing keeps the same entries marked
+        workspace.entries.reverse();
+        assert!(workspace.is_marked(&workspace.entries[0].path));
+        assert!(!workspace.is_marked(&workspace.entries[1].path));
+
+        workspace.mark_all();
+        assert_eq!(workspace.marked_in_listing(), 3);
+        assert_eq!(workspace.marked.len(), 4);
+    }
 }